@@ -9,6 +9,7 @@
 //! - Circuit breaking
 
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
     http::HeaderMap,
     middleware,
@@ -25,6 +26,7 @@ use shared::{
     error::AppError,
     service_client::*,
     types::*,
+    webhook_signature::{SignatureError, SignatureVerifier, SIGNATURE_HEADER},
     Result,
 };
 use std::{net::SocketAddr, sync::Arc, time::Duration};
@@ -34,7 +36,7 @@ use tower_http::{
     trace::TraceLayer,
     timeout::TimeoutLayer,
 };
-use tracing::info;
+use tracing::{error, info, warn};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -47,6 +49,7 @@ pub struct AppState {
     pub rewards_engine_client: Arc<RewardsEngineClient>,
     pub user_management_client: Arc<UserManagementClient>,
     pub notification_client: Arc<NotificationClient>,
+    pub webhook_signature: Arc<SignatureVerifier>,
 }
 
 impl AppState {
@@ -65,6 +68,12 @@ impl AppState {
         let user_management_client = Arc::new(UserManagementClient::new(config.services.user_management_url.clone())?);
         let notification_client = Arc::new(NotificationClient::new(config.services.notification_url.clone())?);
 
+        // WhatsApp app secrets (current + previous during rotation)
+        let webhook_signature = Arc::new(SignatureVerifier::from_env());
+        if !webhook_signature.is_configured() {
+            error!("WHATSAPP_APP_SECRET not set: WhatsApp webhooks will be rejected");
+        }
+
         Ok(Self {
             config,
             auth_service,
@@ -75,6 +84,7 @@ impl AppState {
             rewards_engine_client,
             user_management_client,
             notification_client,
+            webhook_signature,
         })
     }
 }
@@ -221,10 +231,24 @@ async fn webhook_verify(Query(params): Query<std::collections::HashMap<String, S
 
 // WhatsApp webhook handler
 async fn webhook_handler(
-    State(_state): State<AppState>,
-    _headers: HeaderMap,
-    _body: String,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> Result<Json<serde_json::Value>> {
+    // Verify Meta's signature over the raw body before touching the payload
+    let signature = headers.get(SIGNATURE_HEADER).and_then(|v| v.to_str().ok());
+    match state.webhook_signature.verify(signature, &body) {
+        Ok(_) => {}
+        Err(SignatureError::NotConfigured) => {
+            error!("Rejected WhatsApp webhook: app secret not configured");
+            return Err(AppError::internal("Webhook signature verification unavailable"));
+        }
+        Err(e) => {
+            warn!("Rejected WhatsApp webhook: {}", e);
+            return Err(AppError::authentication("Invalid webhook signature"));
+        }
+    }
+
     info!("Received WhatsApp webhook");
     
     // TODO: Process WhatsApp webhook
@@ -356,6 +380,11 @@ async fn services_health_handler(
     // Check other services similarly...
     // (OCR, Rewards, User Management, Notification)
 
+    services.insert(
+        "whatsapp-webhook-signatures".to_string(),
+        serde_json::to_value(state.webhook_signature.metrics.snapshot())?,
+    );
+
     Ok(Json(serde_json::Value::Object(services)))
 }
 
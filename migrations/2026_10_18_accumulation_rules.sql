@@ -0,0 +1,43 @@
+-- ============================================================================
+-- MIGRATION: Declarative accumulation rules
+-- Date: 2026-10-18
+-- Descripción: Convierte rewards.dim_accumulations en el catálogo de reglas por
+--              factura: condiciones JSON, prioridad, stacking y exclusividad.
+--              Reemplaza la regla única `id = 0` de credit_lumis_for_invoice.
+-- ============================================================================
+
+BEGIN;
+
+ALTER TABLE rewards.dim_accumulations
+ADD COLUMN IF NOT EXISTS rule_scope VARCHAR(20),            -- 'invoice' = evaluada en cada factura
+ADD COLUMN IF NOT EXISTS conditions JSONB,                   -- NULL = siempre aplica
+ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 100,  -- Mayor se evalúa primero
+ADD COLUMN IF NOT EXISTS stacking VARCHAR(20) NOT NULL DEFAULT 'stack',  -- stack | exclusive
+ADD COLUMN IF NOT EXISTS exclusive_group VARCHAR(50),        -- Máximo una regla aplicada por grupo
+ADD COLUMN IF NOT EXISTS points_per_dollar NUMERIC(10, 4),   -- Lümis adicionales por dólar
+ADD COLUMN IF NOT EXISTS max_points INTEGER,                 -- Tope por factura
+ADD COLUMN IF NOT EXISTS is_active BOOLEAN NOT NULL DEFAULT TRUE;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'valid_accumulation_stacking') THEN
+        ALTER TABLE rewards.dim_accumulations
+        ADD CONSTRAINT valid_accumulation_stacking CHECK (stacking IN ('stack', 'exclusive'));
+    END IF;
+END $$;
+
+-- La regla base existente pasa a ser una regla de factura más (prioridad baja)
+UPDATE rewards.dim_accumulations
+SET rule_scope = 'invoice', priority = 0
+WHERE id = 0 AND rule_scope IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_dim_accumulations_invoice_rules
+ON rewards.dim_accumulations(priority DESC)
+WHERE rule_scope = 'invoice' AND is_active;
+
+COMMENT ON COLUMN rewards.dim_accumulations.conditions IS
+'Condiciones JSON: all/any/not, amount {gt,gte,lt,lte}, source [...], issuer_category [...], weekday [...], user_level {...}';
+COMMENT ON COLUMN rewards.dim_accumulations.stacking IS
+'stack: suma con otras reglas; exclusive: aplica sola si ninguna regla de mayor prioridad aplicó';
+
+COMMIT;
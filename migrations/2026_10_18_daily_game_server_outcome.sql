@@ -0,0 +1,52 @@
+-- ============================================================================
+-- MIGRATION: Server-authoritative daily game outcome
+-- Date: 2026-10-18
+-- Descripción: Tabla de premios configurable por fecha y registro de la tabla
+--              y del valor esperado usados en cada jugada. El premio ya no
+--              viene del cliente.
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Tablas de premios versionadas por fecha de vigencia (hora de Panamá)
+CREATE TABLE IF NOT EXISTS rewards.daily_game_prize_config (
+    config_id BIGSERIAL PRIMARY KEY,
+    effective_from DATE NOT NULL UNIQUE,
+    tiers JSONB NOT NULL,                 -- [{"lumis": 0, "weight": 40}, {"lumis": 1, "weight": 50}, ...]
+    notes TEXT,
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT tiers_is_array CHECK (jsonb_typeof(tiers) = 'array' AND jsonb_array_length(tiers) > 0)
+);
+
+-- Probabilidades del MVP (antes decididas en la app)
+INSERT INTO rewards.daily_game_prize_config (effective_from, tiers, notes)
+VALUES (
+    '2025-01-01',
+    '[{"lumis": 0, "weight": 40}, {"lumis": 1, "weight": 50}, {"lumis": 5, "weight": 10}]'::JSONB,
+    'Tabla inicial (40% vacía, 50% normal, 10% dorada)'
+)
+ON CONFLICT (effective_from) DO NOTHING;
+
+-- 2. Cada jugada guarda la tabla usada y su valor esperado
+ALTER TABLE rewards.fact_daily_game_plays
+ADD COLUMN IF NOT EXISTS config_id BIGINT REFERENCES rewards.daily_game_prize_config(config_id),
+ADD COLUMN IF NOT EXISTS expected_lumis NUMERIC(8, 4);
+
+-- Los premios ahora los define la tabla (0..100)
+ALTER TABLE rewards.fact_daily_game_plays
+DROP CONSTRAINT IF EXISTS fact_daily_game_plays_lumis_won_check;
+
+ALTER TABLE rewards.fact_daily_game_plays
+DROP CONSTRAINT IF EXISTS valid_lumis_won;
+
+ALTER TABLE rewards.fact_daily_game_plays
+ADD CONSTRAINT valid_lumis_won CHECK (lumis_won BETWEEN 0 AND 100);
+
+COMMENT ON TABLE rewards.daily_game_prize_config IS
+'Probabilidades y premios del juego diario; aplica la fila con mayor effective_from <= fecha de la jugada';
+COMMENT ON COLUMN rewards.fact_daily_game_plays.expected_lumis IS
+'Valor esperado de la tabla de premios al momento de la jugada (para comparar pago real vs esperado)';
+
+COMMIT;
@@ -0,0 +1,81 @@
+-- ============================================================================
+-- MIGRATION: Misiones declarativas evaluadas en Rust
+-- Date: 2026-10-18
+-- Descripción: Las misiones nuevas se definen como datos (meta sobre eventos,
+--              ventana de tiempo, recurrencia, elegibilidad y premio) y se
+--              evalúan en Rust (domains::gamification::mission_service) a
+--              partir de eventos de dominio: factura guardada, encuesta
+--              completada y acción registrada. Las misiones existentes en
+--              gamification.user_mechanics siguen funcionando igual.
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Definiciones
+CREATE TABLE IF NOT EXISTS gamification.mission_definitions (
+    mission_id BIGSERIAL PRIMARY KEY,
+    mission_code VARCHAR(60) NOT NULL UNIQUE,
+    name VARCHAR(120) NOT NULL,
+    description TEXT,
+    goal JSONB NOT NULL,                  -- {"type": "invoice_count", "target": 3, "filter": {...}}
+    recurrence VARCHAR(10) NOT NULL DEFAULT 'once'
+        CHECK (recurrence IN ('once', 'daily', 'weekly', 'monthly')),
+    eligibility JSONB NOT NULL DEFAULT '{}'::JSONB,
+    reward_lumis INTEGER NOT NULL DEFAULT 0 CHECK (reward_lumis >= 0),
+    reward_streak_freezes INTEGER NOT NULL DEFAULT 0 CHECK (reward_streak_freezes >= 0),
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT mission_window CHECK (ends_at IS NULL OR ends_at > starts_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_mission_definitions_active
+ON gamification.mission_definitions(starts_at, ends_at)
+WHERE is_active;
+
+-- 2. Progreso por usuario y periodo ('once' | 2026-10-18 | 2026-W42 | 2026-10)
+CREATE TABLE IF NOT EXISTS gamification.user_mission_progress (
+    mission_id BIGINT NOT NULL REFERENCES gamification.mission_definitions(mission_id),
+    user_id INTEGER NOT NULL REFERENCES public.dim_users(id),
+    period_key VARCHAR(20) NOT NULL,
+    progress NUMERIC(12, 2) NOT NULL DEFAULT 0,
+    target NUMERIC(12, 2) NOT NULL,
+    status VARCHAR(10) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed')),
+    lumis_awarded INTEGER NOT NULL DEFAULT 0,
+    freezes_awarded INTEGER NOT NULL DEFAULT 0,
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (mission_id, user_id, period_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_mission_progress_user
+ON gamification.user_mission_progress(user_id, updated_at DESC);
+
+-- 3. Eventos ya aplicados (idempotencia: una factura cuenta una sola vez por misión)
+CREATE TABLE IF NOT EXISTS gamification.mission_progress_events (
+    mission_id BIGINT NOT NULL REFERENCES gamification.mission_definitions(mission_id),
+    user_id INTEGER NOT NULL,
+    event_key VARCHAR(150) NOT NULL,      -- invoice:<cufe> | survey:<id> | action:<uuid>
+    contribution NUMERIC(12, 2) NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (mission_id, user_id, event_key)
+);
+
+-- 4. Regla genérica para las acumulaciones de misiones (quantity lleva el valor real)
+INSERT INTO rewards.dim_accumulations
+(id, name, points, valid_from, valid_to)
+VALUES
+(32, 'mission', 0, '2026-01-01'::DATE, '2099-12-31'::DATE)
+ON CONFLICT (id) DO NOTHING;
+
+COMMENT ON TABLE gamification.mission_definitions IS
+'Misiones declarativas: meta (goal JSONB) sobre eventos de dominio, recurrencia, elegibilidad y premio';
+COMMENT ON TABLE gamification.user_mission_progress IS
+'Progreso de cada usuario por misión y periodo; al completarse se acreditan Lümis y congeladores';
+COMMENT ON TABLE gamification.mission_progress_events IS
+'Eventos aplicados a cada misión; evita contar dos veces la misma factura, encuesta o acción';
+
+COMMIT;
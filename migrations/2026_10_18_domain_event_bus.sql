@@ -0,0 +1,53 @@
+-- ============================================================================
+-- MIGRATION: Bus de eventos de dominio con outbox transaccional
+-- Date: 2026-10-18
+-- Descripción: Los eventos de negocio (factura guardada, Lümis acreditados,
+--              redención creada/confirmada/cancelada/expirada, encuesta
+--              completada, usuario registrado, acción registrada) se escriben
+--              en la misma transacción que el cambio. Un dispatcher en
+--              background (services::event_bus_service) los entrega a los
+--              suscriptores registrados: notificaciones, webhooks, misiones,
+--              leaderboards, referidos, logros y analítica.
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Outbox: una fila por evento
+CREATE TABLE IF NOT EXISTS public.domain_event_outbox (
+    event_id UUID PRIMARY KEY,
+    event_type VARCHAR(60) NOT NULL,      -- invoice.saved, redemption.created...
+    payload JSONB NOT NULL,               -- DomainEvent serializado (incluye "type")
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 8,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMPTZ,             -- Lease del dispatcher; si el proceso muere se reintenta al vencer
+    processed_at TIMESTAMPTZ,
+    last_error TEXT,
+    CONSTRAINT valid_domain_event_status CHECK (
+        status IN ('pending', 'dispatching', 'processed', 'dead')
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_domain_event_outbox_due
+ON public.domain_event_outbox(next_attempt_at)
+WHERE status IN ('pending', 'dispatching');
+
+CREATE INDEX IF NOT EXISTS idx_domain_event_outbox_type
+ON public.domain_event_outbox(event_type, occurred_at DESC);
+
+-- 2. Suscriptores que ya procesaron cada evento: al reintentar solo corren los que fallaron
+CREATE TABLE IF NOT EXISTS public.domain_event_subscriber_runs (
+    event_id UUID NOT NULL REFERENCES public.domain_event_outbox(event_id) ON DELETE CASCADE,
+    subscriber VARCHAR(60) NOT NULL,
+    handled_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (event_id, subscriber)
+);
+
+COMMENT ON TABLE public.domain_event_outbox IS
+'Outbox transaccional de eventos de dominio (pending → dispatching → processed | dead)';
+COMMENT ON TABLE public.domain_event_subscriber_runs IS
+'Suscriptores que procesaron cada evento con éxito; entrega al menos una vez por suscriptor';
+
+COMMIT;
@@ -0,0 +1,77 @@
+-- ============================================================================
+-- MIGRATION: Merchant-funded earn campaigns
+-- Date: 2026-10-18
+-- Descripción: Campañas de acumulación pagadas por el comercio (multiplicador o
+--              bono fijo) por RUC emisor, producto, monto de canasta y ventana
+--              de fechas, con presupuesto en Lümis y reporte de gasto/ROI.
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Definición de campañas
+CREATE TABLE IF NOT EXISTS rewards.earn_campaigns (
+    campaign_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    merchant_id UUID NOT NULL REFERENCES rewards.merchants(merchant_id),
+    name VARCHAR(150) NOT NULL,
+    description TEXT,
+    issuer_rucs TEXT[] NOT NULL,          -- RUCs emisores del comercio (facturas que aplican)
+    product_codes TEXT[],                 -- NULL = cualquier producto
+    min_basket_amount NUMERIC(12, 2),     -- Monto mínimo de la factura (o de los productos si hay product_codes)
+    reward_type VARCHAR(20) NOT NULL,     -- multiplier | fixed_bonus
+    multiplier NUMERIC(6, 2),             -- 3.00 = 3x la regla base (bono = base × (m − 1))
+    bonus_lumis INTEGER,                  -- Lümis fijos por factura
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    budget_lumis BIGINT NOT NULL,         -- Tope financiado por el comercio
+    spent_lumis BIGINT NOT NULL DEFAULT 0,
+    lumi_unit_cost NUMERIC(10, 4) NOT NULL DEFAULT 0.01,  -- USD que paga el comercio por Lümi otorgado
+    max_awards_per_user INTEGER,          -- NULL = sin límite
+    status VARCHAR(20) NOT NULL DEFAULT 'active',
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT valid_campaign_status CHECK (status IN ('active', 'paused', 'exhausted', 'cancelled')),
+    CONSTRAINT valid_campaign_reward CHECK (
+        (reward_type = 'multiplier' AND multiplier > 1)
+        OR (reward_type = 'fixed_bonus' AND bonus_lumis > 0)
+    ),
+    CONSTRAINT valid_campaign_window CHECK (ends_at > starts_at),
+    CONSTRAINT valid_campaign_budget CHECK (budget_lumis > 0 AND spent_lumis >= 0 AND spent_lumis <= budget_lumis),
+    CONSTRAINT non_empty_issuer_rucs CHECK (cardinality(issuer_rucs) > 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_earn_campaigns_merchant
+ON rewards.earn_campaigns(merchant_id, starts_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_earn_campaigns_rucs
+ON rewards.earn_campaigns USING GIN (issuer_rucs)
+WHERE status = 'active';
+
+-- 2. Premios otorgados (una fila por campaña y factura)
+CREATE TABLE IF NOT EXISTS rewards.earn_campaign_awards (
+    award_id BIGSERIAL PRIMARY KEY,
+    campaign_id UUID NOT NULL REFERENCES rewards.earn_campaigns(campaign_id),
+    user_id BIGINT NOT NULL,
+    cufe VARCHAR(255) NOT NULL,
+    lumis INTEGER NOT NULL,
+    basket_amount NUMERIC(12, 2) NOT NULL,    -- Total de la factura
+    matched_amount NUMERIC(12, 2) NOT NULL,   -- Monto de los productos que calificaron
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT unique_campaign_invoice UNIQUE (campaign_id, cufe)
+);
+
+CREATE INDEX IF NOT EXISTS idx_campaign_awards_user
+ON rewards.earn_campaign_awards(campaign_id, user_id);
+
+-- 3. Regla genérica para las acumulaciones de campañas (quantity lleva el valor real)
+INSERT INTO rewards.dim_accumulations
+(id, name, points, valid_from, valid_to)
+VALUES
+(30, 'earn_campaign', 0, '2026-01-01'::DATE, '2099-12-31'::DATE)
+ON CONFLICT (id) DO NOTHING;
+
+COMMENT ON TABLE rewards.earn_campaigns IS 'Campañas de acumulación financiadas por comercios; se evalúan sobre cada factura guardada';
+COMMENT ON TABLE rewards.earn_campaign_awards IS 'Lümis otorgados por campaña y factura; UNIQUE evita premiar dos veces la misma factura';
+COMMENT ON COLUMN rewards.earn_campaigns.lumi_unit_cost IS 'Costo en USD por Lümi otorgado, usado para el gasto y ROI del comercio';
+
+COMMIT;
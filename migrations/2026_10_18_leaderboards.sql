@@ -0,0 +1,81 @@
+-- ============================================================================
+-- MIGRATION: Leaderboards por periodo y alcance (Redis sorted sets)
+-- Date: 2026-10-18
+-- Descripción: Los rankings en vivo viven en Redis (services::leaderboard_service).
+--              Postgres guarda los eventos de XP (fuente de verdad para
+--              reconstruir Redis), las amistades, la ciudad del usuario y las
+--              posiciones finales archivadas al cierre de cada periodo.
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Ciudad del usuario (alcance "city")
+ALTER TABLE public.dim_users
+ADD COLUMN IF NOT EXISTS city VARCHAR(100);
+
+-- 2. Amigos (unidireccional: el ranking de amigos muestra a quienes sigo + yo)
+CREATE TABLE IF NOT EXISTS gamification.user_friends (
+    user_id INTEGER NOT NULL REFERENCES public.dim_users(id),
+    friend_user_id INTEGER NOT NULL REFERENCES public.dim_users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, friend_user_id),
+    CONSTRAINT not_self_friend CHECK (user_id <> friend_user_id)
+);
+
+-- 3. Eventos de XP (1 por factura guardada, idempotente por event_key)
+CREATE TABLE IF NOT EXISTS gamification.leaderboard_xp_events (
+    event_key VARCHAR(150) PRIMARY KEY,   -- 'invoice:<cufe>'
+    user_id INTEGER NOT NULL REFERENCES public.dim_users(id),
+    xp INTEGER NOT NULL CHECK (xp > 0),
+    source VARCHAR(30) NOT NULL,
+    city_slug VARCHAR(100),               -- ciudad del usuario al momento del evento (normalizada)
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_leaderboard_xp_events_time
+ON gamification.leaderboard_xp_events(occurred_at);
+
+CREATE INDEX IF NOT EXISTS idx_leaderboard_xp_events_user
+ON gamification.leaderboard_xp_events(user_id, occurred_at DESC);
+
+-- Backfill: todas las facturas, porque el histórico se reconstruye solo desde
+-- esta tabla
+INSERT INTO gamification.leaderboard_xp_events (event_key, user_id, xp, source, city_slug, occurred_at)
+SELECT 'invoice:' || ih.cufe, ih.user_id, 1, 'invoice', NULL, ih.process_date
+FROM public.invoice_header ih
+JOIN public.dim_users u ON u.id = ih.user_id
+WHERE ih.user_id IS NOT NULL
+  AND ih.process_date IS NOT NULL
+ON CONFLICT (event_key) DO NOTHING;
+
+-- 4. Posiciones finales archivadas (para premiar)
+CREATE TABLE IF NOT EXISTS gamification.leaderboard_archives (
+    period VARCHAR(10) NOT NULL CHECK (period IN ('daily', 'weekly', 'monthly')),
+    period_key VARCHAR(20) NOT NULL,      -- 2026-10-18 | 2026-W42 | 2026-10
+    scope VARCHAR(120) NOT NULL,          -- global | city:<slug>
+    user_id INTEGER NOT NULL REFERENCES public.dim_users(id),
+    rank INTEGER NOT NULL,
+    score BIGINT NOT NULL,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (period, period_key, scope, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_leaderboard_archives_rank
+ON gamification.leaderboard_archives(period, period_key, scope, rank);
+
+CREATE TABLE IF NOT EXISTS gamification.leaderboard_closures (
+    period VARCHAR(10) NOT NULL,
+    period_key VARCHAR(20) NOT NULL,
+    participants INTEGER NOT NULL DEFAULT 0,
+    closed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (period, period_key)
+);
+
+COMMENT ON TABLE gamification.leaderboard_xp_events IS
+'Eventos de XP que alimentan los leaderboards en Redis; permite reconstruirlos y archivar cierres';
+COMMENT ON TABLE gamification.leaderboard_archives IS
+'Ranking final de cada periodo cerrado (hora de Panamá), por alcance global y ciudad';
+COMMENT ON TABLE gamification.user_friends IS
+'Usuarios que cada usuario sigue para el ranking de amigos';
+
+COMMIT;
@@ -0,0 +1,45 @@
+-- ============================================================================
+-- MIGRATION: Server-to-server merchant API keys
+-- Date: 2026-10-18
+-- Descripción: Credenciales de máquina para integraciones POS. El key completo
+--              nunca se guarda: solo el prefijo (lookup) y su SHA-256.
+-- ============================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS rewards.merchant_api_keys (
+    key_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    merchant_id UUID NOT NULL REFERENCES rewards.merchants(merchant_id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    key_prefix VARCHAR(16) NOT NULL,      -- Parte pública del key (lum_sk_<prefix>_...)
+    key_hash CHAR(64) NOT NULL,           -- SHA-256 hex del key completo
+    scopes TEXT[] NOT NULL,
+    rate_limit_per_minute INTEGER,        -- NULL = límite por defecto de validaciones
+    created_by_staff_id UUID,             -- FK en 2026_10_18_merchant_staff_roles_api_key_fk.sql
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    last_used_ip VARCHAR(64),
+    revoked_at TIMESTAMPTZ,
+    revoked_by_staff_id UUID,
+    CONSTRAINT valid_api_key_scopes CHECK (
+        scopes <@ ARRAY['validate', 'confirm', 'reports:read']::TEXT[]
+        AND cardinality(scopes) > 0
+    ),
+    CONSTRAINT positive_api_key_rate_limit CHECK (rate_limit_per_minute IS NULL OR rate_limit_per_minute > 0)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_merchant_api_keys_prefix
+ON rewards.merchant_api_keys(key_prefix);
+
+CREATE INDEX IF NOT EXISTS idx_merchant_api_keys_merchant
+ON rewards.merchant_api_keys(merchant_id, created_at DESC);
+
+-- Auditoría: qué key ejecutó cada acción
+ALTER TABLE rewards.redemption_audit_log
+ADD COLUMN IF NOT EXISTS api_key_id UUID;
+
+COMMENT ON TABLE rewards.merchant_api_keys IS 'API keys de comercios para integraciones servidor a servidor (POS)';
+COMMENT ON COLUMN rewards.merchant_api_keys.scopes IS 'validate, confirm, reports:read';
+
+COMMIT;
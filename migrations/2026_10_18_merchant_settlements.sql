@@ -0,0 +1,109 @@
+-- ============================================================================
+-- MIGRATION: Monthly merchant settlement statements
+-- Date: 2026-10-18
+-- Descripción: Términos comerciales por oferta, cierre mensual por comercio
+--              con líneas congeladas y estado de cuenta (CSV/JSON)
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Términos comerciales por oferta (versionados por fecha de vigencia)
+--    merchant_reimbursed: Lümis paga al comercio unit_amount por redención confirmada
+--    merchant_fee:        el comercio paga a Lümis unit_amount por redención confirmada
+CREATE TABLE IF NOT EXISTS rewards.offer_commercial_terms (
+    term_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    offer_id UUID NOT NULL REFERENCES rewards.redemption_offers(offer_id) ON DELETE CASCADE,
+    direction VARCHAR(30) NOT NULL,
+    unit_amount NUMERIC(12, 2) NOT NULL,
+    currency VARCHAR(3) NOT NULL DEFAULT 'USD',
+    effective_from DATE NOT NULL DEFAULT CURRENT_DATE,
+    notes TEXT,
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT valid_term_direction CHECK (direction IN ('merchant_reimbursed', 'merchant_fee')),
+    CONSTRAINT non_negative_unit_amount CHECK (unit_amount >= 0),
+    CONSTRAINT unique_offer_term_start UNIQUE (offer_id, effective_from)
+);
+
+CREATE INDEX IF NOT EXISTS idx_offer_terms_lookup
+ON rewards.offer_commercial_terms(offer_id, effective_from DESC);
+
+-- 2. Estados de cuenta: uno por comercio y mes (UNIQUE impide cerrar dos veces)
+CREATE TABLE IF NOT EXISTS rewards.merchant_settlements (
+    settlement_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    merchant_id UUID NOT NULL REFERENCES rewards.merchants(merchant_id),
+    period_start DATE NOT NULL,           -- Primer día del mes (hora de Panamá)
+    period_end DATE NOT NULL,             -- Primer día del mes siguiente (exclusivo)
+    status VARCHAR(20) NOT NULL DEFAULT 'closed',
+    redemption_count INTEGER NOT NULL DEFAULT 0,
+    total_lumis BIGINT NOT NULL DEFAULT 0,
+    amount_owed_to_merchant NUMERIC(14, 2) NOT NULL DEFAULT 0,
+    amount_owed_by_merchant NUMERIC(14, 2) NOT NULL DEFAULT 0,
+    net_amount NUMERIC(14, 2) NOT NULL DEFAULT 0,  -- > 0: Lümis paga al comercio
+    currency VARCHAR(3) NOT NULL DEFAULT 'USD',
+    closed_by BIGINT,
+    closed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT valid_settlement_status CHECK (status IN ('closed', 'paid')),
+    CONSTRAINT unique_merchant_period UNIQUE (merchant_id, period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_settlements_period
+ON rewards.merchant_settlements(period_start DESC);
+
+-- 3. Líneas del estado de cuenta (snapshot: no cambian si cambian ofertas o términos)
+CREATE TABLE IF NOT EXISTS rewards.merchant_settlement_lines (
+    line_id BIGSERIAL PRIMARY KEY,
+    settlement_id UUID NOT NULL REFERENCES rewards.merchant_settlements(settlement_id) ON DELETE CASCADE,
+    redemption_id UUID NOT NULL UNIQUE,   -- Una redención solo puede liquidarse una vez
+    redemption_code VARCHAR(100) NOT NULL,
+    offer_id UUID NOT NULL,
+    offer_name TEXT NOT NULL,
+    branch_id UUID,
+    confirmed_at TIMESTAMPTZ NOT NULL,
+    lumis_spent INTEGER NOT NULL,
+    term_id UUID,                         -- NULL si la oferta no tenía términos vigentes
+    direction VARCHAR(30),
+    unit_amount NUMERIC(12, 2) NOT NULL DEFAULT 0,
+    line_amount NUMERIC(12, 2) NOT NULL DEFAULT 0   -- Con signo: > 0 a favor del comercio
+);
+
+CREATE INDEX IF NOT EXISTS idx_settlement_lines_settlement
+ON rewards.merchant_settlement_lines(settlement_id, confirmed_at);
+
+-- 4. Congelar redenciones liquidadas
+ALTER TABLE rewards.user_redemptions
+ADD COLUMN IF NOT EXISTS settlement_id UUID REFERENCES rewards.merchant_settlements(settlement_id);
+
+CREATE INDEX IF NOT EXISTS idx_user_redemptions_unsettled
+ON rewards.user_redemptions(validated_at)
+WHERE redemption_status = 'confirmed' AND settlement_id IS NULL;
+
+CREATE OR REPLACE FUNCTION rewards.prevent_settled_redemption_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF OLD.settlement_id IS NOT NULL AND (
+        NEW.settlement_id IS DISTINCT FROM OLD.settlement_id
+        OR NEW.redemption_status IS DISTINCT FROM OLD.redemption_status
+        OR NEW.lumis_spent IS DISTINCT FROM OLD.lumis_spent
+        OR NEW.offer_id IS DISTINCT FROM OLD.offer_id
+        OR NEW.validated_at IS DISTINCT FROM OLD.validated_at
+    ) THEN
+        RAISE EXCEPTION 'La redención % pertenece a una liquidación cerrada (%)',
+            OLD.redemption_id, OLD.settlement_id;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_prevent_settled_redemption_changes ON rewards.user_redemptions;
+CREATE TRIGGER trg_prevent_settled_redemption_changes
+    BEFORE UPDATE ON rewards.user_redemptions
+    FOR EACH ROW
+    EXECUTE FUNCTION rewards.prevent_settled_redemption_changes();
+
+COMMENT ON TABLE rewards.offer_commercial_terms IS 'Términos comerciales por oferta usados para liquidar redenciones confirmadas';
+COMMENT ON TABLE rewards.merchant_settlements IS 'Estado de cuenta mensual por comercio; un período no puede cerrarse dos veces';
+COMMENT ON TABLE rewards.merchant_settlement_lines IS 'Redenciones incluidas en cada liquidación (snapshot)';
+COMMENT ON COLUMN rewards.user_redemptions.settlement_id IS 'Liquidación que incluyó esta redención; bloquea cambios posteriores';
+
+COMMIT;
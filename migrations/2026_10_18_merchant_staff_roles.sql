@@ -0,0 +1,132 @@
+-- ============================================================================
+-- MIGRATION: Merchant staff accounts, roles and branch scoping
+-- Date: 2026-10-18
+-- Descripción: Cuentas de personal por comercio (owner/manager/cashier),
+--              sucursales opcionales y trazabilidad por empleado en
+--              validaciones y confirmaciones de redenciones
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Sucursales de cada comercio
+CREATE TABLE IF NOT EXISTS rewards.merchant_branches (
+    branch_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    merchant_id UUID NOT NULL REFERENCES rewards.merchants(merchant_id),
+    branch_name VARCHAR(255) NOT NULL,
+    address TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT uq_merchant_branch_name UNIQUE (merchant_id, branch_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_merchant_branches_merchant
+ON rewards.merchant_branches(merchant_id)
+WHERE is_active = true;
+
+-- 2. Personal del comercio (login individual con rol)
+CREATE TABLE IF NOT EXISTS rewards.merchant_users (
+    staff_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    merchant_id UUID NOT NULL REFERENCES rewards.merchants(merchant_id),
+    branch_id UUID REFERENCES rewards.merchant_branches(branch_id),  -- NULL = todas las sucursales
+    email VARCHAR(255) NOT NULL,
+    display_name VARCHAR(255) NOT NULL,
+    password_hash VARCHAR(255) NOT NULL,  -- bcrypt
+    role VARCHAR(20) NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    last_login_at TIMESTAMPTZ,
+    created_by_staff_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT valid_staff_role CHECK (role IN ('owner', 'manager', 'cashier'))
+);
+
+-- Email único (case insensitive) para poder hacer login solo con email
+CREATE UNIQUE INDEX IF NOT EXISTS uq_merchant_users_email
+ON rewards.merchant_users(LOWER(email));
+
+CREATE INDEX IF NOT EXISTS idx_merchant_users_merchant
+ON rewards.merchant_users(merchant_id, is_active);
+
+-- 3. Restricción opcional de ofertas por sucursal
+-- Si una oferta no tiene filas aquí, es canjeable en todas las sucursales
+CREATE TABLE IF NOT EXISTS rewards.offer_branches (
+    offer_id UUID NOT NULL REFERENCES rewards.redemption_offers(offer_id),
+    branch_id UUID NOT NULL REFERENCES rewards.merchant_branches(branch_id),
+    PRIMARY KEY (offer_id, branch_id)
+);
+
+-- 4. Trazabilidad por empleado en redenciones
+ALTER TABLE rewards.user_redemptions
+ADD COLUMN IF NOT EXISTS validated_by_staff_id UUID REFERENCES rewards.merchant_users(staff_id),
+ADD COLUMN IF NOT EXISTS validated_branch_id UUID REFERENCES rewards.merchant_branches(branch_id);
+
+CREATE INDEX IF NOT EXISTS idx_redemptions_staff
+ON rewards.user_redemptions(validated_by_staff_id, validated_at)
+WHERE validated_by_staff_id IS NOT NULL;
+
+ALTER TABLE rewards.redemption_audit_log
+ADD COLUMN IF NOT EXISTS staff_id UUID,
+ADD COLUMN IF NOT EXISTS branch_id UUID,
+ADD COLUMN IF NOT EXISTS staff_role VARCHAR(20);
+
+CREATE INDEX IF NOT EXISTS idx_audit_staff
+ON rewards.redemption_audit_log(staff_id, created_at DESC)
+WHERE staff_id IS NOT NULL;
+
+-- 5. El trigger de auditoría ahora registra al empleado que confirmó
+CREATE OR REPLACE FUNCTION rewards.audit_redemption_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO rewards.redemption_audit (
+            redemption_id, action, actor_type, actor_id,
+            new_status, metadata
+        ) VALUES (
+            NEW.redemption_id, 'created', 'user', NEW.user_id::text,
+            NEW.redemption_status,
+            jsonb_build_object('offer_id', NEW.offer_id, 'lumis_spent', NEW.lumis_spent)
+        );
+    ELSIF TG_OP = 'UPDATE' AND OLD.redemption_status != NEW.redemption_status THEN
+        INSERT INTO rewards.redemption_audit (
+            redemption_id, action, actor_type, actor_id,
+            old_status, new_status, metadata
+        ) VALUES (
+            NEW.redemption_id,
+            CASE NEW.redemption_status
+                WHEN 'confirmed' THEN 'confirmed'
+                WHEN 'cancelled' THEN 'cancelled'
+                WHEN 'expired' THEN 'expired'
+                ELSE 'status_changed'
+            END,
+            CASE
+                WHEN NEW.redemption_status = 'confirmed' AND NEW.validated_by_staff_id IS NOT NULL THEN 'merchant_staff'
+                WHEN NEW.redemption_status = 'confirmed' THEN 'merchant'
+                WHEN NEW.redemption_status = 'cancelled' THEN 'user'
+                ELSE 'system'
+            END,
+            COALESCE(
+                NEW.validated_by_staff_id::text,
+                NEW.validated_by_merchant_id::text,
+                NEW.user_id::text
+            ),
+            OLD.redemption_status,
+            NEW.redemption_status,
+            jsonb_build_object(
+                'validated_at', NEW.validated_at,
+                'merchant_id', NEW.validated_by_merchant_id,
+                'staff_id', NEW.validated_by_staff_id,
+                'branch_id', NEW.validated_branch_id
+            )
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON TABLE rewards.merchant_users IS 'Cuentas de personal de comercios con rol (owner, manager, cashier)';
+COMMENT ON TABLE rewards.merchant_branches IS 'Sucursales de comercios para scoping de permisos';
+COMMENT ON TABLE rewards.offer_branches IS 'Sucursales donde una oferta es canjeable (vacío = todas)';
+COMMENT ON COLUMN rewards.user_redemptions.validated_by_staff_id IS 'Empleado que confirmó la redención';
+
+COMMIT;
@@ -0,0 +1,26 @@
+-- ============================================================================
+-- MIGRATION: FKs de API keys hacia el personal de comercios
+-- Date: 2026-10-18
+-- Descripción: merchant_api_keys se crea antes que rewards.merchant_users
+--              (orden alfabético), así que las referencias al empleado que
+--              creó o revocó el key se agregan aquí, después de ambas tablas.
+-- ============================================================================
+
+BEGIN;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'fk_merchant_api_keys_created_by') THEN
+        ALTER TABLE rewards.merchant_api_keys
+        ADD CONSTRAINT fk_merchant_api_keys_created_by
+        FOREIGN KEY (created_by_staff_id) REFERENCES rewards.merchant_users(staff_id) ON DELETE SET NULL;
+    END IF;
+
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'fk_merchant_api_keys_revoked_by') THEN
+        ALTER TABLE rewards.merchant_api_keys
+        ADD CONSTRAINT fk_merchant_api_keys_revoked_by
+        FOREIGN KEY (revoked_by_staff_id) REFERENCES rewards.merchant_users(staff_id) ON DELETE SET NULL;
+    END IF;
+END $$;
+
+COMMIT;
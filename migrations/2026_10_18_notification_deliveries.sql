@@ -0,0 +1,42 @@
+-- ============================================================================
+-- MIGRATION: Log de entregas del notificador multicanal
+-- Date: 2026-10-18
+-- Descripción: Una fila por intento de entrega y canal (in-app, push,
+--              WhatsApp, email). La dedupe_key agrupa los intentos de una
+--              misma notificación lógica: si ya hay un 'sent' externo no se
+--              vuelve a enviar por ningún otro canal.
+--              notification_delivery_claims reserva la clave (con lease)
+--              antes de tocar cualquier canal, para que dos entregas
+--              concurrentes no lean el log a la vez y envíen ambas.
+-- ============================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS public.notification_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    dedupe_key VARCHAR(255) NOT NULL,
+    user_id BIGINT NOT NULL,
+    channel VARCHAR(16) NOT NULL CHECK (channel IN ('in_app', 'push', 'whatsapp', 'email')),
+    status VARCHAR(10) NOT NULL CHECK (status IN ('sent', 'failed', 'skipped')),
+    detail TEXT,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_key
+    ON public.notification_deliveries (dedupe_key, status);
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_user
+    ON public.notification_deliveries (user_id, attempted_at DESC);
+
+CREATE TABLE IF NOT EXISTS public.notification_delivery_claims (
+    dedupe_key VARCHAR(255) PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    locked_until TIMESTAMPTZ NOT NULL,
+    claimed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE public.notification_deliveries IS 'Intentos de entrega por canal del notificador multicanal';
+COMMENT ON COLUMN public.notification_deliveries.detail IS 'Motivo del fallo u omisión (sin token, horas de silencio, error del proveedor)';
+COMMENT ON TABLE public.notification_delivery_claims IS 'Reserva por dedupe_key mientras una entrega está en curso';
+COMMENT ON COLUMN public.notification_delivery_claims.locked_until IS 'Fin del lease; al terminar la entrega se libera con NOW()';
+
+COMMIT;
@@ -0,0 +1,49 @@
+-- ============================================================================
+-- MIGRATION: Preferencias de notificación y horas de silencio
+-- Date: 2026-10-18
+-- Descripción: Push por categoría (transaccionales, promociones, gamificación,
+--              encuestas) y horas de silencio en la zona horaria del usuario.
+--              La política vive en Rust (domains::notifications::preferences)
+--              y la aplican el creador de notificaciones, el envío directo de
+--              push y el worker de la cola. Los push directos que caen en horas
+--              de silencio esperan en deferred_push_notifications; los de la
+--              cola solo mueven su next_attempt_at.
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Preferencias (sin fila = todo activo y sin horas de silencio)
+CREATE TABLE IF NOT EXISTS public.notification_preferences (
+    user_id BIGINT PRIMARY KEY,
+    transactional BOOLEAN NOT NULL DEFAULT TRUE,
+    promotions BOOLEAN NOT NULL DEFAULT TRUE,
+    gamification BOOLEAN NOT NULL DEFAULT TRUE,
+    surveys BOOLEAN NOT NULL DEFAULT TRUE,
+    quiet_hours_start TIME,
+    quiet_hours_end TIME,
+    timezone VARCHAR(64) NOT NULL DEFAULT 'America/Panama',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((quiet_hours_start IS NULL) = (quiet_hours_end IS NULL))
+);
+
+-- 2. Push directos (sin notificación in-app) diferidos por horas de silencio
+CREATE TABLE IF NOT EXISTS public.deferred_push_notifications (
+    id BIGSERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    notification JSONB NOT NULL,
+    deliver_after TIMESTAMPTZ NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'processing', 'sent', 'skipped', 'failed')),
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_deferred_push_due
+ON public.deferred_push_notifications(deliver_after)
+WHERE status = 'pending';
+
+COMMENT ON TABLE public.notification_preferences IS
+'Push por categoría y horas de silencio (hora local en timezone); la bandeja in-app no se filtra';
+
+COMMIT;
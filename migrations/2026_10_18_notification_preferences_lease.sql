@@ -0,0 +1,23 @@
+-- ============================================================================
+-- MIGRATION: Lease para push diferidos
+-- Date: 2026-10-18
+-- Descripción: el worker marca los push diferidos como 'processing' al
+--              reclamarlos. Con locked_until, los que quedan así porque el
+--              proceso cayó a mitad del lote se vuelven a reclamar al vencer.
+-- ============================================================================
+
+BEGIN;
+
+ALTER TABLE public.deferred_push_notifications
+ADD COLUMN IF NOT EXISTS locked_until TIMESTAMPTZ;
+
+-- Filas que ya estaban en 'processing' sin lease: reclamables de inmediato
+UPDATE public.deferred_push_notifications
+SET locked_until = NOW()
+WHERE status = 'processing' AND locked_until IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_deferred_push_processing
+ON public.deferred_push_notifications(locked_until)
+WHERE status = 'processing';
+
+COMMIT;
@@ -0,0 +1,98 @@
+-- ============================================================================
+-- MIGRATION: Campañas de push segmentadas
+-- Date: 2026-10-18
+-- Descripción: Campañas con segmento (tags, nivel, última actividad, provincia),
+--              mensaje con deep link y hora de envío, y una fila por
+--              destinatario con su estado. El envío (ritmo por segundo, tope
+--              por usuario, pausa y cancelación) vive en Rust
+--              (domains::notifications::campaigns); cada destinatario recibe
+--              una notificación 'promo' vía public.create_notification.
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Campañas
+CREATE TABLE IF NOT EXISTS public.push_campaigns (
+    campaign_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(120) NOT NULL,
+    title VARCHAR(200) NOT NULL,
+    body TEXT NOT NULL,
+    deep_link VARCHAR(255),
+    image_url TEXT,
+    segment JSONB NOT NULL DEFAULT '{}'::jsonb,
+    scheduled_at TIMESTAMPTZ NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'scheduled'
+        CHECK (status IN ('scheduled', 'sending', 'paused', 'completed', 'cancelled')),
+    rate_per_second INTEGER NOT NULL DEFAULT 20 CHECK (rate_per_second > 0),
+    conversion_goal VARCHAR(30)
+        CHECK (conversion_goal IN ('invoice_saved', 'survey_completed', 'lumimatch_answered')),
+    conversion_window_hours INTEGER NOT NULL DEFAULT 72,
+    audience_size INTEGER,
+    created_by BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_push_campaigns_due
+ON public.push_campaigns(scheduled_at)
+WHERE status IN ('scheduled', 'sending');
+
+-- 2. Destinatarios: audiencia congelada al empezar el envío
+CREATE TABLE IF NOT EXISTS public.push_campaign_recipients (
+    campaign_id UUID NOT NULL REFERENCES public.push_campaigns(campaign_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sent', 'throttled', 'failed', 'cancelled')),
+    notification_id BIGINT,
+    sent_at TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ,
+    opened_at TIMESTAMPTZ,
+    converted_at TIMESTAMPTZ,
+    error_message TEXT,
+    PRIMARY KEY (campaign_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_push_campaign_recipients_pending
+ON public.push_campaign_recipients(campaign_id, user_id)
+WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_push_campaign_recipients_conversion
+ON public.push_campaign_recipients(user_id, sent_at)
+WHERE status = 'sent' AND converted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_push_campaign_recipients_notification
+ON public.push_campaign_recipients(notification_id)
+WHERE notification_id IS NOT NULL;
+
+-- 3. Aperturas: se copian al destinatario al marcar la notificación como
+--    leída, porque las notificaciones se borran a los 90 días y la cola de
+--    push a los 7 (delivered_at lo escribe el worker de la cola)
+CREATE OR REPLACE FUNCTION public.record_push_campaign_open()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE public.push_campaign_recipients
+    SET opened_at = COALESCE(NEW.read_at, NOW())
+    WHERE notification_id = NEW.id AND opened_at IS NULL;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_record_push_campaign_open ON public.notifications;
+CREATE TRIGGER trg_record_push_campaign_open
+    AFTER UPDATE OF is_read ON public.notifications
+    FOR EACH ROW
+    WHEN (NEW.is_read AND NOT OLD.is_read AND NEW.type = 'promo')
+    EXECUTE FUNCTION public.record_push_campaign_open();
+
+COMMENT ON TABLE public.push_campaigns IS
+'Campañas de push; scheduled_at se carga en hora de Panamá y se guarda en UTC';
+
+COMMENT ON COLUMN public.push_campaign_recipients.status IS
+'pending, sent (notificación creada), throttled (tope por usuario), failed, cancelled';
+
+COMMENT ON COLUMN public.push_campaign_recipients.delivered_at IS
+'Push entregado a FCM por el worker de la cola; sobrevive a la limpieza de notification_push_queue';
+
+COMMIT;
@@ -0,0 +1,88 @@
+-- ============================================================================
+-- MIGRATION: Tómbolas verificables (commit-reveal)
+-- Date: 2026-10-18
+-- Descripción: Tómbolas con boletos pagados en Lümis, tope por usuario y
+--              sorteo demostrablemente justo. Al crear la tómbola se publica
+--              seed_commitment = sha256(seed) y la ronda de drand posterior al
+--              fin de la venta (beacon_round); al cerrar se congela la lista de
+--              boletos (entries_hash); al sortear se revela el seed, se mezcla
+--              con la ronda del beacon y cualquiera puede recalcular los
+--              ganadores (domains::rewards::raffle_service).
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Tómbolas
+CREATE TABLE IF NOT EXISTS rewards.raffles (
+    raffle_id SERIAL PRIMARY KEY,
+    title VARCHAR(150) NOT NULL,
+    description TEXT,
+    prize_type VARCHAR(20) NOT NULL,          -- cash, merch, other
+    prize_description TEXT NOT NULL,
+    ticket_price_lumis INTEGER NOT NULL,
+    max_tickets_per_user INTEGER NOT NULL,
+    max_tickets_total INTEGER,                -- NULL = sin límite
+    winners_count INTEGER NOT NULL DEFAULT 1,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'open',
+    tickets_sold INTEGER NOT NULL DEFAULT 0,
+    seed_commitment CHAR(64) NOT NULL,        -- sha256(seed), público desde la creación
+    seed CHAR(64) NOT NULL,                   -- Secreto hasta el sorteo
+    beacon_round BIGINT NOT NULL,             -- Ronda de drand posterior a ends_at, pública desde la creación
+    beacon_randomness CHAR(64),               -- Randomness de esa ronda, guardada al sortear
+    holder_salt CHAR(64) NOT NULL,            -- Salt secreto de los pseudónimos de la lista pública
+    entries_hash CHAR(64),                    -- sha256 de la lista de boletos al cerrar
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    closed_at TIMESTAMPTZ,
+    drawn_at TIMESTAMPTZ,
+    CONSTRAINT valid_raffle_status CHECK (status IN ('open', 'closed', 'drawn')),
+    CONSTRAINT valid_raffle_prize_type CHECK (prize_type IN ('cash', 'merch', 'other')),
+    CONSTRAINT valid_raffle_window CHECK (ends_at > starts_at),
+    CONSTRAINT valid_raffle_limits CHECK (
+        ticket_price_lumis > 0
+        AND max_tickets_per_user > 0
+        AND winners_count > 0
+        AND (max_tickets_total IS NULL OR max_tickets_total > 0)
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_raffles_open
+ON rewards.raffles(prize_type, ends_at)
+WHERE status = 'open';
+
+-- 2. Boletos: numeración consecutiva por tómbola
+CREATE TABLE IF NOT EXISTS rewards.raffle_tickets (
+    raffle_id INTEGER NOT NULL REFERENCES rewards.raffles(raffle_id),
+    ticket_number INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    purchase_id UUID NOT NULL,                -- Boletos comprados juntos
+    channel VARCHAR(20) NOT NULL,             -- api, whatsapp
+    lumis_spent INTEGER NOT NULL,
+    purchased_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (raffle_id, ticket_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_raffle_tickets_user
+ON rewards.raffle_tickets(raffle_id, user_id);
+
+-- 3. Ganadores (un premio por usuario)
+CREATE TABLE IF NOT EXISTS rewards.raffle_winners (
+    raffle_id INTEGER NOT NULL REFERENCES rewards.raffles(raffle_id),
+    position INTEGER NOT NULL,
+    ticket_number INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    notified_at TIMESTAMPTZ,
+    PRIMARY KEY (raffle_id, position),
+    UNIQUE (raffle_id, user_id)
+);
+
+COMMENT ON TABLE rewards.raffles IS
+'Tómbolas con sorteo commit-reveal: seed_commitment y beacon_round se publican al crear, seed y beacon_randomness al sortear';
+COMMENT ON TABLE rewards.raffle_tickets IS
+'Boletos comprados con Lümis (spend en fact_accumulations con dtype raffle_ticket)';
+COMMENT ON TABLE rewards.raffle_winners IS
+'Ganadores calculados con raffle_service::draw_winners a partir del seed revelado';
+
+COMMIT;
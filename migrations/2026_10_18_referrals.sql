@@ -0,0 +1,82 @@
+-- ============================================================================
+-- MIGRATION: Programa de referidos
+-- Date: 2026-10-18
+-- Descripción: Códigos de referido en dim_users, atribución del registro
+--              (registro por email y auth unificado), premio al referidor solo
+--              cuando el referido completa acciones calificantes (facturas
+--              válidas) y señales antifraude (dispositivo, IP, email
+--              desechable). La lógica vive en Rust
+--              (domains::gamification::referral_service).
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Código propio y referidor en dim_users (pueden existir ya)
+ALTER TABLE public.dim_users
+ADD COLUMN IF NOT EXISTS referral_code VARCHAR(20),
+ADD COLUMN IF NOT EXISTS referred_by INTEGER;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_dim_users_referral_code
+ON public.dim_users (UPPER(referral_code))
+WHERE referral_code IS NOT NULL;
+
+-- 2. Atribuciones (una por referido)
+CREATE TABLE IF NOT EXISTS gamification.referrals (
+    referral_id BIGSERIAL PRIMARY KEY,
+    referrer_user_id INTEGER NOT NULL REFERENCES public.dim_users(id),
+    referee_user_id INTEGER NOT NULL UNIQUE REFERENCES public.dim_users(id),
+    referral_code VARCHAR(20) NOT NULL,
+    source VARCHAR(20) NOT NULL,              -- register | unified_auth
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN (
+        'pending',    -- esperando acciones calificantes
+        'review',     -- calificó pero tiene señales de fraude: requiere revisión
+        'rewarded',   -- premios acreditados
+        'blocked',    -- fraude evidente al atribuir (no se premia)
+        'rejected'    -- descartado en revisión
+    )),
+    fraud_flags TEXT[] NOT NULL DEFAULT '{}',
+    signup_ip VARCHAR(45),
+    device_id VARCHAR(255),
+    email_domain VARCHAR(255),
+    qualifying_invoices INTEGER NOT NULL DEFAULT 0,
+    required_invoices INTEGER NOT NULL,
+    referrer_lumis INTEGER NOT NULL DEFAULT 0,   -- acreditados al referidor
+    referee_lumis INTEGER NOT NULL DEFAULT 0,    -- acreditados al referido
+    qualified_at TIMESTAMPTZ,
+    rewarded_at TIMESTAMPTZ,
+    reviewed_by BIGINT,
+    review_note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT not_self_referral CHECK (referrer_user_id <> referee_user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_referrals_referrer
+ON gamification.referrals(referrer_user_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_referrals_signup_ip
+ON gamification.referrals(signup_ip, created_at)
+WHERE signup_ip IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_referrals_device
+ON gamification.referrals(device_id)
+WHERE device_id IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_referrals_status
+ON gamification.referrals(status)
+WHERE status IN ('pending', 'review');
+
+-- 3. Regla genérica para las acumulaciones de referidos (quantity lleva el valor real).
+--    Sin rule_scope: el motor de reglas de facturas no la evalúa.
+INSERT INTO rewards.dim_accumulations
+(id, name, points, valid_from, valid_to)
+VALUES
+(31, 'referral', 0, '2026-01-01'::DATE, '2099-12-31'::DATE)
+ON CONFLICT (id) DO NOTHING;
+
+COMMENT ON TABLE gamification.referrals IS
+'Atribución de referidos. El premio se libera cuando el referido guarda required_invoices facturas válidas';
+COMMENT ON COLUMN gamification.referrals.fraud_flags IS
+'Señales antifraude: disposable_email, shared_device, shared_ip, ip_velocity, referrer_cap';
+
+COMMIT;
@@ -0,0 +1,70 @@
+-- ============================================================================
+-- MIGRATION: Streak freezes y recuperación de rachas
+-- Date: 2026-10-18
+-- Descripción: Inventario de "congeladores" de racha (comprados con Lümis u
+--              otorgados por misiones), consumo automático al faltar un día,
+--              recuperación pagada de una racha rota dentro de 48h e
+--              historial de eventos de racha. La lógica vive en Rust
+--              (domains::gamification::streak_service).
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Estado de ruptura en la racha (para ofrecer recuperación)
+ALTER TABLE gamification.user_streaks
+ADD COLUMN IF NOT EXISTS broken_count INTEGER,
+ADD COLUMN IF NOT EXISTS broken_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN gamification.user_streaks.broken_count IS
+'Días que tenía la racha cuando se rompió (NULL si no hay recuperación pendiente)';
+COMMENT ON COLUMN gamification.user_streaks.broken_at IS
+'Momento en que se rompió la racha; la recuperación es posible durante 48h';
+
+-- 2. Inventario de congeladores por usuario
+CREATE TABLE IF NOT EXISTS gamification.user_streak_freezes (
+    user_id INTEGER PRIMARY KEY REFERENCES public.dim_users(id),
+    available INTEGER NOT NULL DEFAULT 0 CHECK (available >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 3. Libro de movimientos de congeladores (+ compra/misión/admin, - consumo)
+CREATE TABLE IF NOT EXISTS gamification.streak_freeze_ledger (
+    entry_id BIGSERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES public.dim_users(id),
+    delta INTEGER NOT NULL CHECK (delta <> 0),
+    reason VARCHAR(20) NOT NULL CHECK (reason IN ('purchase', 'mission', 'admin', 'consumed')),
+    reference VARCHAR(100),               -- código de misión, admin, etc.
+    covered_date DATE,                    -- día protegido (solo 'consumed')
+    lumis_spent INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_streak_freeze_ledger_user
+ON gamification.streak_freeze_ledger(user_id, created_at DESC);
+
+-- 4. Historial de eventos de racha (visible en el dashboard)
+CREATE TABLE IF NOT EXISTS gamification.streak_history (
+    event_id BIGSERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES public.dim_users(id),
+    streak_type VARCHAR(30) NOT NULL,
+    event_type VARCHAR(20) NOT NULL CHECK (event_type IN (
+        'started', 'extended', 'frozen', 'broken', 'restored', 'cycle_completed'
+    )),
+    streak_count INTEGER NOT NULL,        -- racha después del evento
+    previous_count INTEGER,
+    event_date DATE NOT NULL,
+    lumis_spent INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_streak_history_user
+ON gamification.streak_history(user_id, created_at DESC);
+
+COMMENT ON TABLE gamification.user_streak_freezes IS
+'Congeladores de racha disponibles; se consumen automáticamente al faltar un día de login';
+COMMENT ON TABLE gamification.streak_freeze_ledger IS
+'Movimientos de congeladores. Las compras tienen además un spend en rewards.fact_accumulations (dtype streak_freeze)';
+COMMENT ON TABLE gamification.streak_history IS
+'Eventos de racha: inicio, avance, días congelados, ruptura, recuperación y ciclo completado';
+
+COMMIT;
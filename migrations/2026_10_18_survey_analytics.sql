@@ -0,0 +1,35 @@
+-- ============================================================================
+-- MIGRATION: Resultados de encuestas para admins y patrocinadores
+-- Date: 2026-10-18
+-- Descripción: Los resultados se agregan en Rust (domains::surveys::analytics)
+--              con supresión de celdas pequeñas. Aquí solo se agrega la
+--              provincia del usuario (para cruces) y la relación campaña →
+--              patrocinador que define quién puede ver cada encuesta.
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Provincia del usuario (cruces y geo_restriction de encuestas)
+ALTER TABLE public.dim_users
+ADD COLUMN IF NOT EXISTS province VARCHAR(100);
+
+-- 2. Patrocinadores: usuarios que pueden ver los resultados de una campaña
+CREATE TABLE IF NOT EXISTS survey.campaign_sponsors (
+    campaign_id INTEGER NOT NULL REFERENCES survey.dim_campaigns(campaign_id),
+    user_id BIGINT NOT NULL,
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (campaign_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_campaign_sponsors_user
+ON survey.campaign_sponsors(user_id);
+
+-- 3. Lectura de todas las asignaciones de una encuesta
+CREATE INDEX IF NOT EXISTS idx_user_survey_status_survey
+ON survey.fact_user_survey_status(survey_id, status);
+
+COMMENT ON TABLE survey.campaign_sponsors IS
+'Usuarios con acceso a los resultados agregados (con supresión) de las encuestas de la campaña';
+
+COMMIT;
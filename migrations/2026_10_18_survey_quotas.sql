@@ -0,0 +1,48 @@
+-- ============================================================================
+-- MIGRATION: Cuotas de muestra por encuesta
+-- Date: 2026-10-18
+-- Descripción: Definición de cuotas (total y celdas por género, edad, país o
+--              provincia) y reservas de cupo por usuario. La lógica de
+--              elegibilidad y balanceo vive en Rust (domains::surveys::quotas);
+--              la fila de survey_quotas se bloquea al reservar para que dos
+--              usuarios no tomen el último cupo de una celda a la vez.
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Definición de cuotas (una por encuesta)
+CREATE TABLE IF NOT EXISTS survey.survey_quotas (
+    survey_id INTEGER PRIMARY KEY REFERENCES survey.dim_surveys(survey_id),
+    definition JSONB NOT NULL,
+    updated_by BIGINT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 2. Cupos tomados: reserved (vigente hasta expires_at), completed, released
+CREATE TABLE IF NOT EXISTS survey.quota_reservations (
+    survey_id INTEGER NOT NULL REFERENCES survey.dim_surveys(survey_id),
+    user_id BIGINT NOT NULL,
+    cells JSONB NOT NULL DEFAULT '{}'::jsonb,
+    status VARCHAR(20) NOT NULL DEFAULT 'reserved'
+        CHECK (status IN ('reserved', 'completed', 'released')),
+    reserved_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ,
+    released_at TIMESTAMPTZ,
+    PRIMARY KEY (survey_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_quota_reservations_survey_status
+ON survey.quota_reservations(survey_id, status);
+
+CREATE INDEX IF NOT EXISTS idx_quota_reservations_expiring
+ON survey.quota_reservations(expires_at)
+WHERE status = 'reserved';
+
+COMMENT ON TABLE survey.survey_quotas IS
+'Cuotas compradas por el patrocinador: {"total": N, "groups": [{"attribute", "cells": [{value, min, max, percent}], "closed"}]}';
+
+COMMENT ON TABLE survey.quota_reservations IS
+'Cupo por usuario con las celdas de su perfil; cuenta si está completed o reserved sin vencer';
+
+COMMIT;
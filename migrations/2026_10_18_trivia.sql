@@ -0,0 +1,90 @@
+-- ============================================================================
+-- MIGRATION: Motor de trivias
+-- Date: 2026-10-18
+-- Descripción: Banco de preguntas con dificultad, un set diario por fecha de
+--              Panamá (2 fáciles, 2 medias, 1 difícil) y un intento por
+--              pregunta con ventana de respuesta medida en el servidor.
+--              5 Lümis por respuesta correcta más bonos por racha dentro del
+--              set (domains::gamification::trivia_service). Se juega por
+--              /api/v4/trivia y por WhatsApp (/trivia).
+--              Nota: trivia_schema_optimized.sql contiene en realidad el
+--              esquema de encuestas; las trivias viven en el esquema trivia.
+-- ============================================================================
+
+BEGIN;
+
+CREATE SCHEMA IF NOT EXISTS trivia;
+
+-- 1. Banco de preguntas
+CREATE TABLE IF NOT EXISTS trivia.questions (
+    question_id SERIAL PRIMARY KEY,
+    question_text VARCHAR(500) NOT NULL,
+    options JSONB NOT NULL,                   -- ["Opción A", "Opción B", ...] (2 a 4)
+    correct_option SMALLINT NOT NULL,         -- Índice base 0 dentro de options
+    explanation TEXT,
+    category VARCHAR(50) NOT NULL DEFAULT 'general',
+    difficulty VARCHAR(10) NOT NULL,
+    time_limit_seconds INTEGER NOT NULL DEFAULT 20,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT valid_trivia_difficulty CHECK (difficulty IN ('easy', 'medium', 'hard')),
+    CONSTRAINT valid_trivia_options CHECK (
+        jsonb_typeof(options) = 'array'
+        AND jsonb_array_length(options) BETWEEN 2 AND 4
+        AND correct_option >= 0
+        AND correct_option < jsonb_array_length(options)
+    ),
+    CONSTRAINT valid_trivia_time_limit CHECK (time_limit_seconds BETWEEN 5 AND 120)
+);
+
+CREATE INDEX IF NOT EXISTS idx_trivia_questions_active
+ON trivia.questions(difficulty)
+WHERE is_active;
+
+-- 2. Set diario (se genera con la primera jugada del día o lo fija un admin)
+CREATE TABLE IF NOT EXISTS trivia.daily_sets (
+    set_date DATE PRIMARY KEY,                -- Fecha de Panamá
+    question_ids INTEGER[] NOT NULL,          -- En orden de juego
+    created_by BIGINT,                        -- NULL = generado automáticamente
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 3. Intentos: uno por usuario y pregunta del set
+CREATE TABLE IF NOT EXISTS trivia.attempts (
+    user_id INTEGER NOT NULL,
+    set_date DATE NOT NULL,
+    question_id INTEGER NOT NULL REFERENCES trivia.questions(question_id),
+    position SMALLINT NOT NULL,               -- 1..N dentro del set
+    channel VARCHAR(20) NOT NULL,             -- api, whatsapp
+    served_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deadline_at TIMESTAMPTZ NOT NULL,         -- served_at + tiempo límite + gracia del canal
+    answered_at TIMESTAMPTZ,
+    selected_option SMALLINT,                 -- NULL si se agotó el tiempo
+    is_correct BOOLEAN,
+    response_ms INTEGER,                      -- answered_at - served_at
+    timed_out BOOLEAN NOT NULL DEFAULT FALSE,
+    flagged BOOLEAN NOT NULL DEFAULT FALSE,   -- Respuesta más rápida de lo humanamente posible
+    streak INTEGER NOT NULL DEFAULT 0,        -- Correctas seguidas al responder
+    lumis_awarded INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, set_date, question_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_trivia_attempts_question
+ON trivia.attempts(question_id, set_date);
+
+-- 4. Regla genérica para las acumulaciones de trivias
+INSERT INTO rewards.dim_accumulations
+(id, name, points, valid_from, valid_to)
+VALUES
+(33, 'trivia', 0, '2026-01-01'::DATE, '2099-12-31'::DATE)
+ON CONFLICT (id) DO NOTHING;
+
+COMMENT ON TABLE trivia.questions IS
+'Banco de preguntas de trivia; correct_option nunca se envía al cliente antes de responder';
+COMMENT ON TABLE trivia.daily_sets IS
+'Preguntas del día por fecha de Panamá';
+COMMENT ON TABLE trivia.attempts IS
+'Un intento por pregunta con tiempos medidos en el servidor (anti-trampa)';
+
+COMMIT;
@@ -0,0 +1,33 @@
+-- ============================================================================
+-- MIGRATION: Idioma preferido de usuarios y comercios
+-- Date: 2026-10-18
+-- Descripción: Los textos salientes (push, notificaciones in-app, WhatsApp,
+--              emails) se renderizan desde el catálogo src/shared/i18n en el
+--              idioma guardado aquí. Español por defecto; si falta un mensaje
+--              en inglés se usa el español.
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Usuarios (editable en PUT /api/v4/notifications/preferences)
+ALTER TABLE public.dim_users
+    ADD COLUMN IF NOT EXISTS locale VARCHAR(5) NOT NULL DEFAULT 'es';
+
+ALTER TABLE public.dim_users
+    DROP CONSTRAINT IF EXISTS dim_users_locale_check;
+ALTER TABLE public.dim_users
+    ADD CONSTRAINT dim_users_locale_check CHECK (locale IN ('es', 'en'));
+
+-- 2. Comercios (reporte semanal)
+ALTER TABLE rewards.merchants
+    ADD COLUMN IF NOT EXISTS locale VARCHAR(5) NOT NULL DEFAULT 'es';
+
+ALTER TABLE rewards.merchants
+    DROP CONSTRAINT IF EXISTS merchants_locale_check;
+ALTER TABLE rewards.merchants
+    ADD CONSTRAINT merchants_locale_check CHECK (locale IN ('es', 'en'));
+
+COMMENT ON COLUMN public.dim_users.locale IS 'Idioma de los mensajes salientes (es, en)';
+COMMENT ON COLUMN rewards.merchants.locale IS 'Idioma de los reportes por email (es, en)';
+
+COMMIT;
@@ -0,0 +1,53 @@
+-- ============================================================================
+-- MIGRATION: Tags de usuario derivados (facturas, LumiMatch, encuestas)
+-- Date: 2026-10-18
+-- Descripción: Evidencia por tag y referencia de origen, y confianza/vigencia
+--              en lumimatch.user_tags. La derivación y el recálculo viven en
+--              Rust (domains::lumimatch::tagging); las filas existentes quedan
+--              como 'manual' y la derivación automática no las modifica.
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Confianza y vigencia de cada tag
+ALTER TABLE lumimatch.user_tags
+    ADD COLUMN IF NOT EXISTS source VARCHAR(20) NOT NULL DEFAULT 'manual',
+    ADD COLUMN IF NOT EXISTS confidence REAL NOT NULL DEFAULT 1.0,
+    ADD COLUMN IF NOT EXISTS evidence_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ DEFAULT NOW();
+
+CREATE INDEX IF NOT EXISTS idx_lumimatch_user_tags_expiring
+ON lumimatch.user_tags(expires_at)
+WHERE expires_at IS NOT NULL;
+
+-- 2. Evidencia: una fila por tag y referencia (CUFE, question_id, survey_id)
+CREATE TABLE IF NOT EXISTS lumimatch.user_tag_evidence (
+    user_id INTEGER NOT NULL,
+    tag TEXT NOT NULL,
+    source VARCHAR(20) NOT NULL CHECK (source IN ('invoice', 'lumimatch', 'survey')),
+    source_ref TEXT NOT NULL,
+    label TEXT,
+    observed_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, tag, source, source_ref)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_tag_evidence_ref
+ON lumimatch.user_tag_evidence(user_id, source, source_ref);
+
+CREATE INDEX IF NOT EXISTS idx_user_tag_evidence_observed
+ON lumimatch.user_tag_evidence(source, observed_at);
+
+-- 3. Tags que recibe quien elige una opción de LumiMatch
+ALTER TABLE lumimatch.options
+    ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}';
+
+COMMENT ON TABLE lumimatch.user_tag_evidence IS
+'Evidencia de tags derivados; confidence = 1 - Π(1 - peso) sobre la evidencia vigente (invoice 0.5/180d, lumimatch 0.9/365d, survey 0.8/365d)';
+
+COMMENT ON COLUMN lumimatch.user_tags.source IS
+'manual (carga del equipo, no se recalcula) o la fuente de mayor peso: invoice, lumimatch, survey';
+
+COMMIT;
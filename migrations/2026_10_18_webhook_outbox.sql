@@ -0,0 +1,61 @@
+-- ============================================================================
+-- MIGRATION: Durable webhook outbox for merchant notifications
+-- Date: 2026-10-18
+-- Descripción: Los eventos de webhook se escriben en la misma transacción
+--              que el cambio de la redención y los entrega un dispatcher en
+--              background con backoff exponencial y estado dead-letter
+-- ============================================================================
+
+BEGIN;
+
+-- 1. Outbox: una fila por entrega (un replay crea una nueva entrega del mismo evento)
+CREATE TABLE IF NOT EXISTS rewards.webhook_outbox (
+    delivery_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_id UUID NOT NULL,               -- Estable entre replays, para deduplicar del lado del merchant
+    merchant_id UUID NOT NULL REFERENCES rewards.merchants(merchant_id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    payload JSONB NOT NULL,               -- Cuerpo completo del evento (id, event, timestamp, data)
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 10,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMPTZ,             -- Lease del dispatcher; si el proceso muere se reintenta al vencer
+    last_attempt_at TIMESTAMPTZ,
+    last_status_code INTEGER,
+    last_error TEXT,
+    delivered_at TIMESTAMPTZ,
+    replay_of UUID REFERENCES rewards.webhook_outbox(delivery_id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT valid_webhook_status CHECK (
+        status IN ('pending', 'delivering', 'delivered', 'dead')
+    )
+);
+
+-- Índice para el dispatcher (solo filas pendientes de entrega)
+CREATE INDEX IF NOT EXISTS idx_webhook_outbox_due
+ON rewards.webhook_outbox(next_attempt_at)
+WHERE status IN ('pending', 'delivering');
+
+-- Índice para el listado del merchant
+CREATE INDEX IF NOT EXISTS idx_webhook_outbox_merchant
+ON rewards.webhook_outbox(merchant_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_outbox_event
+ON rewards.webhook_outbox(event_id);
+
+-- 2. webhook_logs pasa a ser el log por intento de entrega
+ALTER TABLE rewards.webhook_logs
+ADD COLUMN IF NOT EXISTS delivery_id UUID REFERENCES rewards.webhook_outbox(delivery_id) ON DELETE CASCADE,
+ADD COLUMN IF NOT EXISTS attempt_number INTEGER,
+ADD COLUMN IF NOT EXISTS status_code INTEGER,
+ADD COLUMN IF NOT EXISTS response_body TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_webhook_logs_delivery
+ON rewards.webhook_logs(delivery_id, attempt_number)
+WHERE delivery_id IS NOT NULL;
+
+COMMENT ON TABLE rewards.webhook_outbox IS 'Outbox transaccional de webhooks a merchants (pending → delivering → delivered | dead)';
+COMMENT ON COLUMN rewards.webhook_outbox.event_id IS 'ID del evento enviado en el payload; se mantiene en replays';
+COMMENT ON COLUMN rewards.webhook_logs.delivery_id IS 'Entrega del outbox a la que pertenece este intento';
+
+COMMIT;
@@ -6,6 +6,7 @@
 //! - Redis caching
 //! - Common types and utilities
 //! - Service communication helpers
+//! - WhatsApp webhook signature verification
 
 pub mod auth;
 pub mod cache;
@@ -16,6 +17,7 @@ pub mod models;
 pub mod service_client;
 pub mod types;
 pub mod utils;
+pub mod webhook_signature;
 
 // Re-export commonly used types
 pub use auth::{AuthService, Claims, TokenPair};
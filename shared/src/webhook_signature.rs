@@ -0,0 +1,223 @@
+//! WhatsApp webhook signature verification (`X-Hub-Signature-256`)
+//!
+//! Meta signs every webhook POST with HMAC-SHA256 over the raw body using the
+//! app secret (`sha256=<hex>`). Two secrets can be active at once so the app
+//! secret can be rotated without dropping webhooks: `WHATSAPP_APP_SECRET`
+//! (current) and `WHATSAPP_APP_SECRET_PREVIOUS` (removed once no traffic is
+//! signed with it).
+//!
+//! This is the only implementation: the monolith webhook (`/webhookws`) and the
+//! api-gateway both use it, so parsing, rotation and metric labels stay in sync.
+
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::sync::atomic::{AtomicU64, Ordering};
+use thiserror::Error;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "x-hub-signature-256";
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+/// Which active secret validated the signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretSlot {
+    Current,
+    Previous,
+}
+
+impl SecretSlot {
+    /// Metric label
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SecretSlot::Current => "current",
+            SecretSlot::Previous => "previous",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+pub enum SignatureError {
+    #[error("WhatsApp app secret is not configured")]
+    NotConfigured,
+    #[error("Missing X-Hub-Signature-256 header")]
+    Missing,
+    #[error("Malformed X-Hub-Signature-256 header")]
+    Malformed,
+    #[error("Signature does not match any active secret")]
+    Mismatch,
+}
+
+impl SignatureError {
+    /// Metric label
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SignatureError::NotConfigured => "not_configured",
+            SignatureError::Missing => "missing",
+            SignatureError::Malformed => "malformed",
+            SignatureError::Mismatch => "mismatch",
+        }
+    }
+}
+
+/// Signature check counters (api-gateway health endpoint; the monolith also
+/// exports them to Prometheus with the `as_str` labels)
+#[derive(Debug, Default)]
+pub struct SignatureMetrics {
+    valid_current: AtomicU64,
+    valid_previous: AtomicU64,
+    missing: AtomicU64,
+    malformed: AtomicU64,
+    mismatch: AtomicU64,
+    not_configured: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SignatureStats {
+    pub valid_current: u64,
+    pub valid_previous: u64,
+    pub missing: u64,
+    pub malformed: u64,
+    pub mismatch: u64,
+    pub not_configured: u64,
+}
+
+impl SignatureMetrics {
+    pub fn record(&self, result: &Result<SecretSlot, SignatureError>) {
+        let counter = match result {
+            Ok(SecretSlot::Current) => &self.valid_current,
+            Ok(SecretSlot::Previous) => &self.valid_previous,
+            Err(SignatureError::Missing) => &self.missing,
+            Err(SignatureError::Malformed) => &self.malformed,
+            Err(SignatureError::Mismatch) => &self.mismatch,
+            Err(SignatureError::NotConfigured) => &self.not_configured,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> SignatureStats {
+        SignatureStats {
+            valid_current: self.valid_current.load(Ordering::Relaxed),
+            valid_previous: self.valid_previous.load(Ordering::Relaxed),
+            missing: self.missing.load(Ordering::Relaxed),
+            malformed: self.malformed.load(Ordering::Relaxed),
+            mismatch: self.mismatch.load(Ordering::Relaxed),
+            not_configured: self.not_configured.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Build the `sha256=<hex>` header for a body (tests and local scripts)
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(body);
+    format!("{}{}", SIGNATURE_PREFIX, hex::encode(mac.finalize().into_bytes()))
+}
+
+#[derive(Debug, Default)]
+pub struct SignatureVerifier {
+    current: Option<String>,
+    previous: Option<String>,
+    pub metrics: SignatureMetrics,
+}
+
+impl SignatureVerifier {
+    pub fn new(current: Option<String>, previous: Option<String>) -> Self {
+        let non_empty = |s: Option<String>| s.filter(|s| !s.trim().is_empty());
+        Self {
+            current: non_empty(current),
+            previous: non_empty(previous),
+            metrics: SignatureMetrics::default(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("WHATSAPP_APP_SECRET").ok(),
+            std::env::var("WHATSAPP_APP_SECRET_PREVIOUS").ok(),
+        )
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.current.is_some() || self.previous.is_some()
+    }
+
+    /// Verify the header against the active secrets (constant-time compare) and record the outcome
+    pub fn verify(&self, header: Option<&str>, body: &[u8]) -> Result<SecretSlot, SignatureError> {
+        let result = self.check(header, body);
+        self.metrics.record(&result);
+        result
+    }
+
+    fn check(&self, header: Option<&str>, body: &[u8]) -> Result<SecretSlot, SignatureError> {
+        if !self.is_configured() {
+            return Err(SignatureError::NotConfigured);
+        }
+        let header = header.ok_or(SignatureError::Missing)?;
+        let expected = header
+            .trim()
+            .strip_prefix(SIGNATURE_PREFIX)
+            .and_then(|h| hex::decode(h).ok())
+            .filter(|bytes| bytes.len() == 32)
+            .ok_or(SignatureError::Malformed)?;
+
+        let slots = [(SecretSlot::Current, &self.current), (SecretSlot::Previous, &self.previous)];
+        for (slot, secret) in slots {
+            let Some(secret) = secret else { continue };
+            let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+                continue;
+            };
+            mac.update(body);
+            if mac.verify_slice(&expected).is_ok() {
+                return Ok(slot);
+            }
+        }
+        Err(SignatureError::Mismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"object":"whatsapp_business_account","entry":[]}"#;
+
+    #[test]
+    fn test_verify_rotating_secrets() {
+        let verifier = SignatureVerifier::new(Some("new".to_string()), Some("old".to_string()));
+        assert_eq!(verifier.verify(Some(&sign("new", BODY)), BODY), Ok(SecretSlot::Current));
+        assert_eq!(verifier.verify(Some(&sign("old", BODY)), BODY), Ok(SecretSlot::Previous));
+        assert_eq!(verifier.verify(Some(&sign("other", BODY)), BODY), Err(SignatureError::Mismatch));
+        assert_eq!(verifier.verify(Some(&sign("new", BODY)), b"{}"), Err(SignatureError::Mismatch));
+        assert_eq!(verifier.verify(None, BODY), Err(SignatureError::Missing));
+        assert_eq!(verifier.verify(Some("sha256=zz"), BODY), Err(SignatureError::Malformed));
+
+        let stats = verifier.metrics.snapshot();
+        assert_eq!((stats.valid_current, stats.valid_previous), (1, 1));
+        assert_eq!((stats.mismatch, stats.missing, stats.malformed), (2, 1, 1));
+
+        // Once the previous secret is retired its signatures stop validating
+        let rotated = SignatureVerifier::new(Some("new".to_string()), None);
+        assert_eq!(rotated.verify(Some(&sign("old", BODY)), BODY), Err(SignatureError::Mismatch));
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_headers() {
+        let verifier = SignatureVerifier::new(Some("new".to_string()), None);
+        for header in ["sha1=abcd", "sha256=not-hex", "sha256=abcd"] {
+            assert_eq!(verifier.verify(Some(header), BODY), Err(SignatureError::Malformed));
+        }
+        assert_eq!(SignatureError::Malformed.as_str(), "malformed");
+        assert_eq!(SecretSlot::Previous.as_str(), "previous");
+    }
+
+    #[test]
+    fn test_verify_fails_closed_without_secrets() {
+        let verifier = SignatureVerifier::new(None, Some(String::new()));
+        assert_eq!(verifier.verify(Some(&sign("", BODY)), BODY), Err(SignatureError::NotConfigured));
+
+        let blank = SignatureVerifier::new(Some("  ".to_string()), None);
+        assert!(!blank.is_configured());
+    }
+}
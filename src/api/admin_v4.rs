@@ -32,36 +32,12 @@ use std::sync::Arc;
 use tracing::{info, warn, error};
 use uuid::Uuid;
 
+use crate::shared::admin::is_admin;
 use crate::api::common::{ApiError, ApiResponse};
 use crate::middleware::auth::CurrentUser;
 use crate::state::AppState;
 use axum::Extension;
 
-// ============================================================================
-// ADMIN USER VALIDATION
-// ============================================================================
-
-/// List of admin user IDs (loaded from env or hardcoded for now)
-/// In production, this should come from database or environment variable
-fn get_admin_user_ids() -> Vec<i64> {
-    // Try to load from environment variable
-    if let Ok(admin_ids) = std::env::var("ADMIN_USER_IDS") {
-        admin_ids
-            .split(',')
-            .filter_map(|s| s.trim().parse::<i64>().ok())
-            .collect()
-    } else {
-        // Default admin IDs - UPDATE THESE for production
-        vec![1, 2, 3] // user_id 1, 2, 3 are admins by default
-    }
-}
-
-/// Validates if the current user is an admin
-fn is_admin_user(user_id: i64) -> bool {
-    let admin_ids = get_admin_user_ids();
-    admin_ids.contains(&user_id)
-}
-
 // ============================================================================
 // REQUEST/RESPONSE MODELS
 // ============================================================================
@@ -138,16 +114,16 @@ pub async fn update_dgi_captcha_handler(
     let user_id = current_user.user_id;
     
     // SECURITY: Validate admin role
-    if !is_admin_user(user_id) {
+    if !is_admin(user_id) {
         error!("🚫 Unauthorized admin access attempt by user {}", user_id);
-        return Err(ApiError::new("FORBIDDEN", "No tienes permisos de administrador"));
+        return Err(ApiError::localized("FORBIDDEN", "api.forbidden_admin", &[]));
     }
     
     info!("🔐 Admin user {} is updating DGI captcha token", user_id);
     
     // Validate captcha token
     if request.captcha_token.is_empty() {
-        return Err(ApiError::validation_error("captcha_token cannot be empty"));
+        return Err(ApiError::localized("VALIDATION_ERROR", "api.admin.captcha_token_empty", &[]));
     }
     
     if request.captcha_token.len() < 100 {
@@ -62,7 +62,7 @@ pub async fn login_user(
             .collect::<Vec<_>>()
             .join(", ");
             
-        return Err(ApiError::new("VALIDATION_FAILED", &error_message));
+        return Err(ApiError::localized("VALIDATION_FAILED", "api.validation.fields", &[("fields", &error_message)]));
     }
 
     // Step 2: Normalize email
@@ -89,7 +89,7 @@ pub async fn login_user(
                 "❌ User not found for login"
             );
             
-            return Err(ApiError::new("AUTHENTICATION_FAILED", "User not found"));
+            return Err(ApiError::localized("AUTHENTICATION_FAILED", "api.auth.user_not_found", &[]));
         }
         Err(e) => {
             error!(
@@ -98,7 +98,7 @@ pub async fn login_user(
                 "❌ Database error during login"
             );
             
-            return Err(ApiError::new("DATABASE_ERROR", "Failed to query user data"));
+            return Err(ApiError::localized("DATABASE_ERROR", "api.database_error", &[]));
         }
     };
 
@@ -114,7 +114,11 @@ pub async fn login_user(
                         error = %e,
                         "❌ Error verifying password"
                     );
-                    return Err(ApiError::new("AUTHENTICATION_ERROR", "Password verification failed"));
+                    return Err(ApiError::localized(
+                        "AUTHENTICATION_ERROR",
+                        "api.auth.password_verification_failed",
+                        &[],
+                    ));
                 }
             }
         }
@@ -125,10 +129,7 @@ pub async fn login_user(
                 email = %email,
                 "❌ User attempted login but has no password set - should use account setup flow"
             );
-            return Err(ApiError::new(
-                "ACCOUNT_SETUP_REQUIRED", 
-                "This account requires password setup. Please use the account setup process."
-            ));
+            return Err(ApiError::localized("ACCOUNT_SETUP_REQUIRED", "api.auth.account_setup_required", &[]));
         }
     };
 
@@ -139,7 +140,7 @@ pub async fn login_user(
             "❌ Invalid password provided"
         );
         
-        return Err(ApiError::new("AUTHENTICATION_FAILED", "Invalid credentials"));
+        return Err(ApiError::localized("AUTHENTICATION_FAILED", "api.auth.invalid_credentials", &[]));
     }
 
     // Step 5: Check if account is active
@@ -150,7 +151,7 @@ pub async fn login_user(
             "❌ Inactive account attempted login"
         );
         
-        return Err(ApiError::new("ACCOUNT_INACTIVE", "User account is not active"));
+        return Err(ApiError::localized("ACCOUNT_INACTIVE", "api.auth.account_inactive", &[]));
     }
 
     // Step 6: Generate JWT token and update last login
@@ -171,7 +172,7 @@ pub async fn login_user(
                 error = %e,
                 "❌ Failed to create JWT token"
             );
-            return Err(ApiError::new("TOKEN_ERROR", "Failed to generate authentication token"));
+            return Err(ApiError::localized("TOKEN_ERROR", "api.auth.token_error", &[]));
         }
     };
 
@@ -250,7 +251,7 @@ pub async fn register_user(
             .collect::<Vec<_>>()
             .join(", ");
             
-        return Err(ApiError::new("VALIDATION_FAILED", &error_message));
+        return Err(ApiError::localized("VALIDATION_FAILED", "api.validation.fields", &[("fields", &error_message)]));
     }
 
     // Step 2: Sanitize inputs
@@ -265,7 +266,7 @@ pub async fn register_user(
             email = %email,
             "❌ Invalid email format"
         );
-        return Err(ApiError::new("VALIDATION_FAILED", "Invalid email format"));
+        return Err(ApiError::localized("VALIDATION_FAILED", "api.auth.invalid_email", &[]));
     }
 
     // Step 4: Check if user already exists
@@ -283,7 +284,7 @@ pub async fn register_user(
                 email = %email,
                 "❌ Email already registered"
             );
-            return Err(ApiError::new("EMAIL_EXISTS", "Email already registered"));
+            return Err(ApiError::localized("EMAIL_EXISTS", "api.auth.email_exists", &[]));
         }
         Ok(None) => {
             info!(
@@ -298,7 +299,7 @@ pub async fn register_user(
                 error = %e,
                 "❌ Database error checking user existence"
             );
-            return Err(ApiError::new("DATABASE_ERROR", "Failed to check email availability"));
+            return Err(ApiError::localized("DATABASE_ERROR", "api.database_error", &[]));
         }
     }
 
@@ -311,7 +312,7 @@ pub async fn register_user(
                 error = %e,
                 "❌ Failed to hash password"
             );
-            return Err(ApiError::new("INTERNAL_ERROR", "Password processing failed"));
+            return Err(ApiError::localized("INTERNAL_ERROR", "api.auth.password_processing_failed", &[]));
         }
     };
 
@@ -346,7 +347,7 @@ pub async fn register_user(
                 error = %e,
                 "❌ Failed to create user"
             );
-            return Err(ApiError::new("DATABASE_ERROR", "Failed to create user account"));
+            return Err(ApiError::localized("DATABASE_ERROR", "api.auth.account_creation_failed", &[]));
         }
     };
 
@@ -361,7 +362,7 @@ pub async fn register_user(
                 error = %e,
                 "❌ Failed to create JWT token"
             );
-            return Err(ApiError::new("TOKEN_ERROR", "Failed to generate authentication token"));
+            return Err(ApiError::localized("TOKEN_ERROR", "api.auth.token_error", &[]));
         }
     };
 
@@ -409,7 +410,7 @@ pub async fn check_user_status(
             "❌ Invalid user ID provided"
         );
         
-        return Err(ApiError::new("VALIDATION_FAILED", "Invalid user ID"));
+        return Err(ApiError::localized("VALIDATION_FAILED", "api.auth.invalid_user_id", &[]));
     }
 
     // Step 2: Get user from database
@@ -459,7 +460,7 @@ pub async fn check_user_status(
                 "❌ User not found for status check"
             );
             
-            Err(ApiError::new("USER_NOT_FOUND", "User not found"))
+            Err(ApiError::localized("USER_NOT_FOUND", "api.not_found.user", &[]))
         }
         Err(e) => {
             error!(
@@ -469,7 +470,7 @@ pub async fn check_user_status(
                 "❌ Database error during status check"
             );
             
-            Err(ApiError::new("DATABASE_ERROR", "Failed to query user data"))
+            Err(ApiError::localized("DATABASE_ERROR", "api.database_error", &[]))
         }
     }
 }
@@ -495,7 +496,11 @@ pub async fn check_user_status_post(
             "❌ Status check validation failed"
         );
         
-        return Err(ApiError::new("VALIDATION_FAILED", &format!("Input validation errors: {:?}", validation_errors)));
+        return Err(ApiError::localized(
+            "VALIDATION_FAILED",
+            "api.validation.fields",
+            &[("fields", &format!("{:?}", validation_errors))],
+        ));
     }
 
     // Reuse the GET endpoint logic
@@ -23,6 +23,7 @@ use uuid::Uuid;
 use sqlx::PgPool;
 use serde_json::Value;
 
+use crate::shared::i18n;
 use crate::state::AppState;
 
 
@@ -211,6 +212,21 @@ impl ApiError {
     pub fn too_many_requests(message: &str) -> Self {
         Self::new("TOO_MANY_REQUESTS", message)
     }
+
+    /// Error con el mensaje del catálogo en el idioma de la petición
+    pub fn localized(code: &str, message_id: &str, params: &[(&str, &dyn std::fmt::Display)]) -> Self {
+        Self::new(code, &i18n::render(i18n::request_locale(), message_id, params))
+    }
+
+    /// Falla de base de datos: el detalle va al log, no a la respuesta
+    pub fn database_failure(context: &str, err: impl std::fmt::Display) -> Self {
+        error!("{}: {}", context, err);
+        Self::localized("DATABASE_ERROR", "api.database_error", &[])
+    }
+
+    pub fn forbidden() -> Self {
+        Self::localized("FORBIDDEN", "api.forbidden", &[])
+    }
 }
 
 impl IntoResponse for ApiError {
@@ -267,7 +283,7 @@ impl DatabaseService {
             .await
             .map_err(|e| {
                 error!("Database query failed: {}", e);
-                ApiError::database_error(&format!("Query execution failed: {}", e))
+                ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
             })?;
 
         let query_time = start_time.elapsed();
@@ -302,7 +318,7 @@ impl DatabaseService {
             .await
             .map_err(|e| {
                 error!("Database query with parameters failed: {}", e);
-                ApiError::database_error(&format!("Query execution failed: {}", e))
+                ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
             })?;
 
         let query_time = start_time.elapsed();
@@ -345,7 +361,7 @@ impl DatabaseService {
             .await
             .map_err(|e| {
                 error!("Database query failed: {}", e);
-                ApiError::database_error(&format!("Query execution failed: {}", e))
+                ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
             })?;
 
         // Cache the result (simplified for now)
@@ -381,7 +397,7 @@ impl DatabaseService {
             .await
             .map_err(|e| {
                 error!("Write operation failed: {}", e);
-                ApiError::database_error(&format!("Write operation failed: {}", e))
+                ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
             })?;
 
         info!("Write operation completed, affected rows: {}", result.rows_affected());
@@ -411,7 +427,7 @@ impl DatabaseService {
             .await
             .map_err(|e| {
                 error!("Write operation failed: {}", e);
-                ApiError::database_error(&format!("Write operation failed: {}", e))
+                ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
             })?;
 
         info!("Write operation completed, affected rows: {}", result.rows_affected());
@@ -0,0 +1,298 @@
+// ============================================================================
+// DAILY GAME ADMIN - Tabla de premios y pago real vs esperado
+// ============================================================================
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use chrono_tz::America::Panama;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::shared::admin::is_admin;
+use crate::{
+    api::daily_game::engine::{self, PrizeTable, PrizeTier},
+    middleware::CurrentUser,
+    shared::i18n,
+    state::AppState,
+};
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct PrizeConfigRequest {
+    /// Fecha (Panamá) desde la que aplica. Solo hoy o futuro.
+    pub effective_from: NaiveDate,
+    pub tiers: Vec<PrizeTier>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrizeConfigView {
+    #[serde(flatten)]
+    pub table: PrizeTable,
+    pub expected_lumis: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrizeConfigListResponse {
+    pub success: bool,
+    pub current_config_id: i64,
+    pub configs: Vec<PrizeConfigView>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrizeConfigResponse {
+    pub success: bool,
+    pub config: PrizeConfigView,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    /// YYYY-MM-DD (inclusive). Default: hace 30 días
+    pub from: Option<NaiveDate>,
+    /// YYYY-MM-DD (inclusive). Default: hoy
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DailyPayout {
+    pub play_date: NaiveDate,
+    pub plays: i64,
+    pub lumis_paid: i64,
+    pub lumis_expected: f64,
+    pub avg_paid: f64,
+    pub avg_expected: f64,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct PrizeDistribution {
+    pub lumis_won: i32,
+    pub plays: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatsResponse {
+    pub success: bool,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub total_plays: i64,
+    pub total_lumis_paid: i64,
+    pub total_lumis_expected: f64,
+    pub days: Vec<DailyPayout>,
+    pub distribution: Vec<PrizeDistribution>,
+}
+
+const DEFAULT_STATS_DAYS: i64 = 30;
+const MAX_STATS_DAYS: i64 = 366;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn require_admin(current_user: &CurrentUser) -> Result<(), ApiError> {
+    if !is_admin(current_user.user_id) {
+        warn!("User {} attempted daily game admin access", current_user.user_id);
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
+    }
+    Ok(())
+}
+
+fn db_error(e: sqlx::Error) -> ApiError {
+    error!("Daily game admin database error: {}", e);
+    ApiError::InternalError("Error al consultar el juego diario".to_string())
+}
+
+fn view(table: PrizeTable) -> PrizeConfigView {
+    let expected_lumis = table.expected_value();
+    PrizeConfigView { table, expected_lumis }
+}
+
+// ============================================================================
+// Admin Endpoints
+// ============================================================================
+
+/// Tablas de premios configuradas y la vigente hoy
+/// GET /api/v4/daily-game/admin/prize-config
+pub async fn admin_list_prize_config(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<PrizeConfigListResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    let today = Utc::now().with_timezone(&Panama).date_naive();
+    let current = engine::prize_table_for_date(&state.db_pool, today).await.map_err(db_error)?;
+    let configs = engine::list_prize_tables(&state.db_pool).await.map_err(db_error)?;
+
+    Ok(Json(PrizeConfigListResponse {
+        success: true,
+        current_config_id: current.config_id,
+        configs: configs.into_iter().map(view).collect(),
+    }))
+}
+
+/// Crea o reemplaza la tabla de premios que aplica desde una fecha
+/// PUT /api/v4/daily-game/admin/prize-config
+pub async fn admin_upsert_prize_config(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(payload): Json<PrizeConfigRequest>,
+) -> Result<Json<PrizeConfigResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    engine::validate_tiers(&payload.tiers).map_err(ApiError::BadRequest)?;
+
+    // Cambiar la tabla de un día ya en curso alteraría tableros ya comprometidos
+    let today = Utc::now().with_timezone(&Panama).date_naive();
+    if payload.effective_from <= today {
+        return Err(ApiError::BadRequest(
+            "effective_from debe ser una fecha futura (hora de Panamá)".to_string(),
+        ));
+    }
+
+    let tiers = serde_json::to_value(&payload.tiers)
+        .map_err(|e| ApiError::InternalError(format!("Error serializando premios: {}", e)))?;
+
+    let config_id = sqlx::query_scalar::<_, i64>(
+        r#"
+        INSERT INTO rewards.daily_game_prize_config (effective_from, tiers, notes, created_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (effective_from) DO UPDATE
+        SET tiers = EXCLUDED.tiers,
+            notes = EXCLUDED.notes,
+            created_by = EXCLUDED.created_by,
+            updated_at = NOW()
+        RETURNING config_id
+        "#,
+    )
+    .bind(payload.effective_from)
+    .bind(tiers)
+    .bind(payload.notes.as_deref())
+    .bind(current_user.user_id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(db_error)?;
+
+    let table = PrizeTable {
+        config_id,
+        effective_from: payload.effective_from,
+        tiers: payload.tiers,
+    };
+
+    info!(
+        "Admin {} set daily game prize config {} from {} (expected {:.3} Lumis/play)",
+        current_user.user_id,
+        config_id,
+        table.effective_from,
+        table.expected_value()
+    );
+
+    Ok(Json(PrizeConfigResponse { success: true, config: view(table) }))
+}
+
+/// Pago real vs esperado por día y distribución de premios
+/// GET /api/v4/daily-game/admin/stats?from=YYYY-MM-DD&to=YYYY-MM-DD
+pub async fn admin_daily_game_stats(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<StatsQuery>,
+) -> Result<Json<StatsResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    let today = Utc::now().with_timezone(&Panama).date_naive();
+    let to = params.to.unwrap_or(today);
+    let from = params.from.unwrap_or(to - Duration::days(DEFAULT_STATS_DAYS - 1));
+    if from > to {
+        return Err(ApiError::BadRequest("'from' debe ser anterior o igual a 'to'".to_string()));
+    }
+    if (to - from).num_days() >= MAX_STATS_DAYS {
+        return Err(ApiError::BadRequest(format!("El rango máximo es de {} días", MAX_STATS_DAYS)));
+    }
+
+    // Jugadas previas a la migración no tienen expected_lumis: se usa la tabla por defecto
+    let default_expected = PrizeTable::default_table().expected_value();
+
+    let days = sqlx::query_as::<_, DailyPayout>(
+        r#"
+        SELECT
+            play_date,
+            COUNT(*) AS plays,
+            COALESCE(SUM(lumis_won), 0)::BIGINT AS lumis_paid,
+            COALESCE(SUM(COALESCE(expected_lumis, $3::NUMERIC)), 0)::FLOAT8 AS lumis_expected,
+            COALESCE(AVG(lumis_won), 0)::FLOAT8 AS avg_paid,
+            COALESCE(AVG(COALESCE(expected_lumis, $3::NUMERIC)), 0)::FLOAT8 AS avg_expected
+        FROM rewards.fact_daily_game_plays
+        WHERE play_date BETWEEN $1 AND $2
+        GROUP BY play_date
+        ORDER BY play_date
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(default_expected)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(db_error)?;
+
+    let distribution = sqlx::query_as::<_, PrizeDistribution>(
+        r#"
+        SELECT lumis_won::INT AS lumis_won, COUNT(*) AS plays
+        FROM rewards.fact_daily_game_plays
+        WHERE play_date BETWEEN $1 AND $2
+        GROUP BY lumis_won
+        ORDER BY lumis_won
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(StatsResponse {
+        success: true,
+        from,
+        to,
+        total_plays: days.iter().map(|d| d.plays).sum(),
+        total_lumis_paid: days.iter().map(|d| d.lumis_paid).sum(),
+        total_lumis_expected: days.iter().map(|d| d.lumis_expected).sum(),
+        days,
+        distribution,
+    }))
+}
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Forbidden(String),
+    InternalError(String),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        };
+
+        let body = Json(serde_json::json!({
+            "success": false,
+            "error": message,
+        }));
+
+        (status, body).into_response()
+    }
+}
@@ -5,11 +5,12 @@ use axum::{
 };
 use chrono::Utc;
 use chrono_tz::America::Panama;
-use tracing::{info, warn, error};
+use tracing::{debug, info, warn, error};
 // use rust_decimal::Decimal; // Unused - comentado
 
 use std::sync::Arc;
 use crate::{
+    api::daily_game::engine,
     api::daily_game::templates::{DailyGameClaimRequest, DailyGameClaimResponse},
     api::common::SimpleApiResponse,
     state::AppState,
@@ -19,10 +20,12 @@ use crate::{
 /// POST /v4/daily-game/claim
 /// 
 /// Reclama la recompensa diaria después de que el usuario seleccione una estrella.
+/// El premio lo calcula el servidor a partir del tablero del día (ver `engine`);
+/// `lumis_won` enviado por el cliente se ignora.
 /// 
 /// Validaciones:
-/// - lumis_won debe ser 0, 1, o 5
 /// - star_id debe ser star_0 a star_8
+/// - game_token (si viene) debe estar firmado para este usuario y para hoy
 /// - Usuario no debe haber jugado hoy (garantizado por UNIQUE constraint)
 pub async fn handle_claim(
     State(state): State<Arc<AppState>>,
@@ -31,8 +34,11 @@ pub async fn handle_claim(
 ) -> Result<Json<SimpleApiResponse<DailyGameClaimResponse>>, (StatusCode, Json<SimpleApiResponse<()>>)> {
     
     let user_id = current_user.user_id;
-    info!("🎮 Daily game claim request from user {}: star_id={}, lumis_won={}", 
-          user_id, request.star_id, request.lumis_won);
+    info!("🎮 Daily game claim request from user {}: star_id={}", user_id, request.star_id);
+    
+    if let Some(client_lumis) = request.lumis_won {
+        debug!("Ignoring client-supplied lumis_won={} for user {}", client_lumis, user_id);
+    }
     
     // 1. Validar request
     if let Err(e) = request.validate() {
@@ -50,6 +56,54 @@ pub async fn handle_claim(
     
     info!("📅 Play date: {}, time: {}", today, play_time);
     
+    // 2b. Tabla de premios: la firmada en el token o la vigente hoy
+    let secret = engine::game_secret();
+    let pinned_config = match request.game_token.as_deref() {
+        Some(token) => match engine::verify_game_token(secret, user_id, token) {
+            Ok(game_token) if game_token.date == today => Some(game_token.config_id),
+            Ok(_) => {
+                warn!("⚠️ User {} sent a game_token for another day", user_id);
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(SimpleApiResponse::<()>::error_with_code(
+                        "GAME_TOKEN_EXPIRED",
+                        "El juego de hoy cambió. Actualiza e intenta de nuevo."
+                    )),
+                ));
+            }
+            Err(e) => {
+                warn!("❌ Invalid game_token from user {}: {}", user_id, e);
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(SimpleApiResponse::<()>::error_with_code("INVALID_GAME_TOKEN", &e)),
+                ));
+            }
+        },
+        None => None,
+    };
+    
+    let table = match pinned_config {
+        Some(config_id) => engine::prize_table_by_id(&state.db_pool, config_id).await.map(|t| t.unwrap_or_else(engine::PrizeTable::default_table)),
+        None => engine::prize_table_for_date(&state.db_pool, today).await,
+    };
+    let table = match table {
+        Ok(table) => table,
+        Err(e) => {
+            error!("❌ Failed to load daily game prize table: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(SimpleApiResponse::<()>::error("Database error")),
+            ));
+        }
+    };
+    
+    // 2c. Resultado decidido por el servidor
+    let layout = engine::derive_layout(secret, user_id, today, &table);
+    let star_index = engine::star_index(&request.star_id).unwrap_or(0);
+    let lumis_won = layout[star_index];
+    let nonce = engine::layout_nonce(secret, user_id, today);
+    let layout_commitment = engine::layout_commitment(&layout, &nonce);
+    
     // 3. Iniciar transacción
     let mut tx = match state.db_pool.begin().await {
         Ok(tx) => tx,
@@ -64,26 +118,28 @@ pub async fn handle_claim(
     
     // 4. Insertar en fact_daily_game_plays
     // El constraint UNIQUE valida automáticamente "ya jugó hoy"
-    let play_result = sqlx::query!(
+    let play_result = sqlx::query_scalar::<_, i64>(
         r#"
         INSERT INTO rewards.fact_daily_game_plays
-        (user_id, play_date, play_time, star_id, lumis_won)
-        VALUES ($1, $2, $3, $4, $5)
+        (user_id, play_date, play_time, star_id, lumis_won, config_id, expected_lumis)
+        VALUES ($1, $2, $3, $4, $5, $6, $7::NUMERIC)
         RETURNING id
-        "#,
-        user_id,
-        today,
-        play_time,
-        request.star_id,
-        request.lumis_won as i16
+        "#
     )
+    .bind(user_id)
+    .bind(today)
+    .bind(play_time)
+    .bind(&request.star_id)
+    .bind(lumis_won as i16)
+    .bind(if table.config_id > 0 { Some(table.config_id) } else { None })
+    .bind(table.expected_value())
     .fetch_one(&mut *tx)
     .await;
     
     let play_id = match play_result {
-        Ok(record) => {
-            info!("✅ Inserted daily game play with id: {}", record.id);
-            record.id
+        Ok(id) => {
+            info!("✅ Inserted daily game play with id: {} (star {} = {} Lümis)", id, request.star_id, lumis_won);
+            id
         },
         Err(e) => {
             // Detectar violación de UNIQUE constraint
@@ -108,25 +164,25 @@ pub async fn handle_claim(
     };
     
     // 5. Registrar en fact_accumulations (solo si ganó Lümis)
-    if request.lumis_won > 0 {
+    if lumis_won > 0 {
         let accum_key = format!("daily_game_{}_{}", user_id, today);
         
-        let accum_result = sqlx::query!(
+        let accum_result = sqlx::query(
             r#"
             INSERT INTO rewards.fact_accumulations 
             (user_id, accum_type, accum_key, dtype, quantity, date, accum_id)
             VALUES ($1, 'daily_game', $2, 'points', $3, NOW(), 10)
-            "#,
-            user_id as i32,
-            accum_key,
-            rust_decimal::Decimal::from(request.lumis_won)
+            "#
         )
+        .bind(user_id as i32)
+        .bind(accum_key)
+        .bind(rust_decimal::Decimal::from(lumis_won))
         .execute(&mut *tx)
         .await;
         
         match accum_result {
             Ok(_) => {
-                info!("✅ Recorded accumulation: {} Lümis for user {}", request.lumis_won, user_id);
+                info!("✅ Recorded accumulation: {} Lümis for user {}", lumis_won, user_id);
             },
             Err(e) => {
                 error!("❌ Failed to record accumulation: {}", e);
@@ -159,22 +215,29 @@ pub async fn handle_claim(
         }
     };
     
-    info!("💰 User {} new balance: {} Lümis (added: {})", user_id, new_balance, request.lumis_won);
+    info!("💰 User {} new balance: {} Lümis (added: {})", user_id, new_balance, lumis_won);
     
     // 8. Construir respuesta
-    let message = if request.lumis_won == 0 {
+    let max_prize = table.tiers.iter().map(|t| t.lumis).max().unwrap_or(0);
+    let message = if lumis_won == 0 {
         "¡Ups! Estrella vacía. Mejor suerte mañana. 🌟".to_string()
-    } else if request.lumis_won == 5 {
-        format!("¡Increíble! 🌟✨ ¡Encontraste la estrella dorada! +{} Lümis", request.lumis_won)
+    } else if lumis_won == max_prize && max_prize > 1 {
+        format!("¡Increíble! 🌟✨ ¡Encontraste la estrella dorada! +{} Lümis", lumis_won)
+    } else if lumis_won == 1 {
+        format!("¡Genial! +{} Lümi ganado. 🌟", lumis_won)
     } else {
-        format!("¡Genial! +{} Lümi ganado. 🌟", request.lumis_won)
+        format!("¡Genial! +{} Lümis ganados. 🌟", lumis_won)
     };
     
     Ok(Json(SimpleApiResponse::success_with_message(
         DailyGameClaimResponse {
-            lumis_added: request.lumis_won,
+            lumis_added: lumis_won,
             new_balance,
             play_id,
+            star_id: request.star_id,
+            layout,
+            nonce,
+            layout_commitment,
         },
         message,
     )))
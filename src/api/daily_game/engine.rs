@@ -0,0 +1,354 @@
+//! Resultado del juego diario decidido por el servidor
+//!
+//! Cada usuario tiene, por día, un tablero de 9 estrellas derivado con HMAC de un
+//! secreto del servidor: el cliente no puede predecirlo ni elegir el premio. El
+//! endpoint de status entrega un `game_token` firmado (usuario + fecha + tabla de
+//! premios vigente) y un `layout_commitment`. Al reclamar, el servidor verifica el
+//! token, calcula el premio de la estrella elegida y revela el tablero completo y
+//! el nonce para que la app pueda comprobar el commitment.
+
+use chrono::NaiveDate;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::sync::LazyLock;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const STAR_COUNT: usize = 9;
+pub const MAX_PRIZE_LUMIS: i32 = 100;
+pub const MAX_TIERS: usize = 10;
+
+/// Secreto del juego. Si no se define DAILY_GAME_SECRET se deriva de JWT_SECRET.
+static GAME_SECRET: LazyLock<String> = LazyLock::new(|| {
+    std::env::var("DAILY_GAME_SECRET")
+        .or_else(|_| std::env::var("JWT_SECRET").map(|s| format!("daily-game:{}", s)))
+        .expect("CRITICAL: DAILY_GAME_SECRET or JWT_SECRET must be set")
+});
+
+pub fn game_secret() -> &'static str {
+    &GAME_SECRET
+}
+
+// ======================================================================
+// TABLA DE PREMIOS
+// ======================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrizeTier {
+    pub lumis: i32,
+    pub weight: u32,
+}
+
+/// Tabla de premios vigente desde `effective_from` (hora de Panamá)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrizeTable {
+    /// 0 = tabla por defecto (sin configuración en BD)
+    pub config_id: i64,
+    pub effective_from: NaiveDate,
+    pub tiers: Vec<PrizeTier>,
+}
+
+impl PrizeTable {
+    /// Probabilidades originales del MVP: 40% vacía, 50% normal, 10% dorada
+    pub fn default_table() -> Self {
+        Self {
+            config_id: 0,
+            effective_from: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            tiers: vec![
+                PrizeTier { lumis: 0, weight: 40 },
+                PrizeTier { lumis: 1, weight: 50 },
+                PrizeTier { lumis: 5, weight: 10 },
+            ],
+        }
+    }
+
+    pub fn total_weight(&self) -> u64 {
+        self.tiers.iter().map(|t| t.weight as u64).sum()
+    }
+
+    /// Lümis esperados por jugada
+    pub fn expected_value(&self) -> f64 {
+        let total = self.total_weight();
+        if total == 0 {
+            return 0.0;
+        }
+        self.tiers
+            .iter()
+            .map(|t| t.lumis as f64 * t.weight as f64)
+            .sum::<f64>()
+            / total as f64
+    }
+
+    pub fn probability_of(&self, lumis: i32) -> f64 {
+        let total = self.total_weight();
+        if total == 0 {
+            return 0.0;
+        }
+        self.tiers
+            .iter()
+            .filter(|t| t.lumis == lumis)
+            .map(|t| t.weight as f64)
+            .sum::<f64>()
+            / total as f64
+    }
+
+    /// Premio para un número aleatorio uniforme
+    pub fn pick(&self, roll: u64) -> i32 {
+        let total = self.total_weight();
+        if total == 0 {
+            return 0;
+        }
+        let mut point = roll % total;
+        for tier in &self.tiers {
+            if point < tier.weight as u64 {
+                return tier.lumis;
+            }
+            point -= tier.weight as u64;
+        }
+        0
+    }
+}
+
+pub fn validate_tiers(tiers: &[PrizeTier]) -> Result<(), String> {
+    if tiers.is_empty() || tiers.len() > MAX_TIERS {
+        return Err(format!("Se requieren entre 1 y {} premios", MAX_TIERS));
+    }
+    if tiers.iter().any(|t| t.lumis < 0 || t.lumis > MAX_PRIZE_LUMIS) {
+        return Err(format!("Los premios deben estar entre 0 y {} Lümis", MAX_PRIZE_LUMIS));
+    }
+    if tiers.iter().all(|t| t.weight == 0) {
+        return Err("Al menos un premio debe tener peso mayor a 0".to_string());
+    }
+    let mut values: Vec<i32> = tiers.iter().map(|t| t.lumis).collect();
+    values.sort_unstable();
+    values.dedup();
+    if values.len() != tiers.len() {
+        return Err("Los valores de premio no pueden repetirse".to_string());
+    }
+    Ok(())
+}
+
+// ======================================================================
+// TABLERO, TOKEN Y COMMITMENT
+// ======================================================================
+
+fn hmac_bytes(secret: &str, message: &str) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+/// Índice de `star_0`..`star_8`
+pub fn star_index(star_id: &str) -> Option<usize> {
+    star_id
+        .strip_prefix("star_")
+        .and_then(|n| n.parse::<usize>().ok())
+        .filter(|n| *n < STAR_COUNT)
+}
+
+/// Premio de cada estrella para el usuario y día (independiente por estrella)
+pub fn derive_layout(secret: &str, user_id: i64, date: NaiveDate, table: &PrizeTable) -> Vec<i32> {
+    (0..STAR_COUNT)
+        .map(|i| {
+            let digest = hmac_bytes(
+                secret,
+                &format!("layout:{}:{}:{}:{}", user_id, date, table.config_id, i),
+            );
+            let roll = u64::from_be_bytes(digest[..8].try_into().unwrap());
+            table.pick(roll)
+        })
+        .collect()
+}
+
+/// Nonce revelado después del claim para verificar el commitment
+pub fn layout_nonce(secret: &str, user_id: i64, date: NaiveDate) -> String {
+    hex::encode(&hmac_bytes(secret, &format!("nonce:{}:{}", user_id, date))[..16])
+}
+
+/// sha256("l0,l1,...,l8:nonce") en hex
+pub fn layout_commitment(layout: &[i32], nonce: &str) -> String {
+    let joined = layout.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(",");
+    hex::encode(Sha256::digest(format!("{}:{}", joined, nonce).as_bytes()))
+}
+
+fn token_signature(secret: &str, user_id: i64, date: NaiveDate, config_id: i64) -> String {
+    hex::encode(&hmac_bytes(secret, &format!("token:{}:{}:{}", user_id, date, config_id))[..16])
+}
+
+/// `v1.<fecha>.<config_id>.<firma>`
+pub fn issue_game_token(secret: &str, user_id: i64, date: NaiveDate, config_id: i64) -> String {
+    format!("v1.{}.{}.{}", date, config_id, token_signature(secret, user_id, date, config_id))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameToken {
+    pub date: NaiveDate,
+    pub config_id: i64,
+}
+
+/// Verifica la firma y devuelve fecha y tabla de premios del token
+pub fn verify_game_token(secret: &str, user_id: i64, token: &str) -> Result<GameToken, String> {
+    let parts: Vec<&str> = token.trim().split('.').collect();
+    if parts.len() != 4 || parts[0] != "v1" {
+        return Err("Formato de game_token inválido".to_string());
+    }
+    let date = NaiveDate::parse_from_str(parts[1], "%Y-%m-%d").map_err(|_| "Fecha de game_token inválida".to_string())?;
+    let config_id = parts[2].parse::<i64>().map_err(|_| "config de game_token inválida".to_string())?;
+
+    let expected = token_signature(secret, user_id, date, config_id);
+    // Comparación en tiempo constante
+    let valid = expected.len() == parts[3].len()
+        && expected
+            .bytes()
+            .zip(parts[3].bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0;
+    if !valid {
+        return Err("Firma de game_token inválida".to_string());
+    }
+
+    Ok(GameToken { date, config_id })
+}
+
+// ======================================================================
+// PERSISTENCIA
+// ======================================================================
+
+fn table_from_row(config_id: i64, effective_from: NaiveDate, tiers: serde_json::Value) -> PrizeTable {
+    match serde_json::from_value::<Vec<PrizeTier>>(tiers) {
+        Ok(tiers) if validate_tiers(&tiers).is_ok() => PrizeTable { config_id, effective_from, tiers },
+        _ => {
+            tracing::error!("❌ Invalid daily game prize config {}, using default table", config_id);
+            PrizeTable::default_table()
+        }
+    }
+}
+
+/// Tabla vigente para una fecha (la de mayor effective_from <= fecha)
+pub async fn prize_table_for_date(pool: &PgPool, date: NaiveDate) -> Result<PrizeTable, sqlx::Error> {
+    let row = sqlx::query_as::<_, (i64, NaiveDate, serde_json::Value)>(
+        r#"
+        SELECT config_id, effective_from, tiers
+        FROM rewards.daily_game_prize_config
+        WHERE effective_from <= $1
+        ORDER BY effective_from DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(date)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row
+        .map(|(id, from, tiers)| table_from_row(id, from, tiers))
+        .unwrap_or_else(PrizeTable::default_table))
+}
+
+pub async fn prize_table_by_id(pool: &PgPool, config_id: i64) -> Result<Option<PrizeTable>, sqlx::Error> {
+    if config_id == 0 {
+        return Ok(Some(PrizeTable::default_table()));
+    }
+    let row = sqlx::query_as::<_, (i64, NaiveDate, serde_json::Value)>(
+        "SELECT config_id, effective_from, tiers FROM rewards.daily_game_prize_config WHERE config_id = $1",
+    )
+    .bind(config_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(id, from, tiers)| table_from_row(id, from, tiers)))
+}
+
+pub async fn list_prize_tables(pool: &PgPool) -> Result<Vec<PrizeTable>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (i64, NaiveDate, serde_json::Value)>(
+        "SELECT config_id, effective_from, tiers FROM rewards.daily_game_prize_config ORDER BY effective_from",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|(id, from, tiers)| table_from_row(id, from, tiers)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "test-secret";
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+    }
+
+    #[test]
+    fn test_layout_is_deterministic_and_per_user() {
+        let table = PrizeTable::default_table();
+        let a = derive_layout(SECRET, 42, date(), &table);
+        assert_eq!(a.len(), STAR_COUNT);
+        assert_eq!(a, derive_layout(SECRET, 42, date(), &table));
+        assert!(a.iter().all(|l| [0, 1, 5].contains(l)));
+
+        let boards: Vec<Vec<i32>> = (0..20).map(|u| derive_layout(SECRET, u, date(), &table)).collect();
+        assert!(boards.iter().any(|b| b != &a));
+    }
+
+    #[test]
+    fn test_layout_follows_probability_table() {
+        let table = PrizeTable::default_table();
+        let mut counts = std::collections::HashMap::new();
+        let mut plays = 0;
+        for user in 0..4000 {
+            for lumis in derive_layout(SECRET, user, date(), &table) {
+                *counts.entry(lumis).or_insert(0) += 1;
+                plays += 1;
+            }
+        }
+        let golden = *counts.get(&5).unwrap_or(&0) as f64 / plays as f64;
+        let empty = *counts.get(&0).unwrap_or(&0) as f64 / plays as f64;
+        assert!((golden - 0.10).abs() < 0.01, "golden rate {}", golden);
+        assert!((empty - 0.40).abs() < 0.02, "empty rate {}", empty);
+    }
+
+    #[test]
+    fn test_expected_value_and_pick() {
+        let table = PrizeTable::default_table();
+        assert!((table.expected_value() - 1.0).abs() < 1e-9);
+        assert_eq!(table.pick(0), 0);
+        assert_eq!(table.pick(40), 1);
+        assert_eq!(table.pick(95), 5);
+        assert_eq!(table.pick(100), 0);
+    }
+
+    #[test]
+    fn test_game_token_round_trip_and_tamper() {
+        let token = issue_game_token(SECRET, 7, date(), 3);
+        assert_eq!(verify_game_token(SECRET, 7, &token).unwrap(), GameToken { date: date(), config_id: 3 });
+        assert!(verify_game_token(SECRET, 8, &token).is_err());
+        assert!(verify_game_token(SECRET, 7, &token.replace(".3.", ".4.")).is_err());
+        assert!(verify_game_token("other", 7, &token).is_err());
+        assert!(verify_game_token(SECRET, 7, "garbage").is_err());
+    }
+
+    #[test]
+    fn test_commitment_matches_revealed_layout() {
+        let table = PrizeTable::default_table();
+        let layout = derive_layout(SECRET, 1, date(), &table);
+        let nonce = layout_nonce(SECRET, 1, date());
+        let commitment = layout_commitment(&layout, &nonce);
+        assert_eq!(commitment.len(), 64);
+        let mut tampered = layout.clone();
+        tampered[0] = if tampered[0] == 5 { 0 } else { 5 };
+        assert_ne!(commitment, layout_commitment(&tampered, &nonce));
+    }
+
+    #[test]
+    fn test_validate_tiers() {
+        assert!(validate_tiers(&PrizeTable::default_table().tiers).is_ok());
+        assert!(validate_tiers(&[]).is_err());
+        assert!(validate_tiers(&[PrizeTier { lumis: 0, weight: 0 }]).is_err());
+        assert!(validate_tiers(&[PrizeTier { lumis: 1, weight: 1 }, PrizeTier { lumis: 1, weight: 2 }]).is_err());
+        assert!(validate_tiers(&[PrizeTier { lumis: 500, weight: 1 }]).is_err());
+        assert_eq!(star_index("star_8"), Some(8));
+        assert_eq!(star_index("star_9"), None);
+    }
+}
@@ -3,10 +3,14 @@
 /// Endpoints:
 /// - POST /v4/daily-game/claim - Reclamar recompensa diaria
 /// - GET /v4/daily-game/status - Verificar estado del juego
+/// - GET/PUT /v4/daily-game/admin/prize-config - Tabla de premios (admin)
+/// - GET /v4/daily-game/admin/stats - Pago real vs esperado (admin)
 
 pub mod templates;
 pub mod claim;
 pub mod status;
+pub mod engine;
+pub mod admin;
 
 // Re-exports para facilitar uso
 pub use templates::{
@@ -14,6 +18,7 @@ pub use templates::{
     DailyGameClaimResponse,
     DailyGameStatusResponse,
     DailyGameStats,
+    DailyGamePrize,
 };
 
 pub use claim::handle_claim;
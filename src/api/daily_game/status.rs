@@ -9,7 +9,8 @@ use tracing::{info, error};
 
 use std::sync::Arc;
 use crate::{
-    api::daily_game::templates::{DailyGameStatusResponse, DailyGameStats},
+    api::daily_game::engine,
+    api::daily_game::templates::{DailyGameStatusResponse, DailyGameStats, DailyGamePrize},
     api::common::SimpleApiResponse,
     state::AppState,
     middleware::CurrentUser,
@@ -22,6 +23,7 @@ use crate::{
 /// - Última fecha de juego
 /// - Recompensa de hoy (si ya jugó)
 /// - Estadísticas básicas
+/// - Premios posibles hoy y, si puede jugar, el `game_token` para el claim
 pub async fn handle_status(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
@@ -98,6 +100,37 @@ pub async fn handle_status(
         None
     };
     
+    // Tabla de premios vigente hoy
+    let table = match engine::prize_table_for_date(&state.db_pool, today).await {
+        Ok(table) => table,
+        Err(e) => {
+            error!("❌ Failed to load daily game prize table: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(SimpleApiResponse::<()>::error("Failed to fetch status")),
+            ));
+        }
+    };
+    
+    let prizes = table.tiers.iter()
+        .map(|tier| DailyGamePrize {
+            lumis: tier.lumis,
+            probability: table.probability_of(tier.lumis),
+        })
+        .collect();
+    
+    let (game_token, layout_commitment) = if can_play {
+        let secret = engine::game_secret();
+        let layout = engine::derive_layout(secret, user_id, today, &table);
+        let nonce = engine::layout_nonce(secret, user_id, today);
+        (
+            Some(engine::issue_game_token(secret, user_id, today, table.config_id)),
+            Some(engine::layout_commitment(&layout, &nonce)),
+        )
+    } else {
+        (None, None)
+    };
+    
     info!(
         "📊 User {} status: can_play={}, has_played={}, last_played={:?}, stats={:?}",
         user_id, can_play, has_played_today, query_result.last_played, stats
@@ -109,5 +142,8 @@ pub async fn handle_status(
         last_played_date: query_result.last_played,
         todays_reward,
         stats,
+        game_token,
+        layout_commitment,
+        prizes,
     })))
 }
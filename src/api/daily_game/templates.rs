@@ -11,8 +11,13 @@ pub struct DailyGameClaimRequest {
     /// ID de la estrella seleccionada (star_0 a star_8)
     pub star_id: String,
     
-    /// Lümis ganados: 0 (vacía), 1 (normal), o 5 (dorada)
-    pub lumis_won: i32,
+    /// Token firmado entregado por GET /status (recomendado)
+    #[serde(default)]
+    pub game_token: Option<String>,
+    
+    /// DEPRECATED: ignorado. El premio lo calcula el servidor.
+    #[serde(default)]
+    pub lumis_won: Option<i32>,
 }
 
 // ============================================================================
@@ -30,6 +35,17 @@ pub struct DailyGameClaimResponse {
     
     /// ID de la jugada registrada
     pub play_id: i64,
+    
+    /// Estrella elegida
+    pub star_id: String,
+    
+    /// Premio de cada estrella (star_0..star_8), revelado tras jugar
+    pub layout: Vec<i32>,
+    
+    /// Nonce para verificar: sha256("l0,...,l8:nonce") == layout_commitment
+    pub nonce: String,
+    
+    pub layout_commitment: String,
 }
 
 /// Estado del juego diario para el usuario
@@ -52,6 +68,24 @@ pub struct DailyGameStatusResponse {
     /// Estadísticas básicas
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stats: Option<DailyGameStats>,
+    
+    /// Token firmado para enviar en el claim (solo si puede jugar hoy)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_token: Option<String>,
+    
+    /// Compromiso del tablero de hoy (sha256), verificable después del claim
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout_commitment: Option<String>,
+    
+    /// Premios posibles hoy y su probabilidad
+    pub prizes: Vec<DailyGamePrize>,
+}
+
+/// Premio posible y su probabilidad
+#[derive(Debug, Serialize)]
+pub struct DailyGamePrize {
+    pub lumis: i32,
+    pub probability: f64,
 }
 
 /// Estadísticas básicas del juego diario
@@ -72,16 +106,9 @@ pub struct DailyGameStats {
 // ============================================================================
 
 impl DailyGameClaimRequest {
-    /// Valida que los valores del request sean correctos
+    /// Valida que los valores del request sean correctos.
+    /// `lumis_won` ya no se valida: el servidor decide el premio.
     pub fn validate(&self) -> Result<(), String> {
-        // Validar lumis_won
-        if ![0, 1, 5].contains(&self.lumis_won) {
-            return Err(format!(
-                "Invalid lumis_won value: {}. Must be 0, 1, or 5",
-                self.lumis_won
-            ));
-        }
-        
         // Validar formato de star_id
         if !self.star_id.starts_with("star_") {
             return Err(format!(
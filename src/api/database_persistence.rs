@@ -5,6 +5,7 @@ use chrono_tz::America::Panama;
 
 use crate::api::webscraping::{InvoiceHeader, InvoiceDetail, InvoicePayment, ScrapingResult};
 use crate::api::templates::url_processing_templates::ProcessUrlResponse;
+use crate::services::event_bus_service::{DomainEvent, EventBus};
 
 // ============================================================================
 // DATE UTILITIES
@@ -179,6 +180,17 @@ pub async fn persist_scraped_data(
         return Err(ProcessUrlResponse::error("Error al guardar pagos de factura"));
     }
 
+    // Evento de dominio en la misma transacción (leaderboards, referidos, misiones...)
+    let saved = DomainEvent::InvoiceSaved {
+        user_id: header.user_id,
+        cufe: header.cufe.clone(),
+        source: header.origin.clone(),
+    };
+    if let Err(e) = EventBus::publish(&mut *tx, &saved).await {
+        log_error!("Failed to publish invoice.saved: {}", e);
+        return Err(ProcessUrlResponse::error("Error de base de datos"));
+    }
+
     if let Err(e) = tx.commit().await {
         log_error!("Failed to commit transaction: {}", e);
         return Err(ProcessUrlResponse::error("Error al confirmar transacción"));
@@ -49,7 +49,7 @@ pub async fn check_email_availability(
         .bind(&request.email)
         .fetch_optional(&state.db_pool)
         .await
-        .map_err(|e| ApiError::database_error(&format!("Failed to check email: {}", e)))?;
+        .map_err(|e| ApiError::database_failure("Failed to check email", e))?;
 
     let exists = exists_result.is_some();
     let message = if exists {
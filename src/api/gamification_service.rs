@@ -1,7 +1,10 @@
 use sqlx::{PgPool, Row};
-use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
+use crate::domains::gamification::referral_service::{ReferralService, SignupContext};
+use crate::domains::rewards::accumulation_rules::AccumulationRuleEngine;
+use crate::services::event_bus_service::{DomainEvent, EventBus};
+
 /// Estructura simplificada para la respuesta de Lumis
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LumisResult {
@@ -11,60 +14,41 @@ pub struct LumisResult {
 
 /// Acredita Lumis al usuario después de procesar una factura exitosamente
 /// 
-/// Este flujo replica EXACTAMENTE la implementación de Python/WhatsApp:
-/// 1. Consulta la regla de acumulación activa (id=0) desde rewards.dim_accumulations
-/// 2. Inserta registro en rewards.fact_accumulations (trigger automático actualiza balance)
+/// 1. Evalúa las reglas de acumulación activas (`rule_scope = 'invoice'`) de
+///    rewards.dim_accumulations e inserta una fila en rewards.fact_accumulations
+///    por cada regla aplicada (trigger automático actualiza balance)
+/// 2. Publica `lumis.credited` (los efectos de la factura en sí —leaderboards,
+///    referido, misiones, campañas de comercios— los dispara `invoice.saved`
+///    al guardarla)
 /// 3. Consulta el balance actualizado desde rewards.fact_balance_points
 pub async fn credit_lumis_for_invoice(
     pool: &PgPool,
     user_id: i64,
     cufe: &str,
 ) -> Result<LumisResult, sqlx::Error> {
-    // 1. Consultar la regla de acumulación activa (ID = 0)
-    let rule = sqlx::query_as::<_, (i32, String, i32)>(
-        r#"
-        SELECT id, name, points
-        FROM rewards.dim_accumulations
-        WHERE id = 0 
-        AND valid_from <= NOW() 
-        AND valid_to >= NOW()
-        "#
-    )
-    .fetch_optional(pool)
-    .await?;
-    
-    let (rule_id, rule_name, points) = match rule {
-        Some((id, name, pts)) => {
-            tracing::info!("�� Found accumulation rule: '{}' (id={}) with {} Lumis", name, id, pts);
-            (id, name, pts)
-        },
-        None => {
-            tracing::warn!("⚠️ No active accumulation rule found (id=0)");
-            return Err(sqlx::Error::RowNotFound);
-        }
-    };
+    // 1. Motor de reglas: prioridad, stacking y exclusividad
+    let credited = AccumulationRuleEngine::new(pool.clone())
+        .credit_invoice(user_id, cufe)
+        .await?;
     
-    // 2. Registrar acumulación en rewards.fact_accumulations
-    // El TRIGGER de PostgreSQL actualizará automáticamente rewards.fact_balance_points
-    let current_time = Utc::now().naive_utc();
-    
-    sqlx::query(
-        r#"
-        INSERT INTO rewards.fact_accumulations 
-        (user_id, accum_type, accum_key, dtype, quantity, date, accum_id)
-        VALUES ($1, $2, $3, 'points', $4, $5, $6)
-        "#
-    )
-    .bind(user_id)
-    .bind(&rule_name)
-    .bind(cufe)
-    .bind(points)
-    .bind(current_time)
-    .bind(rule_id)
-    .execute(pool)
-    .await?;
+    for applied in credited.evaluation.applied() {
+        tracing::info!(
+            "✅ Rule '{}' (id={}) granted {} Lumis to user {} (CUFE: {})",
+            applied.name, applied.rule_id, applied.points, user_id, cufe
+        );
+    }
     
-    tracing::info!("✅ Recorded accumulation for user {} - {} Lumis (CUFE: {})", user_id, points, cufe);
+    // 2. Evento de dominio para notificaciones y analítica
+    let lumis_earned = credited.total_points;
+    if lumis_earned > 0 {
+        let event = DomainEvent::LumisCredited {
+            user_id,
+            lumis: lumis_earned,
+            source: "invoice".to_string(),
+            reference: Some(cufe.to_string()),
+        };
+        EventBus::publish_best_effort(pool, &event).await;
+    }
     
     // 3. Consultar el balance actualizado (el trigger ya lo actualizó)
     let new_balance = get_user_balance(pool, user_id).await?;
@@ -72,11 +56,23 @@ pub async fn credit_lumis_for_invoice(
     tracing::info!("💰 New balance for user {}: {} Lumis", user_id, new_balance);
     
     Ok(LumisResult {
-        lumis_earned: points,
+        lumis_earned,
         lumis_balance: new_balance,
     })
 }
 
+/// Atribuye un registro nuevo a un código de referido. Un código inválido o
+/// cualquier error se registra y no afecta el registro.
+pub async fn attribute_signup_referral(pool: &PgPool, user_id: i64, referral_code: &str, ctx: SignupContext) {
+    match ReferralService::new(pool.clone()).attribute(user_id as i32, referral_code, &ctx).await {
+        Ok(attribution) => tracing::info!(
+            "🤝 Signup {} attributed to referrer {} via {} (status {})",
+            user_id, attribution.referrer_user_id, ctx.source, attribution.status
+        ),
+        Err(e) => tracing::warn!("⚠️ Referral code '{}' not applied to user {}: {}", referral_code, user_id, e),
+    }
+}
+
 /// Obtiene el balance actual de Lumis del usuario desde rewards.fact_balance_points
 pub async fn get_user_balance(pool: &PgPool, user_id: i64) -> Result<i32, sqlx::Error> {
     let result = sqlx::query(
@@ -1,7 +1,7 @@
 use axum::{
-    extract::{Query, State, Extension},
+    extract::{Path, Query, State, Extension},
     Json,
-    routing::{get, post},
+    routing::{get, post, put},
     Router,
 };
 use serde::{Deserialize, Serialize};
@@ -9,11 +9,23 @@ use sqlx::FromRow;
 use std::sync::Arc;
 use uuid::Uuid;
 use chrono::{DateTime, Utc, NaiveDate};
-use rust_decimal::Decimal;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 
+use crate::shared::admin::is_admin;
 use crate::{
     middleware::CurrentUser,
     api::common::{ApiError, ApiResponse},
+    shared::i18n::Text,
+    domains::gamification::mission_service::{
+        self, MissionDefinition, MissionError, MissionPreview, MissionService, StoredMission,
+    },
+    domains::gamification::streak_service::{
+        self, FreezePurchase, StreakError, StreakOverview, StreakRestore, StreakService,
+    },
+    services::event_bus_service::{DomainEvent, EventBus},
+    services::leaderboard_service::{
+        self, AddFriendOutcome, ArchivedStandings, Friend, LeaderboardPage, LeaderboardService, MyRank, Period, Scope,
+    },
     AppState,
 };
 
@@ -34,6 +46,19 @@ const VALID_ACTIONS: &[&str] = &[
     "first_redemption",
 ];
 
+/// Actions recorded by the server only (clients cannot self-report them)
+const SERVER_ONLY_ACTIONS: &[&str] = &[
+    "referral_complete", // referral_service, when a referral qualifies
+    "invoice_upload",    // achievements subscriber, on invoice.saved
+    "survey_complete",   // achievements subscriber, on survey.completed
+];
+
+/// Server-only actions that older app versions still send after each upload or
+/// survey. Deprecated: until 2027-01-18 /track answers 200 without crediting
+/// anything (the server already counted them); afterwards they return 400 like
+/// the rest of SERVER_ONLY_ACTIONS.
+const LEGACY_CLIENT_ACTIONS: &[&str] = &["invoice_upload", "survey_complete"];
+
 /// Valid channel types
 const VALID_CHANNELS: &[&str] = &[
     "mobile_app",
@@ -46,13 +71,17 @@ const VALID_CHANNELS: &[&str] = &[
 /// Maximum metadata JSON size in bytes (10KB)
 const MAX_METADATA_SIZE: usize = 10 * 1024;
 
+/// Streak history entries shown in the dashboard / max per request
+const DASHBOARD_STREAK_HISTORY: i64 = 14;
+const MAX_STREAK_HISTORY: i64 = 100;
+
 // ============================================================================
 // REQUEST/RESPONSE MODELS
 // ============================================================================
 
 #[derive(Debug, Deserialize)]
 pub struct TrackActionRequest {
-    pub action: String,  // 'daily_login', 'invoice_upload', 'survey_complete'
+    pub action: String,  // 'daily_login', 'profile_complete', 'share_invoice'...
     #[serde(default = "default_channel")]
     pub channel: String,  // 'mobile_app', 'whatsapp', 'web_app'
     #[serde(default)]
@@ -61,39 +90,44 @@ pub struct TrackActionRequest {
 
 impl TrackActionRequest {
     /// Validate the request fields
-    pub fn validate(&self) -> Result<(), String> {
+    pub fn validate(&self) -> Result<(), Text> {
         // Validate action
         if !VALID_ACTIONS.contains(&self.action.as_str()) {
-            return Err(format!(
-                "Invalid action '{}'. Valid actions: {:?}",
-                self.action, VALID_ACTIONS
-            ));
+            return Err(Text::new("api.track.invalid_action")
+                .with("action", &self.action)
+                .with("valid", VALID_ACTIONS.join(", ")));
+        }
+        if SERVER_ONLY_ACTIONS.contains(&self.action.as_str()) && !self.is_legacy_action() {
+            return Err(Text::new("api.track.server_only_action").with("action", &self.action));
         }
         
         // Validate channel
         if !VALID_CHANNELS.contains(&self.channel.as_str()) {
-            return Err(format!(
-                "Invalid channel '{}'. Valid channels: {:?}",
-                self.channel, VALID_CHANNELS
-            ));
+            return Err(Text::new("api.track.invalid_channel")
+                .with("channel", &self.channel)
+                .with("valid", VALID_CHANNELS.join(", ")));
         }
         
         // Validate metadata size (prevent DoS with huge JSON)
         let metadata_str = self.metadata.to_string();
         if metadata_str.len() > MAX_METADATA_SIZE {
-            return Err(format!(
-                "Metadata too large ({} bytes). Maximum allowed: {} bytes",
-                metadata_str.len(), MAX_METADATA_SIZE
-            ));
+            return Err(Text::new("api.track.metadata_too_large")
+                .with("size", metadata_str.len())
+                .with("max", MAX_METADATA_SIZE));
         }
         
         // Validate metadata is object or null (not array, string, etc.)
         if !self.metadata.is_null() && !self.metadata.is_object() {
-            return Err("Metadata must be a JSON object or null".to_string());
+            return Err(Text::new("api.track.metadata_not_object"));
         }
         
         Ok(())
     }
+
+    /// Deprecated client report of an action the server records on its own
+    pub fn is_legacy_action(&self) -> bool {
+        LEGACY_CLIENT_ACTIONS.contains(&self.action.as_str())
+    }
 }
 
 fn default_channel() -> String {
@@ -135,6 +169,14 @@ pub struct UserDashboard {
     pub recent_activity: Option<serde_json::Value>,
 }
 
+/// Dashboard con el estado de protección de racha (congeladores, recuperación, historial)
+#[derive(Debug, Serialize)]
+pub struct DashboardResponse {
+    #[serde(flatten)]
+    pub dashboard: UserDashboard,
+    pub streak_protection: Option<StreakOverview>,
+}
+
 #[derive(Debug, Serialize, FromRow)]
 pub struct Mission {
     pub mission_code: Option<String>,
@@ -223,7 +265,26 @@ pub async fn track_action(
     
     // Validate request using the new validation method
     if let Err(validation_error) = request.validate() {
-        return Err(ApiError::validation_error(&validation_error));
+        return Err(ApiError::validation_error(&validation_error.to_string()));
+    }
+    
+    if request.is_legacy_action() {
+        tracing::info!(
+            "Ignoring deprecated /track action '{}' from user {} (channel {})",
+            request.action, current_user.user_id, request.channel
+        );
+        return legacy_action_response(&state, current_user.user_id, start_time).await;
+    }
+    
+    // El login diario se cuenta en Rust (congeladores + ruptura recuperable);
+    // update_daily_login_streak dentro de track_user_action queda como no-op.
+    if request.action == "daily_login" {
+        if let Err(e) = StreakService::new(state.db_pool.clone())
+            .record_daily_login(current_user.user_id as i32)
+            .await
+        {
+            tracing::warn!("Failed to record daily login streak for user {}: {}", current_user.user_id, e);
+        }
     }
     
     // Call the database function
@@ -248,9 +309,16 @@ pub async fn track_action(
     .await
     .map_err(|e| {
         tracing::error!("Failed to track action: {:?}", e);
-        ApiError::database_error("Failed to track action")
+        ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
     })?;
     
+    let tracked = DomainEvent::ActionTracked {
+        user_id: current_user.user_id,
+        action: request.action.clone(),
+        channel: request.channel.clone(),
+    };
+    EventBus::publish_best_effort(&state.db_pool, &tracked).await;
+    
     // Get user's updated total lumis and level info
     let user_info = sqlx::query!(
         r#"
@@ -267,7 +335,7 @@ pub async fn track_action(
     )
     .fetch_one(&state.db_pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Failed to fetch user info: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Failed to fetch user info", e))?;
     
     let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
     
@@ -286,12 +354,51 @@ pub async fn track_action(
     Ok(Json(ApiResponse::success(response, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
 }
 
+/// Respuesta de /track para LEGACY_CLIENT_ACTIONS: estado actual, nada acreditado
+async fn legacy_action_response(
+    state: &AppState,
+    user_id: i64,
+    start_time: chrono::DateTime<Utc>,
+) -> ResponseJson<GamificationResponse> {
+    let user_info = sqlx::query_as::<_, (i32, i32, String)>(
+        r#"
+        SELECT 
+            COALESCE(us.total_xp, 0),
+            COALESCE(us.current_level_id, 1),
+            COALESCE(l.level_name, 'Chispa Lüm')
+        FROM public.dim_users u
+        LEFT JOIN gamification.user_status us ON u.id = us.user_id
+        LEFT JOIN gamification.dim_user_levels l ON us.current_level_id = l.level_id
+        WHERE u.id = $1
+        "#,
+    )
+    .bind(user_id as i32)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::database_failure("Failed to fetch user info", e))?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    let response = GamificationResponse {
+        lumis_earned: 0,
+        total_lumis: user_info.0,
+        xp_earned: 0,
+        current_level: user_info.1,
+        level_name: user_info.2,
+        streaks: serde_json::json!({}),
+        achievements_unlocked: serde_json::json!([]),
+        active_events: serde_json::json!([]),
+        message: Some("Action already recorded by the server".to_string()),
+    };
+
+    Ok(Json(ApiResponse::success(response, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
 /// Get complete gamification dashboard for user
 #[axum::debug_handler]
 pub async fn get_dashboard(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
-) -> ResponseJson<UserDashboard> {
+) -> ResponseJson<DashboardResponse> {
     let start_time = Utc::now();
 
     // Refrescar rachas de forma best-effort para que el frontend vea datos actuales
@@ -300,12 +407,9 @@ pub async fn get_dashboard(
     // - daily_login: se actualiza 1 vez por día (idempotente si se llama varias veces)
     // - consistent_month: recalcula basado en facturas
     let user_id = current_user.user_id as i32;
+    let streaks = StreakService::new(state.db_pool.clone());
 
-    if let Err(e) = sqlx::query("SELECT gamification.update_daily_login_streak($1)")
-        .bind(user_id)
-        .fetch_optional(&state.db_pool)
-        .await
-    {
+    if let Err(e) = streaks.record_daily_login(user_id).await {
         tracing::warn!("Failed to refresh daily_login streak for user {}: {}", user_id, e);
     }
 
@@ -347,12 +451,21 @@ pub async fn get_dashboard(
     )
     .fetch_optional(&state.db_pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Failed to fetch dashboard: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Failed to fetch dashboard", e))?;
     
     let dashboard = dashboard.ok_or_else(|| {
-        ApiError::not_found("User not found or inactive")
+        ApiError::localized("NOT_FOUND", "api.not_found.user", &[])
     })?;
     
+    let streak_protection = match streaks.overview(user_id, DASHBOARD_STREAK_HISTORY).await {
+        Ok(overview) => Some(overview),
+        Err(e) => {
+            tracing::warn!("Failed to load streak overview for user {}: {}", user_id, e);
+            None
+        }
+    };
+    let dashboard = DashboardResponse { dashboard, streak_protection };
+    
     let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
     
     Ok(Json(ApiResponse::success(dashboard, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
@@ -366,7 +479,7 @@ pub async fn get_missions(
 ) -> ResponseJson<Vec<Mission>> {
     let start_time = Utc::now();
     
-    let missions = sqlx::query_as!(
+    let mut missions = sqlx::query_as!(
         Mission,
         r#"
         SELECT 
@@ -400,7 +513,32 @@ pub async fn get_missions(
     )
     .fetch_all(&state.db_pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Failed to fetch missions: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Failed to fetch missions", e))?;
+    
+    // Misiones declarativas (periodo actual) con el mismo formato
+    let declarative = MissionService::new(state.db_pool.clone())
+        .user_missions(current_user.user_id as i32)
+        .await
+        .map_err(mission_error)?;
+    missions.extend(declarative.into_iter().map(|m| {
+        let progress_percentage = if m.target > Decimal::ZERO {
+            (m.progress / m.target * Decimal::from(100)).min(Decimal::from(100)).to_f64()
+        } else {
+            Some(0.0)
+        };
+        Mission {
+            mission_code: Some(m.mission_code),
+            mission_name: Some(m.name),
+            mission_type: Some(format!("mission_{}", m.recurrence.as_str())),
+            description: m.description,
+            current_progress: Some(mission_service::progress_as_i32(m.progress)),
+            target_count: Some(mission_service::progress_as_i32(m.target)),
+            reward_lumis: Some(m.reward_lumis),
+            due_date: m.ends_at.map(|end| end.with_timezone(&chrono_tz::America::Panama).date_naive()),
+            status: Some(m.status),
+            progress_percentage,
+        }
+    }));
     
     let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
     
@@ -442,7 +580,7 @@ pub async fn get_events(
     )
     .fetch_all(&state.db_pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Failed to fetch events: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Failed to fetch events", e))?;
     
     let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
     
@@ -490,7 +628,7 @@ pub async fn get_achievements(
     )
     .fetch_all(&state.db_pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Failed to fetch achievements: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Failed to fetch achievements", e))?;
     
     let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
     
@@ -533,7 +671,7 @@ pub async fn get_mechanics_info(
     )
     .fetch_all(&state.db_pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Failed to fetch mechanics: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Failed to fetch mechanics", e))?;
     
     let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
     
@@ -574,13 +712,486 @@ pub async fn get_leaderboard(
     )
     .fetch_all(&state.db_pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Failed to fetch leaderboard: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Failed to fetch leaderboard", e))?;
     
     let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
     
     Ok(Json(ApiResponse::success(leaderboard, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
 }
 
+/// Streak status: freezes, restore offer and history
+#[axum::debug_handler]
+pub async fn get_streaks(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<StreakHistoryQuery>,
+) -> ResponseJson<StreakOverview> {
+    let start_time = Utc::now();
+    
+    let limit = params.limit.unwrap_or(DASHBOARD_STREAK_HISTORY).clamp(1, MAX_STREAK_HISTORY);
+    
+    let overview = StreakService::new(state.db_pool.clone())
+        .overview(current_user.user_id as i32, limit)
+        .await
+        .map_err(streak_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(overview, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Buy streak freezes with Lümis
+#[axum::debug_handler]
+pub async fn purchase_streak_freezes(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<PurchaseFreezesRequest>,
+) -> ResponseJson<FreezePurchase> {
+    let start_time = Utc::now();
+    
+    let purchase = StreakService::new(state.db_pool.clone())
+        .purchase_freezes(current_user.user_id as i32, request.quantity)
+        .await
+        .map_err(streak_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(purchase, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Pay Lümis to restore a streak broken in the last 48h
+#[axum::debug_handler]
+pub async fn restore_streak(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> ResponseJson<StreakRestore> {
+    let start_time = Utc::now();
+    
+    let restore = StreakService::new(state.db_pool.clone())
+        .restore_streak(current_user.user_id as i32)
+        .await
+        .map_err(streak_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(restore, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Grant streak freezes to a user (admin / mission rewards)
+#[axum::debug_handler]
+pub async fn admin_grant_streak_freezes(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<GrantFreezesRequest>,
+) -> ResponseJson<GrantFreezesResponse> {
+    let start_time = Utc::now();
+    
+    if !is_admin(current_user.user_id) {
+        tracing::warn!("User {} attempted to grant streak freezes", current_user.user_id);
+        return Err(ApiError::forbidden());
+    }
+    
+    let reason = request.reason.as_deref().unwrap_or(streak_service::FREEZE_REASON_ADMIN);
+    if ![streak_service::FREEZE_REASON_ADMIN, streak_service::FREEZE_REASON_MISSION].contains(&reason) {
+        return Err(ApiError::localized("VALIDATION_ERROR", "api.streak.invalid_freeze_reason", &[]));
+    }
+    
+    let reference = request.reference.clone().unwrap_or_else(|| format!("admin:{}", current_user.user_id));
+    let granted = StreakService::new(state.db_pool.clone())
+        .grant_freezes(request.user_id, request.quantity, reason, Some(&reference))
+        .await
+        .map_err(streak_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(
+        GrantFreezesResponse { user_id: request.user_id, granted },
+        Uuid::new_v4().to_string(),
+        Some(execution_time.try_into().unwrap()),
+        false,
+    )))
+}
+
+/// Period leaderboard (daily, weekly, monthly, all_time) for global, city or friends
+#[axum::debug_handler]
+pub async fn get_period_leaderboard(
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<PeriodLeaderboardQuery>,
+) -> ResponseJson<LeaderboardPage> {
+    let start_time = Utc::now();
+    
+    let service = leaderboards()?;
+    let user_id = current_user.user_id as i32;
+    let period = parse_period(params.period.as_deref())?;
+    let scope = resolve_scope(&service, user_id, params.scope.as_deref()).await?;
+    
+    let page = service
+        .top(period, &scope, user_id, params.limit.unwrap_or(50), params.offset.unwrap_or(0))
+        .await
+        .map_err(leaderboard_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(page, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// My position in a period leaderboard with the users around me
+#[axum::debug_handler]
+pub async fn get_my_leaderboard_rank(
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<MyRankQuery>,
+) -> ResponseJson<MyRank> {
+    let start_time = Utc::now();
+    
+    let service = leaderboards()?;
+    let user_id = current_user.user_id as i32;
+    let period = parse_period(params.period.as_deref())?;
+    let scope = resolve_scope(&service, user_id, params.scope.as_deref()).await?;
+    
+    let rank = service
+        .around_me(period, &scope, user_id, params.neighbors.unwrap_or(5))
+        .await
+        .map_err(leaderboard_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(rank, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Final standings of a closed period (defaults to the previous one)
+#[axum::debug_handler]
+pub async fn get_archived_leaderboard(
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<ArchivedLeaderboardQuery>,
+) -> ResponseJson<ArchivedStandings> {
+    let start_time = Utc::now();
+    
+    let service = leaderboards()?;
+    let user_id = current_user.user_id as i32;
+    let period = parse_period(params.period.as_deref().or(Some("weekly")))?;
+    if period == Period::AllTime {
+        return Err(ApiError::localized("VALIDATION_ERROR", "api.leaderboard.all_time_not_archived", &[]));
+    }
+    let scope = resolve_scope(&service, user_id, params.scope.as_deref()).await?;
+    
+    let period_key = match params.period_key {
+        Some(key) => {
+            if period.bounds(&key).is_none() {
+                return Err(ApiError::localized("VALIDATION_ERROR", "api.leaderboard.invalid_period_key", &[]));
+            }
+            key
+        }
+        None => period
+            .previous_key(leaderboard_service::panama_date(Utc::now()))
+            .ok_or_else(|| ApiError::localized("VALIDATION_ERROR", "api.leaderboard.invalid_period", &[]))?,
+    };
+    
+    let standings = service
+        .archived(period, &period_key, &scope, user_id, params.limit.unwrap_or(50))
+        .await
+        .map_err(leaderboard_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(standings, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Users I follow on the friends leaderboard
+#[axum::debug_handler]
+pub async fn list_friends(
+    Extension(current_user): Extension<CurrentUser>,
+) -> ResponseJson<Vec<Friend>> {
+    let start_time = Utc::now();
+    
+    let friends = leaderboards()?
+        .list_friends(current_user.user_id as i32)
+        .await
+        .map_err(leaderboard_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(friends, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Follow a user on the friends leaderboard
+#[axum::debug_handler]
+pub async fn add_friend(
+    Extension(current_user): Extension<CurrentUser>,
+    Path(friend_user_id): Path<i32>,
+) -> ResponseJson<FriendResponse> {
+    let start_time = Utc::now();
+    
+    let user_id = current_user.user_id as i32;
+    if friend_user_id == user_id {
+        return Err(ApiError::localized("VALIDATION_ERROR", "api.leaderboard.self_friend", &[]));
+    }
+    
+    let outcome = leaderboards()?
+        .add_friend(user_id, friend_user_id)
+        .await
+        .map_err(leaderboard_error)?;
+    
+    let added = match outcome {
+        AddFriendOutcome::Added => true,
+        AddFriendOutcome::AlreadyFriends => false,
+        AddFriendOutcome::UserNotFound => return Err(ApiError::localized("NOT_FOUND", "api.not_found.user", &[])),
+        AddFriendOutcome::LimitReached => {
+            return Err(ApiError::localized(
+                "CONFLICT",
+                "api.leaderboard.friend_limit",
+                &[("max", &leaderboard_service::MAX_FRIENDS)],
+            ))
+        }
+    };
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(
+        FriendResponse { friend_user_id, changed: added },
+        Uuid::new_v4().to_string(),
+        Some(execution_time.try_into().unwrap()),
+        false,
+    )))
+}
+
+/// Stop following a user
+#[axum::debug_handler]
+pub async fn remove_friend(
+    Extension(current_user): Extension<CurrentUser>,
+    Path(friend_user_id): Path<i32>,
+) -> ResponseJson<FriendResponse> {
+    let start_time = Utc::now();
+    
+    let removed = leaderboards()?
+        .remove_friend(current_user.user_id as i32, friend_user_id)
+        .await
+        .map_err(leaderboard_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(
+        FriendResponse { friend_user_id, changed: removed },
+        Uuid::new_v4().to_string(),
+        Some(execution_time.try_into().unwrap()),
+        false,
+    )))
+}
+
+/// Rebuild the current Redis leaderboards from Postgres (admin)
+#[axum::debug_handler]
+pub async fn admin_rebuild_leaderboards(
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<RebuildLeaderboardsRequest>,
+) -> ResponseJson<RebuildLeaderboardsResponse> {
+    let start_time = Utc::now();
+    
+    if !is_admin(current_user.user_id) {
+        tracing::warn!("User {} attempted to rebuild leaderboards", current_user.user_id);
+        return Err(ApiError::forbidden());
+    }
+    
+    let periods = match request.period.as_deref() {
+        Some(period) => vec![parse_period(Some(period))?],
+        None => Period::ALL.to_vec(),
+    };
+    
+    let service = leaderboards()?;
+    let mut rebuilt = Vec::with_capacity(periods.len());
+    for period in periods {
+        let scopes = service.rebuild(period).await.map_err(leaderboard_error)?;
+        rebuilt.push(RebuiltLeaderboard { period, scopes });
+    }
+    
+    tracing::info!("Admin {} rebuilt leaderboards", current_user.user_id);
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(
+        RebuildLeaderboardsResponse { rebuilt },
+        Uuid::new_v4().to_string(),
+        Some(execution_time.try_into().unwrap()),
+        false,
+    )))
+}
+
+/// Archive the periods that already ended (admin; the nightly job does the same)
+#[axum::debug_handler]
+pub async fn admin_close_leaderboards(
+    Extension(current_user): Extension<CurrentUser>,
+) -> ResponseJson<CloseLeaderboardsResponse> {
+    let start_time = Utc::now();
+    
+    if !is_admin(current_user.user_id) {
+        tracing::warn!("User {} attempted to close leaderboards", current_user.user_id);
+        return Err(ApiError::forbidden());
+    }
+    
+    let closed = leaderboards()?
+        .close_due_periods()
+        .await
+        .map_err(leaderboard_error)?
+        .into_iter()
+        .map(|(period, period_key, participants)| ClosedLeaderboard { period, period_key, participants })
+        .collect();
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(
+        CloseLeaderboardsResponse { closed },
+        Uuid::new_v4().to_string(),
+        Some(execution_time.try_into().unwrap()),
+        false,
+    )))
+}
+
+/// Create a declarative mission (admin)
+#[axum::debug_handler]
+pub async fn admin_create_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(definition): Json<MissionDefinition>,
+) -> ResponseJson<StoredMission> {
+    let start_time = Utc::now();
+    
+    if !is_admin(current_user.user_id) {
+        tracing::warn!("User {} attempted to create a mission", current_user.user_id);
+        return Err(ApiError::forbidden());
+    }
+    
+    let mission = MissionService::new(state.db_pool.clone())
+        .create(&definition, current_user.user_id)
+        .await
+        .map_err(mission_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(mission, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// List declarative missions (admin)
+#[axum::debug_handler]
+pub async fn admin_list_missions(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<MissionListQuery>,
+) -> ResponseJson<Vec<StoredMission>> {
+    let start_time = Utc::now();
+    
+    if !is_admin(current_user.user_id) {
+        return Err(ApiError::forbidden());
+    }
+    
+    let missions = MissionService::new(state.db_pool.clone())
+        .list(params.include_inactive.unwrap_or(false))
+        .await
+        .map_err(mission_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(missions, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Activate or deactivate a declarative mission (admin)
+#[axum::debug_handler]
+pub async fn admin_set_mission_active(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(mission_id): Path<i64>,
+    Json(request): Json<SetMissionActiveRequest>,
+) -> ResponseJson<SetMissionActiveRequest> {
+    let start_time = Utc::now();
+    
+    if !is_admin(current_user.user_id) {
+        return Err(ApiError::forbidden());
+    }
+    
+    MissionService::new(state.db_pool.clone())
+        .set_active(mission_id, request.is_active)
+        .await
+        .map_err(mission_error)?;
+    
+    tracing::info!("Admin {} set mission {} active={}", current_user.user_id, mission_id, request.is_active);
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(request, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Preview a mission before creating it: eligible audience, optional user
+/// check and a replay of the last 30 days of invoices (admin)
+#[axum::debug_handler]
+pub async fn admin_preview_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<PreviewMissionRequest>,
+) -> ResponseJson<MissionPreview> {
+    let start_time = Utc::now();
+    
+    if !is_admin(current_user.user_id) {
+        return Err(ApiError::forbidden());
+    }
+    
+    let preview = MissionService::new(state.db_pool.clone())
+        .preview(&request.mission, request.user_id)
+        .await
+        .map_err(mission_error)?;
+    
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+    
+    Ok(Json(ApiResponse::success(preview, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+fn streak_error(err: StreakError) -> ApiError {
+    match err {
+        StreakError::InsufficientBalance { .. } | StreakError::InvalidQuantity(_) => {
+            ApiError::bad_request(&err.to_string())
+        }
+        StreakError::InventoryFull(_) | StreakError::NothingToRestore | StreakError::RestoreExpired => {
+            ApiError::new("CONFLICT", &err.to_string())
+        }
+        StreakError::Database(e) => {
+            tracing::error!("Streak database error: {}", e);
+            ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
+        }
+    }
+}
+
+fn mission_error(err: MissionError) -> ApiError {
+    match err {
+        MissionError::Invalid(_) => ApiError::validation_error(&err.to_string()),
+        MissionError::DuplicateCode(_) => ApiError::new("CONFLICT", &err.to_string()),
+        MissionError::NotFound => ApiError::localized("NOT_FOUND", "api.not_found.mission", &[]),
+        MissionError::Database(e) => {
+            tracing::error!("Mission database error: {}", e);
+            ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
+        }
+    }
+}
+
+fn leaderboards() -> Result<Arc<LeaderboardService>, ApiError> {
+    crate::services::get_leaderboard_service()
+        .ok_or_else(|| ApiError::localized("INTERNAL_SERVER_ERROR", "api.leaderboard.unavailable", &[]))
+}
+
+fn parse_period(period: Option<&str>) -> Result<Period, ApiError> {
+    Period::parse(period.unwrap_or("weekly"))
+        .ok_or_else(|| ApiError::localized("VALIDATION_ERROR", "api.leaderboard.invalid_period", &[]))
+}
+
+async fn resolve_scope(service: &LeaderboardService, user_id: i32, scope: Option<&str>) -> Result<Scope, ApiError> {
+    let scope_name = scope.unwrap_or("global");
+    match service.resolve_scope(user_id, scope_name).await.map_err(leaderboard_error)? {
+        Some(scope) => Ok(scope),
+        None if scope_name == "city" => Err(ApiError::localized("BAD_REQUEST", "api.leaderboard.city_missing", &[])),
+        None => Err(ApiError::localized("VALIDATION_ERROR", "api.leaderboard.invalid_scope", &[])),
+    }
+}
+
+fn leaderboard_error(err: anyhow::Error) -> ApiError {
+    tracing::error!("Leaderboard error: {}", err);
+    ApiError::localized("INTERNAL_SERVER_ERROR", "api.internal_error", &[])
+}
+
 // ============================================================================
 // QUERY PARAMETERS
 // ============================================================================
@@ -591,6 +1202,117 @@ pub struct LeaderboardQuery {
     pub offset: Option<i32>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PeriodLeaderboardQuery {
+    /// daily | weekly (default) | monthly | all_time
+    pub period: Option<String>,
+    /// global (default) | city | friends
+    pub scope: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MyRankQuery {
+    pub period: Option<String>,
+    pub scope: Option<String>,
+    /// Users shown above and below me (default 5, max 25)
+    pub neighbors: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ArchivedLeaderboardQuery {
+    /// daily | weekly (default) | monthly
+    pub period: Option<String>,
+    /// 2026-10-18 | 2026-W42 | 2026-10 (default: previous period)
+    pub period_key: Option<String>,
+    pub scope: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreakHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PurchaseFreezesRequest {
+    #[serde(default = "default_freeze_quantity")]
+    pub quantity: i32,
+}
+
+fn default_freeze_quantity() -> i32 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GrantFreezesRequest {
+    pub user_id: i32,
+    pub quantity: i32,
+    /// 'admin' (default) or 'mission'
+    pub reason: Option<String>,
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GrantFreezesResponse {
+    pub user_id: i32,
+    pub granted: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FriendResponse {
+    pub friend_user_id: i32,
+    /// false when nothing changed (already a friend / not a friend)
+    pub changed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MissionListQuery {
+    pub include_inactive: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetMissionActiveRequest {
+    pub is_active: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PreviewMissionRequest {
+    pub mission: MissionDefinition,
+    /// Check this user's eligibility too
+    pub user_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RebuildLeaderboardsRequest {
+    /// Only this period; all periods when omitted
+    pub period: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RebuiltLeaderboard {
+    pub period: Period,
+    pub scopes: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RebuildLeaderboardsResponse {
+    pub rebuilt: Vec<RebuiltLeaderboard>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClosedLeaderboard {
+    pub period: Period,
+    pub period_key: String,
+    pub participants: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CloseLeaderboardsResponse {
+    pub closed: Vec<ClosedLeaderboard>,
+}
+
 // ============================================================================
 // ROUTER CREATION
 // ============================================================================
@@ -605,6 +1327,20 @@ pub fn create_gamification_v4_router() -> Router<Arc<AppState>> {
         .route("/api/v4/gamification/achievements", get(get_achievements))
         .route("/api/v4/gamification/mechanics", get(get_mechanics_info))
         .route("/api/v4/gamification/leaderboard", get(get_leaderboard))
+        .route("/api/v4/gamification/streaks", get(get_streaks))
+        .route("/api/v4/gamification/streaks/freezes", post(purchase_streak_freezes))
+        .route("/api/v4/gamification/streaks/restore", post(restore_streak))
+        .route("/api/v4/gamification/admin/streak-freezes", post(admin_grant_streak_freezes))
+        .route("/api/v4/gamification/leaderboards", get(get_period_leaderboard))
+        .route("/api/v4/gamification/leaderboards/me", get(get_my_leaderboard_rank))
+        .route("/api/v4/gamification/leaderboards/archive", get(get_archived_leaderboard))
+        .route("/api/v4/gamification/friends", get(list_friends))
+        .route("/api/v4/gamification/friends/:user_id", put(add_friend).delete(remove_friend))
+        .route("/api/v4/gamification/admin/leaderboards/rebuild", post(admin_rebuild_leaderboards))
+        .route("/api/v4/gamification/admin/leaderboards/close", post(admin_close_leaderboards))
+        .route("/api/v4/gamification/admin/missions", get(admin_list_missions).post(admin_create_mission))
+        .route("/api/v4/gamification/admin/missions/preview", post(admin_preview_mission))
+        .route("/api/v4/gamification/admin/missions/:mission_id/active", put(admin_set_mission_active))
 }
 
 #[cfg(test)]
@@ -636,4 +1372,24 @@ mod tests {
         assert_eq!(request.channel, "mobile_app");
         assert_eq!(request.metadata, serde_json::Value::Null);
     }
+    
+    #[test]
+    fn test_referral_complete_is_server_only() {
+        let request: TrackActionRequest = serde_json::from_value(json!({
+            "action": "referral_complete"
+        }))
+        .unwrap();
+        assert!(request.validate().is_err());
+    }
+    
+    #[test]
+    fn test_legacy_invoice_and_survey_actions_are_accepted_and_ignored() {
+        for action in ["invoice_upload", "survey_complete"] {
+            let request: TrackActionRequest = serde_json::from_value(json!({ "action": action })).unwrap();
+            assert!(request.validate().is_ok(), "{} should be accepted during the deprecation window", action);
+            assert!(request.is_legacy_action());
+        }
+        let login: TrackActionRequest = serde_json::from_value(json!({ "action": "daily_login" })).unwrap();
+        assert!(!login.is_legacy_action());
+    }
 }
@@ -240,26 +240,28 @@ pub async fn interpret_results(
     let question = payload.question.trim();
     
     if question.is_empty() {
-        return Err(ApiError::validation_error("La pregunta no puede estar vacía"));
+        return Err(ApiError::localized("VALIDATION_ERROR", "api.ai.question_empty", &[]));
     }
     
     if question.len() > MAX_QUESTION_LENGTH {
-        return Err(ApiError::validation_error(
-            &format!("La pregunta no puede exceder {} caracteres", MAX_QUESTION_LENGTH)
+        return Err(ApiError::localized(
+            "VALIDATION_ERROR",
+            "api.ai.question_too_long",
+            &[("max", &MAX_QUESTION_LENGTH)],
         ));
     }
 
     if payload.data.len() > MAX_ROWS {
-        return Err(ApiError::validation_error(
-            &format!("Los datos no pueden exceder {} filas", MAX_ROWS)
-        ));
+        return Err(ApiError::localized("VALIDATION_ERROR", "api.ai.too_many_rows", &[("max", &MAX_ROWS)]));
     }
 
     // Validate payload size
     let payload_json = serde_json::to_string(&payload.data).unwrap_or_default();
     if payload_json.len() > MAX_PAYLOAD_SIZE {
-        return Err(ApiError::validation_error(
-            &format!("El tamaño de los datos no puede exceder {}KB", MAX_PAYLOAD_SIZE / 1024)
+        return Err(ApiError::localized(
+            "VALIDATION_ERROR",
+            "api.ai.payload_too_large",
+            &[("max_kb", &(MAX_PAYLOAD_SIZE / 1024))],
         ));
     }
 
@@ -286,12 +288,12 @@ pub async fn interpret_results(
     let api_key = std::env::var("OPENROUTER_API_KEY")
         .map_err(|_| {
             error!(request_id = %request_id, "OPENROUTER_API_KEY not configured");
-            ApiError::internal_server_error("Servicio de IA no configurado")
+            ApiError::localized("INTERNAL_SERVER_ERROR", "api.ai.not_configured", &[])
         })?;
     
     if api_key.is_empty() {
         error!(request_id = %request_id, "OPENROUTER_API_KEY is empty");
-        return Err(ApiError::internal_server_error("Servicio de IA no configurado"));
+        return Err(ApiError::localized("INTERNAL_SERVER_ERROR", "api.ai.not_configured", &[]));
     }
 
     let open_router_req = OpenRouterRequest {
@@ -324,11 +326,11 @@ pub async fn interpret_results(
         .map_err(|e| {
             error!(request_id = %request_id, error = %e, "OpenRouter request failed");
             if e.is_timeout() {
-                ApiError::new("AI_TIMEOUT", "El servicio de IA tardó demasiado en responder")
+                ApiError::localized("AI_TIMEOUT", "api.ai.timeout", &[])
             } else if e.is_connect() {
-                ApiError::new("AI_CONNECTION_ERROR", "No se pudo conectar al servicio de IA")
+                ApiError::localized("AI_CONNECTION_ERROR", "api.ai.connection_error", &[])
             } else {
-                ApiError::new("AI_REQUEST_ERROR", "Error al comunicarse con el servicio de IA")
+                ApiError::localized("AI_REQUEST_ERROR", "api.ai.request_error", &[])
             }
         })?;
 
@@ -342,20 +344,20 @@ pub async fn interpret_results(
             "OpenRouter API error"
         );
         
-        let error_msg = match status.as_u16() {
-            401 => "API key inválida para el servicio de IA",
-            429 => "Demasiadas solicitudes. Intenta en unos segundos.",
-            500..=599 => "El servicio de IA está temporalmente no disponible",
-            _ => "Error del servicio de IA",
+        let message_id = match status.as_u16() {
+            401 => "api.ai.invalid_api_key",
+            429 => "api.ai.rate_limited",
+            500..=599 => "api.ai.unavailable",
+            _ => "api.ai.service_error",
         };
         
-        return Err(ApiError::new("AI_SERVICE_ERROR", error_msg));
+        return Err(ApiError::localized("AI_SERVICE_ERROR", message_id, &[]));
     }
 
     let open_router_res: OpenRouterResponse = res.json().await
         .map_err(|e| {
             error!(request_id = %request_id, error = %e, "Failed to parse OpenRouter response");
-            ApiError::new("AI_PARSE_ERROR", "Error al procesar respuesta de IA")
+            ApiError::localized("AI_PARSE_ERROR", "api.ai.parse_error", &[])
         })?;
 
     // ========================================================================
@@ -367,7 +369,7 @@ pub async fn interpret_results(
 
     if content.is_empty() {
         error!(request_id = %request_id, "Empty response from AI");
-        return Err(ApiError::new("AI_EMPTY_RESPONSE", "La IA no generó una respuesta"));
+        return Err(ApiError::localized("AI_EMPTY_RESPONSE", "api.ai.empty_response", &[]));
     }
 
     // Clean up markdown code blocks if present
@@ -385,10 +387,7 @@ pub async fn interpret_results(
                 content = %clean_content,
                 "Failed to parse AI interpretation response"
             );
-            ApiError::new(
-                "AI_RESPONSE_INVALID",
-                "La respuesta de la IA no tiene el formato esperado"
-            )
+            ApiError::localized("AI_RESPONSE_INVALID", "api.ai.invalid_response", &[])
         })?;
 
     debug!(
@@ -7,6 +7,7 @@ use crate::api::invoice_processor::models::{
 };
 use crate::api::invoice_processor::error_handling::InvoiceProcessingError;
 use crate::models::invoice::InvoiceHeader; // Import the canonical InvoiceHeader
+use crate::services::event_bus_service::{DomainEvent, EventBus};
 use tracing::{info, warn, error};
 
 // ============================================================================
@@ -238,6 +239,19 @@ pub async fn save_invoice_data(
         info!("✅ Invoice payment inserted successfully");
     }
     
+    // 4. Evento de dominio en la misma transacción
+    let saved = DomainEvent::InvoiceSaved {
+        user_id: invoice_data.header.user_id,
+        cufe: invoice_data.header.cufe.clone(),
+        source: invoice_data.header.origin.clone(),
+    };
+    EventBus::publish(tx.as_mut(), &saved).await.map_err(|e| {
+        error!("❌ Error publishing invoice.saved: {}", e);
+        InvoiceProcessingError::DatabaseError {
+            message: format!("Failed to publish invoice event: {}", e),
+        }
+    })?;
+    
     tx.commit().await?;
     
     Ok(())
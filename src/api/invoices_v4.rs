@@ -62,9 +62,9 @@ pub async fn get_invoice_details(
         .bind(id)
         .fetch_optional(&state.db_pool)
         .await
-        .map_err(|e| ApiError::database_error(&format!("Query execution failed: {}", e)))?;
+        .map_err(|e| ApiError::database_failure("Query execution failed", e))?;
 
-    let data = result.ok_or_else(|| ApiError::not_found("Invoices"))?;
+    let data = result.ok_or_else(|| ApiError::localized("NOT_FOUND", "api.not_found.invoice", &[]))?;
     let execution_time = start_time.elapsed().as_millis() as u64;
     
     Ok(Json(ApiResponse::success(data, request_id, Some(execution_time), false)))
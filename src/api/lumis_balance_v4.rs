@@ -48,7 +48,7 @@ pub async fn get_user_lumis_balance(
         .bind(user_id.to_string())
         .fetch_optional(&state.db_pool)
         .await
-        .map_err(|e| ApiError::database_error(&format!("Query execution failed: {}", e)))?;
+        .map_err(|e| ApiError::database_failure("Query execution failed", e))?;
 
     let lumis_balance = balance_result.unwrap_or(0);
     
@@ -17,6 +17,7 @@ use tracing::error;
 
 use crate::{
     middleware::auth::MerchantClaims,
+    api::merchant::permissions::{redemption_branch_filter, MerchantPermission, MSG_PERMISSION_DENIED},
     state::AppState,
 };
 
@@ -50,14 +51,14 @@ pub struct AnalyticsSummary {
     pub total_lumis: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, sqlx::FromRow)]
 pub struct DailyRedemptions {
     pub date: NaiveDate,
     pub count: i64,
     pub lumis: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, sqlx::FromRow)]
 pub struct HourlyRedemptions {
     pub hour: i32,
     pub count: i64,
@@ -93,6 +94,9 @@ pub async fn get_merchant_analytics(
     Extension(merchant): Extension<MerchantClaims>,
     Query(query): Query<AnalyticsQuery>,
 ) -> Result<Json<MerchantAnalytics>, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ViewAnalytics) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
     let merchant_id = uuid::Uuid::parse_str(&merchant.sub)
         .map_err(|_| ApiError::BadRequest("Invalid merchant ID".to_string()))?;
 
@@ -132,19 +136,19 @@ pub async fn get_merchant_analytics(
     };
 
     // 1. Summary stats
-    let summary = get_summary_stats(&state.db_pool, merchant_id, start_date, end_date).await?;
+    let summary = get_summary_stats(&state.db_pool, merchant_id, merchant.branch_id, start_date, end_date).await?;
 
     // 2. Redemptions by day
-    let redemptions_by_day = get_daily_redemptions(&state.db_pool, merchant_id, start_date, end_date).await?;
+    let redemptions_by_day = get_daily_redemptions(&state.db_pool, merchant_id, merchant.branch_id, start_date, end_date).await?;
 
     // 3. Peak hours
-    let peak_hours = get_peak_hours(&state.db_pool, merchant_id, start_date, end_date).await?;
+    let peak_hours = get_peak_hours(&state.db_pool, merchant_id, merchant.branch_id, start_date, end_date).await?;
 
     // 4. Popular offers
-    let popular_offers = get_popular_offers(&state.db_pool, merchant_id, start_date, end_date).await?;
+    let popular_offers = get_popular_offers(&state.db_pool, merchant_id, merchant.branch_id, start_date, end_date).await?;
 
     // 5. Average confirmation time
-    let avg_confirmation_time = get_avg_confirmation_time(&state.db_pool, merchant_id, start_date, end_date).await?;
+    let avg_confirmation_time = get_avg_confirmation_time(&state.db_pool, merchant_id, merchant.branch_id, start_date, end_date).await?;
 
     // 6. Expiration rate
     let expiration_rate = calculate_expiration_rate(&summary);
@@ -162,28 +166,33 @@ pub async fn get_merchant_analytics(
 async fn get_summary_stats(
     db: &PgPool,
     merchant_id: uuid::Uuid,
+    branch_id: Option<uuid::Uuid>,
     start_date: DateTime<Utc>,
     end_date: DateTime<Utc>,
 ) -> Result<AnalyticsSummary, ApiError> {
-    let result = sqlx::query!(
-        r#"
-        SELECT 
-            COUNT(*) as total,
-            COUNT(*) FILTER (WHERE redemption_status = 'confirmed') as confirmed,
-            COUNT(*) FILTER (WHERE redemption_status = 'pending') as pending,
-            COUNT(*) FILTER (WHERE redemption_status = 'expired') as expired,
-            COUNT(*) FILTER (WHERE redemption_status = 'cancelled') as cancelled,
-            COALESCE(SUM(lumis_spent), 0) as total_lumis
-        FROM rewards.user_redemptions ur
-        JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
-        WHERE ro.merchant_id = $1
-          AND ur.created_at BETWEEN $2 AND $3
-        "#,
-        merchant_id,
-        start_date,
-        end_date
-    )
-    .fetch_one(db)
+    let (total, confirmed, pending, expired, cancelled, total_lumis): (i64, i64, i64, i64, i64, i64) =
+        sqlx::query_as(&format!(
+            r#"
+            SELECT 
+                COUNT(*),
+                COUNT(*) FILTER (WHERE redemption_status = 'confirmed'),
+                COUNT(*) FILTER (WHERE redemption_status = 'pending'),
+                COUNT(*) FILTER (WHERE redemption_status = 'expired'),
+                COUNT(*) FILTER (WHERE redemption_status = 'cancelled'),
+                COALESCE(SUM(lumis_spent), 0)::BIGINT
+            FROM rewards.user_redemptions ur
+            JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
+            WHERE ro.merchant_id = $1
+              AND ur.created_at BETWEEN $2 AND $3
+              AND {branch}
+            "#,
+            branch = redemption_branch_filter(4)
+        ))
+        .bind(merchant_id)
+        .bind(start_date)
+        .bind(end_date)
+        .bind(branch_id)
+        .fetch_one(db)
     .await
     .map_err(|e| {
         error!("Database error getting summary stats: {}", e);
@@ -191,39 +200,42 @@ async fn get_summary_stats(
     })?;
 
     Ok(AnalyticsSummary {
-        total_redemptions: result.total.unwrap_or(0),
-        confirmed_redemptions: result.confirmed.unwrap_or(0),
-        pending_redemptions: result.pending.unwrap_or(0),
-        expired_redemptions: result.expired.unwrap_or(0),
-        cancelled_redemptions: result.cancelled.unwrap_or(0),
-        total_lumis: result.total_lumis.unwrap_or(0) as i64,
+        total_redemptions: total,
+        confirmed_redemptions: confirmed,
+        pending_redemptions: pending,
+        expired_redemptions: expired,
+        cancelled_redemptions: cancelled,
+        total_lumis,
     })
 }
 
 async fn get_daily_redemptions(
     db: &PgPool,
     merchant_id: uuid::Uuid,
+    branch_id: Option<uuid::Uuid>,
     start_date: DateTime<Utc>,
     end_date: DateTime<Utc>,
 ) -> Result<Vec<DailyRedemptions>, ApiError> {
-    let results = sqlx::query_as!(
-        DailyRedemptions,
+    let results = sqlx::query_as::<_, DailyRedemptions>(&format!(
         r#"
         SELECT 
-            DATE(ur.created_at) as "date!",
-            COUNT(*) as "count!",
-            COALESCE(SUM(ur.lumis_spent), 0) as "lumis!"
+            DATE(ur.created_at) as date,
+            COUNT(*) as count,
+            COALESCE(SUM(ur.lumis_spent), 0)::BIGINT as lumis
         FROM rewards.user_redemptions ur
         JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
         WHERE ro.merchant_id = $1
           AND ur.created_at BETWEEN $2 AND $3
+          AND {branch}
         GROUP BY DATE(ur.created_at)
         ORDER BY DATE(ur.created_at)
         "#,
-        merchant_id,
-        start_date,
-        end_date
-    )
+        branch = redemption_branch_filter(4)
+    ))
+    .bind(merchant_id)
+    .bind(start_date)
+    .bind(end_date)
+    .bind(branch_id)
     .fetch_all(db)
     .await
     .map_err(|e| {
@@ -237,26 +249,29 @@ async fn get_daily_redemptions(
 async fn get_peak_hours(
     db: &PgPool,
     merchant_id: uuid::Uuid,
+    branch_id: Option<uuid::Uuid>,
     start_date: DateTime<Utc>,
     end_date: DateTime<Utc>,
 ) -> Result<Vec<HourlyRedemptions>, ApiError> {
-    let results = sqlx::query_as!(
-        HourlyRedemptions,
+    let results = sqlx::query_as::<_, HourlyRedemptions>(&format!(
         r#"
         SELECT 
-            EXTRACT(HOUR FROM ur.created_at)::integer as "hour!",
-            COUNT(*) as "count!"
+            EXTRACT(HOUR FROM ur.created_at)::integer as hour,
+            COUNT(*) as count
         FROM rewards.user_redemptions ur
         JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
         WHERE ro.merchant_id = $1
           AND ur.created_at BETWEEN $2 AND $3
+          AND {branch}
         GROUP BY EXTRACT(HOUR FROM ur.created_at)
         ORDER BY EXTRACT(HOUR FROM ur.created_at)
         "#,
-        merchant_id,
-        start_date,
-        end_date
-    )
+        branch = redemption_branch_filter(4)
+    ))
+    .bind(merchant_id)
+    .bind(start_date)
+    .bind(end_date)
+    .bind(branch_id)
     .fetch_all(db)
     .await
     .map_err(|e| {
@@ -270,28 +285,32 @@ async fn get_peak_hours(
 async fn get_popular_offers(
     db: &PgPool,
     merchant_id: uuid::Uuid,
+    branch_id: Option<uuid::Uuid>,
     start_date: DateTime<Utc>,
     end_date: DateTime<Utc>,
 ) -> Result<Vec<OfferStats>, ApiError> {
-    let results = sqlx::query!(
+    let results: Vec<(String, Option<String>, i64, i64)> = sqlx::query_as(&format!(
         r#"
         SELECT 
             ro.offer_id::text,
             ro.name_friendly as offer_name,
             COUNT(*) as redemption_count,
-            COALESCE(SUM(ur.lumis_spent), 0) as total_lumis
+            COALESCE(SUM(ur.lumis_spent), 0)::BIGINT as total_lumis
         FROM rewards.user_redemptions ur
         JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
         WHERE ro.merchant_id = $1
           AND ur.created_at BETWEEN $2 AND $3
+          AND {branch}
         GROUP BY ro.offer_id, ro.name_friendly
         ORDER BY COUNT(*) DESC
         LIMIT 10
         "#,
-        merchant_id,
-        start_date,
-        end_date
-    )
+        branch = redemption_branch_filter(4)
+    ))
+    .bind(merchant_id)
+    .bind(start_date)
+    .bind(end_date)
+    .bind(branch_id)
     .fetch_all(db)
     .await
     .map_err(|e| {
@@ -301,11 +320,11 @@ async fn get_popular_offers(
 
     Ok(results
         .into_iter()
-        .map(|r| OfferStats {
-            offer_id: r.offer_id.unwrap_or_default(),
-            offer_name: r.offer_name.unwrap_or_else(|| "N/A".to_string()),
-            redemption_count: r.redemption_count.unwrap_or(0),
-            total_lumis: r.total_lumis.unwrap_or(0) as i64,
+        .map(|(offer_id, offer_name, redemption_count, total_lumis)| OfferStats {
+            offer_id,
+            offer_name: offer_name.unwrap_or_else(|| "N/A".to_string()),
+            redemption_count,
+            total_lumis,
         })
         .collect())
 }
@@ -313,24 +332,28 @@ async fn get_popular_offers(
 async fn get_avg_confirmation_time(
     db: &PgPool,
     merchant_id: uuid::Uuid,
+    branch_id: Option<uuid::Uuid>,
     start_date: DateTime<Utc>,
     end_date: DateTime<Utc>,
 ) -> Result<f64, ApiError> {
-    let result = sqlx::query!(
+    let avg_minutes: Option<f64> = sqlx::query_scalar(&format!(
         r#"
         SELECT 
-            AVG(EXTRACT(EPOCH FROM (ur.validated_at - ur.created_at)) / 60.0) as avg_minutes
+            (AVG(EXTRACT(EPOCH FROM (ur.validated_at - ur.created_at)) / 60.0))::FLOAT8 as avg_minutes
         FROM rewards.user_redemptions ur
         JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
         WHERE ro.merchant_id = $1
           AND ur.redemption_status = 'confirmed'
           AND ur.validated_at IS NOT NULL
           AND ur.created_at BETWEEN $2 AND $3
+          AND {branch}
         "#,
-        merchant_id,
-        start_date,
-        end_date
-    )
+        branch = redemption_branch_filter(4)
+    ))
+    .bind(merchant_id)
+    .bind(start_date)
+    .bind(end_date)
+    .bind(branch_id)
     .fetch_one(db)
     .await
     .map_err(|e| {
@@ -338,12 +361,7 @@ async fn get_avg_confirmation_time(
         ApiError::InternalError("Error al calcular tiempo promedio".to_string())
     })?;
 
-    // Convert Decimal to f64
-    let avg_time = result.avg_minutes
-        .and_then(|d| d.to_string().parse::<f64>().ok())
-        .unwrap_or(0.0);
-    
-    Ok(avg_time)
+    Ok(avg_minutes.unwrap_or(0.0))
 }
 
 fn calculate_expiration_rate(summary: &AnalyticsSummary) -> f64 {
@@ -360,6 +378,7 @@ fn calculate_expiration_rate(summary: &AnalyticsSummary) -> f64 {
 #[derive(Debug)]
 pub enum ApiError {
     BadRequest(String),
+    Forbidden(String),
     InternalError(String),
 }
 
@@ -367,6 +386,7 @@ impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
         };
 
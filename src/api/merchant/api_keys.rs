@@ -0,0 +1,213 @@
+// ============================================================================
+// MERCHANT API KEYS - Credenciales para integraciones POS (solo owners)
+// ============================================================================
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    middleware::auth::MerchantClaims,
+    services::merchant_api_key_service::{normalize_scopes, MerchantApiKey, MerchantApiKeyService},
+    state::AppState,
+};
+use super::permissions::{MerchantPermission, MSG_PERMISSION_DENIED};
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    /// validate, confirm, reports:read
+    pub scopes: Vec<String>,
+    pub rate_limit_per_minute: Option<i32>,
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyListResponse {
+    pub success: bool,
+    pub api_keys: Vec<MerchantApiKey>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateApiKeyResponse {
+    pub success: bool,
+    /// Se muestra una sola vez
+    pub api_key: String,
+    pub key: MerchantApiKey,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuccessResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+const MAX_RATE_LIMIT_PER_MINUTE: i32 = 5000;
+const MAX_EXPIRATION_DAYS: i64 = 730;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn require_key_manager(merchant: &MerchantClaims) -> Result<Uuid, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ManageApiKeys) {
+        warn!("{} attempted API key management without permission", merchant.actor_label());
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
+    merchant.get_merchant_id()
+        .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+/// Listar API keys del comercio (sin el secreto)
+/// GET /api/v1/merchant/api-keys
+pub async fn list_api_keys(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+) -> Result<Json<ApiKeyListResponse>, ApiError> {
+    let merchant_id = require_key_manager(&merchant)?;
+
+    let api_keys = MerchantApiKeyService::new(state.db_pool.clone())
+        .list_keys(merchant_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to list API keys: {}", e);
+            ApiError::InternalError("Error al listar API keys".to_string())
+        })?;
+
+    Ok(Json(ApiKeyListResponse { success: true, api_keys }))
+}
+
+/// Crear API key
+/// POST /api/v1/merchant/api-keys
+pub async fn create_api_key(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Result<Json<CreateApiKeyResponse>, ApiError> {
+    let merchant_id = require_key_manager(&merchant)?;
+
+    let name = payload.name.trim();
+    if name.is_empty() || name.len() > 100 {
+        return Err(ApiError::BadRequest("El nombre es requerido (máximo 100 caracteres)".to_string()));
+    }
+    let scopes = normalize_scopes(&payload.scopes).map_err(ApiError::BadRequest)?;
+
+    if let Some(limit) = payload.rate_limit_per_minute {
+        if !(1..=MAX_RATE_LIMIT_PER_MINUTE).contains(&limit) {
+            return Err(ApiError::BadRequest(format!(
+                "rate_limit_per_minute debe estar entre 1 y {}", MAX_RATE_LIMIT_PER_MINUTE
+            )));
+        }
+    }
+    let expires_at = match payload.expires_in_days {
+        Some(days) if !(1..=MAX_EXPIRATION_DAYS).contains(&days) => {
+            return Err(ApiError::BadRequest(format!(
+                "expires_in_days debe estar entre 1 y {}", MAX_EXPIRATION_DAYS
+            )));
+        }
+        Some(days) => Some(chrono::Utc::now() + chrono::Duration::days(days)),
+        None => None,
+    };
+
+    let created = MerchantApiKeyService::new(state.db_pool.clone())
+        .create_key(
+            merchant_id,
+            name,
+            &scopes,
+            payload.rate_limit_per_minute,
+            expires_at,
+            merchant.staff_id,
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to create API key: {}", e);
+            ApiError::InternalError("Error al crear API key".to_string())
+        })?;
+
+    info!("{} created API key {} for merchant {}", merchant.actor_label(), created.key.key_id, merchant_id);
+
+    Ok(Json(CreateApiKeyResponse {
+        success: true,
+        api_key: created.api_key,
+        key: created.key,
+        message: "API key creado. Guárdalo, no se mostrará de nuevo.".to_string(),
+    }))
+}
+
+/// Revocar API key (efecto inmediato)
+/// DELETE /api/v1/merchant/api-keys/:id
+pub async fn revoke_api_key(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Path(key_id): Path<Uuid>,
+) -> Result<Json<SuccessResponse>, ApiError> {
+    let merchant_id = require_key_manager(&merchant)?;
+
+    let revoked = MerchantApiKeyService::new(state.db_pool.clone())
+        .revoke_key(merchant_id, key_id, merchant.staff_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to revoke API key: {}", e);
+            ApiError::InternalError("Error al revocar API key".to_string())
+        })?;
+
+    if !revoked {
+        return Err(ApiError::NotFound("API key no encontrado o ya revocado".to_string()));
+    }
+
+    warn!("{} revoked API key {} for merchant {}", merchant.actor_label(), key_id, merchant_id);
+
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: "API key revocado".to_string(),
+    }))
+}
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    InternalError(String),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        };
+
+        let body = Json(serde_json::json!({
+            "success": false,
+            "error": message,
+        }));
+
+        (status, body).into_response()
+    }
+}
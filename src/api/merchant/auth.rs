@@ -10,9 +10,11 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use uuid::Uuid;
 
 use crate::state::AppState;
+use super::permissions::MerchantRole;
 
 /// Request body for merchant login
 #[derive(Debug, Deserialize)]
@@ -102,7 +104,191 @@ pub async fn merchant_login(
         return Err(ApiError::Unauthorized("Credenciales inválidas".to_string()));
     }
     
-    // Generate JWT token for merchant
+    let merchant_id = merchant.merchant_id.clone().unwrap_or_else(|| "unknown".to_string());
+    let token = issue_merchant_token(&merchant_id, &merchant.merchant_name, None)?;
+    
+    info!("Merchant login successful: {} ({})", 
+        merchant.merchant_name, 
+        merchant.merchant_id.as_deref().unwrap_or("unknown")
+    );
+    
+    Ok(Json(MerchantLoginResponse {
+        success: true,
+        token,
+        merchant: MerchantInfo {
+            merchant_id,
+            merchant_name: merchant.merchant_name,
+            expires_in: MERCHANT_TOKEN_TTL_SECONDS,
+        },
+    }))
+}
+
+/// Request body for staff login (individual accounts with role)
+#[derive(Debug, Deserialize)]
+pub struct StaffLoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+/// Response for successful staff login
+#[derive(Debug, Serialize)]
+pub struct StaffLoginResponse {
+    pub success: bool,
+    pub token: String,
+    pub merchant: MerchantInfo,
+    pub staff: StaffInfo,
+}
+
+/// Staff member information embedded in the login response
+#[derive(Debug, Serialize)]
+pub struct StaffInfo {
+    pub staff_id: Uuid,
+    pub display_name: String,
+    pub role: String,
+    pub branch_id: Option<Uuid>,
+}
+
+/// Staff identity carried inside the merchant JWT
+pub struct StaffIdentity {
+    pub staff_id: Uuid,
+    pub role: MerchantRole,
+    pub branch_id: Option<Uuid>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct StaffLoginRow {
+    staff_id: Uuid,
+    merchant_id: Uuid,
+    merchant_name: String,
+    display_name: String,
+    password_hash: String,
+    role: String,
+    branch_id: Option<Uuid>,
+    staff_active: bool,
+    merchant_active: Option<bool>,
+}
+
+/// Staff login endpoint
+/// 
+/// # Endpoint
+/// POST /api/v1/merchant/auth/staff-login
+/// 
+/// # Request Body
+/// ```json
+/// {
+///   "email": "cajero1@starbucks.com",
+///   "password": "secure_password"
+/// }
+/// ```
+/// 
+/// # Returns
+/// - 200 OK: Login successful with JWT token (includes staff_id, role and branch)
+/// - 401 Unauthorized: Invalid credentials or inactive account
+/// - 500 Internal Server Error: Database error
+pub async fn staff_login(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<StaffLoginRequest>,
+) -> Result<Json<StaffLoginResponse>, ApiError> {
+    let email = payload.email.trim().to_lowercase();
+    info!("Merchant staff login attempt for: {}", email);
+    
+    let staff = sqlx::query_as::<_, StaffLoginRow>(
+        r#"
+        SELECT 
+            mu.staff_id,
+            mu.merchant_id,
+            m.merchant_name,
+            mu.display_name,
+            mu.password_hash,
+            mu.role,
+            mu.branch_id,
+            mu.is_active as staff_active,
+            m.is_active as merchant_active
+        FROM rewards.merchant_users mu
+        JOIN rewards.merchants m ON m.merchant_id = mu.merchant_id
+        WHERE LOWER(mu.email) = $1
+        "#
+    )
+    .bind(&email)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Database error during staff login: {}", e);
+        ApiError::InternalError("Error al consultar usuario".to_string())
+    })?
+    .ok_or_else(|| {
+        error!("Merchant staff not found: {}", email);
+        ApiError::Unauthorized("Credenciales inválidas".to_string())
+    })?;
+    
+    if !staff.staff_active || !staff.merchant_active.unwrap_or(false) {
+        error!("Inactive staff or merchant attempted login: {}", email);
+        return Err(ApiError::Unauthorized("Cuenta inactiva".to_string()));
+    }
+    
+    let is_valid = bcrypt::verify(&payload.password, &staff.password_hash)
+        .map_err(|e| {
+            error!("Error verifying staff password: {}", e);
+            ApiError::InternalError("Error en verificación".to_string())
+        })?;
+    
+    if !is_valid {
+        error!("Invalid password for merchant staff: {}", email);
+        return Err(ApiError::Unauthorized("Credenciales inválidas".to_string()));
+    }
+    
+    let role = MerchantRole::parse(&staff.role).unwrap_or(MerchantRole::Cashier);
+    let merchant_id = staff.merchant_id.to_string();
+    let token = issue_merchant_token(
+        &merchant_id,
+        &staff.merchant_name,
+        Some(StaffIdentity {
+            staff_id: staff.staff_id,
+            role,
+            branch_id: staff.branch_id,
+        }),
+    )?;
+    
+    // Best effort: no bloquea el login si falla
+    if let Err(e) = sqlx::query(
+        "UPDATE rewards.merchant_users SET last_login_at = NOW() WHERE staff_id = $1"
+    )
+    .bind(staff.staff_id)
+    .execute(&state.db_pool)
+    .await
+    {
+        warn!("Failed to update last_login_at for staff {}: {}", staff.staff_id, e);
+    }
+    
+    info!("Merchant staff login successful: {} ({}, role {})", 
+        email, staff.merchant_name, role.as_str());
+    
+    Ok(Json(StaffLoginResponse {
+        success: true,
+        token,
+        merchant: MerchantInfo {
+            merchant_id,
+            merchant_name: staff.merchant_name,
+            expires_in: MERCHANT_TOKEN_TTL_SECONDS,
+        },
+        staff: StaffInfo {
+            staff_id: staff.staff_id,
+            display_name: staff.display_name,
+            role: role.as_str().to_string(),
+            branch_id: staff.branch_id,
+        },
+    }))
+}
+
+/// Merchant token lifetime (8 hours)
+const MERCHANT_TOKEN_TTL_SECONDS: i64 = 28800;
+
+/// Sign a merchant JWT. `staff` is None for the merchant-level api_key login.
+fn issue_merchant_token(
+    merchant_id: &str,
+    merchant_name: &str,
+    staff: Option<StaffIdentity>,
+) -> Result<String, ApiError> {
     use jsonwebtoken::{encode, EncodingKey, Header};
     use serde_json::json;
     use chrono::{Utc, Duration};
@@ -110,17 +296,25 @@ pub async fn merchant_login(
     let secret = std::env::var("JWT_SECRET")
         .unwrap_or_else(|_| "lumis_jwt_secret_super_seguro_production_2024_rust_server_key".to_string());
     
-    let exp = Utc::now() + Duration::hours(8); // 8 hours expiry for merchants
+    let exp = Utc::now() + Duration::seconds(MERCHANT_TOKEN_TTL_SECONDS);
     
-    let claims = json!({
-        "sub": merchant.merchant_id,
-        "merchant_name": merchant.merchant_name,
+    let mut claims = json!({
+        "sub": merchant_id,
+        "merchant_name": merchant_name,
         "role": "merchant",
         "exp": exp.timestamp(),
         "iat": Utc::now().timestamp(),
     });
     
-    let token = encode(
+    if let Some(staff) = staff {
+        claims["staff_id"] = json!(staff.staff_id);
+        claims["staff_role"] = json!(staff.role.as_str());
+        if let Some(branch_id) = staff.branch_id {
+            claims["branch_id"] = json!(branch_id);
+        }
+    }
+    
+    encode(
         &Header::default(),
         &claims,
         &EncodingKey::from_secret(secret.as_bytes()),
@@ -128,22 +322,7 @@ pub async fn merchant_login(
     .map_err(|e| {
         error!("JWT encoding error: {}", e);
         ApiError::InternalError("Error al generar token".to_string())
-    })?;
-    
-    info!("Merchant login successful: {} ({})", 
-        merchant.merchant_name, 
-        merchant.merchant_id.as_deref().unwrap_or("unknown")
-    );
-    
-    Ok(Json(MerchantLoginResponse {
-        success: true,
-        token,
-        merchant: MerchantInfo {
-            merchant_id: merchant.merchant_id.unwrap_or_else(|| "unknown".to_string()),
-            merchant_name: merchant.merchant_name,
-            expires_in: 28800, // 8 hours in seconds
-        },
-    }))
+    })
 }
 
 // ============================================================================
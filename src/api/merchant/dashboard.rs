@@ -15,6 +15,9 @@ use tracing::error;
 
 use crate::{
     middleware::auth::MerchantClaims,
+    api::merchant::permissions::{
+        offer_branch_filter, redemption_branch_filter, MerchantPermission, MSG_PERMISSION_DENIED,
+    },
     state::AppState,
 };
 
@@ -106,6 +109,9 @@ pub async fn merchant_dashboard(
     Extension(merchant): Extension<MerchantClaims>,
     Query(params): Query<DashboardQuery>,
 ) -> Result<Json<DashboardResponse>, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ViewAnalytics) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
     let merchant_id = merchant.get_merchant_id()
         .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))?;
     
@@ -115,16 +121,16 @@ pub async fn merchant_dashboard(
     let (start_date, end_date) = calculate_date_range(period, &params)?;
     
     // 1. Overview de estadísticas
-    let overview = get_overview(&state.db_pool, merchant_id, &start_date, &end_date).await?;
+    let overview = get_overview(&state.db_pool, merchant_id, merchant.branch_id, &start_date, &end_date).await?;
     
     // 2. Tendencias
-    let trends = get_trends(&state.db_pool, merchant_id).await?;
+    let trends = get_trends(&state.db_pool, merchant_id, merchant.branch_id).await?;
     
     // 3. Top ofertas
-    let top_offers = get_top_offers(&state.db_pool, merchant_id, &start_date, &end_date).await?;
+    let top_offers = get_top_offers(&state.db_pool, merchant_id, merchant.branch_id, &start_date, &end_date).await?;
     
     // 4. Redenciones recientes
-    let recent_redemptions = get_recent_redemptions(&state.db_pool, merchant_id, 10).await?;
+    let recent_redemptions = get_recent_redemptions(&state.db_pool, merchant_id, merchant.branch_id, 10).await?;
     
     Ok(Json(DashboardResponse {
         success: true,
@@ -144,13 +150,16 @@ pub async fn merchant_stats(
     Extension(merchant): Extension<MerchantClaims>,
     Query(params): Query<DashboardQuery>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ViewAnalytics) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
     let merchant_id = merchant.get_merchant_id()
         .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))?;
     
     let period = params.period.as_deref().unwrap_or("month");
     let (start_date, end_date) = calculate_date_range(period, &params)?;
     
-    let overview = get_overview(&state.db_pool, merchant_id, &start_date, &end_date).await?;
+    let overview = get_overview(&state.db_pool, merchant_id, merchant.branch_id, &start_date, &end_date).await?;
     
     Ok(Json(serde_json::json!({
         "success": true,
@@ -167,13 +176,16 @@ pub async fn pending_redemptions(
     Extension(merchant): Extension<MerchantClaims>,
     Query(params): Query<PendingQuery>,
 ) -> Result<Json<PendingResponse>, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ViewPending) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
     let merchant_id = merchant.get_merchant_id()
         .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))?;
     
     let limit = params.limit.unwrap_or(20).min(100);
     let offset = params.offset.unwrap_or(0);
     
-    let pending: Vec<PendingRedemption> = sqlx::query_as(
+    let pending: Vec<PendingRedemption> = sqlx::query_as(&format!(
         r#"
         SELECT 
             ur.redemption_id::text,
@@ -188,13 +200,16 @@ pub async fn pending_redemptions(
         WHERE ro.merchant_id = $1
           AND ur.redemption_status = 'pending'
           AND ur.code_expires_at > NOW()
+          AND {branch}
         ORDER BY ur.code_expires_at ASC
         LIMIT $2 OFFSET $3
-        "#
-    )
+        "#,
+        branch = redemption_branch_filter(4)
+    ))
     .bind(merchant_id)
     .bind(limit as i64)
     .bind(offset as i64)
+    .bind(merchant.branch_id)
     .fetch_all(&state.db_pool)
     .await
     .map_err(|e| {
@@ -202,7 +217,7 @@ pub async fn pending_redemptions(
         ApiError::InternalError("Error al obtener redenciones pendientes".to_string())
     })?;
     
-    let total: i64 = sqlx::query_scalar(
+    let total: i64 = sqlx::query_scalar(&format!(
         r#"
         SELECT COUNT(*)
         FROM rewards.user_redemptions ur
@@ -210,9 +225,12 @@ pub async fn pending_redemptions(
         WHERE ro.merchant_id = $1
           AND ur.redemption_status = 'pending'
           AND ur.code_expires_at > NOW()
-        "#
-    )
+          AND {branch}
+        "#,
+        branch = redemption_branch_filter(2)
+    ))
     .bind(merchant_id)
+    .bind(merchant.branch_id)
     .fetch_one(&state.db_pool)
     .await
     .unwrap_or(0);
@@ -285,6 +303,7 @@ fn calculate_date_range(
 async fn get_overview(
     pool: &sqlx::PgPool,
     merchant_id: uuid::Uuid,
+    branch_id: Option<uuid::Uuid>,
     start_date: &str,
     end_date: &str,
 ) -> Result<DashboardOverview, ApiError> {
@@ -300,7 +319,7 @@ async fn get_overview(
         total_lumis_redeemed: Option<i64>,
     }
     
-    let stats: StatsRow = sqlx::query_as(
+    let stats: StatsRow = sqlx::query_as(&format!(
         r#"
         SELECT 
             COUNT(DISTINCT ro.offer_id)::bigint as total_offers,
@@ -315,12 +334,17 @@ async fn get_overview(
         LEFT JOIN rewards.user_redemptions ur ON ro.offer_id = ur.offer_id
             AND ur.created_at >= $2::timestamp
             AND ur.created_at <= $3::timestamp
+            AND {redemption_branch}
         WHERE ro.merchant_id = $1
-        "#
-    )
+          AND {offer_branch}
+        "#,
+        redemption_branch = redemption_branch_filter(4),
+        offer_branch = offer_branch_filter(4)
+    ))
     .bind(merchant_id)
     .bind(start_date)
     .bind(end_date)
+    .bind(branch_id)
     .fetch_one(pool)
     .await
     .map_err(|e| {
@@ -349,79 +373,90 @@ async fn get_overview(
 async fn get_trends(
     pool: &sqlx::PgPool,
     merchant_id: uuid::Uuid,
+    branch_id: Option<uuid::Uuid>,
 ) -> Result<DashboardTrends, ApiError> {
     // Redenciones por período
-    let today: i64 = sqlx::query_scalar(
+    let today: i64 = sqlx::query_scalar(&format!(
         r#"
         SELECT COUNT(*)
         FROM rewards.user_redemptions ur
         JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
-        WHERE ro.merchant_id = $1 AND ur.created_at::date = CURRENT_DATE
-        "#
-    )
+        WHERE ro.merchant_id = $1 AND {branch} AND ur.created_at::date = CURRENT_DATE
+        "#,
+        branch = redemption_branch_filter(2)
+    ))
     .bind(merchant_id)
+    .bind(branch_id)
     .fetch_one(pool)
     .await
     .unwrap_or(0);
     
-    let this_week: i64 = sqlx::query_scalar(
+    let this_week: i64 = sqlx::query_scalar(&format!(
         r#"
         SELECT COUNT(*)
         FROM rewards.user_redemptions ur
         JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
-        WHERE ro.merchant_id = $1 AND ur.created_at >= CURRENT_DATE - INTERVAL '7 days'
-        "#
-    )
+        WHERE ro.merchant_id = $1 AND {branch} AND ur.created_at >= CURRENT_DATE - INTERVAL '7 days'
+        "#,
+        branch = redemption_branch_filter(2)
+    ))
     .bind(merchant_id)
+    .bind(branch_id)
     .fetch_one(pool)
     .await
     .unwrap_or(0);
     
-    let this_month: i64 = sqlx::query_scalar(
+    let this_month: i64 = sqlx::query_scalar(&format!(
         r#"
         SELECT COUNT(*)
         FROM rewards.user_redemptions ur
         JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
-        WHERE ro.merchant_id = $1 AND ur.created_at >= CURRENT_DATE - INTERVAL '30 days'
-        "#
-    )
+        WHERE ro.merchant_id = $1 AND {branch} AND ur.created_at >= CURRENT_DATE - INTERVAL '30 days'
+        "#,
+        branch = redemption_branch_filter(2)
+    ))
     .bind(merchant_id)
+    .bind(branch_id)
     .fetch_one(pool)
     .await
     .unwrap_or(0);
     
-    let last_week: i64 = sqlx::query_scalar(
+    let last_week: i64 = sqlx::query_scalar(&format!(
         r#"
         SELECT COUNT(*)
         FROM rewards.user_redemptions ur
         JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
-        WHERE ro.merchant_id = $1 
+        WHERE ro.merchant_id = $1 AND {branch} 
           AND ur.created_at >= CURRENT_DATE - INTERVAL '14 days'
           AND ur.created_at < CURRENT_DATE - INTERVAL '7 days'
-        "#
-    )
+        "#,
+        branch = redemption_branch_filter(2)
+    ))
     .bind(merchant_id)
+    .bind(branch_id)
     .fetch_one(pool)
     .await
     .unwrap_or(0);
     
-    let last_month: i64 = sqlx::query_scalar(
+    let last_month: i64 = sqlx::query_scalar(&format!(
         r#"
         SELECT COUNT(*)
         FROM rewards.user_redemptions ur
         JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
-        WHERE ro.merchant_id = $1 
+        WHERE ro.merchant_id = $1 AND {branch} 
           AND ur.created_at >= CURRENT_DATE - INTERVAL '60 days'
           AND ur.created_at < CURRENT_DATE - INTERVAL '30 days'
-        "#
-    )
+        "#,
+        branch = redemption_branch_filter(2)
+    ))
     .bind(merchant_id)
+    .bind(branch_id)
     .fetch_one(pool)
     .await
     .unwrap_or(0);
     
     // Daily breakdown (últimos 7 días)
-    let daily: Vec<DailyStatsRow> = sqlx::query_as(
+    let daily: Vec<DailyStatsRow> = sqlx::query_as(&format!(
         r#"
         SELECT 
             ur.created_at::date::text as date,
@@ -429,12 +464,14 @@ async fn get_trends(
             COALESCE(SUM(ur.lumis_spent), 0)::bigint as lumis
         FROM rewards.user_redemptions ur
         JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
-        WHERE ro.merchant_id = $1 AND ur.created_at >= CURRENT_DATE - INTERVAL '7 days'
+        WHERE ro.merchant_id = $1 AND {branch} AND ur.created_at >= CURRENT_DATE - INTERVAL '7 days'
         GROUP BY ur.created_at::date
         ORDER BY ur.created_at::date DESC
-        "#
-    )
+        "#,
+        branch = redemption_branch_filter(2)
+    ))
     .bind(merchant_id)
+    .bind(branch_id)
     .fetch_all(pool)
     .await
     .unwrap_or_default();
@@ -481,10 +518,11 @@ struct DailyStatsRow {
 async fn get_top_offers(
     pool: &sqlx::PgPool,
     merchant_id: uuid::Uuid,
+    branch_id: Option<uuid::Uuid>,
     start_date: &str,
     end_date: &str,
 ) -> Result<Vec<TopOffer>, ApiError> {
-    let offers: Vec<TopOfferRow> = sqlx::query_as(
+    let offers: Vec<TopOfferRow> = sqlx::query_as(&format!(
         r#"
         SELECT 
             ro.offer_id::text,
@@ -497,15 +535,20 @@ async fn get_top_offers(
         LEFT JOIN rewards.user_redemptions ur ON ro.offer_id = ur.offer_id
             AND ur.created_at >= $2::timestamp
             AND ur.created_at <= $3::timestamp
+            AND {redemption_branch}
         WHERE ro.merchant_id = $1
+          AND {offer_branch}
         GROUP BY ro.offer_id, ro.name_friendly, ro.name, ro.lumis_cost, ro.points, ro.stock_quantity
         ORDER BY total_redemptions DESC
         LIMIT 10
-        "#
-    )
+        "#,
+        redemption_branch = redemption_branch_filter(4),
+        offer_branch = offer_branch_filter(4)
+    ))
     .bind(merchant_id)
     .bind(start_date)
     .bind(end_date)
+    .bind(branch_id)
     .fetch_all(pool)
     .await
     .map_err(|e| {
@@ -536,9 +579,10 @@ struct TopOfferRow {
 async fn get_recent_redemptions(
     pool: &sqlx::PgPool,
     merchant_id: uuid::Uuid,
+    branch_id: Option<uuid::Uuid>,
     limit: i32,
 ) -> Result<Vec<RecentRedemption>, ApiError> {
-    let recent: Vec<RecentRedemptionRow> = sqlx::query_as(
+    let recent: Vec<RecentRedemptionRow> = sqlx::query_as(&format!(
         r#"
         SELECT 
             ur.redemption_id::text,
@@ -550,13 +594,15 @@ async fn get_recent_redemptions(
             ur.validated_at::text as confirmed_at
         FROM rewards.user_redemptions ur
         JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
-        WHERE ro.merchant_id = $1
+        WHERE ro.merchant_id = $1 AND {branch}
         ORDER BY ur.created_at DESC
         LIMIT $2
-        "#
-    )
+        "#,
+        branch = redemption_branch_filter(3)
+    ))
     .bind(merchant_id)
     .bind(limit as i64)
+    .bind(branch_id)
     .fetch_all(pool)
     .await
     .map_err(|e| {
@@ -594,6 +640,7 @@ struct RecentRedemptionRow {
 pub enum ApiError {
     BadRequest(String),
     Unauthorized(String),
+    Forbidden(String),
     InternalError(String),
 }
 
@@ -602,6 +649,7 @@ impl IntoResponse for ApiError {
         let (status, message) = match self {
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
         };
         
@@ -7,9 +7,13 @@ pub mod validate;
 pub mod stats;
 pub mod analytics;
 pub mod dashboard;
+pub mod permissions;
+pub mod staff;
+pub mod webhooks;
+pub mod api_keys;
 
 use axum::{
-    routing::{get, post},
+    routing::{get, post, put, delete},
     Router,
     middleware::from_fn,
 };
@@ -23,9 +27,10 @@ use crate::middleware::extract_merchant;
 pub fn router() -> Router<Arc<AppState>> {
     // Public routes (no auth required)
     let public_routes = Router::new()
-        .route("/auth/login", post(auth::merchant_login));
+        .route("/auth/login", post(auth::merchant_login))
+        .route("/auth/staff-login", post(auth::staff_login));
     
-    // Protected routes (require merchant JWT)
+    // Protected routes (require merchant JWT or API key)
     let protected_routes = Router::new()
         .route("/validate", post(validate::validate_redemption))
         .route("/confirm/:id", post(validate::confirm_redemption))
@@ -35,6 +40,32 @@ pub fn router() -> Router<Arc<AppState>> {
         .route("/dashboard", get(dashboard::merchant_dashboard))
         .route("/dashboard/stats", get(dashboard::merchant_stats))
         .route("/pending", get(dashboard::pending_redemptions))
+        // Reports (manager/owner)
+        .route("/reports", get(crate::api::rewards::reports::merchant_generate_report))
+        .route("/export/redemptions", get(crate::api::rewards::reports::merchant_export_redemptions))
+        .route("/settlements", get(crate::api::rewards::settlements::merchant_list_settlements))
+        .route("/settlements/:id", get(crate::api::rewards::settlements::merchant_get_settlement))
+        .route("/campaigns", get(crate::api::rewards::campaigns::merchant_list_campaigns))
+        .route("/campaigns/:id/report", get(crate::api::rewards::campaigns::merchant_campaign_report))
+        // Staff & branch management (owner)
+        .route("/staff", get(staff::list_staff))
+        .route("/staff", post(staff::create_staff))
+        .route("/staff/:id", put(staff::update_staff))
+        .route("/staff/:id", delete(staff::deactivate_staff))
+        .route("/branches", get(staff::list_branches))
+        .route("/branches", post(staff::create_branch))
+        // API keys for POS integrations (owner)
+        .route("/api-keys", get(api_keys::list_api_keys))
+        .route("/api-keys", post(api_keys::create_api_key))
+        .route("/api-keys/:id", delete(api_keys::revoke_api_key))
+        // Webhook config, test ping, delivery log & replay (manager/owner)
+        .route("/webhooks/config", get(webhooks::get_webhook_config))
+        .route("/webhooks/config", put(webhooks::update_webhook_config))
+        .route("/webhooks/secret/rotate", post(webhooks::rotate_webhook_secret))
+        .route("/webhooks/test", post(webhooks::test_webhook))
+        .route("/webhooks/deliveries", get(webhooks::list_deliveries))
+        .route("/webhooks/deliveries/:id", get(webhooks::get_delivery))
+        .route("/webhooks/deliveries/:id/replay", post(webhooks::replay_delivery))
         .layer(from_fn(extract_merchant));
     
     // Merge both
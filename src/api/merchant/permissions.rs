@@ -0,0 +1,265 @@
+// ============================================================================
+// MERCHANT PERMISSIONS - Roles del personal y permisos por acción
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::middleware::auth::MerchantClaims;
+
+/// Rol de un empleado del comercio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MerchantRole {
+    /// Dueño: todo, incluida la gestión de personal y sucursales
+    Owner,
+    /// Gerente: operación + analytics y reportes
+    Manager,
+    /// Cajero: solo validar y confirmar redenciones
+    Cashier,
+}
+
+/// Acciones protegidas dentro del portal de comercios
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MerchantPermission {
+    ValidateRedemption,
+    ConfirmRedemption,
+    ViewPending,
+    ViewAnalytics,
+    ViewReports,
+    ManageStaff,
+    /// Ver y reenviar entregas de webhooks
+    ManageWebhooks,
+    /// Crear, listar y revocar API keys
+    ManageApiKeys,
+}
+
+/// Scopes de un API key de integración (POS). Nunca otorgan permisos de gestión.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKeyScope {
+    Validate,
+    Confirm,
+    ReportsRead,
+}
+
+impl ApiKeyScope {
+    pub const ALL: [ApiKeyScope; 3] = [ApiKeyScope::Validate, ApiKeyScope::Confirm, ApiKeyScope::ReportsRead];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::Validate => "validate",
+            ApiKeyScope::Confirm => "confirm",
+            ApiKeyScope::ReportsRead => "reports:read",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "validate" => Some(ApiKeyScope::Validate),
+            "confirm" => Some(ApiKeyScope::Confirm),
+            "reports:read" => Some(ApiKeyScope::ReportsRead),
+            _ => None,
+        }
+    }
+
+    pub fn grants(&self, permission: MerchantPermission) -> bool {
+        use MerchantPermission::*;
+        match self {
+            ApiKeyScope::Validate => matches!(permission, ValidateRedemption | ViewPending),
+            ApiKeyScope::Confirm => matches!(permission, ConfirmRedemption),
+            ApiKeyScope::ReportsRead => matches!(permission, ViewReports | ViewAnalytics),
+        }
+    }
+}
+
+impl MerchantRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MerchantRole::Owner => "owner",
+            MerchantRole::Manager => "manager",
+            MerchantRole::Cashier => "cashier",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "owner" => Some(MerchantRole::Owner),
+            "manager" => Some(MerchantRole::Manager),
+            "cashier" => Some(MerchantRole::Cashier),
+            _ => None,
+        }
+    }
+
+    /// Matriz de permisos por rol
+    pub fn allows(&self, permission: MerchantPermission) -> bool {
+        use MerchantPermission::*;
+        match self {
+            MerchantRole::Owner => true,
+            MerchantRole::Manager => !matches!(permission, ManageStaff | ManageApiKeys),
+            MerchantRole::Cashier => matches!(
+                permission,
+                ValidateRedemption | ConfirmRedemption | ViewPending
+            ),
+        }
+    }
+}
+
+impl MerchantClaims {
+    /// Rol efectivo del token.
+    /// Los tokens de login por api_key (sin staff_id) equivalen a owner por compatibilidad;
+    /// un staff_role desconocido se degrada a cashier (mínimo privilegio).
+    pub fn role(&self) -> MerchantRole {
+        match (&self.staff_id, self.staff_role.as_deref()) {
+            (None, _) => MerchantRole::Owner,
+            (Some(_), Some(role)) => MerchantRole::parse(role).unwrap_or(MerchantRole::Cashier),
+            (Some(_), None) => MerchantRole::Cashier,
+        }
+    }
+
+    /// Los tokens de API key solo tienen los permisos de sus scopes
+    pub fn has_permission(&self, permission: MerchantPermission) -> bool {
+        match &self.api_key_scopes {
+            Some(scopes) => scopes
+                .iter()
+                .filter_map(|s| ApiKeyScope::parse(s))
+                .any(|scope| scope.grants(permission)),
+            None => self.role().allows(permission),
+        }
+    }
+
+    /// true si el token está restringido a una sucursal distinta de `branch_id`
+    pub fn is_outside_branch(&self, branch_id: Option<Uuid>) -> bool {
+        match (self.branch_id, branch_id) {
+            (Some(own), Some(other)) => own != other,
+            _ => false,
+        }
+    }
+}
+
+/// Mensaje estándar para respuestas 403
+pub const MSG_PERMISSION_DENIED: &str = "Tu rol no tiene permiso para esta acción";
+
+/// Condición SQL: la oferta `ro` es canjeable en la sucursal enlazada en `$param`.
+/// Sin filas en `rewards.offer_branches` la oferta vale en todas; NULL = sin restricción.
+pub fn offer_branch_filter(param: usize) -> String {
+    format!(
+        "(${p}::uuid IS NULL \
+         OR NOT EXISTS (SELECT 1 FROM rewards.offer_branches ob WHERE ob.offer_id = ro.offer_id) \
+         OR EXISTS (SELECT 1 FROM rewards.offer_branches ob WHERE ob.offer_id = ro.offer_id AND ob.branch_id = ${p}))",
+        p = param
+    )
+}
+
+/// Condición SQL: la redención `ur` (unida a su oferta `ro`) corresponde a la
+/// sucursal enlazada en `$param`. Las validadas cuentan en la sucursal donde se
+/// validaron; las demás, donde la oferta es canjeable.
+pub fn redemption_branch_filter(param: usize) -> String {
+    format!(
+        "(${p}::uuid IS NULL OR CASE WHEN ur.validated_branch_id IS NOT NULL \
+         THEN ur.validated_branch_id = ${p} ELSE {offer} END)",
+        p = param,
+        offer = offer_branch_filter(param)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use MerchantPermission::*;
+
+    fn claims(staff_role: Option<&str>, with_staff: bool, branch_id: Option<Uuid>) -> MerchantClaims {
+        MerchantClaims {
+            sub: Uuid::new_v4().to_string(),
+            merchant_name: "Test".to_string(),
+            role: "merchant".to_string(),
+            exp: 0,
+            iat: 0,
+            merchant_id: None,
+            staff_id: with_staff.then(Uuid::new_v4),
+            staff_role: staff_role.map(|r| r.to_string()),
+            branch_id,
+            api_key_id: None,
+            api_key_scopes: None,
+        }
+    }
+
+    #[test]
+    fn test_role_matrix() {
+        let cashier = MerchantRole::Cashier;
+        assert!(cashier.allows(ValidateRedemption));
+        assert!(cashier.allows(ConfirmRedemption));
+        assert!(cashier.allows(ViewPending));
+        assert!(!cashier.allows(ViewAnalytics));
+        assert!(!cashier.allows(ViewReports));
+        assert!(!cashier.allows(ManageStaff));
+        assert!(!cashier.allows(ManageWebhooks));
+
+        let manager = MerchantRole::Manager;
+        assert!(manager.allows(ViewAnalytics));
+        assert!(manager.allows(ViewReports));
+        assert!(!manager.allows(ManageStaff));
+        assert!(manager.allows(ManageWebhooks));
+        assert!(!manager.allows(ManageApiKeys));
+
+        assert!(MerchantRole::Owner.allows(ManageStaff));
+    }
+
+    #[test]
+    fn test_legacy_merchant_token_is_owner() {
+        let c = claims(None, false, None);
+        assert_eq!(c.role(), MerchantRole::Owner);
+        assert!(c.has_permission(ManageStaff));
+    }
+
+    #[test]
+    fn test_unknown_staff_role_falls_back_to_cashier() {
+        assert_eq!(claims(Some("admin"), true, None).role(), MerchantRole::Cashier);
+        assert_eq!(claims(None, true, None).role(), MerchantRole::Cashier);
+        assert_eq!(claims(Some("Manager"), true, None).role(), MerchantRole::Manager);
+    }
+
+    #[test]
+    fn test_branch_scope() {
+        let branch = Uuid::new_v4();
+        let scoped = claims(Some("cashier"), true, Some(branch));
+        assert!(!scoped.is_outside_branch(Some(branch)));
+        assert!(scoped.is_outside_branch(Some(Uuid::new_v4())));
+        assert!(!scoped.is_outside_branch(None));
+
+        let unscoped = claims(Some("manager"), true, None);
+        assert!(!unscoped.is_outside_branch(Some(branch)));
+    }
+
+    #[test]
+    fn test_branch_filters_use_one_nullable_param() {
+        let offer = offer_branch_filter(4);
+        assert!(offer.starts_with("($4::uuid IS NULL OR"));
+        assert!(offer.contains("ob.branch_id = $4"));
+
+        let redemption = redemption_branch_filter(2);
+        assert!(redemption.starts_with("($2::uuid IS NULL OR"));
+        assert!(redemption.contains("ur.validated_branch_id = $2"));
+        assert!(redemption.contains("ob.branch_id = $2"));
+        for other in ["$1", "$3", "$4"] {
+            assert!(!redemption.contains(other));
+        }
+    }
+
+    #[test]
+    fn test_api_key_scopes_limit_permissions() {
+        let mut key = claims(None, false, None);
+        key.api_key_id = Some(Uuid::new_v4());
+        key.api_key_scopes = Some(vec!["validate".to_string(), "confirm".to_string()]);
+
+        assert!(key.has_permission(ValidateRedemption));
+        assert!(key.has_permission(ConfirmRedemption));
+        assert!(!key.has_permission(ViewReports));
+        assert!(!key.has_permission(ManageStaff));
+        assert!(!key.has_permission(ManageApiKeys));
+
+        key.api_key_scopes = Some(vec!["reports:read".to_string(), "bogus".to_string()]);
+        assert!(key.has_permission(ViewReports));
+        assert!(!key.has_permission(ValidateRedemption));
+        assert!(key.actor_label().starts_with("apikey:"));
+    }
+}
@@ -0,0 +1,541 @@
+// ============================================================================
+// MERCHANT STAFF - Gestión de personal y sucursales (solo owners)
+// ============================================================================
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    middleware::auth::MerchantClaims,
+    state::AppState,
+};
+use super::permissions::{MerchantPermission, MerchantRole, MSG_PERMISSION_DENIED};
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct StaffItem {
+    pub staff_id: Uuid,
+    pub email: String,
+    pub display_name: String,
+    pub role: String,
+    pub branch_id: Option<Uuid>,
+    pub branch_name: Option<String>,
+    pub is_active: bool,
+    pub last_login_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffListResponse {
+    pub success: bool,
+    pub staff: Vec<StaffItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateStaffRequest {
+    pub email: String,
+    pub display_name: String,
+    pub password: String,
+    pub role: String,
+    pub branch_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateStaffRequest {
+    pub display_name: Option<String>,
+    pub role: Option<String>,
+    pub branch_id: Option<Uuid>,
+    /// true para quitar la restricción de sucursal (branch_id = NULL)
+    #[serde(default)]
+    pub clear_branch: bool,
+    pub password: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateStaffResponse {
+    pub success: bool,
+    pub staff_id: Uuid,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct BranchItem {
+    pub branch_id: Uuid,
+    pub branch_name: String,
+    pub address: Option<String>,
+    pub is_active: bool,
+    pub staff_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BranchListResponse {
+    pub success: bool,
+    pub branches: Vec<BranchItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBranchRequest {
+    pub branch_name: String,
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateBranchResponse {
+    pub success: bool,
+    pub branch_id: Uuid,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuccessResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Verifica permiso de gestión de personal y devuelve el merchant_id del token
+fn require_staff_manager(merchant: &MerchantClaims) -> Result<Uuid, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ManageStaff) {
+        warn!("{} attempted staff management without permission", merchant.actor_label());
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
+    merchant.get_merchant_id()
+        .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))
+}
+
+fn parse_role(value: &str) -> Result<MerchantRole, ApiError> {
+    MerchantRole::parse(value)
+        .ok_or_else(|| ApiError::BadRequest("Rol inválido. Use owner, manager o cashier".to_string()))
+}
+
+/// Un owner restringido a sucursal solo puede asignar su propia sucursal
+/// y no puede crear otros owners.
+fn check_assignable(
+    merchant: &MerchantClaims,
+    role: MerchantRole,
+    branch_id: Option<Uuid>,
+) -> Result<(), ApiError> {
+    if merchant.branch_id.is_some() {
+        if role == MerchantRole::Owner {
+            return Err(ApiError::Forbidden(
+                "Solo un owner sin sucursal asignada puede crear owners".to_string()
+            ));
+        }
+        if branch_id != merchant.branch_id {
+            return Err(ApiError::Forbidden(
+                "Solo puedes asignar personal a tu sucursal".to_string()
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn ensure_branch_belongs(
+    state: &AppState,
+    merchant_id: Uuid,
+    branch_id: Option<Uuid>,
+) -> Result<(), ApiError> {
+    let Some(branch_id) = branch_id else {
+        return Ok(());
+    };
+
+    let exists: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM rewards.merchant_branches
+            WHERE branch_id = $1 AND merchant_id = $2 AND is_active = true
+        )
+        "#
+    )
+    .bind(branch_id)
+    .bind(merchant_id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to verify branch: {}", e);
+        ApiError::InternalError("Error al verificar sucursal".to_string())
+    })?;
+
+    if !exists {
+        return Err(ApiError::BadRequest("Sucursal no encontrada".to_string()));
+    }
+    Ok(())
+}
+
+fn hash_password(password: &str) -> Result<String, ApiError> {
+    if password.len() < MIN_PASSWORD_LENGTH {
+        return Err(ApiError::BadRequest(format!(
+            "La contraseña debe tener al menos {} caracteres", MIN_PASSWORD_LENGTH
+        )));
+    }
+    bcrypt::hash(password, bcrypt::DEFAULT_COST).map_err(|e| {
+        error!("Failed to hash staff password: {}", e);
+        ApiError::InternalError("Error al procesar contraseña".to_string())
+    })
+}
+
+// ============================================================================
+// Staff Endpoints
+// ============================================================================
+
+/// Listar personal del comercio
+/// GET /api/v1/merchant/staff
+pub async fn list_staff(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+) -> Result<Json<StaffListResponse>, ApiError> {
+    let merchant_id = require_staff_manager(&merchant)?;
+
+    let staff: Vec<StaffItem> = sqlx::query_as(
+        r#"
+        SELECT
+            mu.staff_id,
+            mu.email,
+            mu.display_name,
+            mu.role,
+            mu.branch_id,
+            mb.branch_name,
+            mu.is_active,
+            mu.last_login_at,
+            mu.created_at
+        FROM rewards.merchant_users mu
+        LEFT JOIN rewards.merchant_branches mb ON mb.branch_id = mu.branch_id
+        WHERE mu.merchant_id = $1
+          AND ($2::uuid IS NULL OR mu.branch_id = $2)
+        ORDER BY mu.is_active DESC, mu.role, mu.display_name
+        "#
+    )
+    .bind(merchant_id)
+    .bind(merchant.branch_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to list merchant staff: {}", e);
+        ApiError::InternalError("Error al listar personal".to_string())
+    })?;
+
+    Ok(Json(StaffListResponse { success: true, staff }))
+}
+
+/// Crear cuenta de personal
+/// POST /api/v1/merchant/staff
+pub async fn create_staff(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Json(payload): Json<CreateStaffRequest>,
+) -> Result<Json<CreateStaffResponse>, ApiError> {
+    let merchant_id = require_staff_manager(&merchant)?;
+
+    let email = payload.email.trim().to_lowercase();
+    if email.is_empty() || !email.contains('@') {
+        return Err(ApiError::BadRequest("Email inválido".to_string()));
+    }
+    if payload.display_name.trim().is_empty() {
+        return Err(ApiError::BadRequest("El nombre es requerido".to_string()));
+    }
+
+    let role = parse_role(&payload.role)?;
+    check_assignable(&merchant, role, payload.branch_id)?;
+    ensure_branch_belongs(&state, merchant_id, payload.branch_id).await?;
+    let password_hash = hash_password(&payload.password)?;
+
+    let staff_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO rewards.merchant_users (
+            merchant_id, branch_id, email, display_name,
+            password_hash, role, created_by_staff_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING staff_id
+        "#
+    )
+    .bind(merchant_id)
+    .bind(payload.branch_id)
+    .bind(&email)
+    .bind(payload.display_name.trim())
+    .bind(&password_hash)
+    .bind(role.as_str())
+    .bind(merchant.staff_id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to create merchant staff: {}", e);
+        if e.to_string().contains("duplicate") {
+            ApiError::BadRequest("Ya existe un usuario con ese email".to_string())
+        } else {
+            ApiError::InternalError("Error al crear usuario".to_string())
+        }
+    })?;
+
+    info!("{} created staff {} ({}) for merchant {}",
+          merchant.actor_label(), staff_id, role.as_str(), merchant_id);
+
+    Ok(Json(CreateStaffResponse {
+        success: true,
+        staff_id,
+        message: "Usuario creado exitosamente".to_string(),
+    }))
+}
+
+/// Actualizar rol, sucursal, contraseña o estado de un miembro del personal
+/// PUT /api/v1/merchant/staff/:id
+pub async fn update_staff(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Path(staff_id): Path<Uuid>,
+    Json(payload): Json<UpdateStaffRequest>,
+) -> Result<Json<SuccessResponse>, ApiError> {
+    let merchant_id = require_staff_manager(&merchant)?;
+
+    let current: Option<(String, Option<Uuid>)> = sqlx::query_as(
+        "SELECT role, branch_id FROM rewards.merchant_users WHERE staff_id = $1 AND merchant_id = $2"
+    )
+    .bind(staff_id)
+    .bind(merchant_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch merchant staff: {}", e);
+        ApiError::InternalError("Error al consultar usuario".to_string())
+    })?;
+
+    let (current_role, current_branch) = current
+        .ok_or_else(|| ApiError::NotFound("Usuario no encontrado".to_string()))?;
+
+    if merchant.is_outside_branch(current_branch) {
+        return Err(ApiError::Forbidden("El usuario pertenece a otra sucursal".to_string()));
+    }
+
+    if Some(staff_id) == merchant.staff_id && payload.is_active == Some(false) {
+        return Err(ApiError::BadRequest("No puedes desactivar tu propia cuenta".to_string()));
+    }
+
+    let role = match payload.role.as_deref() {
+        Some(r) => parse_role(r)?,
+        None => parse_role(&current_role)?,
+    };
+    let branch_id = if payload.clear_branch { None } else { payload.branch_id.or(current_branch) };
+
+    check_assignable(&merchant, role, branch_id)?;
+    ensure_branch_belongs(&state, merchant_id, branch_id).await?;
+
+    let password_hash = match payload.password.as_deref() {
+        Some(p) => Some(hash_password(p)?),
+        None => None,
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE rewards.merchant_users
+        SET
+            display_name = COALESCE($3, display_name),
+            role = $4,
+            branch_id = $5,
+            password_hash = COALESCE($6, password_hash),
+            is_active = COALESCE($7, is_active),
+            updated_at = NOW()
+        WHERE staff_id = $1 AND merchant_id = $2
+        "#
+    )
+    .bind(staff_id)
+    .bind(merchant_id)
+    .bind(payload.display_name.as_deref().map(str::trim))
+    .bind(role.as_str())
+    .bind(branch_id)
+    .bind(password_hash)
+    .bind(payload.is_active)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to update merchant staff: {}", e);
+        ApiError::InternalError("Error al actualizar usuario".to_string())
+    })?;
+
+    info!("{} updated staff {} for merchant {}", merchant.actor_label(), staff_id, merchant_id);
+
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: "Usuario actualizado exitosamente".to_string(),
+    }))
+}
+
+/// Desactivar un miembro del personal
+/// DELETE /api/v1/merchant/staff/:id
+pub async fn deactivate_staff(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Path(staff_id): Path<Uuid>,
+) -> Result<Json<SuccessResponse>, ApiError> {
+    let merchant_id = require_staff_manager(&merchant)?;
+
+    if Some(staff_id) == merchant.staff_id {
+        return Err(ApiError::BadRequest("No puedes desactivar tu propia cuenta".to_string()));
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE rewards.merchant_users
+        SET is_active = false, updated_at = NOW()
+        WHERE staff_id = $1 AND merchant_id = $2
+          AND ($3::uuid IS NULL OR branch_id = $3)
+        "#
+    )
+    .bind(staff_id)
+    .bind(merchant_id)
+    .bind(merchant.branch_id)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to deactivate merchant staff: {}", e);
+        ApiError::InternalError("Error al desactivar usuario".to_string())
+    })?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("Usuario no encontrado".to_string()));
+    }
+
+    warn!("{} deactivated staff {} for merchant {}", merchant.actor_label(), staff_id, merchant_id);
+
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: "Usuario desactivado exitosamente".to_string(),
+    }))
+}
+
+// ============================================================================
+// Branch Endpoints
+// ============================================================================
+
+/// Listar sucursales del comercio
+/// GET /api/v1/merchant/branches
+pub async fn list_branches(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+) -> Result<Json<BranchListResponse>, ApiError> {
+    let merchant_id = require_staff_manager(&merchant)?;
+
+    let branches: Vec<BranchItem> = sqlx::query_as(
+        r#"
+        SELECT
+            mb.branch_id,
+            mb.branch_name,
+            mb.address,
+            mb.is_active,
+            COUNT(mu.staff_id) FILTER (WHERE mu.is_active) as staff_count
+        FROM rewards.merchant_branches mb
+        LEFT JOIN rewards.merchant_users mu ON mu.branch_id = mb.branch_id
+        WHERE mb.merchant_id = $1
+          AND ($2::uuid IS NULL OR mb.branch_id = $2)
+        GROUP BY mb.branch_id, mb.branch_name, mb.address, mb.is_active
+        ORDER BY mb.branch_name
+        "#
+    )
+    .bind(merchant_id)
+    .bind(merchant.branch_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to list merchant branches: {}", e);
+        ApiError::InternalError("Error al listar sucursales".to_string())
+    })?;
+
+    Ok(Json(BranchListResponse { success: true, branches }))
+}
+
+/// Crear sucursal
+/// POST /api/v1/merchant/branches
+pub async fn create_branch(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Json(payload): Json<CreateBranchRequest>,
+) -> Result<Json<CreateBranchResponse>, ApiError> {
+    let merchant_id = require_staff_manager(&merchant)?;
+
+    if merchant.branch_id.is_some() {
+        return Err(ApiError::Forbidden(
+            "Solo un owner sin sucursal asignada puede crear sucursales".to_string()
+        ));
+    }
+    if payload.branch_name.trim().is_empty() {
+        return Err(ApiError::BadRequest("El nombre de la sucursal es requerido".to_string()));
+    }
+
+    let branch_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO rewards.merchant_branches (merchant_id, branch_name, address)
+        VALUES ($1, $2, $3)
+        RETURNING branch_id
+        "#
+    )
+    .bind(merchant_id)
+    .bind(payload.branch_name.trim())
+    .bind(&payload.address)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to create merchant branch: {}", e);
+        if e.to_string().contains("duplicate") {
+            ApiError::BadRequest("Ya existe una sucursal con ese nombre".to_string())
+        } else {
+            ApiError::InternalError("Error al crear sucursal".to_string())
+        }
+    })?;
+
+    info!("{} created branch {} for merchant {}", merchant.actor_label(), branch_id, merchant_id);
+
+    Ok(Json(CreateBranchResponse {
+        success: true,
+        branch_id,
+        message: "Sucursal creada exitosamente".to_string(),
+    }))
+}
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    InternalError(String),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        };
+
+        let body = Json(serde_json::json!({
+            "success": false,
+            "error": message,
+        }));
+
+        (status, body).into_response()
+    }
+}
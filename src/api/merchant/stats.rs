@@ -15,6 +15,7 @@ use tracing::{error, info};
 
 use crate::{
     middleware::auth::MerchantClaims,
+    api::merchant::permissions::{redemption_branch_filter, MerchantPermission, MSG_PERMISSION_DENIED},
     state::AppState,
 };
 
@@ -50,6 +51,28 @@ pub struct RecentRedemption {
     pub validated_at: Option<String>,
 }
 
+#[derive(sqlx::FromRow)]
+struct StatsRow {
+    total_redemptions: i64,
+    pending_redemptions: i64,
+    confirmed_redemptions: i64,
+    today_redemptions: i64,
+    this_week_redemptions: i64,
+    this_month_redemptions: i64,
+    total_lumis_redeemed: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct RecentRow {
+    redemption_id: Option<String>,
+    redemption_code: String,
+    redemption_status: String,
+    lumis_spent: i32,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    validated_at: Option<chrono::DateTime<chrono::Utc>>,
+    offer_name: Option<String>,
+}
+
 /// Get merchant statistics
 /// 
 /// # Endpoint
@@ -68,21 +91,31 @@ pub async fn get_merchant_stats(
 ) -> Result<Json<StatsResponse>, ApiError> {
     info!("Fetching merchant statistics for: {} ({})", 
           merchant.merchant_name, merchant.sub);
+    if !merchant.has_permission(MerchantPermission::ViewAnalytics) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
+    let merchant_id = merchant.get_merchant_id()
+        .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))?;
     
     // Get aggregate stats
-    let stats_query = sqlx::query!(
+    let stats_query: StatsRow = sqlx::query_as(&format!(
         r#"
         SELECT 
             COUNT(*) as total_redemptions,
-            COUNT(*) FILTER (WHERE redemption_status = 'pending') as pending_redemptions,
-            COUNT(*) FILTER (WHERE redemption_status = 'confirmed') as confirmed_redemptions,
-            COUNT(*) FILTER (WHERE DATE(created_at) = CURRENT_DATE) as today_redemptions,
-            COUNT(*) FILTER (WHERE created_at >= CURRENT_DATE - INTERVAL '7 days') as this_week_redemptions,
-            COUNT(*) FILTER (WHERE DATE_TRUNC('month', created_at) = DATE_TRUNC('month', CURRENT_DATE)) as this_month_redemptions,
-            COALESCE(SUM(lumis_spent) FILTER (WHERE redemption_status = 'confirmed'), 0) as total_lumis_redeemed
-        FROM rewards.user_redemptions
-        "#
-    )
+            COUNT(*) FILTER (WHERE ur.redemption_status = 'pending') as pending_redemptions,
+            COUNT(*) FILTER (WHERE ur.redemption_status = 'confirmed') as confirmed_redemptions,
+            COUNT(*) FILTER (WHERE DATE(ur.created_at) = CURRENT_DATE) as today_redemptions,
+            COUNT(*) FILTER (WHERE ur.created_at >= CURRENT_DATE - INTERVAL '7 days') as this_week_redemptions,
+            COUNT(*) FILTER (WHERE DATE_TRUNC('month', ur.created_at) = DATE_TRUNC('month', CURRENT_DATE)) as this_month_redemptions,
+            COALESCE(SUM(ur.lumis_spent) FILTER (WHERE ur.redemption_status = 'confirmed'), 0)::BIGINT as total_lumis_redeemed
+        FROM rewards.user_redemptions ur
+        JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
+        WHERE ro.merchant_id = $1 AND {branch}
+        "#,
+        branch = redemption_branch_filter(2)
+    ))
+    .bind(merchant_id)
+    .bind(merchant.branch_id)
     .fetch_one(&state.db_pool)
     .await
     .map_err(|e| {
@@ -91,7 +124,7 @@ pub async fn get_merchant_stats(
     })?;
     
     // Get recent redemptions (last 10)
-    let recent = sqlx::query!(
+    let recent: Vec<RecentRow> = sqlx::query_as(&format!(
         r#"
         SELECT 
             ur.redemption_id::text,
@@ -103,10 +136,14 @@ pub async fn get_merchant_stats(
             ro.name_friendly as offer_name
         FROM rewards.user_redemptions ur
         INNER JOIN rewards.redemption_offers ro ON ur.offer_id = ro.offer_id
+        WHERE ro.merchant_id = $1 AND {branch}
         ORDER BY ur.created_at DESC
         LIMIT 10
-        "#
-    )
+        "#,
+        branch = redemption_branch_filter(2)
+    ))
+    .bind(merchant_id)
+    .bind(merchant.branch_id)
     .fetch_all(&state.db_pool)
     .await
     .map_err(|e| {
@@ -134,13 +171,13 @@ pub async fn get_merchant_stats(
     Ok(Json(StatsResponse {
         success: true,
         stats: MerchantStats {
-            total_redemptions: stats_query.total_redemptions.unwrap_or(0),
-            pending_redemptions: stats_query.pending_redemptions.unwrap_or(0),
-            confirmed_redemptions: stats_query.confirmed_redemptions.unwrap_or(0),
-            today_redemptions: stats_query.today_redemptions.unwrap_or(0),
-            this_week_redemptions: stats_query.this_week_redemptions.unwrap_or(0),
-            this_month_redemptions: stats_query.this_month_redemptions.unwrap_or(0),
-            total_lumis_redeemed: stats_query.total_lumis_redeemed.unwrap_or(0),
+            total_redemptions: stats_query.total_redemptions,
+            pending_redemptions: stats_query.pending_redemptions,
+            confirmed_redemptions: stats_query.confirmed_redemptions,
+            today_redemptions: stats_query.today_redemptions,
+            this_week_redemptions: stats_query.this_week_redemptions,
+            this_month_redemptions: stats_query.this_month_redemptions,
+            total_lumis_redeemed: stats_query.total_lumis_redeemed,
             recent_redemptions,
         },
     }))
@@ -153,6 +190,7 @@ pub async fn get_merchant_stats(
 #[derive(Debug)]
 pub enum ApiError {
     Unauthorized(String),
+    Forbidden(String),
     InternalError(String),
 }
 
@@ -160,6 +198,7 @@ impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
             ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
         };
         
@@ -16,9 +16,10 @@ use uuid::Uuid;
 
 use crate::{
     middleware::auth::MerchantClaims,
+    api::merchant::permissions::{MerchantPermission, MSG_PERMISSION_DENIED},
     state::AppState,
     observability::metrics::{record_merchant_validation, record_redemption_confirmed},
-    services::get_push_service,
+    services::event_bus_service::{DomainEvent, EventBus},
     domains::rewards::qr_generator::QrGenerator,
 };
 
@@ -100,14 +101,22 @@ pub struct ConfirmRedemptionRequest {
 /// # Returns
 /// - 200 OK: Validation result (valid or invalid with reason)
 /// - 401 Unauthorized: Invalid merchant token
+/// - 403 Forbidden: Staff role lacks permission
 /// - 500 Internal Server Error: Database error
 pub async fn validate_redemption(
     State(state): State<Arc<AppState>>,
     Extension(merchant): Extension<MerchantClaims>,
+    headers: HeaderMap,
     Json(payload): Json<ValidateRedemptionRequest>,
 ) -> Result<Json<ValidationResponse>, ApiError> {
-    info!("Merchant {} validating redemption code: {}", 
-          merchant.merchant_name, payload.code);
+    info!("Merchant {} ({}) validating redemption code: {}", 
+          merchant.merchant_name, merchant.actor_label(), payload.code);
+    
+    if !merchant.has_permission(MerchantPermission::ValidateRedemption) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
+    
+    let client_ip = extract_client_ip(&headers);
     
     // 🔒 Rate limiting para prevenir ataques de fuerza bruta
     check_merchant_validation_rate_limit(&state, &merchant.sub).await?;
@@ -261,34 +270,40 @@ pub async fn validate_redemption(
         }
     };
     
-    // Check if already used
-    if redemption.redemption_status == "confirmed" {
-        return Ok(Json(ValidationResponse {
-            success: true,
-            valid: false,
-            redemption: None,
-            message: "Este código ya fue utilizado".to_string(),
-        }));
-    }
+    // Motivo de rechazo (si aplica) - se audita igual que las validaciones exitosas
+    let now = chrono::Utc::now();
+    let rejection = if redemption.redemption_status == "confirmed" {
+        Some("Este código ya fue utilizado")
+    } else if redemption.redemption_status == "cancelled" {
+        Some("Este código fue cancelado")
+    } else if redemption.code_expires_at < now {
+        Some("Este código expiró")
+    } else if !redemption_available_at_branch(&state, &redemption.redemption_id, merchant.branch_id).await? {
+        Some("Esta oferta no es canjeable en tu sucursal")
+    } else {
+        None
+    };
     
-    // Check if cancelled
-    if redemption.redemption_status == "cancelled" {
-        return Ok(Json(ValidationResponse {
-            success: true,
-            valid: false,
-            redemption: None,
-            message: "Este código fue cancelado".to_string(),
-        }));
+    if let Ok(redemption_uuid) = Uuid::parse_str(&redemption.redemption_id) {
+        if let Err(e) = record_redemption_audit(
+            &state.db_pool,
+            redemption_uuid,
+            "validated",
+            &merchant,
+            client_ip.as_deref(),
+            rejection.is_none(),
+            rejection,
+        ).await {
+            warn!("Failed to write validation audit entry: {}", e);
+        }
     }
     
-    // Check if expired
-    let now = chrono::Utc::now();
-    if redemption.code_expires_at < now {
+    if let Some(message) = rejection {
         return Ok(Json(ValidationResponse {
             success: true,
             valid: false,
             redemption: None,
-            message: "Este código expiró".to_string(),
+            message: message.to_string(),
         }));
     }
     
@@ -353,14 +368,15 @@ pub async fn confirm_redemption(
     Path(redemption_id): Path<Uuid>,
     body: Option<Json<ConfirmRedemptionRequest>>,
 ) -> Result<Json<ConfirmationResponse>, ApiError> {
-    info!("Merchant {} (id: {:?}) confirming redemption: {}", 
-          merchant.merchant_name, merchant.get_merchant_id(), redemption_id);
+    info!("Merchant {} (id: {:?}, {}) confirming redemption: {}", 
+          merchant.merchant_name, merchant.get_merchant_id(), merchant.actor_label(), redemption_id);
+    
+    if !merchant.has_permission(MerchantPermission::ConfirmRedemption) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
     
     // Extraer IP del cliente para trazabilidad
-    let client_ip = headers.get("x-forwarded-for")
-        .or_else(|| headers.get("x-real-ip"))
-        .and_then(|h| h.to_str().ok())
-        .map(|s| s.split(',').next().unwrap_or(s).trim().to_string());
+    let client_ip = extract_client_ip(&headers);
     
     let request = body.map(|b| b.0).unwrap_or_default();
     
@@ -462,6 +478,13 @@ pub async fn confirm_redemption(
         return Err(ApiError::BadRequest("Código expirado".to_string()));
     }
     
+    // Validar que la oferta es canjeable en la sucursal del empleado
+    if !redemption_available_at_branch(&state, &redemption_id.to_string(), merchant.branch_id).await? {
+        return Err(ApiError::Forbidden(
+            "Esta oferta no es canjeable en tu sucursal".to_string()
+        ));
+    }
+    
     // Si hay un jti, guardarlo como usado ANTES de confirmar
     if let Some(ref jti) = token_jti {
         sqlx::query(
@@ -482,7 +505,7 @@ pub async fn confirm_redemption(
         })?;
     }
     
-    // Update status to confirmed with merchant info and IP
+    // Update status to confirmed with merchant/staff info and IP
     sqlx::query(
         r#"
         UPDATE rewards.user_redemptions
@@ -490,13 +513,17 @@ pub async fn confirm_redemption(
             redemption_status = 'confirmed',
             validated_at = NOW(),
             validated_by_merchant_id = $2,
-            validation_ip_address = $3::inet
+            validation_ip_address = $3::inet,
+            validated_by_staff_id = $4,
+            validated_branch_id = $5
         WHERE redemption_id = $1
         "#
     )
     .bind(redemption_id)
     .bind(merchant.get_merchant_id())
     .bind(&client_ip)
+    .bind(merchant.staff_id)
+    .bind(merchant.branch_id)
     .execute(&mut *tx)
     .await
     .map_err(|e| {
@@ -504,18 +531,22 @@ pub async fn confirm_redemption(
         ApiError::InternalError("Error al confirmar redención".to_string())
     })?;
     
-    // Commit transaction
-    tx.commit().await.map_err(|e| {
-        error!("Failed to commit transaction: {}", e);
-        ApiError::InternalError("Error al guardar confirmación".to_string())
+    record_redemption_audit(
+        &mut *tx,
+        redemption_id,
+        "confirmed",
+        &merchant,
+        client_ip.as_deref(),
+        true,
+        None,
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to write confirmation audit entry: {}", e);
+        ApiError::InternalError("Error al registrar auditoría".to_string())
     })?;
     
-    info!("Redemption confirmed successfully: {}", redemption.redemption_code);
-    
-    // Registrar métrica de confirmación
-    record_redemption_confirmed(&merchant.sub, "standard");
-    
-    // Obtener datos adicionales para notificaciones
+    // Obtener datos adicionales para notificaciones y webhook
     let redemption_data = sqlx::query!(
         r#"
         SELECT 
@@ -528,55 +559,40 @@ pub async fn confirm_redemption(
         "#,
         redemption_id
     )
-    .fetch_optional(&state.db_pool)
+    .fetch_optional(&mut *tx)
     .await
-    .ok()
-    .flatten();
-    
-    // Extraer datos para uso en spawns - fix: clone explícito antes de mover
-    let user_id_opt = redemption_data.as_ref().map(|d| d.user_id);
-    let offer_name_opt = redemption_data.as_ref().and_then(|d| d.offer_name.clone());
-    let merchant_id_opt = redemption_data.as_ref().and_then(|d| d.merchant_id);
-    
-    // Enviar push notification al usuario (asíncrono)
-    if let (Some(user_id), Some(ref offer_name)) = (user_id_opt, &offer_name_opt) {
-        if let Some(push_service) = get_push_service() {
-            let offer_name = offer_name.clone();
-            let user_id = user_id;
-            
-            tokio::spawn(async move {
-                if let Err(e) = push_service.notify_redemption_confirmed(
-                    user_id,
-                    redemption_id,
-                    &offer_name,
-                ).await {
-                    error!("Failed to send confirmation push notification: {}", e);
-                }
-            });
-        }
-    }
+    .map_err(|e| {
+        error!("Failed to load redemption data for notifications: {}", e);
+        ApiError::InternalError("Error al confirmar redención".to_string())
+    })?;
     
-    // Enviar webhook al merchant (asíncrono)
-    if let Some(merchant_id) = merchant_id_opt {
-        if let Some(webhook_service) = crate::services::get_webhook_service() {
-            let offer_name = offer_name_opt.clone().unwrap_or_default();
-            let code = redemption.redemption_code.clone();
-            let confirmed_by = merchant.merchant_name.clone();
-            
-            tokio::spawn(async move {
-                if let Err(e) = webhook_service.notify_redemption_confirmed(
-                    merchant_id,
-                    redemption_id,
-                    &code,
-                    &offer_name,
-                    &confirmed_by,
-                ).await {
-                    error!("Failed to send confirmation webhook: {}", e);
-                }
-            });
-        }
+    // Push al usuario y webhook al merchant vía bus (misma transacción que la confirmación)
+    if let Some(data) = redemption_data {
+        let event = DomainEvent::RedemptionConfirmed {
+            user_id: data.user_id as i64,
+            redemption_id,
+            redemption_code: redemption.redemption_code.clone(),
+            offer_name: data.offer_name.unwrap_or_default(),
+            merchant_id: data.merchant_id,
+            confirmed_by: merchant.merchant_name.clone(),
+        };
+        EventBus::publish(&mut *tx, &event).await.map_err(|e| {
+            error!("Failed to publish redemption.confirmed: {}", e);
+            ApiError::InternalError("Error al confirmar redención".to_string())
+        })?;
     }
     
+    // Commit transaction
+    tx.commit().await.map_err(|e| {
+        error!("Failed to commit transaction: {}", e);
+        ApiError::InternalError("Error al guardar confirmación".to_string())
+    })?;
+    
+    info!("Redemption confirmed successfully: {}", redemption.redemption_code);
+    
+    // Registrar métrica de confirmación
+    record_redemption_confirmed(&merchant.sub, "standard");
+    
     Ok(Json(ConfirmationResponse {
         success: true,
         message: "Redención confirmada exitosamente".to_string(),
@@ -585,6 +601,88 @@ pub async fn confirm_redemption(
     }))
 }
 
+/// IP del cliente (primer valor de X-Forwarded-For o X-Real-IP)
+fn extract_client_ip(headers: &HeaderMap) -> Option<String> {
+    headers.get("x-forwarded-for")
+        .or_else(|| headers.get("x-real-ip"))
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.split(',').next().unwrap_or(s).trim().to_string())
+}
+
+/// Verifica si la oferta de una redención es canjeable en la sucursal del token.
+/// Ofertas sin filas en rewards.offer_branches son válidas en todas las sucursales.
+async fn redemption_available_at_branch(
+    state: &AppState,
+    redemption_id: &str,
+    branch_id: Option<Uuid>,
+) -> Result<bool, ApiError> {
+    let Some(branch_id) = branch_id else {
+        return Ok(true);
+    };
+    
+    sqlx::query_scalar(
+        r#"
+        SELECT 
+            NOT EXISTS (SELECT 1 FROM rewards.offer_branches ob WHERE ob.offer_id = ur.offer_id)
+            OR EXISTS (
+                SELECT 1 FROM rewards.offer_branches ob 
+                WHERE ob.offer_id = ur.offer_id AND ob.branch_id = $2
+            )
+        FROM rewards.user_redemptions ur
+        WHERE ur.redemption_id = $1::uuid
+        "#
+    )
+    .bind(redemption_id)
+    .bind(branch_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map(|allowed: Option<bool>| allowed.unwrap_or(false))
+    .map_err(|e| {
+        error!("Database error checking branch availability: {}", e);
+        ApiError::InternalError("Error al validar sucursal".to_string())
+    })
+}
+
+/// Registra una acción del comercio sobre una redención, incluyendo el empleado y sucursal
+async fn record_redemption_audit<'e, E>(
+    executor: E,
+    redemption_id: Uuid,
+    action_type: &str,
+    merchant: &MerchantClaims,
+    client_ip: Option<&str>,
+    success: bool,
+    error_message: Option<&str>,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO rewards.redemption_audit_log (
+            redemption_id, action_type, performed_by, merchant_id,
+            ip_address, success, error_message,
+            staff_id, branch_id, staff_role, api_key_id
+        )
+        VALUES ($1, $2, $3, $4, $5::inet, $6, $7, $8, $9, $10, $11)
+        "#
+    )
+    .bind(redemption_id)
+    .bind(action_type)
+    .bind(merchant.actor_label())
+    .bind(merchant.get_merchant_id())
+    .bind(client_ip)
+    .bind(success)
+    .bind(error_message)
+    .bind(merchant.staff_id)
+    .bind(merchant.branch_id)
+    .bind(merchant.staff_id.map(|_| merchant.role().as_str()))
+    .bind(merchant.api_key_id)
+    .execute(executor)
+    .await?;
+    
+    Ok(())
+}
+
 #[derive(Debug)]
 pub enum ApiError {
     BadRequest(String),
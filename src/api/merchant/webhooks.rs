@@ -0,0 +1,564 @@
+// ============================================================================
+// MERCHANT WEBHOOKS - Configuración, prueba, log de entregas y reenvío (replay)
+// ============================================================================
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    middleware::auth::MerchantClaims,
+    services::{get_webhook_service, WebhookEvent, WebhookService},
+    services::webhook_service::{
+        generate_webhook_secret, validate_webhook_url, SUPPORTED_EVENTS, TEST_EVENT,
+    },
+    state::AppState,
+};
+use super::permissions::{MerchantPermission, MSG_PERMISSION_DENIED};
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct WebhookConfig {
+    pub webhook_url: Option<String>,
+    pub events: Vec<String>,
+    pub enabled: bool,
+    pub has_secret: bool,
+    /// Últimos 4 caracteres del secreto, para identificarlo sin exponerlo
+    pub secret_hint: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookConfigResponse {
+    pub success: bool,
+    pub config: WebhookConfig,
+    pub supported_events: Vec<&'static str>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookConfigRequest {
+    pub webhook_url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotateSecretResponse {
+    pub success: bool,
+    /// Se muestra una sola vez
+    pub webhook_secret: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestWebhookResponse {
+    pub success: bool,
+    pub event_id: Uuid,
+    pub delivered: bool,
+    pub status_code: Option<u16>,
+    pub latency_ms: i64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveryListQuery {
+    /// pending | delivering | delivered | dead
+    pub status: Option<String>,
+    pub event_type: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DeliveryItem {
+    pub delivery_id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub next_attempt_at: chrono::DateTime<chrono::Utc>,
+    pub last_attempt_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub replay_of: Option<Uuid>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeliveryListResponse {
+    pub success: bool,
+    pub deliveries: Vec<DeliveryItem>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DeliveryAttempt {
+    pub attempt_number: Option<i32>,
+    pub success: bool,
+    pub status_code: Option<i32>,
+    pub error_message: Option<String>,
+    pub response_body: Option<String>,
+    pub response_time_ms: Option<i32>,
+    pub sent_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeliveryDetailResponse {
+    pub success: bool,
+    pub delivery: DeliveryItem,
+    pub payload: serde_json::Value,
+    pub attempts: Vec<DeliveryAttempt>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayResponse {
+    pub success: bool,
+    pub delivery_id: Uuid,
+    pub message: String,
+}
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+const VALID_STATUSES: [&str; 4] = ["pending", "delivering", "delivered", "dead"];
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn require_webhook_access(merchant: &MerchantClaims) -> Result<Uuid, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ManageWebhooks) {
+        warn!("{} attempted webhook management without permission", merchant.actor_label());
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
+    merchant.get_merchant_id()
+        .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))
+}
+
+async fn load_config(state: &AppState, merchant_id: Uuid) -> Result<WebhookConfig, ApiError> {
+    sqlx::query_as::<_, WebhookConfig>(
+        r#"
+        SELECT
+            NULLIF(webhook_url, '') as webhook_url,
+            COALESCE(webhook_events, ARRAY[]::text[]) as events,
+            COALESCE(webhook_enabled, false) as enabled,
+            COALESCE(webhook_secret, '') != '' as has_secret,
+            CASE WHEN COALESCE(webhook_secret, '') != ''
+                 THEN RIGHT(webhook_secret, 4) END as secret_hint
+        FROM rewards.merchants
+        WHERE merchant_id = $1
+        "#
+    )
+    .bind(merchant_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to load webhook config: {}", e);
+        ApiError::InternalError("Error al consultar configuración de webhook".to_string())
+    })?
+    .ok_or_else(|| ApiError::NotFound("Comercio no encontrado".to_string()))
+}
+
+fn normalize_events(events: &[String]) -> Result<Vec<String>, ApiError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for event in events {
+        let event = event.trim().to_lowercase();
+        // Se aceptan nombres cortos ("confirmed") y completos ("redemption.confirmed")
+        let full = if event.contains('.') { event } else { format!("redemption.{}", event) };
+        if !SUPPORTED_EVENTS.contains(&full.as_str()) {
+            return Err(ApiError::BadRequest(format!(
+                "Evento no soportado: {}. Use: {}", full, SUPPORTED_EVENTS.join(", ")
+            )));
+        }
+        if !normalized.contains(&full) {
+            normalized.push(full);
+        }
+    }
+    Ok(normalized)
+}
+
+// ============================================================================
+// Configuration Endpoints
+// ============================================================================
+
+/// Configuración actual del webhook (sin exponer el secreto)
+/// GET /api/v1/merchant/webhooks/config
+pub async fn get_webhook_config(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+) -> Result<Json<WebhookConfigResponse>, ApiError> {
+    let merchant_id = require_webhook_access(&merchant)?;
+    let config = load_config(&state, merchant_id).await?;
+
+    Ok(Json(WebhookConfigResponse {
+        success: true,
+        config,
+        supported_events: SUPPORTED_EVENTS.to_vec(),
+    }))
+}
+
+/// Actualizar URL, eventos suscritos y estado del webhook
+/// PUT /api/v1/merchant/webhooks/config
+pub async fn update_webhook_config(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Json(payload): Json<UpdateWebhookConfigRequest>,
+) -> Result<Json<WebhookConfigResponse>, ApiError> {
+    let merchant_id = require_webhook_access(&merchant)?;
+
+    let webhook_url = match payload.webhook_url {
+        Some(ref url) => {
+            validate_webhook_url(url).map_err(ApiError::BadRequest)?;
+            Some(url.trim().to_string())
+        }
+        None => None,
+    };
+    let events = match payload.events {
+        Some(ref events) => Some(normalize_events(events)?),
+        None => None,
+    };
+
+    let current = load_config(&state, merchant_id).await?;
+    if payload.enabled == Some(true) {
+        if webhook_url.is_none() && current.webhook_url.is_none() {
+            return Err(ApiError::BadRequest(
+                "Configura una URL antes de activar el webhook".to_string()
+            ));
+        }
+        if !current.has_secret {
+            return Err(ApiError::BadRequest(
+                "Genera un secreto (POST /webhooks/secret/rotate) antes de activar el webhook".to_string()
+            ));
+        }
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE rewards.merchants
+        SET webhook_url = COALESCE($2, webhook_url),
+            webhook_events = COALESCE($3, webhook_events),
+            webhook_enabled = COALESCE($4, webhook_enabled),
+            updated_at = NOW()
+        WHERE merchant_id = $1
+        "#
+    )
+    .bind(merchant_id)
+    .bind(&webhook_url)
+    .bind(&events)
+    .bind(payload.enabled)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to update webhook config: {}", e);
+        ApiError::InternalError("Error al actualizar configuración de webhook".to_string())
+    })?;
+
+    info!("{} updated webhook config for merchant {}", merchant.actor_label(), merchant_id);
+
+    let config = load_config(&state, merchant_id).await?;
+    Ok(Json(WebhookConfigResponse {
+        success: true,
+        config,
+        supported_events: SUPPORTED_EVENTS.to_vec(),
+    }))
+}
+
+/// Generar un nuevo secreto de firma (reemplaza al anterior de inmediato)
+/// POST /api/v1/merchant/webhooks/secret/rotate
+pub async fn rotate_webhook_secret(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+) -> Result<Json<RotateSecretResponse>, ApiError> {
+    let merchant_id = require_webhook_access(&merchant)?;
+    let secret = generate_webhook_secret();
+
+    let result = sqlx::query(
+        r#"
+        UPDATE rewards.merchants
+        SET webhook_secret = $2, updated_at = NOW()
+        WHERE merchant_id = $1
+        "#
+    )
+    .bind(merchant_id)
+    .bind(&secret)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to rotate webhook secret: {}", e);
+        ApiError::InternalError("Error al generar secreto".to_string())
+    })?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("Comercio no encontrado".to_string()));
+    }
+
+    info!("{} rotated webhook secret for merchant {}", merchant.actor_label(), merchant_id);
+
+    Ok(Json(RotateSecretResponse {
+        success: true,
+        webhook_secret: secret,
+        message: "Guarda este secreto: no se volverá a mostrar".to_string(),
+    }))
+}
+
+/// Enviar un evento de prueba firmado y devolver la respuesta del servidor del merchant
+/// POST /api/v1/merchant/webhooks/test
+pub async fn test_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+) -> Result<Json<TestWebhookResponse>, ApiError> {
+    let merchant_id = require_webhook_access(&merchant)?;
+
+    let service = get_webhook_service()
+        .unwrap_or_else(|| Arc::new(WebhookService::new(state.db_pool.clone())));
+
+    let webhook = service
+        .get_merchant_webhook(merchant_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to load merchant webhook: {}", e);
+            ApiError::InternalError("Error al consultar configuración de webhook".to_string())
+        })?
+        .ok_or_else(|| ApiError::BadRequest(
+            "Configura la URL y el secreto del webhook antes de probarlo".to_string()
+        ))?;
+
+    // La URL pudo guardarse por SQL antes de existir la validación
+    validate_webhook_url(&webhook.webhook_url).map_err(ApiError::BadRequest)?;
+
+    let event = WebhookEvent::test_ping(merchant_id);
+    let body = serde_json::to_string(&event).map_err(|e| {
+        error!("Failed to serialize test event: {}", e);
+        ApiError::InternalError("Error al generar evento de prueba".to_string())
+    })?;
+
+    let outcome = service.post_signed(&webhook, TEST_EVENT, &body).await;
+
+    info!(
+        "{} sent test webhook to merchant {}: status={:?}, {}ms",
+        merchant.actor_label(), merchant_id, outcome.status_code, outcome.duration_ms
+    );
+
+    Ok(Json(TestWebhookResponse {
+        success: true,
+        event_id: event.id,
+        delivered: outcome.success,
+        status_code: outcome.status_code,
+        latency_ms: outcome.duration_ms,
+        error: outcome.error,
+    }))
+}
+
+// ============================================================================
+// Delivery Log Endpoints
+// ============================================================================
+
+/// Listar entregas de webhooks del comercio
+/// GET /api/v1/merchant/webhooks/deliveries?status=dead&event_type=redemption.confirmed
+pub async fn list_deliveries(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Query(query): Query<DeliveryListQuery>,
+) -> Result<Json<DeliveryListResponse>, ApiError> {
+    let merchant_id = require_webhook_access(&merchant)?;
+
+    if let Some(ref status) = query.status {
+        if !VALID_STATUSES.contains(&status.as_str()) {
+            return Err(ApiError::BadRequest(
+                "Estado inválido. Use pending, delivering, delivered o dead".to_string()
+            ));
+        }
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let deliveries: Vec<DeliveryItem> = sqlx::query_as(
+        r#"
+        SELECT
+            delivery_id, event_id, event_type, status, attempts, max_attempts,
+            next_attempt_at, last_attempt_at, last_status_code, last_error,
+            delivered_at, replay_of, created_at
+        FROM rewards.webhook_outbox
+        WHERE merchant_id = $1
+          AND ($2::text IS NULL OR status = $2)
+          AND ($3::text IS NULL OR event_type = $3)
+        ORDER BY created_at DESC
+        LIMIT $4 OFFSET $5
+        "#
+    )
+    .bind(merchant_id)
+    .bind(&query.status)
+    .bind(&query.event_type)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to list webhook deliveries: {}", e);
+        ApiError::InternalError("Error al listar entregas de webhooks".to_string())
+    })?;
+
+    let total: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*)
+        FROM rewards.webhook_outbox
+        WHERE merchant_id = $1
+          AND ($2::text IS NULL OR status = $2)
+          AND ($3::text IS NULL OR event_type = $3)
+        "#
+    )
+    .bind(merchant_id)
+    .bind(&query.status)
+    .bind(&query.event_type)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to count webhook deliveries: {}", e);
+        ApiError::InternalError("Error al listar entregas de webhooks".to_string())
+    })?;
+
+    Ok(Json(DeliveryListResponse {
+        success: true,
+        deliveries,
+        total,
+        limit,
+        offset,
+    }))
+}
+
+/// Detalle de una entrega con payload e intentos
+/// GET /api/v1/merchant/webhooks/deliveries/:id
+pub async fn get_delivery(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Path(delivery_id): Path<Uuid>,
+) -> Result<Json<DeliveryDetailResponse>, ApiError> {
+    let merchant_id = require_webhook_access(&merchant)?;
+
+    let delivery: DeliveryItem = sqlx::query_as(
+        r#"
+        SELECT
+            delivery_id, event_id, event_type, status, attempts, max_attempts,
+            next_attempt_at, last_attempt_at, last_status_code, last_error,
+            delivered_at, replay_of, created_at
+        FROM rewards.webhook_outbox
+        WHERE delivery_id = $1 AND merchant_id = $2
+        "#
+    )
+    .bind(delivery_id)
+    .bind(merchant_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch webhook delivery: {}", e);
+        ApiError::InternalError("Error al consultar entrega".to_string())
+    })?
+    .ok_or_else(|| ApiError::NotFound("Entrega no encontrada".to_string()))?;
+
+    let payload: serde_json::Value = sqlx::query_scalar(
+        "SELECT payload FROM rewards.webhook_outbox WHERE delivery_id = $1"
+    )
+    .bind(delivery_id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch webhook payload: {}", e);
+        ApiError::InternalError("Error al consultar entrega".to_string())
+    })?;
+
+    let attempts: Vec<DeliveryAttempt> = sqlx::query_as(
+        r#"
+        SELECT attempt_number, success, status_code, error_message,
+               response_body, response_time_ms, sent_at
+        FROM rewards.webhook_logs
+        WHERE delivery_id = $1
+        ORDER BY attempt_number
+        "#
+    )
+    .bind(delivery_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch webhook attempts: {}", e);
+        ApiError::InternalError("Error al consultar intentos".to_string())
+    })?;
+
+    Ok(Json(DeliveryDetailResponse {
+        success: true,
+        delivery,
+        payload,
+        attempts,
+    }))
+}
+
+/// Reenviar una entrega (crea una nueva con el mismo event_id y payload)
+/// POST /api/v1/merchant/webhooks/deliveries/:id/replay
+pub async fn replay_delivery(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Path(delivery_id): Path<Uuid>,
+) -> Result<Json<ReplayResponse>, ApiError> {
+    let merchant_id = require_webhook_access(&merchant)?;
+
+    let new_id = WebhookService::replay(&state.db_pool, merchant_id, delivery_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to replay webhook delivery {}: {}", delivery_id, e);
+            ApiError::InternalError("Error al reenviar webhook".to_string())
+        })?
+        .ok_or_else(|| ApiError::NotFound("Entrega no encontrada".to_string()))?;
+
+    info!("{} replayed webhook delivery {} as {}", merchant.actor_label(), delivery_id, new_id);
+
+    Ok(Json(ReplayResponse {
+        success: true,
+        delivery_id: new_id,
+        message: "Webhook encolado para reenvío".to_string(),
+    }))
+}
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    InternalError(String),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        };
+
+        let body = Json(serde_json::json!({
+            "success": false,
+            "error": message,
+        }));
+
+        (status, body).into_response()
+    }
+}
@@ -48,8 +48,12 @@ pub mod rewards_v4; // Nuevo módulo para rewards y métricas de facturas
 pub mod userdata_v4; // Nuevo módulo para datos de usuario desde dim_users
 pub mod rewards_history_v4; // Nuevo módulo para historial de acumulaciones y redenciones
 pub mod surveys_v4; // Nuevo módulo para encuestas y surveys
+pub mod survey_results_v4; // Resultados agregados de encuestas (admins y patrocinadores)
 pub mod tinder_v4; // Módulo Lumimatch - preguntas tipo Tinder
 pub mod gamification_v4; // Nuevo módulo para gamificación completa
+pub mod referrals_v4; // Programa de referidos (códigos, atribución y premios)
+pub mod raffles_v4; // Tómbolas con boletos en Lümis y sorteo verificable
+pub mod trivia_v4; // Trivias diarias con respuestas cronometradas
 pub mod ocr_iterative_v4; // Nuevo módulo para OCR iterativo
 pub mod upload_ocr_v4; // Nuevo módulo para upload OCR endpoint
 pub mod upload_ocr_retry_v4; // Nuevo módulo para retry de OCR con campos específicos
@@ -64,6 +68,7 @@ pub mod integrity_summary_v4; // Nuevo módulo para validación de integridad di
 pub mod unified_password; // Nuevo módulo para gestión unificada de contraseñas
 pub mod ofertasws_v4; // Nuevo módulo para ofertas WS con cache Redis
 pub mod notifications_v4; // Sistema de notificaciones in-app y push
+pub mod push_campaigns_v4; // Campañas de push segmentadas (admin)
 pub mod admin_v4; // Admin endpoints - DGI captcha configuration
 pub mod ask_ai_v4; // AI endpoint for natural language queries
 pub mod interpret_results_v4; // AI interpretation of query results
@@ -122,6 +127,7 @@ fn create_public_v4_router() -> Router<Arc<AppState>> {
         .merge(register_v4::create_register_v4_router())
         .merge(user_registration_v4::create_user_registration_v4_public_router())
         .merge(email_check_v4::create_email_check_v4_router())
+        .merge(raffles_v4::create_raffles_v4_public_router())
         .nest("/api/v4/users", unified_password::create_unified_verification_v4_router())  // Unified verification system
         .merge(unified_password::create_unified_password_v4_router())
         // NEW: Add robust invoice processing API (public for WhatsApp integration)
@@ -145,11 +151,16 @@ fn create_protected_v4_router() -> Router<Arc<AppState>> {
         .merge(userdata_v4::create_userdata_v4_router())
         .merge(rewards_history_v4::create_rewards_history_v4_router())
         .merge(surveys_v4::create_surveys_v4_router())
+        .merge(survey_results_v4::create_survey_results_v4_router())
         .merge(gamification_v4::create_gamification_v4_router())
+        .merge(referrals_v4::create_referrals_v4_router())
+        .merge(raffles_v4::create_raffles_v4_router())
+        .merge(trivia_v4::create_trivia_v4_router())
         .merge(tinder_v4::create_tinder_router())
         .nest("/api/v4/rewards", rewards_v4::create_rewards_v4_router())
         // Notifications system endpoints
         .nest("/api/v4/notifications", notifications_v4::create_notifications_v4_router())
+        .merge(push_campaigns_v4::create_push_campaigns_v4_router())
         // ADD: Protected URL processing endpoint with JWT authentication
         .route("/api/v4/invoices/process-from-url", post(url_processing_v4::process_url_handler))
         // ADD: Protected CUFE processing endpoint (for OCR-detected CUFE codes)
@@ -157,6 +168,8 @@ fn create_protected_v4_router() -> Router<Arc<AppState>> {
         // Daily Game endpoints (protected)
         .route("/api/v4/daily-game/claim", post(daily_game::handle_claim))
         .route("/api/v4/daily-game/status", get(daily_game::handle_status))
+        .route("/api/v4/daily-game/admin/prize-config", get(daily_game::admin::admin_list_prize_config).put(daily_game::admin::admin_upsert_prize_config))
+        .route("/api/v4/daily-game/admin/stats", get(daily_game::admin::admin_daily_game_stats))
         // Ofertas WS endpoints
         .route("/api/v4/ofertasws", get(ofertasws_v4::get_ofertasws))
         .route("/api/v4/ofertasws/refresh", post(ofertasws_v4::refresh_ofertasws_cache))
@@ -49,7 +49,7 @@ pub async fn get_user_movements_summary(
         .bind(user_id)
         .fetch_optional(&state.db_pool)
         .await
-        .map_err(|e| ApiError::database_error(&format!("Query execution failed: {}", e)))?;
+        .map_err(|e| ApiError::database_failure("Query execution failed", e))?;
 
     let mut response_data = summary_result.unwrap_or(MovementsSummaryResponse {
         total_transactions: 0,
@@ -106,7 +106,7 @@ pub async fn get_recent_movements(
         .bind(user_id)
         .fetch_all(&state.db_pool)
         .await
-        .map_err(|e| ApiError::database_error(&format!("Query execution failed: {}", e)))?;
+        .map_err(|e| ApiError::database_failure("Query execution failed", e))?;
 
     let execution_time = start_time.elapsed().as_millis() as u64;
     
@@ -8,6 +8,7 @@
 //! - Dismiss/delete notifications (soft-delete)
 //! - Badge count for unread notifications
 //! - FCM token registration and management
+//! - Push preferences per category and quiet hours
 //!
 //! Security:
 //! - All endpoints require JWT authentication
@@ -28,6 +29,10 @@ use chrono::{DateTime, Utc};
 use crate::{
     middleware::CurrentUser,
     api::common::{ApiError, ApiResponse},
+    domains::notifications::preferences::{
+        Delivery, NotificationPreferences, PreferenceError, PreferenceService, PreferencesUpdate,
+    },
+    shared::i18n::Text,
     AppState,
 };
 
@@ -212,18 +217,17 @@ pub struct RegisterTokenRequest {
 }
 
 impl RegisterTokenRequest {
-    pub fn validate(&self) -> Result<(), String> {
+    pub fn validate(&self) -> Result<(), Text> {
         if self.fcm_token.is_empty() {
-            return Err("fcm_token is required".to_string());
+            return Err(Text::new("api.validation.required").with("field", "fcm_token"));
         }
         if self.fcm_token.len() > 500 {
-            return Err("fcm_token is too long (max 500 chars)".to_string());
+            return Err(Text::new("api.validation.max_length").with("field", "fcm_token").with("max", 500));
         }
         if !VALID_PLATFORMS.contains(&self.platform.as_str()) {
-            return Err(format!(
-                "Invalid platform '{}'. Valid platforms: {:?}",
-                self.platform, VALID_PLATFORMS
-            ));
+            return Err(Text::new("api.validation.one_of")
+                .with("field", "platform")
+                .with("values", VALID_PLATFORMS.join(", ")));
         }
         Ok(())
     }
@@ -265,6 +269,8 @@ pub fn create_notifications_v4_router() -> Router<Arc<AppState>> {
         // Device token endpoints (under /devices prefix)
         .route("/devices/fcm-token", post(register_fcm_token))
         .route("/devices/fcm-token", delete(remove_fcm_token))
+        // Push preferences and quiet hours
+        .route("/preferences", get(get_preferences).put(update_preferences))
 }
 
 // ============================================================================
@@ -293,11 +299,11 @@ pub async fn list_notifications(
     // Validate type if provided
     if let Some(ref t) = params.notification_type {
         if !VALID_TYPES.contains(&t.as_str()) {
-            return Err(ApiError {
-                code: "INVALID_TYPE".to_string(),
-                message: format!("Invalid notification type '{}'. Valid types: {:?}", t, VALID_TYPES),
-                details: None,
-            });
+            return Err(ApiError::localized(
+                "INVALID_TYPE",
+                "api.validation.one_of",
+                &[("field", &"type"), ("values", &VALID_TYPES.join(", "))],
+            ));
         }
     }
     
@@ -347,11 +353,7 @@ pub async fn list_notifications(
     )
     .fetch_all(&state.db_pool)
     .await
-    .map_err(|e| ApiError {
-        code: "DATABASE_ERROR".to_string(),
-        message: format!("Failed to fetch notifications: {}", e),
-        details: None,
-    })?;
+    .map_err(|e| ApiError::database_failure("Failed to fetch notifications", e))?;
     
     // Extract totals from first row (or default to 0 if empty)
     let (total, unread_count) = rows.first()
@@ -421,11 +423,7 @@ pub async fn get_badge_count(
     )
     .fetch_all(&state.db_pool)
     .await
-    .map_err(|e| ApiError {
-        code: "DATABASE_ERROR".to_string(),
-        message: format!("Failed to count notifications: {}", e),
-        details: None,
-    })?;
+    .map_err(|e| ApiError::database_failure("Failed to count notifications", e))?;
     
     let mut by_type = serde_json::Map::new();
     let mut total_unread: i64 = 0;
@@ -479,11 +477,7 @@ pub async fn mark_as_read(
     )
     .fetch_optional(&state.db_pool)
     .await
-    .map_err(|e| ApiError {
-        code: "DATABASE_ERROR".to_string(),
-        message: format!("Failed to update notification: {}", e),
-        details: None,
-    })?;
+    .map_err(|e| ApiError::database_failure("Failed to update notification", e))?;
     
     match result {
         Some(row) => {
@@ -495,11 +489,7 @@ pub async fn mark_as_read(
             let elapsed = start.elapsed().as_millis() as u64;
             Ok(Json(ApiResponse::success(response, request_id, Some(elapsed), false)))
         }
-        None => Err(ApiError {
-            code: "NOTIFICATION_NOT_FOUND".to_string(),
-            message: "Notificación no encontrada".to_string(),
-            details: None,
-        }),
+        None => Err(ApiError::localized("NOTIFICATION_NOT_FOUND", "api.not_found.notification", &[])),
     }
 }
 
@@ -545,11 +535,7 @@ pub async fn mark_all_as_read(
             )
             .fetch_one(&state.db_pool)
             .await
-            .map_err(|e| ApiError {
-                code: "DATABASE_ERROR".to_string(),
-                message: format!("Failed to mark notifications as read: {}", e),
-                details: None,
-            })?
+            .map_err(|e| ApiError::database_failure("Failed to mark notifications as read", e))?
         } else {
             sqlx::query_scalar!(
                 r#"
@@ -570,11 +556,7 @@ pub async fn mark_all_as_read(
             )
             .fetch_one(&state.db_pool)
             .await
-            .map_err(|e| ApiError {
-                code: "DATABASE_ERROR".to_string(),
-                message: format!("Failed to mark notifications as read: {}", e),
-                details: None,
-            })?
+            .map_err(|e| ApiError::database_failure("Failed to mark notifications as read", e))?
         }
     } else if let Some(ref before) = payload.before {
         sqlx::query_scalar!(
@@ -596,11 +578,7 @@ pub async fn mark_all_as_read(
         )
         .fetch_one(&state.db_pool)
         .await
-        .map_err(|e| ApiError {
-            code: "DATABASE_ERROR".to_string(),
-            message: format!("Failed to mark notifications as read: {}", e),
-            details: None,
-        })?
+        .map_err(|e| ApiError::database_failure("Failed to mark notifications as read", e))?
     } else {
         sqlx::query_scalar!(
             r#"
@@ -619,11 +597,7 @@ pub async fn mark_all_as_read(
         )
         .fetch_one(&state.db_pool)
         .await
-        .map_err(|e| ApiError {
-            code: "DATABASE_ERROR".to_string(),
-            message: format!("Failed to mark notifications as read: {}", e),
-            details: None,
-        })?
+        .map_err(|e| ApiError::database_failure("Failed to mark notifications as read", e))?
     };
     
     let response = MarkAllReadResponse {
@@ -660,11 +634,7 @@ pub async fn dismiss_notification(
     )
     .fetch_optional(&state.db_pool)
     .await
-    .map_err(|e| ApiError {
-        code: "DATABASE_ERROR".to_string(),
-        message: format!("Failed to dismiss notification: {}", e),
-        details: None,
-    })?;
+    .map_err(|e| ApiError::database_failure("Failed to dismiss notification", e))?;
     
     match result {
         Some(row) => {
@@ -675,11 +645,7 @@ pub async fn dismiss_notification(
             let elapsed = start.elapsed().as_millis() as u64;
             Ok(Json(ApiResponse::success(response, request_id, Some(elapsed), false)))
         }
-        None => Err(ApiError {
-            code: "NOTIFICATION_NOT_FOUND".to_string(),
-            message: "Notificación no encontrada".to_string(),
-            details: None,
-        }),
+        None => Err(ApiError::localized("NOTIFICATION_NOT_FOUND", "api.not_found.notification", &[])),
     }
 }
 
@@ -696,11 +662,7 @@ pub async fn register_fcm_token(
     let user_id = current_user.user_id as i64;
     
     // Validate request
-    payload.validate().map_err(|e| ApiError {
-        code: "INVALID_REQUEST".to_string(),
-        message: e,
-        details: None,
-    })?;
+    payload.validate().map_err(|e| ApiError::new("INVALID_REQUEST", &e.to_string()))?;
     
     // Use ON CONFLICT to handle race conditions
     // The trigger handles deactivating tokens from other users
@@ -728,11 +690,7 @@ pub async fn register_fcm_token(
     )
     .fetch_one(&state.db_pool)
     .await
-    .map_err(|e| ApiError {
-        code: "DATABASE_ERROR".to_string(),
-        message: format!("Failed to register token: {}", e),
-        details: None,
-    })?;
+    .map_err(|e| ApiError::database_failure("Failed to register token", e))?;
     
     let response = RegisterTokenResponse {
         registered: true,
@@ -769,11 +727,7 @@ pub async fn remove_fcm_token(
     )
     .fetch_optional(&state.db_pool)
     .await
-    .map_err(|e| ApiError {
-        code: "DATABASE_ERROR".to_string(),
-        message: format!("Failed to remove token: {}", e),
-        details: None,
-    })?;
+    .map_err(|e| ApiError::database_failure("Failed to remove token", e))?;
     
     let response = RemoveTokenResponse {
         removed: result.is_some(),
@@ -783,6 +737,52 @@ pub async fn remove_fcm_token(
     Ok(Json(ApiResponse::success(response, request_id, Some(elapsed), false)))
 }
 
+/// GET /api/v4/notifications/preferences
+/// Push preferences per category and quiet hours (defaults if never changed)
+#[axum::debug_handler]
+pub async fn get_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> ResponseJson<NotificationPreferences> {
+    let start = std::time::Instant::now();
+    let request_id = uuid::Uuid::new_v4().to_string();
+
+    let preferences = PreferenceService::new(state.db_pool.clone())
+        .get(current_user.user_id)
+        .await
+        .map_err(preference_error)?;
+
+    let elapsed = start.elapsed().as_millis() as u64;
+    Ok(Json(ApiResponse::success(preferences, request_id, Some(elapsed), false)))
+}
+
+/// PUT /api/v4/notifications/preferences
+/// Partial update; `"quiet_hours": null` turns quiet hours off
+#[axum::debug_handler]
+pub async fn update_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(payload): Json<PreferencesUpdate>,
+) -> ResponseJson<NotificationPreferences> {
+    let start = std::time::Instant::now();
+    let request_id = uuid::Uuid::new_v4().to_string();
+
+    let preferences = PreferenceService::new(state.db_pool.clone())
+        .update(current_user.user_id, &payload)
+        .await
+        .map_err(preference_error)?;
+
+    let elapsed = start.elapsed().as_millis() as u64;
+    Ok(Json(ApiResponse::success(preferences, request_id, Some(elapsed), false)))
+}
+
+fn preference_error(err: PreferenceError) -> ApiError {
+    match err {
+        PreferenceError::Invalid(reason) => ApiError::new("INVALID_REQUEST", &reason.to_string()),
+        PreferenceError::Database(e) => ApiError::database_failure("Failed to process notification preferences", e),
+    }
+}
+
 // ============================================================================
 // HELPER FUNCTIONS FOR OTHER MODULES
 // ============================================================================
@@ -801,6 +801,20 @@ pub async fn create_notification_from_rust(
     idempotency_key: Option<&str>,
     send_push: bool,
 ) -> Result<Option<i64>, sqlx::Error> {
+    // Preferences and quiet hours only affect the push; the in-app notification is always created
+    let delivery = if send_push {
+        PreferenceService::new(pool.clone())
+            .decide(user_id, notification_type, priority, Utc::now())
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Notification preferences check failed for user {}: {}", user_id, e);
+                Delivery::Now
+            })
+    } else {
+        Delivery::Now
+    };
+    let send_push = send_push && !matches!(delivery, Delivery::Suppress(_));
+
     let result = sqlx::query_scalar!(
         r#"
         SELECT public.create_notification(
@@ -830,6 +844,21 @@ pub async fn create_notification_from_rust(
     )
     .fetch_one(pool)
     .await?;
+
+    // Quiet hours: the push waits in the queue until the window ends
+    if let (Some(notification_id), Delivery::Defer(until)) = (result, delivery) {
+        sqlx::query(
+            r#"
+            UPDATE public.notification_push_queue
+            SET next_attempt_at = $2
+            WHERE notification_id = $1 AND status = 'pending'
+            "#,
+        )
+        .bind(notification_id)
+        .bind(until)
+        .execute(pool)
+        .await?;
+    }
     
     Ok(result)
 }
@@ -0,0 +1,216 @@
+use axum::{
+    extract::{Path, State, Extension},
+    Json,
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+use chrono::Utc;
+
+use crate::shared::admin::is_admin;
+use crate::{
+    middleware::CurrentUser,
+    api::common::{ApiError, ApiResponse},
+    domains::notifications::campaigns::{
+        CampaignAction, CampaignError, CampaignReport, CampaignService, CampaignStats, DryRun, NewCampaign,
+        PushCampaign,
+    },
+    AppState,
+};
+
+// Response wrapper for JSON
+type ResponseJson<T> = Result<Json<ApiResponse<T>>, ApiError>;
+
+// ============================================================================
+// API HANDLERS (admin)
+// ============================================================================
+
+/// Schedule a segmented push campaign; `send_at` is Panama local time
+#[axum::debug_handler]
+pub async fn create_campaign(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<NewCampaign>,
+) -> ResponseJson<PushCampaign> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let campaign = CampaignService::new(state.db_pool.clone())
+        .create(&request, current_user.user_id)
+        .await
+        .map_err(campaign_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(campaign, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Audience size and estimated duration without creating or sending anything
+#[axum::debug_handler]
+pub async fn dry_run_campaign(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<NewCampaign>,
+) -> ResponseJson<DryRun> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let dry_run = CampaignService::new(state.db_pool.clone())
+        .dry_run(&request)
+        .await
+        .map_err(campaign_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(dry_run, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Latest campaigns, newest send time first
+#[axum::debug_handler]
+pub async fn list_campaigns(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> ResponseJson<Vec<PushCampaign>> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let campaigns = CampaignService::new(state.db_pool.clone())
+        .list()
+        .await
+        .map_err(campaign_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(campaigns, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Campaign detail with delivery, open and conversion stats
+#[axum::debug_handler]
+pub async fn get_campaign(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(campaign_id): Path<Uuid>,
+) -> ResponseJson<CampaignReport> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let report = CampaignService::new(state.db_pool.clone())
+        .report(campaign_id)
+        .await
+        .map_err(campaign_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(report, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Delivery, open and conversion stats only (cheap to poll while sending)
+#[axum::debug_handler]
+pub async fn get_campaign_stats(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(campaign_id): Path<Uuid>,
+) -> ResponseJson<CampaignStats> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let service = CampaignService::new(state.db_pool.clone());
+    service.get(campaign_id).await.map_err(campaign_error)?;
+    let stats = service.stats(campaign_id).await.map_err(campaign_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(stats, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Pause a scheduled campaign or stop one mid-send
+#[axum::debug_handler]
+pub async fn pause_campaign(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(campaign_id): Path<Uuid>,
+) -> ResponseJson<PushCampaign> {
+    apply_action(state, current_user, campaign_id, CampaignAction::Pause).await
+}
+
+/// Resume a paused campaign; pending recipients go out on the next run
+#[axum::debug_handler]
+pub async fn resume_campaign(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(campaign_id): Path<Uuid>,
+) -> ResponseJson<PushCampaign> {
+    apply_action(state, current_user, campaign_id, CampaignAction::Resume).await
+}
+
+/// Cancel a campaign; recipients not reached yet are marked cancelled
+#[axum::debug_handler]
+pub async fn cancel_campaign(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(campaign_id): Path<Uuid>,
+) -> ResponseJson<PushCampaign> {
+    apply_action(state, current_user, campaign_id, CampaignAction::Cancel).await
+}
+
+async fn apply_action(
+    state: Arc<AppState>,
+    current_user: CurrentUser,
+    campaign_id: Uuid,
+    action: CampaignAction,
+) -> ResponseJson<PushCampaign> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let campaign = CampaignService::new(state.db_pool.clone())
+        .apply(campaign_id, action)
+        .await
+        .map_err(campaign_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(campaign, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+fn require_admin(current_user: &CurrentUser) -> Result<(), ApiError> {
+    if !is_admin(current_user.user_id) {
+        tracing::warn!("User {} attempted push campaign admin access", current_user.user_id);
+        return Err(ApiError::forbidden());
+    }
+    Ok(())
+}
+
+fn campaign_error(err: CampaignError) -> ApiError {
+    match err {
+        CampaignError::NotFound => ApiError::localized("NOT_FOUND", "api.not_found.push_campaign", &[]),
+        CampaignError::Invalid(_) => ApiError::validation_error(&err.to_string()),
+        CampaignError::InvalidState { .. } => ApiError::new("CONFLICT", &err.to_string()),
+        CampaignError::Database(e) => {
+            tracing::error!("Push campaign database error: {}", e);
+            ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
+        }
+    }
+}
+
+// ============================================================================
+// ROUTER CREATION
+// ============================================================================
+
+/// Create router for push campaign endpoints (admin only)
+pub fn create_push_campaigns_v4_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/v4/push-campaigns", get(list_campaigns).post(create_campaign))
+        .route("/api/v4/push-campaigns/dry-run", post(dry_run_campaign))
+        .route("/api/v4/push-campaigns/:campaign_id", get(get_campaign))
+        .route("/api/v4/push-campaigns/:campaign_id/stats", get(get_campaign_stats))
+        .route("/api/v4/push-campaigns/:campaign_id/pause", post(pause_campaign))
+        .route("/api/v4/push-campaigns/:campaign_id/resume", post(resume_campaign))
+        .route("/api/v4/push-campaigns/:campaign_id/cancel", post(cancel_campaign))
+}
@@ -66,11 +66,11 @@ pub async fn qr_detect(
     let mut image_data: Option<Vec<u8>> = None;
     
     while let Some(field) = multipart.next_field().await.map_err(|e| {
-        ApiError::validation_error(&format!("Failed to read multipart: {}", e))
+        ApiError::localized("VALIDATION_ERROR", "api.upload.invalid_multipart", &[("error", &e)])
     })? {
         if field.name() == Some("image") {
             let data = field.bytes().await.map_err(|e| {
-                ApiError::validation_error(&format!("Failed to read image data: {}", e))
+                ApiError::localized("VALIDATION_ERROR", "api.upload.invalid_image", &[("error", &e)])
             })?;
             image_data = Some(data.to_vec());
             break;
@@ -78,11 +78,11 @@ pub async fn qr_detect(
     }
 
     let image_bytes = image_data.ok_or_else(|| {
-        ApiError::validation_error("No image field found in multipart data")
+        ApiError::localized("VALIDATION_ERROR", "api.upload.image_missing", &[])
     })?;
 
     if image_bytes.is_empty() {
-        return Err(ApiError::validation_error("Image data is empty"));
+        return Err(ApiError::localized("VALIDATION_ERROR", "api.upload.image_empty", &[]));
     }
 
     debug!(request_id = %request_id, size = image_bytes.len(), "📷 Image received");
@@ -0,0 +1,256 @@
+use axum::{
+    extract::{Path, State, Extension},
+    Json,
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+use chrono::Utc;
+
+use crate::shared::admin::is_admin;
+use crate::{
+    middleware::CurrentUser,
+    api::common::{ApiError, ApiResponse},
+    domains::rewards::raffle_service::{
+        self, NewRaffle, Raffle, RaffleAudit, RaffleError, RaffleProof, RaffleService, RaffleView, TicketPurchase,
+    },
+    AppState,
+};
+
+// Response wrapper for JSON
+type ResponseJson<T> = Result<Json<ApiResponse<T>>, ApiError>;
+
+// ============================================================================
+// REQUEST/RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct BuyTicketsRequest {
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+// ============================================================================
+// API HANDLERS
+// ============================================================================
+
+/// Raffles selling tickets now, closed, or drawn in the last 30 days
+#[axum::debug_handler]
+pub async fn list_raffles(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> ResponseJson<Vec<RaffleView>> {
+    let start_time = Utc::now();
+
+    let raffles = RaffleService::new(state.db_pool.clone())
+        .list(current_user.user_id as i32)
+        .await
+        .map_err(raffle_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(raffles, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Raffle detail with my tickets and, once drawn, the winners
+#[axum::debug_handler]
+pub async fn get_raffle(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(raffle_id): Path<i32>,
+) -> ResponseJson<RaffleView> {
+    let start_time = Utc::now();
+
+    let raffle = RaffleService::new(state.db_pool.clone())
+        .get(raffle_id, current_user.user_id as i32)
+        .await
+        .map_err(raffle_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(raffle, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Buy tickets with Lumis
+#[axum::debug_handler]
+pub async fn buy_tickets(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(raffle_id): Path<i32>,
+    Json(request): Json<BuyTicketsRequest>,
+) -> ResponseJson<TicketPurchase> {
+    let start_time = Utc::now();
+
+    let purchase = RaffleService::new(state.db_pool.clone())
+        .buy_tickets(raffle_id, current_user.user_id as i32, request.quantity, raffle_service::CHANNEL_API)
+        .await
+        .map_err(raffle_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(purchase, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Public verification data: commitment, revealed seed, ticket list and winners
+#[axum::debug_handler]
+pub async fn get_raffle_proof(
+    State(state): State<Arc<AppState>>,
+    Path(raffle_id): Path<i32>,
+) -> ResponseJson<RaffleProof> {
+    let start_time = Utc::now();
+
+    let proof = RaffleService::new(state.db_pool.clone())
+        .proof(raffle_id)
+        .await
+        .map_err(raffle_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(proof, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Create a raffle and publish its seed commitment (admin)
+#[axum::debug_handler]
+pub async fn admin_create_raffle(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<NewRaffle>,
+) -> ResponseJson<Raffle> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let raffle = RaffleService::new(state.db_pool.clone())
+        .create(request, current_user.user_id)
+        .await
+        .map_err(raffle_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(raffle, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Stop ticket sales and freeze the entries hash (admin)
+#[axum::debug_handler]
+pub async fn admin_close_raffle(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(raffle_id): Path<i32>,
+) -> ResponseJson<Raffle> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let raffle = RaffleService::new(state.db_pool.clone())
+        .close(raffle_id)
+        .await
+        .map_err(raffle_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(raffle, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Reveal the seed, pick the winners and notify them (admin)
+#[axum::debug_handler]
+pub async fn admin_draw_raffle(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(raffle_id): Path<i32>,
+) -> ResponseJson<RaffleProof> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let proof = RaffleService::new(state.db_pool.clone())
+        .draw(raffle_id)
+        .await
+        .map_err(raffle_error)?;
+
+    tracing::info!("Raffle {} drawn by admin {}", raffle_id, current_user.user_id);
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(proof, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Participants, Lumis collected, winners and notification status (admin)
+#[axum::debug_handler]
+pub async fn admin_audit_raffle(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(raffle_id): Path<i32>,
+) -> ResponseJson<RaffleAudit> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let audit = RaffleService::new(state.db_pool.clone())
+        .audit(raffle_id)
+        .await
+        .map_err(raffle_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(audit, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+fn require_admin(current_user: &CurrentUser) -> Result<(), ApiError> {
+    if !is_admin(current_user.user_id) {
+        tracing::warn!("User {} attempted raffle admin access", current_user.user_id);
+        return Err(ApiError::forbidden());
+    }
+    Ok(())
+}
+
+fn raffle_error(err: RaffleError) -> ApiError {
+    match err {
+        RaffleError::NotFound | RaffleError::NoneOpen => ApiError::localized("NOT_FOUND", "api.not_found.raffle", &[]),
+        RaffleError::Invalid(_) | RaffleError::InvalidQuantity(_) => ApiError::validation_error(&err.to_string()),
+        RaffleError::NotSelling
+        | RaffleError::UserLimit { .. }
+        | RaffleError::SoldOut(_)
+        | RaffleError::InvalidState { .. }
+        | RaffleError::BeaconPending(_) => ApiError::new("CONFLICT", &err.to_string()),
+        RaffleError::InsufficientBalance { .. } => ApiError::bad_request(&err.to_string()),
+        RaffleError::EntriesMismatch => {
+            tracing::error!("Raffle integrity check failed: {}", err);
+            ApiError::internal_server_error(&err.to_string())
+        }
+        RaffleError::Beacon(e) => {
+            tracing::error!("Raffle beacon error: {}", e);
+            ApiError::localized("INTERNAL_SERVER_ERROR", "api.raffle.beacon_unavailable", &[])
+        }
+        RaffleError::Database(e) => {
+            tracing::error!("Raffle database error: {}", e);
+            ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
+        }
+    }
+}
+
+// ============================================================================
+// ROUTER CREATION
+// ============================================================================
+
+/// Public router: anyone can verify a draw without an account
+pub fn create_raffles_v4_public_router() -> Router<Arc<AppState>> {
+    Router::new().route("/api/v4/raffles/:raffle_id/proof", get(get_raffle_proof))
+}
+
+/// Create router for raffle endpoints
+pub fn create_raffles_v4_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/v4/raffles", get(list_raffles))
+        .route("/api/v4/raffles/:raffle_id", get(get_raffle))
+        .route("/api/v4/raffles/:raffle_id/tickets", post(buy_tickets))
+        .route("/api/v4/raffles/admin", post(admin_create_raffle))
+        .route("/api/v4/raffles/admin/:raffle_id/close", post(admin_close_raffle))
+        .route("/api/v4/raffles/admin/:raffle_id/draw", post(admin_draw_raffle))
+        .route("/api/v4/raffles/admin/:raffle_id/audit", get(admin_audit_raffle))
+}
@@ -0,0 +1,190 @@
+use axum::{
+    extract::{Path, Query, State, Extension},
+    Json,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+use chrono::Utc;
+
+use crate::shared::admin::is_admin;
+use crate::{
+    middleware::CurrentUser,
+    api::common::{ApiError, ApiResponse},
+    domains::gamification::referral_service::{
+        ReferralError, ReferralItem, ReferralOverview, ReferralReviewItem, ReferralService,
+    },
+    AppState,
+};
+
+// Response wrapper for JSON
+type ResponseJson<T> = Result<Json<ApiResponse<T>>, ApiError>;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+// ============================================================================
+// REQUEST/RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ReferralListQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReferralListResponse {
+    pub referrals: Vec<ReferralItem>,
+    pub lumis_earned: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewReferralRequest {
+    pub approve: bool,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewReferralResponse {
+    pub referral_id: i64,
+    pub status: String,
+}
+
+// ============================================================================
+// API HANDLERS
+// ============================================================================
+
+/// My referral code, share link and totals
+#[axum::debug_handler]
+pub async fn get_my_referral(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> ResponseJson<ReferralOverview> {
+    let start_time = Utc::now();
+
+    let overview = ReferralService::new(state.db_pool.clone())
+        .overview(current_user.user_id as i32)
+        .await
+        .map_err(referral_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(overview, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Users I referred with their progress and my earnings
+#[axum::debug_handler]
+pub async fn list_my_referrals(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<ReferralListQuery>,
+) -> ResponseJson<ReferralListResponse> {
+    let start_time = Utc::now();
+
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let service = ReferralService::new(state.db_pool.clone());
+    let user_id = current_user.user_id as i32;
+    let referrals = service.list(user_id, limit, offset).await.map_err(referral_error)?;
+    let lumis_earned = service.overview(user_id).await.map_err(referral_error)?.lumis_earned;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(
+        ReferralListResponse { referrals, lumis_earned, limit, offset },
+        Uuid::new_v4().to_string(),
+        Some(execution_time.try_into().unwrap()),
+        false,
+    )))
+}
+
+/// Referrals that qualified with fraud signals (admin)
+#[axum::debug_handler]
+pub async fn admin_list_review(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<ReferralListQuery>,
+) -> ResponseJson<Vec<ReferralReviewItem>> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let items = ReferralService::new(state.db_pool.clone())
+        .pending_review(limit)
+        .await
+        .map_err(referral_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(items, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Approve (release rewards) or reject a referral under review (admin)
+#[axum::debug_handler]
+pub async fn admin_review_referral(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(referral_id): Path<i64>,
+    Json(request): Json<ReviewReferralRequest>,
+) -> ResponseJson<ReviewReferralResponse> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let status = ReferralService::new(state.db_pool.clone())
+        .review(referral_id, current_user.user_id, request.approve, request.note.as_deref())
+        .await
+        .map_err(referral_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(
+        ReviewReferralResponse { referral_id, status },
+        Uuid::new_v4().to_string(),
+        Some(execution_time.try_into().unwrap()),
+        false,
+    )))
+}
+
+fn require_admin(current_user: &CurrentUser) -> Result<(), ApiError> {
+    if !is_admin(current_user.user_id) {
+        tracing::warn!("User {} attempted referral admin access", current_user.user_id);
+        return Err(ApiError::forbidden());
+    }
+    Ok(())
+}
+
+fn referral_error(err: ReferralError) -> ApiError {
+    match err {
+        ReferralError::NotFound => ApiError::localized("NOT_FOUND", "api.not_found.referral", &[]),
+        ReferralError::NotInReview => ApiError::new("CONFLICT", &err.to_string()),
+        ReferralError::InvalidCode | ReferralError::SelfReferral | ReferralError::AlreadyAttributed => {
+            ApiError::bad_request(&err.to_string())
+        }
+        ReferralError::CodeGeneration => ApiError::internal_server_error(&err.to_string()),
+        ReferralError::Database(e) => {
+            tracing::error!("Referral database error: {}", e);
+            ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
+        }
+    }
+}
+
+// ============================================================================
+// ROUTER CREATION
+// ============================================================================
+
+/// Create router for referral endpoints
+pub fn create_referrals_v4_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/v4/referrals/me", get(get_my_referral))
+        .route("/api/v4/referrals", get(list_my_referrals))
+        .route("/api/v4/referrals/admin/review", get(admin_list_review))
+        .route("/api/v4/referrals/admin/:referral_id/review", post(admin_review_referral))
+}
@@ -0,0 +1,234 @@
+// ============================================================================
+// ACCUMULATION RULES - Explain y dry-run del motor de reglas (admin)
+// ============================================================================
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::shared::admin::is_admin;
+use crate::{
+    domains::rewards::accumulation_rules::{
+        AccumulationRule, AccumulationRuleEngine, DryRunSummary, RuleEvaluation, STACKING_STACK,
+    },
+    middleware::CurrentUser,
+    shared::i18n,
+    state::AppState,
+};
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ExplainQuery {
+    pub cufe: String,
+}
+
+/// Regla candidata. Si `id` coincide con una regla activa, la reemplaza en la proyección.
+#[derive(Debug, Deserialize)]
+pub struct CandidateRule {
+    pub id: Option<i32>,
+    pub name: String,
+    #[serde(default)]
+    pub points: i32,
+    pub points_per_dollar: Option<Decimal>,
+    pub max_points: Option<i32>,
+    #[serde(default = "default_priority")]
+    pub priority: i32,
+    pub stacking: Option<String>,
+    pub exclusive_group: Option<String>,
+    pub conditions: Option<serde_json::Value>,
+}
+
+fn default_priority() -> i32 {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DryRunRequest {
+    pub rule: CandidateRule,
+    /// YYYY-MM-DD (inclusive)
+    pub from: NaiveDate,
+    /// YYYY-MM-DD (exclusivo)
+    pub to: NaiveDate,
+    /// Máximo de facturas a evaluar (default 1000, máximo 10000)
+    pub limit: Option<i64>,
+    /// Facturas de ejemplo en la respuesta (default 20, máximo 100)
+    pub samples: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RulesResponse {
+    pub success: bool,
+    pub rules: Vec<AccumulationRule>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExplainResponse {
+    pub success: bool,
+    pub cufe: String,
+    #[serde(flatten)]
+    pub evaluation: RuleEvaluation,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DryRunResponse {
+    pub success: bool,
+    #[serde(flatten)]
+    pub summary: DryRunSummary,
+}
+
+const DEFAULT_DRY_RUN_LIMIT: i64 = 1000;
+const MAX_DRY_RUN_LIMIT: i64 = 10000;
+const DEFAULT_SAMPLES: usize = 20;
+const MAX_SAMPLES: usize = 100;
+const MAX_DRY_RUN_DAYS: i64 = 366;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn require_admin(current_user: &CurrentUser) -> Result<(), ApiError> {
+    if !is_admin(current_user.user_id) {
+        warn!("User {} attempted accumulation rules admin access", current_user.user_id);
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
+    }
+    Ok(())
+}
+
+fn db_error(e: sqlx::Error) -> ApiError {
+    error!("Accumulation rules database error: {}", e);
+    ApiError::InternalError("Error al evaluar reglas de acumulación".to_string())
+}
+
+// ============================================================================
+// Admin Endpoints
+// ============================================================================
+
+/// Reglas de factura vigentes, en orden de evaluación
+/// GET /api/v1/rewards/admin/accumulation-rules
+pub async fn admin_list_rules(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<RulesResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    let rules = AccumulationRuleEngine::new(state.db_pool.clone())
+        .load_active_rules()
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(RulesResponse { success: true, rules }))
+}
+
+/// Explica qué reglas aplican a una factura guardada (no acredita)
+/// GET /api/v1/rewards/admin/accumulation-rules/explain?cufe=...
+pub async fn admin_explain_invoice(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<ExplainQuery>,
+) -> Result<Json<ExplainResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    let evaluation = AccumulationRuleEngine::new(state.db_pool.clone())
+        .explain_invoice(&params.cufe)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ApiError::NotFound("Factura no encontrada".to_string()))?;
+
+    Ok(Json(ExplainResponse { success: true, cufe: params.cufe, evaluation }))
+}
+
+/// Prueba una regla candidata contra facturas históricas (no escribe nada)
+/// POST /api/v1/rewards/admin/accumulation-rules/dry-run
+pub async fn admin_dry_run_rule(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(payload): Json<DryRunRequest>,
+) -> Result<Json<DryRunResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    if payload.to <= payload.from {
+        return Err(ApiError::BadRequest("'to' debe ser posterior a 'from'".to_string()));
+    }
+    if (payload.to - payload.from).num_days() > MAX_DRY_RUN_DAYS {
+        return Err(ApiError::BadRequest(format!(
+            "El rango máximo es de {} días", MAX_DRY_RUN_DAYS
+        )));
+    }
+
+    let candidate = AccumulationRule {
+        // Sin id, la regla se agrega como nueva en la proyección
+        id: payload.rule.id.unwrap_or(-1),
+        name: payload.rule.name,
+        points: payload.rule.points,
+        points_per_dollar: payload.rule.points_per_dollar,
+        max_points: payload.rule.max_points,
+        priority: payload.rule.priority,
+        stacking: payload.rule.stacking.unwrap_or_else(|| STACKING_STACK.to_string()),
+        exclusive_group: payload.rule.exclusive_group,
+        conditions: payload.rule.conditions,
+    };
+    candidate.validate().map_err(|e| {
+        ApiError::BadRequest(i18n::render(i18n::request_locale(), "api.rule.invalid", &[("reason", &e)]))
+    })?;
+
+    let limit = payload.limit.unwrap_or(DEFAULT_DRY_RUN_LIMIT).clamp(1, MAX_DRY_RUN_LIMIT);
+    let samples = payload.samples.unwrap_or(DEFAULT_SAMPLES).min(MAX_SAMPLES);
+
+    let summary = AccumulationRuleEngine::new(state.db_pool.clone())
+        .dry_run(&candidate, payload.from, payload.to, limit, samples)
+        .await
+        .map_err(db_error)?;
+
+    info!(
+        "Admin {} dry-ran rule '{}': {}/{} invoices matched, {} Lumis",
+        current_user.user_id,
+        candidate.name,
+        summary.invoices_matched,
+        summary.invoices_evaluated,
+        summary.candidate_points
+    );
+
+    Ok(Json(DryRunResponse { success: true, summary }))
+}
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Forbidden(String),
+    NotFound(String),
+    InternalError(String),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        };
+
+        let body = Json(serde_json::json!({
+            "success": false,
+            "error": message,
+        }));
+
+        (status, body).into_response()
+    }
+}
@@ -14,8 +14,10 @@ use std::sync::Arc;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use crate::shared::admin::is_admin;
 use crate::{
     middleware::auth::JwtClaims,
+    shared::i18n,
     state::AppState,
 };
 
@@ -125,15 +127,6 @@ pub struct RegenerateApiKeyResponse {
 // Helper Functions
 // ============================================================================
 
-fn is_admin(user_id: i32) -> bool {
-    let admin_ids: Vec<i32> = std::env::var("ADMIN_USER_IDS")
-        .unwrap_or_else(|_| "1,2,3".to_string())
-        .split(',')
-        .filter_map(|s| s.trim().parse().ok())
-        .collect();
-    admin_ids.contains(&user_id)
-}
-
 fn generate_api_key() -> String {
     use rand::Rng;
     let mut rng = rand::thread_rng();
@@ -170,8 +163,8 @@ pub async fn list_merchants(
 ) -> Result<Json<MerchantListResponse>, ApiError> {
     let user_id = claims.user_id().map_err(|e| ApiError::Unauthorized(e))?;
     
-    if !is_admin(user_id) {
-        return Err(ApiError::Forbidden("Acceso no autorizado".to_string()));
+    if !is_admin(i64::from(user_id)) {
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
     }
 
     let limit = params.limit.unwrap_or(20).min(100);
@@ -267,8 +260,8 @@ pub async fn get_merchant(
 ) -> Result<Json<MerchantDetailResponse>, ApiError> {
     let user_id = claims.user_id().map_err(|e| ApiError::Unauthorized(e))?;
     
-    if !is_admin(user_id) {
-        return Err(ApiError::Forbidden("Acceso no autorizado".to_string()));
+    if !is_admin(i64::from(user_id)) {
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
     }
 
     // Obtener merchant básico
@@ -359,8 +352,8 @@ pub async fn create_merchant(
 ) -> Result<Json<CreateMerchantResponse>, ApiError> {
     let user_id = claims.user_id().map_err(|e| ApiError::Unauthorized(e))?;
     
-    if !is_admin(user_id) {
-        return Err(ApiError::Forbidden("Acceso no autorizado".to_string()));
+    if !is_admin(i64::from(user_id)) {
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
     }
 
     // Validar nombre
@@ -420,8 +413,8 @@ pub async fn update_merchant(
 ) -> Result<Json<SuccessResponse>, ApiError> {
     let user_id = claims.user_id().map_err(|e| ApiError::Unauthorized(e))?;
     
-    if !is_admin(user_id) {
-        return Err(ApiError::Forbidden("Acceso no autorizado".to_string()));
+    if !is_admin(i64::from(user_id)) {
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
     }
 
     // Verificar que existe
@@ -481,8 +474,8 @@ pub async fn delete_merchant(
 ) -> Result<Json<SuccessResponse>, ApiError> {
     let user_id = claims.user_id().map_err(|e| ApiError::Unauthorized(e))?;
     
-    if !is_admin(user_id) {
-        return Err(ApiError::Forbidden("Acceso no autorizado".to_string()));
+    if !is_admin(i64::from(user_id)) {
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
     }
 
     // Soft delete - solo desactivar
@@ -535,8 +528,8 @@ pub async fn regenerate_api_key(
 ) -> Result<Json<RegenerateApiKeyResponse>, ApiError> {
     let user_id = claims.user_id().map_err(|e| ApiError::Unauthorized(e))?;
     
-    if !is_admin(user_id) {
-        return Err(ApiError::Forbidden("Acceso no autorizado".to_string()));
+    if !is_admin(i64::from(user_id)) {
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
     }
 
     // Generar nuevo API key
@@ -581,8 +574,8 @@ pub async fn activate_merchant(
 ) -> Result<Json<SuccessResponse>, ApiError> {
     let user_id = claims.user_id().map_err(|e| ApiError::Unauthorized(e))?;
     
-    if !is_admin(user_id) {
-        return Err(ApiError::Forbidden("Acceso no autorizado".to_string()));
+    if !is_admin(i64::from(user_id)) {
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
     }
 
     let result = sqlx::query!(
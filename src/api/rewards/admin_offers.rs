@@ -17,9 +17,10 @@ use axum::{
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{error, info, warn};
+use tracing::{info, warn};
 use uuid::Uuid;
 
+use crate::shared::admin::is_admin;
 use crate::api::common::{ApiError, ApiResponse};
 use crate::middleware::auth::CurrentUser;
 use crate::state::AppState;
@@ -154,18 +155,12 @@ struct OfferWithStatsRow {
 // ADMIN VALIDATION
 // ============================================================================
 
-fn get_admin_user_ids() -> Vec<i64> {
-    std::env::var("ADMIN_USER_IDS")
-        .map(|s| s.split(',').filter_map(|id| id.trim().parse().ok()).collect())
-        .unwrap_or_else(|_| vec![1, 2, 3])
-}
-
 fn verify_admin(user_id: i64) -> Result<(), ApiError> {
-    if get_admin_user_ids().contains(&user_id) {
+    if is_admin(user_id) {
         Ok(())
     } else {
         warn!("Non-admin user {} attempted admin action", user_id);
-        Err(ApiError::new("FORBIDDEN", "No tienes permisos de administrador"))
+        Err(ApiError::localized("FORBIDDEN", "api.forbidden_admin", &[]))
     }
 }
 
@@ -224,15 +219,12 @@ pub async fn list_offers(
     .bind(filters.offset)
     .fetch_all(pool)
     .await
-    .map_err(|e| {
-        error!("Error fetching offers: {}", e);
-        ApiError::database_error(&format!("Error obteniendo ofertas: {}", e))
-    })?;
+    .map_err(|e| ApiError::database_failure("Error obteniendo ofertas", e))?;
     
     let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM rewards.redemption_offers")
         .fetch_one(pool)
         .await
-        .map_err(|e| ApiError::database_error(&format!("Error contando: {}", e)))?;
+        .map_err(|e| ApiError::database_failure("Error contando", e))?;
     
     let offers: Vec<AdminOfferResponse> = rows.into_iter().map(|r| AdminOfferResponse {
         offer_id: r.offer_id,
@@ -319,8 +311,8 @@ pub async fn get_offer(
     .bind(offer_id)
     .fetch_optional(pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Error: {}", e)))?
-    .ok_or_else(|| ApiError::not_found("Oferta"))?;
+    .map_err(|e| ApiError::database_failure("Error obteniendo oferta", e))?
+    .ok_or_else(|| ApiError::localized("NOT_FOUND", "api.not_found.offer", &[]))?;
     
     Ok(ok_response(AdminOfferResponse {
         offer_id: row.offer_id,
@@ -356,10 +348,10 @@ pub async fn create_offer(
     verify_admin(user.user_id)?;
     
     if req.name_friendly.trim().is_empty() {
-        return Err(ApiError::bad_request("El nombre es requerido"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.offers.name_required", &[]));
     }
     if req.lumis_cost < 0 {
-        return Err(ApiError::bad_request("El costo debe ser positivo"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.offers.cost_negative", &[]));
     }
     
     let pool = &state.db_pool;
@@ -393,7 +385,7 @@ pub async fn create_offer(
     .bind(now)
     .execute(pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Error creando: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Error creando", e))?;
     
     info!("Admin {} created offer {} ({})", user.user_id, offer_id, req.name_friendly);
     
@@ -418,10 +410,10 @@ pub async fn update_offer(
     .bind(offer_id)
     .fetch_optional(pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Error: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Error verificando oferta", e))?;
     
     if exists.is_none() {
-        return Err(ApiError::not_found("Oferta"));
+        return Err(ApiError::localized("NOT_FOUND", "api.not_found.offer", &[]));
     }
     
     // Update with provided fields
@@ -462,7 +454,7 @@ pub async fn update_offer(
     .bind(req.is_active)
     .execute(pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Error actualizando: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Error actualizando", e))?;
     
     info!("Admin {} updated offer {}", user.user_id, offer_id);
     
@@ -486,12 +478,10 @@ pub async fn delete_offer(
     .bind(offer_id)
     .fetch_one(pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Error: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Error contando redenciones pendientes", e))?;
     
     if pending.0 > 0 {
-        return Err(ApiError::bad_request(&format!(
-            "No se puede eliminar: {} redenciones pendientes", pending.0
-        )));
+        return Err(ApiError::localized("BAD_REQUEST", "api.offers.pending_redemptions", &[("count", &pending.0)]));
     }
     
     let result = sqlx::query(
@@ -500,10 +490,10 @@ pub async fn delete_offer(
     .bind(offer_id)
     .execute(pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Error: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Error eliminando oferta", e))?;
     
     if result.rows_affected() == 0 {
-        return Err(ApiError::not_found("Oferta"));
+        return Err(ApiError::localized("NOT_FOUND", "api.not_found.offer", &[]));
     }
     
     info!("Admin {} soft-deleted offer {}", user.user_id, offer_id);
@@ -528,10 +518,10 @@ pub async fn activate_offer(
     .bind(offer_id)
     .execute(&state.db_pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Error: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Error activando oferta", e))?;
     
     if result.rows_affected() == 0 {
-        return Err(ApiError::not_found("Oferta"));
+        return Err(ApiError::localized("NOT_FOUND", "api.not_found.offer", &[]));
     }
     
     info!("Admin {} activated offer {}", user.user_id, offer_id);
@@ -557,10 +547,10 @@ pub async fn deactivate_offer(
     .bind(offer_id)
     .execute(&state.db_pool)
     .await
-    .map_err(|e| ApiError::database_error(&format!("Error: {}", e)))?;
+    .map_err(|e| ApiError::database_failure("Error desactivando oferta", e))?;
     
     if result.rows_affected() == 0 {
-        return Err(ApiError::not_found("Oferta"));
+        return Err(ApiError::localized("NOT_FOUND", "api.not_found.offer", &[]));
     }
     
     info!("Admin {} deactivated offer {}", user.user_id, offer_id);
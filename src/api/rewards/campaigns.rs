@@ -0,0 +1,249 @@
+// ============================================================================
+// EARN CAMPAIGNS - Campañas de acumulación financiadas por comercios
+// ============================================================================
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::shared::admin::is_admin;
+use crate::{
+    api::merchant::permissions::{MerchantPermission, MSG_PERMISSION_DENIED},
+    domains::rewards::campaign_service::{
+        CampaignError, CampaignReport, CampaignService, CampaignUpdate, EarnCampaign, NewCampaign,
+    },
+    middleware::{auth::MerchantClaims, CurrentUser},
+    shared::i18n,
+    state::AppState,
+};
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct CampaignListQuery {
+    /// Solo admin
+    pub merchant_id: Option<Uuid>,
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CampaignListResponse {
+    pub success: bool,
+    pub campaigns: Vec<EarnCampaign>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CampaignResponse {
+    pub success: bool,
+    pub campaign: EarnCampaign,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CampaignReportResponse {
+    pub success: bool,
+    #[serde(flatten)]
+    pub report: CampaignReport,
+}
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn require_admin(current_user: &CurrentUser) -> Result<(), ApiError> {
+    if !is_admin(current_user.user_id) {
+        warn!("User {} attempted campaign admin access", current_user.user_id);
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
+    }
+    Ok(())
+}
+
+fn require_reports(merchant: &MerchantClaims) -> Result<Uuid, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ViewReports) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
+    merchant.get_merchant_id()
+        .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))
+}
+
+// ============================================================================
+// Admin Endpoints
+// ============================================================================
+
+/// Crear campaña
+/// POST /api/v1/rewards/admin/campaigns
+pub async fn admin_create_campaign(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(payload): Json<NewCampaign>,
+) -> Result<Response, ApiError> {
+    require_admin(&current_user)?;
+
+    let campaign = CampaignService::new(state.db_pool.clone())
+        .create_campaign(payload, current_user.user_id)
+        .await?;
+
+    info!(
+        "Admin {} created campaign {} ('{}') for merchant {} with budget {} Lumis",
+        current_user.user_id, campaign.campaign_id, campaign.name, campaign.merchant_id, campaign.budget_lumis
+    );
+
+    Ok((StatusCode::CREATED, Json(CampaignResponse { success: true, campaign })).into_response())
+}
+
+/// Listar campañas
+/// GET /api/v1/rewards/admin/campaigns?merchant_id=...&status=active
+pub async fn admin_list_campaigns(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<CampaignListQuery>,
+) -> Result<Json<CampaignListResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let campaigns = CampaignService::new(state.db_pool.clone())
+        .list_campaigns(params.merchant_id, params.status.as_deref(), limit, offset)
+        .await?;
+
+    Ok(Json(CampaignListResponse { success: true, campaigns, limit, offset }))
+}
+
+/// Pausar/reanudar/cancelar, ampliar presupuesto o extender la fecha de fin
+/// PUT /api/v1/rewards/admin/campaigns/:id
+pub async fn admin_update_campaign(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(campaign_id): Path<Uuid>,
+    Json(payload): Json<CampaignUpdate>,
+) -> Result<Json<CampaignResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    let campaign = CampaignService::new(state.db_pool.clone())
+        .update_campaign(campaign_id, payload)
+        .await?;
+
+    info!(
+        "Admin {} updated campaign {}: status={}, budget={}",
+        current_user.user_id, campaign_id, campaign.status, campaign.budget_lumis
+    );
+
+    Ok(Json(CampaignResponse { success: true, campaign }))
+}
+
+/// Gasto y ROI de una campaña
+/// GET /api/v1/rewards/admin/campaigns/:id/report
+pub async fn admin_campaign_report(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(campaign_id): Path<Uuid>,
+) -> Result<Json<CampaignReportResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    let report = CampaignService::new(state.db_pool.clone())
+        .campaign_report(campaign_id, None)
+        .await?;
+
+    Ok(Json(CampaignReportResponse { success: true, report }))
+}
+
+// ============================================================================
+// Merchant Endpoints
+// ============================================================================
+
+/// Campañas del comercio
+/// GET /api/v1/merchant/campaigns
+pub async fn merchant_list_campaigns(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Query(params): Query<CampaignListQuery>,
+) -> Result<Json<CampaignListResponse>, ApiError> {
+    let merchant_id = require_reports(&merchant)?;
+
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let campaigns = CampaignService::new(state.db_pool.clone())
+        .list_campaigns(Some(merchant_id), params.status.as_deref(), limit, offset)
+        .await?;
+
+    Ok(Json(CampaignListResponse { success: true, campaigns, limit, offset }))
+}
+
+/// Gasto y ROI de una campaña del comercio
+/// GET /api/v1/merchant/campaigns/:id/report
+pub async fn merchant_campaign_report(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Path(campaign_id): Path<Uuid>,
+) -> Result<Json<CampaignReportResponse>, ApiError> {
+    let merchant_id = require_reports(&merchant)?;
+
+    let report = CampaignService::new(state.db_pool.clone())
+        .campaign_report(campaign_id, Some(merchant_id))
+        .await?;
+
+    Ok(Json(CampaignReportResponse { success: true, report }))
+}
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    InternalError(String),
+}
+
+impl From<CampaignError> for ApiError {
+    fn from(err: CampaignError) -> Self {
+        match err {
+            CampaignError::Invalid(_) => ApiError::BadRequest(err.to_string()),
+            CampaignError::MerchantNotFound | CampaignError::NotFound => ApiError::NotFound(err.to_string()),
+            CampaignError::Database(e) => {
+                error!("Campaign database error: {}", e);
+                ApiError::InternalError("Error al procesar campaña".to_string())
+            }
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        };
+
+        let body = Json(serde_json::json!({
+            "success": false,
+            "error": message,
+        }));
+
+        (status, body).into_response()
+    }
+}
@@ -9,6 +9,9 @@ pub mod qr_static;
 pub mod admin_offers;
 pub mod admin_merchants;
 pub mod reports;
+pub mod settlements;
+pub mod campaigns;
+pub mod accumulation_rules;
 
 use axum::{
     routing::{get, post, put, delete},
@@ -63,6 +66,30 @@ pub fn router() -> Router<Arc<AppState>> {
         .route("/admin/export/redemptions", get(reports::admin_export_redemptions))
         .layer(from_fn(extract_current_user));
     
+    // Admin settlements routes
+    let admin_settlements_routes = Router::new()
+        .route("/admin/settlements", get(settlements::admin_list_settlements))
+        .route("/admin/settlements/close", post(settlements::admin_close_settlement))
+        .route("/admin/settlements/:id", get(settlements::admin_get_settlement))
+        .route("/admin/commercial-terms/:offer_id", get(settlements::admin_list_commercial_terms))
+        .route("/admin/commercial-terms/:offer_id", put(settlements::admin_set_commercial_term))
+        .layer(from_fn(extract_current_user));
+    
+    // Admin earn campaigns routes
+    let admin_campaigns_routes = Router::new()
+        .route("/admin/campaigns", get(campaigns::admin_list_campaigns))
+        .route("/admin/campaigns", post(campaigns::admin_create_campaign))
+        .route("/admin/campaigns/:id", put(campaigns::admin_update_campaign))
+        .route("/admin/campaigns/:id/report", get(campaigns::admin_campaign_report))
+        .layer(from_fn(extract_current_user));
+    
+    // Admin accumulation rules routes (explain + dry-run)
+    let admin_rules_routes = Router::new()
+        .route("/admin/accumulation-rules", get(accumulation_rules::admin_list_rules))
+        .route("/admin/accumulation-rules/explain", get(accumulation_rules::admin_explain_invoice))
+        .route("/admin/accumulation-rules/dry-run", post(accumulation_rules::admin_dry_run_rule))
+        .layer(from_fn(extract_current_user));
+    
     // Public routes (QR images don't need auth)
     let public = Router::new()
         .route("/qr/:filename", get(qr_static::serve_qr_image));
@@ -72,5 +99,8 @@ pub fn router() -> Router<Arc<AppState>> {
         .merge(admin_offers_routes)
         .merge(admin_merchants_routes)
         .merge(admin_reports_routes)
+        .merge(admin_settlements_routes)
+        .merge(admin_campaigns_routes)
+        .merge(admin_rules_routes)
         .merge(public)
 }
@@ -17,6 +17,7 @@ use uuid::Uuid;
 use crate::{
     domains::rewards::models::{OfferFilters, OfferListItem, RedemptionOffer, RedemptionError},
     middleware::auth::CurrentUser,
+    shared::i18n,
     state::AppState,
 };
 
@@ -143,27 +144,15 @@ pub enum ApiError {
 impl From<RedemptionError> for ApiError {
     fn from(err: RedemptionError) -> Self {
         match err {
-            RedemptionError::OfferNotFound => ApiError::NotFound("Offer not found".to_string()),
-            RedemptionError::Database(msg) => ApiError::InternalError(msg),
-            RedemptionError::InsufficientBalance { required, current } => {
-                ApiError::BadRequest(format!(
-                    "Insufficient balance: required {} Lümis, available {}",
-                    required, current
-                ))
+            RedemptionError::OfferNotFound => ApiError::NotFound(err.to_string()),
+            RedemptionError::InsufficientBalance { .. }
+            | RedemptionError::MaxRedemptionsReached { .. }
+            | RedemptionError::OfferInactive
+            | RedemptionError::OutOfStock => ApiError::BadRequest(err.to_string()),
+            _ => {
+                error!("Offer request failed: {}", err);
+                ApiError::InternalError(i18n::render(i18n::request_locale(), "api.internal_error", &[]))
             }
-            RedemptionError::MaxRedemptionsReached { max, current } => {
-                ApiError::BadRequest(format!(
-                    "Maximum redemptions reached: {}/{}",
-                    current, max
-                ))
-            }
-            RedemptionError::OfferInactive => {
-                ApiError::BadRequest("Offer is not currently active".to_string())
-            }
-            RedemptionError::OutOfStock => {
-                ApiError::BadRequest("Offer is out of stock".to_string())
-            }
-            _ => ApiError::InternalError(format!("{:?}", err)),
         }
     }
 }
@@ -17,6 +17,7 @@ use uuid::Uuid;
 use crate::{
     domains::rewards::models::{CreateRedemptionRequest, RedemptionCreatedResponse, RedemptionError},
     middleware::auth::CurrentUser,
+    shared::i18n,
     state::AppState,
 };
 
@@ -113,7 +114,7 @@ async fn check_redemption_rate_limit(state: &AppState, user_id: i32) -> Result<(
     let mut conn = state.redis_pool.get().await.map_err(|e| {
         error!("Redis connection error for rate limiting: {}", e);
         // Fallback: permitir si Redis no está disponible
-        ApiError::InternalError("Error temporal, intenta de nuevo".to_string())
+        ApiError::InternalError(i18n::render(i18n::request_locale(), "api.temporary_error", &[]))
     })?;
     
     let now = chrono::Utc::now();
@@ -139,9 +140,10 @@ async fn check_redemption_rate_limit(state: &AppState, user_id: i32) -> Result<(
     }
     
     if hour_count > REDEMPTIONS_PER_HOUR {
-        return Err(ApiError::TooManyRequests(format!(
-            "Límite de redenciones por hora alcanzado ({}/{}). Intenta en la próxima hora.",
-            hour_count - 1, REDEMPTIONS_PER_HOUR
+        return Err(ApiError::TooManyRequests(i18n::render(
+            i18n::request_locale(),
+            "api.redemption.hourly_limit",
+            &[("count", &(hour_count - 1)), ("max", &REDEMPTIONS_PER_HOUR)],
         )));
     }
     
@@ -168,9 +170,10 @@ async fn check_redemption_rate_limit(state: &AppState, user_id: i32) -> Result<(
             .query_async(&mut *conn)
             .await
             .unwrap_or(());
-        return Err(ApiError::TooManyRequests(format!(
-            "Límite de redenciones diarias alcanzado ({}/{}). Intenta mañana.",
-            day_count - 1, REDEMPTIONS_PER_DAY
+        return Err(ApiError::TooManyRequests(i18n::render(
+            i18n::request_locale(),
+            "api.redemption.daily_limit",
+            &[("count", &(day_count - 1)), ("max", &REDEMPTIONS_PER_DAY)],
         )));
     }
     
@@ -202,30 +205,15 @@ pub enum ApiError {
 impl From<RedemptionError> for ApiError {
     fn from(err: RedemptionError) -> Self {
         match err {
-            RedemptionError::OfferNotFound => ApiError::NotFound("Offer not found".to_string()),
-            RedemptionError::Database(msg) => ApiError::InternalError(msg),
-            RedemptionError::InsufficientBalance { required, current } => {
-                ApiError::BadRequest(format!(
-                    "Saldo insuficiente. Necesitas {} Lümis, tienes {}",
-                    required, current
-                ))
+            RedemptionError::OfferNotFound => ApiError::NotFound(err.to_string()),
+            RedemptionError::InsufficientBalance { .. }
+            | RedemptionError::MaxRedemptionsReached { .. }
+            | RedemptionError::OfferInactive
+            | RedemptionError::OutOfStock => ApiError::BadRequest(err.to_string()),
+            _ => {
+                error!("Redemption failed: {}", err);
+                ApiError::InternalError(i18n::render(i18n::request_locale(), "api.internal_error", &[]))
             }
-            RedemptionError::MaxRedemptionsReached { max, current } => {
-                ApiError::BadRequest(format!(
-                    "Límite de redenciones alcanzado: {}/{}",
-                    current, max
-                ))
-            }
-            RedemptionError::OfferInactive => {
-                ApiError::BadRequest("La oferta no está activa actualmente".to_string())
-            }
-            RedemptionError::OutOfStock => {
-                ApiError::BadRequest("La oferta no tiene stock disponible".to_string())
-            }
-            RedemptionError::QRGenerationFailed(msg) => {
-                ApiError::InternalError(format!("Error generando QR: {}", msg))
-            }
-            _ => ApiError::InternalError(format!("{:?}", err)),
         }
     }
 }
@@ -15,8 +15,11 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::error;
 
+use crate::shared::admin::is_admin;
 use crate::{
     middleware::auth::{JwtClaims, MerchantClaims},
+    api::merchant::permissions::{MerchantPermission, MSG_PERMISSION_DENIED},
+    shared::i18n,
     state::AppState,
 };
 
@@ -121,8 +124,8 @@ pub async fn admin_generate_report(
 ) -> Result<Response, ApiError> {
     let user_id = claims.user_id().map_err(|e| ApiError::Unauthorized(e))?;
     
-    if !is_admin(user_id) {
-        return Err(ApiError::Forbidden("Acceso no autorizado".to_string()));
+    if !is_admin(i64::from(user_id)) {
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
     }
 
     let format = params.format.as_deref().unwrap_or("json");
@@ -161,8 +164,8 @@ pub async fn admin_export_redemptions(
 ) -> Result<Response, ApiError> {
     let user_id = claims.user_id().map_err(|e| ApiError::Unauthorized(e))?;
     
-    if !is_admin(user_id) {
-        return Err(ApiError::Forbidden("Acceso no autorizado".to_string()));
+    if !is_admin(i64::from(user_id)) {
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
     }
 
     let (start_date, end_date) = get_date_range(&params.start_date, &params.end_date);
@@ -189,6 +192,10 @@ pub async fn merchant_generate_report(
     Extension(merchant): Extension<MerchantClaims>,
     Query(params): Query<ReportQuery>,
 ) -> Result<Response, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ViewReports) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
+    
     let merchant_id = merchant.get_merchant_id()
         .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))?;
 
@@ -223,6 +230,10 @@ pub async fn merchant_export_redemptions(
     Extension(merchant): Extension<MerchantClaims>,
     Query(params): Query<ExportQuery>,
 ) -> Result<Response, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ViewReports) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
+    
     let merchant_id = merchant.get_merchant_id()
         .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))?;
 
@@ -243,15 +254,6 @@ pub async fn merchant_export_redemptions(
 // Helper Functions
 // ============================================================================
 
-fn is_admin(user_id: i32) -> bool {
-    let admin_ids: Vec<i32> = std::env::var("ADMIN_USER_IDS")
-        .unwrap_or_else(|_| "1,2,3".to_string())
-        .split(',')
-        .filter_map(|s| s.trim().parse().ok())
-        .collect();
-    admin_ids.contains(&user_id)
-}
-
 fn get_date_range(start: &Option<String>, end: &Option<String>) -> (String, String) {
     use chrono::{Duration, Utc};
     
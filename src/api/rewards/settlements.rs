@@ -0,0 +1,352 @@
+// ============================================================================
+// SETTLEMENTS - Liquidaciones mensuales por comercio (admin + portal de comercios)
+// ============================================================================
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::shared::admin::is_admin;
+use crate::{
+    api::merchant::permissions::{MerchantPermission, MSG_PERMISSION_DENIED},
+    domains::rewards::settlement_service::{
+        parse_period, statement_csv, CommercialTerm, Settlement, SettlementError,
+        SettlementService, SettlementStatement,
+    },
+    middleware::{auth::MerchantClaims, CurrentUser},
+    shared::i18n,
+    state::AppState,
+};
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct CloseSettlementRequest {
+    pub merchant_id: Uuid,
+    /// Mes a cerrar (YYYY-MM)
+    pub period: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SettlementListQuery {
+    /// Solo admin
+    pub merchant_id: Option<Uuid>,
+    /// YYYY-MM
+    pub period: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatementQuery {
+    /// json (default) o csv
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetCommercialTermRequest {
+    /// merchant_reimbursed | merchant_fee
+    pub direction: String,
+    pub unit_amount: Decimal,
+    /// YYYY-MM-DD (default: hoy)
+    pub effective_from: Option<NaiveDate>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettlementListResponse {
+    pub success: bool,
+    pub settlements: Vec<Settlement>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatementResponse {
+    pub success: bool,
+    #[serde(flatten)]
+    pub statement: SettlementStatement,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommercialTermsResponse {
+    pub success: bool,
+    pub terms: Vec<CommercialTerm>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommercialTermResponse {
+    pub success: bool,
+    pub term: CommercialTerm,
+}
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn require_admin(current_user: &CurrentUser) -> Result<(), ApiError> {
+    if !is_admin(current_user.user_id) {
+        warn!("User {} attempted settlement admin access", current_user.user_id);
+        return Err(ApiError::Forbidden(i18n::t(i18n::request_locale(), "api.forbidden")));
+    }
+    Ok(())
+}
+
+fn statement_response(statement: SettlementStatement, format: Option<&str>) -> Response {
+    match format {
+        Some("csv") => {
+            let filename = format!(
+                "liquidacion_{}_{}.csv",
+                statement.settlement.merchant_name.replace(' ', "_"),
+                statement.settlement.period_start.format("%Y-%m"),
+            );
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "text/csv; charset=utf-8")
+                .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename))
+                .body(Body::from(statement_csv(&statement)))
+                .unwrap()
+        }
+        _ => Json(StatementResponse { success: true, statement }).into_response(),
+    }
+}
+
+fn parse_optional_period(period: &Option<String>) -> Result<Option<NaiveDate>, ApiError> {
+    period
+        .as_deref()
+        .map(|p| parse_period(p).map(|(start, _)| start))
+        .transpose()
+        .map_err(ApiError::from)
+}
+
+// ============================================================================
+// Admin Endpoints
+// ============================================================================
+
+/// Cerrar un mes para un comercio
+/// POST /api/v1/rewards/admin/settlements/close
+pub async fn admin_close_settlement(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(payload): Json<CloseSettlementRequest>,
+) -> Result<Response, ApiError> {
+    require_admin(&current_user)?;
+
+    let service = SettlementService::new(state.db_pool.clone());
+    let statement = service
+        .close_period(payload.merchant_id, &payload.period, current_user.user_id)
+        .await?;
+
+    info!(
+        "Admin {} closed settlement {} ({} {})",
+        current_user.user_id,
+        statement.settlement.settlement_id,
+        statement.settlement.merchant_name,
+        payload.period
+    );
+
+    Ok((StatusCode::CREATED, Json(StatementResponse { success: true, statement })).into_response())
+}
+
+/// Listar liquidaciones
+/// GET /api/v1/rewards/admin/settlements?merchant_id=...&period=2026-09
+pub async fn admin_list_settlements(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<SettlementListQuery>,
+) -> Result<Json<SettlementListResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    let period_start = parse_optional_period(&params.period)?;
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let settlements = SettlementService::new(state.db_pool.clone())
+        .list_settlements(params.merchant_id, period_start, limit, offset)
+        .await?;
+
+    Ok(Json(SettlementListResponse { success: true, settlements, limit, offset }))
+}
+
+/// Estado de cuenta de una liquidación (JSON o CSV)
+/// GET /api/v1/rewards/admin/settlements/:id?format=csv
+pub async fn admin_get_settlement(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(settlement_id): Path<Uuid>,
+    Query(params): Query<StatementQuery>,
+) -> Result<Response, ApiError> {
+    require_admin(&current_user)?;
+
+    let statement = SettlementService::new(state.db_pool.clone())
+        .get_statement(settlement_id, None)
+        .await?;
+
+    Ok(statement_response(statement, params.format.as_deref()))
+}
+
+/// Historial de términos comerciales de una oferta
+/// GET /api/v1/rewards/admin/commercial-terms/:offer_id
+pub async fn admin_list_commercial_terms(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(offer_id): Path<Uuid>,
+) -> Result<Json<CommercialTermsResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    let terms = SettlementService::new(state.db_pool.clone())
+        .list_commercial_terms(offer_id)
+        .await?;
+
+    Ok(Json(CommercialTermsResponse { success: true, terms }))
+}
+
+/// Registrar término comercial vigente desde `effective_from`
+/// PUT /api/v1/rewards/admin/commercial-terms/:offer_id
+pub async fn admin_set_commercial_term(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(offer_id): Path<Uuid>,
+    Json(payload): Json<SetCommercialTermRequest>,
+) -> Result<Json<CommercialTermResponse>, ApiError> {
+    require_admin(&current_user)?;
+
+    let effective_from = payload
+        .effective_from
+        .unwrap_or_else(|| chrono::Utc::now().with_timezone(&chrono_tz::America::Panama).date_naive());
+
+    let term = SettlementService::new(state.db_pool.clone())
+        .set_commercial_term(
+            offer_id,
+            &payload.direction,
+            payload.unit_amount,
+            effective_from,
+            payload.notes.as_deref(),
+            current_user.user_id,
+        )
+        .await?;
+
+    info!(
+        "Admin {} set commercial term for offer {}: {} {} from {}",
+        current_user.user_id, offer_id, term.direction, term.unit_amount, term.effective_from
+    );
+
+    Ok(Json(CommercialTermResponse { success: true, term }))
+}
+
+// ============================================================================
+// Merchant Endpoints
+// ============================================================================
+
+/// Liquidaciones del comercio
+/// GET /api/v1/merchant/settlements
+pub async fn merchant_list_settlements(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Query(params): Query<SettlementListQuery>,
+) -> Result<Json<SettlementListResponse>, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ViewReports) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
+    let merchant_id = merchant.get_merchant_id()
+        .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))?;
+
+    let period_start = parse_optional_period(&params.period)?;
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let settlements = SettlementService::new(state.db_pool.clone())
+        .list_settlements(Some(merchant_id), period_start, limit, offset)
+        .await?;
+
+    Ok(Json(SettlementListResponse { success: true, settlements, limit, offset }))
+}
+
+/// Estado de cuenta del comercio (JSON o CSV)
+/// GET /api/v1/merchant/settlements/:id?format=csv
+pub async fn merchant_get_settlement(
+    State(state): State<Arc<AppState>>,
+    Extension(merchant): Extension<MerchantClaims>,
+    Path(settlement_id): Path<Uuid>,
+    Query(params): Query<StatementQuery>,
+) -> Result<Response, ApiError> {
+    if !merchant.has_permission(MerchantPermission::ViewReports) {
+        return Err(ApiError::Forbidden(MSG_PERMISSION_DENIED.to_string()));
+    }
+    let merchant_id = merchant.get_merchant_id()
+        .ok_or_else(|| ApiError::Unauthorized("ID de comercio no válido".to_string()))?;
+
+    let statement = SettlementService::new(state.db_pool.clone())
+        .get_statement(settlement_id, Some(merchant_id))
+        .await?;
+
+    Ok(statement_response(statement, params.format.as_deref()))
+}
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    InternalError(String),
+}
+
+impl From<SettlementError> for ApiError {
+    fn from(err: SettlementError) -> Self {
+        match err {
+            SettlementError::InvalidPeriod
+            | SettlementError::PeriodNotEnded(_)
+            | SettlementError::InvalidTerm(_) => ApiError::BadRequest(err.to_string()),
+            SettlementError::AlreadyClosed(_) => ApiError::Conflict(err.to_string()),
+            SettlementError::MerchantNotFound
+            | SettlementError::OfferNotFound
+            | SettlementError::NotFound => ApiError::NotFound(err.to_string()),
+            SettlementError::Database(e) => {
+                error!("Settlement database error: {}", e);
+                ApiError::InternalError("Error al procesar liquidación".to_string())
+            }
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        };
+
+        let body = Json(serde_json::json!({
+            "success": false,
+            "error": message,
+        }));
+
+        (status, body).into_response()
+    }
+}
@@ -21,6 +21,7 @@ use crate::{
         // CancellationResponse, // Unused - CancelResponse defined locally
     },
     middleware::auth::CurrentUser,
+    shared::i18n,
     state::AppState,
 };
 
@@ -241,23 +242,22 @@ pub enum ApiError {
 impl From<RedemptionError> for ApiError {
     fn from(err: RedemptionError) -> Self {
         match err {
-            RedemptionError::RedemptionNotFound => {
-                ApiError::NotFound("Redención no encontrada".to_string())
+            RedemptionError::RedemptionNotFound => ApiError::NotFound(err.to_string()),
+            RedemptionError::CannotCancel { .. } => ApiError::BadRequest(err.to_string()),
+            RedemptionError::InvalidRedemptionCode => ApiError::BadRequest(i18n::render(
+                i18n::request_locale(),
+                "api.redemption.already_used",
+                &[],
+            )),
+            RedemptionError::CodeExpired => ApiError::BadRequest(i18n::render(
+                i18n::request_locale(),
+                "api.redemption.expired",
+                &[],
+            )),
+            _ => {
+                error!("Redemption request failed: {}", err);
+                ApiError::InternalError(i18n::render(i18n::request_locale(), "api.internal_error", &[]))
             }
-            RedemptionError::Database(msg) => ApiError::InternalError(msg),
-            RedemptionError::CannotCancel { status } => {
-                ApiError::BadRequest(format!(
-                    "No puedes cancelar una redención con estado: {}",
-                    status
-                ))
-            }
-            RedemptionError::InvalidRedemptionCode => {
-                ApiError::BadRequest("Esta redención ya fue utilizada".to_string())
-            }
-            RedemptionError::CodeExpired => {
-                ApiError::BadRequest("Esta redención ya expiró".to_string())
-            }
-            _ => ApiError::InternalError(format!("{:?}", err)),
         }
     }
 }
@@ -50,9 +50,9 @@ pub async fn get_user_rewards_balance(
         .bind(user_id)
         .fetch_optional(&state.db_pool)
         .await
-        .map_err(|e| ApiError::database_error(&format!("Query execution failed: {}", e)))?;
+        .map_err(|e| ApiError::database_failure("Query execution failed", e))?;
 
-    let data = result.ok_or_else(|| ApiError::not_found("User rewards balance"))?;
+    let data = result.ok_or_else(|| ApiError::localized("NOT_FOUND", "api.not_found.rewards_balance", &[]))?;
     let execution_time = start_time.elapsed().as_millis() as u64;
     
     // Add helpful message to response
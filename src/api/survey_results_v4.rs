@@ -0,0 +1,301 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State, Extension},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+    routing::{delete, get, post, put},
+    Router,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::shared::admin::is_admin;
+use crate::{
+    middleware::CurrentUser,
+    api::common::{ApiError, ApiResponse},
+    domains::surveys::analytics::{
+        self, AnalyticsError, AnalyzableSurvey, Dimension, SurveyAnalyticsService,
+    },
+    domains::surveys::quotas::{QuotaDefinition, QuotaError, QuotaFill, QuotaService},
+    AppState,
+};
+
+// Response wrapper for JSON
+type ResponseJson<T> = Result<Json<ApiResponse<T>>, ApiError>;
+
+// ============================================================================
+// REQUEST/RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ResultsQuery {
+    /// age_band, country o province
+    pub dimension: Option<String>,
+    /// json (default) o csv
+    pub format: Option<String>,
+    /// Tamaño mínimo de celda; solo puede subir sobre el mínimo global
+    pub min_cell: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddSponsorRequest {
+    pub user_id: i64,
+}
+
+// ============================================================================
+// API HANDLERS
+// ============================================================================
+
+/// Surveys whose results the caller can see (all for admins, own campaigns for sponsors)
+#[axum::debug_handler]
+pub async fn list_analyzable_surveys(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> ResponseJson<Vec<AnalyzableSurvey>> {
+    let start_time = Utc::now();
+
+    let surveys = SurveyAnalyticsService::new(state.db_pool.clone())
+        .analyzable_surveys(current_user.user_id, is_admin(current_user.user_id))
+        .await
+        .map_err(analytics_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(surveys, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Aggregated results with small-cell suppression, as JSON or CSV
+#[axum::debug_handler]
+pub async fn get_survey_results(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(survey_id): Path<i32>,
+    Query(query): Query<ResultsQuery>,
+) -> Result<Response, ApiError> {
+    let start_time = Utc::now();
+
+    let service = SurveyAnalyticsService::new(state.db_pool.clone());
+    if !is_admin(current_user.user_id) && !service.is_sponsor(current_user.user_id, survey_id).await.map_err(analytics_error)? {
+        tracing::warn!("User {} attempted to read results of survey {}", current_user.user_id, survey_id);
+        return Err(ApiError::forbidden());
+    }
+
+    let dimension = match query.dimension.as_deref() {
+        None | Some("") => None,
+        Some(value) => Some(
+            Dimension::parse(value)
+                .ok_or_else(|| ApiError::localized("VALIDATION_ERROR", "api.survey_results.invalid_dimension", &[]))?,
+        ),
+    };
+
+    let results = service
+        .results(survey_id, dimension, query.min_cell.unwrap_or(analytics::MIN_CELL_SIZE))
+        .await
+        .map_err(analytics_error)?;
+
+    tracing::info!(
+        "Survey {} results exported by user {} (dimension: {:?})",
+        survey_id,
+        current_user.user_id,
+        dimension.map(Dimension::as_str)
+    );
+
+    match query.format.as_deref() {
+        Some("csv") => {
+            let filename = match dimension {
+                Some(dimension) => format!("encuesta_{}_{}.csv", survey_id, dimension.as_str()),
+                None => format!("encuesta_{}.csv", survey_id),
+            };
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "text/csv; charset=utf-8")
+                .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename))
+                .body(Body::from(analytics::results_csv(&results)))
+                .unwrap())
+        }
+        None | Some("json") => {
+            let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+            Ok(Json(ApiResponse::success(results, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false))
+                .into_response())
+        }
+        Some(_) => Err(ApiError::localized("VALIDATION_ERROR", "api.survey_results.invalid_format", &[])),
+    }
+}
+
+/// Live quota fill per cell (admin or campaign sponsor)
+#[axum::debug_handler]
+pub async fn get_quota_fill(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(survey_id): Path<i32>,
+) -> ResponseJson<QuotaFill> {
+    let start_time = Utc::now();
+
+    if !is_admin(current_user.user_id)
+        && !SurveyAnalyticsService::new(state.db_pool.clone())
+            .is_sponsor(current_user.user_id, survey_id)
+            .await
+            .map_err(analytics_error)?
+    {
+        tracing::warn!("User {} attempted to read quotas of survey {}", current_user.user_id, survey_id);
+        return Err(ApiError::forbidden());
+    }
+
+    let fill = QuotaService::new(state.db_pool.clone())
+        .fill(survey_id)
+        .await
+        .map_err(quota_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(fill, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Create or replace a survey's quotas (admin)
+#[axum::debug_handler]
+pub async fn admin_set_quotas(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(survey_id): Path<i32>,
+    Json(definition): Json<QuotaDefinition>,
+) -> ResponseJson<QuotaDefinition> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    QuotaService::new(state.db_pool.clone())
+        .set(survey_id, &definition, current_user.user_id)
+        .await
+        .map_err(quota_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(definition, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Remove a survey's quotas; the survey opens to everyone again (admin)
+#[axum::debug_handler]
+pub async fn admin_remove_quotas(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(survey_id): Path<i32>,
+) -> ResponseJson<bool> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let removed = QuotaService::new(state.db_pool.clone())
+        .remove(survey_id)
+        .await
+        .map_err(quota_error)?;
+    if !removed {
+        return Err(ApiError::localized("NOT_FOUND", "api.not_found.quota", &[]));
+    }
+
+    tracing::info!("Survey {} quotas removed by admin {}", survey_id, current_user.user_id);
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(true, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Grant a user access to a campaign's survey results (admin)
+#[axum::debug_handler]
+pub async fn admin_add_sponsor(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(campaign_id): Path<i32>,
+    Json(request): Json<AddSponsorRequest>,
+) -> ResponseJson<bool> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    SurveyAnalyticsService::new(state.db_pool.clone())
+        .add_sponsor(campaign_id, request.user_id, current_user.user_id)
+        .await
+        .map_err(analytics_error)?;
+
+    tracing::info!("User {} added as sponsor of survey campaign {}", request.user_id, campaign_id);
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(true, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Revoke a sponsor's access (admin)
+#[axum::debug_handler]
+pub async fn admin_remove_sponsor(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((campaign_id, user_id)): Path<(i32, i64)>,
+) -> ResponseJson<bool> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let removed = SurveyAnalyticsService::new(state.db_pool.clone())
+        .remove_sponsor(campaign_id, user_id)
+        .await
+        .map_err(analytics_error)?;
+    if !removed {
+        return Err(ApiError::localized("NOT_FOUND", "api.not_found.sponsor", &[]));
+    }
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(true, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+fn require_admin(current_user: &CurrentUser) -> Result<(), ApiError> {
+    if !is_admin(current_user.user_id) {
+        tracing::warn!("User {} attempted survey sponsor admin access", current_user.user_id);
+        return Err(ApiError::forbidden());
+    }
+    Ok(())
+}
+
+fn analytics_error(err: AnalyticsError) -> ApiError {
+    match err {
+        AnalyticsError::NotFound => ApiError::localized("NOT_FOUND", "api.not_found.survey", &[]),
+        AnalyticsError::InvalidDefinition(_) => {
+            tracing::error!("Survey results failed: {}", err);
+            ApiError::localized("INTERNAL_SERVER_ERROR", "api.survey_results.invalid_definition", &[])
+        }
+        AnalyticsError::Database(e) => {
+            tracing::error!("Survey analytics database error: {}", e);
+            ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
+        }
+    }
+}
+
+fn quota_error(err: QuotaError) -> ApiError {
+    match err {
+        QuotaError::NotFound => ApiError::localized("NOT_FOUND", "api.not_found.survey", &[]),
+        QuotaError::NoQuotas => ApiError::localized("NOT_FOUND", "api.not_found.quota", &[]),
+        QuotaError::Invalid(reason) => ApiError::validation_error(&reason.to_string()),
+        QuotaError::Full(_) => ApiError::new("SURVEY_QUOTA_FULL", &err.to_string()),
+        QuotaError::Database(e) => {
+            tracing::error!("Survey quota database error: {}", e);
+            ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
+        }
+    }
+}
+
+// ============================================================================
+// ROUTER CREATION
+// ============================================================================
+
+/// Create router for survey results endpoints
+pub fn create_survey_results_v4_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/v4/surveys/analytics", get(list_analyzable_surveys))
+        .route("/api/v4/surveys/:survey_id/results", get(get_survey_results))
+        .route("/api/v4/surveys/:survey_id/quotas", get(get_quota_fill))
+        .route("/api/v4/surveys/admin/:survey_id/quotas", put(admin_set_quotas).delete(admin_remove_quotas))
+        .route("/api/v4/surveys/admin/campaigns/:campaign_id/sponsors", post(admin_add_sponsor))
+        .route("/api/v4/surveys/admin/campaigns/:campaign_id/sponsors/:user_id", delete(admin_remove_sponsor))
+}
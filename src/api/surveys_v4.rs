@@ -9,7 +9,7 @@ use axum::{
     extract::{Json, Path, Query, State, Extension},
     http::StatusCode,
     response::Json as ResponseJson,
-    routing::{get, patch},
+    routing::{get, patch, post, put},
     Router,
 };
 use serde::{Deserialize, Serialize};
@@ -17,10 +17,13 @@ use chrono::{DateTime, Utc};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::shared::admin::is_admin;
 use crate::{
     state::AppState,
     middleware::CurrentUser,
     api::common::{ApiResponse, ApiError},
+    domains::surveys::{QuotaError, QuotaService, SurveyDefinition, SurveyError, SurveyService},
+    shared::i18n::{self, Text},
 };
 
 // ============================================
@@ -71,7 +74,7 @@ pub struct SurveyDetail {
     pub total_questions: i32,
     pub time_limit_minutes: Option<i32>,
     pub difficulty: String,
-    pub questions: serde_json::Value, // JSONB content
+    pub questions: SurveyDefinition, // Vista pública: sin is_correct ni weight
     pub user_status: Option<UserSurveyStatus>,
 }
 
@@ -136,7 +139,12 @@ pub async fn get_user_surveys(
     .await;
     
     match result {
-        Ok(surveys_json) => {
+        Ok(mut surveys_json) => {
+            // Pendientes cuya cuota ya está completa para el perfil del usuario
+            if let Some(surveys_json) = surveys_json.as_mut() {
+                hide_full_quota_surveys(&state, current_user.user_id, surveys_json).await;
+            }
+            
             let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
             
             Ok(ResponseJson(ApiResponse {
@@ -156,7 +164,7 @@ pub async fn get_user_surveys(
                 data: None,
                 error: Some(ApiError {
                     code: "DATABASE_ERROR".to_string(),
-                    message: "Error al obtener encuestas del usuario".to_string(),
+                    message: i18n::t(i18n::request_locale(), "api.survey.list_failed"),
                     details: Some(format!("Error: {}", e).into()),
                 }),
                 request_id: Uuid::new_v4().to_string(),
@@ -193,9 +201,48 @@ pub async fn get_survey_detail(
     .await;
     
     match result {
-        Ok(Some(survey_json)) => {
+        Ok(Some(mut survey_json)) => {
             let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
             
+            // Abrir la encuesta reserva el cupo del usuario si tiene cuotas
+            let already_completed = survey_json
+                .pointer("/user_status/status")
+                .and_then(|status| status.as_str())
+                == Some("completed");
+            if !already_completed {
+                match QuotaService::new(state.db_pool.clone()).reserve(current_user.user_id, survey_id).await {
+                    Ok(Some(reservation)) => {
+                        survey_json["quota_reservation"] = serde_json::to_value(&reservation).unwrap_or_default();
+                    }
+                    Ok(None) => {}
+                    Err(QuotaError::Full(block)) => {
+                        return Ok(ResponseJson(ApiResponse {
+                            success: false,
+                            data: None,
+                            error: Some(ApiError {
+                                code: "SURVEY_QUOTA_FULL".to_string(),
+                                message: i18n::t(i18n::request_locale(), "api.quota.full"),
+                                details: serde_json::to_value(&block).ok(),
+                            }),
+                            request_id: Uuid::new_v4().to_string(),
+                            timestamp: Utc::now(),
+                            execution_time_ms: None,
+                            cached: false,
+                        }));
+                    }
+                    Err(e) => {
+                        eprintln!("Error reserving survey quota: {:?}", e);
+                        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                    }
+                }
+            }
+            
+            // Las respuestas correctas y los pesos no salen del servidor
+            // (si la definición no parsea se sirve tal cual, sin esas claves)
+            if let Err(e) = SurveyDefinition::strip_answer_keys(&mut survey_json) {
+                tracing::warn!("Survey {} definition could not be parsed: {}", survey_id, e);
+            }
+            
             Ok(ResponseJson(ApiResponse {
                 success: true,
                 data: Some(survey_json),
@@ -212,7 +259,7 @@ pub async fn get_survey_detail(
                 data: None,
                 error: Some(ApiError {
                     code: "SURVEY_NOT_FOUND".to_string(),
-                    message: "Encuesta no encontrada o inactiva".to_string(),
+                    message: i18n::t(i18n::request_locale(), "api.survey.not_found_or_inactive"),
                     details: None,
                 }),
                 request_id: Uuid::new_v4().to_string(),
@@ -228,7 +275,7 @@ pub async fn get_survey_detail(
                 data: None,
                 error: Some(ApiError {
                     code: "DATABASE_ERROR".to_string(),
-                    message: "Error al obtener detalle de encuesta".to_string(),
+                    message: i18n::t(i18n::request_locale(), "api.survey.detail_failed"),
                     details: Some(format!("Error: {}", e).into()),
                 }),
                 request_id: Uuid::new_v4().to_string(),
@@ -260,7 +307,7 @@ pub async fn save_survey_responses(
             data: None,
             error: Some(ApiError {
                 code: "PARTIAL_RESPONSES_NOT_SUPPORTED".to_string(),
-                message: "El guardado de respuestas parciales no está soportado aún".to_string(),
+                message: i18n::t(i18n::request_locale(), "api.survey.partial_not_supported"),
                 details: None,
             }),
             request_id: Uuid::new_v4().to_string(),
@@ -270,82 +317,55 @@ pub async fn save_survey_responses(
         }));
     }
     
-    // Usar la función de la base de datos para guardar respuestas
-    let result = sqlx::query_scalar!(
-        r#"
-        SELECT survey.api_submit_survey_responses($1, $2, $3, $4)
-        "#,
-        current_user.user_id as i32,
-        request.survey_id,
-        request.responses,
-        request.total_time_minutes
-    )
-    .fetch_one(&state.db_pool)
-    .await;
+    // Validación contra la definición y puntaje en el servidor
+    let result = SurveyService::new(state.db_pool.clone())
+        .submit(
+            current_user.user_id as i32,
+            request.survey_id,
+            &request.responses,
+            request.total_time_minutes,
+        )
+        .await;
     
     match result {
-        Ok(Some(response_json)) => {
+        Ok(submission) => {
             let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
             
-            // Verificar si la función retornó success: true
-            if let Some(success) = response_json.get("success").and_then(|v| v.as_bool()) {
-                if success {
-                    Ok(ResponseJson(ApiResponse {
-                        success: true,
-                        data: Some(response_json),
-                        error: None,
-                        request_id: Uuid::new_v4().to_string(),
-                        timestamp: Utc::now(),
-                        execution_time_ms: Some(execution_time.try_into().unwrap()),
-                        cached: false,
-                    }))
-                } else {
-                    // Error de negocio (ej: max attempts reached)
-                    let error_code = response_json.get("error")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("BUSINESS_ERROR");
-                    let error_message = response_json.get("message")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("Error en el procesamiento de la encuesta");
-                    
-                    Ok(ResponseJson(ApiResponse {
-                        success: false,
-                        data: None,
-                        error: Some(ApiError {
-                            code: error_code.to_string(),
-                            message: error_message.to_string(),
-                            details: Some(response_json),
-                        }),
-                        request_id: Uuid::new_v4().to_string(),
-                        timestamp: Utc::now(),
-                        execution_time_ms: Some(execution_time.try_into().unwrap()),
-                        cached: false,
-                    }))
-                }
-            } else {
-                Ok(ResponseJson(ApiResponse {
-                    success: false,
-                    data: None,
-                    error: Some(ApiError {
-                        code: "INVALID_RESPONSE".to_string(),
-                        message: "Respuesta inválida de la función de base de datos".to_string(),
-                        details: Some(response_json),
-                    }),
-                    request_id: Uuid::new_v4().to_string(),
-                    timestamp: Utc::now(),
-                    execution_time_ms: None,
-                    cached: false,
-                }))
-            }
+            let mut data = serde_json::to_value(&submission).unwrap_or_default();
+            data["success"] = serde_json::Value::Bool(true);
+            
+            Ok(ResponseJson(ApiResponse {
+                success: true,
+                data: Some(data),
+                error: None,
+                request_id: Uuid::new_v4().to_string(),
+                timestamp: Utc::now(),
+                execution_time_ms: Some(execution_time.try_into().unwrap()),
+                cached: false,
+            }))
         }
-        Ok(None) => {
+        Err(e) => {
+            let details = match &e {
+                SurveyError::InvalidResponses(issues) => Some(serde_json::json!({ "issues": issues })),
+                SurveyError::Database(db_error) => {
+                    eprintln!("Error saving survey responses: {:?}", db_error);
+                    None
+                }
+                _ => None,
+            };
+            let message = match &e {
+                SurveyError::Database(_) => i18n::t(i18n::request_locale(), "api.survey.save_failed"),
+                SurveyError::InvalidDefinition { .. } => i18n::t(i18n::request_locale(), "api.survey.unavailable"),
+                _ => e.to_string(),
+            };
+            
             Ok(ResponseJson(ApiResponse {
                 success: false,
                 data: None,
                 error: Some(ApiError {
-                    code: "NULL_RESPONSE".to_string(),
-                    message: "La función de base de datos retornó NULL".to_string(),
-                    details: None,
+                    code: e.code().to_string(),
+                    message,
+                    details,
                 }),
                 request_id: Uuid::new_v4().to_string(),
                 timestamp: Utc::now(),
@@ -353,23 +373,169 @@ pub async fn save_survey_responses(
                 cached: false,
             }))
         }
-        Err(e) => {
-            eprintln!("Error saving survey responses: {:?}", e);
-            Ok(ResponseJson(ApiResponse {
+    }
+}
+
+// ============================================
+// API 4: DEFINICIONES (ADMIN)
+// ============================================
+
+/// POST /api/v4/surveys/admin/definitions/validate
+/// Valida una definición sin guardarla (tipos, opciones, show_if, pesos)
+pub async fn validate_survey_definition(
+    Extension(current_user): Extension<CurrentUser>,
+    Json(questions): Json<serde_json::Value>,
+) -> Result<ResponseJson<ApiResponse<serde_json::Value>>, StatusCode> {
+    require_admin(&current_user)?;
+    
+    let checked = SurveyDefinition::parse(&questions).and_then(|definition| {
+        definition.validate()?;
+        Ok(definition)
+    });
+    
+    Ok(ResponseJson(definition_response(checked.map(|definition| {
+        serde_json::json!({
+            "valid": true,
+            "total_questions": definition.questions.len(),
+        })
+    }))))
+}
+
+/// PUT /api/v4/surveys/admin/:survey_id/definition
+/// Reemplaza las preguntas de una encuesta; se rechaza si la definición no es válida
+pub async fn update_survey_definition(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(survey_id): Path<i32>,
+    Json(questions): Json<serde_json::Value>,
+) -> Result<ResponseJson<ApiResponse<serde_json::Value>>, StatusCode> {
+    require_admin(&current_user)?;
+    
+    let definition = match SurveyDefinition::parse(&questions) {
+        Ok(definition) => definition,
+        Err(reason) => return Ok(ResponseJson(definition_response(Err(reason)))),
+    };
+    
+    let result = SurveyService::new(state.db_pool.clone())
+        .update_definition(survey_id, &definition)
+        .await;
+    
+    let response = match result {
+        Ok(()) => {
+            tracing::info!("Survey {} definition replaced by admin {}", survey_id, current_user.user_id);
+            Ok(serde_json::json!({
+                "survey_id": survey_id,
+                "total_questions": definition.questions.len(),
+            }))
+        }
+        Err(SurveyError::InvalidDefinition { reason, .. }) => Err(reason),
+        Err(SurveyError::NotFound) => {
+            return Ok(ResponseJson(ApiResponse {
                 success: false,
                 data: None,
                 error: Some(ApiError {
-                    code: "DATABASE_ERROR".to_string(),
-                    message: "Error al guardar respuestas de encuesta".to_string(),
-                    details: Some(format!("Error: {}", e).into()),
+                    code: "SURVEY_NOT_FOUND".to_string(),
+                    message: i18n::t(i18n::request_locale(), "api.not_found.survey"),
+                    details: None,
                 }),
                 request_id: Uuid::new_v4().to_string(),
                 timestamp: Utc::now(),
                 execution_time_ms: None,
                 cached: false,
-            }))
+            }));
+        }
+        Err(e) => {
+            eprintln!("Error updating survey definition: {:?}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
         }
+    };
+    
+    Ok(ResponseJson(definition_response(response)))
+}
+
+fn definition_response(result: Result<serde_json::Value, Text>) -> ApiResponse<serde_json::Value> {
+    match result {
+        Ok(data) => ApiResponse {
+            success: true,
+            data: Some(data),
+            error: None,
+            request_id: Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            execution_time_ms: None,
+            cached: false,
+        },
+        Err(reason) => ApiResponse {
+            success: false,
+            data: None,
+            error: Some(ApiError {
+                code: "INVALID_SURVEY_DEFINITION".to_string(),
+                message: reason.to_string(),
+                details: None,
+            }),
+            request_id: Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            execution_time_ms: None,
+            cached: false,
+        },
+    }
+}
+
+/// Quita de la lista las encuestas pendientes cuya cuota no admite al
+/// usuario y ajusta los conteos del resumen. Si la consulta de cuotas falla
+/// se devuelve la lista sin filtrar: la reserva al abrirla vuelve a validar.
+async fn hide_full_quota_surveys(state: &AppState, user_id: i64, surveys_json: &mut serde_json::Value) {
+    let open_ids: Vec<i32> = surveys_json["surveys"]
+        .as_array()
+        .map(|surveys| {
+            surveys
+                .iter()
+                .filter(|survey| matches!(survey["status"].as_str(), Some("pending" | "overdue")))
+                .filter_map(|survey| survey["survey_id"].as_i64().map(|id| id as i32))
+                .collect()
+        })
+        .unwrap_or_default();
+    if open_ids.is_empty() {
+        return;
+    }
+    
+    let blocked = match QuotaService::new(state.db_pool.clone()).blocked_surveys(user_id, &open_ids).await {
+        Ok(blocked) if !blocked.is_empty() => blocked,
+        Ok(_) => return,
+        Err(e) => {
+            tracing::warn!("Could not check survey quotas for user {}: {}", user_id, e);
+            return;
+        }
+    };
+    
+    let mut hidden: Vec<String> = Vec::new();
+    if let Some(surveys) = surveys_json["surveys"].as_array_mut() {
+        surveys.retain(|survey| {
+            let is_blocked = matches!(survey["status"].as_str(), Some("pending" | "overdue"))
+                && survey["survey_id"].as_i64().is_some_and(|id| blocked.contains(&(id as i32)));
+            if is_blocked {
+                hidden.push(survey["status"].as_str().unwrap_or_default().to_string());
+            }
+            !is_blocked
+        });
+    }
+    
+    if let Some(summary) = surveys_json.get_mut("summary") {
+        for status in &hidden {
+            for key in ["total_count".to_string(), format!("{}_count", status)] {
+                if let Some(count) = summary[&key].as_i64() {
+                    summary[&key] = serde_json::json!((count - 1).max(0));
+                }
+            }
+        }
+    }
+}
+
+fn require_admin(current_user: &CurrentUser) -> Result<(), StatusCode> {
+    if !is_admin(current_user.user_id) {
+        tracing::warn!("User {} attempted survey admin access", current_user.user_id);
+        return Err(StatusCode::FORBIDDEN);
     }
+    Ok(())
 }
 
 // ============================================
@@ -382,4 +548,6 @@ pub fn create_surveys_v4_router() -> Router<Arc<AppState>> {
         .route("/api/v4/surveys", get(get_user_surveys))
         .route("/api/v4/surveys/:survey_id", get(get_survey_detail))
         .route("/api/v4/surveys/responses", patch(save_survey_responses))
+        .route("/api/v4/surveys/admin/definitions/validate", post(validate_survey_definition))
+        .route("/api/v4/surveys/admin/:survey_id/definition", put(update_survey_definition))
 }
\ No newline at end of file
@@ -20,6 +20,14 @@ pub struct UserRegistrationRequest {
     #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
     #[validate(custom(function = "validate_name_format"))]
     pub name: String,
+    
+    /// Código o link de referido (opcional)
+    #[serde(default)]
+    pub referral_code: Option<String>,
+    
+    /// Identificador del dispositivo (antifraude de referidos)
+    #[serde(default)]
+    pub device_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1,7 +1,7 @@
 use axum::{
-    extract::{State, Request, Query},
+    extract::{Path, State, Request, Query},
     http::HeaderMap,
-    routing::{get, post},
+    routing::{get, post, put},
     Router,
     Json,
 };
@@ -10,11 +10,21 @@ use sqlx::{FromRow, types::Json as SqlxJson};
 use std::sync::Arc;
 use uuid::Uuid;
 use chrono::{DateTime, Utc, NaiveDate};
-use tracing::{info, error, debug};
+use tracing::{info, error, debug, warn};
 
+use crate::shared::admin::is_admin;
 use crate::state::AppState;
 use crate::api::common::{ApiResponse, ApiError};
 use crate::middleware::auth::{get_current_user_from_request, extract_user_from_headers};
+use crate::domains::lumimatch::{
+    service::DEFAULT_PREVIEW_SAMPLE,
+    tagging::TagDetail,
+    service::InvalidTargeting,
+    targeting::{age_from_birth, RuleExplanation, TARGETING_CACHE},
+    AudiencePreview, LumiMatchError, LumiMatchService, NewQuestion, QuestionUpdate, TagService,
+    TargetingContext,
+};
+use crate::services::event_bus_service::{DomainEvent, EventBus};
 
 // --- Models ---
 
@@ -27,6 +37,9 @@ pub struct TinderQuestion {
     pub targeting_rules: SqlxJson<serde_json::Value>,
     pub specific_date: Option<NaiveDate>,
     pub created_at: Option<DateTime<Utc>>,
+    /// Versión de las reglas para el cache de targeting compilado
+    #[serde(skip)]
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, FromRow)]
@@ -62,152 +75,105 @@ pub struct GetQuestionsParams {
 
 fn default_limit() -> u32 { 20 }
 
-// Helper struct for user context
-struct UserContext {
-    user_id: i64,
-    age: Option<i32>,
-    country: Option<String>,
-    tags: Vec<String>,
+#[derive(Debug, Deserialize)]
+pub struct AudiencePreviewRequest {
+    #[serde(default)]
+    pub targeting_rules: serde_json::Value,
+    /// Number of sample users to return (default: 20, max: 100)
+    pub sample_size: Option<usize>,
+}
+
+/// "Why am I seeing this?" for one question
+#[derive(Debug, Serialize)]
+pub struct QuestionExplanation {
+    pub question_id: Uuid,
+    pub title: String,
+    /// Whether the question matches the user's profile today
+    pub shown: bool,
+    pub reasons: Vec<RuleExplanation>,
+    /// Provenance of the user's tags that the rules used
+    pub tags: Vec<TagDetail>,
 }
 
 // --- Logic ---
 
-fn calculate_age(dob_str: &Option<String>) -> Option<i32> {
-    let dob_str = dob_str.as_ref()?;
-    // Try parsing YYYY-MM-DD
-    let dob = NaiveDate::parse_from_str(dob_str, "%Y-%m-%d").ok()?;
-    let now = Utc::now().date_naive();
-    let age = now.years_since(dob).unwrap_or(0);
-    Some(age as i32)
+/// Compiled targeting + specific_date (column-level, not in JSON).
+/// Rules are compiled once per (question, updated_at); stored rules that no
+/// longer validate hide the question instead of showing it to everyone.
+fn question_matches(question: &TinderQuestion, context: &TargetingContext, today: NaiveDate) -> bool {
+    if question.specific_date.is_some_and(|target_date| target_date != today) {
+        return false;
+    }
+    TARGETING_CACHE
+        .get_or_compile(question.id, question.updated_at, &question.targeting_rules.0)
+        .is_some_and(|compiled| compiled.matches(context))
 }
 
-fn matches_targeting(rules: &serde_json::Value, context: &UserContext, specific_date: &Option<NaiveDate>) -> bool {
-    // Check specific_date first (column-level, not in JSON)
-    if let Some(target_date) = specific_date {
-        let today = Utc::now().date_naive();
-        if *target_date != today {
-            return false;
-        }
-    }
+/// Profile + active tags (confident and not expired), fetched in parallel
+async fn load_context(state: &AppState, user_id: i64, today: NaiveDate) -> Result<TargetingContext, ApiError> {
+    let profile_future = sqlx::query!(
+        "SELECT date_of_birth, country_residence FROM dim_users WHERE id = $1",
+        user_id
+    )
+    .fetch_optional(&state.db_pool);
 
-    let rules_obj = match rules.as_object() {
-        Some(obj) => obj,
-        None => return true, // No rules = match all
-    };
+    let tag_service = TagService::new(state.db_pool.clone());
+    let tags_future = tag_service.active_tags(user_id as i32);
 
-    // 1. User IDs Check (specific users)
-    if let Some(user_ids) = rules_obj.get("user_ids").and_then(|v| v.as_array()) {
-        let allowed: Vec<i64> = user_ids.iter().filter_map(|v| v.as_i64()).collect();
-        if !allowed.is_empty() && !allowed.contains(&(context.user_id as i64)) {
-            return false;
-        }
-    }
+    // Execute both queries concurrently
+    let (user_profile_result, user_tags_result) = tokio::join!(profile_future, tags_future);
 
-    // 2. Age Check
-    if let Some(min_age) = rules_obj.get("min_age").and_then(|v| v.as_i64()) {
-        if let Some(age) = context.age {
-            if (age as i64) < min_age { return false; }
-        } else {
-            return false; // Age required but unknown
-        }
-    }
-    if let Some(max_age) = rules_obj.get("max_age").and_then(|v| v.as_i64()) {
-        if let Some(age) = context.age {
-            if (age as i64) > max_age { return false; }
-        }
-    }
+    let user_profile = user_profile_result.map_err(|e| {
+        error!("Failed to fetch user profile: {}", e);
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
+    })?;
 
-    // 3. Country Check
-    if let Some(countries) = rules_obj.get("countries").and_then(|v| v.as_array()) {
-        let user_country = context.country.as_deref().unwrap_or("");
-        let allowed: Vec<&str> = countries.iter().filter_map(|v| v.as_str()).collect();
-        if !allowed.is_empty() && !allowed.contains(&user_country) {
-            return false;
-        }
-    }
+    let user_tags: Vec<String> = user_tags_result.unwrap_or_default();
 
-    // 4. Tags Check (The "Engine" part)
-    // required_tags: User MUST have ALL these tags
-    if let Some(required) = rules_obj.get("required_tags").and_then(|v| v.as_array()) {
-        for tag_val in required {
-            if let Some(tag) = tag_val.as_str() {
-                if !context.tags.contains(&tag.to_string()) {
-                    return false;
-                }
-            }
-        }
-    }
+    Ok(if let Some(profile) = user_profile {
+        TargetingContext::new(
+            user_id,
+            age_from_birth(profile.date_of_birth.as_deref(), today),
+            profile.country_residence,
+            user_tags,
+        )
+    } else {
+        // Should not happen for authenticated user
+        TargetingContext::new(user_id, None, None, Vec::new())
+    })
+}
 
-    // any_tags: User MUST have AT LEAST ONE of these tags
-    if let Some(any) = rules_obj.get("any_tags").and_then(|v| v.as_array()) {
-        let mut found = false;
-        for tag_val in any {
-            if let Some(tag) = tag_val.as_str() {
-                if context.tags.contains(&tag.to_string()) {
-                    found = true;
-                    break;
-                }
-            }
-        }
-        if !found && !any.is_empty() { return false; }
-    }
 
-    // excluded_tags: User MUST NOT have ANY of these tags
-    if let Some(excluded) = rules_obj.get("excluded_tags").and_then(|v| v.as_array()) {
-        for tag_val in excluded {
-            if let Some(tag) = tag_val.as_str() {
-                if context.tags.contains(&tag.to_string()) {
-                    return false;
-                }
-            }
-        }
+/// Admin user from the Authorization header
+fn require_admin(headers: &HeaderMap) -> Result<i64, ApiError> {
+    let current_user = extract_user_from_headers(headers)
+        .map_err(|(_status, json_error)| {
+            ApiError::new("UNAUTHORIZED", &json_error.0.message)
+        })?;
+    if !is_admin(current_user.user_id) {
+        warn!("User {} attempted LumiMatch admin access", current_user.user_id);
+        return Err(ApiError::forbidden());
     }
-
-    // 5. Product-based targeting (via tags)
-    // product_codes -> tags like "product_code:ABC123"
-    if !check_prefixed_tags(rules_obj, "product_codes", "product_code:", &context.tags) { return false; }
-    if !check_prefixed_tags(rules_obj, "product_l1", "product_l1:", &context.tags) { return false; }
-    if !check_prefixed_tags(rules_obj, "product_l2", "product_l2:", &context.tags) { return false; }
-    if !check_prefixed_tags(rules_obj, "product_l3", "product_l3:", &context.tags) { return false; }
-    if !check_prefixed_tags(rules_obj, "product_l4", "product_l4:", &context.tags) { return false; }
-    if !check_prefixed_tags(rules_obj, "product_brands", "product_brand:", &context.tags) { return false; }
-
-    // 6. Issuer/Merchant-based targeting (via tags)
-    if !check_prefixed_tags(rules_obj, "issuer_rucs", "issuer_ruc:", &context.tags) { return false; }
-    if !check_prefixed_tags(rules_obj, "issuer_brand_names", "issuer_brand_name:", &context.tags) { return false; }
-    if !check_prefixed_tags(rules_obj, "issuer_store_names", "issuer_store_name:", &context.tags) { return false; }
-    if !check_prefixed_tags(rules_obj, "issuer_l1", "issuer_l1:", &context.tags) { return false; }
-    if !check_prefixed_tags(rules_obj, "issuer_l2", "issuer_l2:", &context.tags) { return false; }
-    if !check_prefixed_tags(rules_obj, "issuer_l3", "issuer_l3:", &context.tags) { return false; }
-    if !check_prefixed_tags(rules_obj, "issuer_l4", "issuer_l4:", &context.tags) { return false; }
-
-    true
+    Ok(current_user.user_id)
 }
 
-/// Helper function: Check if user has at least one tag matching the pattern.
-/// Example: rule_key="product_codes", prefix="product_code:", rule_values=["ABC", "XYZ"]
-/// Checks if user has tag "product_code:ABC" OR "product_code:XYZ"
-fn check_prefixed_tags(
-    rules_obj: &serde_json::Map<String, serde_json::Value>,
-    rule_key: &str,
-    prefix: &str,
-    user_tags: &[String],
-) -> bool {
-    if let Some(values) = rules_obj.get(rule_key).and_then(|v| v.as_array()) {
-        if values.is_empty() { return true; }
-        
-        // User must have at least ONE of these (OR logic)
-        for val in values {
-            if let Some(v) = val.as_str() {
-                let expected_tag = format!("{}{}", prefix, v.to_lowercase());
-                if user_tags.iter().any(|t| t.to_lowercase() == expected_tag) {
-                    return true;
-                }
-            }
+fn lumimatch_error(err: LumiMatchError) -> ApiError {
+    match err {
+        LumiMatchError::NotFound => ApiError::localized("NOT_FOUND", "api.not_found.question", &[]),
+        LumiMatchError::Invalid(reason) => ApiError::validation_error(&reason.to_string()),
+        LumiMatchError::Database(e) => {
+            error!("LumiMatch admin database error: {}", e);
+            ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
         }
-        return false; // Had rules but no match
     }
-    true // No rule for this key = pass
+}
+
+fn request_id(headers: &HeaderMap) -> String {
+    headers
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("unknown")
+        .to_string()
 }
 
 // --- Handlers ---
@@ -242,46 +208,15 @@ pub async fn get_pending_questions(
 
     // 2. Fetch User Context (Profile + Tags) IN PARALLEL
     // This reduces latency by ~30% compared to sequential fetching
-    let profile_future = sqlx::query!(
-        "SELECT date_of_birth, country_residence FROM dim_users WHERE id = $1",
-        user_id as i64
-    )
-    .fetch_optional(&state.db_pool);
-
-    let tags_future = sqlx::query_scalar!(
-        "SELECT tag FROM lumimatch.user_tags WHERE user_id = $1",
-        user_id as i32
-    )
-    .fetch_all(&state.db_pool);
-
-    // Execute both queries concurrently
-    let (user_profile_result, user_tags_result) = tokio::join!(profile_future, tags_future);
-
-    let user_profile = user_profile_result.map_err(|e| {
-        error!("Failed to fetch user profile: {}", e);
-        ApiError::new("INTERNAL_SERVER_ERROR", "Could not fetch user profile")
-    })?;
-
-    let user_tags: Vec<String> = user_tags_result.unwrap_or_default();
-
-    let context = if let Some(profile) = user_profile {
-        UserContext {
-            user_id,
-            age: calculate_age(&profile.date_of_birth),
-            country: profile.country_residence,
-            tags: user_tags,
-        }
-    } else {
-        // Should not happen for authenticated user
-        UserContext { user_id, age: None, country: None, tags: vec![] }
-    };
+    let today = Utc::now().date_naive();
+    let context = load_context(&state, user_id, today).await?;
 
     // 3. Fetch active questions not answered by user
     // We use a LEFT JOIN on lumimatch.user_answers to exclude answered questions
     // Over-fetch to ensure enough after targeting filter
     let questions = sqlx::query_as::<_, TinderQuestion>(
         r#"
-        SELECT q.id, q.title, q.image_url, q.priority, q.targeting_rules, q.specific_date, q.created_at
+        SELECT q.id, q.title, q.image_url, q.priority, q.targeting_rules, q.specific_date, q.created_at, q.updated_at
         FROM lumimatch.questions q
         LEFT JOIN lumimatch.user_answers a ON q.id = a.question_id AND a.user_id = $1
         WHERE q.is_active = true
@@ -298,13 +233,13 @@ pub async fn get_pending_questions(
     .await
     .map_err(|e| {
         error!("Failed to fetch lumimatch questions: {}", e);
-        ApiError::new("INTERNAL_SERVER_ERROR", "Could not fetch questions")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
 
     // 4. Filter questions based on targeting rules (including specific_date)
     let filtered_questions: Vec<TinderQuestion> = questions
         .into_iter()
-        .filter(|q| matches_targeting(&q.targeting_rules, &context, &q.specific_date))
+        .filter(|q| question_matches(q, &context, today))
         .take(requested_limit) // Apply requested limit after filtering
         .collect();
 
@@ -327,7 +262,7 @@ pub async fn get_pending_questions(
         .await
         .map_err(|e| {
             error!("Failed to fetch lumimatch options: {}", e);
-            ApiError::new("INTERNAL_SERVER_ERROR", "Could not fetch options")
+            ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
         })?
     };
 
@@ -395,7 +330,7 @@ pub async fn submit_answer(
     .await
     .map_err(|e| {
         error!("Failed to submit lumimatch answer: {}", e);
-        ApiError::new("INTERNAL_SERVER_ERROR", "Could not save answer")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.lumimatch.answer_not_saved", &[])
     })?;
 
     if result.rows_affected() == 0 {
@@ -403,16 +338,167 @@ pub async fn submit_answer(
         debug!("User {} already answered question {}", user_id, payload.question_id);
     } else {
         info!("User {} answered question {}", user_id, payload.question_id);
+        // Derives the option's tags (see UserTagSubscriber)
+        let answered = DomainEvent::LumiMatchAnswered {
+            user_id,
+            question_id: payload.question_id,
+            option_id: payload.option_id,
+        };
+        EventBus::publish_best_effort(&state.db_pool, &answered).await;
     }
 
     let execution_time_ms = start_time.elapsed().as_millis() as u64;
     Ok(Json(ApiResponse::success("Answer received".to_string(), request_id, Some(execution_time_ms), false)))
 }
 
+/// Why a question is (or is not) shown to the current user, with the tags
+/// behind each rule and where those tags came from
+pub async fn explain_question(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(question_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<QuestionExplanation>>, ApiError> {
+    let start_time = std::time::Instant::now();
+
+    let current_user = extract_user_from_headers(&headers)
+        .map_err(|(_status, json_error)| {
+            ApiError::new("UNAUTHORIZED", &json_error.0.message)
+        })?;
+    let user_id = current_user.user_id;
+
+    let question = sqlx::query_as::<_, TinderQuestion>(
+        r#"
+        SELECT id, title, image_url, priority, targeting_rules, specific_date, created_at, updated_at
+        FROM lumimatch.questions
+        WHERE id = $1
+        "#
+    )
+    .bind(question_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch lumimatch question {}: {}", question_id, e);
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
+    })?
+    .ok_or_else(|| ApiError::localized("NOT_FOUND", "api.not_found.question", &[]))?;
+
+    let today = Utc::now().date_naive();
+    let context = load_context(&state, user_id, today).await?;
+
+    let reasons = TARGETING_CACHE
+        .get_or_compile(question.id, question.updated_at, &question.targeting_rules.0)
+        .map(|compiled| compiled.explain(&context))
+        .unwrap_or_default();
+    let mut used_tags: Vec<String> = reasons.iter().flat_map(|r| r.matched_tags.iter().cloned()).collect();
+    used_tags.sort();
+    used_tags.dedup();
+
+    let tags = TagService::new(state.db_pool.clone())
+        .tag_details(user_id as i32, &used_tags)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch tag details for user {}: {}", user_id, e);
+            ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
+        })?;
+
+    let explanation = QuestionExplanation {
+        question_id,
+        shown: question_matches(&question, &context, today),
+        title: question.title,
+        reasons,
+        tags,
+    };
+
+    let execution_time_ms = start_time.elapsed().as_millis() as u64;
+    Ok(Json(ApiResponse::success(explanation, request_id(&headers), Some(execution_time_ms), false)))
+}
+
+/// Create a question with its options (admin). targeting_rules is validated
+pub async fn admin_create_question(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<NewQuestion>,
+) -> Result<Json<ApiResponse<Uuid>>, ApiError> {
+    let start_time = std::time::Instant::now();
+    let admin_id = require_admin(&headers)?;
+
+    let question_id = LumiMatchService::new(state.db_pool.clone())
+        .create_question(&payload)
+        .await
+        .map_err(lumimatch_error)?;
+
+    info!("Admin {} created LumiMatch question {}", admin_id, question_id);
+
+    let execution_time_ms = start_time.elapsed().as_millis() as u64;
+    Ok(Json(ApiResponse::success(question_id, request_id(&headers), Some(execution_time_ms), false)))
+}
+
+/// Update a question (admin). Absent fields are left unchanged
+pub async fn admin_update_question(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(question_id): Path<Uuid>,
+    Json(payload): Json<QuestionUpdate>,
+) -> Result<Json<ApiResponse<Uuid>>, ApiError> {
+    let start_time = std::time::Instant::now();
+    let admin_id = require_admin(&headers)?;
+
+    LumiMatchService::new(state.db_pool.clone())
+        .update_question(question_id, &payload)
+        .await
+        .map_err(lumimatch_error)?;
+
+    info!("Admin {} updated LumiMatch question {}", admin_id, question_id);
+
+    let execution_time_ms = start_time.elapsed().as_millis() as u64;
+    Ok(Json(ApiResponse::success(question_id, request_id(&headers), Some(execution_time_ms), false)))
+}
+
+/// How many users (and a sample of them) a rule set matches right now (admin)
+pub async fn admin_audience_preview(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<AudiencePreviewRequest>,
+) -> Result<Json<ApiResponse<AudiencePreview>>, ApiError> {
+    let start_time = std::time::Instant::now();
+    require_admin(&headers)?;
+
+    let preview = LumiMatchService::new(state.db_pool.clone())
+        .audience_preview(&payload.targeting_rules, payload.sample_size.unwrap_or(DEFAULT_PREVIEW_SAMPLE))
+        .await
+        .map_err(lumimatch_error)?;
+
+    let execution_time_ms = start_time.elapsed().as_millis() as u64;
+    Ok(Json(ApiResponse::success(preview, request_id(&headers), Some(execution_time_ms), false)))
+}
+
+/// Stored questions whose targeting_rules no longer pass the schema (admin).
+/// The feed hides them; fix them with PUT /admin/questions/{question_id}
+pub async fn admin_invalid_targeting(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<Vec<InvalidTargeting>>>, ApiError> {
+    let start_time = std::time::Instant::now();
+    require_admin(&headers)?;
+
+    let report = LumiMatchService::new(state.db_pool.clone())
+        .invalid_targeting()
+        .await
+        .map_err(lumimatch_error)?;
+
+    let execution_time_ms = start_time.elapsed().as_millis() as u64;
+    Ok(Json(ApiResponse::success(report, request_id(&headers), Some(execution_time_ms), false)))
+}
+
 // --- Router ---
 
 pub fn create_tinder_router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/api/v4/lumimatch/questions", get(get_pending_questions))
+        .route("/api/v4/lumimatch/questions/:question_id/why", get(explain_question))
         .route("/api/v4/lumimatch/answers", post(submit_answer))
+        .route("/api/v4/lumimatch/admin/questions", post(admin_create_question))
+        .route("/api/v4/lumimatch/admin/questions/:question_id", put(admin_update_question))
+        .route("/api/v4/lumimatch/admin/audience-preview", post(admin_audience_preview))
+        .route("/api/v4/lumimatch/admin/invalid-targeting", get(admin_invalid_targeting))
 }
@@ -0,0 +1,249 @@
+use axum::{
+    extract::{Path, Query, State, Extension},
+    Json,
+    routing::{get, post, put},
+    Router,
+};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::shared::admin::is_admin;
+use crate::{
+    middleware::CurrentUser,
+    api::common::{ApiError, ApiResponse},
+    domains::gamification::trivia_service::{
+        self, AnswerResult, NewTriviaQuestion, ServedQuestion, TriviaDay, TriviaError, TriviaQuestion,
+        TriviaQuestionStats, TriviaService,
+    },
+    AppState,
+};
+
+// Response wrapper for JSON
+type ResponseJson<T> = Result<Json<ApiResponse<T>>, ApiError>;
+
+// ============================================================================
+// REQUEST/RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct TriviaAnswerRequest {
+    pub question_id: i32,
+    pub selected_option: i16,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NextQuestionResponse {
+    /// None once the day's set is finished
+    pub question: Option<ServedQuestion>,
+    pub day: TriviaDay,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PinSetRequest {
+    pub question_ids: Vec<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuestionStatusRequest {
+    pub is_active: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    pub days: Option<i32>,
+}
+
+// ============================================================================
+// API HANDLERS
+// ============================================================================
+
+/// Today's progress: answered, correct, Lumis earned and current streak
+#[axum::debug_handler]
+pub async fn get_today(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> ResponseJson<TriviaDay> {
+    let start_time = Utc::now();
+
+    let day = TriviaService::new(state.db_pool.clone())
+        .today(current_user.user_id as i32)
+        .await
+        .map_err(trivia_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(day, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Serve the current question (same deadline if already served) or the next one
+#[axum::debug_handler]
+pub async fn get_next_question(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> ResponseJson<NextQuestionResponse> {
+    let start_time = Utc::now();
+
+    let service = TriviaService::new(state.db_pool.clone());
+    let user_id = current_user.user_id as i32;
+    let question = service
+        .next_question(user_id, trivia_service::CHANNEL_API)
+        .await
+        .map_err(trivia_error)?;
+    let day = service.today(user_id).await.map_err(trivia_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(
+        NextQuestionResponse { question, day },
+        Uuid::new_v4().to_string(),
+        Some(execution_time.try_into().unwrap()),
+        false,
+    )))
+}
+
+/// Answer a served question; timing is measured on the server
+#[axum::debug_handler]
+pub async fn answer_question(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<TriviaAnswerRequest>,
+) -> ResponseJson<AnswerResult> {
+    let start_time = Utc::now();
+
+    let result = TriviaService::new(state.db_pool.clone())
+        .answer(current_user.user_id as i32, request.question_id, request.selected_option, trivia_service::CHANNEL_API)
+        .await
+        .map_err(trivia_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(result, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Add a question to the bank (admin)
+#[axum::debug_handler]
+pub async fn admin_create_question(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<NewTriviaQuestion>,
+) -> ResponseJson<TriviaQuestion> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let question = TriviaService::new(state.db_pool.clone())
+        .create_question(request, current_user.user_id)
+        .await
+        .map_err(trivia_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(question, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Enable or retire a question (admin)
+#[axum::debug_handler]
+pub async fn admin_set_question_status(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(question_id): Path<i32>,
+    Json(request): Json<QuestionStatusRequest>,
+) -> ResponseJson<bool> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let updated = TriviaService::new(state.db_pool.clone())
+        .set_question_active(question_id, request.is_active)
+        .await
+        .map_err(trivia_error)?;
+    if !updated {
+        return Err(ApiError::localized("NOT_FOUND", "api.not_found.trivia_question", &[]));
+    }
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(true, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Pin the question set for a date before anyone plays it (admin)
+#[axum::debug_handler]
+pub async fn admin_pin_daily_set(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(set_date): Path<NaiveDate>,
+    Json(request): Json<PinSetRequest>,
+) -> ResponseJson<bool> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    TriviaService::new(state.db_pool.clone())
+        .pin_daily_set(set_date, &request.question_ids, current_user.user_id)
+        .await
+        .map_err(trivia_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(true, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+/// Per-question accuracy, timeouts and flagged answers (admin)
+#[axum::debug_handler]
+pub async fn admin_stats(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(query): Query<StatsQuery>,
+) -> ResponseJson<Vec<TriviaQuestionStats>> {
+    let start_time = Utc::now();
+
+    require_admin(&current_user)?;
+
+    let stats = TriviaService::new(state.db_pool.clone())
+        .stats(query.days.unwrap_or(7).clamp(1, 90))
+        .await
+        .map_err(trivia_error)?;
+
+    let execution_time = Utc::now().signed_duration_since(start_time).num_milliseconds();
+
+    Ok(Json(ApiResponse::success(stats, Uuid::new_v4().to_string(), Some(execution_time.try_into().unwrap()), false)))
+}
+
+fn require_admin(current_user: &CurrentUser) -> Result<(), ApiError> {
+    if !is_admin(current_user.user_id) {
+        tracing::warn!("User {} attempted trivia admin access", current_user.user_id);
+        return Err(ApiError::forbidden());
+    }
+    Ok(())
+}
+
+fn trivia_error(err: TriviaError) -> ApiError {
+    match err {
+        TriviaError::Invalid(_) | TriviaError::InvalidOption => ApiError::validation_error(&err.to_string()),
+        TriviaError::NotServed => ApiError::bad_request(&err.to_string()),
+        TriviaError::AlreadyAnswered | TriviaError::SetInUse(_) => ApiError::new("CONFLICT", &err.to_string()),
+        TriviaError::NotEnoughQuestions => ApiError::localized("NOT_FOUND", "api.not_found.trivia", &[]),
+        TriviaError::Database(e) => {
+            tracing::error!("Trivia database error: {}", e);
+            ApiError::localized("DATABASE_ERROR", "api.database_error", &[])
+        }
+    }
+}
+
+// ============================================================================
+// ROUTER CREATION
+// ============================================================================
+
+/// Create router for trivia endpoints
+pub fn create_trivia_v4_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/v4/trivia/today", get(get_today))
+        .route("/api/v4/trivia/next", get(get_next_question))
+        .route("/api/v4/trivia/answer", post(answer_question))
+        .route("/api/v4/trivia/admin/questions", post(admin_create_question))
+        .route("/api/v4/trivia/admin/questions/:question_id/status", put(admin_set_question_status))
+        .route("/api/v4/trivia/admin/sets/:set_date", put(admin_pin_daily_set))
+        .route("/api/v4/trivia/admin/stats", get(admin_stats))
+}
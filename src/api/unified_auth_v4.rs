@@ -6,10 +6,12 @@
 // ============================================================================
 
 use crate::{
+    domains::gamification::referral_service::{self, SignupContext},
     models::unified_auth::{
         UnifiedAuthRequest,
     },
     services::{
+        event_bus_service::{DomainEvent, EventBus},
         google_service::GoogleService,
         token_service::TokenService,
         redis_service::RedisService,
@@ -129,7 +131,35 @@ pub async fn unified_auth(
 
     // Use the real authentication service with client info
     match auth_service.authenticate_with_client_info(&request, ip_address, user_agent).await {
-        Ok(response) => Ok((StatusCode::OK, Json(response)).into_response()),
+        Ok(response) => {
+            if response.metadata.is_new_user {
+                if let crate::models::unified_auth::AuthResult::Success { user, .. } = &response.result {
+                    let registered = DomainEvent::UserRegistered {
+                        user_id: user.id,
+                        source: response.metadata.provider_used.clone(),
+                    };
+                    EventBus::publish_best_effort(&app_state.db_pool, &registered).await;
+                }
+            }
+
+            // Referral attribution only for accounts created by this request
+            if let (Some(referral_code), true) = (request.referral_code.as_deref(), response.metadata.is_new_user) {
+                if let crate::models::unified_auth::AuthResult::Success { user, .. } = &response.result {
+                    crate::api::gamification_service::attribute_signup_referral(
+                        &app_state.db_pool,
+                        user.id,
+                        referral_code,
+                        SignupContext {
+                            source: referral_service::SOURCE_UNIFIED_AUTH,
+                            email: user.email.clone(),
+                            ip_address: ip_address.map(str::to_string),
+                            device_id: request.client_info.as_ref().and_then(|ci| ci.device_id.clone()),
+                        },
+                    ).await;
+                }
+            }
+            Ok((StatusCode::OK, Json(response)).into_response())
+        }
         Err(e) => {
             error!("Authentication failed: {}", e);
             let error_response = crate::models::unified_auth::UnifiedAuthResponse {
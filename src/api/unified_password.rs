@@ -14,6 +14,7 @@ use uuid::Uuid;
 
 use crate::api::common::{ApiResponse, ApiError};
 use crate::api::verification_v4::send_email_verification;
+use crate::shared::i18n;
 use crate::state::AppState;
 
 fn generate_request_id() -> String {
@@ -158,10 +159,10 @@ pub struct SetPasswordWithEmailCodeResponse {
 // Validación de contraseña
 pub fn validate_password(password: &str) -> Result<(), ApiError> {
     if password.len() < 8 {
-        return Err(ApiError::bad_request("Password must be at least 8 characters long"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.password.too_short", &[]));
     }
     if password.len() > 128 {
-        return Err(ApiError::bad_request("Password must be less than 128 characters"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.password.too_long", &[]));
     }
     
     let has_upper = password.chars().any(|c| c.is_uppercase());
@@ -170,9 +171,7 @@ pub fn validate_password(password: &str) -> Result<(), ApiError> {
     let has_special = password.chars().any(|c| "!@#$%^&*()_+-=[]{}|;:,.<>?".contains(c));
     
     if !has_upper || !has_lower || !has_digit || !has_special {
-        return Err(ApiError::bad_request(
-            "Password must contain at least one uppercase letter, one lowercase letter, one number, and one special character"
-        ));
+        return Err(ApiError::localized("BAD_REQUEST", "api.password.too_weak", &[]));
     }
     
     Ok(())
@@ -202,7 +201,7 @@ pub async fn request_password_code(
     
     // Validar formato de email
     if !payload.email.contains('@') || payload.email.len() < 5 {
-        return Err(ApiError::bad_request("Invalid email format"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.auth.invalid_email", &[]));
     }
     
     // Verificar que el usuario existe
@@ -218,7 +217,7 @@ pub async fn request_password_code(
             error = %e,
             "❌ Database error while checking user"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     let user = user.ok_or_else(|| {
@@ -227,24 +226,24 @@ pub async fn request_password_code(
             email = %payload.email,
             "⚠️ User not found for password code request"
         );
-        ApiError::not_found("User not found")
+        ApiError::localized("NOT_FOUND", "api.not_found.user", &[])
     })?;
     
     // Validar purpose según el estado del usuario
     match payload.purpose {
         PasswordCodePurpose::FirstTimeSetup => {
             if user.password_hash.is_some() {
-                return Err(ApiError::bad_request("User already has a password set"));
+                return Err(ApiError::localized("BAD_REQUEST", "api.password.already_set", &[]));
             }
         }
         PasswordCodePurpose::ResetPassword => {
             if user.password_hash.is_none() {
-                return Err(ApiError::bad_request("User has no password to reset. Use first_time_setup instead"));
+                return Err(ApiError::localized("BAD_REQUEST", "api.password.none_to_reset", &[]));
             }
         }
         PasswordCodePurpose::ChangePassword => {
             if user.password_hash.is_none() {
-                return Err(ApiError::bad_request("User has no password to change. Use first_time_setup instead"));
+                return Err(ApiError::localized("BAD_REQUEST", "api.password.none_to_change", &[]));
             }
         }
         PasswordCodePurpose::EmailVerification => {
@@ -271,7 +270,7 @@ pub async fn request_password_code(
             error = %e,
             "❌ Database error while checking rate limit"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     if recent_codes.count.unwrap_or(0) >= 3 {
@@ -280,7 +279,7 @@ pub async fn request_password_code(
             email = %payload.email,
             "⚠️ Rate limit exceeded for password code requests"
         );
-        return Err(ApiError::too_many_requests("Too many verification codes requested. Try again in 1 hour"));
+        return Err(ApiError::localized("TOO_MANY_REQUESTS", "api.password.too_many_codes", &[]));
     }
     
     // Invalidar códigos anteriores del mismo tipo
@@ -299,7 +298,7 @@ pub async fn request_password_code(
             error = %e,
             "❌ Database error while invalidating old codes"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     // Generar nuevo código
@@ -324,11 +323,11 @@ pub async fn request_password_code(
             error = %e,
             "❌ Database error while saving verification code"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     // Enviar email de verificación
-    if let Err(e) = send_email_verification(&payload.email, &code, &request_id).await {
+    if let Err(e) = send_email_verification(&payload.email, &code, &request_id, i18n::request_locale()).await {
         error!(
             request_id = %request_id,
             error = %e,
@@ -402,7 +401,7 @@ pub async fn set_password_with_code(
     
     // Validar que las contraseñas coinciden
     if payload.new_password != payload.confirmation_password {
-        return Err(ApiError::bad_request("Passwords do not match"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.password.mismatch", &[]));
     }
     
     // Validar fortaleza de contraseña
@@ -426,7 +425,7 @@ pub async fn set_password_with_code(
             error = %e,
             "❌ Database error while checking verification code"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     let verification = verification.ok_or_else(|| {
@@ -435,7 +434,7 @@ pub async fn set_password_with_code(
             email = %payload.email,
             "⚠️ Invalid or expired verification code"
         );
-        ApiError::bad_request("Invalid or expired verification code")
+        ApiError::localized("BAD_REQUEST", "api.verification.invalid_code", &[])
     })?;
     
     // Verificar que no está expirado
@@ -445,7 +444,7 @@ pub async fn set_password_with_code(
             expires_at = %verification.expires_at,
             "⚠️ Verification code has expired"
         );
-        return Err(ApiError::bad_request("Verification code has expired"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.verification.expired", &[]));
     }
     
     // Verificar intentos
@@ -456,7 +455,7 @@ pub async fn set_password_with_code(
             max_attempts = verification.max_attempts,
             "⚠️ Too many attempts for verification code"
         );
-        return Err(ApiError::bad_request("Too many attempts. Request a new code"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.verification.too_many_attempts", &[]));
     }
     
     // Incrementar intentos
@@ -472,7 +471,7 @@ pub async fn set_password_with_code(
             error = %e,
             "❌ Database error while updating attempts"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     // Hash de la nueva contraseña
@@ -483,7 +482,7 @@ pub async fn set_password_with_code(
                 error = %e,
                 "❌ Failed to hash password"
             );
-            ApiError::internal_server_error("Failed to hash password")
+            ApiError::localized("INTERNAL_SERVER_ERROR", "api.auth.password_processing_failed", &[])
         })?;
     
     // Actualizar contraseña del usuario
@@ -502,7 +501,7 @@ pub async fn set_password_with_code(
             error = %e,
             "❌ Database error while updating user password"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     // Marcar código como usado
@@ -518,7 +517,7 @@ pub async fn set_password_with_code(
             error = %e,
             "❌ Database error while marking code as used"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     // Generar JWT token para login automático
@@ -591,7 +590,7 @@ pub async fn verify_email_only(
             error = %e,
             "❌ Database error while fetching verification code"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     let verification = verification.ok_or_else(|| {
@@ -619,7 +618,7 @@ pub async fn verify_email_only(
             }
         });
         
-        ApiError::bad_request("Invalid or expired email verification code")
+        ApiError::localized("BAD_REQUEST", "api.verification.invalid_code", &[])
     })?;
     
     // Verificar expiración
@@ -629,7 +628,7 @@ pub async fn verify_email_only(
             email = %payload.email,
             "⚠️ Verification code expired"
         );
-        return Err(ApiError::bad_request("Verification code expired"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.verification.expired", &[]));
     }
     
     // Verificar intentos máximos
@@ -641,7 +640,7 @@ pub async fn verify_email_only(
             max_attempts = verification.max_attempts,
             "⚠️ Maximum attempts exceeded"
         );
-        return Err(ApiError::bad_request("Maximum verification attempts exceeded"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.verification.too_many_attempts", &[]));
     }
     
     // Marcar código como usado
@@ -657,7 +656,7 @@ pub async fn verify_email_only(
             error = %e,
             "❌ Database error while marking code as used"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     // Log audit event
@@ -714,7 +713,7 @@ pub async fn set_password_with_email_code(
             email = %payload.email,
             "⚠️ Password confirmation mismatch"
         );
-        return Err(ApiError::bad_request("Password confirmation does not match"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.password.mismatch", &[]));
     }
     
     // Validar contraseña
@@ -738,7 +737,7 @@ pub async fn set_password_with_email_code(
             error = %e,
             "❌ Database error while fetching verification code"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     let verification = verification.ok_or_else(|| {
@@ -747,7 +746,7 @@ pub async fn set_password_with_email_code(
             email = %payload.email,
             "⚠️ Invalid or expired email verification code"
         );
-        ApiError::bad_request("Invalid or expired email verification code")
+        ApiError::localized("BAD_REQUEST", "api.verification.invalid_code", &[])
     })?;
     
     // Verificar expiración
@@ -757,7 +756,7 @@ pub async fn set_password_with_email_code(
             email = %payload.email,
             "⚠️ Email verification code expired"
         );
-        return Err(ApiError::bad_request("Email verification code expired"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.verification.expired", &[]));
     }
     
     // Verificar intentos máximos
@@ -769,7 +768,7 @@ pub async fn set_password_with_email_code(
             max_attempts = verification.max_attempts,
             "⚠️ Maximum attempts exceeded for email verification code"
         );
-        return Err(ApiError::bad_request("Maximum verification attempts exceeded"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.verification.too_many_attempts", &[]));
     }
     
     // Verificar que el usuario no tenga contraseña ya establecida
@@ -785,7 +784,7 @@ pub async fn set_password_with_email_code(
             error = %e,
             "❌ Database error while fetching user"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     let user = user.ok_or_else(|| {
@@ -794,7 +793,7 @@ pub async fn set_password_with_email_code(
             email = %payload.email,
             "⚠️ User not found"
         );
-        ApiError::not_found("User not found")
+        ApiError::localized("NOT_FOUND", "api.not_found.user", &[])
     })?;
     
     // Verificar que no tenga contraseña ya establecida
@@ -805,7 +804,7 @@ pub async fn set_password_with_email_code(
             user_id = user.id,
             "⚠️ User already has password set"
         );
-        return Err(ApiError::bad_request("User already has a password set. Use reset password flow instead"));
+        return Err(ApiError::localized("BAD_REQUEST", "api.password.already_set_use_reset", &[]));
     }
     
     // Hash de la nueva contraseña
@@ -816,7 +815,7 @@ pub async fn set_password_with_email_code(
                 error = %e,
                 "❌ Failed to hash password"
             );
-            ApiError::internal_server_error("Failed to hash password")
+            ApiError::localized("INTERNAL_SERVER_ERROR", "api.auth.password_processing_failed", &[])
         })?;
     
     // Actualizar contraseña del usuario
@@ -835,7 +834,7 @@ pub async fn set_password_with_email_code(
             error = %e,
             "❌ Database error while updating user password"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     // Marcar código de verificación como usado
@@ -851,7 +850,7 @@ pub async fn set_password_with_email_code(
             error = %e,
             "❌ Database error while marking verification code as used"
         );
-        ApiError::internal_server_error("Database error")
+        ApiError::localized("INTERNAL_SERVER_ERROR", "api.database_error", &[])
     })?;
     
     // Generar JWT token para login automático
@@ -920,7 +919,7 @@ pub async fn send_verification_unified(
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let email = payload.get("email")
         .and_then(|e| e.as_str())
-        .ok_or_else(|| ApiError::bad_request("Email is required"))?;
+        .ok_or_else(|| ApiError::localized("BAD_REQUEST", "api.verification.email_required", &[]))?;
     
     let request = RequestPasswordCodeRequest {
         email: email.to_string(),
@@ -295,7 +295,7 @@ pub async fn process_url_handler(
     info!("Processing URL request for user {}: {}", user_id, url);
     
     if url.trim().is_empty() {
-        return Err(ApiError::validation_error("URL is required"));
+        return Err(ApiError::localized("VALIDATION_ERROR", "api.invoice_url.url_required", &[]));
     }
 
     // 1. Get final URL after following redirections
@@ -322,9 +322,7 @@ pub async fn process_url_handler(
        !final_url.contains("fep.mef.gob.pa") &&
        !final_url.contains("mef.gob.pa") {
         error!("❌ Invalid final URL - not from MEF Panama: {}", final_url);
-        return Err(ApiError::validation_error(
-            "La URL no corresponde a una factura válida del MEF de Panamá"
-        ));
+        return Err(ApiError::localized("VALIDATION_ERROR", "api.invoice_url.not_mef", &[]));
     }
 
     info!("✅ Final URL validated as MEF invoice: {}", final_url);
@@ -493,7 +491,7 @@ pub async fn process_url_handler(
                 Ok(tx) => tx,
                 Err(tx_error) => {
                     error!("Failed to start transaction for mef_pending: {}", tx_error);
-                    return Err(ApiError::new("SCRAPING_ERROR", &format!("Error al extraer datos de la factura: {}", e)));
+                    return Err(ApiError::localized("SCRAPING_ERROR", "api.invoice_url.scraping_failed", &[]));
                 }
             };
             
@@ -583,23 +581,24 @@ pub struct DgiCufeResponse {
 }
 
 /// Validates CUFE format
-/// Valid CUFE: starts with "FE", 60-75 characters, alphanumeric with hyphens
+/// Valid CUFE: starts with "FE", 60-75 characters, alphanumeric with hyphens.
+/// The error is a message catalog id
 fn validate_cufe(cufe: &str) -> Result<String, &'static str> {
     let cufe = cufe.trim().to_uppercase();
     
     // Check prefix
     if !cufe.starts_with("FE") {
-        return Err("CUFE debe comenzar con 'FE'");
+        return Err("api.cufe.invalid_prefix");
     }
     
     // Check length (CUFE typically 66-70 chars, but allow some flexibility)
     if cufe.len() < 60 || cufe.len() > 75 {
-        return Err("CUFE debe tener entre 60 y 75 caracteres");
+        return Err("api.cufe.invalid_length");
     }
     
     // Check valid characters (alphanumeric and hyphens only)
     if !cufe.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
-        return Err("CUFE contiene caracteres inválidos");
+        return Err("api.cufe.invalid_characters");
     }
     
     Ok(cufe)
@@ -736,9 +735,9 @@ pub async fn process_cufe_handler(
     // 1. Validate CUFE format
     let cufe = match validate_cufe(&request.cufe) {
         Ok(valid_cufe) => valid_cufe,
-        Err(error_msg) => {
-            warn!("❌ Invalid CUFE format from user {}: {}", user_id, error_msg);
-            return Err(ApiError::validation_error(error_msg));
+        Err(message_id) => {
+            warn!("❌ Invalid CUFE format from user {}: {}", user_id, message_id);
+            return Err(ApiError::localized("VALIDATION_ERROR", message_id, &[]));
         }
     };
     
@@ -750,7 +749,7 @@ pub async fn process_cufe_handler(
     
     if captcha_token.is_empty() {
         error!("❌ DGI captcha token not configured");
-        return Err(ApiError::new("CONFIG_ERROR", "Servicio DGI no configurado. Contacte al administrador."));
+        return Err(ApiError::localized("CONFIG_ERROR", "api.cufe.dgi_not_configured", &[]));
     }
     
     info!("🔑 Using captcha token ({} chars) and session ({} chars)", 
@@ -51,9 +51,9 @@ pub async fn get_current_user_profile(
         .bind(user_id)
         .fetch_optional(&state.db_pool)
         .await
-        .map_err(|e| ApiError::database_error(&format!("Query execution failed: {}", e)))?;
+        .map_err(|e| ApiError::database_failure("Query execution failed", e))?;
 
-    let profile = result.ok_or_else(|| ApiError::not_found("User profile"))?;
+    let profile = result.ok_or_else(|| ApiError::localized("NOT_FOUND", "api.not_found.profile", &[]))?;
     
     // Convert to safe response using From trait (removes sensitive data like password)
     let safe_data = UserProfileSafeResponse::from(profile);
@@ -1,6 +1,6 @@
 use axum::{
     extract::State,
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
     routing::post,
     Router,
@@ -19,6 +19,8 @@ use crate::api::templates::user_registration_templates::{
     EMAIL_APP_SOURCE, JWT_EXPIRATION_HOURS
 };
 use crate::utils::create_jwt_token;
+use crate::domains::gamification::referral_service::{self, SignupContext};
+use crate::services::event_bus_service::{DomainEvent, EventBus};
 
 // ============================================================================
 // API HANDLERS
@@ -27,6 +29,7 @@ use crate::utils::create_jwt_token;
 /// Register a new user with email, password, and name
 pub async fn register_user(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(req): Json<UserRegistrationRequest>,
 ) -> Result<Json<UserRegistrationResponse>, StatusCode> {
     let request_id = uuid::Uuid::new_v4().to_string();
@@ -104,6 +107,31 @@ pub async fn register_user(
         Ok(user_id) => {
             info!("Request {}: Successfully created user with ID: {}", request_id, user_id);
             
+            let registered = DomainEvent::UserRegistered {
+                user_id: user_id as i64,
+                source: EMAIL_APP_SOURCE.to_string(),
+            };
+            EventBus::publish_best_effort(&state.db_pool, &registered).await;
+            
+            // Atribución de referido (no bloquea el registro)
+            if let Some(referral_code) = req.referral_code.as_deref().filter(|c| !c.trim().is_empty()) {
+                let ip_address = headers.get("x-forwarded-for")
+                    .or_else(|| headers.get("x-real-ip"))
+                    .and_then(|h| h.to_str().ok())
+                    .map(|s| s.split(',').next().unwrap_or(s).trim().to_string());
+                crate::api::gamification_service::attribute_signup_referral(
+                    &state.db_pool,
+                    user_id as i64,
+                    referral_code,
+                    SignupContext {
+                        source: referral_service::SOURCE_REGISTER,
+                        email: Some(email.clone()),
+                        ip_address,
+                        device_id: req.device_id.clone(),
+                    },
+                ).await;
+            }
+            
             // Generate JWT token
             let expires_in = JWT_EXPIRATION_HOURS * 3600;
             match create_jwt_token(user_id as i64, &email) {
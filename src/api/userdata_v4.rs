@@ -126,6 +126,8 @@ pub struct UpdateUserData {
     pub segment_activity: Option<String>,
     pub genre: Option<String>,
     pub ws_id: Option<String>,
+    /// Ciudad de residencia (alcance "city" de los leaderboards)
+    pub city: Option<String>,
 }
 
 /// PUT /api/v4/userdata - Actualizar datos del usuario en public.dim_users
@@ -182,6 +184,11 @@ pub async fn update_user_data(
         params.push(payload.ws_id.clone().unwrap());
         param_count += 1;
     }
+    if payload.city.is_some() {
+        set_clauses.push(format!("city = ${}", param_count));
+        params.push(payload.city.clone().unwrap());
+        param_count += 1;
+    }
 
     // Si no hay campos para actualizar, retornar error
     if set_clauses.is_empty() {
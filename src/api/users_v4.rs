@@ -114,7 +114,7 @@ async fn get_user_profile_data(
     // TODO: Get user basic info from database
     // For now, return simulated user data
     if user_id <= 0 {
-        return Err(ApiError::not_found("User not found"));
+        return Err(ApiError::localized("NOT_FOUND", "api.not_found.user", &[]));
     }
 
     // Create UserProfileResponse with correct fields from user_profile_templates
@@ -86,7 +86,7 @@ pub async fn get_user_invoices(
         .bind(offset as i64)
         .fetch_all(&state.db_pool)
         .await
-        .map_err(|e| ApiError::database_error(&format!("Query failed: {}", e)))
+        .map_err(|e| ApiError::database_failure("Query failed", e))
         .map(|rows| (rows, false))?;
 
     let execution_time = start_time.elapsed().as_millis() as u64;
@@ -173,7 +173,7 @@ pub async fn deduct_user_balance(
         .await?;
 
     if affected_rows == 0 {
-        return Err(ApiError::validation_error("Insufficient balance or user not found"));
+        return Err(ApiError::localized("VALIDATION_ERROR", "api.balance.insufficient_or_no_user", &[]));
     }
 
     let execution_time = start_time.elapsed().as_millis() as u64;
@@ -213,11 +213,11 @@ pub async fn update_invoice_status(
         .bind(invoice_id)
         .fetch_optional(&state.db_pool)
         .await
-        .map_err(|e| ApiError::database_error(&format!("Failed to get user_id: {}", e)))?;
+        .map_err(|e| ApiError::database_failure("Failed to get user_id", e))?;
 
     let user_id = user_row
         .map(|(uid,)| uid)
-        .ok_or_else(|| ApiError::not_found("Invoice"))?;
+        .ok_or_else(|| ApiError::localized("NOT_FOUND", "api.not_found.invoice", &[]))?;
 
     // Update invoice status
     let sql = "UPDATE invoices SET status = $1, notes = $2, updated_at = NOW() WHERE invoice_id = $3";
@@ -230,7 +230,7 @@ pub async fn update_invoice_status(
         .await?;
 
     if affected_rows == 0 {
-        return Err(ApiError::not_found("Invoice"));
+        return Err(ApiError::localized("NOT_FOUND", "api.not_found.invoice", &[]));
     }
 
     let execution_time = start_time.elapsed().as_millis() as u64;
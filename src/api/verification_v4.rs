@@ -11,6 +11,7 @@ use redis::AsyncCommands;
 use chrono::{Utc, Duration};
 use uuid;
 
+use crate::shared::i18n::{self, Locale};
 use crate::state::AppState;
 use crate::api::templates::verification_templates::{
     SendVerificationRequest, SendVerificationResponse,
@@ -251,7 +252,7 @@ pub async fn send_verification_code(
     // Send verification code
     let actual_method = match method {
         "email" => {
-            if let Err(e) = send_email_verification(&email, &code, &request_id).await {
+            if let Err(e) = send_email_verification(&email, &code, &request_id, i18n::request_locale()).await {
                 error!("Request {}: Failed to send email: {}", request_id, e);
                 return Err(StatusCode::INTERNAL_SERVER_ERROR);
             }
@@ -263,7 +264,7 @@ pub async fn send_verification_code(
             warn!("Request {}: WhatsApp not implemented, falling back to email", request_id);
             
             // Fallback to email
-            if let Err(e) = send_email_verification(&email, &code, &request_id).await {
+            if let Err(e) = send_email_verification(&email, &code, &request_id, i18n::request_locale()).await {
                 error!("Request {}: Failed to send fallback email: {}", request_id, e);
                 return Err(StatusCode::INTERNAL_SERVER_ERROR);
             }
@@ -369,36 +370,59 @@ pub fn create_verification_v4_router() -> Router<Arc<AppState>> {
 // EMAIL SENDING FUNCTION
 // ============================================================================
 
+const SUPPORT_EMAIL: &str = "soporte@lumapp.org";
+
 // Function to load and process the HTML email template
-pub fn load_email_template(code: &str, user_name: &str, expiry_time: &str) -> Result<String, String> {
+pub fn load_email_template(code: &str, user_name: &str, expiry_time: &str, locale: Locale) -> Result<String, String> {
     let template_content = include_str!("../templates/email_verification.html");
+    let strong_name = format!("<strong>{}</strong>", user_name);
+    let strong_expiry = format!("<strong>{}</strong>", expiry_time);
+    let support_link = format!("<a href=\"mailto:{0}\">{0}</a>", SUPPORT_EMAIL);
     
     let html_body = template_content
-        .replace("{{VERIFICATION_CODE}}", code)
-        .replace("{{USER_NAME}}", user_name)
-        .replace("{{EXPIRY_TIME}}", expiry_time);
+        .replace("{{LANG}}", locale.as_str())
+        .replace("{{SUBJECT}}", &i18n::t(locale, "email.verification.subject"))
+        .replace("{{HEADING}}", &i18n::t(locale, "email.verification.heading"))
+        .replace("{{SUBHEADING}}", &i18n::t(locale, "email.verification.subheading"))
+        .replace("{{GREETING}}", &i18n::render(locale, "email.verification.greeting", &[("name", &strong_name)]))
+        .replace("{{INTRO}}", &i18n::t(locale, "email.verification.intro"))
+        .replace("{{EXPIRY}}", &i18n::render(locale, "email.verification.expiry", &[("expiry", &strong_expiry)]))
+        .replace("{{IGNORE}}", &i18n::t(locale, "email.verification.ignore"))
+        .replace("{{THANKS}}", &i18n::t(locale, "email.verification.thanks"))
+        .replace("{{TAGLINE}}", &i18n::t(locale, "email.footer.tagline"))
+        .replace("{{HELP}}", &i18n::render(locale, "email.footer.help", &[("email", &support_link)]))
+        .replace("{{AUTOMATIC}}", &i18n::t(locale, "email.footer.automatic"))
+        .replace("{{VERIFICATION_CODE}}", code);
     
     Ok(html_body)
 }
 
 // Function to create plain text fallback
-pub fn create_plain_text_body(code: &str, user_name: &str, expiry_time: &str) -> String {
+pub fn create_plain_text_body(code: &str, user_name: &str, expiry_time: &str, locale: Locale) -> String {
     format!(
-        "¡Hola {}!\n\nPara completar tu verificación en Lüm, usa el siguiente código de 6 dígitos:\n\n{} \n\n⏰ Importante: Este código expira en {} por tu seguridad.\n\nSi no solicitaste este código, puedes ignorar este mensaje de forma segura.\n\n¡Gracias por ser parte del universo Lüm! 🌟\n\nLüm - Tu app de recompensas\n¿Necesitas ayuda? Contáctanos en soporte@lumapp.org\n\nEste es un correo automático, por favor no respondas a esta dirección.",
-        user_name, code, expiry_time
+        "{}\n\n{}\n\n{} \n\n{}\n\n{}\n\n{}\n\n{}\n{}\n\n{}",
+        i18n::render(locale, "email.verification.greeting", &[("name", &user_name)]),
+        i18n::t(locale, "email.verification.intro"),
+        code,
+        i18n::render(locale, "email.verification.expiry", &[("expiry", &expiry_time)]),
+        i18n::t(locale, "email.verification.ignore"),
+        i18n::t(locale, "email.verification.thanks"),
+        i18n::t(locale, "email.footer.tagline"),
+        i18n::render(locale, "email.footer.help", &[("email", &SUPPORT_EMAIL)]),
+        i18n::t(locale, "email.footer.automatic"),
     )
 }
 
-pub async fn send_email_verification(email: &str, code: &str, request_id: &str) -> Result<(), String> {
+pub async fn send_email_verification(email: &str, code: &str, request_id: &str, locale: Locale) -> Result<(), String> {
     // Email content
-    let subject = "Código de Verificación - Lüm";
-    let user_name = "Usuario"; // Default, could be extracted from email or database
-    let expiry_time = "1 hora";
+    let subject = i18n::t(locale, "email.verification.subject");
+    let user_name = i18n::t(locale, "email.verification.default_name"); // Default, could be extracted from email or database
+    let expiry_time = i18n::t(locale, "email.verification.expiry_time");
     
     // Create both HTML and plain text versions
-    let html_body = load_email_template(code, user_name, expiry_time)
+    let html_body = load_email_template(code, &user_name, &expiry_time, locale)
         .map_err(|e| format!("Failed to load email template: {}", e))?;
-    let plain_body = create_plain_text_body(code, user_name, expiry_time);
+    let plain_body = create_plain_text_body(code, &user_name, &expiry_time, locale);
     
     // Log email content for debugging
     info!(
@@ -406,10 +430,32 @@ pub async fn send_email_verification(email: &str, code: &str, request_id: &str)
         email, request_id, email, subject, code
     );
     
+    match send_html_email(email, &subject, &html_body, &plain_body, request_id).await {
+        Err(e) if e == EMAIL_NOT_CONFIGURED => {}
+        result => return result,
+    }
+    
+    // Fallback: simulation with detailed logging
+    warn!("⚠️ No email service configured (SENDGRID_API_KEY or SMTP_* variables), using simulation");
+    info!("🚀 SIMULATING EMAIL SEND");
+    info!("📬 From: info@lumapp.org");
+    info!("📬 To: {}", email);
+    info!("📬 Subject: {}", subject);
+    info!("📬 Code: {}", code);
+    info!("📬 Request: {}", request_id);
+    
+    info!("✅ Email simulated successfully to: {} (Request: {})", email, request_id);
+    Ok(())
+}
+
+pub const EMAIL_NOT_CONFIGURED: &str = "No email service configured";
+
+// Send through SendGrid or SMTP, whichever is configured (EMAIL_NOT_CONFIGURED otherwise)
+pub async fn send_html_email(email: &str, subject: &str, html_body: &str, plain_body: &str, request_id: &str) -> Result<(), String> {
     // Try SendGrid API first (easiest to configure)
     if let Ok(sendgrid_api_key) = std::env::var("SENDGRID_API_KEY") {
         if !sendgrid_api_key.is_empty() {
-            return send_via_sendgrid_html(email, &subject, &html_body, &plain_body, &sendgrid_api_key, request_id).await;
+            return send_via_sendgrid_html(email, subject, html_body, plain_body, &sendgrid_api_key, request_id).await;
         }
     }
     
@@ -420,21 +466,11 @@ pub async fn send_email_verification(email: &str, code: &str, request_id: &str)
         std::env::var("SMTP_PASSWORD")
     ) {
         if !smtp_server.is_empty() && !smtp_username.is_empty() && !smtp_password.is_empty() {
-            return send_via_smtp_html(email, &subject, &html_body, &plain_body, &smtp_server, &smtp_username, &smtp_password, request_id).await;
+            return send_via_smtp_html(email, subject, html_body, plain_body, &smtp_server, &smtp_username, &smtp_password, request_id).await;
         }
     }
     
-    // Fallback: simulation with detailed logging
-    warn!("⚠️ No email service configured (SENDGRID_API_KEY or SMTP_* variables), using simulation");
-    info!("🚀 SIMULATING EMAIL SEND");
-    info!("📬 From: info@lumapp.org");
-    info!("📬 To: {}", email);
-    info!("📬 Subject: {}", subject);
-    info!("📬 Code: {}", code);
-    info!("📬 Request: {}", request_id);
-    
-    info!("✅ Email simulated successfully to: {} (Request: {})", email, request_id);
-    Ok(())
+    Err(EMAIL_NOT_CONFIGURED.to_string())
 }
 
 // SendGrid API implementation with HTML support
@@ -562,7 +598,7 @@ pub async fn test_email_sending(
     info!("🧪 TEST EMAIL REQUEST: {} to {}", request_id, req.email);
     
     // Send test email
-    match send_email_verification(&req.email, &test_code, &request_id).await {
+    match send_email_verification(&req.email, &test_code, &request_id, i18n::request_locale()).await {
         Ok(_) => {
             Ok(Json(TestEmailResponse {
                 success: true,
@@ -616,7 +652,7 @@ pub async fn test_smtp_configuration(
     let user_name = "Usuario de Prueba";
     let expiry_time = "10 minutos";
     
-    let html_body = match load_email_template(&test_code, user_name, expiry_time) {
+    let html_body = match load_email_template(&test_code, user_name, expiry_time, Locale::Es) {
         Ok(html) => html,
         Err(e) => {
             error!("🧪 SMTP TEST FAILED - Template error: {}", e);
@@ -628,7 +664,7 @@ pub async fn test_smtp_configuration(
             }));
         }
     };
-    let plain_body = create_plain_text_body(&test_code, user_name, expiry_time);
+    let plain_body = create_plain_text_body(&test_code, user_name, expiry_time, Locale::Es);
     
     match send_via_smtp_html(&req.email, &subject, &html_body, &plain_body, &req.smtp_server, &req.smtp_username, &req.smtp_password, &request_id).await {
         Ok(_) => {
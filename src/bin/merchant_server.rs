@@ -1,8 +1,10 @@
 // ============================================================================
 // LUM MERCHANT SERVER - Microservicio independiente para comercios
 // ============================================================================
-// Este binario sirve únicamente los endpoints de comercios:
+// Este binario sirve únicamente los endpoints de comercios
+// (JWT de merchant o API key: X-Api-Key / Bearer lum_sk_...):
 // - POST /api/v1/merchant/auth/login
+// - POST /api/v1/merchant/auth/staff-login
 // - POST /api/v1/merchant/validate
 // - POST /api/v1/merchant/confirm/:id
 // - GET  /api/v1/merchant/stats
@@ -10,6 +12,13 @@
 // - GET  /api/v1/merchant/dashboard
 // - GET  /api/v1/merchant/dashboard/stats
 // - GET  /api/v1/merchant/pending
+// - GET  /api/v1/merchant/reports, /export/redemptions
+// - GET  /api/v1/merchant/settlements[/:id]?format=csv
+// - GET  /api/v1/merchant/campaigns, /campaigns/:id/report
+// - CRUD /api/v1/merchant/staff, /branches
+// - GET|POST /api/v1/merchant/api-keys, DELETE /api-keys/:id
+// - GET|PUT /api/v1/merchant/webhooks/config, POST /webhooks/secret/rotate, /webhooks/test
+// - GET  /api/v1/merchant/webhooks/deliveries[/:id], POST .../:id/replay
 // ============================================================================
 
 use anyhow::Result;
@@ -98,6 +107,12 @@ async fn main() -> Result<()> {
     let app_state = AppState::new().await?;
     info!("✅ Database and Redis connections established");
 
+    // API keys de integraciones POS (con rate limit por key)
+    lum_rust_ws::services::init_rate_limiter(app_state.redis_pool.clone());
+    lum_rust_ws::services::init_merchant_api_key_service(app_state.db_pool.clone());
+    // Sesiones del personal: rol, sucursal y estado se revalidan por request
+    lum_rust_ws::services::init_merchant_session_service(app_state.db_pool.clone());
+
     let app = create_merchant_router(Arc::new(app_state));
 
     // Puerto configurable, default 8001
@@ -0,0 +1,1258 @@
+//! Misiones declarativas
+//!
+//! Una misión es un dato en `gamification.mission_definitions`: una meta sobre
+//! un flujo de eventos, una ventana de tiempo, una recurrencia, reglas de
+//! elegibilidad y un premio. Ejemplos de meta:
+//!
+//! ```json
+//! {"type": "invoice_count", "target": 3, "filter": {"issuer_category": ["Supermercado"]}}
+//! {"type": "invoice_spend", "target_amount": 50, "filter": {"issuer_ruc": ["8-NT-12345"]}}
+//! {"type": "survey_count", "target": 2}
+//! {"type": "action_count", "action": "daily_login", "target": 5}
+//! ```
+//!
+//! El evaluador (`apply_event`) es puro y se prueba reproduciendo secuencias
+//! de eventos. `MissionService::handle_event` lo aplica contra la base de datos:
+//! cada evento cuenta una sola vez por misión (`mission_progress_events`) y al
+//! completar se acreditan Lümis (`rewards.fact_accumulations`) y congeladores
+//! de racha (`StreakService::grant_freezes_in`) en la misma transacción.
+
+use chrono::{DateTime, Datelike, Duration, Utc};
+use chrono_tz::America::Panama;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use std::collections::BTreeMap;
+use tracing::{info, warn};
+
+use crate::domains::gamification::streak_service::{self, StreakError, StreakService};
+use crate::domains::rewards::accumulation_rules::Range;
+use crate::services::event_bus_service::{DomainEvent, EventBus};
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+/// Regla genérica en `rewards.dim_accumulations` para las acumulaciones de misiones
+pub const MISSION_ACCUM_ID: i32 = 32;
+pub const MISSION_ACCUM_TYPE: &str = "mission";
+
+pub const STATUS_ACTIVE: &str = "active";
+pub const STATUS_COMPLETED: &str = "completed";
+
+pub const MAX_TARGET_COUNT: u32 = 1_000;
+pub const MAX_REWARD_LUMIS: i32 = 1_000;
+/// Días de facturas que se reproducen en el preview
+pub const PREVIEW_REPLAY_DAYS: i64 = 30;
+const PREVIEW_MAX_EVENTS: i64 = 100_000;
+
+// ======================================================================
+// DEFINICIONES
+// ======================================================================
+
+/// Filtro sobre facturas; todas las condiciones presentes deben cumplirse
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InvoiceFilter {
+    /// Categoría L1 del comercio (dim_issuer_stores.l1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer_category: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer_ruc: Option<Vec<String>>,
+    /// Total de la factura
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Range<Decimal>>,
+}
+
+fn contains_ci(values: &[String], value: &str) -> bool {
+    let value = value.trim();
+    values.iter().any(|v| v.trim().eq_ignore_ascii_case(value))
+}
+
+impl InvoiceFilter {
+    pub fn matches(&self, invoice: &InvoiceEvent) -> bool {
+        let category_ok = self.issuer_category.as_ref().map_or(true, |values| {
+            invoice.issuer_category.as_deref().is_some_and(|c| contains_ci(values, c))
+        });
+        let ruc_ok = self
+            .issuer_ruc
+            .as_ref()
+            .map_or(true, |values| contains_ci(values, &invoice.issuer_ruc));
+        let amount_ok = self.amount.as_ref().map_or(true, |range| range.contains(invoice.amount));
+        category_ok && ruc_ok && amount_ok
+    }
+
+    fn validate(&self) -> Result<(), Text> {
+        let filters = [("filter.issuer_category", &self.issuer_category), ("filter.issuer_ruc", &self.issuer_ruc)];
+        for (name, values) in filters {
+            if values.as_ref().is_some_and(|v| v.is_empty()) {
+                return Err(Text::new("api.validation.not_empty").with("field", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum MissionGoal {
+    /// Cantidad de facturas que cumplen el filtro
+    InvoiceCount {
+        target: u32,
+        #[serde(default)]
+        filter: InvoiceFilter,
+    },
+    /// Monto acumulado en facturas que cumplen el filtro
+    InvoiceSpend {
+        target_amount: Decimal,
+        #[serde(default)]
+        filter: InvoiceFilter,
+    },
+    /// Encuestas completadas (opcionalmente solo algunas)
+    SurveyCount {
+        target: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        survey_ids: Option<Vec<i32>>,
+    },
+    /// Acciones registradas en /gamification/track (daily_login, profile_complete...)
+    ActionCount { action: String, target: u32 },
+}
+
+impl MissionGoal {
+    pub fn target(&self) -> Decimal {
+        match self {
+            MissionGoal::InvoiceCount { target, .. }
+            | MissionGoal::SurveyCount { target, .. }
+            | MissionGoal::ActionCount { target, .. } => Decimal::from(*target),
+            MissionGoal::InvoiceSpend { target_amount, .. } => *target_amount,
+        }
+    }
+
+    /// Cuánto avanza la meta con un evento (None si no aplica)
+    pub fn contribution(&self, event: &MissionEventKind) -> Option<Decimal> {
+        match (self, event) {
+            (MissionGoal::InvoiceCount { filter, .. }, MissionEventKind::InvoiceSaved(invoice)) => {
+                filter.matches(invoice).then_some(Decimal::ONE)
+            }
+            (MissionGoal::InvoiceSpend { filter, .. }, MissionEventKind::InvoiceSaved(invoice)) => {
+                (filter.matches(invoice) && invoice.amount > Decimal::ZERO).then_some(invoice.amount)
+            }
+            (MissionGoal::SurveyCount { survey_ids, .. }, MissionEventKind::SurveyCompleted { survey_id }) => {
+                survey_ids
+                    .as_ref()
+                    .map_or(true, |ids| ids.contains(survey_id))
+                    .then_some(Decimal::ONE)
+            }
+            (MissionGoal::ActionCount { action, .. }, MissionEventKind::ActionTracked { action: tracked }) => {
+                (action == tracked).then_some(Decimal::ONE)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Text> {
+        let count_ok = |target: u32| (1..=MAX_TARGET_COUNT).contains(&target);
+        let target_out_of_range = || {
+            Text::new("api.validation.range").with("field", "target").with("min", 1).with("max", MAX_TARGET_COUNT)
+        };
+        match self {
+            MissionGoal::InvoiceCount { target, filter } => {
+                if !count_ok(*target) {
+                    return Err(target_out_of_range());
+                }
+                filter.validate()
+            }
+            MissionGoal::InvoiceSpend { target_amount, filter } => {
+                if *target_amount <= Decimal::ZERO {
+                    return Err(Text::new("api.validation.positive").with("field", "target_amount"));
+                }
+                filter.validate()
+            }
+            MissionGoal::SurveyCount { target, survey_ids } => {
+                if !count_ok(*target) {
+                    return Err(target_out_of_range());
+                }
+                if survey_ids.as_ref().is_some_and(|ids| ids.is_empty()) {
+                    return Err(Text::new("api.validation.not_empty").with("field", "survey_ids"));
+                }
+                Ok(())
+            }
+            MissionGoal::ActionCount { action, target } => {
+                if !count_ok(*target) {
+                    return Err(target_out_of_range());
+                }
+                if action.trim().is_empty() {
+                    return Err(Text::new("api.validation.required").with("field", "action"));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recurrence {
+    /// Se completa una sola vez en toda la ventana
+    Once,
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Recurrence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Recurrence::Once => "once",
+            Recurrence::Daily => "daily",
+            Recurrence::Weekly => "weekly",
+            Recurrence::Monthly => "monthly",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "once" => Some(Recurrence::Once),
+            "daily" => Some(Recurrence::Daily),
+            "weekly" => Some(Recurrence::Weekly),
+            "monthly" => Some(Recurrence::Monthly),
+            _ => None,
+        }
+    }
+
+    /// Periodo (hora de Panamá) al que pertenece un instante
+    pub fn period_key(&self, at: DateTime<Utc>) -> String {
+        let date = at.with_timezone(&Panama).date_naive();
+        match self {
+            Recurrence::Once => "once".to_string(),
+            Recurrence::Daily => date.format("%Y-%m-%d").to_string(),
+            Recurrence::Weekly => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Recurrence::Monthly => date.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// Reglas de elegibilidad; todas las presentes deben cumplirse
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Eligibility {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_level: Option<i32>,
+    /// Solo usuarios registrados en los últimos N días
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registered_within_days: Option<i32>,
+    /// Facturas históricas del usuario (user_status.total_xp)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_lifetime_invoices: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_lifetime_invoices: Option<i32>,
+}
+
+/// Definición tal como la envía el admin (y como se guarda)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissionDefinition {
+    pub mission_code: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub goal: MissionGoal,
+    #[serde(default = "default_recurrence")]
+    pub recurrence: Recurrence,
+    #[serde(default)]
+    pub eligibility: Eligibility,
+    #[serde(default)]
+    pub reward_lumis: i32,
+    #[serde(default)]
+    pub reward_streak_freezes: i32,
+    pub starts_at: DateTime<Utc>,
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+fn default_recurrence() -> Recurrence {
+    Recurrence::Once
+}
+
+impl MissionDefinition {
+    pub fn validate(&self) -> Result<(), Text> {
+        let code_ok = !self.mission_code.is_empty()
+            && self.mission_code.len() <= 60
+            && self.mission_code.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !code_ok {
+            return Err(Text::new("api.mission.invalid_code"));
+        }
+        if self.name.trim().is_empty() || self.name.len() > 120 {
+            return Err(Text::new("api.validation.length").with("field", "name").with("min", 1).with("max", 120));
+        }
+        self.goal.validate()?;
+        if !(0..=MAX_REWARD_LUMIS).contains(&self.reward_lumis) {
+            return Err(Text::new("api.validation.range")
+                .with("field", "reward_lumis")
+                .with("min", 0)
+                .with("max", MAX_REWARD_LUMIS));
+        }
+        if !(0..=streak_service::MAX_FREEZES_HELD).contains(&self.reward_streak_freezes) {
+            return Err(Text::new("api.validation.range")
+                .with("field", "reward_streak_freezes")
+                .with("min", 0)
+                .with("max", streak_service::MAX_FREEZES_HELD));
+        }
+        if self.reward_lumis == 0 && self.reward_streak_freezes == 0 {
+            return Err(Text::new("api.mission.no_reward"));
+        }
+        if self.ends_at.is_some_and(|end| end <= self.starts_at) {
+            return Err(Text::new("api.validation.after").with("field", "ends_at").with("other", "starts_at"));
+        }
+        let e = &self.eligibility;
+        if let (Some(min), Some(max)) = (e.min_lifetime_invoices, e.max_lifetime_invoices) {
+            if min > max {
+                return Err(Text::new("api.validation.not_greater")
+                    .with("field", "min_lifetime_invoices")
+                    .with("other", "max_lifetime_invoices"));
+            }
+        }
+        if e.registered_within_days.is_some_and(|d| d < 1) {
+            return Err(Text::new("api.validation.min").with("field", "registered_within_days").with("min", 1));
+        }
+        Ok(())
+    }
+
+    pub fn is_open_at(&self, at: DateTime<Utc>) -> bool {
+        at >= self.starts_at && self.ends_at.map_or(true, |end| at < end)
+    }
+}
+
+// ======================================================================
+// EVENTOS
+// ======================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvoiceEvent {
+    pub cufe: String,
+    pub issuer_ruc: String,
+    pub issuer_category: Option<String>,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MissionEventKind {
+    InvoiceSaved(InvoiceEvent),
+    SurveyCompleted { survey_id: i32 },
+    ActionTracked { action: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissionEvent {
+    pub user_id: i32,
+    /// Identificador estable para no contar dos veces el mismo evento
+    pub event_key: String,
+    pub occurred_at: DateTime<Utc>,
+    pub kind: MissionEventKind,
+}
+
+impl MissionEvent {
+    pub fn invoice(user_id: i32, occurred_at: DateTime<Utc>, invoice: InvoiceEvent) -> Self {
+        Self {
+            user_id,
+            event_key: format!("invoice:{}", invoice.cufe),
+            occurred_at,
+            kind: MissionEventKind::InvoiceSaved(invoice),
+        }
+    }
+
+    pub fn survey(user_id: i32, survey_id: i32, occurred_at: DateTime<Utc>) -> Self {
+        Self {
+            user_id,
+            event_key: format!("survey:{}", survey_id),
+            occurred_at,
+            kind: MissionEventKind::SurveyCompleted { survey_id },
+        }
+    }
+
+    /// `event_id`: id del evento de dominio (cada acción registrada cuenta una vez)
+    pub fn action(user_id: i32, action: &str, event_id: uuid::Uuid, occurred_at: DateTime<Utc>) -> Self {
+        Self {
+            user_id,
+            event_key: format!("action:{}", event_id),
+            occurred_at,
+            kind: MissionEventKind::ActionTracked { action: action.to_string() },
+        }
+    }
+}
+
+// ======================================================================
+// EVALUADOR (PURO)
+// ======================================================================
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PeriodProgress {
+    pub progress: Decimal,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Resultado de aplicar un evento a una misión
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventOutcome {
+    /// El evento no aplica (otro tipo, filtro, fuera de ventana o periodo ya completado)
+    Ignored,
+    Progressed { period_key: String, progress: Decimal },
+    Completed { period_key: String, progress: Decimal },
+}
+
+/// Aplica un evento al progreso de un periodo
+pub fn apply_event(definition: &MissionDefinition, current: &PeriodProgress, event: &MissionEvent) -> EventOutcome {
+    if !definition.is_open_at(event.occurred_at) || current.completed_at.is_some() {
+        return EventOutcome::Ignored;
+    }
+    let Some(contribution) = definition.goal.contribution(&event.kind) else {
+        return EventOutcome::Ignored;
+    };
+    let period_key = definition.recurrence.period_key(event.occurred_at);
+    let progress = current.progress + contribution;
+    if progress >= definition.goal.target() {
+        EventOutcome::Completed { period_key, progress }
+    } else {
+        EventOutcome::Progressed { period_key, progress }
+    }
+}
+
+/// Reproduce una secuencia de eventos de un usuario (en orden) y devuelve el
+/// progreso por periodo. Los eventos repetidos (`event_key`) cuentan una vez.
+pub fn replay(definition: &MissionDefinition, events: &[MissionEvent]) -> BTreeMap<String, PeriodProgress> {
+    let mut periods: BTreeMap<String, PeriodProgress> = BTreeMap::new();
+    let mut seen = std::collections::HashSet::new();
+    for event in events {
+        if !seen.insert(event.event_key.as_str()) {
+            continue;
+        }
+        let key = definition.recurrence.period_key(event.occurred_at);
+        let current = periods.get(&key).cloned().unwrap_or_default();
+        match apply_event(definition, &current, event) {
+            EventOutcome::Ignored => {}
+            EventOutcome::Progressed { period_key, progress } => {
+                periods.insert(period_key, PeriodProgress { progress, completed_at: None });
+            }
+            EventOutcome::Completed { period_key, progress } => {
+                periods.insert(period_key, PeriodProgress { progress, completed_at: Some(event.occurred_at) });
+            }
+        }
+    }
+    periods
+}
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum MissionError {
+    #[error("{}", Text::new("api.mission.invalid").with("reason", .0))]
+    Invalid(Text),
+
+    #[error("{}", Text::new("api.mission.duplicate_code").with("code", .0))]
+    DuplicateCode(String),
+
+    #[error("{}", Text::new("api.not_found.mission"))]
+    NotFound,
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for MissionError {
+    fn from(err: sqlx::Error) -> Self {
+        MissionError::Database(err.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredMission {
+    pub mission_id: i64,
+    pub is_active: bool,
+    #[serde(flatten)]
+    pub definition: MissionDefinition,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+struct MissionRow {
+    mission_id: i64,
+    mission_code: String,
+    name: String,
+    description: Option<String>,
+    goal: serde_json::Value,
+    recurrence: String,
+    eligibility: serde_json::Value,
+    reward_lumis: i32,
+    reward_streak_freezes: i32,
+    starts_at: DateTime<Utc>,
+    ends_at: Option<DateTime<Utc>>,
+    is_active: bool,
+    created_at: DateTime<Utc>,
+}
+
+impl MissionRow {
+    /// Filas con JSON inválido se omiten (y se registran) en lugar de romper la evaluación
+    fn into_stored(self) -> Option<StoredMission> {
+        let goal = match serde_json::from_value::<MissionGoal>(self.goal) {
+            Ok(goal) => goal,
+            Err(e) => {
+                warn!("Mission {} has an invalid goal: {}", self.mission_id, e);
+                return None;
+            }
+        };
+        let eligibility = serde_json::from_value::<Eligibility>(self.eligibility).unwrap_or_else(|e| {
+            warn!("Mission {} has invalid eligibility, ignoring it: {}", self.mission_id, e);
+            Eligibility::default()
+        });
+        Some(StoredMission {
+            mission_id: self.mission_id,
+            is_active: self.is_active,
+            definition: MissionDefinition {
+                mission_code: self.mission_code,
+                name: self.name,
+                description: self.description,
+                goal,
+                recurrence: Recurrence::parse(&self.recurrence).unwrap_or(Recurrence::Once),
+                eligibility,
+                reward_lumis: self.reward_lumis,
+                reward_streak_freezes: self.reward_streak_freezes,
+                starts_at: self.starts_at,
+                ends_at: self.ends_at,
+            },
+            created_at: self.created_at,
+        })
+    }
+}
+
+const MISSION_COLUMNS: &str = r#"
+    mission_id, mission_code, name, description, goal, recurrence, eligibility,
+    reward_lumis, reward_streak_freezes, starts_at, ends_at, is_active, created_at
+"#;
+
+/// Misión completada por un evento
+#[derive(Debug, Clone, Serialize)]
+pub struct MissionCompletion {
+    pub mission_id: i64,
+    pub mission_code: String,
+    pub period_key: String,
+    pub lumis_awarded: i32,
+    pub freezes_awarded: i32,
+}
+
+/// Progreso del usuario en una misión declarativa (periodo actual)
+#[derive(Debug, Clone, Serialize)]
+pub struct UserMissionView {
+    pub mission_code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub recurrence: Recurrence,
+    pub period_key: String,
+    pub progress: Decimal,
+    pub target: Decimal,
+    pub status: String,
+    pub reward_lumis: i32,
+    pub reward_streak_freezes: i32,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MissionPreview {
+    pub eligible_users: i64,
+    /// Solo si se pidió un usuario concreto
+    pub user_eligible: Option<bool>,
+    /// Reproducción de las facturas de los últimos días sobre los usuarios elegibles
+    /// (solo metas de facturas)
+    pub replay: Option<ReplayEstimate>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ReplayEstimate {
+    pub days: i64,
+    pub events_replayed: i64,
+    pub users_with_progress: i64,
+    pub completions: i64,
+    pub projected_lumis: i64,
+    pub projected_freezes: i64,
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+/// Predicado de elegibilidad; $1 user_id opcional, $2..$5 reglas
+const ELIGIBILITY_QUERY: &str = r#"
+    FROM public.dim_users u
+    LEFT JOIN gamification.user_status us ON us.user_id = u.id
+    WHERE u.is_active = true
+      AND ($1::INT IS NULL OR u.id = $1)
+      AND ($2::INT IS NULL OR COALESCE(us.current_level_id, 1) >= $2)
+      AND ($3::INT IS NULL OR u.created_at >= NOW() - make_interval(days => $3))
+      AND ($4::INT IS NULL OR COALESCE(us.total_xp, 0) >= $4)
+      AND ($5::INT IS NULL OR COALESCE(us.total_xp, 0) <= $5)
+"#;
+
+pub struct MissionService {
+    db: PgPool,
+}
+
+impl MissionService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(&self, definition: &MissionDefinition, created_by: i64) -> Result<StoredMission, MissionError> {
+        definition.validate().map_err(MissionError::Invalid)?;
+
+        let goal = serde_json::to_value(&definition.goal)
+            .map_err(|e| MissionError::Invalid(Text::new("api.validation.serialization").with("error", e)))?;
+        let eligibility =
+            serde_json::to_value(&definition.eligibility)
+                .map_err(|e| MissionError::Invalid(Text::new("api.validation.serialization").with("error", e)))?;
+
+        let row = sqlx::query_as::<_, MissionRow>(&format!(
+            r#"
+            INSERT INTO gamification.mission_definitions (
+                mission_code, name, description, goal, recurrence, eligibility,
+                reward_lumis, reward_streak_freezes, starts_at, ends_at, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (mission_code) DO NOTHING
+            RETURNING {}
+            "#,
+            MISSION_COLUMNS
+        ))
+        .bind(&definition.mission_code)
+        .bind(definition.name.trim())
+        .bind(definition.description.as_deref())
+        .bind(goal)
+        .bind(definition.recurrence.as_str())
+        .bind(eligibility)
+        .bind(definition.reward_lumis)
+        .bind(definition.reward_streak_freezes)
+        .bind(definition.starts_at)
+        .bind(definition.ends_at)
+        .bind(created_by)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| MissionError::DuplicateCode(definition.mission_code.clone()))?;
+
+        info!("🎯 Mission '{}' created by admin {}", definition.mission_code, created_by);
+        row.into_stored().ok_or_else(|| MissionError::Invalid(Text::new("api.mission.invalid_goal")))
+    }
+
+    pub async fn list(&self, include_inactive: bool) -> Result<Vec<StoredMission>, MissionError> {
+        let rows = sqlx::query_as::<_, MissionRow>(&format!(
+            "SELECT {} FROM gamification.mission_definitions WHERE is_active OR $1 ORDER BY starts_at DESC",
+            MISSION_COLUMNS
+        ))
+        .bind(include_inactive)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().filter_map(MissionRow::into_stored).collect())
+    }
+
+    pub async fn set_active(&self, mission_id: i64, is_active: bool) -> Result<(), MissionError> {
+        let result = sqlx::query(
+            "UPDATE gamification.mission_definitions SET is_active = $2, updated_at = NOW() WHERE mission_id = $1",
+        )
+        .bind(mission_id)
+        .bind(is_active)
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(MissionError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn open_missions(&self, at: DateTime<Utc>) -> Result<Vec<StoredMission>, MissionError> {
+        let rows = sqlx::query_as::<_, MissionRow>(&format!(
+            r#"
+            SELECT {} FROM gamification.mission_definitions
+            WHERE is_active AND starts_at <= $1 AND (ends_at IS NULL OR ends_at > $1)
+            ORDER BY mission_id
+            "#,
+            MISSION_COLUMNS
+        ))
+        .bind(at)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().filter_map(MissionRow::into_stored).collect())
+    }
+
+    async fn is_eligible(&self, user_id: i32, eligibility: &Eligibility) -> Result<bool, MissionError> {
+        let count = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) {}", ELIGIBILITY_QUERY))
+            .bind(Some(user_id))
+            .bind(eligibility.min_level)
+            .bind(eligibility.registered_within_days)
+            .bind(eligibility.min_lifetime_invoices)
+            .bind(eligibility.max_lifetime_invoices)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(count > 0)
+    }
+
+    /// Evento de factura guardada con los datos que usan los filtros
+    pub async fn invoice_event(
+        &self,
+        user_id: i32,
+        cufe: &str,
+        occurred_at: DateTime<Utc>,
+    ) -> Result<Option<MissionEvent>, MissionError> {
+        let row = sqlx::query_as::<_, (String, Option<String>, Decimal)>(
+            r#"
+            SELECT COALESCE(ih.issuer_ruc, ''),
+                   (SELECT s.l1 FROM public.dim_issuer_stores s
+                    WHERE s.issuer_ruc = ih.issuer_ruc AND s.store_id = ih.store_id
+                    LIMIT 1),
+                   COALESCE(ih.tot_amount, 0)::NUMERIC
+            FROM public.invoice_header ih
+            WHERE ih.cufe = $1
+            "#,
+        )
+        .bind(cufe)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(|(issuer_ruc, issuer_category, amount)| {
+            MissionEvent::invoice(
+                user_id,
+                occurred_at,
+                InvoiceEvent { cufe: cufe.to_string(), issuer_ruc, issuer_category, amount },
+            )
+        }))
+    }
+
+    /// Aplica un evento de dominio a todas las misiones abiertas
+    pub async fn handle_event(&self, event: &MissionEvent) -> Result<Vec<MissionCompletion>, MissionError> {
+        let missions = self.open_missions(event.occurred_at).await?;
+        let mut completions = Vec::new();
+
+        for mission in missions {
+            let definition = &mission.definition;
+            let Some(contribution) = definition.goal.contribution(&event.kind) else {
+                continue;
+            };
+            if !self.is_eligible(event.user_id, &definition.eligibility).await? {
+                continue;
+            }
+            if let Some(completion) = self.apply(&mission, event, contribution).await? {
+                completions.push(completion);
+            }
+        }
+
+        Ok(completions)
+    }
+
+    async fn apply(
+        &self,
+        mission: &StoredMission,
+        event: &MissionEvent,
+        contribution: Decimal,
+    ) -> Result<Option<MissionCompletion>, MissionError> {
+        let definition = &mission.definition;
+        let period_key = definition.recurrence.period_key(event.occurred_at);
+
+        let mut tx = self.db.begin().await?;
+
+        let first_time = sqlx::query_scalar::<_, i32>(
+            r#"
+            INSERT INTO gamification.mission_progress_events (mission_id, user_id, event_key, contribution)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (mission_id, user_id, event_key) DO NOTHING
+            RETURNING 1
+            "#,
+        )
+        .bind(mission.mission_id)
+        .bind(event.user_id)
+        .bind(&event.event_key)
+        .bind(contribution)
+        .fetch_optional(&mut *tx)
+        .await?
+        .is_some();
+        if !first_time {
+            return Ok(None);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO gamification.user_mission_progress (mission_id, user_id, period_key, target)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (mission_id, user_id, period_key) DO NOTHING
+            "#,
+        )
+        .bind(mission.mission_id)
+        .bind(event.user_id)
+        .bind(&period_key)
+        .bind(definition.goal.target())
+        .execute(&mut *tx)
+        .await?;
+
+        let (progress, completed_at) = sqlx::query_as::<_, (Decimal, Option<DateTime<Utc>>)>(
+            r#"
+            SELECT progress, completed_at FROM gamification.user_mission_progress
+            WHERE mission_id = $1 AND user_id = $2 AND period_key = $3
+            FOR UPDATE
+            "#,
+        )
+        .bind(mission.mission_id)
+        .bind(event.user_id)
+        .bind(&period_key)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let outcome = apply_event(definition, &PeriodProgress { progress, completed_at }, event);
+        let (new_progress, completed) = match outcome {
+            EventOutcome::Ignored => {
+                tx.commit().await?;
+                return Ok(None);
+            }
+            EventOutcome::Progressed { progress, .. } => (progress, false),
+            EventOutcome::Completed { progress, .. } => (progress, true),
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE gamification.user_mission_progress
+            SET progress = $4,
+                status = CASE WHEN $5 THEN 'completed' ELSE status END,
+                completed_at = CASE WHEN $5 THEN NOW() ELSE completed_at END,
+                lumis_awarded = CASE WHEN $5 THEN $6 ELSE lumis_awarded END,
+                updated_at = NOW()
+            WHERE mission_id = $1 AND user_id = $2 AND period_key = $3
+            "#,
+        )
+        .bind(mission.mission_id)
+        .bind(event.user_id)
+        .bind(&period_key)
+        .bind(new_progress)
+        .bind(completed)
+        .bind(definition.reward_lumis)
+        .execute(&mut *tx)
+        .await?;
+
+        if completed && definition.reward_lumis > 0 {
+            sqlx::query(
+                r#"
+                INSERT INTO rewards.fact_accumulations
+                (user_id, accum_type, accum_key, dtype, quantity, date, accum_id)
+                VALUES ($1, $2, $3, 'points', $4, $5, $6)
+                "#,
+            )
+            .bind(event.user_id)
+            .bind(MISSION_ACCUM_TYPE)
+            .bind(format!("mission:{}:{}", mission.mission_id, period_key))
+            .bind(definition.reward_lumis)
+            .bind(Utc::now().naive_utc())
+            .bind(MISSION_ACCUM_ID)
+            .execute(&mut *tx)
+            .await?;
+
+            let credited = DomainEvent::LumisCredited {
+                user_id: event.user_id as i64,
+                lumis: definition.reward_lumis,
+                source: MISSION_ACCUM_TYPE.to_string(),
+                reference: Some(definition.mission_code.clone()),
+            };
+            EventBus::publish(&mut *tx, &credited)
+                .await
+                .map_err(|e| MissionError::Database(e.to_string()))?;
+        }
+
+        let freezes_awarded = if completed {
+            Self::grant_freezes(&mut tx, mission, event.user_id, &period_key).await?
+        } else {
+            0
+        };
+
+        tx.commit().await?;
+
+        if !completed {
+            return Ok(None);
+        }
+
+        info!(
+            "🎯 User {} completed mission '{}' ({}): {} Lümis, {} freeze(s)",
+            event.user_id, definition.mission_code, period_key, definition.reward_lumis, freezes_awarded
+        );
+
+        Ok(Some(MissionCompletion {
+            mission_id: mission.mission_id,
+            mission_code: definition.mission_code.clone(),
+            period_key,
+            lumis_awarded: definition.reward_lumis,
+            freezes_awarded,
+        }))
+    }
+
+    /// Congeladores del premio, en la transacción que completa la misión.
+    /// Con el inventario lleno la misión se completa igual, sin congeladores.
+    async fn grant_freezes(
+        tx: &mut Transaction<'_, Postgres>,
+        mission: &StoredMission,
+        user_id: i32,
+        period_key: &str,
+    ) -> Result<i32, MissionError> {
+        let quantity = mission.definition.reward_streak_freezes;
+        if quantity == 0 {
+            return Ok(0);
+        }
+        let reference = format!("mission:{}:{}", mission.definition.mission_code, period_key);
+        let granted = match StreakService::grant_freezes_in(
+            tx,
+            user_id,
+            quantity,
+            streak_service::FREEZE_REASON_MISSION,
+            Some(&reference),
+        )
+        .await
+        {
+            Ok(granted) => granted,
+            Err(StreakError::InventoryFull(_)) => {
+                warn!("Mission {} granted no freezes to user {}: inventory full", reference, user_id);
+                return Ok(0);
+            }
+            Err(e) => return Err(MissionError::Database(e.to_string())),
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE gamification.user_mission_progress SET freezes_awarded = $4
+            WHERE mission_id = $1 AND user_id = $2 AND period_key = $3
+            "#,
+        )
+        .bind(mission.mission_id)
+        .bind(user_id)
+        .bind(period_key)
+        .bind(granted)
+        .execute(&mut **tx)
+        .await?;
+        Ok(granted)
+    }
+
+    /// Misiones abiertas para las que el usuario es elegible, con su progreso actual
+    pub async fn user_missions(&self, user_id: i32) -> Result<Vec<UserMissionView>, MissionError> {
+        let now = Utc::now();
+        let mut views = Vec::new();
+
+        for mission in self.open_missions(now).await? {
+            let definition = mission.definition;
+            if !self.is_eligible(user_id, &definition.eligibility).await? {
+                continue;
+            }
+            let period_key = definition.recurrence.period_key(now);
+            let row = sqlx::query_as::<_, (Decimal, String)>(
+                r#"
+                SELECT progress, status FROM gamification.user_mission_progress
+                WHERE mission_id = $1 AND user_id = $2 AND period_key = $3
+                "#,
+            )
+            .bind(mission.mission_id)
+            .bind(user_id)
+            .bind(&period_key)
+            .fetch_optional(&self.db)
+            .await?;
+            let (progress, status) = row.unwrap_or((Decimal::ZERO, STATUS_ACTIVE.to_string()));
+
+            views.push(UserMissionView {
+                target: definition.goal.target(),
+                mission_code: definition.mission_code,
+                name: definition.name,
+                description: definition.description,
+                recurrence: definition.recurrence,
+                period_key,
+                progress,
+                status,
+                reward_lumis: definition.reward_lumis,
+                reward_streak_freezes: definition.reward_streak_freezes,
+                ends_at: definition.ends_at,
+            });
+        }
+
+        Ok(views)
+    }
+
+    /// Cuántos usuarios son elegibles y, para metas de facturas, qué habría
+    /// pasado con las facturas de los últimos `PREVIEW_REPLAY_DAYS` días
+    pub async fn preview(&self, definition: &MissionDefinition, user_id: Option<i32>) -> Result<MissionPreview, MissionError> {
+        definition.validate().map_err(MissionError::Invalid)?;
+        let e = &definition.eligibility;
+
+        let eligible_users = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) {}", ELIGIBILITY_QUERY))
+            .bind(None::<i32>)
+            .bind(e.min_level)
+            .bind(e.registered_within_days)
+            .bind(e.min_lifetime_invoices)
+            .bind(e.max_lifetime_invoices)
+            .fetch_one(&self.db)
+            .await?;
+
+        let user_eligible = match user_id {
+            Some(id) => Some(self.is_eligible(id, e).await?),
+            None => None,
+        };
+
+        let replay = match definition.goal {
+            MissionGoal::InvoiceCount { .. } | MissionGoal::InvoiceSpend { .. } => {
+                Some(self.replay_recent_invoices(definition).await?)
+            }
+            _ => None,
+        };
+
+        Ok(MissionPreview { eligible_users, user_eligible, replay })
+    }
+
+    async fn replay_recent_invoices(&self, definition: &MissionDefinition) -> Result<ReplayEstimate, MissionError> {
+        let e = &definition.eligibility;
+        let since = Utc::now() - Duration::days(PREVIEW_REPLAY_DAYS);
+
+        let rows = sqlx::query_as::<_, (i32, String, String, Option<String>, Decimal, DateTime<Utc>)>(&format!(
+            r#"
+            WITH eligible AS (SELECT u.id {eligibility})
+            SELECT ih.user_id::INT,
+                   ih.cufe,
+                   COALESCE(ih.issuer_ruc, ''),
+                   (SELECT s.l1 FROM public.dim_issuer_stores s
+                    WHERE s.issuer_ruc = ih.issuer_ruc AND s.store_id = ih.store_id
+                    LIMIT 1),
+                   COALESCE(ih.tot_amount, 0)::NUMERIC,
+                   ih.process_date
+            FROM public.invoice_header ih
+            JOIN eligible el ON el.id = ih.user_id
+            WHERE ih.process_date >= $6
+            ORDER BY ih.user_id, ih.process_date
+            LIMIT $7
+            "#,
+            eligibility = ELIGIBILITY_QUERY
+        ))
+        .bind(None::<i32>)
+        .bind(e.min_level)
+        .bind(e.registered_within_days)
+        .bind(e.min_lifetime_invoices)
+        .bind(e.max_lifetime_invoices)
+        .bind(since)
+        .bind(PREVIEW_MAX_EVENTS)
+        .fetch_all(&self.db)
+        .await?;
+
+        // Ventana del preview: la misma duración, pero sobre los últimos días
+        let mut simulated = definition.clone();
+        simulated.starts_at = since;
+        simulated.ends_at = None;
+
+        let mut estimate = ReplayEstimate {
+            days: PREVIEW_REPLAY_DAYS,
+            events_replayed: rows.len() as i64,
+            ..Default::default()
+        };
+
+        let mut by_user: BTreeMap<i32, Vec<MissionEvent>> = BTreeMap::new();
+        for (user_id, cufe, issuer_ruc, issuer_category, amount, occurred_at) in rows {
+            by_user.entry(user_id).or_default().push(MissionEvent::invoice(
+                user_id,
+                occurred_at,
+                InvoiceEvent { cufe, issuer_ruc, issuer_category, amount },
+            ));
+        }
+
+        for events in by_user.values() {
+            let periods = replay(&simulated, events);
+            if periods.values().any(|p| p.progress > Decimal::ZERO) {
+                estimate.users_with_progress += 1;
+            }
+            let completions = periods.values().filter(|p| p.completed_at.is_some()).count() as i64;
+            estimate.completions += completions;
+        }
+        estimate.projected_lumis = estimate.completions * definition.reward_lumis as i64;
+        estimate.projected_freezes = estimate.completions * definition.reward_streak_freezes as i64;
+
+        Ok(estimate)
+    }
+}
+
+/// Progreso entero para vistas que no manejan decimales (p.ej. montos en dólares)
+pub fn progress_as_i32(value: Decimal) -> i32 {
+    value.trunc().to_i32().unwrap_or(i32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    fn d(value: i64) -> Decimal {
+        Decimal::from(value)
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap()
+    }
+
+    fn invoice(user_id: i32, cufe: &str, when: DateTime<Utc>, category: Option<&str>, amount: Decimal) -> MissionEvent {
+        MissionEvent::invoice(
+            user_id,
+            when,
+            InvoiceEvent {
+                cufe: cufe.to_string(),
+                issuer_ruc: "155-1-2023".to_string(),
+                issuer_category: category.map(str::to_string),
+                amount,
+            },
+        )
+    }
+
+    fn mission(goal: MissionGoal, recurrence: Recurrence) -> MissionDefinition {
+        MissionDefinition {
+            mission_code: "test_mission".to_string(),
+            name: "Misión de prueba".to_string(),
+            description: None,
+            goal,
+            recurrence,
+            eligibility: Eligibility::default(),
+            reward_lumis: 10,
+            reward_streak_freezes: 0,
+            starts_at: at(1, 5),
+            ends_at: Some(at(31, 5)),
+        }
+    }
+
+    #[test]
+    fn test_goal_json_round_trip() {
+        let goal: MissionGoal = serde_json::from_value(serde_json::json!({
+            "type": "invoice_count",
+            "target": 3,
+            "filter": {"issuer_category": ["Supermercado"], "amount": {"gte": 5}}
+        }))
+        .unwrap();
+        assert_eq!(goal.target(), d(3));
+        let back: MissionGoal = serde_json::from_value(serde_json::to_value(&goal).unwrap()).unwrap();
+        assert_eq!(back, goal);
+
+        let unknown = serde_json::from_value::<MissionGoal>(serde_json::json!({"type": "invoice_count", "target": 3, "extra": 1}));
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn test_replay_counts_invoices_in_category() {
+        let definition = mission(
+            MissionGoal::InvoiceCount {
+                target: 3,
+                filter: InvoiceFilter {
+                    issuer_category: Some(vec!["Supermercado".to_string()]),
+                    ..Default::default()
+                },
+            },
+            Recurrence::Once,
+        );
+        let events = vec![
+            invoice(7, "a", at(2, 15), Some("supermercado"), d(12)),
+            invoice(7, "b", at(3, 15), Some("Farmacia"), d(30)),
+            invoice(7, "a", at(3, 16), Some("Supermercado"), d(12)), // repetida
+            invoice(7, "c", at(4, 15), Some("Supermercado"), d(5)),
+            invoice(7, "d", at(5, 15), None, d(8)),
+        ];
+        let periods = replay(&definition, &events);
+        assert_eq!(periods["once"].progress, d(2));
+        assert!(periods["once"].completed_at.is_none());
+
+        let mut more = events.clone();
+        more.push(invoice(7, "e", at(6, 15), Some("Supermercado"), d(1)));
+        more.push(invoice(7, "f", at(7, 15), Some("Supermercado"), d(1)));
+        let periods = replay(&definition, &more);
+        assert_eq!(periods["once"].progress, d(3));
+        assert_eq!(periods["once"].completed_at, Some(at(6, 15)));
+    }
+
+    #[test]
+    fn test_replay_spend_at_issuer_with_weekly_recurrence() {
+        let definition = mission(
+            MissionGoal::InvoiceSpend {
+                target_amount: d(50),
+                filter: InvoiceFilter {
+                    issuer_ruc: Some(vec!["155-1-2023".to_string()]),
+                    ..Default::default()
+                },
+            },
+            Recurrence::Weekly,
+        );
+        // Semana 42 (12-18 oct): 30 + 25 completa; semana 43: 20 no completa
+        let events = vec![
+            invoice(7, "a", at(13, 15), None, d(30)),
+            invoice(7, "b", at(15, 15), None, d(25)),
+            invoice(7, "c", at(16, 15), None, d(40)), // ya completada en esa semana
+            invoice(7, "d", at(20, 15), None, d(20)),
+        ];
+        let periods = replay(&definition, &events);
+        assert_eq!(periods["2026-W42"].progress, d(55));
+        assert_eq!(periods["2026-W42"].completed_at, Some(at(15, 15)));
+        assert_eq!(periods["2026-W43"].progress, d(20));
+        assert!(periods["2026-W43"].completed_at.is_none());
+    }
+
+    #[test]
+    fn test_daily_period_uses_panama_time() {
+        let definition = mission(MissionGoal::ActionCount { action: "daily_login".to_string(), target: 2 }, Recurrence::Daily);
+        // 03:00 UTC del 19 sigue siendo el 18 en Panamá
+        let events = vec![
+            MissionEvent::action(7, "daily_login", uuid::Uuid::new_v4(), at(18, 15)),
+            MissionEvent::action(7, "daily_login", uuid::Uuid::new_v4(), at(19, 3)),
+            MissionEvent::action(7, "invoice_upload", uuid::Uuid::new_v4(), at(18, 16)),
+        ];
+        let periods = replay(&definition, &events);
+        assert_eq!(periods.len(), 1);
+        assert!(periods["2026-10-18"].completed_at.is_some());
+    }
+
+    #[test]
+    fn test_surveys_and_window() {
+        let definition = mission(MissionGoal::SurveyCount { target: 2, survey_ids: Some(vec![1, 2, 3]) }, Recurrence::Once);
+        let events = vec![
+            MissionEvent::survey(7, 9, at(2, 15)),   // no está en la lista
+            MissionEvent::survey(7, 1, at(2, 16)),
+            MissionEvent::survey(7, 2, at(31, 6)),   // fuera de la ventana
+        ];
+        let periods = replay(&definition, &events);
+        assert_eq!(periods["once"].progress, d(1));
+
+        let outcome = apply_event(&definition, &PeriodProgress::default(), &MissionEvent::survey(7, 3, at(1, 4)));
+        assert_eq!(outcome, EventOutcome::Ignored);
+    }
+
+    #[test]
+    fn test_validation() {
+        let valid = mission(MissionGoal::InvoiceCount { target: 3, filter: InvoiceFilter::default() }, Recurrence::Once);
+        assert!(valid.validate().is_ok());
+
+        let mut no_reward = valid.clone();
+        no_reward.reward_lumis = 0;
+        assert!(no_reward.validate().is_err());
+
+        let mut bad_code = valid.clone();
+        bad_code.mission_code = "Mi Misión".to_string();
+        assert!(bad_code.validate().is_err());
+
+        let mut bad_window = valid.clone();
+        bad_window.ends_at = Some(valid.starts_at);
+        assert!(bad_window.validate().is_err());
+
+        let empty_filter = mission(
+            MissionGoal::InvoiceCount {
+                target: 3,
+                filter: InvoiceFilter { issuer_ruc: Some(vec![]), ..Default::default() },
+            },
+            Recurrence::Once,
+        );
+        assert!(empty_filter.validate().is_err());
+
+        let zero_spend = mission(
+            MissionGoal::InvoiceSpend { target_amount: Decimal::ZERO, filter: InvoiceFilter::default() },
+            Recurrence::Once,
+        );
+        assert!(zero_spend.validate().is_err());
+    }
+}
@@ -0,0 +1,10 @@
+pub mod mission_service;
+pub mod referral_service;
+pub mod streak_service;
+pub mod trivia_service;
+
+// Re-exports para facilitar imports
+pub use mission_service::{MissionError, MissionService};
+pub use referral_service::{ReferralError, ReferralService};
+pub use streak_service::{StreakError, StreakService};
+pub use trivia_service::{TriviaError, TriviaService};
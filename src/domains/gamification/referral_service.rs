@@ -0,0 +1,887 @@
+//! Programa de referidos
+//!
+//! Cada usuario tiene un `referral_code` en `dim_users` (se genera la primera
+//! vez que lo pide) y un link para compartir. El código se captura al
+//! registrarse (`/api/v4/users/register` y `/api/v4/auth/unified`) y crea una
+//! fila en `gamification.referrals`.
+//!
+//! El premio no se entrega al registrarse: se libera cuando el referido guarda
+//! `required_invoices` facturas válidas (`record_invoice`, llamado al guardar
+//! cada factura). Señales antifraude:
+//!
+//! - Bloquean la atribución: email desechable, dispositivo compartido con el
+//!   referidor u otro referido del mismo referidor.
+//! - Envían a revisión manual al calificar: IP compartida con el referidor,
+//!   demasiados registros referidos desde la misma IP en 24h y tope mensual de
+//!   premios del referidor.
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use std::sync::LazyLock;
+use tracing::{info, warn};
+
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+/// Regla genérica en `rewards.dim_accumulations` para las acumulaciones de referidos
+pub const REFERRAL_ACCUM_ID: i32 = 31;
+pub const REFERRAL_ACCUM_TYPE: &str = "referral";
+
+pub const SOURCE_REGISTER: &str = "register";
+pub const SOURCE_UNIFIED_AUTH: &str = "unified_auth";
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_REVIEW: &str = "review";
+pub const STATUS_REWARDED: &str = "rewarded";
+pub const STATUS_BLOCKED: &str = "blocked";
+pub const STATUS_REJECTED: &str = "rejected";
+
+pub const FLAG_DISPOSABLE_EMAIL: &str = "disposable_email";
+pub const FLAG_SHARED_DEVICE: &str = "shared_device";
+pub const FLAG_SHARED_IP: &str = "shared_ip";
+pub const FLAG_IP_VELOCITY: &str = "ip_velocity";
+pub const FLAG_REFERRER_CAP: &str = "referrer_cap";
+
+/// Sin 0/O/1/I/L para que se pueda dictar sin confusiones
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 8;
+const CODE_GENERATION_ATTEMPTS: usize = 5;
+/// Ventana para comparar la IP del referido con los logins del referidor
+const SHARED_IP_LOOKBACK_DAYS: i32 = 30;
+
+/// Dominios de email temporales más comunes (REFERRAL_BLOCKED_EMAIL_DOMAINS agrega más)
+const DISPOSABLE_EMAIL_DOMAINS: &[&str] = &[
+    "10minutemail.com",
+    "33mail.com",
+    "burnermail.io",
+    "dispostable.com",
+    "emailondeck.com",
+    "fakeinbox.com",
+    "getnada.com",
+    "guerrillamail.com",
+    "guerrillamail.net",
+    "mailinator.com",
+    "maildrop.cc",
+    "mintemail.com",
+    "mohmal.com",
+    "sharklasers.com",
+    "tempail.com",
+    "temp-mail.org",
+    "tempmail.com",
+    "throwawaymail.com",
+    "trashmail.com",
+    "yopmail.com",
+];
+
+fn env_i32(name: &str, default: i32) -> i32 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(default)
+}
+
+static QUALIFYING_INVOICES: LazyLock<i32> = LazyLock::new(|| env_i32("REFERRAL_QUALIFYING_INVOICES", 3));
+static REFERRER_LUMIS: LazyLock<i32> = LazyLock::new(|| env_i32("REFERRAL_REFERRER_LUMIS", 10));
+static REFEREE_LUMIS: LazyLock<i32> = LazyLock::new(|| env_i32("REFERRAL_REFEREE_LUMIS", 5));
+static MONTHLY_REWARD_CAP: LazyLock<i64> = LazyLock::new(|| env_i32("REFERRAL_MONTHLY_CAP", 20) as i64);
+static MAX_SIGNUPS_PER_IP_DAY: LazyLock<i64> = LazyLock::new(|| env_i32("REFERRAL_MAX_SIGNUPS_PER_IP_DAY", 3) as i64);
+
+static LINK_BASE_URL: LazyLock<String> = LazyLock::new(|| {
+    std::env::var("REFERRAL_LINK_BASE_URL")
+        .unwrap_or_else(|_| "https://lumis.pa/invite".to_string())
+        .trim_end_matches('/')
+        .to_string()
+});
+
+static EXTRA_BLOCKED_DOMAINS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    std::env::var("REFERRAL_BLOCKED_EMAIL_DOMAINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|d| d.trim().to_lowercase())
+        .filter(|d| !d.is_empty())
+        .collect()
+});
+
+pub fn qualifying_invoices() -> i32 {
+    *QUALIFYING_INVOICES
+}
+
+pub fn referrer_reward() -> i32 {
+    *REFERRER_LUMIS
+}
+
+pub fn referee_reward() -> i32 {
+    *REFEREE_LUMIS
+}
+
+pub fn referral_link(code: &str) -> String {
+    format!("{}/{}", *LINK_BASE_URL, code)
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+pub fn generate_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..CODE_LENGTH)
+        .map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+/// Normaliza lo que llega del cliente: código suelto o link completo
+pub fn normalize_code(input: &str) -> Option<String> {
+    let raw = input.trim().trim_end_matches('/');
+    let raw = raw.rsplit('/').next().unwrap_or(raw);
+    let raw = raw.split(['?', '#']).next().unwrap_or(raw);
+    let code = raw.trim().to_uppercase();
+    let valid_chars = code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid_chars && (4..=20).contains(&code.len()) {
+        Some(code)
+    } else {
+        None
+    }
+}
+
+pub fn email_domain(email: &str) -> Option<String> {
+    let (_, domain) = email.trim().rsplit_once('@')?;
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    if domain.is_empty() {
+        None
+    } else {
+        Some(domain)
+    }
+}
+
+/// Coincide el dominio o cualquiera de sus subdominios
+pub fn is_disposable_domain(domain: &str, extra: &[String]) -> bool {
+    let matches = |blocked: &str| domain == blocked || domain.ends_with(&format!(".{}", blocked));
+    DISPOSABLE_EMAIL_DOMAINS.iter().any(|d| matches(d)) || extra.iter().any(|d| matches(d))
+}
+
+/// IP canónica (descarta valores que no son IP, p.ej. "unknown")
+pub fn normalize_ip(raw: &str) -> Option<String> {
+    raw.trim().parse::<std::net::IpAddr>().ok().map(|ip| ip.to_string())
+}
+
+/// Señales que se evalúan al atribuir un registro
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FraudSignals {
+    pub disposable_email: bool,
+    /// El dispositivo pertenece al referidor o a otro de sus referidos
+    pub shared_device: bool,
+    /// La IP de registro coincide con un login reciente del referidor
+    pub shared_ip: bool,
+    /// Registros referidos desde la misma IP en las últimas 24h (sin contar este)
+    pub ip_signups_24h: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FraudAssessment {
+    pub flags: Vec<&'static str>,
+    /// No se premia nunca (la atribución queda como `blocked`)
+    pub blocked: bool,
+}
+
+pub fn assess(signals: &FraudSignals, max_signups_per_ip: i64) -> FraudAssessment {
+    let mut flags = Vec::new();
+    if signals.disposable_email {
+        flags.push(FLAG_DISPOSABLE_EMAIL);
+    }
+    if signals.shared_device {
+        flags.push(FLAG_SHARED_DEVICE);
+    }
+    let blocked = !flags.is_empty();
+    if signals.shared_ip {
+        flags.push(FLAG_SHARED_IP);
+    }
+    if signals.ip_signups_24h >= max_signups_per_ip {
+        flags.push(FLAG_IP_VELOCITY);
+    }
+    FraudAssessment { flags, blocked }
+}
+
+/// Estado al que pasa un referido pendiente después de una factura
+pub fn status_after_invoice(qualifying: i32, required: i32, has_flags: bool) -> &'static str {
+    if qualifying < required {
+        STATUS_PENDING
+    } else if has_flags {
+        STATUS_REVIEW
+    } else {
+        STATUS_REWARDED
+    }
+}
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReferralError {
+    #[error("{}", Text::new("api.referral.invalid_code"))]
+    InvalidCode,
+
+    #[error("{}", Text::new("api.referral.self_referral"))]
+    SelfReferral,
+
+    #[error("{}", Text::new("api.referral.already_attributed"))]
+    AlreadyAttributed,
+
+    #[error("{}", Text::new("api.not_found.referral"))]
+    NotFound,
+
+    #[error("{}", Text::new("api.referral.not_in_review"))]
+    NotInReview,
+
+    #[error("{}", Text::new("api.referral.code_generation"))]
+    CodeGeneration,
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for ReferralError {
+    fn from(err: sqlx::Error) -> Self {
+        ReferralError::Database(err.to_string())
+    }
+}
+
+/// Datos del registro para atribuir y evaluar fraude
+#[derive(Debug, Clone, Default)]
+pub struct SignupContext {
+    pub source: &'static str,
+    pub email: Option<String>,
+    pub ip_address: Option<String>,
+    pub device_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Attribution {
+    pub referral_id: i64,
+    pub referrer_user_id: i32,
+    pub status: String,
+    pub fraud_flags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReferralOverview {
+    pub referral_code: String,
+    pub referral_link: String,
+    pub share_message: String,
+    pub required_invoices: i32,
+    pub referrer_reward_lumis: i32,
+    pub referee_reward_lumis: i32,
+    pub total_referrals: i64,
+    pub pending: i64,
+    pub in_review: i64,
+    pub rewarded: i64,
+    pub lumis_earned: i64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ReferralItem {
+    pub referral_id: i64,
+    /// Primer nombre del referido (nunca el email)
+    pub referee_name: String,
+    pub status: String,
+    pub qualifying_invoices: i32,
+    pub required_invoices: i32,
+    pub lumis_earned: i32,
+    pub created_at: DateTime<Utc>,
+    pub rewarded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ReferralReviewItem {
+    pub referral_id: i64,
+    pub referrer_user_id: i32,
+    pub referee_user_id: i32,
+    pub fraud_flags: Vec<String>,
+    pub signup_ip: Option<String>,
+    pub device_id: Option<String>,
+    pub email_domain: Option<String>,
+    pub qualifying_invoices: i32,
+    pub created_at: DateTime<Utc>,
+    pub qualified_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, FromRow)]
+struct PendingReferral {
+    referral_id: i64,
+    referrer_user_id: i32,
+    required_invoices: i32,
+    fraud_flags: Vec<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReferralProgress {
+    pub referral_id: i64,
+    pub status: String,
+    pub qualifying_invoices: i32,
+    pub required_invoices: i32,
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct ReferralService {
+    db: PgPool,
+}
+
+impl ReferralService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Código del usuario; lo genera si aún no tiene
+    pub async fn ensure_code(&self, user_id: i32) -> Result<String, ReferralError> {
+        let existing = sqlx::query_scalar::<_, Option<String>>("SELECT referral_code FROM public.dim_users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or(ReferralError::NotFound)?;
+        if let Some(code) = existing.filter(|c| !c.trim().is_empty()) {
+            return Ok(code);
+        }
+
+        for _ in 0..CODE_GENERATION_ATTEMPTS {
+            let code = generate_code();
+            let result = sqlx::query_scalar::<_, String>(
+                r#"
+                UPDATE public.dim_users
+                SET referral_code = COALESCE(NULLIF(TRIM(referral_code), ''), $2)
+                WHERE id = $1
+                RETURNING referral_code
+                "#,
+            )
+            .bind(user_id)
+            .bind(&code)
+            .fetch_one(&self.db)
+            .await;
+
+            match result {
+                Ok(code) => return Ok(code),
+                // Colisión con el índice único: reintentar con otro código
+                Err(sqlx::Error::Database(e)) if e.is_unique_violation() => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(ReferralError::CodeGeneration)
+    }
+
+    pub async fn overview(&self, user_id: i32) -> Result<ReferralOverview, ReferralError> {
+        let code = self.ensure_code(user_id).await?;
+        let link = referral_link(&code);
+
+        let (total, pending, in_review, rewarded, lumis_earned) = sqlx::query_as::<_, (i64, i64, i64, i64, i64)>(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE status <> 'blocked'),
+                COUNT(*) FILTER (WHERE status = 'pending'),
+                COUNT(*) FILTER (WHERE status = 'review'),
+                COUNT(*) FILTER (WHERE status = 'rewarded'),
+                COALESCE(SUM(referrer_lumis), 0)::BIGINT
+            FROM gamification.referrals
+            WHERE referrer_user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(ReferralOverview {
+            share_message: format!(
+                "¡Únete a Lümis con mi código {} y gana {} Lümis al subir tus primeras {} facturas! {}",
+                code,
+                referee_reward(),
+                qualifying_invoices(),
+                link
+            ),
+            referral_code: code,
+            referral_link: link,
+            required_invoices: qualifying_invoices(),
+            referrer_reward_lumis: referrer_reward(),
+            referee_reward_lumis: referee_reward(),
+            total_referrals: total,
+            pending,
+            in_review,
+            rewarded,
+            lumis_earned,
+        })
+    }
+
+    /// Referidos del usuario (los bloqueados por fraude no se muestran)
+    pub async fn list(&self, user_id: i32, limit: i64, offset: i64) -> Result<Vec<ReferralItem>, ReferralError> {
+        Ok(sqlx::query_as::<_, ReferralItem>(
+            r#"
+            SELECT
+                r.referral_id,
+                COALESCE(NULLIF(split_part(TRIM(u.name), ' ', 1), ''), 'Lümer #' || u.id) AS referee_name,
+                r.status,
+                r.qualifying_invoices,
+                r.required_invoices,
+                r.referrer_lumis AS lumis_earned,
+                r.created_at,
+                r.rewarded_at
+            FROM gamification.referrals r
+            JOIN public.dim_users u ON u.id = r.referee_user_id
+            WHERE r.referrer_user_id = $1 AND r.status <> 'blocked'
+            ORDER BY r.created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn collect_signals(
+        &self,
+        referrer_user_id: i32,
+        ctx: &SignupContext,
+        ip: Option<&str>,
+        domain: Option<&str>,
+    ) -> Result<FraudSignals, ReferralError> {
+        let mut signals = FraudSignals {
+            disposable_email: domain.is_some_and(|d| is_disposable_domain(d, &EXTRA_BLOCKED_DOMAINS)),
+            ..Default::default()
+        };
+
+        if let Some(device_id) = ctx.device_id.as_deref().filter(|d| !d.trim().is_empty()) {
+            signals.shared_device = sqlx::query_scalar::<_, bool>(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM public.device_tokens WHERE user_id = $1 AND device_id = $2
+                ) OR EXISTS (
+                    SELECT 1 FROM gamification.referrals WHERE referrer_user_id = $1 AND device_id = $2
+                )
+                "#,
+            )
+            .bind(referrer_user_id)
+            .bind(device_id)
+            .fetch_one(&self.db)
+            .await?;
+        }
+
+        if let Some(ip) = ip {
+            signals.shared_ip = sqlx::query_scalar::<_, bool>(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM public.auth_audit_log
+                    WHERE user_id = $1
+                      AND host(ip_address) = $2
+                      AND created_at > NOW() - make_interval(days => $3)
+                )
+                "#,
+            )
+            .bind(referrer_user_id)
+            .bind(ip)
+            .bind(SHARED_IP_LOOKBACK_DAYS)
+            .fetch_one(&self.db)
+            .await?;
+
+            signals.ip_signups_24h = sqlx::query_scalar::<_, i64>(
+                r#"
+                SELECT COUNT(*) FROM gamification.referrals
+                WHERE signup_ip = $1 AND created_at > NOW() - INTERVAL '24 hours'
+                "#,
+            )
+            .bind(ip)
+            .fetch_one(&self.db)
+            .await?;
+        }
+
+        Ok(signals)
+    }
+
+    /// Atribuye un registro nuevo a un código de referido
+    pub async fn attribute(
+        &self,
+        referee_user_id: i32,
+        raw_code: &str,
+        ctx: &SignupContext,
+    ) -> Result<Attribution, ReferralError> {
+        let code = normalize_code(raw_code).ok_or(ReferralError::InvalidCode)?;
+
+        let referrer_user_id = sqlx::query_scalar::<_, i32>(
+            "SELECT id::INT FROM public.dim_users WHERE UPPER(referral_code) = $1 AND is_active = true",
+        )
+        .bind(&code)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or(ReferralError::InvalidCode)?;
+
+        if referrer_user_id == referee_user_id {
+            return Err(ReferralError::SelfReferral);
+        }
+
+        let ip = ctx.ip_address.as_deref().and_then(normalize_ip);
+        let domain = ctx.email.as_deref().and_then(email_domain);
+        let signals = self
+            .collect_signals(referrer_user_id, ctx, ip.as_deref(), domain.as_deref())
+            .await?;
+        let assessment = assess(&signals, *MAX_SIGNUPS_PER_IP_DAY);
+        let status = if assessment.blocked { STATUS_BLOCKED } else { STATUS_PENDING };
+        let flags: Vec<String> = assessment.flags.iter().map(|f| f.to_string()).collect();
+
+        let mut tx = self.db.begin().await?;
+
+        let referral_id = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO gamification.referrals (
+                referrer_user_id, referee_user_id, referral_code, source, status,
+                fraud_flags, signup_ip, device_id, email_domain, required_invoices
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (referee_user_id) DO NOTHING
+            RETURNING referral_id
+            "#,
+        )
+        .bind(referrer_user_id)
+        .bind(referee_user_id)
+        .bind(&code)
+        .bind(ctx.source)
+        .bind(status)
+        .bind(&flags)
+        .bind(ip.as_deref())
+        .bind(ctx.device_id.as_deref())
+        .bind(domain.as_deref())
+        .bind(qualifying_invoices())
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ReferralError::AlreadyAttributed)?;
+
+        if !assessment.blocked {
+            sqlx::query("UPDATE public.dim_users SET referred_by = $2 WHERE id = $1 AND referred_by IS NULL")
+                .bind(referee_user_id)
+                .bind(referrer_user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        if flags.is_empty() {
+            info!("🤝 User {} referred by {} (referral {})", referee_user_id, referrer_user_id, referral_id);
+        } else {
+            warn!(
+                "🚩 Referral {} ({} -> {}) flagged {:?}, status {}",
+                referral_id, referrer_user_id, referee_user_id, flags, status
+            );
+        }
+
+        Ok(Attribution {
+            referral_id,
+            referrer_user_id,
+            status: status.to_string(),
+            fraud_flags: flags,
+        })
+    }
+
+    /// Avanza el referido pendiente del usuario después de guardar una factura.
+    /// Devuelve None si el usuario no tiene un referido pendiente.
+    pub async fn record_invoice(&self, referee_user_id: i32) -> Result<Option<ReferralProgress>, ReferralError> {
+        let mut tx = self.db.begin().await?;
+
+        let Some(referral) = sqlx::query_as::<_, PendingReferral>(
+            r#"
+            SELECT referral_id, referrer_user_id, required_invoices, fraud_flags, created_at
+            FROM gamification.referrals
+            WHERE referee_user_id = $1 AND status = 'pending'
+            FOR UPDATE
+            "#,
+        )
+        .bind(referee_user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        // Facturas guardadas desde la atribución (las de OCR pendientes no cuentan)
+        let qualifying = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM public.invoice_header
+            WHERE user_id = $1
+              AND process_date >= $2
+              AND COALESCE(type, '') <> 'ocr_pending'
+            "#,
+        )
+        .bind(referee_user_id)
+        .bind(referral.created_at)
+        .fetch_one(&mut *tx)
+        .await? as i32;
+
+        let mut flags = referral.fraud_flags.clone();
+        if qualifying >= referral.required_invoices && flags.is_empty() {
+            let rewarded_this_month = sqlx::query_scalar::<_, i64>(
+                r#"
+                SELECT COUNT(*) FROM gamification.referrals
+                WHERE referrer_user_id = $1
+                  AND status = 'rewarded'
+                  AND rewarded_at >= date_trunc('month', NOW())
+                "#,
+            )
+            .bind(referral.referrer_user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            if rewarded_this_month >= *MONTHLY_REWARD_CAP {
+                flags.push(FLAG_REFERRER_CAP.to_string());
+            }
+        }
+
+        let status = status_after_invoice(qualifying, referral.required_invoices, !flags.is_empty());
+
+        sqlx::query(
+            r#"
+            UPDATE gamification.referrals
+            SET qualifying_invoices = $2,
+                fraud_flags = $3,
+                status = $4,
+                qualified_at = CASE WHEN $4 <> 'pending' THEN NOW() ELSE qualified_at END,
+                updated_at = NOW()
+            WHERE referral_id = $1
+            "#,
+        )
+        .bind(referral.referral_id)
+        .bind(qualifying)
+        .bind(&flags)
+        .bind(status)
+        .execute(&mut *tx)
+        .await?;
+
+        if status == STATUS_REWARDED {
+            Self::release_rewards(&mut tx, referral.referral_id, referral.referrer_user_id, referee_user_id).await?;
+        }
+
+        tx.commit().await?;
+
+        if status == STATUS_REWARDED {
+            self.track_referral_complete(referral.referrer_user_id, referral.referral_id).await;
+        } else if status == STATUS_REVIEW {
+            warn!("🚩 Referral {} qualified with flags {:?}, sent to review", referral.referral_id, flags);
+        }
+
+        Ok(Some(ReferralProgress {
+            referral_id: referral.referral_id,
+            status: status.to_string(),
+            qualifying_invoices: qualifying,
+            required_invoices: referral.required_invoices,
+        }))
+    }
+
+    /// Acredita Lümis a ambos y marca el referido como premiado
+    async fn release_rewards(
+        tx: &mut Transaction<'_, Postgres>,
+        referral_id: i64,
+        referrer_user_id: i32,
+        referee_user_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        let (referrer_lumis, referee_lumis) = (referrer_reward(), referee_reward());
+
+        for (user_id, role, lumis) in [(referrer_user_id, "referrer", referrer_lumis), (referee_user_id, "referee", referee_lumis)] {
+            sqlx::query(
+                r#"
+                INSERT INTO rewards.fact_accumulations
+                (user_id, accum_type, accum_key, dtype, quantity, date, accum_id)
+                VALUES ($1, $2, $3, 'points', $4, $5, $6)
+                "#,
+            )
+            .bind(user_id)
+            .bind(REFERRAL_ACCUM_TYPE)
+            .bind(format!("referral:{}:{}", referral_id, role))
+            .bind(lumis)
+            .bind(Utc::now().naive_utc())
+            .bind(REFERRAL_ACCUM_ID)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE gamification.referrals
+            SET status = 'rewarded',
+                referrer_lumis = $2,
+                referee_lumis = $3,
+                rewarded_at = NOW(),
+                updated_at = NOW()
+            WHERE referral_id = $1
+            "#,
+        )
+        .bind(referral_id)
+        .bind(referrer_lumis)
+        .bind(referee_lumis)
+        .execute(&mut **tx)
+        .await?;
+
+        info!(
+            "🎁 Referral {} rewarded: {} Lümis to referrer {}, {} to referee {}",
+            referral_id, referrer_lumis, referrer_user_id, referee_lumis, referee_user_id
+        );
+        Ok(())
+    }
+
+    /// `referral_complete` alimenta logros y misiones del referidor (best effort)
+    async fn track_referral_complete(&self, referrer_user_id: i32, referral_id: i64) {
+        let result = sqlx::query("SELECT * FROM gamification.track_user_action($1, 'referral_complete', 'api', $2)")
+            .bind(referrer_user_id)
+            .bind(serde_json::json!({ "referral_id": referral_id }))
+            .execute(&self.db)
+            .await;
+        if let Err(e) = result {
+            warn!("Failed to track referral_complete for user {}: {}", referrer_user_id, e);
+        }
+    }
+
+    /// Referidos que calificaron con señales de fraude
+    pub async fn pending_review(&self, limit: i64) -> Result<Vec<ReferralReviewItem>, ReferralError> {
+        Ok(sqlx::query_as::<_, ReferralReviewItem>(
+            r#"
+            SELECT referral_id, referrer_user_id, referee_user_id, fraud_flags, signup_ip,
+                   device_id, email_domain, qualifying_invoices, created_at, qualified_at
+            FROM gamification.referrals
+            WHERE status = 'review'
+            ORDER BY qualified_at NULLS LAST, referral_id
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    /// Aprueba (libera premios) o rechaza un referido en revisión
+    pub async fn review(
+        &self,
+        referral_id: i64,
+        admin_user_id: i64,
+        approve: bool,
+        note: Option<&str>,
+    ) -> Result<String, ReferralError> {
+        let mut tx = self.db.begin().await?;
+
+        let (status, referrer_user_id, referee_user_id) = sqlx::query_as::<_, (String, i32, i32)>(
+            "SELECT status, referrer_user_id, referee_user_id FROM gamification.referrals WHERE referral_id = $1 FOR UPDATE",
+        )
+        .bind(referral_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ReferralError::NotFound)?;
+
+        if status != STATUS_REVIEW {
+            return Err(ReferralError::NotInReview);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE gamification.referrals
+            SET status = $2, reviewed_by = $3, review_note = $4, updated_at = NOW()
+            WHERE referral_id = $1
+            "#,
+        )
+        .bind(referral_id)
+        .bind(if approve { STATUS_REWARDED } else { STATUS_REJECTED })
+        .bind(admin_user_id)
+        .bind(note)
+        .execute(&mut *tx)
+        .await?;
+
+        if approve {
+            Self::release_rewards(&mut tx, referral_id, referrer_user_id, referee_user_id).await?;
+        }
+
+        tx.commit().await?;
+
+        info!(
+            "Admin {} {} referral {}",
+            admin_user_id,
+            if approve { "approved" } else { "rejected" },
+            referral_id
+        );
+
+        if approve {
+            self.track_referral_complete(referrer_user_id, referral_id).await;
+            Ok(STATUS_REWARDED.to_string())
+        } else {
+            Ok(STATUS_REJECTED.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_code_uses_unambiguous_alphabet() {
+        for _ in 0..50 {
+            let code = generate_code();
+            assert_eq!(code.len(), CODE_LENGTH);
+            assert!(code.bytes().all(|b| CODE_ALPHABET.contains(&b)));
+            assert_eq!(normalize_code(&code), Some(code.clone()));
+        }
+    }
+
+    #[test]
+    fn test_normalize_code_accepts_links() {
+        assert_eq!(normalize_code(" ab12cd34 ").as_deref(), Some("AB12CD34"));
+        assert_eq!(normalize_code("https://lumis.pa/invite/AB12CD34/").as_deref(), Some("AB12CD34"));
+        assert_eq!(normalize_code("https://lumis.pa/invite/AB12CD34?utm=wa").as_deref(), Some("AB12CD34"));
+        assert_eq!(normalize_code("REF_USER123_2025").as_deref(), Some("REF_USER123_2025"));
+        assert_eq!(normalize_code("ab"), None);
+        assert_eq!(normalize_code("AB12 CD34"), None);
+    }
+
+    #[test]
+    fn test_disposable_domains() {
+        assert_eq!(email_domain("Ana@Mailinator.com").as_deref(), Some("mailinator.com"));
+        assert_eq!(email_domain("sin-arroba"), None);
+        assert!(is_disposable_domain("mailinator.com", &[]));
+        assert!(is_disposable_domain("eu.mailinator.com", &[]));
+        assert!(!is_disposable_domain("notmailinator.com", &[]));
+        assert!(!is_disposable_domain("gmail.com", &[]));
+        assert!(is_disposable_domain("spam.example", &["spam.example".to_string()]));
+    }
+
+    #[test]
+    fn test_normalize_ip() {
+        assert_eq!(normalize_ip(" 190.140.1.2 ").as_deref(), Some("190.140.1.2"));
+        assert_eq!(normalize_ip("unknown"), None);
+    }
+
+    #[test]
+    fn test_assess_blocks_hard_signals_and_flags_soft_ones() {
+        let clean = assess(&FraudSignals::default(), 3);
+        assert!(clean.flags.is_empty());
+        assert!(!clean.blocked);
+
+        let device = assess(&FraudSignals { shared_device: true, ..Default::default() }, 3);
+        assert!(device.blocked);
+        assert_eq!(device.flags, vec![FLAG_SHARED_DEVICE]);
+
+        let soft = assess(&FraudSignals { shared_ip: true, ip_signups_24h: 3, ..Default::default() }, 3);
+        assert!(!soft.blocked);
+        assert_eq!(soft.flags, vec![FLAG_SHARED_IP, FLAG_IP_VELOCITY]);
+
+        let under_velocity = assess(&FraudSignals { ip_signups_24h: 2, ..Default::default() }, 3);
+        assert!(under_velocity.flags.is_empty());
+    }
+
+    #[test]
+    fn test_status_after_invoice() {
+        assert_eq!(status_after_invoice(2, 3, false), STATUS_PENDING);
+        assert_eq!(status_after_invoice(2, 3, true), STATUS_PENDING);
+        assert_eq!(status_after_invoice(3, 3, false), STATUS_REWARDED);
+        assert_eq!(status_after_invoice(4, 3, true), STATUS_REVIEW);
+    }
+}
@@ -0,0 +1,896 @@
+//! Protección y recuperación de la racha de login diario
+//!
+//! La racha `daily_login` avanza en ciclos de 7 días (día 7 = `week_perfect`).
+//! Antes, cualquier día sin login la reiniciaba. Ahora:
+//!
+//! - Los congeladores (`user_streak_freezes`) se consumen automáticamente, uno
+//!   por día faltante, si alcanzan para cubrir el hueco completo.
+//! - Si la racha se rompe, el usuario puede pagar Lümis para recuperarla
+//!   (`restore_streak`) hasta 48h después del inicio del primer día sin login.
+//! - Cada compra o recuperación escribe un spend en `rewards.fact_accumulations`
+//!   y cada movimiento de congeladores queda en `streak_freeze_ledger`.
+//!
+//! Las fechas usan `CURRENT_DATE` de la base de datos para coincidir con
+//! `gamification.update_daily_login_streak`, que sigue existiendo para
+//! `track_user_action` (queda como no-op porque el login ya se contó aquí).
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use std::sync::LazyLock;
+use tracing::{info, warn};
+
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+pub const DAILY_LOGIN_STREAK: &str = "daily_login";
+pub const STREAK_CYCLE_DAYS: i32 = 7;
+/// Congeladores que un usuario puede tener a la vez
+pub const MAX_FREEZES_HELD: i32 = 5;
+/// Días seguidos sin login que los congeladores pueden cubrir
+pub const MAX_FROZEN_DAYS_PER_GAP: i64 = 3;
+pub const RESTORE_WINDOW_HOURS: i64 = 48;
+
+pub const FREEZE_REASON_PURCHASE: &str = "purchase";
+pub const FREEZE_REASON_MISSION: &str = "mission";
+pub const FREEZE_REASON_ADMIN: &str = "admin";
+const FREEZE_REASON_CONSUMED: &str = "consumed";
+
+static FREEZE_PRICE_LUMIS: LazyLock<i32> = LazyLock::new(|| {
+    std::env::var("STREAK_FREEZE_PRICE_LUMIS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(2)
+});
+
+static RESTORE_PRICE_LUMIS: LazyLock<i32> = LazyLock::new(|| {
+    std::env::var("STREAK_RESTORE_PRICE_LUMIS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(3)
+});
+
+pub fn freeze_price() -> i32 {
+    *FREEZE_PRICE_LUMIS
+}
+
+pub fn restore_price() -> i32 {
+    *RESTORE_PRICE_LUMIS
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+/// Resultado de contar el login de hoy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginPlan {
+    pub already_counted: bool,
+    pub new_count: i32,
+    /// Días faltantes cubiertos con congeladores
+    pub frozen_dates: Vec<NaiveDate>,
+    /// Racha perdida (Some si se rompió y es recuperable)
+    pub broken_count: Option<i32>,
+    /// Primer día sin login; la ventana de recuperación corre desde su inicio
+    pub broken_on: Option<NaiveDate>,
+    pub cycle_completed: bool,
+}
+
+/// Calcula el efecto del login de `today` sobre la racha
+pub fn plan_daily_login(
+    current_count: i32,
+    last_activity: Option<NaiveDate>,
+    today: NaiveDate,
+    freezes_available: i32,
+) -> LoginPlan {
+    let mut plan = LoginPlan {
+        already_counted: false,
+        new_count: 1,
+        frozen_dates: Vec::new(),
+        broken_count: None,
+        broken_on: None,
+        cycle_completed: false,
+    };
+
+    let Some(last) = last_activity else {
+        return plan;
+    };
+    if last >= today {
+        plan.already_counted = true;
+        plan.new_count = current_count;
+        return plan;
+    }
+
+    let missed_days = (today - last).num_days() - 1;
+    // Un ciclo completo no se "rompe": simplemente empieza uno nuevo
+    let protectable = current_count > 0 && current_count < STREAK_CYCLE_DAYS;
+    let continues = if missed_days == 0 {
+        true
+    } else if protectable
+        && missed_days <= MAX_FROZEN_DAYS_PER_GAP
+        && missed_days <= freezes_available as i64
+    {
+        plan.frozen_dates = (1..=missed_days).map(|d| last + Duration::days(d)).collect();
+        true
+    } else {
+        // Al iniciar `today` ya pasaron `missed_days` días completos desde la
+        // ruptura; si eso agota la ventana no hay nada que recuperar
+        if protectable && missed_days * 24 < RESTORE_WINDOW_HOURS {
+            plan.broken_count = Some(current_count);
+            plan.broken_on = Some(last + Duration::days(1));
+        }
+        false
+    };
+
+    if continues && current_count < STREAK_CYCLE_DAYS {
+        plan.new_count = current_count + 1;
+    }
+    plan.cycle_completed = plan.new_count == STREAK_CYCLE_DAYS;
+    plan
+}
+
+/// Racha resultante de recuperar `broken_count` sobre la racha actual
+pub fn restored_count(broken_count: i32, current_count: i32) -> i32 {
+    (broken_count + current_count).min(STREAK_CYCLE_DAYS)
+}
+
+/// La recuperación vence 48h después de la ruptura
+pub fn restore_deadline(broken_at: DateTime<Utc>) -> DateTime<Utc> {
+    broken_at + Duration::hours(RESTORE_WINDOW_HOURS)
+}
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum StreakError {
+    #[error("{}", Text::new("api.balance.insufficient").with("balance", .balance).with("cost", .cost))]
+    InsufficientBalance { balance: i64, cost: i64 },
+
+    #[error("{}", Text::new("api.validation.quantity").with("max", .0))]
+    InvalidQuantity(i32),
+
+    #[error("{}", Text::new("api.streak.inventory_full").with("max", .0))]
+    InventoryFull(i32),
+
+    #[error("{}", Text::new("api.streak.nothing_to_restore"))]
+    NothingToRestore,
+
+    #[error("{}", Text::new("api.streak.restore_expired"))]
+    RestoreExpired,
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for StreakError {
+    fn from(err: sqlx::Error) -> Self {
+        StreakError::Database(err.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyLoginResult {
+    pub streak_count: i32,
+    pub already_counted: bool,
+    pub freezes_used: i32,
+    pub freezes_available: i32,
+    pub broken_count: Option<i32>,
+    pub cycle_completed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FreezePurchase {
+    pub quantity: i32,
+    pub lumis_spent: i32,
+    pub freezes_available: i32,
+    pub new_balance: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreakRestore {
+    pub previous_count: i32,
+    pub streak_count: i32,
+    pub lumis_spent: i32,
+    pub new_balance: i64,
+    pub cycle_completed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreOffer {
+    pub broken_count: i32,
+    pub restored_count: i32,
+    pub cost_lumis: i32,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct StreakHistoryEntry {
+    pub event_type: String,
+    pub streak_count: i32,
+    pub previous_count: Option<i32>,
+    pub event_date: NaiveDate,
+    pub lumis_spent: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreakOverview {
+    pub current_count: i32,
+    pub max_count: i32,
+    pub cycle_days: i32,
+    pub last_activity_date: Option<NaiveDate>,
+    pub freezes_available: i32,
+    pub freezes_used_total: i32,
+    pub max_freezes: i32,
+    pub freeze_price_lumis: i32,
+    pub restore: Option<RestoreOffer>,
+    pub history: Vec<StreakHistoryEntry>,
+}
+
+#[derive(Debug, FromRow)]
+struct StreakRow {
+    current_count: i32,
+    max_count: i32,
+    freeze_count: i32,
+    last_activity_date: Option<NaiveDate>,
+    streak_start_date: Option<NaiveDate>,
+    broken_count: Option<i32>,
+    broken_at: Option<DateTime<Utc>>,
+    today: NaiveDate,
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct StreakService {
+    db: PgPool,
+}
+
+impl StreakService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    async fn lock_streak(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<Option<StreakRow>, sqlx::Error> {
+        sqlx::query_as::<_, StreakRow>(
+            r#"
+            SELECT COALESCE(current_count, 0) AS current_count,
+                   COALESCE(max_count, 0) AS max_count,
+                   COALESCE(freeze_count, 0) AS freeze_count,
+                   last_activity_date, streak_start_date,
+                   broken_count, broken_at,
+                   CURRENT_DATE AS today
+            FROM gamification.user_streaks
+            WHERE user_id = $1 AND streak_type = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(user_id)
+        .bind(DAILY_LOGIN_STREAK)
+        .fetch_optional(&mut **tx)
+        .await
+    }
+
+    async fn lock_freezes(tx: &mut Transaction<'_, Postgres>, user_id: i32) -> Result<i32, sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO gamification.user_streak_freezes (user_id) VALUES ($1) ON CONFLICT (user_id) DO NOTHING",
+        )
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query_scalar::<_, i32>(
+            "SELECT available FROM gamification.user_streak_freezes WHERE user_id = $1 FOR UPDATE",
+        )
+        .bind(user_id)
+        .fetch_one(&mut **tx)
+        .await
+    }
+
+    /// Bloquea el balance y valida que alcance para `cost`
+    async fn lock_balance(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        cost: i64,
+    ) -> Result<i64, StreakError> {
+        let balance = sqlx::query_scalar::<_, i64>(
+            "SELECT COALESCE(balance, 0)::BIGINT FROM rewards.fact_balance_points WHERE user_id = $1 FOR UPDATE",
+        )
+        .bind(user_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .unwrap_or(0);
+
+        if balance < cost {
+            return Err(StreakError::InsufficientBalance { balance, cost });
+        }
+        Ok(balance)
+    }
+
+    /// Ledger: spend negativo; el trigger actualiza fact_balance_points
+    async fn record_spend(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        dtype: &str,
+        accum_key: &str,
+        cost: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO rewards.fact_accumulations (
+                user_id, accum_type, accum_key, dtype, quantity, balance, date
+            )
+            SELECT
+                $1, 'spend', $2, $3, -$4::NUMERIC,
+                COALESCE(fbp.balance, 0) - $4,
+                NOW()
+            FROM rewards.fact_balance_points fbp
+            WHERE fbp.user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(accum_key)
+        .bind(dtype)
+        .bind(cost)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    async fn record_history(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        event_type: &str,
+        streak_count: i32,
+        previous_count: Option<i32>,
+        event_date: NaiveDate,
+        lumis_spent: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO gamification.streak_history
+            (user_id, streak_type, event_type, streak_count, previous_count, event_date, lumis_spent)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(user_id)
+        .bind(DAILY_LOGIN_STREAK)
+        .bind(event_type)
+        .bind(streak_count)
+        .bind(previous_count)
+        .bind(event_date)
+        .bind(lumis_spent)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Otorga `week_perfect` si no se otorgó ya para la racha que inició en `start_date`
+    async fn grant_week_perfect(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        start_date: NaiveDate,
+    ) -> Result<(), sqlx::Error> {
+        let already_granted = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM rewards.fact_accumulations fa
+                JOIN rewards.dim_accumulations da ON fa.accum_id = da.id
+                WHERE fa.user_id = $1
+                  AND da.name LIKE '%week_perfect%'
+                  AND fa.date >= $2
+            )
+            "#,
+        )
+        .bind(user_id)
+        .bind(start_date)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        if !already_granted {
+            sqlx::query("SELECT gamification.grant_achievement_reward($1, 'week_perfect')")
+                .bind(user_id)
+                .execute(&mut **tx)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Cuenta el login de hoy aplicando congeladores si hacen falta (idempotente por día)
+    pub async fn record_daily_login(&self, user_id: i32) -> Result<DailyLoginResult, StreakError> {
+        let mut tx = self.db.begin().await?;
+
+        let streak = Self::lock_streak(&mut tx, user_id).await?;
+        let freezes_available = Self::lock_freezes(&mut tx, user_id).await?;
+
+        let today = match &streak {
+            Some(row) => row.today,
+            None => sqlx::query_scalar::<_, NaiveDate>("SELECT CURRENT_DATE").fetch_one(&mut *tx).await?,
+        };
+        let (current_count, last_activity) = streak
+            .as_ref()
+            .map(|row| (row.current_count, row.last_activity_date))
+            .unwrap_or((0, None));
+
+        let plan = plan_daily_login(current_count, last_activity, today, freezes_available);
+        if plan.already_counted {
+            tx.commit().await?;
+            return Ok(DailyLoginResult {
+                streak_count: plan.new_count,
+                already_counted: true,
+                freezes_used: 0,
+                freezes_available,
+                broken_count: None,
+                cycle_completed: false,
+            });
+        }
+
+        let start_date = if plan.new_count == 1 {
+            today
+        } else {
+            streak
+                .as_ref()
+                .and_then(|row| row.streak_start_date)
+                .unwrap_or(today - Duration::days((plan.new_count - 1) as i64))
+        };
+        let freezes_used = plan.frozen_dates.len() as i32;
+
+        sqlx::query(
+            r#"
+            INSERT INTO gamification.user_streaks
+            (user_id, streak_type, current_count, max_count, last_activity_date, streak_start_date, broken_count, broken_at)
+            VALUES ($1, $2, $3, $3, $4, $5, $6, $8::DATE::TIMESTAMPTZ)
+            ON CONFLICT (user_id, streak_type) DO UPDATE SET
+                current_count = EXCLUDED.current_count,
+                max_count = GREATEST(COALESCE(gamification.user_streaks.max_count, 0), EXCLUDED.current_count),
+                last_activity_date = EXCLUDED.last_activity_date,
+                streak_start_date = EXCLUDED.streak_start_date,
+                freeze_count = COALESCE(gamification.user_streaks.freeze_count, 0) + $7,
+                broken_count = COALESCE(EXCLUDED.broken_count, gamification.user_streaks.broken_count),
+                broken_at = COALESCE(EXCLUDED.broken_at, gamification.user_streaks.broken_at),
+                updated_at = NOW()
+            "#,
+        )
+        .bind(user_id)
+        .bind(DAILY_LOGIN_STREAK)
+        .bind(plan.new_count)
+        .bind(today)
+        .bind(start_date)
+        .bind(plan.broken_count)
+        .bind(freezes_used)
+        .bind(plan.broken_on)
+        .execute(&mut *tx)
+        .await?;
+
+        if freezes_used > 0 {
+            sqlx::query(
+                "UPDATE gamification.user_streak_freezes SET available = available - $2, updated_at = NOW() WHERE user_id = $1",
+            )
+            .bind(user_id)
+            .bind(freezes_used)
+            .execute(&mut *tx)
+            .await?;
+
+            for covered_date in &plan.frozen_dates {
+                sqlx::query(
+                    r#"
+                    INSERT INTO gamification.streak_freeze_ledger (user_id, delta, reason, covered_date)
+                    VALUES ($1, -1, $2, $3)
+                    "#,
+                )
+                .bind(user_id)
+                .bind(FREEZE_REASON_CONSUMED)
+                .bind(covered_date)
+                .execute(&mut *tx)
+                .await?;
+
+                Self::record_history(&mut tx, user_id, "frozen", current_count, Some(current_count), *covered_date, 0)
+                    .await?;
+            }
+        }
+
+        if let (Some(broken), Some(broken_on)) = (plan.broken_count, plan.broken_on) {
+            Self::record_history(&mut tx, user_id, "broken", 0, Some(broken), broken_on, 0).await?;
+        }
+
+        let event_type = if plan.new_count == 1 { "started" } else { "extended" };
+        Self::record_history(&mut tx, user_id, event_type, plan.new_count, Some(current_count), today, 0).await?;
+
+        if plan.cycle_completed {
+            Self::grant_week_perfect(&mut tx, user_id, start_date).await?;
+            Self::record_history(&mut tx, user_id, "cycle_completed", plan.new_count, Some(current_count), today, 0)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        if freezes_used > 0 {
+            info!("🧊 User {} used {} streak freeze(s) to keep a {}-day streak", user_id, freezes_used, current_count);
+        }
+        if let Some(broken) = plan.broken_count {
+            info!("💔 User {} broke a {}-day streak (restorable for {}h)", user_id, broken, RESTORE_WINDOW_HOURS);
+        }
+
+        Ok(DailyLoginResult {
+            streak_count: plan.new_count,
+            already_counted: false,
+            freezes_used,
+            freezes_available: freezes_available - freezes_used,
+            broken_count: plan.broken_count,
+            cycle_completed: plan.cycle_completed,
+        })
+    }
+
+    /// Compra congeladores con Lümis
+    pub async fn purchase_freezes(&self, user_id: i32, quantity: i32) -> Result<FreezePurchase, StreakError> {
+        if !(1..=MAX_FREEZES_HELD).contains(&quantity) {
+            return Err(StreakError::InvalidQuantity(MAX_FREEZES_HELD));
+        }
+        let cost = freeze_price() * quantity;
+
+        let mut tx = self.db.begin().await?;
+
+        let available = Self::lock_freezes(&mut tx, user_id).await?;
+        if available + quantity > MAX_FREEZES_HELD {
+            return Err(StreakError::InventoryFull(MAX_FREEZES_HELD));
+        }
+        let balance = Self::lock_balance(&mut tx, user_id, cost as i64).await?;
+
+        let entry_id = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO gamification.streak_freeze_ledger (user_id, delta, reason, lumis_spent)
+            VALUES ($1, $2, $3, $4)
+            RETURNING entry_id
+            "#,
+        )
+        .bind(user_id)
+        .bind(quantity)
+        .bind(FREEZE_REASON_PURCHASE)
+        .bind(cost)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::record_spend(&mut tx, user_id, "streak_freeze", &format!("streak_freeze_{}", entry_id), cost as i64)
+            .await?;
+
+        sqlx::query(
+            "UPDATE gamification.user_streak_freezes SET available = available + $2, updated_at = NOW() WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .bind(quantity)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        info!("🧊 User {} bought {} streak freeze(s) for {} Lümis", user_id, quantity, cost);
+
+        Ok(FreezePurchase {
+            quantity,
+            lumis_spent: cost,
+            freezes_available: available + quantity,
+            new_balance: balance - cost as i64,
+        })
+    }
+
+    /// Otorga congeladores sin costo (misiones, soporte). Respeta el máximo y
+    /// devuelve cuántos se otorgaron realmente.
+    pub async fn grant_freezes(
+        &self,
+        user_id: i32,
+        quantity: i32,
+        reason: &str,
+        reference: Option<&str>,
+    ) -> Result<i32, StreakError> {
+        let mut tx = self.db.begin().await?;
+        let granted = Self::grant_freezes_in(&mut tx, user_id, quantity, reason, reference).await?;
+        tx.commit().await?;
+
+        if granted < quantity {
+            warn!("User {} only received {}/{} streak freezes (inventory cap)", user_id, granted, quantity);
+        }
+        info!("🧊 Granted {} streak freeze(s) to user {} ({})", granted, user_id, reason);
+
+        Ok(granted)
+    }
+
+    /// Igual que `grant_freezes` pero dentro de la transacción del llamador
+    /// (p. ej. la que completa una misión), sin confirmarla
+    pub async fn grant_freezes_in(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        quantity: i32,
+        reason: &str,
+        reference: Option<&str>,
+    ) -> Result<i32, StreakError> {
+        if quantity < 1 {
+            return Err(StreakError::InvalidQuantity(MAX_FREEZES_HELD));
+        }
+
+        let available = Self::lock_freezes(tx, user_id).await?;
+        let granted = quantity.min(MAX_FREEZES_HELD - available);
+        if granted <= 0 {
+            return Err(StreakError::InventoryFull(MAX_FREEZES_HELD));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO gamification.streak_freeze_ledger (user_id, delta, reason, reference)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(user_id)
+        .bind(granted)
+        .bind(reason)
+        .bind(reference)
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE gamification.user_streak_freezes SET available = available + $2, updated_at = NOW() WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .bind(granted)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(granted)
+    }
+
+    /// Recupera una racha rota pagando Lümis (dentro de 48h)
+    pub async fn restore_streak(&self, user_id: i32) -> Result<StreakRestore, StreakError> {
+        let mut tx = self.db.begin().await?;
+
+        let streak = Self::lock_streak(&mut tx, user_id).await?.ok_or(StreakError::NothingToRestore)?;
+        let (broken_count, broken_at) = match (streak.broken_count, streak.broken_at) {
+            (Some(count), Some(at)) if count > 0 => (count, at),
+            _ => return Err(StreakError::NothingToRestore),
+        };
+        if Utc::now() > restore_deadline(broken_at) {
+            return Err(StreakError::RestoreExpired);
+        }
+
+        let cost = restore_price();
+        let balance = Self::lock_balance(&mut tx, user_id, cost as i64).await?;
+
+        let new_count = restored_count(broken_count, streak.current_count);
+        let start_date = streak.today - Duration::days((new_count - 1) as i64);
+        let cycle_completed = new_count == STREAK_CYCLE_DAYS && streak.current_count < STREAK_CYCLE_DAYS;
+
+        Self::record_spend(
+            &mut tx,
+            user_id,
+            "streak_restore",
+            &format!("streak_restore_{}_{}", user_id, broken_at.timestamp()),
+            cost as i64,
+        )
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE gamification.user_streaks
+            SET current_count = $3,
+                max_count = GREATEST(COALESCE(max_count, 0), $3),
+                streak_start_date = $4,
+                broken_count = NULL,
+                broken_at = NULL,
+                updated_at = NOW()
+            WHERE user_id = $1 AND streak_type = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(DAILY_LOGIN_STREAK)
+        .bind(new_count)
+        .bind(start_date)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::record_history(&mut tx, user_id, "restored", new_count, Some(streak.current_count), streak.today, cost)
+            .await?;
+
+        if cycle_completed {
+            Self::grant_week_perfect(&mut tx, user_id, start_date).await?;
+            Self::record_history(&mut tx, user_id, "cycle_completed", new_count, Some(streak.current_count), streak.today, 0)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        info!("♻️ User {} restored a {}-day streak to {} for {} Lümis", user_id, broken_count, new_count, cost);
+
+        Ok(StreakRestore {
+            previous_count: streak.current_count,
+            streak_count: new_count,
+            lumis_spent: cost,
+            new_balance: balance - cost as i64,
+            cycle_completed,
+        })
+    }
+
+    pub async fn history(&self, user_id: i32, limit: i64) -> Result<Vec<StreakHistoryEntry>, StreakError> {
+        let entries = sqlx::query_as::<_, StreakHistoryEntry>(
+            r#"
+            SELECT event_type, streak_count, previous_count, event_date, lumis_spent, created_at
+            FROM gamification.streak_history
+            WHERE user_id = $1 AND streak_type = $2
+            ORDER BY created_at DESC, event_id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(DAILY_LOGIN_STREAK)
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(entries)
+    }
+
+    /// Estado de la racha, congeladores, oferta de recuperación e historial reciente
+    pub async fn overview(&self, user_id: i32, history_limit: i64) -> Result<StreakOverview, StreakError> {
+        let streak = sqlx::query_as::<_, StreakRow>(
+            r#"
+            SELECT COALESCE(current_count, 0) AS current_count,
+                   COALESCE(max_count, 0) AS max_count,
+                   COALESCE(freeze_count, 0) AS freeze_count,
+                   last_activity_date, streak_start_date,
+                   broken_count, broken_at,
+                   CURRENT_DATE AS today
+            FROM gamification.user_streaks
+            WHERE user_id = $1 AND streak_type = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(DAILY_LOGIN_STREAK)
+        .fetch_optional(&self.db)
+        .await?;
+
+        let freezes_available = sqlx::query_scalar::<_, i32>(
+            "SELECT available FROM gamification.user_streak_freezes WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?
+        .unwrap_or(0);
+
+        let history = self.history(user_id, history_limit).await?;
+
+        let restore = streak.as_ref().and_then(|row| match (row.broken_count, row.broken_at) {
+            (Some(count), Some(at)) if count > 0 && Utc::now() <= restore_deadline(at) => Some(RestoreOffer {
+                broken_count: count,
+                restored_count: restored_count(count, row.current_count),
+                cost_lumis: restore_price(),
+                expires_at: restore_deadline(at),
+            }),
+            _ => None,
+        });
+
+        Ok(StreakOverview {
+            current_count: streak.as_ref().map(|r| r.current_count).unwrap_or(0),
+            max_count: streak.as_ref().map(|r| r.max_count).unwrap_or(0),
+            cycle_days: STREAK_CYCLE_DAYS,
+            last_activity_date: streak.as_ref().and_then(|r| r.last_activity_date),
+            freezes_available,
+            freezes_used_total: streak.as_ref().map(|r| r.freeze_count).unwrap_or(0),
+            max_freezes: MAX_FREEZES_HELD,
+            freeze_price_lumis: freeze_price(),
+            restore,
+            history,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    #[test]
+    fn test_first_login_starts_streak() {
+        let plan = plan_daily_login(0, None, d(18), 0);
+        assert_eq!(plan.new_count, 1);
+        assert!(!plan.already_counted);
+        assert!(plan.broken_count.is_none());
+    }
+
+    #[test]
+    fn test_same_day_is_idempotent() {
+        let plan = plan_daily_login(3, Some(d(18)), d(18), 2);
+        assert!(plan.already_counted);
+        assert_eq!(plan.new_count, 3);
+        assert!(plan.frozen_dates.is_empty());
+    }
+
+    #[test]
+    fn test_consecutive_day_extends() {
+        let plan = plan_daily_login(3, Some(d(17)), d(18), 0);
+        assert_eq!(plan.new_count, 4);
+        assert!(!plan.cycle_completed);
+    }
+
+    #[test]
+    fn test_day_seven_completes_cycle_and_day_eight_restarts() {
+        let plan = plan_daily_login(6, Some(d(17)), d(18), 0);
+        assert_eq!(plan.new_count, 7);
+        assert!(plan.cycle_completed);
+
+        let next = plan_daily_login(7, Some(d(18)), d(19), 0);
+        assert_eq!(next.new_count, 1);
+        assert!(next.broken_count.is_none());
+    }
+
+    #[test]
+    fn test_freezes_cover_missed_days() {
+        let plan = plan_daily_login(4, Some(d(15)), d(18), 2);
+        assert_eq!(plan.frozen_dates, vec![d(16), d(17)]);
+        assert_eq!(plan.new_count, 5);
+        assert!(plan.broken_count.is_none());
+    }
+
+    #[test]
+    fn test_one_missed_day_without_freezes_is_restorable() {
+        let plan = plan_daily_login(4, Some(d(16)), d(18), 0);
+        assert!(plan.frozen_dates.is_empty());
+        assert_eq!(plan.new_count, 1);
+        assert_eq!(plan.broken_count, Some(4));
+        assert_eq!(plan.broken_on, Some(d(17)));
+    }
+
+    #[test]
+    fn test_not_enough_freezes_breaks_without_consuming() {
+        let plan = plan_daily_login(4, Some(d(14)), d(18), 2);
+        assert!(plan.frozen_dates.is_empty());
+        assert_eq!(plan.new_count, 1);
+        // 3 días sin login: la ventana de 48h ya venció al llegar hoy
+        assert!(plan.broken_count.is_none());
+    }
+
+    #[test]
+    fn test_gap_longer_than_limit_breaks() {
+        let plan = plan_daily_login(4, Some(d(10)), d(18), MAX_FREEZES_HELD);
+        assert!(plan.frozen_dates.is_empty());
+        assert_eq!(plan.new_count, 1);
+        assert!(plan.broken_count.is_none());
+    }
+
+    #[test]
+    fn test_long_gap_break_is_not_restorable() {
+        let plan = plan_daily_login(5, Some(d(1)), d(18), 0);
+        assert_eq!(plan.new_count, 1);
+        assert!(plan.broken_count.is_none());
+        assert!(plan.broken_on.is_none());
+    }
+
+    #[test]
+    fn test_completed_cycle_gap_is_not_a_break() {
+        let plan = plan_daily_login(7, Some(d(15)), d(18), 3);
+        assert!(plan.frozen_dates.is_empty());
+        assert!(plan.broken_count.is_none());
+        assert_eq!(plan.new_count, 1);
+    }
+
+    #[test]
+    fn test_restored_count_caps_at_cycle() {
+        assert_eq!(restored_count(4, 1), 5);
+        assert_eq!(restored_count(6, 2), STREAK_CYCLE_DAYS);
+    }
+
+    #[test]
+    fn test_restore_deadline_is_48h() {
+        let broken_at = DateTime::parse_from_rfc3339("2026-10-18T05:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(restore_deadline(broken_at), broken_at + Duration::hours(48));
+    }
+}
@@ -0,0 +1,879 @@
+//! Trivias diarias con Lümis
+//!
+//! Cada día (fecha de Panamá) hay un set de preguntas: 2 fáciles, 2 medias y
+//! 1 difícil, elegidas del banco evitando las usadas en los últimos 30 días, o
+//! fijadas por un admin. Las preguntas se sirven de una en una:
+//!
+//! - Al servir una pregunta se guarda `served_at` y `deadline_at` (tiempo
+//!   límite de la pregunta + gracia del canal). La respuesta se mide contra el
+//!   reloj del servidor; pasada la ventana cuenta como incorrecta.
+//! - Un solo intento por pregunta (PK en `trivia.attempts`), con el tiempo de
+//!   respuesta registrado. Las respuestas más rápidas que `MIN_HUMAN_RESPONSE_MS`
+//!   se marcan `flagged` y no dan Lümis.
+//! - 5 Lümis por respuesta correcta, más bono al llegar a 3 y 5 correctas
+//!   seguidas dentro del set.
+//!
+//! La respuesta correcta nunca sale del servidor antes de responder.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::America::Panama;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool};
+use tracing::{info, warn};
+
+use crate::services::event_bus_service::{DomainEvent, EventBus};
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+pub const LUMIS_PER_CORRECT: i32 = 5;
+/// (racha alcanzada, bono)
+pub const STREAK_BONUSES: &[(i32, i32)] = &[(3, 3), (5, 5)];
+/// Composición del set diario, en orden de juego
+pub const DAILY_MIX: &[(&str, i64)] = &[("easy", 2), ("medium", 2), ("hard", 1)];
+/// Días sin repetir una pregunta en los sets automáticos
+pub const REPEAT_AFTER_DAYS: i64 = 30;
+/// Por debajo de esto no es una respuesta humana
+pub const MIN_HUMAN_RESPONSE_MS: i64 = 800;
+
+pub const DIFFICULTIES: &[&str] = &["easy", "medium", "hard"];
+
+pub const CHANNEL_API: &str = "api";
+pub const CHANNEL_WHATSAPP: &str = "whatsapp";
+
+/// Regla genérica en `rewards.dim_accumulations` para las trivias
+pub const TRIVIA_ACCUM_ID: i32 = 33;
+pub const TRIVIA_ACCUM_TYPE: &str = "trivia";
+
+/// Segundos extra sobre el tiempo límite: latencia de red en la app y
+/// entrega + lectura del mensaje en WhatsApp
+pub fn channel_grace_seconds(channel: &str) -> i64 {
+    match channel {
+        CHANNEL_WHATSAPP => 40,
+        _ => 2,
+    }
+}
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TriviaQuestion {
+    pub question_id: i32,
+    pub question_text: String,
+    pub options: Json<Vec<String>>,
+    #[serde(skip_serializing)]
+    pub correct_option: i16,
+    pub explanation: Option<String>,
+    pub category: String,
+    pub difficulty: String,
+    pub time_limit_seconds: i32,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewTriviaQuestion {
+    pub question_text: String,
+    pub options: Vec<String>,
+    pub correct_option: i16,
+    pub explanation: Option<String>,
+    pub category: Option<String>,
+    pub difficulty: String,
+    pub time_limit_seconds: Option<i32>,
+}
+
+/// Pregunta servida al jugador (sin la respuesta)
+#[derive(Debug, Clone, Serialize)]
+pub struct ServedQuestion {
+    pub set_date: NaiveDate,
+    pub question_id: i32,
+    pub position: i32,
+    pub total_questions: i32,
+    pub question_text: String,
+    pub options: Vec<String>,
+    pub category: String,
+    pub difficulty: String,
+    pub time_limit_seconds: i32,
+    pub served_at: DateTime<Utc>,
+    pub deadline_at: DateTime<Utc>,
+    pub remaining_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnswerResult {
+    pub question_id: i32,
+    pub correct: bool,
+    pub correct_option: i16,
+    pub explanation: Option<String>,
+    pub timed_out: bool,
+    pub flagged: bool,
+    pub response_ms: i64,
+    pub streak: i32,
+    pub lumis_awarded: i32,
+    pub streak_bonus: i32,
+    pub day: TriviaDay,
+}
+
+/// Progreso del usuario en el set del día
+#[derive(Debug, Clone, Default, Serialize, FromRow)]
+pub struct TriviaDay {
+    pub set_date: NaiveDate,
+    pub total_questions: i32,
+    pub answered: i32,
+    pub correct: i32,
+    pub lumis_earned: i32,
+    pub current_streak: i32,
+    pub completed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TriviaQuestionStats {
+    pub set_date: NaiveDate,
+    pub question_id: i32,
+    pub question_text: String,
+    pub difficulty: String,
+    pub served: i64,
+    pub answered: i64,
+    pub correct: i64,
+    pub timed_out: i64,
+    pub flagged: i64,
+    pub avg_response_ms: Option<f64>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TriviaError {
+    #[error("{}", Text::new("api.trivia.invalid").with("reason", .0))]
+    Invalid(Text),
+
+    #[error("{}", Text::new("api.trivia.not_enough_questions"))]
+    NotEnoughQuestions,
+
+    #[error("{}", Text::new("api.trivia.not_served"))]
+    NotServed,
+
+    #[error("{}", Text::new("api.trivia.already_answered"))]
+    AlreadyAnswered,
+
+    #[error("{}", Text::new("api.trivia.invalid_option"))]
+    InvalidOption,
+
+    #[error("{}", Text::new("api.trivia.set_in_use").with("date", .0))]
+    SetInUse(NaiveDate),
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for TriviaError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+/// Fecha de juego (Panamá)
+pub fn trivia_date(now: DateTime<Utc>) -> NaiveDate {
+    now.with_timezone(&Panama).date_naive()
+}
+
+pub fn answer_deadline(served_at: DateTime<Utc>, time_limit_seconds: i32, channel: &str) -> DateTime<Utc> {
+    served_at + Duration::seconds(time_limit_seconds as i64 + channel_grace_seconds(channel))
+}
+
+/// Plazo que se aplica a una respuesta: la gracia es la menor entre el canal
+/// donde se sirvió y el canal donde se responde, así pedir la pregunta por
+/// WhatsApp y contestarla por la API no gana los 40 s de WhatsApp
+pub fn effective_deadline(served_deadline: DateTime<Utc>, served_channel: &str, answer_channel: &str) -> DateTime<Utc> {
+    let served_grace = channel_grace_seconds(served_channel);
+    let grace = served_grace.min(channel_grace_seconds(answer_channel));
+    served_deadline - Duration::seconds(served_grace - grace)
+}
+
+/// Bono por alcanzar exactamente esta racha
+pub fn streak_bonus(streak: i32) -> i32 {
+    STREAK_BONUSES
+        .iter()
+        .find(|(at, _)| *at == streak)
+        .map(|(_, bonus)| *bonus)
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnswerScore {
+    pub correct: bool,
+    pub flagged: bool,
+    pub streak: i32,
+    pub base_lumis: i32,
+    pub bonus_lumis: i32,
+}
+
+impl AnswerScore {
+    pub fn total(&self) -> i32 {
+        self.base_lumis + self.bonus_lumis
+    }
+}
+
+/// Puntaje de una respuesta. Fuera de tiempo cuenta como incorrecta; una
+/// respuesta marcada no da Lümis ni suma a la racha.
+pub fn score_answer(correct: bool, timed_out: bool, response_ms: i64, streak_before: i32) -> AnswerScore {
+    let correct = correct && !timed_out;
+    let flagged = !timed_out && response_ms < MIN_HUMAN_RESPONSE_MS;
+    if !correct || flagged {
+        return AnswerScore { correct, flagged, streak: 0, base_lumis: 0, bonus_lumis: 0 };
+    }
+    let streak = streak_before + 1;
+    AnswerScore { correct, flagged, streak, base_lumis: LUMIS_PER_CORRECT, bonus_lumis: streak_bonus(streak) }
+}
+
+/// Letra de la opción en WhatsApp (los títulos de fila admiten solo 24 caracteres)
+pub fn option_letter(index: usize) -> char {
+    (b'A' + index as u8) as char
+}
+
+/// Id de fila de la lista interactiva: `trivia:<question_id>:<opción>`
+pub fn whatsapp_row_id(question_id: i32, option: usize) -> String {
+    format!("trivia:{}:{}", question_id, option)
+}
+
+pub fn parse_whatsapp_row_id(id: &str) -> Option<(i32, i16)> {
+    let mut parts = id.strip_prefix("trivia:")?.split(':');
+    let question_id = parts.next()?.parse().ok()?;
+    let option = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((question_id, option))
+}
+
+pub fn validate_question(question: &NewTriviaQuestion) -> Result<(), TriviaError> {
+    let invalid = |text: Text| Err(TriviaError::Invalid(text));
+
+    if question.question_text.trim().is_empty() || question.question_text.len() > 500 {
+        return invalid(
+            Text::new("api.validation.length").with("field", "question_text").with("min", 1).with("max", 500),
+        );
+    }
+    if !(2..=4).contains(&question.options.len()) {
+        return invalid(Text::new("api.trivia.option_count"));
+    }
+    if question.options.iter().any(|o| o.trim().is_empty() || o.chars().count() > 72) {
+        return invalid(Text::new("api.validation.length").with("field", "options").with("min", 1).with("max", 72));
+    }
+    if question.correct_option < 0 || question.correct_option as usize >= question.options.len() {
+        return invalid(Text::new("api.validation.out_of_range").with("field", "correct_option"));
+    }
+    if !DIFFICULTIES.contains(&question.difficulty.as_str()) {
+        return invalid(
+            Text::new("api.validation.one_of").with("field", "difficulty").with("values", "easy, medium, hard"),
+        );
+    }
+    if let Some(limit) = question.time_limit_seconds {
+        if !(5..=120).contains(&limit) {
+            return invalid(
+                Text::new("api.validation.range").with("field", "time_limit_seconds").with("min", 5).with("max", 120),
+            );
+        }
+    }
+    Ok(())
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+const QUESTION_COLUMNS: &str = r#"
+    question_id, question_text, options, correct_option, explanation, category,
+    difficulty, time_limit_seconds, is_active
+"#;
+
+/// Columnas de `AttemptRow`: todo SELECT que la llene usa esta lista
+const ATTEMPT_COLUMNS: &str = r#"
+    set_date, question_id, position, channel, served_at, deadline_at, answered_at, timed_out
+"#;
+
+#[derive(Debug, FromRow)]
+struct AttemptRow {
+    set_date: NaiveDate,
+    question_id: i32,
+    position: i16,
+    channel: String,
+    served_at: DateTime<Utc>,
+    deadline_at: DateTime<Utc>,
+    answered_at: Option<DateTime<Utc>>,
+    timed_out: bool,
+}
+
+pub struct TriviaService {
+    db: PgPool,
+}
+
+impl TriviaService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn create_question(&self, question: NewTriviaQuestion, created_by: i64) -> Result<TriviaQuestion, TriviaError> {
+        validate_question(&question)?;
+        let options: Vec<String> = question.options.iter().map(|o| o.trim().to_string()).collect();
+
+        Ok(sqlx::query_as::<_, TriviaQuestion>(&format!(
+            r#"
+            INSERT INTO trivia.questions (
+                question_text, options, correct_option, explanation, category,
+                difficulty, time_limit_seconds, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING {}
+            "#,
+            QUESTION_COLUMNS
+        ))
+        .bind(question.question_text.trim())
+        .bind(Json(&options))
+        .bind(question.correct_option)
+        .bind(&question.explanation)
+        .bind(question.category.as_deref().unwrap_or("general"))
+        .bind(&question.difficulty)
+        .bind(question.time_limit_seconds.unwrap_or(20))
+        .bind(created_by)
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    pub async fn set_question_active(&self, question_id: i32, active: bool) -> Result<bool, TriviaError> {
+        let result = sqlx::query("UPDATE trivia.questions SET is_active = $2 WHERE question_id = $1")
+            .bind(question_id)
+            .bind(active)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Fija el set de una fecha (solo si nadie lo ha jugado todavía)
+    pub async fn pin_daily_set(&self, date: NaiveDate, question_ids: &[i32], created_by: i64) -> Result<(), TriviaError> {
+        if question_ids.is_empty() || question_ids.len() > 10 {
+            return Err(TriviaError::Invalid(Text::new("api.trivia.set_size")));
+        }
+        let mut tx = self.db.begin().await?;
+        let played: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM trivia.attempts WHERE set_date = $1)")
+            .bind(date)
+            .fetch_one(&mut *tx)
+            .await?;
+        if played {
+            return Err(TriviaError::SetInUse(date));
+        }
+        let found: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM trivia.questions WHERE question_id = ANY($1) AND is_active",
+        )
+        .bind(question_ids)
+        .fetch_one(&mut *tx)
+        .await?;
+        if found != question_ids.len() as i64 {
+            return Err(TriviaError::Invalid(Text::new("api.trivia.set_questions")));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO trivia.daily_sets (set_date, question_ids, created_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (set_date) DO UPDATE
+            SET question_ids = EXCLUDED.question_ids, created_by = EXCLUDED.created_by, created_at = NOW()
+            "#,
+        )
+        .bind(date)
+        .bind(question_ids)
+        .bind(created_by)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Set del día; si no existe se arma con `DAILY_MIX`
+    async fn daily_set(&self, date: NaiveDate) -> Result<Vec<i32>, TriviaError> {
+        if let Some(ids) = sqlx::query_scalar::<_, Vec<i32>>("SELECT question_ids FROM trivia.daily_sets WHERE set_date = $1")
+            .bind(date)
+            .fetch_optional(&self.db)
+            .await?
+        {
+            return Ok(ids);
+        }
+
+        let mut ids = Vec::new();
+        for (difficulty, count) in DAILY_MIX {
+            // Orden estable por fecha: primero las no usadas recientemente
+            let picked: Vec<i32> = sqlx::query_scalar(
+                r#"
+                SELECT q.question_id
+                FROM trivia.questions q
+                WHERE q.is_active AND q.difficulty = $1
+                ORDER BY EXISTS (
+                    SELECT 1 FROM trivia.daily_sets ds
+                    WHERE q.question_id = ANY(ds.question_ids)
+                      AND ds.set_date >= $2 - $3::INTEGER
+                      AND ds.set_date < $2
+                ), md5(q.question_id::TEXT || $2::TEXT)
+                LIMIT $4
+                "#,
+            )
+            .bind(difficulty)
+            .bind(date)
+            .bind(REPEAT_AFTER_DAYS as i32)
+            .bind(count)
+            .fetch_all(&self.db)
+            .await?;
+            ids.extend(picked);
+        }
+        if ids.is_empty() {
+            return Err(TriviaError::NotEnoughQuestions);
+        }
+
+        // Otra instancia pudo crearlo al mismo tiempo: gana el primero
+        sqlx::query("INSERT INTO trivia.daily_sets (set_date, question_ids) VALUES ($1, $2) ON CONFLICT (set_date) DO NOTHING")
+            .bind(date)
+            .bind(&ids)
+            .execute(&self.db)
+            .await?;
+        Ok(sqlx::query_scalar("SELECT question_ids FROM trivia.daily_sets WHERE set_date = $1")
+            .bind(date)
+            .fetch_one(&self.db)
+            .await?)
+    }
+
+    async fn question(&self, question_id: i32) -> Result<TriviaQuestion, TriviaError> {
+        Ok(sqlx::query_as::<_, TriviaQuestion>(&format!(
+            "SELECT {} FROM trivia.questions WHERE question_id = $1",
+            QUESTION_COLUMNS
+        ))
+        .bind(question_id)
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    /// Progreso del día
+    pub async fn today(&self, user_id: i32) -> Result<TriviaDay, TriviaError> {
+        let date = trivia_date(Utc::now());
+        let total = self.daily_set(date).await?.len() as i32;
+        self.day_summary(user_id, date, total).await
+    }
+
+    async fn day_summary(&self, user_id: i32, date: NaiveDate, total: i32) -> Result<TriviaDay, TriviaError> {
+        let (answered, correct, lumis, streak): (i64, i64, i64, Option<i32>) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE answered_at IS NOT NULL OR timed_out),
+                   COUNT(*) FILTER (WHERE is_correct),
+                   COALESCE(SUM(lumis_awarded), 0)::BIGINT,
+                   (SELECT streak FROM trivia.attempts
+                    WHERE user_id = $1 AND set_date = $2 AND (answered_at IS NOT NULL OR timed_out)
+                    ORDER BY position DESC LIMIT 1)
+            FROM trivia.attempts
+            WHERE user_id = $1 AND set_date = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(TriviaDay {
+            set_date: date,
+            total_questions: total,
+            answered: answered as i32,
+            correct: correct as i32,
+            lumis_earned: lumis as i32,
+            current_streak: streak.unwrap_or(0),
+            completed: answered as i32 >= total,
+        })
+    }
+
+    /// Cierra como incorrectas las preguntas servidas cuyo plazo venció
+    async fn expire_overdue(&self, user_id: i32, date: NaiveDate) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE trivia.attempts
+            SET timed_out = TRUE, is_correct = FALSE, streak = 0
+            WHERE user_id = $1 AND set_date = $2
+              AND answered_at IS NULL AND NOT timed_out
+              AND deadline_at < NOW()
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Pregunta en curso o la siguiente del set. `None` si ya terminó el día.
+    pub async fn next_question(&self, user_id: i32, channel: &str) -> Result<Option<ServedQuestion>, TriviaError> {
+        let date = trivia_date(Utc::now());
+        let set = self.daily_set(date).await?;
+        self.expire_overdue(user_id, date).await?;
+
+        let attempts = sqlx::query_as::<_, AttemptRow>(&format!(
+            r#"
+            SELECT {}
+            FROM trivia.attempts
+            WHERE user_id = $1 AND set_date = $2
+            "#,
+            ATTEMPT_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(date)
+        .fetch_all(&self.db)
+        .await?;
+        // La pregunta en curso se vuelve a entregar con el mismo plazo
+        if let Some(open) = attempts.iter().find(|a| a.answered_at.is_none() && !a.timed_out) {
+            let question = self.question(open.question_id).await?;
+            return Ok(Some(Self::served(&question, open.set_date, open.position as i32, set.len(), open.served_at, open.deadline_at)));
+        }
+
+        let done: Vec<i32> = attempts.iter().map(|a| a.question_id).collect();
+        let Some((index, question_id)) = set.iter().enumerate().find(|(_, id)| !done.contains(id)) else {
+            return Ok(None);
+        };
+
+        let question = self.question(*question_id).await?;
+        let served_at = Utc::now();
+        let deadline_at = answer_deadline(served_at, question.time_limit_seconds, channel);
+        let position = index as i32 + 1;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO trivia.attempts (user_id, set_date, question_id, position, channel, served_at, deadline_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (user_id, set_date, question_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .bind(question.question_id)
+        .bind(position as i16)
+        .bind(channel)
+        .bind(served_at)
+        .bind(deadline_at)
+        .execute(&self.db)
+        .await?;
+        if inserted.rows_affected() == 0 {
+            // Petición concurrente: entregar la que quedó guardada
+            let (served_at, deadline_at): (DateTime<Utc>, DateTime<Utc>) = sqlx::query_as(
+                "SELECT served_at, deadline_at FROM trivia.attempts WHERE user_id = $1 AND set_date = $2 AND question_id = $3",
+            )
+            .bind(user_id)
+            .bind(date)
+            .bind(question.question_id)
+            .fetch_one(&self.db)
+            .await?;
+            return Ok(Some(Self::served(&question, date, position, set.len(), served_at, deadline_at)));
+        }
+
+        Ok(Some(Self::served(&question, date, position, set.len(), served_at, deadline_at)))
+    }
+
+    fn served(
+        question: &TriviaQuestion,
+        set_date: NaiveDate,
+        position: i32,
+        total: usize,
+        served_at: DateTime<Utc>,
+        deadline_at: DateTime<Utc>,
+    ) -> ServedQuestion {
+        ServedQuestion {
+            set_date,
+            question_id: question.question_id,
+            position,
+            total_questions: total as i32,
+            question_text: question.question_text.clone(),
+            options: question.options.0.clone(),
+            category: question.category.clone(),
+            difficulty: question.difficulty.clone(),
+            time_limit_seconds: question.time_limit_seconds,
+            served_at,
+            deadline_at,
+            remaining_ms: (deadline_at - Utc::now()).num_milliseconds().max(0),
+        }
+    }
+
+    /// Registra la respuesta (una sola vez) y acredita los Lümis. `channel` es
+    /// el canal por donde llega la respuesta (ver `effective_deadline`).
+    pub async fn answer(
+        &self,
+        user_id: i32,
+        question_id: i32,
+        selected_option: i16,
+        channel: &str,
+    ) -> Result<AnswerResult, TriviaError> {
+        let answered_at = Utc::now();
+        let mut tx = self.db.begin().await?;
+
+        // El intento más reciente de esa pregunta (puede venir del set de ayer si se sirvió antes de medianoche)
+        let attempt = sqlx::query_as::<_, AttemptRow>(&format!(
+            r#"
+            SELECT {}
+            FROM trivia.attempts
+            WHERE user_id = $1 AND question_id = $2 AND set_date >= $3
+            ORDER BY set_date DESC
+            LIMIT 1
+            FOR UPDATE
+            "#,
+            ATTEMPT_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(question_id)
+        .bind(trivia_date(answered_at) - Duration::days(1))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(TriviaError::NotServed)?;
+
+        if attempt.answered_at.is_some() || attempt.timed_out {
+            return Err(TriviaError::AlreadyAnswered);
+        }
+
+        let question = self.question(question_id).await?;
+        if selected_option < 0 || selected_option as usize >= question.options.0.len() {
+            return Err(TriviaError::InvalidOption);
+        }
+
+        let streak_before: i32 = sqlx::query_scalar(
+            r#"
+            SELECT COALESCE((
+                SELECT streak FROM trivia.attempts
+                WHERE user_id = $1 AND set_date = $2 AND position < $3
+                  AND (answered_at IS NOT NULL OR timed_out)
+                ORDER BY position DESC LIMIT 1
+            ), 0)
+            "#,
+        )
+        .bind(user_id)
+        .bind(attempt.set_date)
+        .bind(attempt.position)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let timed_out = answered_at > effective_deadline(attempt.deadline_at, &attempt.channel, channel);
+        let response_ms = (answered_at - attempt.served_at).num_milliseconds();
+        let score = score_answer(selected_option == question.correct_option, timed_out, response_ms, streak_before);
+
+        sqlx::query(
+            r#"
+            UPDATE trivia.attempts
+            SET answered_at = $4, selected_option = $5, is_correct = $6, response_ms = $7,
+                timed_out = $8, flagged = $9, streak = $10, lumis_awarded = $11
+            WHERE user_id = $1 AND set_date = $2 AND question_id = $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(attempt.set_date)
+        .bind(question_id)
+        .bind(answered_at)
+        .bind(selected_option)
+        .bind(score.correct)
+        .bind(response_ms.min(i32::MAX as i64) as i32)
+        .bind(timed_out)
+        .bind(score.flagged)
+        .bind(score.streak)
+        .bind(score.total())
+        .execute(&mut *tx)
+        .await?;
+
+        if score.total() > 0 {
+            sqlx::query(
+                r#"
+                INSERT INTO rewards.fact_accumulations
+                (user_id, accum_type, accum_key, dtype, quantity, date, accum_id)
+                VALUES ($1, $2, $3, 'points', $4, $5, $6)
+                "#,
+            )
+            .bind(user_id)
+            .bind(TRIVIA_ACCUM_TYPE)
+            .bind(format!("trivia:{}:{}", attempt.set_date, question_id))
+            .bind(score.total())
+            .bind(answered_at.naive_utc())
+            .bind(TRIVIA_ACCUM_ID)
+            .execute(&mut *tx)
+            .await?;
+
+            let credited = DomainEvent::LumisCredited {
+                user_id: user_id as i64,
+                lumis: score.total(),
+                source: "trivia".to_string(),
+                reference: Some(format!("{}:{}", attempt.set_date, question_id)),
+            };
+            EventBus::publish(&mut *tx, &credited)
+                .await
+                .map_err(|e| TriviaError::Database(e.to_string()))?;
+        }
+        tx.commit().await?;
+
+        if score.flagged {
+            warn!(
+                "🚩 Trivia answer flagged: user {} question {} answered in {}ms",
+                user_id, question_id, response_ms
+            );
+        }
+        info!(
+            "🧠 Trivia {} q{}: user {} correct={} streak={} +{} Lümis",
+            attempt.set_date, question_id, user_id, score.correct, score.streak, score.total()
+        );
+
+        let total = self.daily_set(attempt.set_date).await?.len() as i32;
+        let day = self.day_summary(user_id, attempt.set_date, total).await?;
+        Ok(AnswerResult {
+            question_id,
+            correct: score.correct,
+            correct_option: question.correct_option,
+            explanation: question.explanation,
+            timed_out,
+            flagged: score.flagged,
+            response_ms,
+            streak: score.streak,
+            lumis_awarded: score.total(),
+            streak_bonus: score.bonus_lumis,
+            day,
+        })
+    }
+
+    /// Estadísticas por pregunta de los últimos `days` días (admin)
+    pub async fn stats(&self, days: i32) -> Result<Vec<TriviaQuestionStats>, TriviaError> {
+        Ok(sqlx::query_as::<_, TriviaQuestionStats>(
+            r#"
+            SELECT a.set_date, a.question_id, q.question_text, q.difficulty,
+                   COUNT(*) AS served,
+                   COUNT(*) FILTER (WHERE a.answered_at IS NOT NULL) AS answered,
+                   COUNT(*) FILTER (WHERE a.is_correct) AS correct,
+                   COUNT(*) FILTER (WHERE a.timed_out) AS timed_out,
+                   COUNT(*) FILTER (WHERE a.flagged) AS flagged,
+                   AVG(a.response_ms)::FLOAT8 AS avg_response_ms
+            FROM trivia.attempts a
+            JOIN trivia.questions q ON q.question_id = a.question_id
+            WHERE a.set_date >= CURRENT_DATE - $1::INTEGER
+            GROUP BY a.set_date, a.question_id, q.question_text, q.difficulty
+            ORDER BY a.set_date DESC, MIN(a.position)
+            "#,
+        )
+        .bind(days)
+        .fetch_all(&self.db)
+        .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn new_question() -> NewTriviaQuestion {
+        NewTriviaQuestion {
+            question_text: "¿En qué año se inauguró el Canal de Panamá?".into(),
+            options: vec!["1903".into(), "1914".into(), "1999".into()],
+            correct_option: 1,
+            explanation: None,
+            category: Some("historia".into()),
+            difficulty: "easy".into(),
+            time_limit_seconds: Some(20),
+        }
+    }
+
+    #[test]
+    fn test_trivia_date_uses_panama() {
+        // 03:00 UTC = 22:00 del día anterior en Panamá
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 3, 0, 0).unwrap();
+        assert_eq!(trivia_date(now), NaiveDate::from_ymd_opt(2026, 10, 17).unwrap());
+    }
+
+    #[test]
+    fn test_deadline_includes_channel_grace() {
+        let served = Utc.with_ymd_and_hms(2026, 10, 18, 15, 0, 0).unwrap();
+        assert_eq!(answer_deadline(served, 20, CHANNEL_API), served + Duration::seconds(22));
+        assert_eq!(answer_deadline(served, 20, CHANNEL_WHATSAPP), served + Duration::seconds(60));
+    }
+
+    #[test]
+    fn test_cross_channel_answer_gets_smaller_grace() {
+        let served = Utc::now();
+        let whatsapp_deadline = answer_deadline(served, 20, CHANNEL_WHATSAPP);
+        let api_deadline = answer_deadline(served, 20, CHANNEL_API);
+
+        assert_eq!(effective_deadline(whatsapp_deadline, CHANNEL_WHATSAPP, CHANNEL_WHATSAPP), whatsapp_deadline);
+        assert_eq!(effective_deadline(api_deadline, CHANNEL_API, CHANNEL_API), api_deadline);
+        // Servida por WhatsApp, respondida por la API: solo la gracia de la app
+        assert_eq!(effective_deadline(whatsapp_deadline, CHANNEL_WHATSAPP, CHANNEL_API), api_deadline);
+        assert_eq!(effective_deadline(api_deadline, CHANNEL_API, CHANNEL_WHATSAPP), api_deadline);
+    }
+
+    #[test]
+    fn test_score_correct_with_streak_bonus() {
+        let first = score_answer(true, false, 4_000, 0);
+        assert_eq!((first.streak, first.total()), (1, 5));
+
+        let third = score_answer(true, false, 4_000, 2);
+        assert_eq!((third.streak, third.base_lumis, third.bonus_lumis), (3, 5, 3));
+
+        let fifth = score_answer(true, false, 4_000, 4);
+        assert_eq!(fifth.total(), 10);
+        assert_eq!(score_answer(true, false, 4_000, 3).bonus_lumis, 0);
+    }
+
+    #[test]
+    fn test_wrong_or_late_answer_resets_streak() {
+        let wrong = score_answer(false, false, 4_000, 2);
+        assert_eq!((wrong.correct, wrong.streak, wrong.total()), (false, 0, 0));
+
+        let late = score_answer(true, true, 90_000, 2);
+        assert!(!late.correct);
+        assert!(!late.flagged);
+        assert_eq!(late.total(), 0);
+    }
+
+    #[test]
+    fn test_too_fast_answer_is_flagged() {
+        let bot = score_answer(true, false, 150, 1);
+        assert!(bot.flagged);
+        assert!(bot.correct);
+        assert_eq!((bot.streak, bot.total()), (0, 0));
+    }
+
+    #[test]
+    fn test_validate_question() {
+        assert!(validate_question(&new_question()).is_ok());
+
+        let mut q = new_question();
+        q.correct_option = 3;
+        assert!(validate_question(&q).is_err());
+
+        let mut q = new_question();
+        q.options = vec!["Solo una".into()];
+        assert!(validate_question(&q).is_err());
+
+        let mut q = new_question();
+        q.difficulty = "extreme".into();
+        assert!(validate_question(&q).is_err());
+
+        let mut q = new_question();
+        q.time_limit_seconds = Some(300);
+        assert!(validate_question(&q).is_err());
+    }
+
+    #[test]
+    fn test_whatsapp_row_id_roundtrip() {
+        let id = whatsapp_row_id(42, 3);
+        assert_eq!(id, "trivia:42:3");
+        assert_eq!(parse_whatsapp_row_id(&id), Some((42, 3)));
+        assert_eq!(option_letter(2), 'C');
+        assert_eq!(parse_whatsapp_row_id("red_tombola_cash"), None);
+        assert_eq!(parse_whatsapp_row_id("trivia:42"), None);
+        assert_eq!(parse_whatsapp_row_id("trivia:42:1:9"), None);
+    }
+
+    #[test]
+    fn test_daily_mix_size() {
+        assert_eq!(DAILY_MIX.iter().map(|(_, n)| n).sum::<i64>(), 5);
+        assert_eq!(streak_bonus(5), 5);
+    }
+}
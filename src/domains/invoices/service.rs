@@ -1,6 +1,7 @@
 use crate::{
     models::invoice::{InvoiceHeader, InvoiceDetail, InvoicePayment, MefPending},
     processing::web_scraping::{data_parser, http_client, ocr_extractor},
+    services::event_bus_service::{DomainEvent, EventBus},
     shared::database as db_service,
     shared::whatsapp as whatsapp_service,
     AppState,
@@ -48,6 +49,12 @@ pub async fn process_invoice_url(
             match db_service::save_invoice_data(&mut tx, &header, &details, &payments).await {
                 Ok(()) => {
                     // PASO 4A: Guardado exitoso en tablas principales
+                    let saved = DomainEvent::InvoiceSaved {
+                        user_id,
+                        cufe: header.cufe.clone(),
+                        source: header.origin.clone(),
+                    };
+                    EventBus::publish(&mut *tx, &saved).await?;
                     tx.commit().await.context("Failed to commit transaction")?;
                     
                     let success_message = format!(
@@ -0,0 +1,8 @@
+pub mod service;
+pub mod tagging;
+pub mod targeting;
+
+// Re-exports para facilitar imports
+pub use service::{AudiencePreview, LumiMatchError, LumiMatchService, NewQuestion, QuestionUpdate};
+pub use targeting::{CompiledTargeting, TargetingContext, TargetingRules};
+pub use tagging::{TagService, TaggingError};
@@ -0,0 +1,403 @@
+//! Administración de preguntas de LumiMatch y preview de audiencia
+//!
+//! Toda pregunta que se crea o edita pasa por `TargetingRules::validate`;
+//! lo que queda guardado en `targeting_rules` es la forma normalizada del
+//! esquema tipado. El preview evalúa el mismo `CompiledTargeting` que usa el
+//! feed contra los usuarios y sus tags vigentes.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use super::tagging::MIN_TARGETING_CONFIDENCE;
+use super::targeting::{age_from_birth, CompiledTargeting, TargetingContext, TargetingRules};
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+pub const DEFAULT_PREVIEW_SAMPLE: usize = 20;
+pub const MAX_PREVIEW_SAMPLE: usize = 100;
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewOption {
+    pub label: Option<String>,
+    pub image_url: Option<String>,
+    pub icon_url: Option<String>,
+    /// Tags que recibe quien elige la opción (ver `tagging`)
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewQuestion {
+    pub title: String,
+    pub image_url: Option<String>,
+    pub priority: Option<i32>,
+    pub is_active: Option<bool>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_to: Option<DateTime<Utc>>,
+    pub specific_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub targeting_rules: serde_json::Value,
+    pub options: Vec<NewOption>,
+}
+
+/// Campos ausentes no se modifican
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct QuestionUpdate {
+    pub title: Option<String>,
+    pub image_url: Option<String>,
+    pub priority: Option<i32>,
+    pub is_active: Option<bool>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_to: Option<DateTime<Utc>>,
+    pub specific_date: Option<NaiveDate>,
+    pub targeting_rules: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudienceSample {
+    pub user_id: i64,
+    pub age: Option<i32>,
+    pub country: Option<String>,
+    /// Tags del usuario que la regla usó para incluirlo
+    pub matched_tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudiencePreview {
+    /// Reglas normalizadas tal como se guardarían
+    pub targeting_rules: TargetingRules,
+    pub matched_users: i64,
+    /// Usuarios evaluados tras acotar por user_ids/tags en SQL
+    pub scanned_users: i64,
+    pub sample: Vec<AudienceSample>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Pregunta guardada cuyas reglas ya no pasan el esquema (el feed la oculta)
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidTargeting {
+    pub question_id: Uuid,
+    pub title: String,
+    pub is_active: bool,
+    pub targeting_rules: serde_json::Value,
+    pub error: String,
+}
+
+#[derive(Debug, FromRow)]
+struct StoredRulesRow {
+    id: Uuid,
+    title: String,
+    is_active: bool,
+    targeting_rules: Json<serde_json::Value>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LumiMatchError {
+    #[error("{}", Text::new("api.not_found.question"))]
+    NotFound,
+
+    #[error("{0}")]
+    Invalid(Text),
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for LumiMatchError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct AudienceRow {
+    id: i64,
+    date_of_birth: Option<String>,
+    country_residence: Option<String>,
+    tags: Vec<String>,
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+/// Parse + validación; devuelve la forma normalizada a guardar
+pub fn checked_rules(value: &serde_json::Value) -> Result<TargetingRules, LumiMatchError> {
+    let rules = TargetingRules::parse(value).map_err(LumiMatchError::Invalid)?;
+    rules.validate().map_err(LumiMatchError::Invalid)?;
+    Ok(rules)
+}
+
+fn invalid_window() -> Text {
+    Text::new("api.validation.before").with("field", "valid_from").with("other", "valid_to")
+}
+
+fn check_window(valid_from: Option<DateTime<Utc>>, valid_to: Option<DateTime<Utc>>) -> Result<(), LumiMatchError> {
+    if let (Some(from), Some(to)) = (valid_from, valid_to) {
+        if from >= to {
+            return Err(LumiMatchError::Invalid(invalid_window()));
+        }
+    }
+    Ok(())
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct LumiMatchService {
+    db: PgPool,
+}
+
+impl LumiMatchService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn create_question(&self, question: &NewQuestion) -> Result<Uuid, LumiMatchError> {
+        if question.title.trim().is_empty() {
+            return Err(LumiMatchError::Invalid(Text::new("api.validation.required").with("field", "title")));
+        }
+        if question.options.len() < 2 {
+            return Err(LumiMatchError::Invalid(Text::new("api.lumimatch.min_options")));
+        }
+        if question
+            .options
+            .iter()
+            .any(|option| !option.label.as_deref().is_some_and(|l| !l.trim().is_empty()) && option.image_url.is_none())
+        {
+            return Err(LumiMatchError::Invalid(Text::new("api.lumimatch.option_content")));
+        }
+        if question.options.iter().flat_map(|option| &option.tags).any(|tag| tag.trim().is_empty()) {
+            return Err(LumiMatchError::Invalid(Text::new("api.validation.empty_value").with("field", "options.tags")));
+        }
+        check_window(question.valid_from, question.valid_to)?;
+        let rules = checked_rules(&question.targeting_rules)?;
+
+        let mut tx = self.db.begin().await?;
+
+        let question_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO lumimatch.questions
+                (title, image_url, priority, is_active, valid_from, valid_to, specific_date, targeting_rules)
+            VALUES ($1, $2, COALESCE($3, 0), COALESCE($4, true), $5, $6, $7, $8)
+            RETURNING id
+            "#,
+        )
+        .bind(question.title.trim())
+        .bind(&question.image_url)
+        .bind(question.priority)
+        .bind(question.is_active)
+        .bind(question.valid_from)
+        .bind(question.valid_to)
+        .bind(question.specific_date)
+        .bind(Json(&rules))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for (order, option) in question.options.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO lumimatch.options (question_id, label, image_url, icon_url, display_order, tags)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(question_id)
+            .bind(&option.label)
+            .bind(&option.image_url)
+            .bind(&option.icon_url)
+            .bind(order as i32)
+            .bind(option.tags.iter().map(|tag| tag.trim().to_lowercase()).collect::<Vec<_>>())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        info!("💘 LumiMatch question {} created ({} options)", question_id, question.options.len());
+        Ok(question_id)
+    }
+
+    pub async fn update_question(&self, question_id: Uuid, update: &QuestionUpdate) -> Result<(), LumiMatchError> {
+        if update.title.as_deref().is_some_and(|title| title.trim().is_empty()) {
+            return Err(LumiMatchError::Invalid(Text::new("api.validation.required").with("field", "title")));
+        }
+        check_window(update.valid_from, update.valid_to)?;
+        let rules = update.targeting_rules.as_ref().map(checked_rules).transpose()?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE lumimatch.questions SET
+                title = COALESCE($2, title),
+                image_url = COALESCE($3, image_url),
+                priority = COALESCE($4, priority),
+                is_active = COALESCE($5, is_active),
+                valid_from = COALESCE($6, valid_from),
+                valid_to = COALESCE($7, valid_to),
+                specific_date = COALESCE($8, specific_date),
+                targeting_rules = COALESCE($9, targeting_rules),
+                updated_at = NOW()
+            WHERE id = $1
+              AND (COALESCE($6, valid_from) IS NULL OR COALESCE($7, valid_to) IS NULL
+                   OR COALESCE($6, valid_from) < COALESCE($7, valid_to))
+            "#,
+        )
+        .bind(question_id)
+        .bind(update.title.as_deref().map(str::trim))
+        .bind(&update.image_url)
+        .bind(update.priority)
+        .bind(update.is_active)
+        .bind(update.valid_from)
+        .bind(update.valid_to)
+        .bind(update.specific_date)
+        .bind(rules.as_ref().map(Json))
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM lumimatch.questions WHERE id = $1)")
+                .bind(question_id)
+                .fetch_one(&self.db)
+                .await?;
+            return Err(if exists {
+                LumiMatchError::Invalid(invalid_window())
+            } else {
+                LumiMatchError::NotFound
+            });
+        }
+
+        info!("💘 LumiMatch question {} updated", question_id);
+        Ok(())
+    }
+
+    /// Preguntas cuyo `targeting_rules` guardado no pasa `parse` + `validate`
+    /// (p. ej. escritas antes del esquema tipado). Se revisa en Rust porque
+    /// el esquema no existe en SQL
+    pub async fn invalid_targeting(&self) -> Result<Vec<InvalidTargeting>, LumiMatchError> {
+        let rows = sqlx::query_as::<_, StoredRulesRow>(
+            r#"
+            SELECT id, title, COALESCE(is_active, false) AS is_active,
+                   COALESCE(targeting_rules, '{}'::jsonb) AS targeting_rules
+            FROM lumimatch.questions
+            ORDER BY is_active DESC, created_at DESC
+            "#,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let error = CompiledTargeting::from_json(&row.targeting_rules.0).err()?;
+                Some(InvalidTargeting {
+                    question_id: row.id,
+                    title: row.title,
+                    is_active: row.is_active,
+                    targeting_rules: row.targeting_rules.0,
+                    error: error.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    /// Cuántos usuarios (y cuáles, como muestra) cumplen las reglas hoy
+    pub async fn audience_preview(
+        &self,
+        targeting_rules: &serde_json::Value,
+        sample_size: usize,
+    ) -> Result<AudiencePreview, LumiMatchError> {
+        let rules = checked_rules(targeting_rules)?;
+        let compiled: CompiledTargeting = rules.compile();
+        let sample_size = sample_size.min(MAX_PREVIEW_SAMPLE);
+        let today = Utc::now().date_naive();
+
+        // SQL solo acota candidatos (condiciones necesarias); la decisión
+        // final la toma el mismo predicado que usa el feed
+        let mut rows = sqlx::query_as::<_, AudienceRow>(
+            r#"
+            SELECT u.id, u.date_of_birth, u.country_residence,
+                   COALESCE(array_agg(t.tag) FILTER (WHERE t.tag IS NOT NULL), '{}') AS tags
+            FROM public.dim_users u
+            LEFT JOIN lumimatch.user_tags t
+                ON t.user_id = u.id
+               AND t.confidence >= $3
+               AND (t.expires_at IS NULL OR t.expires_at > NOW())
+            WHERE ($1::bigint[] IS NULL OR u.id = ANY($1))
+              AND ($2::text[] IS NULL OR EXISTS (
+                  SELECT 1 FROM lumimatch.user_tags c
+                  WHERE c.user_id = u.id AND lower(c.tag) = ANY($2)
+              ))
+            GROUP BY u.id
+            ORDER BY u.id
+            "#,
+        )
+        .bind(compiled.user_ids())
+        .bind(compiled.candidate_tags())
+        .bind(MIN_TARGETING_CONFIDENCE)
+        .fetch(&self.db);
+
+        let (mut matched_users, mut scanned_users, mut sample) = (0_i64, 0_i64, Vec::new());
+        while let Some(row) = rows.try_next().await? {
+            scanned_users += 1;
+            let context = TargetingContext::new(
+                row.id,
+                age_from_birth(row.date_of_birth.as_deref(), today),
+                row.country_residence,
+                row.tags,
+            );
+            if !compiled.matches(&context) {
+                continue;
+            }
+            matched_users += 1;
+            if sample.len() < sample_size {
+                sample.push(AudienceSample {
+                    user_id: context.user_id,
+                    age: context.age,
+                    matched_tags: compiled.matched_tags(&context),
+                    country: context.country,
+                });
+            }
+        }
+
+        Ok(AudiencePreview {
+            targeting_rules: rules,
+            matched_users,
+            scanned_users,
+            sample,
+            generated_at: Utc::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn checked_rules_normalize_and_reject() {
+        let rules = checked_rules(&json!({"countries": ["PA"], "product_brands": []})).unwrap();
+        assert_eq!(serde_json::to_value(&rules).unwrap(), json!({"countries": ["PA"]}));
+        assert!(matches!(checked_rules(&json!({"min_Age": 18})), Err(LumiMatchError::Invalid(_))));
+    }
+
+    #[test]
+    fn window_must_be_ordered() {
+        let now = Utc::now();
+        assert!(check_window(Some(now), Some(now + chrono::Duration::days(1))).is_ok());
+        assert!(check_window(Some(now), Some(now)).is_err());
+        assert!(check_window(None, Some(now)).is_ok());
+    }
+}
@@ -0,0 +1,743 @@
+//! Tags de usuario derivados automáticamente
+//!
+//! Cada fuente (facturas, respuestas de LumiMatch, respuestas de encuestas)
+//! deja evidencia en `lumimatch.user_tag_evidence`, una fila por tag y
+//! referencia de origen (CUFE, pregunta o encuesta). Al llegar evidencia nueva
+//! solo se recalculan los tags afectados de ese usuario: la confianza combina
+//! las evidencias vigentes (`1 - Π(1 - peso)`) y el tag vence cuando vence su
+//! evidencia más reciente. Los tags cargados a mano (`source = 'manual'`)
+//! nunca se tocan.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::info;
+use uuid::Uuid;
+
+use super::targeting::tag_value;
+use crate::domains::surveys::definition::SurveyDefinition;
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+/// Confianza mínima para que un tag derivado cuente en el targeting
+pub const MIN_TARGETING_CONFIDENCE: f32 = 0.5;
+
+/// Tags cargados por el equipo (o previos a la derivación automática)
+pub const MANUAL_SOURCE: &str = "manual";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagSource {
+    Invoice,
+    Lumimatch,
+    Survey,
+}
+
+impl TagSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TagSource::Invoice => "invoice",
+            TagSource::Lumimatch => "lumimatch",
+            TagSource::Survey => "survey",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "invoice" => Some(TagSource::Invoice),
+            "lumimatch" => Some(TagSource::Lumimatch),
+            "survey" => Some(TagSource::Survey),
+            _ => None,
+        }
+    }
+
+    /// Peso de una evidencia: una respuesta explícita dice más que una compra
+    pub fn weight(self) -> f32 {
+        match self {
+            TagSource::Invoice => 0.5,
+            TagSource::Lumimatch => 0.9,
+            TagSource::Survey => 0.8,
+        }
+    }
+
+    /// Vigencia de una evidencia desde que se observó
+    pub fn ttl(self) -> Duration {
+        match self {
+            TagSource::Invoice => Duration::days(180),
+            TagSource::Lumimatch | TagSource::Survey => Duration::days(365),
+        }
+    }
+}
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+/// Tag propuesto por una fuente, con la etiqueta legible que lo explica
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DerivedTag {
+    pub tag: String,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProductFacts {
+    pub code: String,
+    pub brand: Option<String>,
+    pub l1: Option<String>,
+    pub l2: Option<String>,
+    pub l3: Option<String>,
+    pub l4: Option<String>,
+}
+
+/// Emisor, comercio y productos de una factura guardada
+#[derive(Debug, Clone, Default)]
+pub struct InvoiceFacts {
+    pub issuer_ruc: Option<String>,
+    pub issuer_name: Option<String>,
+    pub brand_name: Option<String>,
+    pub store_name: Option<String>,
+    pub l1: Option<String>,
+    pub l2: Option<String>,
+    pub l3: Option<String>,
+    pub l4: Option<String>,
+    pub products: Vec<ProductFacts>,
+}
+
+/// Evidencia vigente o vencida de un tag
+#[derive(Debug, Clone, FromRow)]
+pub struct TagEvidence {
+    pub source: String,
+    pub label: Option<String>,
+    pub observed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagScore {
+    pub confidence: f32,
+    /// Fuente con más peso entre las evidencias vigentes
+    pub source: TagSource,
+    pub evidence_count: i32,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Un tag del usuario con su procedencia ("¿por qué veo esto?")
+#[derive(Debug, Clone, Serialize)]
+pub struct TagDetail {
+    pub tag: String,
+    pub source: String,
+    pub confidence: f32,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub evidence: Vec<EvidenceDetail>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvidenceDetail {
+    pub source: String,
+    pub label: Option<String>,
+    pub observed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct TagPurge {
+    pub evidence_removed: u64,
+    pub tags_recomputed: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TaggingError {
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for TaggingError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct InvoiceRow {
+    issuer_ruc: Option<String>,
+    issuer_name: Option<String>,
+    brand_name: Option<String>,
+    store_name: Option<String>,
+    l1: Option<String>,
+    l2: Option<String>,
+    l3: Option<String>,
+    l4: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct ProductRow {
+    code: String,
+    brand: Option<String>,
+    l1: Option<String>,
+    l2: Option<String>,
+    l3: Option<String>,
+    l4: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct TagRow {
+    tag: String,
+    source: String,
+    confidence: f32,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, FromRow)]
+struct EvidenceRow {
+    tag: String,
+    source: String,
+    label: Option<String>,
+    observed_at: DateTime<Utc>,
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+fn present(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Tags con prefijo de una factura, con los mismos prefijos que las reglas
+/// de targeting (`issuer_ruc:`, `product_brand:`, `product_l1:`...)
+pub fn invoice_tags(invoice: &InvoiceFacts) -> Vec<DerivedTag> {
+    let store = present(&invoice.store_name)
+        .or(present(&invoice.brand_name))
+        .or(present(&invoice.issuer_name))
+        .unwrap_or("un comercio");
+    let mut tags = BTreeSet::new();
+    let mut push = |prefix: &str, value: Option<&str>, label: String| {
+        if let Some(value) = value {
+            tags.insert(DerivedTag { tag: format!("{}{}", prefix, tag_value(value)), label });
+        }
+    };
+
+    let bought_at = format!("Compraste en {}", store);
+    push("issuer_ruc:", present(&invoice.issuer_ruc), bought_at.clone());
+    push("issuer_brand_name:", present(&invoice.brand_name), bought_at.clone());
+    push("issuer_store_name:", present(&invoice.store_name), bought_at.clone());
+    for (prefix, level) in [
+        ("issuer_l1:", &invoice.l1),
+        ("issuer_l2:", &invoice.l2),
+        ("issuer_l3:", &invoice.l3),
+        ("issuer_l4:", &invoice.l4),
+    ] {
+        push(prefix, present(level), bought_at.clone());
+    }
+
+    for product in &invoice.products {
+        let code = product.code.trim();
+        if code.is_empty() {
+            continue;
+        }
+        push("product_code:", Some(code), format!("Compraste productos en {}", store));
+        if let Some(brand) = present(&product.brand) {
+            push("product_brand:", Some(brand), format!("Compraste productos {}", brand));
+        }
+        for (prefix, level) in [
+            ("product_l1:", &product.l1),
+            ("product_l2:", &product.l2),
+            ("product_l3:", &product.l3),
+            ("product_l4:", &product.l4),
+        ] {
+            if let Some(level) = present(level) {
+                push(prefix, Some(level), format!("Compraste productos de {}", level));
+            }
+        }
+    }
+
+    // Un tag aparece una vez por factura aunque varios productos lo aporten
+    let mut seen = BTreeSet::new();
+    tags.into_iter().filter(|t| seen.insert(t.tag.clone())).collect()
+}
+
+/// Tags configurados en una opción elegida (LumiMatch o encuesta)
+pub fn option_tags(tags: &[String], label: &str) -> Vec<DerivedTag> {
+    let unique: BTreeSet<String> =
+        tags.iter().map(|tag| tag.trim().to_lowercase()).filter(|tag| !tag.is_empty()).collect();
+    unique.into_iter().map(|tag| DerivedTag { tag, label: label.to_string() }).collect()
+}
+
+/// Confianza y vencimiento de un tag a partir de sus evidencias; `None` si
+/// ninguna sigue vigente
+pub fn score(evidence: &[TagEvidence], now: DateTime<Utc>) -> Option<TagScore> {
+    let live: Vec<(TagSource, DateTime<Utc>)> = evidence
+        .iter()
+        .filter_map(|e| TagSource::parse(&e.source).map(|source| (source, e.observed_at)))
+        .filter(|(source, observed_at)| *observed_at + source.ttl() > now)
+        .collect();
+
+    let source = live.iter().map(|(source, _)| *source).max_by(|a, b| a.weight().total_cmp(&b.weight()))?;
+    let miss: f32 = live.iter().map(|(source, _)| 1.0 - source.weight()).product();
+    Some(TagScore {
+        confidence: ((1.0 - miss) * 1000.0).round() / 1000.0,
+        source,
+        evidence_count: live.len() as i32,
+        last_seen_at: live.iter().map(|(_, observed_at)| *observed_at).max()?,
+        expires_at: live.iter().map(|(source, observed_at)| *observed_at + source.ttl()).max()?,
+    })
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct TagService {
+    db: PgPool,
+}
+
+impl TagService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Emisor, comercio y jerarquía de productos de una factura guardada
+    pub async fn invoice_facts(&self, cufe: &str) -> Result<Option<InvoiceFacts>, TaggingError> {
+        let Some(header) = sqlx::query_as::<_, InvoiceRow>(
+            r#"
+            SELECT ih.issuer_ruc, ih.issuer_name, s.brand_name, s.store_name, s.l1, s.l2, s.l3, s.l4
+            FROM public.invoice_header ih
+            LEFT JOIN LATERAL (
+                SELECT brand_name, store_name, l1, l2, l3, l4
+                FROM public.dim_issuer_stores
+                WHERE issuer_ruc = ih.issuer_ruc AND store_id = ih.store_id
+                LIMIT 1
+            ) s ON true
+            WHERE ih.cufe = $1
+            "#,
+        )
+        .bind(cufe)
+        .fetch_optional(&self.db)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let products = sqlx::query_as::<_, ProductRow>(
+            r#"
+            SELECT DISTINCT ON (d.code) d.code, p.brand, p.l1, p.l2, p.l3, p.l4
+            FROM public.invoice_detail d
+            JOIN public.invoice_header ih ON ih.cufe = d.cufe
+            LEFT JOIN public.dim_product p
+                ON p.code = d.code AND p.issuer_ruc = ih.issuer_ruc AND COALESCE(p.is_deleted, false) = false
+            WHERE d.cufe = $1 AND d.code IS NOT NULL AND COALESCE(d.is_deleted, false) = false
+            ORDER BY d.code
+            "#,
+        )
+        .bind(cufe)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(Some(InvoiceFacts {
+            issuer_ruc: header.issuer_ruc,
+            issuer_name: header.issuer_name,
+            brand_name: header.brand_name,
+            store_name: header.store_name,
+            l1: header.l1,
+            l2: header.l2,
+            l3: header.l3,
+            l4: header.l4,
+            products: products
+                .into_iter()
+                .map(|p| ProductFacts { code: p.code, brand: p.brand, l1: p.l1, l2: p.l2, l3: p.l3, l4: p.l4 })
+                .collect(),
+        }))
+    }
+
+    pub async fn record_invoice(&self, user_id: i32, cufe: &str, observed_at: DateTime<Utc>) -> Result<usize, TaggingError> {
+        let Some(facts) = self.invoice_facts(cufe).await? else {
+            return Ok(0);
+        };
+        self.record(user_id, TagSource::Invoice, cufe, observed_at, &invoice_tags(&facts)).await
+    }
+
+    /// Tags de la opción elegida; la referencia es la pregunta, así que
+    /// cambiar la respuesta reemplaza la evidencia anterior
+    pub async fn record_lumimatch_answer(
+        &self,
+        user_id: i32,
+        question_id: Uuid,
+        option_id: Uuid,
+        observed_at: DateTime<Utc>,
+    ) -> Result<usize, TaggingError> {
+        let option: Option<(String, Option<String>, Vec<String>)> = sqlx::query_as(
+            r#"
+            SELECT q.title, o.label, o.tags
+            FROM lumimatch.options o
+            JOIN lumimatch.questions q ON q.id = o.question_id
+            WHERE o.id = $1 AND o.question_id = $2
+            "#,
+        )
+        .bind(option_id)
+        .bind(question_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        let tags = match &option {
+            Some((title, label, tags)) => {
+                let label = match label.as_deref() {
+                    Some(label) => format!("{}: {}", title, label),
+                    None => title.clone(),
+                };
+                option_tags(tags, &label)
+            }
+            None => Vec::new(),
+        };
+        self.record(user_id, TagSource::Lumimatch, &question_id.to_string(), observed_at, &tags).await
+    }
+
+    /// Tags de las opciones elegidas en el último intento guardado
+    pub async fn record_survey(&self, user_id: i32, survey_id: i32, observed_at: DateTime<Utc>) -> Result<usize, TaggingError> {
+        let stored: Option<(serde_json::Value, Option<serde_json::Value>)> = sqlx::query_as(
+            r#"
+            SELECT s.questions, fuss.responses
+            FROM survey.dim_surveys s
+            JOIN survey.fact_user_survey_status fuss ON fuss.survey_id = s.survey_id
+            WHERE s.survey_id = $1 AND fuss.user_id = $2
+            "#,
+        )
+        .bind(survey_id)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+        let Some((questions, Some(responses))) = stored else {
+            return Ok(0);
+        };
+        let Ok(definition) = SurveyDefinition::parse(&questions) else {
+            return Ok(0);
+        };
+
+        let tags: Vec<DerivedTag> = definition
+            .selected_option_tags(&responses)
+            .iter()
+            .flat_map(|(label, tags)| option_tags(tags, label))
+            .collect();
+        self.record(user_id, TagSource::Survey, &survey_id.to_string(), observed_at, &tags).await
+    }
+
+    /// Reemplaza la evidencia de una referencia (re-procesar la misma factura
+    /// o cambiar una respuesta no duplica) y recalcula los tags afectados
+    pub async fn record(
+        &self,
+        user_id: i32,
+        source: TagSource,
+        source_ref: &str,
+        observed_at: DateTime<Utc>,
+        tags: &[DerivedTag],
+    ) -> Result<usize, TaggingError> {
+        let mut tx = self.db.begin().await?;
+
+        let mut affected: BTreeSet<String> = sqlx::query_scalar::<_, String>(
+            r#"
+            DELETE FROM lumimatch.user_tag_evidence
+            WHERE user_id = $1 AND source = $2 AND source_ref = $3
+            RETURNING tag
+            "#,
+        )
+        .bind(user_id)
+        .bind(source.as_str())
+        .bind(source_ref)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .collect();
+
+        for derived in tags {
+            sqlx::query(
+                r#"
+                INSERT INTO lumimatch.user_tag_evidence (user_id, tag, source, source_ref, label, observed_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (user_id, tag, source, source_ref) DO UPDATE
+                SET label = EXCLUDED.label, observed_at = EXCLUDED.observed_at
+                "#,
+            )
+            .bind(user_id)
+            .bind(&derived.tag)
+            .bind(source.as_str())
+            .bind(source_ref)
+            .bind(&derived.label)
+            .bind(observed_at)
+            .execute(&mut *tx)
+            .await?;
+            affected.insert(derived.tag.clone());
+        }
+
+        let affected: Vec<String> = affected.into_iter().collect();
+        recompute(&mut *tx, user_id, &affected, Utc::now()).await?;
+        tx.commit().await?;
+
+        Ok(affected.len())
+    }
+
+    /// Tags que cuentan para el targeting: manuales y derivados vigentes con
+    /// confianza suficiente
+    pub async fn active_tags(&self, user_id: i32) -> Result<Vec<String>, TaggingError> {
+        let tags = sqlx::query_scalar(
+            r#"
+            SELECT tag FROM lumimatch.user_tags
+            WHERE user_id = $1
+              AND confidence >= $2
+              AND (expires_at IS NULL OR expires_at > NOW())
+            "#,
+        )
+        .bind(user_id)
+        .bind(MIN_TARGETING_CONFIDENCE)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(tags)
+    }
+
+    /// Procedencia de los tags indicados (los que usó una regla)
+    pub async fn tag_details(&self, user_id: i32, tags: &[String]) -> Result<Vec<TagDetail>, TaggingError> {
+        if tags.is_empty() {
+            return Ok(Vec::new());
+        }
+        let rows = sqlx::query_as::<_, TagRow>(
+            r#"
+            SELECT tag, source, confidence, expires_at
+            FROM lumimatch.user_tags
+            WHERE user_id = $1 AND tag = ANY($2)
+            ORDER BY confidence DESC, tag
+            "#,
+        )
+        .bind(user_id)
+        .bind(tags)
+        .fetch_all(&self.db)
+        .await?;
+
+        let evidence = sqlx::query_as::<_, EvidenceRow>(
+            r#"
+            SELECT tag, source, label, observed_at
+            FROM lumimatch.user_tag_evidence
+            WHERE user_id = $1 AND tag = ANY($2)
+            ORDER BY observed_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(tags)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut by_tag: BTreeMap<String, Vec<EvidenceDetail>> = BTreeMap::new();
+        for row in evidence {
+            by_tag.entry(row.tag).or_default().push(EvidenceDetail {
+                source: row.source,
+                label: row.label,
+                observed_at: row.observed_at,
+            });
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|row| TagDetail {
+                evidence: by_tag.remove(&row.tag).unwrap_or_default(),
+                tag: row.tag,
+                source: row.source,
+                confidence: row.confidence,
+                expires_at: row.expires_at,
+            })
+            .collect())
+    }
+
+    /// Borra la evidencia vencida y recalcula (o borra) los tags que dependían
+    /// de ella. Lo ejecuta el job diario.
+    pub async fn purge_expired(&self) -> Result<TagPurge, TaggingError> {
+        let mut tx = self.db.begin().await?;
+        let now = Utc::now();
+
+        let mut expired: Vec<(i32, String)> = Vec::new();
+        for source in [TagSource::Invoice, TagSource::Lumimatch, TagSource::Survey] {
+            let rows: Vec<(i32, String)> = sqlx::query_as(
+                r#"
+                DELETE FROM lumimatch.user_tag_evidence
+                WHERE source = $1 AND observed_at <= $2
+                RETURNING user_id, tag
+                "#,
+            )
+            .bind(source.as_str())
+            .bind(now - source.ttl())
+            .fetch_all(&mut *tx)
+            .await?;
+            expired.extend(rows);
+        }
+
+        let mut by_user: BTreeMap<i32, BTreeSet<String>> = BTreeMap::new();
+        for (user_id, tag) in &expired {
+            by_user.entry(*user_id).or_default().insert(tag.clone());
+        }
+        let mut purge = TagPurge { evidence_removed: expired.len() as u64, tags_recomputed: 0 };
+        for (user_id, tags) in by_user {
+            let tags: Vec<String> = tags.into_iter().collect();
+            recompute(&mut *tx, user_id, &tags, now).await?;
+            purge.tags_recomputed += tags.len() as u64;
+        }
+
+        // Tags derivados que vencieron sin evidencia pendiente de purgar
+        let stale = sqlx::query(
+            "DELETE FROM lumimatch.user_tags WHERE source <> $1 AND expires_at IS NOT NULL AND expires_at <= $2",
+        )
+        .bind(MANUAL_SOURCE)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        purge.tags_recomputed += stale.rows_affected();
+
+        tx.commit().await?;
+
+        if purge.evidence_removed > 0 {
+            info!(
+                "🏷️ Purged {} expired tag evidences ({} tags recomputed)",
+                purge.evidence_removed, purge.tags_recomputed
+            );
+        }
+        Ok(purge)
+    }
+}
+
+/// Recalcula `user_tags` para los tags indicados de un usuario a partir de la
+/// evidencia guardada. Las filas manuales se dejan intactas.
+async fn recompute(conn: &mut PgConnection, user_id: i32, tags: &[String], now: DateTime<Utc>) -> Result<(), TaggingError> {
+    if tags.is_empty() {
+        return Ok(());
+    }
+    let evidence = sqlx::query_as::<_, EvidenceRow>(
+        r#"
+        SELECT tag, source, label, observed_at
+        FROM lumimatch.user_tag_evidence
+        WHERE user_id = $1 AND tag = ANY($2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(tags)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut by_tag: BTreeMap<&str, Vec<TagEvidence>> = tags.iter().map(|tag| (tag.as_str(), Vec::new())).collect();
+    for row in evidence {
+        if let Some(list) = by_tag.get_mut(row.tag.as_str()) {
+            list.push(TagEvidence { source: row.source, label: row.label, observed_at: row.observed_at });
+        }
+    }
+
+    let mut dropped = Vec::new();
+    for (tag, evidence) in by_tag {
+        let Some(score) = score(&evidence, now) else {
+            dropped.push(tag.to_string());
+            continue;
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO lumimatch.user_tags
+                (user_id, tag, source, confidence, evidence_count, last_seen_at, expires_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+            ON CONFLICT (user_id, tag) DO UPDATE SET
+                source = EXCLUDED.source,
+                confidence = EXCLUDED.confidence,
+                evidence_count = EXCLUDED.evidence_count,
+                last_seen_at = EXCLUDED.last_seen_at,
+                expires_at = EXCLUDED.expires_at,
+                updated_at = NOW()
+            WHERE lumimatch.user_tags.source <> 'manual'
+            "#,
+        )
+        .bind(user_id)
+        .bind(tag)
+        .bind(score.source.as_str())
+        .bind(score.confidence)
+        .bind(score.evidence_count)
+        .bind(score.last_seen_at)
+        .bind(score.expires_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    if !dropped.is_empty() {
+        sqlx::query("DELETE FROM lumimatch.user_tags WHERE user_id = $1 AND tag = ANY($2) AND source <> $3")
+            .bind(user_id)
+            .bind(&dropped)
+            .bind(MANUAL_SOURCE)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evidence(source: &str, days_ago: i64, now: DateTime<Utc>) -> TagEvidence {
+        TagEvidence { source: source.to_string(), label: None, observed_at: now - Duration::days(days_ago) }
+    }
+
+    #[test]
+    fn invoice_tags_use_targeting_prefixes() {
+        let invoice = InvoiceFacts {
+            issuer_ruc: Some("155-1-2020".into()),
+            brand_name: Some("Super 99".into()),
+            store_name: Some("Super 99 Vía España".into()),
+            l1: Some("Supermercados".into()),
+            products: vec![
+                ProductFacts { code: "7501".into(), brand: Some("Coca Cola".into()), l1: Some("Bebidas".into()), ..Default::default() },
+                ProductFacts { code: "7502".into(), brand: Some("Coca Cola".into()), ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        let tags = invoice_tags(&invoice);
+        let names: Vec<&str> = tags.iter().map(|t| t.tag.as_str()).collect();
+
+        assert!(names.contains(&"issuer_ruc:155-1-2020"));
+        assert!(names.contains(&"issuer_store_name:super_99_vía_españa"));
+        assert!(names.contains(&"issuer_l1:supermercados"));
+        assert!(names.contains(&"product_l1:bebidas"));
+        assert_eq!(names.iter().filter(|t| **t == "product_brand:coca_cola").count(), 1);
+        assert_eq!(names.iter().filter(|t| t.starts_with("product_code:")).count(), 2);
+
+        let brand = tags.iter().find(|t| t.tag == "product_brand:coca_cola").unwrap();
+        assert_eq!(brand.label, "Compraste productos Coca Cola");
+    }
+
+    #[test]
+    fn option_tags_are_normalized() {
+        let tags = option_tags(&[" Fitness ".into(), "fitness".into(), "".into()], "Te gusta: Correr");
+        assert_eq!(tags, vec![DerivedTag { tag: "fitness".into(), label: "Te gusta: Correr".into() }]);
+    }
+
+    #[test]
+    fn confidence_combines_live_evidence() {
+        let now = Utc::now();
+        let one = score(&[evidence("invoice", 1, now)], now).unwrap();
+        assert_eq!(one.confidence, 0.5);
+        assert_eq!(one.source, TagSource::Invoice);
+
+        let two = score(&[evidence("invoice", 1, now), evidence("invoice", 10, now)], now).unwrap();
+        assert_eq!(two.confidence, 0.75);
+        assert_eq!(two.evidence_count, 2);
+        assert_eq!(two.last_seen_at, now - Duration::days(1));
+        assert_eq!(two.expires_at, now - Duration::days(1) + Duration::days(180));
+
+        let mixed = score(&[evidence("invoice", 1, now), evidence("lumimatch", 30, now)], now).unwrap();
+        assert_eq!(mixed.confidence, 0.95);
+        assert_eq!(mixed.source, TagSource::Lumimatch);
+        assert_eq!(mixed.expires_at, now - Duration::days(30) + Duration::days(365));
+    }
+
+    #[test]
+    fn expired_or_unknown_evidence_is_ignored() {
+        let now = Utc::now();
+        assert!(score(&[evidence("invoice", 200, now)], now).is_none());
+        assert!(score(&[evidence("manual", 1, now)], now).is_none());
+
+        let partial = score(&[evidence("invoice", 200, now), evidence("survey", 200, now)], now).unwrap();
+        assert_eq!(partial.confidence, 0.8);
+        assert_eq!(partial.evidence_count, 1);
+    }
+}
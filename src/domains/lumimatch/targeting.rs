@@ -0,0 +1,613 @@
+//! Reglas de segmentación de LumiMatch (`lumimatch.questions.targeting_rules`)
+//!
+//! Antes el JSON se interpretaba en cada request con valores por defecto
+//! silenciosos: un campo mal escrito (`min_Age`) o con el tipo equivocado
+//! (`"min_age": "18"`) se ignoraba y la pregunta salía a todos. Ahora:
+//!
+//! 1. `TargetingRules::parse` rechaza campos desconocidos y tipos inválidos.
+//! 2. `validate` revisa rangos y contradicciones al crear o editar preguntas.
+//! 3. `compile` lo convierte en un `CompiledTargeting` con conjuntos listos
+//!    para evaluar muchas veces (feed del usuario o preview de audiencia).
+//!
+//! Una regla guardada que ya no parsea se trata como "no mostrar" en lugar
+//! de "mostrar a todos". El feed no recompila en cada request: el
+//! `TargetingCache` guarda el resultado por (pregunta, `updated_at`) y solo
+//! avisa una vez por versión inválida.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock, RwLock};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+pub const MAX_AGE: i32 = 120;
+
+/// Tope de preguntas en el cache; al pasarlo se vacía y se recompila a demanda
+const MAX_CACHED_QUESTIONS: usize = 10_000;
+
+/// Campos de producto/comercio → prefijo del tag generado por el OCR
+const PREFIXED_FIELDS: [(&str, &str); 13] = [
+    ("product_codes", "product_code:"),
+    ("product_l1", "product_l1:"),
+    ("product_l2", "product_l2:"),
+    ("product_l3", "product_l3:"),
+    ("product_l4", "product_l4:"),
+    ("product_brands", "product_brand:"),
+    ("issuer_rucs", "issuer_ruc:"),
+    ("issuer_brand_names", "issuer_brand_name:"),
+    ("issuer_store_names", "issuer_store_name:"),
+    ("issuer_l1", "issuer_l1:"),
+    ("issuer_l2", "issuer_l2:"),
+    ("issuer_l3", "issuer_l3:"),
+    ("issuer_l4", "issuer_l4:"),
+];
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+/// Esquema de `targeting_rules`. Todos los campos son opcionales y se
+/// combinan con AND; dentro de `any_tags` y de cada lista de
+/// producto/comercio basta con un valor (OR).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TargetingRules {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_age: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub countries: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub user_ids: Vec<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub any_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub product_codes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub product_l1: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub product_l2: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub product_l3: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub product_l4: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub product_brands: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issuer_rucs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issuer_brand_names: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issuer_store_names: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issuer_l1: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issuer_l2: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issuer_l3: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issuer_l4: Vec<String>,
+}
+
+/// Lo que se sabe del usuario al evaluar; los tags van en minúsculas
+#[derive(Debug, Clone, Default)]
+pub struct TargetingContext {
+    pub user_id: i64,
+    pub age: Option<i32>,
+    pub country: Option<String>,
+    pub tags: HashSet<String>,
+}
+
+/// Predicado listo para evaluar
+#[derive(Debug, Clone, Default)]
+pub struct CompiledTargeting {
+    user_ids: Option<HashSet<i64>>,
+    min_age: Option<i32>,
+    max_age: Option<i32>,
+    countries: Option<HashSet<String>>,
+    required: Vec<String>,
+    excluded: Vec<String>,
+    /// `any_tags` y cada lista de producto/comercio (con el nombre del
+    /// campo): al menos uno de cada grupo
+    any_of: Vec<(&'static str, HashSet<String>)>,
+}
+
+/// Una condición de la regla evaluada para un usuario ("¿por qué veo esto?")
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleExplanation {
+    pub rule: &'static str,
+    pub satisfied: bool,
+    pub message: String,
+    /// Tags del usuario que cumplen la condición
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub matched_tags: Vec<String>,
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+/// Edad cumplida a partir de `date_of_birth` (YYYY-MM-DD)
+pub fn age_from_birth(date_of_birth: Option<&str>, today: NaiveDate) -> Option<i32> {
+    let birth = NaiveDate::parse_from_str(date_of_birth?.trim(), "%Y-%m-%d").ok()?;
+    today.years_since(birth).map(|age| age as i32)
+}
+
+fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Tags de la lista que tiene el usuario, ordenados
+fn owned_tags<'a>(tags: impl IntoIterator<Item = &'a String>, context: &TargetingContext) -> Vec<String> {
+    let mut owned: Vec<String> = tags.into_iter().filter(|tag| context.tags.contains(*tag)).cloned().collect();
+    owned.sort();
+    owned
+}
+
+/// Valor de un tag con prefijo: minúsculas y espacios como `_`
+/// ("Vía España" → "vía_españa")
+pub fn tag_value(raw: &str) -> String {
+    raw.split_whitespace().collect::<Vec<_>>().join("_").to_lowercase()
+}
+
+impl TargetingContext {
+    pub fn new(user_id: i64, age: Option<i32>, country: Option<String>, tags: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            user_id,
+            age,
+            country: country.map(|country| country.trim().to_uppercase()),
+            tags: tags.into_iter().map(|tag| normalize_tag(&tag)).collect(),
+        }
+    }
+}
+
+impl TargetingRules {
+    /// `null` o `{}` = sin restricciones; cualquier campo desconocido o con
+    /// tipo inválido es un error
+    pub fn parse(value: &serde_json::Value) -> Result<Self, Text> {
+        match value {
+            serde_json::Value::Null => Ok(Self::default()),
+            serde_json::Value::Object(_) => serde_json::from_value(value.clone())
+                .map_err(|e| Text::new("api.targeting.invalid_json").with("error", e)),
+            _ => Err(Text::new("api.targeting.not_object")),
+        }
+    }
+
+    fn prefixed(&self) -> impl Iterator<Item = (&'static str, &'static str, &Vec<String>)> + '_ {
+        let lists = [
+            &self.product_codes,
+            &self.product_l1,
+            &self.product_l2,
+            &self.product_l3,
+            &self.product_l4,
+            &self.product_brands,
+            &self.issuer_rucs,
+            &self.issuer_brand_names,
+            &self.issuer_store_names,
+            &self.issuer_l1,
+            &self.issuer_l2,
+            &self.issuer_l3,
+            &self.issuer_l4,
+        ];
+        PREFIXED_FIELDS
+            .into_iter()
+            .zip(lists)
+            .map(|((field, prefix), list)| (field, prefix, list))
+    }
+
+    pub fn validate(&self) -> Result<(), Text> {
+        for (field, age) in [("min_age", self.min_age), ("max_age", self.max_age)] {
+            if age.is_some_and(|age| !(0..=MAX_AGE).contains(&age)) {
+                return Err(Text::new("api.validation.range").with("field", field).with("min", 0).with("max", MAX_AGE));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_age, self.max_age) {
+            if min > max {
+                return Err(Text::new("api.validation.not_greater").with("field", "min_age").with("other", "max_age"));
+            }
+        }
+        if let Some(country) = self
+            .countries
+            .iter()
+            .find(|country| country.trim().len() != 2 || !country.trim().chars().all(|c| c.is_ascii_alphabetic()))
+        {
+            return Err(Text::new("api.targeting.invalid_country").with("country", country));
+        }
+        if self.user_ids.iter().any(|id| *id <= 0) {
+            return Err(Text::new("api.targeting.invalid_user_ids"));
+        }
+
+        let mut lists: Vec<(&str, &Vec<String>)> = vec![
+            ("required_tags", &self.required_tags),
+            ("any_tags", &self.any_tags),
+            ("excluded_tags", &self.excluded_tags),
+        ];
+        lists.extend(self.prefixed().map(|(field, _, list)| (field, list)));
+        for (field, values) in lists {
+            let mut seen = HashSet::new();
+            for value in values {
+                let tag = normalize_tag(value);
+                if tag.is_empty() {
+                    return Err(Text::new("api.validation.empty_value").with("field", field));
+                }
+                if !seen.insert(tag) {
+                    return Err(Text::new("api.validation.duplicate").with("field", field).with("value", value));
+                }
+            }
+        }
+
+        let excluded: HashSet<String> = self.excluded_tags.iter().map(|tag| normalize_tag(tag)).collect();
+        if let Some(tag) = self.required_tags.iter().find(|tag| excluded.contains(&normalize_tag(tag))) {
+            return Err(Text::new("api.validation.tag_conflict").with("tag", tag));
+        }
+        if !self.any_tags.is_empty() && self.any_tags.iter().all(|tag| excluded.contains(&normalize_tag(tag))) {
+            return Err(Text::new("api.targeting.any_tags_excluded"));
+        }
+        Ok(())
+    }
+
+    pub fn compile(&self) -> CompiledTargeting {
+        let set = |values: &Vec<String>| values.iter().map(|v| normalize_tag(v)).collect::<HashSet<_>>();
+
+        let mut any_of = Vec::new();
+        if !self.any_tags.is_empty() {
+            any_of.push(("any_tags", set(&self.any_tags)));
+        }
+        for (field, prefix, values) in self.prefixed() {
+            if !values.is_empty() {
+                any_of.push((field, values.iter().map(|v| format!("{}{}", prefix, tag_value(v))).collect()));
+            }
+        }
+
+        CompiledTargeting {
+            user_ids: (!self.user_ids.is_empty()).then(|| self.user_ids.iter().copied().collect()),
+            min_age: self.min_age,
+            max_age: self.max_age,
+            countries: (!self.countries.is_empty())
+                .then(|| self.countries.iter().map(|c| c.trim().to_uppercase()).collect()),
+            required: self.required_tags.iter().map(|tag| normalize_tag(tag)).collect(),
+            excluded: self.excluded_tags.iter().map(|tag| normalize_tag(tag)).collect(),
+            any_of,
+        }
+    }
+}
+
+impl CompiledTargeting {
+    /// Parse + validación + compilación de lo guardado en la base
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, Text> {
+        let rules = TargetingRules::parse(value)?;
+        rules.validate()?;
+        Ok(rules.compile())
+    }
+
+    pub fn matches(&self, context: &TargetingContext) -> bool {
+        if self.user_ids.as_ref().is_some_and(|ids| !ids.contains(&context.user_id)) {
+            return false;
+        }
+        // Con min_age la edad es obligatoria; max_age solo filtra si se conoce
+        if let Some(min_age) = self.min_age {
+            if context.age.is_none_or(|age| age < min_age) {
+                return false;
+            }
+        }
+        if let (Some(max_age), Some(age)) = (self.max_age, context.age) {
+            if age > max_age {
+                return false;
+            }
+        }
+        if let Some(countries) = &self.countries {
+            if !context.country.as_ref().is_some_and(|country| countries.contains(country)) {
+                return false;
+            }
+        }
+        if !self.required.iter().all(|tag| context.tags.contains(tag)) {
+            return false;
+        }
+        if self.excluded.iter().any(|tag| context.tags.contains(tag)) {
+            return false;
+        }
+        self.any_of.iter().all(|(_, group)| group.iter().any(|tag| context.tags.contains(tag)))
+    }
+
+    /// Cada condición configurada, cumplida o no, en lenguaje del usuario
+    pub fn explain(&self, context: &TargetingContext) -> Vec<RuleExplanation> {
+        let explanation = |rule, satisfied, message: String, matched_tags| RuleExplanation {
+            rule,
+            satisfied,
+            message,
+            matched_tags,
+        };
+
+        let mut reasons = Vec::new();
+        if let Some(ids) = &self.user_ids {
+            let satisfied = ids.contains(&context.user_id);
+            let message = if satisfied {
+                "Eres parte de un grupo de usuarios seleccionado".to_string()
+            } else {
+                "La pregunta es solo para un grupo de usuarios seleccionado".to_string()
+            };
+            reasons.push(explanation("user_ids", satisfied, message, Vec::new()));
+        }
+        if self.min_age.is_some() || self.max_age.is_some() {
+            let range = match (self.min_age, self.max_age) {
+                (Some(min), Some(max)) => format!("de {} a {} años", min, max),
+                (Some(min), None) => format!("de {} años o más", min),
+                (None, Some(max)) => format!("de hasta {} años", max),
+                (None, None) => unreachable!(),
+            };
+            // Misma semántica que `matches`: sin edad conocida solo falla min_age
+            let satisfied = match context.age {
+                Some(age) => self.min_age.is_none_or(|min| age >= min) && self.max_age.is_none_or(|max| age <= max),
+                None => self.min_age.is_none(),
+            };
+            reasons.push(explanation("age", satisfied, format!("Dirigida a personas {}", range), Vec::new()));
+        }
+        if let Some(countries) = &self.countries {
+            let satisfied = context.country.as_ref().is_some_and(|country| countries.contains(country));
+            let message = match &context.country {
+                Some(country) => format!("Tu país de residencia es {}", country),
+                None => "No tenemos tu país de residencia".to_string(),
+            };
+            reasons.push(explanation("countries", satisfied, message, Vec::new()));
+        }
+        if !self.required.is_empty() {
+            let matched = owned_tags(&self.required, context);
+            let satisfied = matched.len() == self.required.len();
+            reasons.push(explanation("required_tags", satisfied, "Por tu perfil".to_string(), matched));
+        }
+        for (field, group) in &self.any_of {
+            let matched = owned_tags(group, context);
+            let message = if field.starts_with("product_") {
+                "Por productos que has comprado".to_string()
+            } else if field.starts_with("issuer_") {
+                "Por comercios donde has comprado".to_string()
+            } else {
+                "Por tus intereses".to_string()
+            };
+            reasons.push(explanation(*field, !matched.is_empty(), message, matched));
+        }
+        if !self.excluded.is_empty() {
+            let matched = owned_tags(&self.excluded, context);
+            reasons.push(explanation(
+                "excluded_tags",
+                matched.is_empty(),
+                "Algunas preguntas se ocultan según tu perfil".to_string(),
+                matched,
+            ));
+        }
+        reasons
+    }
+
+    /// Tags que todo usuario que cumple la regla tiene que tener al menos
+    /// uno; sirve para acotar candidatos en SQL. `None` si no hay condición
+    /// de tags que acote.
+    pub fn candidate_tags(&self) -> Option<Vec<String>> {
+        if let Some(tag) = self.required.first() {
+            return Some(vec![tag.clone()]);
+        }
+        self.any_of
+            .iter()
+            .min_by_key(|(_, group)| group.len())
+            .map(|(_, group)| group.iter().cloned().collect())
+    }
+
+    /// Tags del usuario que la regla usó para incluirlo
+    pub fn matched_tags(&self, context: &TargetingContext) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .required
+            .iter()
+            .chain(self.any_of.iter().flat_map(|(_, group)| group))
+            .filter(|tag| context.tags.contains(*tag))
+            .cloned()
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    pub fn user_ids(&self) -> Option<Vec<i64>> {
+        self.user_ids.as_ref().map(|ids| ids.iter().copied().collect())
+    }
+}
+
+// ======================================================================
+// CACHE
+// ======================================================================
+
+/// Versión compilada de una pregunta; `None` si sus reglas no validan
+struct CachedTargeting {
+    updated_at: Option<DateTime<Utc>>,
+    compiled: Option<Arc<CompiledTargeting>>,
+}
+
+/// Targeting compilado por pregunta, invalidado cuando cambia `updated_at`
+#[derive(Default)]
+pub struct TargetingCache {
+    entries: RwLock<HashMap<Uuid, CachedTargeting>>,
+}
+
+pub static TARGETING_CACHE: LazyLock<TargetingCache> = LazyLock::new(TargetingCache::default);
+
+impl TargetingCache {
+    /// Compila solo la primera vez que ve esta versión de la pregunta.
+    /// Reglas inválidas se cachean como `None` (la pregunta no se muestra)
+    /// y se avisa una sola vez
+    pub fn get_or_compile(
+        &self,
+        question_id: Uuid,
+        updated_at: Option<DateTime<Utc>>,
+        rules: &serde_json::Value,
+    ) -> Option<Arc<CompiledTargeting>> {
+        {
+            let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(entry) = entries.get(&question_id).filter(|entry| entry.updated_at == updated_at) {
+                return entry.compiled.clone();
+            }
+        }
+
+        let compiled = match CompiledTargeting::from_json(rules) {
+            Ok(compiled) => Some(Arc::new(compiled)),
+            Err(e) => {
+                warn!("LumiMatch question {} has invalid targeting_rules, hiding it: {}", question_id, e);
+                None
+            }
+        };
+
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= MAX_CACHED_QUESTIONS && !entries.contains_key(&question_id) {
+            entries.clear();
+        }
+        entries.insert(question_id, CachedTargeting { updated_at, compiled: compiled.clone() });
+        compiled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn context(age: Option<i32>, country: &str, tags: &[&str]) -> TargetingContext {
+        TargetingContext::new(7, age, Some(country.to_string()), tags.iter().map(|t| t.to_string()))
+    }
+
+    #[test]
+    fn rejects_typos_and_wrong_types() {
+        let typo = TargetingRules::parse(&json!({"min_Age": 18})).unwrap_err();
+        assert!(typo.to_string().contains("min_Age"));
+        assert!(TargetingRules::parse(&json!({"min_age": "18"})).is_err());
+        assert!(TargetingRules::parse(&json!(["vip"])).is_err());
+        assert_eq!(TargetingRules::parse(&json!(null)).unwrap(), TargetingRules::default());
+    }
+
+    #[test]
+    fn validates_ranges_and_contradictions() {
+        let check = |value: serde_json::Value| TargetingRules::parse(&value).unwrap().validate();
+        assert!(check(json!({"min_age": 18, "max_age": 65, "countries": ["PA"]})).is_ok());
+        assert!(check(json!({"min_age": 40, "max_age": 30})).is_err());
+        assert!(check(json!({"max_age": 300})).is_err());
+        assert!(check(json!({"countries": ["Panamá"]})).is_err());
+        assert!(check(json!({"user_ids": [0]})).is_err());
+        assert!(check(json!({"product_brands": ["cocacola", "CocaCola"]})).is_err());
+        assert!(check(json!({"required_tags": ["vip"], "excluded_tags": ["VIP"]})).is_err());
+        assert!(check(json!({"any_tags": ["a"], "excluded_tags": ["a"]})).is_err());
+    }
+
+    #[test]
+    fn empty_rules_match_everyone() {
+        let compiled = CompiledTargeting::from_json(&json!({})).unwrap();
+        assert!(compiled.matches(&TargetingContext::default()));
+    }
+
+    #[test]
+    fn demographics() {
+        let compiled = CompiledTargeting::from_json(&json!({"min_age": 18, "max_age": 30, "countries": ["pa"]})).unwrap();
+        assert!(compiled.matches(&context(Some(25), "PA", &[])));
+        assert!(!compiled.matches(&context(None, "PA", &[])));
+        assert!(!compiled.matches(&context(Some(31), "PA", &[])));
+        assert!(!compiled.matches(&context(Some(25), "CO", &[])));
+
+        let only_max = CompiledTargeting::from_json(&json!({"max_age": 30})).unwrap();
+        assert!(only_max.matches(&context(None, "PA", &[])));
+    }
+
+    #[test]
+    fn tags_and_prefixed_groups() {
+        let compiled = CompiledTargeting::from_json(&json!({
+            "required_tags": ["vip"],
+            "excluded_tags": ["churned"],
+            "product_brands": ["CocaCola", "pepsi"],
+            "issuer_l1": ["restaurantes"]
+        }))
+        .unwrap();
+
+        let buyer = context(Some(30), "PA", &["VIP", "product_brand:cocacola", "issuer_l1:restaurantes"]);
+        assert!(compiled.matches(&buyer));
+        assert_eq!(compiled.matched_tags(&buyer), vec!["issuer_l1:restaurantes", "product_brand:cocacola", "vip"]);
+
+        assert!(!compiled.matches(&context(Some(30), "PA", &["vip", "product_brand:cocacola"])));
+        assert!(!compiled.matches(&context(
+            Some(30),
+            "PA",
+            &["vip", "churned", "product_brand:pepsi", "issuer_l1:restaurantes"]
+        )));
+    }
+
+    #[test]
+    fn candidate_tags_narrow_the_audience() {
+        let required = CompiledTargeting::from_json(&json!({"required_tags": ["vip"], "any_tags": ["a", "b"]})).unwrap();
+        assert_eq!(required.candidate_tags(), Some(vec!["vip".to_string()]));
+
+        let prefixed = CompiledTargeting::from_json(&json!({"any_tags": ["a", "b"], "product_l1": ["bebidas"]})).unwrap();
+        assert_eq!(prefixed.candidate_tags(), Some(vec!["product_l1:bebidas".to_string()]));
+
+        assert_eq!(CompiledTargeting::from_json(&json!({"min_age": 18})).unwrap().candidate_tags(), None);
+    }
+
+    #[test]
+    fn prefixed_values_use_tag_slug() {
+        assert_eq!(tag_value("  Vía  España "), "vía_españa");
+        let compiled = CompiledTargeting::from_json(&json!({"issuer_store_names": ["McDonalds Via España"]})).unwrap();
+        assert!(compiled.matches(&context(None, "PA", &["issuer_store_name:mcdonalds_via_españa"])));
+    }
+
+    #[test]
+    fn explains_each_condition() {
+        let compiled = CompiledTargeting::from_json(&json!({
+            "min_age": 18,
+            "countries": ["PA"],
+            "product_brands": ["cocacola"],
+            "excluded_tags": ["churned"]
+        }))
+        .unwrap();
+        let reasons = compiled.explain(&context(Some(30), "PA", &["product_brand:cocacola", "vip"]));
+
+        let rules: Vec<&str> = reasons.iter().map(|r| r.rule).collect();
+        assert_eq!(rules, vec!["age", "countries", "product_brands", "excluded_tags"]);
+        assert!(reasons.iter().all(|r| r.satisfied));
+        assert_eq!(reasons[2].matched_tags, vec!["product_brand:cocacola"]);
+        assert_eq!(reasons[2].message, "Por productos que has comprado");
+
+        let young = compiled.explain(&context(Some(16), "PA", &["churned"]));
+        assert!(!young[0].satisfied);
+        assert!(!young[3].satisfied);
+    }
+
+    #[test]
+    fn age_from_birth_date() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        assert_eq!(age_from_birth(Some("2000-10-19"), today), Some(25));
+        assert_eq!(age_from_birth(Some("2000-10-18"), today), Some(26));
+        assert_eq!(age_from_birth(Some("18/10/2000"), today), None);
+        assert_eq!(age_from_birth(None, today), None);
+    }
+
+    #[test]
+    fn cache_compiles_once_per_question_version() {
+        let cache = TargetingCache::default();
+        let id = Uuid::new_v4();
+        let v1 = Some(Utc::now());
+        let rules = json!({"min_age": 18});
+
+        let first = cache.get_or_compile(id, v1, &rules).unwrap();
+        let again = cache.get_or_compile(id, v1, &json!({"min_age": 99})).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        // Nueva versión: se recompila; si ya no valida, se oculta
+        let v2 = v1.map(|t| t + chrono::Duration::seconds(1));
+        assert!(cache.get_or_compile(id, v2, &json!({"min_Age": 18})).is_none());
+        assert!(cache.get_or_compile(id, v2, &rules).is_none());
+    }
+}
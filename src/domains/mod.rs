@@ -2,6 +2,10 @@ pub mod qr;
 pub mod ocr;
 pub mod rewards;
 pub mod invoices;
+pub mod gamification;
+pub mod surveys;
+pub mod lumimatch;
+pub mod notifications;
 
 // Re-export domain modules for easier access
 pub use qr as qr_service;
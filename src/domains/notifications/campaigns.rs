@@ -0,0 +1,952 @@
+//! Campañas de push segmentadas
+//!
+//! Una campaña define un segmento (tags vigentes de LumiMatch, nivel, última
+//! actividad, provincia), un mensaje con deep link y una hora de envío en
+//! hora de Panamá. Al empezar a enviarse la audiencia se congela en
+//! `push_campaign_recipients`; el envío avanza en lotes de `rate_per_second`
+//! por segundo, respeta el tope por usuario de
+//! `RateLimiter::check_notification_rate_limit` y revisa el estado de la
+//! campaña antes de cada lote, así que pausar o cancelar corta a mitad de
+//! envío. Cada destinatario recibe una notificación `promo` (in-app + push)
+//! con idempotency key por campaña: reanudar tras una caída no duplica.
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::America::Panama;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool};
+use std::collections::HashSet;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::domains::lumimatch::tagging::MIN_TARGETING_CONFIDENCE;
+use crate::services::rate_limiter_service::get_rate_limiter;
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+pub const DEFAULT_RATE_PER_SECOND: i32 = 20;
+pub const MAX_RATE_PER_SECOND: i32 = 500;
+pub const DEFAULT_CONVERSION_WINDOW_HOURS: i32 = 72;
+pub const MAX_CONVERSION_WINDOW_HOURS: i32 = 720;
+
+/// Tipo de notificación (y de tope por usuario) de las campañas
+pub const CAMPAIGN_NOTIFICATION_TYPE: &str = "promo";
+
+/// Sin latido en este tiempo, otra instancia puede retomar el envío
+const STALE_SENDING_MINUTES: i32 = 5;
+
+pub const STATUS_SCHEDULED: &str = "scheduled";
+pub const STATUS_SENDING: &str = "sending";
+pub const STATUS_PAUSED: &str = "paused";
+pub const STATUS_COMPLETED: &str = "completed";
+pub const STATUS_CANCELLED: &str = "cancelled";
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+/// Segmento de la campaña; todas las condiciones son opcionales (AND)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CampaignSegment {
+    /// El usuario debe tener todos estos tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_tags: Vec<String>,
+    /// Al menos uno de estos tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub any_tags: Vec<String>,
+    /// Ninguno de estos tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_level: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_level: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provinces: Vec<String>,
+    /// Con actividad (login o factura) en los últimos N días
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_within_days: Option<i32>,
+    /// Sin actividad hace al menos N días (reactivación)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inactive_for_days: Option<i32>,
+}
+
+/// Evento que cuenta como conversión de un destinatario
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversionGoal {
+    InvoiceSaved,
+    SurveyCompleted,
+    LumimatchAnswered,
+}
+
+impl ConversionGoal {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ConversionGoal::InvoiceSaved => "invoice_saved",
+            ConversionGoal::SurveyCompleted => "survey_completed",
+            ConversionGoal::LumimatchAnswered => "lumimatch_answered",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewCampaign {
+    pub name: String,
+    pub title: String,
+    pub body: String,
+    pub deep_link: Option<String>,
+    pub image_url: Option<String>,
+    #[serde(default)]
+    pub segment: CampaignSegment,
+    /// Hora local de Panamá (sin zona); ausente = enviar ya
+    pub send_at: Option<NaiveDateTime>,
+    pub rate_per_second: Option<i32>,
+    pub conversion_goal: Option<ConversionGoal>,
+    pub conversion_window_hours: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PushCampaign {
+    pub campaign_id: Uuid,
+    pub name: String,
+    pub title: String,
+    pub body: String,
+    pub deep_link: Option<String>,
+    pub image_url: Option<String>,
+    pub segment: Json<CampaignSegment>,
+    pub scheduled_at: DateTime<Utc>,
+    /// `scheduled_at` en hora de Panamá
+    pub scheduled_at_local: NaiveDateTime,
+    pub status: String,
+    pub rate_per_second: i32,
+    pub conversion_goal: Option<String>,
+    pub conversion_window_hours: i32,
+    pub audience_size: Option<i32>,
+    pub created_by: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DryRun {
+    pub audience_size: i64,
+    /// Parte de la audiencia con push activo y promociones permitidas
+    pub reachable_by_push: i64,
+    pub scheduled_at: DateTime<Utc>,
+    pub scheduled_at_local: NaiveDateTime,
+    pub rate_per_second: i32,
+    pub estimated_duration_seconds: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, FromRow)]
+pub struct CampaignStats {
+    pub audience: i64,
+    pub pending: i64,
+    pub sent: i64,
+    pub throttled: i64,
+    pub failed: i64,
+    pub cancelled: i64,
+    /// Push entregado a FCM por el worker de la cola
+    pub delivered: i64,
+    /// Notificación leída en la app
+    pub opened: i64,
+    pub converted: i64,
+    #[sqlx(skip)]
+    pub open_rate: Option<f64>,
+    #[sqlx(skip)]
+    pub conversion_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CampaignReport {
+    #[serde(flatten)]
+    pub campaign: PushCampaign,
+    pub stats: CampaignStats,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CampaignAction {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CampaignError {
+    #[error("{}", Text::new("api.campaign.invalid").with("reason", .0))]
+    Invalid(Text),
+
+    #[error("{}", Text::new("api.not_found.push_campaign"))]
+    NotFound,
+
+    #[error("{}", Text::new(.action.invalid_state_message()).with("status", .actual))]
+    InvalidState { action: CampaignAction, actual: String },
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for CampaignError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+/// Destinatarios del segmento. $1 provincias, $2/$3 nivel, $4 required,
+/// $5 any, $6 excluded, $7 activos en N días, $8 inactivos hace N días,
+/// $9 confianza mínima de los tags
+const SEGMENT_QUERY: &str = r#"
+    FROM public.dim_users u
+    LEFT JOIN gamification.user_status us ON us.user_id = u.id
+    LEFT JOIN LATERAL (
+        SELECT GREATEST(u.last_login_at, MAX(ih.process_date)) AS last_activity_at
+        FROM public.invoice_header ih
+        WHERE ih.user_id = u.id
+    ) act ON true
+    WHERE u.is_active = true
+      AND ($1::TEXT[] IS NULL OR lower(trim(u.province)) = ANY($1))
+      AND ($2::INT IS NULL OR COALESCE(us.current_level_id, 1) >= $2)
+      AND ($3::INT IS NULL OR COALESCE(us.current_level_id, 1) <= $3)
+      AND ($4::TEXT[] IS NULL OR (
+          SELECT COUNT(DISTINCT t.tag) FROM lumimatch.user_tags t
+          WHERE t.user_id = u.id AND t.tag = ANY($4) AND t.confidence >= $9
+            AND (t.expires_at IS NULL OR t.expires_at > NOW())
+      ) = cardinality($4))
+      AND ($5::TEXT[] IS NULL OR EXISTS (
+          SELECT 1 FROM lumimatch.user_tags t
+          WHERE t.user_id = u.id AND t.tag = ANY($5) AND t.confidence >= $9
+            AND (t.expires_at IS NULL OR t.expires_at > NOW())
+      ))
+      AND ($6::TEXT[] IS NULL OR NOT EXISTS (
+          SELECT 1 FROM lumimatch.user_tags t
+          WHERE t.user_id = u.id AND t.tag = ANY($6) AND t.confidence >= $9
+            AND (t.expires_at IS NULL OR t.expires_at > NOW())
+      ))
+      AND ($7::INT IS NULL OR act.last_activity_at >= NOW() - make_interval(days => $7))
+      AND ($8::INT IS NULL OR act.last_activity_at IS NULL
+           OR act.last_activity_at < NOW() - make_interval(days => $8))
+"#;
+
+const CAMPAIGN_COLUMNS: &str = r#"
+    campaign_id, name, title, body, deep_link, image_url, segment, scheduled_at,
+    (scheduled_at AT TIME ZONE 'America/Panama') AS scheduled_at_local,
+    status, rate_per_second, conversion_goal, conversion_window_hours, audience_size,
+    created_by, created_at, updated_at, started_at, finished_at
+"#;
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+/// Hora local de Panamá a UTC (Panamá no tiene horario de verano)
+pub fn panama_to_utc(local: NaiveDateTime) -> DateTime<Utc> {
+    match Panama.from_local_datetime(&local).earliest() {
+        Some(time) => time.with_timezone(&Utc),
+        None => Utc.from_utc_datetime(&local),
+    }
+}
+
+pub fn utc_to_panama(time: DateTime<Utc>) -> NaiveDateTime {
+    time.with_timezone(&Panama).naive_local()
+}
+
+fn normalized(values: &[String]) -> Vec<String> {
+    values.iter().map(|v| v.trim().to_lowercase()).collect()
+}
+
+impl CampaignSegment {
+    pub fn validate(&self) -> Result<(), Text> {
+        for (field, values) in [
+            ("required_tags", &self.required_tags),
+            ("any_tags", &self.any_tags),
+            ("excluded_tags", &self.excluded_tags),
+            ("provinces", &self.provinces),
+        ] {
+            if values.iter().any(|v| v.trim().is_empty()) {
+                return Err(Text::new("api.validation.empty_value").with("field", field));
+            }
+        }
+        let excluded: HashSet<String> = normalized(&self.excluded_tags).into_iter().collect();
+        if let Some(tag) = normalized(&self.required_tags).iter().find(|tag| excluded.contains(*tag)) {
+            return Err(Text::new("api.validation.tag_conflict").with("tag", tag));
+        }
+        if self.min_level.is_some_and(|l| l < 1) || self.max_level.is_some_and(|l| l < 1) {
+            return Err(Text::new("api.push_campaign.invalid_level"));
+        }
+        if let (Some(min), Some(max)) = (self.min_level, self.max_level) {
+            if min > max {
+                return Err(Text::new("api.validation.not_greater")
+                    .with("field", "min_level")
+                    .with("other", "max_level"));
+            }
+        }
+        if self.active_within_days.is_some_and(|d| d < 1) || self.inactive_for_days.is_some_and(|d| d < 1) {
+            return Err(Text::new("api.push_campaign.invalid_activity_days"));
+        }
+        if let (Some(active), Some(inactive)) = (self.active_within_days, self.inactive_for_days) {
+            if active <= inactive {
+                return Err(Text::new("api.validation.greater")
+                    .with("field", "active_within_days")
+                    .with("other", "inactive_for_days"));
+            }
+        }
+        Ok(())
+    }
+
+    fn list(values: &[String]) -> Option<Vec<String>> {
+        (!values.is_empty()).then(|| normalized(values))
+    }
+}
+
+impl NewCampaign {
+    /// Valida y devuelve la hora de envío en UTC
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<DateTime<Utc>, Text> {
+        if self.name.trim().is_empty() || self.title.trim().is_empty() || self.body.trim().is_empty() {
+            return Err(Text::new("api.validation.required").with("field", "name, title, body"));
+        }
+        if self.name.chars().count() > 120 {
+            return Err(Text::new("api.validation.max_length").with("field", "name").with("max", 120));
+        }
+        if self.title.chars().count() > 200 {
+            return Err(Text::new("api.validation.max_length").with("field", "title").with("max", 200));
+        }
+        if let Some(link) = &self.deep_link {
+            if link.len() > 255 || !(link.starts_with('/') || link.contains("://")) {
+                return Err(Text::new("api.push_campaign.invalid_deep_link"));
+            }
+        }
+        if self.rate_per_second.is_some_and(|rate| !(1..=MAX_RATE_PER_SECOND).contains(&rate)) {
+            return Err(Text::new("api.validation.range")
+                .with("field", "rate_per_second")
+                .with("min", 1)
+                .with("max", MAX_RATE_PER_SECOND));
+        }
+        if self
+            .conversion_window_hours
+            .is_some_and(|hours| !(1..=MAX_CONVERSION_WINDOW_HOURS).contains(&hours))
+        {
+            return Err(Text::new("api.validation.range")
+                .with("field", "conversion_window_hours")
+                .with("min", 1)
+                .with("max", MAX_CONVERSION_WINDOW_HOURS));
+        }
+        self.segment.validate()?;
+
+        let scheduled_at = self.send_at.map(panama_to_utc).unwrap_or(now);
+        if scheduled_at < now - chrono::Duration::minutes(1) {
+            return Err(Text::new("api.push_campaign.send_at_past"));
+        }
+        Ok(scheduled_at)
+    }
+}
+
+impl CampaignAction {
+    pub fn verb(self) -> &'static str {
+        match self {
+            CampaignAction::Pause => "pausar",
+            CampaignAction::Resume => "reanudar",
+            CampaignAction::Cancel => "cancelar",
+        }
+    }
+
+    fn invalid_state_message(self) -> &'static str {
+        match self {
+            CampaignAction::Pause => "api.push_campaign.cannot_pause",
+            CampaignAction::Resume => "api.push_campaign.cannot_resume",
+            CampaignAction::Cancel => "api.push_campaign.cannot_cancel",
+        }
+    }
+
+    /// Estados desde los que aplica y estado resultante
+    pub fn transition(self, current: &str) -> Result<&'static str, CampaignError> {
+        let allowed = match self {
+            CampaignAction::Pause => [STATUS_SCHEDULED, STATUS_SENDING].contains(&current),
+            CampaignAction::Resume => current == STATUS_PAUSED,
+            CampaignAction::Cancel => [STATUS_SCHEDULED, STATUS_SENDING, STATUS_PAUSED].contains(&current),
+        };
+        if !allowed {
+            return Err(CampaignError::InvalidState { action: self, actual: current.to_string() });
+        }
+        Ok(match self {
+            CampaignAction::Pause => STATUS_PAUSED,
+            CampaignAction::Resume => STATUS_SCHEDULED,
+            CampaignAction::Cancel => STATUS_CANCELLED,
+        })
+    }
+}
+
+impl CampaignStats {
+    /// Tasas sobre los enviados
+    pub fn with_rates(mut self) -> Self {
+        let rate = |count: i64| (self.sent > 0).then(|| (10_000.0 * count as f64 / self.sent as f64).round() / 100.0);
+        self.open_rate = rate(self.opened);
+        self.conversion_rate = rate(self.converted);
+        self
+    }
+}
+
+pub fn estimated_duration_seconds(audience: i64, rate_per_second: i32) -> i64 {
+    let rate = rate_per_second.max(1) as i64;
+    (audience + rate - 1) / rate
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct CampaignService {
+    db: PgPool,
+}
+
+impl CampaignService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(&self, campaign: &NewCampaign, created_by: i64) -> Result<PushCampaign, CampaignError> {
+        let scheduled_at = campaign.validate(Utc::now()).map_err(CampaignError::Invalid)?;
+
+        let created = sqlx::query_as::<_, PushCampaign>(&format!(
+            r#"
+            INSERT INTO public.push_campaigns
+                (name, title, body, deep_link, image_url, segment, scheduled_at,
+                 rate_per_second, conversion_goal, conversion_window_hours, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING {}
+            "#,
+            CAMPAIGN_COLUMNS
+        ))
+        .bind(campaign.name.trim())
+        .bind(campaign.title.trim())
+        .bind(campaign.body.trim())
+        .bind(&campaign.deep_link)
+        .bind(&campaign.image_url)
+        .bind(Json(&campaign.segment))
+        .bind(scheduled_at)
+        .bind(campaign.rate_per_second.unwrap_or(DEFAULT_RATE_PER_SECOND))
+        .bind(campaign.conversion_goal.map(ConversionGoal::as_str))
+        .bind(campaign.conversion_window_hours.unwrap_or(DEFAULT_CONVERSION_WINDOW_HOURS))
+        .bind(created_by)
+        .fetch_one(&self.db)
+        .await?;
+
+        info!(
+            "📣 Push campaign {} '{}' scheduled for {} (Panamá) by {}",
+            created.campaign_id, created.name, created.scheduled_at_local, created_by
+        );
+        Ok(created)
+    }
+
+    /// Tamaño de la audiencia sin crear ni enviar nada
+    pub async fn dry_run(&self, campaign: &NewCampaign) -> Result<DryRun, CampaignError> {
+        let scheduled_at = campaign.validate(Utc::now()).map_err(CampaignError::Invalid)?;
+        let rate_per_second = campaign.rate_per_second.unwrap_or(DEFAULT_RATE_PER_SECOND);
+
+        let (audience_size, reachable_by_push): (i64, i64) = self
+            .bind_segment(
+                sqlx::query_as(&format!(
+                    r#"
+                    SELECT COUNT(*),
+                           COUNT(*) FILTER (WHERE EXISTS (
+                               SELECT 1 FROM public.device_tokens d
+                               WHERE d.user_id = u.id AND d.is_active = true
+                           ) AND NOT EXISTS (
+                               SELECT 1 FROM public.notification_preferences p
+                               WHERE p.user_id = u.id AND p.promotions = false
+                           ))
+                    {}
+                    "#,
+                    SEGMENT_QUERY
+                )),
+                &campaign.segment,
+            )
+            .fetch_one(&self.db)
+            .await?;
+
+        Ok(DryRun {
+            audience_size,
+            reachable_by_push,
+            scheduled_at,
+            scheduled_at_local: utc_to_panama(scheduled_at),
+            rate_per_second,
+            estimated_duration_seconds: estimated_duration_seconds(audience_size, rate_per_second),
+        })
+    }
+
+    fn bind_segment<'q, O>(
+        &self,
+        query: sqlx::query::QueryAs<'q, sqlx::Postgres, O, sqlx::postgres::PgArguments>,
+        segment: &CampaignSegment,
+    ) -> sqlx::query::QueryAs<'q, sqlx::Postgres, O, sqlx::postgres::PgArguments> {
+        query
+            .bind(CampaignSegment::list(&segment.provinces))
+            .bind(segment.min_level)
+            .bind(segment.max_level)
+            .bind(CampaignSegment::list(&segment.required_tags))
+            .bind(CampaignSegment::list(&segment.any_tags))
+            .bind(CampaignSegment::list(&segment.excluded_tags))
+            .bind(segment.active_within_days)
+            .bind(segment.inactive_for_days)
+            .bind(MIN_TARGETING_CONFIDENCE)
+    }
+
+    pub async fn list(&self) -> Result<Vec<PushCampaign>, CampaignError> {
+        let campaigns = sqlx::query_as::<_, PushCampaign>(&format!(
+            "SELECT {} FROM public.push_campaigns ORDER BY scheduled_at DESC LIMIT 100",
+            CAMPAIGN_COLUMNS
+        ))
+        .fetch_all(&self.db)
+        .await?;
+        Ok(campaigns)
+    }
+
+    pub async fn get(&self, campaign_id: Uuid) -> Result<PushCampaign, CampaignError> {
+        sqlx::query_as::<_, PushCampaign>(&format!(
+            "SELECT {} FROM public.push_campaigns WHERE campaign_id = $1",
+            CAMPAIGN_COLUMNS
+        ))
+        .bind(campaign_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or(CampaignError::NotFound)
+    }
+
+    pub async fn report(&self, campaign_id: Uuid) -> Result<CampaignReport, CampaignError> {
+        let campaign = self.get(campaign_id).await?;
+        let stats = self.stats(campaign_id).await?;
+        Ok(CampaignReport { campaign, stats })
+    }
+
+    /// Entregas, aperturas y conversiones de una campaña. Todo sale de
+    /// `push_campaign_recipients`: la cola y las notificaciones se limpian
+    pub async fn stats(&self, campaign_id: Uuid) -> Result<CampaignStats, CampaignError> {
+        let stats = sqlx::query_as::<_, CampaignStats>(
+            r#"
+            SELECT COUNT(*) AS audience,
+                   COUNT(*) FILTER (WHERE r.status = 'pending') AS pending,
+                   COUNT(*) FILTER (WHERE r.status = 'sent') AS sent,
+                   COUNT(*) FILTER (WHERE r.status = 'throttled') AS throttled,
+                   COUNT(*) FILTER (WHERE r.status = 'failed') AS failed,
+                   COUNT(*) FILTER (WHERE r.status = 'cancelled') AS cancelled,
+                   COUNT(r.delivered_at) AS delivered,
+                   COUNT(r.opened_at) AS opened,
+                   COUNT(r.converted_at) AS converted
+            FROM public.push_campaign_recipients r
+            WHERE r.campaign_id = $1
+            "#,
+        )
+        .bind(campaign_id)
+        .fetch_one(&self.db)
+        .await?;
+        Ok(stats.with_rates())
+    }
+
+    /// Pausar, reanudar o cancelar. Un envío en curso lo nota en el
+    /// siguiente lote.
+    pub async fn apply(&self, campaign_id: Uuid, action: CampaignAction) -> Result<PushCampaign, CampaignError> {
+        let mut tx = self.db.begin().await?;
+
+        let current: String = sqlx::query_scalar("SELECT status FROM public.push_campaigns WHERE campaign_id = $1 FOR UPDATE")
+            .bind(campaign_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(CampaignError::NotFound)?;
+        let next = action.transition(&current)?;
+
+        sqlx::query(
+            r#"
+            UPDATE public.push_campaigns
+            SET status = $2,
+                updated_at = NOW(),
+                finished_at = CASE WHEN $2 = 'cancelled' THEN NOW() ELSE finished_at END
+            WHERE campaign_id = $1
+            "#,
+        )
+        .bind(campaign_id)
+        .bind(next)
+        .execute(&mut *tx)
+        .await?;
+
+        if action == CampaignAction::Cancel {
+            sqlx::query(
+                "UPDATE public.push_campaign_recipients SET status = 'cancelled' WHERE campaign_id = $1 AND status = 'pending'",
+            )
+            .bind(campaign_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        info!("📣 Push campaign {} {} ({} -> {})", campaign_id, action.verb(), current, next);
+        self.get(campaign_id).await
+    }
+
+    /// Reclama las campañas cuya hora llegó (y retoma las que quedaron a
+    /// medias sin latido) y lanza una tarea de envío por campaña, para que
+    /// una audiencia grande no retrase a las demás. Lo llama el job cada
+    /// minuto; devuelve cuántos envíos arrancó.
+    pub async fn run_due(&self) -> Result<usize, CampaignError> {
+        let mut started = 0;
+        while let Some(campaign) = self.claim_due().await? {
+            let service = Self::new(self.db.clone());
+            tokio::spawn(async move {
+                // Sin latido, otra pasada del job la retoma pasados STALE_SENDING_MINUTES
+                if let Err(e) = service.send(&campaign).await {
+                    error!("Push campaign {} send failed: {}", campaign.campaign_id, e);
+                }
+            });
+            started += 1;
+        }
+        Ok(started)
+    }
+
+    async fn claim_due(&self) -> Result<Option<PushCampaign>, CampaignError> {
+        let claimed = sqlx::query_as::<_, PushCampaign>(&format!(
+            r#"
+            UPDATE public.push_campaigns
+            SET status = 'sending', started_at = COALESCE(started_at, NOW()), updated_at = NOW()
+            WHERE campaign_id = (
+                SELECT campaign_id FROM public.push_campaigns
+                WHERE (status = 'scheduled' AND scheduled_at <= NOW())
+                   OR (status = 'sending' AND updated_at < NOW() - make_interval(mins => $1))
+                ORDER BY scheduled_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            CAMPAIGN_COLUMNS
+        ))
+        .bind(STALE_SENDING_MINUTES)
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(claimed)
+    }
+
+    async fn send(&self, campaign: &PushCampaign) -> Result<(), CampaignError> {
+        if campaign.audience_size.is_none() {
+            self.freeze_audience(campaign).await?;
+        }
+
+        let batch_size = campaign.rate_per_second.max(1) as i64;
+        let idempotency_key = format!("campaign_{}", campaign.campaign_id);
+        let payload = serde_json::json!({
+            "campaign_id": campaign.campaign_id,
+            "deep_link": campaign.deep_link,
+        });
+
+        loop {
+            let batch_started = std::time::Instant::now();
+
+            // Latido + control de pausa/cancelación antes de cada lote
+            let still_sending = sqlx::query(
+                "UPDATE public.push_campaigns SET updated_at = NOW() WHERE campaign_id = $1 AND status = 'sending'",
+            )
+            .bind(campaign.campaign_id)
+            .execute(&self.db)
+            .await?
+            .rows_affected()
+                > 0;
+            if !still_sending {
+                info!("📣 Push campaign {} stopped mid-send", campaign.campaign_id);
+                return Ok(());
+            }
+
+            let batch: Vec<i64> = sqlx::query_scalar(
+                r#"
+                SELECT user_id FROM public.push_campaign_recipients
+                WHERE campaign_id = $1 AND status = 'pending'
+                ORDER BY user_id
+                LIMIT $2
+                "#,
+            )
+            .bind(campaign.campaign_id)
+            .bind(batch_size)
+            .fetch_all(&self.db)
+            .await?;
+
+            if batch.is_empty() {
+                sqlx::query(
+                    r#"
+                    UPDATE public.push_campaigns
+                    SET status = 'completed', finished_at = NOW(), updated_at = NOW()
+                    WHERE campaign_id = $1 AND status = 'sending'
+                    "#,
+                )
+                .bind(campaign.campaign_id)
+                .execute(&self.db)
+                .await?;
+                info!("📣 Push campaign {} completed", campaign.campaign_id);
+                return Ok(());
+            }
+
+            for user_id in batch {
+                self.send_one(campaign, user_id, &idempotency_key, &payload).await?;
+            }
+
+            if let Some(rest) = std::time::Duration::from_secs(1).checked_sub(batch_started.elapsed()) {
+                tokio::time::sleep(rest).await;
+            }
+        }
+    }
+
+    async fn freeze_audience(&self, campaign: &PushCampaign) -> Result<(), CampaignError> {
+        let mut tx = self.db.begin().await?;
+        let inserted = self
+            .bind_segment(
+                sqlx::query_as::<_, (i64,)>(&format!(
+                    r#"
+                    WITH audience AS (
+                        INSERT INTO public.push_campaign_recipients (campaign_id, user_id)
+                        SELECT $10, u.id
+                        {}
+                        ON CONFLICT (campaign_id, user_id) DO NOTHING
+                        RETURNING 1
+                    )
+                    SELECT COUNT(*) FROM audience
+                    "#,
+                    SEGMENT_QUERY
+                )),
+                &campaign.segment,
+            )
+            .bind(campaign.campaign_id)
+            .fetch_one(&mut *tx)
+            .await?
+            .0;
+
+        sqlx::query(
+            r#"
+            UPDATE public.push_campaigns
+            SET audience_size = (SELECT COUNT(*) FROM public.push_campaign_recipients WHERE campaign_id = $1)
+            WHERE campaign_id = $1
+            "#,
+        )
+        .bind(campaign.campaign_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        info!("📣 Push campaign {} audience frozen: {} users", campaign.campaign_id, inserted);
+        Ok(())
+    }
+
+    async fn send_one(
+        &self,
+        campaign: &PushCampaign,
+        user_id: i64,
+        idempotency_key: &str,
+        payload: &serde_json::Value,
+    ) -> Result<(), CampaignError> {
+        // Tope por usuario (hora, promos por día, cooldown por tipo)
+        if let Some(limiter) = get_rate_limiter() {
+            match limiter.check_notification_rate_limit(user_id, CAMPAIGN_NOTIFICATION_TYPE).await {
+                Ok(false) => return self.mark(campaign.campaign_id, user_id, "throttled", None, None).await,
+                Ok(true) => {}
+                Err(e) => warn!("Notification rate limit check failed for user {}: {}", user_id, e),
+            }
+        }
+
+        let created = crate::api::notifications_v4::create_notification_from_rust(
+            &self.db,
+            user_id,
+            &campaign.title,
+            &campaign.body,
+            CAMPAIGN_NOTIFICATION_TYPE,
+            "normal",
+            campaign.deep_link.as_deref(),
+            campaign.image_url.as_deref(),
+            payload.clone(),
+            Some(idempotency_key),
+            true,
+        )
+        .await;
+
+        match created {
+            // None = ya existía (reintento tras una caída): se toma la existente
+            Ok(notification_id) => {
+                let notification_id = match notification_id {
+                    Some(id) => Some(id),
+                    None => {
+                        sqlx::query_scalar(
+                            "SELECT id FROM public.notifications WHERE user_id = $1 AND idempotency_key = $2",
+                        )
+                        .bind(user_id)
+                        .bind(idempotency_key)
+                        .fetch_optional(&self.db)
+                        .await?
+                    }
+                };
+                self.mark(campaign.campaign_id, user_id, "sent", notification_id, None).await
+            }
+            Err(e) => {
+                warn!("Push campaign {} failed for user {}: {}", campaign.campaign_id, user_id, e);
+                self.mark(campaign.campaign_id, user_id, "failed", None, Some(&e.to_string())).await
+            }
+        }
+    }
+
+    async fn mark(
+        &self,
+        campaign_id: Uuid,
+        user_id: i64,
+        status: &str,
+        notification_id: Option<i64>,
+        error_message: Option<&str>,
+    ) -> Result<(), CampaignError> {
+        sqlx::query(
+            r#"
+            UPDATE public.push_campaign_recipients
+            SET status = $3,
+                notification_id = $4,
+                error_message = $5,
+                sent_at = CASE WHEN $3 = 'sent' THEN NOW() ELSE sent_at END
+            WHERE campaign_id = $1 AND user_id = $2 AND status = 'pending'
+            "#,
+        )
+        .bind(campaign_id)
+        .bind(user_id)
+        .bind(status)
+        .bind(notification_id)
+        .bind(error_message)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Marca como convertidos a los destinatarios con esa meta dentro de la
+    /// ventana (idempotente: solo la primera conversión cuenta)
+    pub async fn record_conversion(
+        &self,
+        user_id: i64,
+        goal: ConversionGoal,
+        occurred_at: DateTime<Utc>,
+    ) -> Result<u64, CampaignError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE public.push_campaign_recipients r
+            SET converted_at = $3
+            FROM public.push_campaigns c
+            WHERE c.campaign_id = r.campaign_id
+              AND r.user_id = $1
+              AND r.status = 'sent'
+              AND r.converted_at IS NULL
+              AND c.conversion_goal = $2
+              AND $3 >= r.sent_at
+              AND $3 <= r.sent_at + make_interval(hours => c.conversion_window_hours)
+            "#,
+        )
+        .bind(user_id)
+        .bind(goal.as_str())
+        .bind(occurred_at)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Timelike};
+
+    fn campaign() -> NewCampaign {
+        NewCampaign {
+            name: "Black Friday".into(),
+            title: "🔥 Doble Lümis".into(),
+            body: "Sube tus facturas hoy".into(),
+            deep_link: Some("/invoices/upload".into()),
+            image_url: None,
+            segment: CampaignSegment::default(),
+            send_at: None,
+            rate_per_second: None,
+            conversion_goal: Some(ConversionGoal::InvoiceSaved),
+            conversion_window_hours: None,
+        }
+    }
+
+    #[test]
+    fn send_at_is_panama_time() {
+        let local = NaiveDate::from_ymd_opt(2026, 11, 27).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let utc = panama_to_utc(local);
+        assert_eq!(utc.hour(), 14);
+        assert_eq!(utc_to_panama(utc), local);
+
+        let now = panama_to_utc(local) - chrono::Duration::days(1);
+        let scheduled = NewCampaign { send_at: Some(local), ..campaign() }.validate(now).unwrap();
+        assert_eq!(scheduled, utc);
+
+        let late = panama_to_utc(local) + chrono::Duration::hours(1);
+        assert!(NewCampaign { send_at: Some(local), ..campaign() }.validate(late).is_err());
+        assert_eq!(campaign().validate(late).unwrap(), late);
+    }
+
+    #[test]
+    fn rejects_invalid_campaigns() {
+        let now = Utc::now();
+        assert!(NewCampaign { title: " ".into(), ..campaign() }.validate(now).is_err());
+        assert!(NewCampaign { deep_link: Some("invoices".into()), ..campaign() }.validate(now).is_err());
+        assert!(NewCampaign { rate_per_second: Some(0), ..campaign() }.validate(now).is_err());
+        assert!(NewCampaign { conversion_window_hours: Some(10_000), ..campaign() }.validate(now).is_err());
+    }
+
+    #[test]
+    fn validates_segment() {
+        let ok = CampaignSegment {
+            required_tags: vec!["vip".into()],
+            min_level: Some(2),
+            max_level: Some(5),
+            provinces: vec!["Panamá".into()],
+            active_within_days: Some(90),
+            inactive_for_days: Some(30),
+            ..Default::default()
+        };
+        assert!(ok.validate().is_ok());
+
+        let contradictory = CampaignSegment {
+            required_tags: vec!["VIP".into()],
+            excluded_tags: vec!["vip".into()],
+            ..Default::default()
+        };
+        assert!(contradictory.validate().is_err());
+        assert!(CampaignSegment { min_level: Some(5), max_level: Some(2), ..Default::default() }.validate().is_err());
+        assert!(CampaignSegment { active_within_days: Some(7), inactive_for_days: Some(30), ..Default::default() }
+            .validate()
+            .is_err());
+        assert!(CampaignSegment { any_tags: vec![" ".into()], ..Default::default() }.validate().is_err());
+
+        let unknown = serde_json::from_value::<CampaignSegment>(serde_json::json!({"min_lvl": 2}));
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn state_transitions() {
+        assert_eq!(CampaignAction::Pause.transition("sending").unwrap(), STATUS_PAUSED);
+        assert_eq!(CampaignAction::Resume.transition("paused").unwrap(), STATUS_SCHEDULED);
+        assert_eq!(CampaignAction::Cancel.transition("paused").unwrap(), STATUS_CANCELLED);
+        assert!(CampaignAction::Resume.transition("sending").is_err());
+        assert!(CampaignAction::Cancel.transition("completed").is_err());
+        assert!(CampaignAction::Pause.transition("cancelled").is_err());
+    }
+
+    #[test]
+    fn stats_rates_over_sent() {
+        let stats = CampaignStats { audience: 10, sent: 8, opened: 2, converted: 1, ..Default::default() }.with_rates();
+        assert_eq!(stats.open_rate, Some(25.0));
+        assert_eq!(stats.conversion_rate, Some(12.5));
+        assert_eq!(CampaignStats::default().with_rates().open_rate, None);
+    }
+
+    #[test]
+    fn duration_rounds_up() {
+        assert_eq!(estimated_duration_seconds(0, 20), 0);
+        assert_eq!(estimated_duration_seconds(41, 20), 3);
+        assert_eq!(estimated_duration_seconds(5, 0), 5);
+    }
+}
@@ -0,0 +1,10 @@
+pub mod campaigns;
+pub mod notifier;
+pub mod preferences;
+pub mod transports;
+
+// Re-exports para facilitar imports
+pub use campaigns::{CampaignAction, CampaignError, CampaignService, NewCampaign, PushCampaign};
+pub use notifier::{Channel, DeliveryReport, LogicalNotification, Notifier, NotifierError, Transport};
+pub use preferences::{Delivery, NotificationPreferences, PreferenceError, PreferenceService, PreferencesUpdate};
+pub use transports::{get_notifier, init_notifier};
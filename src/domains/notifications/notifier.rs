@@ -0,0 +1,835 @@
+//! Notificador multicanal
+//!
+//! Un solo punto de entrada para avisar a un usuario: el llamador describe la
+//! notificación lógica (ids del catálogo i18n, tipo, prioridad y una clave de
+//! dedupe) y el `Notifier` decide los canales. La notificación in-app se crea
+//! siempre; para llegar fuera de la app se intenta un canal externo tras otro
+//! (push → WhatsApp → email por defecto) hasta que uno funcione, saltando los
+//! que el usuario no tiene (sin token FCM, sin WhatsApp, sin email).
+//!
+//! Las preferencias de `preferences` mandan: una categoría apagada deja solo
+//! la in-app, y en horas de silencio solo se usa el push (que espera a que
+//! terminen) en vez de escribir por WhatsApp o email de madrugada.
+//!
+//! Cada intento queda en el log de entregas por canal. Con la misma clave de
+//! dedupe, un canal externo que ya entregó impide volver a escribir por otro.
+//! Antes de tocar cualquier canal se reclama la clave en el log (con un lease
+//! por si el proceso muere): dos entregas concurrentes de la misma clave no
+//! pueden leer el log a la vez y escribir ambas.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use super::preferences::{Delivery, NotificationPreferences};
+use crate::shared::i18n::{self, Locale};
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+/// Orden de canales externos cuando el llamador no pide otro
+pub const DEFAULT_EXTERNAL_CHANNELS: [Channel; 3] = [Channel::Push, Channel::WhatsApp, Channel::Email];
+
+/// Los avisos de canje se usan en caja: un email llegaría tarde
+const REDEMPTION_CHANNELS: [Channel; 2] = [Channel::Push, Channel::WhatsApp];
+
+/// Tiempo que una entrega retiene su clave si el proceso muere sin liberarla
+pub const CLAIM_LEASE_SECS: i64 = 120;
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    InApp,
+    Push,
+    #[serde(rename = "whatsapp")]
+    WhatsApp,
+    Email,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Sent,
+    Failed,
+    Skipped,
+}
+
+/// Lo que el llamador quiere comunicar, independiente del canal
+#[derive(Debug, Clone)]
+pub struct LogicalNotification {
+    pub user_id: i64,
+    /// Tipo in-app (`reward`, `promo`, ...); define la categoría de preferencias
+    pub notification_type: String,
+    /// `low`, `normal`, `high` o `urgent`
+    pub priority: String,
+    pub title_id: String,
+    pub body_id: String,
+    pub params: Vec<(String, String)>,
+    /// Elige la forma plural del cuerpo
+    pub count: Option<i64>,
+    pub action_url: Option<String>,
+    pub payload: serde_json::Value,
+    /// Misma clave = misma notificación (reintentos, eventos repetidos)
+    pub dedupe_key: String,
+    /// Canales externos en orden de preferencia
+    pub channels: Vec<Channel>,
+    pub in_app: bool,
+}
+
+/// A quién se entrega y por dónde se le puede alcanzar
+#[derive(Debug, Clone)]
+pub struct Recipient {
+    pub user_id: i64,
+    pub locale: Locale,
+    pub whatsapp_id: Option<String>,
+    pub email: Option<String>,
+    pub has_push_token: bool,
+    pub preferences: NotificationPreferences,
+}
+
+/// Texto ya renderizado en el idioma del usuario
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedMessage {
+    pub title: String,
+    pub body: String,
+    pub notification_type: String,
+    pub priority: String,
+    pub action_url: Option<String>,
+    pub payload: serde_json::Value,
+    pub dedupe_key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryPlan {
+    pub in_app: bool,
+    /// Canales externos a intentar, en orden, hasta que uno entregue
+    pub chain: Vec<Channel>,
+    pub skipped: Vec<(Channel, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeliveryAttempt {
+    pub dedupe_key: String,
+    pub user_id: i64,
+    pub channel: Channel,
+    pub status: DeliveryStatus,
+    pub detail: Option<String>,
+    pub attempted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryReport {
+    pub dedupe_key: String,
+    /// Canal externo que entregó (None = solo in-app o nada)
+    pub delivered_via: Option<Channel>,
+    pub attempts: Vec<DeliveryAttempt>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransportError {
+    #[error("Destinatario no alcanzable: {0}")]
+    Unreachable(String),
+
+    #[error("Fallo del proveedor: {0}")]
+    Failed(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NotifierError {
+    #[error("Usuario {0} no encontrado")]
+    UnknownRecipient(i64),
+
+    #[error("Entrega en curso para la clave {0}")]
+    InProgress(String),
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for NotifierError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+/// Un canal de salida (FCM, WhatsApp Cloud API, SMTP, bandeja in-app)
+#[async_trait]
+pub trait Transport: Send + Sync {
+    fn channel(&self) -> Channel;
+
+    async fn send(&self, recipient: &Recipient, message: &RenderedMessage) -> Result<(), TransportError>;
+}
+
+/// Log de entregas por canal
+#[async_trait]
+pub trait DeliveryLog: Send + Sync {
+    /// Reserva la clave para una entrega; false si otra la tiene tomada.
+    /// Debe ser atómico: dos llamadas concurrentes no pueden ganar ambas.
+    async fn claim(&self, dedupe_key: &str, user_id: i64) -> Result<bool, NotifierError>;
+
+    /// Libera la clave al terminar (haya entregado o no)
+    async fn release(&self, dedupe_key: &str) -> Result<(), NotifierError>;
+
+    /// Canales que ya entregaron esta clave
+    async fn delivered_channels(&self, dedupe_key: &str) -> Result<Vec<Channel>, NotifierError>;
+
+    async fn record(&self, attempt: &DeliveryAttempt) -> Result<(), NotifierError>;
+}
+
+/// Resuelve el destinatario de un usuario
+#[async_trait]
+pub trait RecipientDirectory: Send + Sync {
+    async fn recipient(&self, user_id: i64) -> Result<Option<Recipient>, NotifierError>;
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+impl Channel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Channel::InApp => "in_app",
+            Channel::Push => "push",
+            Channel::WhatsApp => "whatsapp",
+            Channel::Email => "email",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "in_app" => Some(Channel::InApp),
+            "push" => Some(Channel::Push),
+            "whatsapp" => Some(Channel::WhatsApp),
+            "email" => Some(Channel::Email),
+            _ => None,
+        }
+    }
+}
+
+impl DeliveryStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeliveryStatus::Sent => "sent",
+            DeliveryStatus::Failed => "failed",
+            DeliveryStatus::Skipped => "skipped",
+        }
+    }
+}
+
+impl LogicalNotification {
+    pub fn new(user_id: i64, notification_type: &str, title_id: &str, body_id: &str, dedupe_key: &str) -> Self {
+        Self {
+            user_id,
+            notification_type: notification_type.to_string(),
+            priority: "normal".to_string(),
+            title_id: title_id.to_string(),
+            body_id: body_id.to_string(),
+            params: Vec::new(),
+            count: None,
+            action_url: None,
+            payload: serde_json::json!({}),
+            dedupe_key: dedupe_key.to_string(),
+            channels: DEFAULT_EXTERNAL_CHANNELS.to_vec(),
+            in_app: true,
+        }
+    }
+
+    pub fn param(mut self, name: &str, value: impl Display) -> Self {
+        self.params.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn priority(mut self, priority: &str) -> Self {
+        self.priority = priority.to_string();
+        self
+    }
+
+    pub fn count(mut self, count: i64) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn action_url(mut self, url: &str) -> Self {
+        self.action_url = Some(url.to_string());
+        self
+    }
+
+    pub fn payload(mut self, payload: serde_json::Value) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// Canales externos en orden; vacío = solo in-app
+    pub fn channels(mut self, channels: &[Channel]) -> Self {
+        self.channels = channels.iter().copied().filter(|c| *c != Channel::InApp).collect();
+        self
+    }
+
+    pub fn without_in_app(mut self) -> Self {
+        self.in_app = false;
+        self
+    }
+
+    pub fn render(&self, locale: Locale) -> RenderedMessage {
+        let params: Vec<(&str, &dyn Display)> =
+            self.params.iter().map(|(name, value)| (name.as_str(), value as &dyn Display)).collect();
+        let body = match self.count {
+            Some(count) => i18n::render_count(locale, &self.body_id, count, &params),
+            None => i18n::render(locale, &self.body_id, &params),
+        };
+        RenderedMessage {
+            title: i18n::render(locale, &self.title_id, &params),
+            body,
+            notification_type: self.notification_type.clone(),
+            priority: self.priority.clone(),
+            action_url: self.action_url.clone(),
+            payload: self.payload.clone(),
+            dedupe_key: self.dedupe_key.clone(),
+        }
+    }
+}
+
+/// Variante del mensaje de canje confirmado según el nombre de la oferta
+pub fn redemption_confirmed_kind(offer_name: &str) -> &'static str {
+    let offer = offer_name.to_lowercase();
+    if offer.contains("café") || offer.contains("coffee") {
+        "coffee"
+    } else if offer.contains("comida") || offer.contains("almuerzo") || offer.contains("cena") {
+        "food"
+    } else if offer.contains("descuento") || offer.contains('%') {
+        "discount"
+    } else if offer.contains("gratis") || offer.contains("free") {
+        "free"
+    } else {
+        "default"
+    }
+}
+
+/// Avisos de canje: sin in-app (el canje ya aparece en la app) y solo por
+/// canales inmediatos
+impl LogicalNotification {
+    pub fn redemption_created(user_id: i64, redemption_id: Uuid, offer_name: &str, redemption_code: &str) -> Self {
+        Self::new(
+            user_id,
+            "redemption_created",
+            "push.redemption_created.title",
+            "push.redemption_created.body",
+            &format!("redemption_created:{}", redemption_id),
+        )
+        .param("code", redemption_code)
+        .payload(serde_json::json!({
+            "redemption_id": redemption_id.to_string(),
+            "offer_name": offer_name,
+            "redemption_code": redemption_code,
+        }))
+        .channels(&REDEMPTION_CHANNELS)
+        .without_in_app()
+    }
+
+    pub fn redemption_confirmed(user_id: i64, redemption_id: Uuid, offer_name: &str) -> Self {
+        let kind = redemption_confirmed_kind(offer_name);
+        Self::new(
+            user_id,
+            "redemption_confirmed",
+            &format!("push.redemption_confirmed.{}.title", kind),
+            &format!("push.redemption_confirmed.{}.body", kind),
+            &format!("redemption_confirmed:{}", redemption_id),
+        )
+        .priority("high")
+        .param("offer", offer_name)
+        .payload(serde_json::json!({
+            "redemption_id": redemption_id.to_string(),
+            "offer_name": offer_name,
+        }))
+        .channels(&REDEMPTION_CHANNELS)
+        .without_in_app()
+    }
+
+    pub fn redemption_expiring(user_id: i64, redemption_id: Uuid, offer_name: &str, minutes_remaining: i64) -> Self {
+        Self::new(
+            user_id,
+            "redemption_expiring",
+            "push.redemption_expiring.title",
+            "push.redemption_expiring.body",
+            &format!("redemption_expiring:{}", redemption_id),
+        )
+        .priority("high")
+        .count(minutes_remaining)
+        .param("offer", offer_name)
+        .payload(serde_json::json!({
+            "redemption_id": redemption_id.to_string(),
+            "offer_name": offer_name,
+            "minutes_remaining": minutes_remaining,
+        }))
+        .channels(&REDEMPTION_CHANNELS)
+        .without_in_app()
+    }
+}
+
+impl Recipient {
+    /// Si el usuario tiene el canal (token FCM, WhatsApp, email)
+    pub fn reachable(&self, channel: Channel) -> Result<(), &'static str> {
+        let ok = match channel {
+            Channel::InApp => true,
+            Channel::Push => self.has_push_token,
+            Channel::WhatsApp => self.whatsapp_id.as_deref().is_some_and(|id| !id.is_empty()),
+            Channel::Email => self.email.as_deref().is_some_and(|email| email.contains('@')),
+        };
+        match (ok, channel) {
+            (true, _) => Ok(()),
+            (false, Channel::Push) => Err("Sin token FCM activo"),
+            (false, Channel::WhatsApp) => Err("Sin WhatsApp registrado"),
+            (false, _) => Err("Sin email registrado"),
+        }
+    }
+}
+
+/// Canales a intentar según preferencias, alcance y entregas previas
+pub fn plan(
+    recipient: &Recipient,
+    notification: &LogicalNotification,
+    delivery: Delivery,
+    already_delivered: &[Channel],
+) -> DeliveryPlan {
+    let in_app = notification.in_app && !already_delivered.contains(&Channel::InApp);
+    let mut chain = Vec::new();
+    let mut skipped = Vec::new();
+
+    let delivered_externally = already_delivered.iter().find(|c| **c != Channel::InApp);
+    for &channel in &notification.channels {
+        if chain.contains(&channel) || skipped.iter().any(|(c, _)| *c == channel) {
+            continue;
+        }
+        let reason = if let Some(previous) = delivered_externally {
+            Some(format!("Ya entregada por {}", previous.as_str()))
+        } else {
+            match delivery {
+                Delivery::Suppress(reason) => Some(reason.to_string()),
+                // El push espera al final de las horas de silencio; los demás escribirían ya
+                Delivery::Defer(_) if channel != Channel::Push => Some("Horas de silencio".to_string()),
+                _ => recipient.reachable(channel).err().map(str::to_string),
+            }
+        };
+        match reason {
+            Some(reason) => skipped.push((channel, reason)),
+            None => chain.push(channel),
+        }
+    }
+
+    DeliveryPlan { in_app, chain, skipped }
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct Notifier {
+    transports: HashMap<Channel, Arc<dyn Transport>>,
+    log: Arc<dyn DeliveryLog>,
+    directory: Arc<dyn RecipientDirectory>,
+}
+
+impl Notifier {
+    pub fn new(directory: Arc<dyn RecipientDirectory>, log: Arc<dyn DeliveryLog>) -> Self {
+        Self { transports: HashMap::new(), log, directory }
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transports.insert(transport.channel(), transport);
+        self
+    }
+
+    /// Entrega a un usuario buscando sus datos de contacto y preferencias
+    pub async fn notify(&self, notification: &LogicalNotification) -> Result<DeliveryReport, NotifierError> {
+        let recipient = self
+            .directory
+            .recipient(notification.user_id)
+            .await?
+            .ok_or(NotifierError::UnknownRecipient(notification.user_id))?;
+        self.deliver(&recipient, notification, Utc::now()).await
+    }
+
+    /// Entrega con la clave reclamada; otra entrega en curso de la misma clave
+    /// devuelve `InProgress` sin tocar ningún canal
+    pub async fn deliver(
+        &self,
+        recipient: &Recipient,
+        notification: &LogicalNotification,
+        now: DateTime<Utc>,
+    ) -> Result<DeliveryReport, NotifierError> {
+        if !self.log.claim(&notification.dedupe_key, recipient.user_id).await? {
+            return Err(NotifierError::InProgress(notification.dedupe_key.clone()));
+        }
+        let result = self.deliver_claimed(recipient, notification, now).await;
+        self.log.release(&notification.dedupe_key).await?;
+        result
+    }
+
+    async fn deliver_claimed(
+        &self,
+        recipient: &Recipient,
+        notification: &LogicalNotification,
+        now: DateTime<Utc>,
+    ) -> Result<DeliveryReport, NotifierError> {
+        let already = self.log.delivered_channels(&notification.dedupe_key).await?;
+        let delivery = recipient.preferences.decide(&notification.notification_type, &notification.priority, now);
+        let plan = plan(recipient, notification, delivery, &already);
+        let message = notification.render(recipient.locale);
+
+        let mut report = DeliveryReport {
+            dedupe_key: notification.dedupe_key.clone(),
+            delivered_via: None,
+            attempts: Vec::new(),
+        };
+
+        if plan.in_app {
+            self.attempt(recipient, &message, Channel::InApp, now, &mut report).await?;
+        }
+        for (channel, reason) in plan.skipped {
+            let attempt = self.attempt_record(recipient, &message, channel, DeliveryStatus::Skipped, Some(reason), now);
+            self.log.record(&attempt).await?;
+            report.attempts.push(attempt);
+        }
+        for channel in plan.chain {
+            if self.attempt(recipient, &message, channel, now, &mut report).await? {
+                report.delivered_via = Some(channel);
+                break;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Intenta un canal y lo deja en el log; true si entregó
+    async fn attempt(
+        &self,
+        recipient: &Recipient,
+        message: &RenderedMessage,
+        channel: Channel,
+        now: DateTime<Utc>,
+        report: &mut DeliveryReport,
+    ) -> Result<bool, NotifierError> {
+        let (status, detail) = match self.transports.get(&channel) {
+            None => (DeliveryStatus::Skipped, Some("Canal no configurado".to_string())),
+            Some(transport) => match transport.send(recipient, message).await {
+                Ok(()) => (DeliveryStatus::Sent, None),
+                Err(e @ TransportError::Unreachable(_)) => (DeliveryStatus::Skipped, Some(e.to_string())),
+                Err(e) => (DeliveryStatus::Failed, Some(e.to_string())),
+            },
+        };
+        let attempt = self.attempt_record(recipient, message, channel, status, detail, now);
+        self.log.record(&attempt).await?;
+        report.attempts.push(attempt);
+        Ok(status == DeliveryStatus::Sent)
+    }
+
+    fn attempt_record(
+        &self,
+        recipient: &Recipient,
+        message: &RenderedMessage,
+        channel: Channel,
+        status: DeliveryStatus,
+        detail: Option<String>,
+        now: DateTime<Utc>,
+    ) -> DeliveryAttempt {
+        DeliveryAttempt {
+            dedupe_key: message.dedupe_key.clone(),
+            user_id: recipient.user_id,
+            channel,
+            status,
+            detail,
+            attempted_at: now,
+        }
+    }
+}
+
+// ======================================================================
+// MOCKS (pruebas y desarrollo local)
+// ======================================================================
+
+/// Transporte que guarda lo enviado y responde lo configurado
+pub struct MockTransport {
+    channel: Channel,
+    failure: Option<String>,
+    sent: Mutex<Vec<(i64, RenderedMessage)>>,
+}
+
+impl MockTransport {
+    pub fn new(channel: Channel) -> Arc<Self> {
+        Arc::new(Self { channel, failure: None, sent: Mutex::new(Vec::new()) })
+    }
+
+    pub fn failing(channel: Channel, error: &str) -> Arc<Self> {
+        Arc::new(Self { channel, failure: Some(error.to_string()), sent: Mutex::new(Vec::new()) })
+    }
+
+    pub fn sent(&self) -> Vec<(i64, RenderedMessage)> {
+        self.sent.lock().map(|sent| sent.clone()).unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl Transport for MockTransport {
+    fn channel(&self) -> Channel {
+        self.channel
+    }
+
+    async fn send(&self, recipient: &Recipient, message: &RenderedMessage) -> Result<(), TransportError> {
+        if let Some(error) = &self.failure {
+            return Err(TransportError::Failed(error.clone()));
+        }
+        if let Ok(mut sent) = self.sent.lock() {
+            sent.push((recipient.user_id, message.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Log de entregas en memoria
+#[derive(Default)]
+pub struct MemoryDeliveryLog {
+    attempts: Mutex<Vec<DeliveryAttempt>>,
+    claims: Mutex<HashSet<String>>,
+}
+
+impl MemoryDeliveryLog {
+    pub fn attempts(&self) -> Vec<DeliveryAttempt> {
+        self.attempts.lock().map(|attempts| attempts.clone()).unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl DeliveryLog for MemoryDeliveryLog {
+    async fn claim(&self, dedupe_key: &str, _user_id: i64) -> Result<bool, NotifierError> {
+        Ok(self.claims.lock().map(|mut claims| claims.insert(dedupe_key.to_string())).unwrap_or(false))
+    }
+
+    async fn release(&self, dedupe_key: &str) -> Result<(), NotifierError> {
+        if let Ok(mut claims) = self.claims.lock() {
+            claims.remove(dedupe_key);
+        }
+        Ok(())
+    }
+
+    async fn delivered_channels(&self, dedupe_key: &str) -> Result<Vec<Channel>, NotifierError> {
+        Ok(self
+            .attempts()
+            .into_iter()
+            .filter(|a| a.dedupe_key == dedupe_key && a.status == DeliveryStatus::Sent)
+            .map(|a| a.channel)
+            .collect())
+    }
+
+    async fn record(&self, attempt: &DeliveryAttempt) -> Result<(), NotifierError> {
+        if let Ok(mut attempts) = self.attempts.lock() {
+            attempts.push(attempt.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Directorio fijo de destinatarios
+#[derive(Default)]
+pub struct MemoryDirectory {
+    recipients: HashMap<i64, Recipient>,
+}
+
+impl MemoryDirectory {
+    pub fn with(mut self, recipient: Recipient) -> Self {
+        self.recipients.insert(recipient.user_id, recipient);
+        self
+    }
+}
+
+#[async_trait]
+impl RecipientDirectory for MemoryDirectory {
+    async fn recipient(&self, user_id: i64) -> Result<Option<Recipient>, NotifierError> {
+        Ok(self.recipients.get(&user_id).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, TimeZone};
+
+    fn recipient() -> Recipient {
+        Recipient {
+            user_id: 7,
+            locale: Locale::Es,
+            whatsapp_id: Some("50760000000".into()),
+            email: Some("ana@example.com".into()),
+            has_push_token: true,
+            preferences: NotificationPreferences::default(),
+        }
+    }
+
+    fn notification() -> LogicalNotification {
+        LogicalNotification::new(7, "reward", "notification.raffle_won.title", "notification.raffle_won.body", "raffle_won:3:7")
+            .param("ticket", 17)
+            .param("raffle", "Tómbola de Cash")
+            .param("prize", "$100")
+    }
+
+    fn noon() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 18, 17, 0, 0).unwrap()
+    }
+
+    struct Setup {
+        notifier: Notifier,
+        log: Arc<MemoryDeliveryLog>,
+        in_app: Arc<MockTransport>,
+        push: Arc<MockTransport>,
+        whatsapp: Arc<MockTransport>,
+        email: Arc<MockTransport>,
+    }
+
+    fn setup(push: Arc<MockTransport>) -> Setup {
+        let log = Arc::new(MemoryDeliveryLog::default());
+        let directory = Arc::new(MemoryDirectory::default().with(recipient()));
+        let in_app = MockTransport::new(Channel::InApp);
+        let whatsapp = MockTransport::new(Channel::WhatsApp);
+        let email = MockTransport::new(Channel::Email);
+        let notifier = Notifier::new(directory, log.clone())
+            .with_transport(in_app.clone())
+            .with_transport(push.clone())
+            .with_transport(whatsapp.clone())
+            .with_transport(email.clone());
+        Setup { notifier, log, in_app, push, whatsapp, email }
+    }
+
+    #[test]
+    fn plans_reachable_channels_in_order() {
+        let mut r = recipient();
+        r.has_push_token = false;
+        let p = plan(&r, &notification(), Delivery::Now, &[]);
+        assert!(p.in_app);
+        assert_eq!(p.chain, vec![Channel::WhatsApp, Channel::Email]);
+        assert_eq!(p.skipped, vec![(Channel::Push, "Sin token FCM activo".to_string())]);
+
+        let only_email = notification().channels(&[Channel::Email, Channel::InApp, Channel::Email]);
+        assert_eq!(plan(&recipient(), &only_email, Delivery::Now, &[]).chain, vec![Channel::Email]);
+    }
+
+    #[test]
+    fn plan_respects_preferences_and_previous_deliveries() {
+        let suppressed = plan(&recipient(), &notification(), Delivery::Suppress("Desactivado"), &[]);
+        assert!(suppressed.in_app && suppressed.chain.is_empty());
+        assert_eq!(suppressed.skipped.len(), 3);
+
+        let quiet = plan(&recipient(), &notification(), Delivery::Defer(noon()), &[]);
+        assert_eq!(quiet.chain, vec![Channel::Push]);
+
+        let repeated = plan(&recipient(), &notification(), Delivery::Now, &[Channel::InApp, Channel::WhatsApp]);
+        assert!(!repeated.in_app && repeated.chain.is_empty());
+        assert!(repeated.skipped.iter().all(|(_, reason)| reason == "Ya entregada por whatsapp"));
+    }
+
+    #[test]
+    fn renders_in_recipient_locale() {
+        let es = notification().render(Locale::Es);
+        let en = notification().render(Locale::En);
+        assert_eq!(es.body, "Tu boleto #17 ganó en \"Tómbola de Cash\": $100");
+        assert!(en.title.contains("raffle"));
+        assert_eq!(Channel::parse(Channel::WhatsApp.as_str()), Some(Channel::WhatsApp));
+        assert_eq!(serde_json::to_value(Channel::WhatsApp).unwrap(), "whatsapp");
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_push_fails() {
+        let s = setup(MockTransport::failing(Channel::Push, "InvalidToken: UNREGISTERED"));
+        let report = s.notifier.notify(&notification()).await.unwrap();
+
+        assert_eq!(report.delivered_via, Some(Channel::WhatsApp));
+        assert_eq!(s.in_app.sent().len(), 1);
+        assert_eq!(s.whatsapp.sent().len(), 1);
+        assert!(s.email.sent().is_empty());
+        let statuses: Vec<_> = s.log.attempts().iter().map(|a| (a.channel, a.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (Channel::InApp, DeliveryStatus::Sent),
+                (Channel::Push, DeliveryStatus::Failed),
+                (Channel::WhatsApp, DeliveryStatus::Sent),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn deduplicates_across_channels() {
+        let s = setup(MockTransport::new(Channel::Push));
+        let first = s.notifier.notify(&notification()).await.unwrap();
+        let second = s.notifier.notify(&notification()).await.unwrap();
+
+        assert_eq!(first.delivered_via, Some(Channel::Push));
+        assert_eq!(second.delivered_via, None);
+        assert_eq!(s.push.sent().len(), 1);
+        assert_eq!(s.in_app.sent().len(), 1);
+        assert!(second.attempts.iter().all(|a| a.status == DeliveryStatus::Skipped));
+    }
+
+    #[tokio::test]
+    async fn concurrent_delivery_of_a_claimed_key_is_rejected() {
+        let s = setup(MockTransport::new(Channel::Push));
+        assert!(s.log.claim("raffle_won:3:7", 7).await.unwrap());
+
+        let busy = s.notifier.notify(&notification()).await;
+        assert!(matches!(busy, Err(NotifierError::InProgress(key)) if key == "raffle_won:3:7"));
+        assert!(s.push.sent().is_empty() && s.in_app.sent().is_empty());
+
+        s.log.release("raffle_won:3:7").await.unwrap();
+        let report = s.notifier.notify(&notification()).await.unwrap();
+        assert_eq!(report.delivered_via, Some(Channel::Push));
+        // La clave queda libre para reintentos, que el log deduplica
+        assert!(s.log.claim("raffle_won:3:7", 7).await.unwrap());
+    }
+
+    #[test]
+    fn redemption_notifications_skip_in_app_and_email() {
+        let id = Uuid::nil();
+        let created = LogicalNotification::redemption_created(7, id, "Café grande", "LUM-4821");
+        assert!(!created.in_app);
+        assert_eq!(created.channels, REDEMPTION_CHANNELS.to_vec());
+        assert!(created.render(Locale::Es).body.contains("LUM-4821"));
+
+        let confirmed = LogicalNotification::redemption_confirmed(7, id, "Café grande");
+        assert_eq!(confirmed.title_id, "push.redemption_confirmed.coffee.title");
+        assert_eq!(confirmed.dedupe_key, format!("redemption_confirmed:{}", id));
+        assert_eq!(redemption_confirmed_kind("2x1 en hamburguesas"), "default");
+        assert_eq!(redemption_confirmed_kind("20% off"), "discount");
+
+        let expiring = LogicalNotification::redemption_expiring(7, id, "Café grande", 3);
+        assert_eq!(expiring.count, Some(3));
+    }
+
+    #[tokio::test]
+    async fn quiet_hours_only_use_push() {
+        let s = setup(MockTransport::failing(Channel::Push, "FCM down"));
+        let mut r = recipient();
+        r.preferences.quiet_hours_start = NaiveTime::from_hms_opt(22, 0, 0);
+        r.preferences.quiet_hours_end = NaiveTime::from_hms_opt(7, 0, 0);
+        // 03:00 en Panamá
+        let night = Utc.with_ymd_and_hms(2026, 10, 18, 8, 0, 0).unwrap();
+
+        let report = s.notifier.deliver(&r, &notification(), night).await.unwrap();
+        assert_eq!(report.delivered_via, None);
+        assert!(s.whatsapp.sent().is_empty() && s.email.sent().is_empty());
+        assert_eq!(s.in_app.sent().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unknown_recipient_is_an_error() {
+        let s = setup(MockTransport::new(Channel::Push));
+        let other = LogicalNotification::new(99, "system", "api.forbidden", "api.forbidden", "x");
+        assert!(matches!(s.notifier.notify(&other).await, Err(NotifierError::UnknownRecipient(99))));
+    }
+}
@@ -0,0 +1,448 @@
+//! Preferencias de notificación y horas de silencio
+//!
+//! Cada usuario puede apagar los push por categoría (transaccionales,
+//! promociones, gamificación, encuestas) y definir horas de silencio en su
+//! zona horaria. La bandeja in-app no se filtra: apagar una categoría solo
+//! evita el push. La política se aplica en un solo lugar (`decide`) y la usan
+//! `create_notification_from_rust`, `PushNotificationService::send_notification`
+//! y el worker de la cola, así que también cubre las notificaciones creadas
+//! desde SQL.
+//!
+//! El idioma de los textos (`locale`) vive en `dim_users.locale` y se edita
+//! junto con estas preferencias.
+//!
+//! Un push que cae en horas de silencio se difiere al final de la ventana,
+//! salvo los que solo tienen sentido en el momento: los de prioridad `urgent`
+//! y la confirmación de un canje en caja salen igual, y el aviso de "tu canje
+//! vence en N minutos" se descarta (llegaría vencido).
+
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::shared::i18n::{self, Locale, Text};
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+pub const DEFAULT_TIMEZONE: &str = "America/Panama";
+
+/// Tipos de push que no esperan a que terminen las horas de silencio
+const TIME_CRITICAL_TYPES: &[&str] = &["redemption_created", "redemption_confirmed"];
+
+/// Tipos de push que pierden sentido si se difieren
+const EPHEMERAL_TYPES: &[&str] = &["redemption_expiring"];
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    Transactional,
+    Promotions,
+    Gamification,
+    Surveys,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct NotificationPreferences {
+    pub transactional: bool,
+    pub promotions: bool,
+    pub gamification: bool,
+    pub surveys: bool,
+    /// Inicio de las horas de silencio (hora local del usuario)
+    pub quiet_hours_start: Option<NaiveTime>,
+    pub quiet_hours_end: Option<NaiveTime>,
+    /// Zona IANA, p. ej. `America/Panama`
+    pub timezone: String,
+    /// Idioma de push, WhatsApp y emails (se guarda en `dim_users`)
+    #[sqlx(skip)]
+    pub locale: Locale,
+}
+
+/// Cambios parciales; `quiet_hours: null` desactiva las horas de silencio
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PreferencesUpdate {
+    pub transactional: Option<bool>,
+    pub promotions: Option<bool>,
+    pub gamification: Option<bool>,
+    pub surveys: Option<bool>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub quiet_hours: Option<Option<QuietHours>>,
+    pub timezone: Option<String>,
+    pub locale: Option<Locale>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+/// Qué hacer con un push
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Now,
+    /// Horas de silencio: enviar a esta hora (UTC)
+    Defer(DateTime<Utc>),
+    /// No enviar; el texto queda como motivo en la cola
+    Suppress(&'static str),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PreferenceError {
+    #[error("{}", Text::new("api.preferences.invalid").with("reason", .0))]
+    Invalid(Text),
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for PreferenceError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+const PREFERENCE_COLUMNS: &str =
+    "transactional, promotions, gamification, surveys, quiet_hours_start, quiet_hours_end, timezone";
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+fn double_option<'de, D>(deserializer: D) -> Result<Option<Option<QuietHours>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<QuietHours>::deserialize(deserializer).map(Some)
+}
+
+impl NotificationCategory {
+    /// Categoría de un tipo de notificación in-app o de un `data.type` de push
+    pub fn for_type(notification_type: &str) -> Self {
+        match notification_type {
+            "promo" => NotificationCategory::Promotions,
+            "achievement" | "streak" | "challenge" | "level_up" | "reminder" => NotificationCategory::Gamification,
+            t if t.starts_with("survey") => NotificationCategory::Surveys,
+            _ => NotificationCategory::Transactional,
+        }
+    }
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            transactional: true,
+            promotions: true,
+            gamification: true,
+            surveys: true,
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+            timezone: DEFAULT_TIMEZONE.to_string(),
+            locale: Locale::default(),
+        }
+    }
+}
+
+impl NotificationPreferences {
+    pub fn allows(&self, category: NotificationCategory) -> bool {
+        match category {
+            NotificationCategory::Transactional => self.transactional,
+            NotificationCategory::Promotions => self.promotions,
+            NotificationCategory::Gamification => self.gamification,
+            NotificationCategory::Surveys => self.surveys,
+        }
+    }
+
+    fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(chrono_tz::America::Panama)
+    }
+
+    /// Si `now` cae en horas de silencio, cuándo terminan (UTC)
+    pub fn quiet_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let (start, end) = (self.quiet_hours_start?, self.quiet_hours_end?);
+        if start == end {
+            return None;
+        }
+        let tz = self.tz();
+        let local = now.with_timezone(&tz).naive_local();
+        let time = local.time();
+        let end_date = if start < end {
+            if time < start || time >= end {
+                return None;
+            }
+            local.date()
+        } else if time >= start {
+            local.date() + Duration::days(1)
+        } else if time < end {
+            local.date()
+        } else {
+            return None;
+        };
+        let end_local = end_date.and_time(end);
+        let until = tz
+            .from_local_datetime(&end_local)
+            .earliest()
+            // Hora inexistente por cambio de horario: una hora después
+            .unwrap_or_else(|| tz.from_utc_datetime(&(end_local + Duration::hours(1))));
+        Some(until.with_timezone(&Utc))
+    }
+
+    /// Política central para un push
+    pub fn decide(&self, notification_type: &str, priority: &str, now: DateTime<Utc>) -> Delivery {
+        if !self.allows(NotificationCategory::for_type(notification_type)) {
+            return Delivery::Suppress("Desactivado en las preferencias del usuario");
+        }
+        if priority == "urgent" || TIME_CRITICAL_TYPES.contains(&notification_type) {
+            return Delivery::Now;
+        }
+        match self.quiet_until(now) {
+            None => Delivery::Now,
+            Some(_) if EPHEMERAL_TYPES.contains(&notification_type) => {
+                Delivery::Suppress("Horas de silencio: el aviso vencería antes de enviarse")
+            }
+            Some(until) => Delivery::Defer(until),
+        }
+    }
+
+    pub fn apply(&mut self, update: &PreferencesUpdate) -> Result<(), Text> {
+        if let Some(timezone) = &update.timezone {
+            if timezone.parse::<Tz>().is_err() {
+                return Err(Text::new("api.preferences.unknown_timezone").with("timezone", timezone));
+            }
+            self.timezone = timezone.clone();
+        }
+        if let Some(locale) = update.locale {
+            self.locale = locale;
+        }
+        if let Some(quiet_hours) = update.quiet_hours {
+            if let Some(QuietHours { start, end }) = quiet_hours {
+                if start == end {
+                    return Err(Text::new("api.preferences.empty_quiet_hours"));
+                }
+            }
+            self.quiet_hours_start = quiet_hours.map(|q| q.start);
+            self.quiet_hours_end = quiet_hours.map(|q| q.end);
+        }
+        for (value, field) in [
+            (update.transactional, &mut self.transactional),
+            (update.promotions, &mut self.promotions),
+            (update.gamification, &mut self.gamification),
+            (update.surveys, &mut self.surveys),
+        ] {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        Ok(())
+    }
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct PreferenceService {
+    db: PgPool,
+}
+
+impl PreferenceService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Preferencias del usuario (por defecto si nunca las cambió)
+    pub async fn get(&self, user_id: i64) -> Result<NotificationPreferences, PreferenceError> {
+        let preferences = sqlx::query_as::<_, NotificationPreferences>(&format!(
+            "SELECT {} FROM public.notification_preferences WHERE user_id = $1",
+            PREFERENCE_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+        let mut preferences = preferences.unwrap_or_default();
+        preferences.locale = i18n::user_locale(&self.db, user_id).await;
+        Ok(preferences)
+    }
+
+    pub async fn update(
+        &self,
+        user_id: i64,
+        update: &PreferencesUpdate,
+    ) -> Result<NotificationPreferences, PreferenceError> {
+        let mut preferences = self.get(user_id).await?;
+        preferences.apply(update).map_err(PreferenceError::Invalid)?;
+
+        let mut tx = self.db.begin().await?;
+        if update.locale.is_some() {
+            sqlx::query("UPDATE public.dim_users SET locale = $2 WHERE id = $1")
+                .bind(user_id)
+                .bind(preferences.locale.as_str())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let mut saved = sqlx::query_as::<_, NotificationPreferences>(&format!(
+            r#"
+            INSERT INTO public.notification_preferences
+                (user_id, transactional, promotions, gamification, surveys,
+                 quiet_hours_start, quiet_hours_end, timezone)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (user_id) DO UPDATE SET
+                transactional = EXCLUDED.transactional,
+                promotions = EXCLUDED.promotions,
+                gamification = EXCLUDED.gamification,
+                surveys = EXCLUDED.surveys,
+                quiet_hours_start = EXCLUDED.quiet_hours_start,
+                quiet_hours_end = EXCLUDED.quiet_hours_end,
+                timezone = EXCLUDED.timezone,
+                updated_at = NOW()
+            RETURNING {}
+            "#,
+            PREFERENCE_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(preferences.transactional)
+        .bind(preferences.promotions)
+        .bind(preferences.gamification)
+        .bind(preferences.surveys)
+        .bind(preferences.quiet_hours_start)
+        .bind(preferences.quiet_hours_end)
+        .bind(&preferences.timezone)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        saved.locale = preferences.locale;
+        Ok(saved)
+    }
+
+    pub async fn decide(
+        &self,
+        user_id: i64,
+        notification_type: &str,
+        priority: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Delivery, PreferenceError> {
+        Ok(self.get(user_id).await?.decide(notification_type, priority, now))
+    }
+
+    /// Política para una notificación ya creada (worker de la cola)
+    pub async fn decide_for_notification(
+        &self,
+        notification_id: i64,
+        now: DateTime<Utc>,
+    ) -> Result<Delivery, PreferenceError> {
+        let row: Option<(i64, String, String)> =
+            sqlx::query_as("SELECT user_id, type, priority FROM public.notifications WHERE id = $1")
+                .bind(notification_id)
+                .fetch_optional(&self.db)
+                .await?;
+        match row {
+            Some((user_id, notification_type, priority)) => {
+                self.decide(user_id, &notification_type, &priority, now).await
+            }
+            None => Ok(Delivery::Now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        // 2026-10-18 en Panamá (UTC-5)
+        chrono_tz::America::Panama
+            .with_ymd_and_hms(2026, 10, 18, hour, minute, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn quiet(start: u32, end: u32) -> NotificationPreferences {
+        NotificationPreferences {
+            quiet_hours_start: NaiveTime::from_hms_opt(start, 0, 0),
+            quiet_hours_end: NaiveTime::from_hms_opt(end, 0, 0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn maps_types_to_categories() {
+        assert_eq!(NotificationCategory::for_type("promo"), NotificationCategory::Promotions);
+        assert_eq!(NotificationCategory::for_type("streak"), NotificationCategory::Gamification);
+        assert_eq!(NotificationCategory::for_type("survey_invite"), NotificationCategory::Surveys);
+        assert_eq!(NotificationCategory::for_type("redemption_confirmed"), NotificationCategory::Transactional);
+        assert_eq!(NotificationCategory::for_type("invoice"), NotificationCategory::Transactional);
+    }
+
+    #[test]
+    fn overnight_quiet_hours() {
+        let prefs = quiet(22, 7);
+        assert_eq!(prefs.quiet_until(at(21, 59)), None);
+        assert_eq!(prefs.quiet_until(at(23, 0)), Some(at(7, 0) + Duration::days(1)));
+        assert_eq!(prefs.quiet_until(at(2, 0)), Some(at(7, 0)));
+        assert_eq!(prefs.quiet_until(at(7, 0)), None);
+    }
+
+    #[test]
+    fn same_day_quiet_hours() {
+        let prefs = quiet(13, 15);
+        assert_eq!(prefs.quiet_until(at(12, 0)), None);
+        assert_eq!(prefs.quiet_until(at(14, 30)), Some(at(15, 0)));
+        assert_eq!(NotificationPreferences::default().quiet_until(at(2, 0)), None);
+    }
+
+    #[test]
+    fn quiet_hours_follow_user_timezone() {
+        let prefs = NotificationPreferences { timezone: "Europe/Madrid".into(), ..quiet(22, 7) };
+        // 18:00 en Panamá = 01:00 en Madrid (UTC+2 en octubre)
+        assert!(prefs.quiet_until(at(18, 0)).is_some());
+        assert_eq!(prefs.quiet_until(at(12, 0)), None);
+    }
+
+    #[test]
+    fn decides_push_delivery() {
+        let prefs = NotificationPreferences { promotions: false, ..quiet(22, 7) };
+        assert!(matches!(prefs.decide("promo", "normal", at(12, 0)), Delivery::Suppress(_)));
+        assert_eq!(prefs.decide("invoice", "normal", at(12, 0)), Delivery::Now);
+        assert_eq!(prefs.decide("invoice", "normal", at(2, 0)), Delivery::Defer(at(7, 0)));
+        assert_eq!(prefs.decide("system", "urgent", at(2, 0)), Delivery::Now);
+        assert_eq!(prefs.decide("redemption_confirmed", "high", at(2, 0)), Delivery::Now);
+        assert!(matches!(prefs.decide("redemption_expiring", "high", at(2, 0)), Delivery::Suppress(_)));
+        assert_eq!(prefs.decide("redemption_expiring", "high", at(12, 0)), Delivery::Now);
+    }
+
+    #[test]
+    fn applies_partial_updates() {
+        let mut prefs = NotificationPreferences::default();
+        let update: PreferencesUpdate = serde_json::from_value(serde_json::json!({
+            "promotions": false,
+            "quiet_hours": { "start": "22:00", "end": "07:00" },
+            "timezone": "America/Bogota",
+            "locale": "en"
+        }))
+        .unwrap();
+        prefs.apply(&update).unwrap();
+        assert!(!prefs.promotions && prefs.transactional);
+        assert_eq!(prefs.quiet_hours_start, NaiveTime::from_hms_opt(22, 0, 0));
+        assert_eq!(prefs.timezone, "America/Bogota");
+        assert_eq!(prefs.locale, Locale::En);
+
+        let clear: PreferencesUpdate = serde_json::from_value(serde_json::json!({ "quiet_hours": null })).unwrap();
+        prefs.apply(&clear).unwrap();
+        assert_eq!(prefs.quiet_hours_start, None);
+        assert!(!prefs.promotions);
+
+        let untouched: PreferencesUpdate = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(untouched.quiet_hours.is_none());
+
+        let bad_tz = PreferencesUpdate { timezone: Some("Mars/Olympus".into()), ..Default::default() };
+        assert!(prefs.apply(&bad_tz).is_err());
+    }
+}
@@ -0,0 +1,314 @@
+//! Transportes reales del notificador y su log en Postgres
+//!
+//! Cada transporte envuelve la API que ya existía para su canal: la bandeja
+//! in-app (`create_notification_from_rust`, sin push porque del push se
+//! encarga su propio transporte), FCM (`PushNotificationService`), la API de
+//! WhatsApp y el envío de emails de `verification_v4` (SendGrid o SMTP).
+//!
+//! `init_notifier` deja una instancia compartida para los suscriptores de
+//! eventos y los jobs, que no tienen el `AppState` a mano.
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::{Arc, OnceLock};
+use tracing::warn;
+
+use super::notifier::{
+    Channel, DeliveryAttempt, DeliveryLog, Notifier, NotifierError, Recipient, RecipientDirectory,
+    RenderedMessage, Transport, TransportError, CLAIM_LEASE_SECS,
+};
+use super::preferences::PreferenceService;
+use crate::services::push_notification_service::{
+    get_push_service, NotificationPriority, PushNotification, PushNotificationService,
+};
+use crate::state::AppState;
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+impl Notifier {
+    /// Notificador con los cuatro canales reales y el log en Postgres
+    pub fn for_app(app_state: &Arc<AppState>) -> Self {
+        let db = app_state.db_pool.clone();
+        let push = get_push_service().unwrap_or_else(|| Arc::new(PushNotificationService::new(db.clone())));
+        Notifier::new(Arc::new(PgRecipientDirectory { db: db.clone() }), Arc::new(PgDeliveryLog { db: db.clone() }))
+            .with_transport(Arc::new(InAppTransport { db }))
+            .with_transport(Arc::new(PushTransport { service: push }))
+            .with_transport(Arc::new(WhatsAppTransport { app_state: app_state.clone() }))
+            .with_transport(Arc::new(EmailTransport))
+    }
+}
+
+pub struct PgDeliveryLog {
+    db: PgPool,
+}
+
+impl PgDeliveryLog {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl DeliveryLog for PgDeliveryLog {
+    async fn claim(&self, dedupe_key: &str, user_id: i64) -> Result<bool, NotifierError> {
+        // Una sola fila por clave: el upsert solo gana si no hay lease vigente
+        let claimed: Option<String> = sqlx::query_scalar(
+            r#"
+            INSERT INTO public.notification_delivery_claims (dedupe_key, user_id, locked_until)
+            VALUES ($1, $2, NOW() + make_interval(secs => $3))
+            ON CONFLICT (dedupe_key) DO UPDATE
+            SET locked_until = EXCLUDED.locked_until, claimed_at = NOW()
+            WHERE notification_delivery_claims.locked_until <= NOW()
+            RETURNING dedupe_key
+            "#,
+        )
+        .bind(dedupe_key)
+        .bind(user_id)
+        .bind(CLAIM_LEASE_SECS as f64)
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(claimed.is_some())
+    }
+
+    async fn release(&self, dedupe_key: &str) -> Result<(), NotifierError> {
+        sqlx::query("UPDATE public.notification_delivery_claims SET locked_until = NOW() WHERE dedupe_key = $1")
+            .bind(dedupe_key)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn delivered_channels(&self, dedupe_key: &str) -> Result<Vec<Channel>, NotifierError> {
+        let channels: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT channel
+            FROM public.notification_deliveries
+            WHERE dedupe_key = $1 AND status = 'sent'
+            "#,
+        )
+        .bind(dedupe_key)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(channels.iter().filter_map(|c| Channel::parse(c)).collect())
+    }
+
+    async fn record(&self, attempt: &DeliveryAttempt) -> Result<(), NotifierError> {
+        sqlx::query(
+            r#"
+            INSERT INTO public.notification_deliveries
+                (dedupe_key, user_id, channel, status, detail, attempted_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(&attempt.dedupe_key)
+        .bind(attempt.user_id)
+        .bind(attempt.channel.as_str())
+        .bind(attempt.status.as_str())
+        .bind(&attempt.detail)
+        .bind(attempt.attempted_at)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+}
+
+pub struct PgRecipientDirectory {
+    db: PgPool,
+}
+
+impl PgRecipientDirectory {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl RecipientDirectory for PgRecipientDirectory {
+    async fn recipient(&self, user_id: i64) -> Result<Option<Recipient>, NotifierError> {
+        let row: Option<(Option<String>, Option<String>, bool)> = sqlx::query_as(
+            r#"
+            SELECT u.ws_id, u.email,
+                   EXISTS (
+                       SELECT 1 FROM public.device_tokens d
+                       WHERE d.user_id = u.id AND d.is_active = true AND d.fcm_token IS NOT NULL
+                   )
+            FROM public.dim_users u
+            WHERE u.id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        let Some((whatsapp_id, email, has_push_token)) = row else {
+            return Ok(None);
+        };
+        let preferences = PreferenceService::new(self.db.clone())
+            .get(user_id)
+            .await
+            .map_err(|e| NotifierError::Database(e.to_string()))?;
+
+        Ok(Some(Recipient {
+            user_id,
+            locale: preferences.locale,
+            whatsapp_id,
+            email,
+            has_push_token,
+            preferences,
+        }))
+    }
+}
+
+// ======================================================================
+// TRANSPORTES
+// ======================================================================
+
+pub struct InAppTransport {
+    db: PgPool,
+}
+
+#[async_trait]
+impl Transport for InAppTransport {
+    fn channel(&self) -> Channel {
+        Channel::InApp
+    }
+
+    async fn send(&self, recipient: &Recipient, message: &RenderedMessage) -> Result<(), TransportError> {
+        // Un duplicado (misma clave) devuelve None: la notificación ya está en la bandeja
+        crate::api::notifications_v4::create_notification_from_rust(
+            &self.db,
+            recipient.user_id,
+            &message.title,
+            &message.body,
+            &message.notification_type,
+            &message.priority,
+            message.action_url.as_deref(),
+            None,
+            message.payload.clone(),
+            Some(&message.dedupe_key),
+            false,
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| TransportError::Failed(e.to_string()))
+    }
+}
+
+pub struct PushTransport {
+    service: Arc<PushNotificationService>,
+}
+
+#[async_trait]
+impl Transport for PushTransport {
+    fn channel(&self) -> Channel {
+        Channel::Push
+    }
+
+    async fn send(&self, recipient: &Recipient, message: &RenderedMessage) -> Result<(), TransportError> {
+        if !self.service.is_configured() {
+            return Err(TransportError::Unreachable("FCM no configurado".to_string()));
+        }
+        let mut data = message.payload.clone();
+        if let Some(object) = data.as_object_mut() {
+            object.insert("type".to_string(), message.notification_type.clone().into());
+            if let Some(url) = &message.action_url {
+                object.insert("action_url".to_string(), url.clone().into());
+            }
+        }
+        let priority = match message.priority.as_str() {
+            "high" | "urgent" => NotificationPriority::High,
+            _ => NotificationPriority::Normal,
+        };
+        self.service
+            .send_notification(PushNotification {
+                user_id: recipient.user_id as i32,
+                title: message.title.clone(),
+                body: message.body.clone(),
+                data,
+                priority,
+            })
+            .await
+            .map_err(|e| TransportError::Failed(e.to_string()))
+    }
+}
+
+pub struct WhatsAppTransport {
+    app_state: Arc<AppState>,
+}
+
+#[async_trait]
+impl Transport for WhatsAppTransport {
+    fn channel(&self) -> Channel {
+        Channel::WhatsApp
+    }
+
+    async fn send(&self, recipient: &Recipient, message: &RenderedMessage) -> Result<(), TransportError> {
+        let whatsapp_id = recipient
+            .whatsapp_id
+            .as_deref()
+            .ok_or_else(|| TransportError::Unreachable("Sin WhatsApp registrado".to_string()))?;
+        let text = format!("*{}*\n\n{}", message.title, message.body);
+        crate::shared::whatsapp::send_text_message(&self.app_state, whatsapp_id, &text)
+            .await
+            .map_err(|e| TransportError::Failed(e.to_string()))
+    }
+}
+
+pub struct EmailTransport;
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[async_trait]
+impl Transport for EmailTransport {
+    fn channel(&self) -> Channel {
+        Channel::Email
+    }
+
+    async fn send(&self, recipient: &Recipient, message: &RenderedMessage) -> Result<(), TransportError> {
+        use crate::api::verification_v4::{send_html_email, EMAIL_NOT_CONFIGURED};
+
+        let email = recipient
+            .email
+            .as_deref()
+            .ok_or_else(|| TransportError::Unreachable("Sin email registrado".to_string()))?;
+        let html_body = format!(
+            r#"<!DOCTYPE html>
+<html lang="{}">
+<body style="font-family: Arial, sans-serif; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <h2 style="color: #6B46C1;">{}</h2>
+    <p>{}</p>
+</body>
+</html>"#,
+            recipient.locale.as_str(),
+            escape_html(&message.title),
+            escape_html(&message.body).replace('\n', "<br>")
+        );
+        let plain_body = format!("{}\n\n{}", message.title, message.body);
+
+        match send_html_email(email, &message.title, &html_body, &plain_body, &message.dedupe_key).await {
+            Ok(()) => Ok(()),
+            Err(e) if e == EMAIL_NOT_CONFIGURED => Err(TransportError::Unreachable(e)),
+            Err(e) => Err(TransportError::Failed(e)),
+        }
+    }
+}
+
+// ======================================================================
+// INSTANCIA COMPARTIDA
+// ======================================================================
+
+static NOTIFIER: OnceLock<Arc<Notifier>> = OnceLock::new();
+
+pub fn init_notifier(app_state: &Arc<AppState>) {
+    if NOTIFIER.set(Arc::new(Notifier::for_app(app_state))).is_err() {
+        warn!("Notifier already initialized");
+    }
+}
+
+pub fn get_notifier() -> Option<Arc<Notifier>> {
+    NOTIFIER.get().cloned()
+}
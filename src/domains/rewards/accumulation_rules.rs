@@ -0,0 +1,691 @@
+//! Motor de reglas de acumulación por factura
+//!
+//! Las reglas viven en `rewards.dim_accumulations` (`rule_scope = 'invoice'`) y
+//! declaran sus condiciones en un JSON pequeño:
+//!
+//! ```json
+//! {"all": [
+//!     {"amount": {"gte": 20, "lt": 100}},
+//!     {"source": ["qr", "cufe"]},
+//!     {"issuer_category": ["Supermercado"]},
+//!     {"weekday": ["sat", "sun"]},
+//!     {"user_level": {"gte": 3}}
+//! ]}
+//! ```
+//!
+//! Además de `all` existen `any` y `not`. Sin condiciones la regla siempre aplica.
+//!
+//! Las reglas se evalúan por `priority` descendente. `stacking = 'stack'` suma y
+//! continúa; `stacking = 'exclusive'` solo aplica si ninguna regla aplicó antes y
+//! detiene la evaluación. Dentro de un mismo `exclusive_group` aplica como máximo
+//! una regla (la de mayor prioridad).
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Utc, Weekday};
+use chrono_tz::America::Panama;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// LENGUAJE DE CONDICIONES
+// ======================================================================
+
+pub const RULE_SCOPE_INVOICE: &str = "invoice";
+pub const STACKING_STACK: &str = "stack";
+pub const STACKING_EXCLUSIVE: &str = "exclusive";
+
+/// Rango numérico: todos los límites presentes deben cumplirse
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Range<T> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gt: Option<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gte: Option<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lt: Option<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lte: Option<T>,
+}
+
+impl<T: PartialOrd + Copy> Range<T> {
+    pub fn contains(&self, value: T) -> bool {
+        self.gt.is_none_or(|b| value > b)
+            && self.gte.is_none_or(|b| value >= b)
+            && self.lt.is_none_or(|b| value < b)
+            && self.lte.is_none_or(|b| value <= b)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.gt.is_none() && self.gte.is_none() && self.lt.is_none() && self.lte.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleCondition {
+    All(Vec<RuleCondition>),
+    Any(Vec<RuleCondition>),
+    Not(Box<RuleCondition>),
+    /// Total de la factura
+    Amount(Range<Decimal>),
+    /// Origen de la factura: qr, cufe, ocr, url...
+    Source(Vec<String>),
+    /// Categoría L1 del comercio emisor (dim_issuer_stores.l1)
+    IssuerCategory(Vec<String>),
+    /// Día de la compra (hora de Panamá): mon, tue, wed, thu, fri, sat, sun
+    Weekday(Vec<String>),
+    /// Nivel de gamificación del usuario
+    UserLevel(Range<i32>),
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    match value.trim().to_lowercase().as_str() {
+        "mon" | "lun" => Some(Weekday::Mon),
+        "tue" | "mar" => Some(Weekday::Tue),
+        "wed" | "mie" | "mié" => Some(Weekday::Wed),
+        "thu" | "jue" => Some(Weekday::Thu),
+        "fri" | "vie" => Some(Weekday::Fri),
+        "sat" | "sab" | "sáb" => Some(Weekday::Sat),
+        "sun" | "dom" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn contains_ci(values: &[String], value: &str) -> bool {
+    let value = value.trim();
+    values.iter().any(|v| v.trim().eq_ignore_ascii_case(value))
+}
+
+impl RuleCondition {
+    /// Verifica la estructura (rangos no vacíos, días válidos, listas no vacías)
+    pub fn validate(&self) -> Result<(), Text> {
+        match self {
+            RuleCondition::All(items) | RuleCondition::Any(items) => {
+                if items.is_empty() {
+                    return Err(Text::new("api.validation.not_empty").with("field", "all/any"));
+                }
+                items.iter().try_for_each(|c| c.validate())
+            }
+            RuleCondition::Not(inner) => inner.validate(),
+            RuleCondition::Amount(range) if range.is_empty() => {
+                Err(Text::new("api.rule.empty_range").with("field", "amount"))
+            }
+            RuleCondition::UserLevel(range) if range.is_empty() => {
+                Err(Text::new("api.rule.empty_range").with("field", "user_level"))
+            }
+            RuleCondition::Source(values) | RuleCondition::IssuerCategory(values) if values.is_empty() => {
+                Err(Text::new("api.validation.not_empty").with("field", "source/issuer_category"))
+            }
+            RuleCondition::Weekday(values) => {
+                if values.is_empty() {
+                    return Err(Text::new("api.validation.not_empty").with("field", "weekday"));
+                }
+                match values.iter().find(|d| parse_weekday(d).is_none()) {
+                    Some(day) => Err(Text::new("api.rule.invalid_weekday").with("day", day)),
+                    None => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn matches(&self, facts: &RuleFacts) -> bool {
+        match self {
+            RuleCondition::All(items) => items.iter().all(|c| c.matches(facts)),
+            RuleCondition::Any(items) => items.iter().any(|c| c.matches(facts)),
+            RuleCondition::Not(inner) => !inner.matches(facts),
+            RuleCondition::Amount(range) => range.contains(facts.amount),
+            RuleCondition::Source(values) => contains_ci(values, &facts.source),
+            RuleCondition::IssuerCategory(values) => facts
+                .issuer_category
+                .as_deref()
+                .is_some_and(|category| contains_ci(values, category)),
+            RuleCondition::Weekday(values) => values.iter().any(|d| parse_weekday(d) == Some(facts.weekday)),
+            RuleCondition::UserLevel(range) => range.contains(facts.user_level),
+        }
+    }
+}
+
+/// Convierte el JSONB de la regla en condición. NULL, `{}` o `[]` significan "siempre".
+pub fn parse_conditions(value: Option<&serde_json::Value>) -> Result<Option<RuleCondition>, Text> {
+    let value = match value {
+        None | Some(serde_json::Value::Null) => return Ok(None),
+        Some(serde_json::Value::Object(map)) if map.is_empty() => return Ok(None),
+        Some(serde_json::Value::Array(items)) if items.is_empty() => return Ok(None),
+        Some(v) => v,
+    };
+
+    // Un arreglo en la raíz se interpreta como "all"
+    let condition = match value {
+        serde_json::Value::Array(_) => {
+            RuleCondition::All(serde_json::from_value(value.clone()).map_err(invalid_json)?)
+        }
+        _ => serde_json::from_value(value.clone()).map_err(invalid_json)?,
+    };
+    condition.validate()?;
+    Ok(Some(condition))
+}
+
+fn invalid_json(err: serde_json::Error) -> Text {
+    Text::new("api.rule.invalid_json").with("error", err)
+}
+
+// ======================================================================
+// REGLAS Y EVALUACIÓN
+// ======================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccumulationRule {
+    pub id: i32,
+    pub name: String,
+    /// Lümis fijos por factura
+    pub points: i32,
+    /// Lümis adicionales por cada dólar de la factura
+    pub points_per_dollar: Option<Decimal>,
+    /// Tope de Lümis que puede otorgar la regla en una factura
+    pub max_points: Option<i32>,
+    pub priority: i32,
+    pub stacking: String,
+    pub exclusive_group: Option<String>,
+    pub conditions: Option<serde_json::Value>,
+}
+
+impl AccumulationRule {
+    pub fn points_for(&self, amount: Decimal) -> i32 {
+        let variable = self
+            .points_per_dollar
+            .map(|ppd| (amount.max(Decimal::ZERO) * ppd).floor().to_i32().unwrap_or(0))
+            .unwrap_or(0);
+        let total = self.points.saturating_add(variable).max(0);
+        match self.max_points {
+            Some(max) => total.min(max.max(0)),
+            None => total,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Text> {
+        if self.name.trim().is_empty() {
+            return Err(Text::new("api.validation.required").with("field", "name"));
+        }
+        if self.points < 0 || self.points_per_dollar.is_some_and(|p| p < Decimal::ZERO) {
+            return Err(Text::new("api.validation.not_negative").with("field", "points/points_per_dollar"));
+        }
+        if self.max_points.is_some_and(|m| m < 0) {
+            return Err(Text::new("api.validation.not_negative").with("field", "max_points"));
+        }
+        if self.stacking != STACKING_STACK && self.stacking != STACKING_EXCLUSIVE {
+            return Err(Text::new("api.validation.one_of").with("field", "stacking").with("values", "stack, exclusive"));
+        }
+        parse_conditions(self.conditions.as_ref()).map(|_| ())
+    }
+}
+
+/// Hechos de la factura sobre los que se evalúan las condiciones
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleFacts {
+    pub amount: Decimal,
+    pub source: String,
+    pub issuer_category: Option<String>,
+    #[serde(serialize_with = "serialize_weekday")]
+    pub weekday: Weekday,
+    pub user_level: i32,
+}
+
+fn serialize_weekday<S: serde::Serializer>(weekday: &Weekday, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&weekday.to_string().to_lowercase())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleDecision {
+    Applied,
+    NotMatched,
+    /// Una regla exclusiva de mayor prioridad ya aplicó
+    BlockedByExclusive,
+    /// Regla exclusiva que no aplica porque otras ya aplicaron
+    ExclusiveSkipped,
+    /// Otra regla del mismo exclusive_group ya aplicó
+    GroupTaken,
+    InvalidConditions,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleOutcome {
+    pub rule_id: i32,
+    pub name: String,
+    pub priority: i32,
+    pub decision: RuleDecision,
+    pub points: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleEvaluation {
+    pub total_points: i32,
+    pub facts: RuleFacts,
+    pub rules: Vec<RuleOutcome>,
+}
+
+impl RuleEvaluation {
+    pub fn applied(&self) -> impl Iterator<Item = &RuleOutcome> {
+        self.rules.iter().filter(|r| r.decision == RuleDecision::Applied)
+    }
+}
+
+/// Evalúa las reglas en orden de prioridad y explica la decisión de cada una
+pub fn evaluate_rules(rules: &[AccumulationRule], facts: &RuleFacts) -> RuleEvaluation {
+    let mut ordered: Vec<&AccumulationRule> = rules.iter().collect();
+    ordered.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.id.cmp(&b.id)));
+
+    let mut outcomes = Vec::with_capacity(ordered.len());
+    let mut total_points = 0i32;
+    let mut any_applied = false;
+    let mut stopped = false;
+    let mut taken_groups: Vec<String> = Vec::new();
+
+    for rule in ordered {
+        let outcome = |decision: RuleDecision, points: i32, detail: Option<String>| RuleOutcome {
+            rule_id: rule.id,
+            name: rule.name.clone(),
+            priority: rule.priority,
+            decision,
+            points,
+            detail,
+        };
+
+        if stopped {
+            outcomes.push(outcome(RuleDecision::BlockedByExclusive, 0, None));
+            continue;
+        }
+
+        let condition = match parse_conditions(rule.conditions.as_ref()) {
+            Ok(condition) => condition,
+            Err(e) => {
+                outcomes.push(outcome(RuleDecision::InvalidConditions, 0, Some(e.to_string())));
+                continue;
+            }
+        };
+
+        if !condition.as_ref().is_none_or(|c| c.matches(facts)) {
+            outcomes.push(outcome(RuleDecision::NotMatched, 0, None));
+            continue;
+        }
+
+        if let Some(group) = rule.exclusive_group.as_deref() {
+            if taken_groups.iter().any(|g| g == group) {
+                outcomes.push(outcome(RuleDecision::GroupTaken, 0, Some(group.to_string())));
+                continue;
+            }
+        }
+
+        let exclusive = rule.stacking == STACKING_EXCLUSIVE;
+        if exclusive && any_applied {
+            outcomes.push(outcome(RuleDecision::ExclusiveSkipped, 0, None));
+            continue;
+        }
+
+        let points = rule.points_for(facts.amount);
+        total_points = total_points.saturating_add(points);
+        any_applied = true;
+        if let Some(group) = rule.exclusive_group.as_deref() {
+            taken_groups.push(group.to_string());
+        }
+        if exclusive {
+            stopped = true;
+        }
+        outcomes.push(outcome(RuleDecision::Applied, points, None));
+    }
+
+    RuleEvaluation { total_points, facts: facts.clone(), rules: outcomes }
+}
+
+/// Normaliza el `type` de invoice_header ("QR", "CUFE", "OCR"...) a minúsculas
+pub fn normalize_source(value: Option<&str>) -> String {
+    match value.map(str::trim) {
+        Some(v) if !v.is_empty() => v.to_lowercase(),
+        _ => "url".to_string(),
+    }
+}
+
+pub fn panama_weekday(at: DateTime<Utc>) -> Weekday {
+    at.with_timezone(&Panama).weekday()
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+const RULE_COLUMNS: &str = r#"
+    id, name, points, points_per_dollar, max_points,
+    COALESCE(priority, 100) AS priority,
+    COALESCE(stacking, 'stack') AS stacking,
+    exclusive_group, conditions
+"#;
+
+#[derive(Debug, Clone, FromRow)]
+struct InvoiceFactsRow {
+    cufe: String,
+    user_id: i64,
+    amount: Option<Decimal>,
+    source: Option<String>,
+    issuer_category: Option<String>,
+    purchased_at: Option<DateTime<Utc>>,
+    user_level: Option<i32>,
+}
+
+impl InvoiceFactsRow {
+    fn facts(&self) -> RuleFacts {
+        RuleFacts {
+            amount: self.amount.unwrap_or(Decimal::ZERO),
+            source: normalize_source(self.source.as_deref()),
+            issuer_category: self.issuer_category.clone(),
+            weekday: panama_weekday(self.purchased_at.unwrap_or_else(Utc::now)),
+            user_level: self.user_level.unwrap_or(1),
+        }
+    }
+}
+
+const INVOICE_FACTS_QUERY: &str = r#"
+    SELECT ih.cufe,
+           ih.user_id,
+           ih.tot_amount::NUMERIC AS amount,
+           ih.type AS source,
+           (SELECT s.l1 FROM public.dim_issuer_stores s
+            WHERE s.issuer_ruc = ih.issuer_ruc AND s.store_id = ih.store_id
+            LIMIT 1) AS issuer_category,
+           COALESCE(ih.date, ih.process_date) AS purchased_at,
+           (SELECT us.current_level_id::INT FROM gamification.user_status us
+            WHERE us.user_id = ih.user_id) AS user_level
+    FROM public.invoice_header ih
+"#;
+
+/// Resultado de acreditar una factura con el motor de reglas
+#[derive(Debug, Clone)]
+pub struct CreditedRules {
+    pub total_points: i32,
+    pub evaluation: RuleEvaluation,
+}
+
+/// Resumen de un dry-run de una regla contra facturas históricas
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DryRunSummary {
+    pub invoices_evaluated: i64,
+    /// Facturas donde la regla candidata aplicó
+    pub invoices_matched: i64,
+    pub candidate_points: i64,
+    /// Lümis con las reglas activas actuales
+    pub current_total_points: i64,
+    /// Lümis si la regla candidata se agrega (o reemplaza la de igual id)
+    pub projected_total_points: i64,
+    pub samples: Vec<DryRunSample>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DryRunSample {
+    pub cufe: String,
+    pub user_id: i64,
+    pub current_points: i32,
+    pub projected_points: i32,
+    pub candidate: RuleOutcome,
+}
+
+pub struct AccumulationRuleEngine {
+    db: PgPool,
+}
+
+impl AccumulationRuleEngine {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Reglas de factura vigentes
+    pub async fn load_active_rules(&self) -> Result<Vec<AccumulationRule>, sqlx::Error> {
+        sqlx::query_as::<_, AccumulationRule>(&format!(
+            r#"
+            SELECT {}
+            FROM rewards.dim_accumulations
+            WHERE rule_scope = $1
+              AND COALESCE(is_active, TRUE)
+              AND valid_from <= NOW()
+              AND valid_to >= NOW()
+            ORDER BY COALESCE(priority, 100) DESC, id
+            "#,
+            RULE_COLUMNS
+        ))
+        .bind(RULE_SCOPE_INVOICE)
+        .fetch_all(&self.db)
+        .await
+    }
+
+    async fn invoice_facts(&self, cufe: &str) -> Result<Option<InvoiceFactsRow>, sqlx::Error> {
+        sqlx::query_as::<_, InvoiceFactsRow>(&format!("{} WHERE ih.cufe = $1", INVOICE_FACTS_QUERY))
+            .bind(cufe)
+            .fetch_optional(&self.db)
+            .await
+    }
+
+    /// Explica qué reglas aplicarían a una factura guardada, sin acreditar nada
+    pub async fn explain_invoice(&self, cufe: &str) -> Result<Option<RuleEvaluation>, sqlx::Error> {
+        let Some(row) = self.invoice_facts(cufe).await? else {
+            return Ok(None);
+        };
+        let rules = self.load_active_rules().await?;
+        Ok(Some(evaluate_rules(&rules, &row.facts())))
+    }
+
+    /// Evalúa las reglas y registra una acumulación por cada regla aplicada.
+    /// Devuelve `RowNotFound` si no hay reglas activas o la factura no existe.
+    pub async fn credit_invoice(&self, user_id: i64, cufe: &str) -> Result<CreditedRules, sqlx::Error> {
+        let rules = self.load_active_rules().await?;
+        if rules.is_empty() {
+            tracing::warn!("⚠️ No active accumulation rules found");
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        let row = self.invoice_facts(cufe).await?.ok_or(sqlx::Error::RowNotFound)?;
+        let evaluation = evaluate_rules(&rules, &row.facts());
+
+        let current_time: NaiveDateTime = Utc::now().naive_utc();
+        let mut tx = self.db.begin().await?;
+        for applied in evaluation.applied().filter(|r| r.points > 0) {
+            // El TRIGGER de PostgreSQL actualizará automáticamente rewards.fact_balance_points
+            sqlx::query(
+                r#"
+                INSERT INTO rewards.fact_accumulations
+                (user_id, accum_type, accum_key, dtype, quantity, date, accum_id)
+                VALUES ($1, $2, $3, 'points', $4, $5, $6)
+                "#,
+            )
+            .bind(user_id)
+            .bind(&applied.name)
+            .bind(cufe)
+            .bind(applied.points)
+            .bind(current_time)
+            .bind(applied.rule_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(CreditedRules { total_points: evaluation.total_points, evaluation })
+    }
+
+    /// Prueba una regla candidata contra facturas históricas sin escribir nada
+    pub async fn dry_run(
+        &self,
+        candidate: &AccumulationRule,
+        from: NaiveDate,
+        to: NaiveDate,
+        limit: i64,
+        sample_size: usize,
+    ) -> Result<DryRunSummary, sqlx::Error> {
+        let current_rules = self.load_active_rules().await?;
+        let mut projected_rules: Vec<AccumulationRule> =
+            current_rules.iter().filter(|r| r.id != candidate.id).cloned().collect();
+        projected_rules.push(candidate.clone());
+
+        let rows = sqlx::query_as::<_, InvoiceFactsRow>(&format!(
+            r#"
+            {}
+            WHERE COALESCE(ih.date, ih.process_date) >= $1
+              AND COALESCE(ih.date, ih.process_date) < $2
+            ORDER BY COALESCE(ih.date, ih.process_date) DESC
+            LIMIT $3
+            "#,
+            INVOICE_FACTS_QUERY
+        ))
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut summary = DryRunSummary::default();
+        for row in rows {
+            let facts = row.facts();
+            let current = evaluate_rules(&current_rules, &facts);
+            let projected = evaluate_rules(&projected_rules, &facts);
+            let Some(candidate_outcome) = projected.rules.iter().find(|r| r.rule_id == candidate.id).cloned() else {
+                continue;
+            };
+
+            summary.invoices_evaluated += 1;
+            summary.current_total_points += current.total_points as i64;
+            summary.projected_total_points += projected.total_points as i64;
+            if candidate_outcome.decision == RuleDecision::Applied {
+                summary.invoices_matched += 1;
+                summary.candidate_points += candidate_outcome.points as i64;
+                if summary.samples.len() < sample_size {
+                    summary.samples.push(DryRunSample {
+                        cufe: row.cufe.clone(),
+                        user_id: row.user_id,
+                        current_points: current.total_points,
+                        projected_points: projected.total_points,
+                        candidate: candidate_outcome,
+                    });
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(id: i32, points: i32, priority: i32, stacking: &str, conditions: serde_json::Value) -> AccumulationRule {
+        AccumulationRule {
+            id,
+            name: format!("rule_{}", id),
+            points,
+            points_per_dollar: None,
+            max_points: None,
+            priority,
+            stacking: stacking.to_string(),
+            exclusive_group: None,
+            conditions: Some(conditions),
+        }
+    }
+
+    fn facts(amount: i64, source: &str, category: Option<&str>, weekday: Weekday, level: i32) -> RuleFacts {
+        RuleFacts {
+            amount: Decimal::from(amount),
+            source: source.to_string(),
+            issuer_category: category.map(str::to_string),
+            weekday,
+            user_level: level,
+        }
+    }
+
+    #[test]
+    fn test_condition_language() {
+        let condition = parse_conditions(Some(&json!({"all": [
+            {"amount": {"gte": 20, "lt": 100}},
+            {"source": ["QR", "cufe"]},
+            {"any": [{"issuer_category": ["Supermercado"]}, {"weekday": ["sat", "sun"]}]},
+            {"not": {"user_level": {"lt": 2}}}
+        ]})))
+        .unwrap()
+        .unwrap();
+
+        assert!(condition.matches(&facts(50, "qr", Some("supermercado"), Weekday::Mon, 3)));
+        assert!(condition.matches(&facts(50, "cufe", Some("Farmacia"), Weekday::Sun, 2)));
+        assert!(!condition.matches(&facts(100, "qr", Some("Supermercado"), Weekday::Mon, 3)));
+        assert!(!condition.matches(&facts(50, "ocr", Some("Supermercado"), Weekday::Mon, 3)));
+        assert!(!condition.matches(&facts(50, "qr", None, Weekday::Mon, 3)));
+        assert!(!condition.matches(&facts(50, "qr", Some("Supermercado"), Weekday::Mon, 1)));
+    }
+
+    #[test]
+    fn test_parse_conditions_rejects_invalid() {
+        assert!(parse_conditions(None).unwrap().is_none());
+        assert!(parse_conditions(Some(&json!({}))).unwrap().is_none());
+        assert!(parse_conditions(Some(&json!({"weekday": ["funday"]}))).is_err());
+        assert!(parse_conditions(Some(&json!({"amount": {}}))).is_err());
+        assert!(parse_conditions(Some(&json!({"amount": {"between": 3}}))).is_err());
+        assert!(parse_conditions(Some(&json!({"store": ["x"]}))).is_err());
+        // Arreglo en la raíz = all
+        let all = parse_conditions(Some(&json!([{"source": ["qr"]}, {"amount": {"gte": 5}}]))).unwrap().unwrap();
+        assert!(matches!(all, RuleCondition::All(ref items) if items.len() == 2));
+    }
+
+    #[test]
+    fn test_stacking_and_priority() {
+        let rules = vec![
+            rule(0, 5, 0, STACKING_STACK, json!({})),
+            rule(1, 10, 50, STACKING_STACK, json!({"amount": {"gte": 20}})),
+            rule(2, 3, 10, STACKING_STACK, json!({"source": ["ocr"]})),
+        ];
+        let eval = evaluate_rules(&rules, &facts(30, "qr", None, Weekday::Tue, 1));
+        assert_eq!(eval.total_points, 15);
+        assert_eq!(eval.rules[0].rule_id, 1);
+        assert_eq!(eval.rules[1].decision, RuleDecision::NotMatched);
+        assert_eq!(eval.rules[2].decision, RuleDecision::Applied);
+    }
+
+    #[test]
+    fn test_exclusive_rule_wins_alone_or_is_skipped() {
+        let promo = rule(7, 50, 100, STACKING_EXCLUSIVE, json!({"weekday": ["sat"]}));
+        let base = rule(0, 5, 0, STACKING_STACK, json!({}));
+
+        let eval = evaluate_rules(&[base.clone(), promo.clone()], &facts(10, "qr", None, Weekday::Sat, 1));
+        assert_eq!(eval.total_points, 50);
+        assert_eq!(eval.rules[1].decision, RuleDecision::BlockedByExclusive);
+
+        // Con menor prioridad que una regla que ya aplicó, la exclusiva no aplica
+        let mut low_promo = promo;
+        low_promo.priority = -1;
+        let eval = evaluate_rules(&[base, low_promo], &facts(10, "qr", None, Weekday::Sat, 1));
+        assert_eq!(eval.total_points, 5);
+        assert_eq!(eval.rules[1].decision, RuleDecision::ExclusiveSkipped);
+    }
+
+    #[test]
+    fn test_exclusive_group_applies_once() {
+        let mut gold = rule(3, 20, 20, STACKING_STACK, json!({"user_level": {"gte": 5}}));
+        gold.exclusive_group = Some("level_bonus".to_string());
+        let mut silver = rule(4, 10, 10, STACKING_STACK, json!({"user_level": {"gte": 3}}));
+        silver.exclusive_group = Some("level_bonus".to_string());
+
+        let eval = evaluate_rules(&[silver, gold], &facts(10, "qr", None, Weekday::Wed, 6));
+        assert_eq!(eval.total_points, 20);
+        assert_eq!(eval.rules[1].decision, RuleDecision::GroupTaken);
+    }
+
+    #[test]
+    fn test_points_per_dollar_with_cap() {
+        let mut r = rule(5, 2, 0, STACKING_STACK, json!({}));
+        r.points_per_dollar = Some(Decimal::new(5, 1));
+        r.max_points = Some(20);
+        assert_eq!(r.points_for(Decimal::new(1099, 2)), 7);
+        assert_eq!(r.points_for(Decimal::from(100)), 20);
+    }
+}
@@ -0,0 +1,934 @@
+//! Campañas de acumulación financiadas por comercios
+//!
+//! Cada factura guardada se evalúa contra las campañas activas cuyo RUC emisor
+//! coincide. Una campaña otorga un multiplicador sobre los Lümis que dan las
+//! reglas de acumulación (`accumulation_rules`) o un bono fijo, opcionalmente
+//! limitado a ciertos productos y a un monto mínimo. Cada acierto escribe su
+//! propia fila en `rewards.fact_accumulations` y descuenta del presupuesto de la
+//! campaña bajo `FOR UPDATE`, por lo que el presupuesto nunca se sobregira.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::str::FromStr;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::accumulation_rules::AccumulationRuleEngine;
+use crate::services::event_bus_service::{DomainEvent, EventBus};
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+pub const REWARD_MULTIPLIER: &str = "multiplier";
+pub const REWARD_FIXED_BONUS: &str = "fixed_bonus";
+
+/// Regla genérica en `rewards.dim_accumulations` para las acumulaciones de campañas
+pub const CAMPAIGN_ACCUM_ID: i32 = 30;
+pub const CAMPAIGN_ACCUM_TYPE: &str = "earn_campaign";
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EarnCampaign {
+    pub campaign_id: Uuid,
+    pub merchant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub issuer_rucs: Vec<String>,
+    pub product_codes: Option<Vec<String>>,
+    pub min_basket_amount: Option<Decimal>,
+    pub reward_type: String,
+    pub multiplier: Option<Decimal>,
+    pub bonus_lumis: Option<i32>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub budget_lumis: i64,
+    pub spent_lumis: i64,
+    pub lumi_unit_cost: Decimal,
+    pub max_awards_per_user: Option<i32>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl EarnCampaign {
+    pub fn remaining_lumis(&self) -> i64 {
+        (self.budget_lumis - self.spent_lumis).max(0)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewCampaign {
+    pub merchant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub issuer_rucs: Vec<String>,
+    pub product_codes: Option<Vec<String>>,
+    pub min_basket_amount: Option<Decimal>,
+    pub reward_type: String,
+    pub multiplier: Option<Decimal>,
+    pub bonus_lumis: Option<i32>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub budget_lumis: i64,
+    pub lumi_unit_cost: Option<Decimal>,
+    pub max_awards_per_user: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CampaignUpdate {
+    pub name: Option<String>,
+    /// active | paused | cancelled
+    pub status: Option<String>,
+    pub budget_lumis: Option<i64>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+/// Línea de factura relevante para las condiciones de producto
+#[derive(Debug, Clone)]
+pub struct InvoiceLine {
+    pub code: String,
+    pub amount: Decimal,
+}
+
+/// Datos de la factura usados para evaluar campañas
+#[derive(Debug, Clone)]
+pub struct InvoiceFacts {
+    pub cufe: String,
+    pub issuer_ruc: String,
+    pub basket_amount: Decimal,
+    pub purchased_at: DateTime<Utc>,
+    pub lines: Vec<InvoiceLine>,
+}
+
+/// Resultado de evaluar una campaña sobre una factura
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CampaignHit {
+    pub lumis: i32,
+    pub matched_amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CampaignAward {
+    pub campaign_id: Uuid,
+    pub campaign_name: String,
+    pub lumis: i32,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CampaignDailySpend {
+    pub day: NaiveDate,
+    pub awards: i64,
+    pub lumis: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CampaignRoi {
+    /// USD pagados por el comercio (Lümis otorgados × costo unitario)
+    pub spend_amount: Decimal,
+    /// Ventas en los RUCs de la campaña durante la parte transcurrida de la campaña
+    pub campaign_sales: Decimal,
+    /// Ventas en los mismos RUCs durante un período previo de igual duración
+    pub baseline_sales: Decimal,
+    pub incremental_sales: Decimal,
+    /// (ventas incrementales − gasto) / gasto; None sin gasto
+    pub roi: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CampaignReport {
+    pub campaign: EarnCampaign,
+    pub awards: i64,
+    pub unique_users: i64,
+    pub lumis_awarded: i64,
+    /// Total de las facturas premiadas
+    pub attributed_sales: Decimal,
+    /// Monto de los productos que calificaron en facturas premiadas
+    pub matched_sales: Decimal,
+    pub budget_used_pct: Decimal,
+    #[serde(flatten)]
+    pub roi: CampaignRoi,
+    pub daily: Vec<CampaignDailySpend>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CampaignError {
+    #[error("{}", Text::new("api.campaign.invalid").with("reason", .0))]
+    Invalid(Text),
+
+    #[error("{}", Text::new("api.not_found.merchant"))]
+    MerchantNotFound,
+
+    #[error("{}", Text::new("api.not_found.campaign"))]
+    NotFound,
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for CampaignError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+/// RUCs y códigos de producto se comparan sin espacios y en mayúsculas
+pub fn normalize_key(value: &str) -> String {
+    value.trim().to_uppercase()
+}
+
+/// Montos de `invoice_detail` vienen como texto ("1,234.50", " 3.00 ")
+pub fn parse_amount(value: &str) -> Option<Decimal> {
+    let cleaned: String = value.trim().chars().filter(|c| *c != ',' && *c != '$').collect();
+    if cleaned.is_empty() {
+        return None;
+    }
+    Decimal::from_str(&cleaned).ok()
+}
+
+/// Lümis que otorga la campaña antes de aplicar el presupuesto
+pub fn reward_lumis(campaign: &EarnCampaign, base_points: i32) -> i32 {
+    match campaign.reward_type.as_str() {
+        REWARD_MULTIPLIER => {
+            let multiplier = campaign.multiplier.unwrap_or(Decimal::ONE);
+            let bonus = Decimal::from(base_points.max(0)) * (multiplier - Decimal::ONE);
+            bonus.floor().to_i32().unwrap_or(0).max(0)
+        }
+        REWARD_FIXED_BONUS => campaign.bonus_lumis.unwrap_or(0).max(0),
+        _ => 0,
+    }
+}
+
+/// Evalúa una campaña sobre una factura.
+///
+/// `user_awards` es la cantidad de premios que el usuario ya recibió de esta campaña.
+/// El resultado se recorta al presupuesto restante; None si la factura no califica.
+pub fn evaluate_campaign(
+    campaign: &EarnCampaign,
+    facts: &InvoiceFacts,
+    base_points: i32,
+    user_awards: i64,
+) -> Option<CampaignHit> {
+    if campaign.status != "active" {
+        return None;
+    }
+    if facts.purchased_at < campaign.starts_at || facts.purchased_at >= campaign.ends_at {
+        return None;
+    }
+
+    let ruc = normalize_key(&facts.issuer_ruc);
+    if !campaign.issuer_rucs.iter().any(|r| normalize_key(r) == ruc) {
+        return None;
+    }
+
+    if let Some(max) = campaign.max_awards_per_user {
+        if user_awards >= max as i64 {
+            return None;
+        }
+    }
+
+    // Con productos: el monto que cuenta es el de las líneas que coinciden
+    let matched_amount = match campaign.product_codes.as_deref() {
+        Some(codes) if !codes.is_empty() => {
+            let codes: Vec<String> = codes.iter().map(|c| normalize_key(c)).collect();
+            let matching: Vec<&InvoiceLine> = facts
+                .lines
+                .iter()
+                .filter(|line| codes.contains(&normalize_key(&line.code)))
+                .collect();
+            if matching.is_empty() {
+                return None;
+            }
+            matching.iter().map(|line| line.amount).sum()
+        }
+        _ => facts.basket_amount,
+    };
+
+    if let Some(min) = campaign.min_basket_amount {
+        if matched_amount < min {
+            return None;
+        }
+    }
+
+    let lumis = (reward_lumis(campaign, base_points) as i64).min(campaign.remaining_lumis());
+    if lumis <= 0 {
+        return None;
+    }
+
+    Some(CampaignHit { lumis: lumis as i32, matched_amount })
+}
+
+pub fn compute_roi(
+    lumis_awarded: i64,
+    lumi_unit_cost: Decimal,
+    campaign_sales: Decimal,
+    baseline_sales: Decimal,
+) -> CampaignRoi {
+    let spend_amount = (Decimal::from(lumis_awarded) * lumi_unit_cost).round_dp(2);
+    let incremental_sales = campaign_sales - baseline_sales;
+    let roi = if spend_amount > Decimal::ZERO {
+        Some(((incremental_sales - spend_amount) / spend_amount).round_dp(4))
+    } else {
+        None
+    };
+
+    CampaignRoi { spend_amount, campaign_sales, baseline_sales, incremental_sales, roi }
+}
+
+pub fn validate_new_campaign(campaign: &NewCampaign) -> Result<(), CampaignError> {
+    if campaign.name.trim().is_empty() || campaign.name.len() > 150 {
+        return Err(CampaignError::Invalid(
+            Text::new("api.validation.length").with("field", "name").with("min", 1).with("max", 150),
+        ));
+    }
+    if campaign.issuer_rucs.iter().all(|r| r.trim().is_empty()) {
+        return Err(CampaignError::Invalid(Text::new("api.validation.not_empty").with("field", "issuer_rucs")));
+    }
+    match campaign.reward_type.as_str() {
+        REWARD_MULTIPLIER => {
+            if campaign.multiplier.map_or(true, |m| m <= Decimal::ONE || m > Decimal::from(20)) {
+                return Err(CampaignError::Invalid(Text::new("api.campaign.invalid_multiplier")));
+            }
+        }
+        REWARD_FIXED_BONUS => {
+            if campaign.bonus_lumis.map_or(true, |b| b <= 0) {
+                return Err(CampaignError::Invalid(Text::new("api.validation.positive").with("field", "bonus_lumis")));
+            }
+        }
+        _ => {
+            return Err(CampaignError::Invalid(
+                Text::new("api.validation.one_of")
+                    .with("field", "reward_type")
+                    .with("values", "multiplier, fixed_bonus"),
+            ));
+        }
+    }
+    if campaign.ends_at <= campaign.starts_at {
+        return Err(CampaignError::Invalid(
+            Text::new("api.validation.after").with("field", "ends_at").with("other", "starts_at"),
+        ));
+    }
+    if campaign.budget_lumis <= 0 {
+        return Err(CampaignError::Invalid(Text::new("api.validation.positive").with("field", "budget_lumis")));
+    }
+    if campaign.min_basket_amount.is_some_and(|m| m < Decimal::ZERO) {
+        return Err(CampaignError::Invalid(Text::new("api.validation.not_negative").with("field", "min_basket_amount")));
+    }
+    if campaign.lumi_unit_cost.is_some_and(|c| c < Decimal::ZERO) {
+        return Err(CampaignError::Invalid(Text::new("api.validation.not_negative").with("field", "lumi_unit_cost")));
+    }
+    if campaign.max_awards_per_user.is_some_and(|m| m <= 0) {
+        return Err(CampaignError::Invalid(Text::new("api.validation.positive").with("field", "max_awards_per_user")));
+    }
+    Ok(())
+}
+
+fn normalize_list(values: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = values
+        .iter()
+        .map(|v| normalize_key(v))
+        .filter(|v| !v.is_empty())
+        .collect();
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+const CAMPAIGN_COLUMNS: &str = r#"
+    campaign_id, merchant_id, name, description, issuer_rucs, product_codes,
+    min_basket_amount, reward_type, multiplier, bonus_lumis, starts_at, ends_at,
+    budget_lumis, spent_lumis, lumi_unit_cost, max_awards_per_user, status,
+    created_at, updated_at
+"#;
+
+pub struct CampaignService {
+    db: PgPool,
+}
+
+impl CampaignService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    async fn load_invoice_facts(&self, cufe: &str) -> Result<Option<InvoiceFacts>, CampaignError> {
+        let header = sqlx::query_as::<_, (String, Option<Decimal>, Option<DateTime<Utc>>)>(
+            r#"
+            SELECT COALESCE(issuer_ruc, ''), tot_amount::NUMERIC, COALESCE(date, process_date)
+            FROM public.invoice_header
+            WHERE cufe = $1
+            "#,
+        )
+        .bind(cufe)
+        .fetch_optional(&self.db)
+        .await?;
+
+        let Some((issuer_ruc, tot_amount, purchased_at)) = header else {
+            return Ok(None);
+        };
+
+        let rows = sqlx::query_as::<_, (Option<String>, Option<String>, Option<String>)>(
+            "SELECT code, total, amount FROM public.invoice_detail WHERE cufe = $1",
+        )
+        .bind(cufe)
+        .fetch_all(&self.db)
+        .await?;
+
+        let lines = rows
+            .into_iter()
+            .filter_map(|(code, total, amount)| {
+                let code = code?;
+                let amount = total
+                    .as_deref()
+                    .and_then(parse_amount)
+                    .or_else(|| amount.as_deref().and_then(parse_amount))
+                    .unwrap_or(Decimal::ZERO);
+                Some(InvoiceLine { code, amount })
+            })
+            .collect();
+
+        Ok(Some(InvoiceFacts {
+            cufe: cufe.to_string(),
+            issuer_ruc,
+            basket_amount: tot_amount.unwrap_or(Decimal::ZERO),
+            purchased_at: purchased_at.unwrap_or_else(Utc::now),
+            lines,
+        }))
+    }
+
+    /// Lümis que las reglas de acumulación otorgan a la factura (base del multiplicador)
+    async fn base_rule_points(&self, cufe: &str) -> Result<i32, CampaignError> {
+        let evaluation = AccumulationRuleEngine::new(self.db.clone()).explain_invoice(cufe).await?;
+        Ok(evaluation.map_or(0, |e| e.total_points))
+    }
+
+    /// Evalúa una factura recién guardada contra las campañas activas y acredita
+    /// una acumulación por cada campaña que aplica. Es idempotente por (campaña, CUFE).
+    pub async fn evaluate_invoice(&self, user_id: i64, cufe: &str) -> Result<Vec<CampaignAward>, CampaignError> {
+        let Some(facts) = self.load_invoice_facts(cufe).await? else {
+            return Ok(Vec::new());
+        };
+        if facts.issuer_ruc.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let candidate_ids = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT campaign_id
+            FROM rewards.earn_campaigns
+            WHERE status = 'active'
+              AND $1 = ANY(issuer_rucs)
+              AND starts_at <= $2 AND ends_at > $2
+            ORDER BY created_at
+            "#,
+        )
+        .bind(normalize_key(&facts.issuer_ruc))
+        .bind(facts.purchased_at)
+        .fetch_all(&self.db)
+        .await?;
+
+        if candidate_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let base_points = self.base_rule_points(cufe).await?;
+        let mut awards = Vec::new();
+
+        for campaign_id in candidate_ids {
+            // Una transacción por campaña: un presupuesto agotado no bloquea las demás
+            let mut tx = self.db.begin().await?;
+
+            let campaign = sqlx::query_as::<_, EarnCampaign>(&format!(
+                "SELECT {} FROM rewards.earn_campaigns WHERE campaign_id = $1 FOR UPDATE",
+                CAMPAIGN_COLUMNS
+            ))
+            .bind(campaign_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let user_awards = sqlx::query_scalar::<_, i64>(
+                "SELECT COUNT(*) FROM rewards.earn_campaign_awards WHERE campaign_id = $1 AND user_id = $2",
+            )
+            .bind(campaign_id)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let Some(hit) = evaluate_campaign(&campaign, &facts, base_points, user_awards) else {
+                continue;
+            };
+
+            let inserted = sqlx::query_scalar::<_, i64>(
+                r#"
+                INSERT INTO rewards.earn_campaign_awards
+                    (campaign_id, user_id, cufe, lumis, basket_amount, matched_amount)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (campaign_id, cufe) DO NOTHING
+                RETURNING award_id
+                "#,
+            )
+            .bind(campaign_id)
+            .bind(user_id)
+            .bind(&facts.cufe)
+            .bind(hit.lumis)
+            .bind(facts.basket_amount)
+            .bind(hit.matched_amount)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if inserted.is_none() {
+                // Factura ya premiada por esta campaña
+                continue;
+            }
+
+            // El trigger de fact_accumulations actualiza el balance
+            sqlx::query(
+                r#"
+                INSERT INTO rewards.fact_accumulations
+                (user_id, accum_type, accum_key, dtype, quantity, date, accum_id)
+                VALUES ($1, $2, $3, 'points', $4, $5, $6)
+                "#,
+            )
+            .bind(user_id)
+            .bind(CAMPAIGN_ACCUM_TYPE)
+            .bind(format!("campaign:{}:{}", campaign_id, facts.cufe))
+            .bind(hit.lumis)
+            .bind(Utc::now().naive_utc())
+            .bind(CAMPAIGN_ACCUM_ID)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE rewards.earn_campaigns
+                SET spent_lumis = spent_lumis + $2,
+                    status = CASE WHEN spent_lumis + $2 >= budget_lumis THEN 'exhausted' ELSE status END,
+                    updated_at = NOW()
+                WHERE campaign_id = $1
+                "#,
+            )
+            .bind(campaign_id)
+            .bind(hit.lumis as i64)
+            .execute(&mut *tx)
+            .await?;
+
+            // En la misma transacción que el premio: si se confirma, el evento también
+            let credited = DomainEvent::LumisCredited {
+                user_id,
+                lumis: hit.lumis,
+                source: CAMPAIGN_ACCUM_TYPE.to_string(),
+                reference: Some(facts.cufe.clone()),
+            };
+            EventBus::publish(&mut *tx, &credited)
+                .await
+                .map_err(|e| CampaignError::Database(e.to_string()))?;
+
+            tx.commit().await?;
+
+            if campaign.spent_lumis + hit.lumis as i64 >= campaign.budget_lumis {
+                warn!("📉 Campaign {} ('{}') exhausted its budget", campaign_id, campaign.name);
+            }
+            info!(
+                "🎯 Campaign '{}' awarded {} Lumis to user {} (CUFE: {})",
+                campaign.name, hit.lumis, user_id, facts.cufe
+            );
+
+            awards.push(CampaignAward {
+                campaign_id,
+                campaign_name: campaign.name,
+                lumis: hit.lumis,
+            });
+        }
+
+        Ok(awards)
+    }
+
+    pub async fn create_campaign(&self, campaign: NewCampaign, created_by: i64) -> Result<EarnCampaign, CampaignError> {
+        validate_new_campaign(&campaign)?;
+
+        let merchant_exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM rewards.merchants WHERE merchant_id = $1)",
+        )
+        .bind(campaign.merchant_id)
+        .fetch_one(&self.db)
+        .await?;
+        if !merchant_exists {
+            return Err(CampaignError::MerchantNotFound);
+        }
+
+        let product_codes = campaign
+            .product_codes
+            .as_deref()
+            .map(normalize_list)
+            .filter(|codes| !codes.is_empty());
+
+        let created = sqlx::query_as::<_, EarnCampaign>(&format!(
+            r#"
+            INSERT INTO rewards.earn_campaigns (
+                merchant_id, name, description, issuer_rucs, product_codes, min_basket_amount,
+                reward_type, multiplier, bonus_lumis, starts_at, ends_at, budget_lumis,
+                lumi_unit_cost, max_awards_per_user, created_by
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, COALESCE($13, 0.01), $14, $15)
+            RETURNING {}
+            "#,
+            CAMPAIGN_COLUMNS
+        ))
+        .bind(campaign.merchant_id)
+        .bind(campaign.name.trim())
+        .bind(&campaign.description)
+        .bind(normalize_list(&campaign.issuer_rucs))
+        .bind(product_codes)
+        .bind(campaign.min_basket_amount)
+        .bind(&campaign.reward_type)
+        .bind(campaign.multiplier.filter(|_| campaign.reward_type == REWARD_MULTIPLIER))
+        .bind(campaign.bonus_lumis.filter(|_| campaign.reward_type == REWARD_FIXED_BONUS))
+        .bind(campaign.starts_at)
+        .bind(campaign.ends_at)
+        .bind(campaign.budget_lumis)
+        .bind(campaign.lumi_unit_cost)
+        .bind(campaign.max_awards_per_user)
+        .bind(created_by)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(created)
+    }
+
+    pub async fn update_campaign(
+        &self,
+        campaign_id: Uuid,
+        update: CampaignUpdate,
+    ) -> Result<EarnCampaign, CampaignError> {
+        if let Some(status) = update.status.as_deref() {
+            if !matches!(status, "active" | "paused" | "cancelled") {
+                return Err(CampaignError::Invalid(
+                    Text::new("api.validation.one_of")
+                        .with("field", "status")
+                        .with("values", "active, paused, cancelled"),
+                ));
+            }
+        }
+        if update.name.as_deref().is_some_and(|n| n.trim().is_empty() || n.len() > 150) {
+            return Err(CampaignError::Invalid(
+                Text::new("api.validation.length").with("field", "name").with("min", 1).with("max", 150),
+            ));
+        }
+
+        let mut tx = self.db.begin().await?;
+
+        let current = sqlx::query_as::<_, EarnCampaign>(&format!(
+            "SELECT {} FROM rewards.earn_campaigns WHERE campaign_id = $1 FOR UPDATE",
+            CAMPAIGN_COLUMNS
+        ))
+        .bind(campaign_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(CampaignError::NotFound)?;
+
+        if current.status == "cancelled" {
+            return Err(CampaignError::Invalid(Text::new("api.campaign.cancelled")));
+        }
+
+        let budget = update.budget_lumis.unwrap_or(current.budget_lumis);
+        if budget < current.spent_lumis {
+            return Err(CampaignError::Invalid(
+                Text::new("api.campaign.budget_below_spent").with("spent", current.spent_lumis),
+            ));
+        }
+        let ends_at = update.ends_at.unwrap_or(current.ends_at);
+        if ends_at <= current.starts_at {
+            return Err(CampaignError::Invalid(
+                Text::new("api.validation.after").with("field", "ends_at").with("other", "starts_at"),
+            ));
+        }
+
+        // Reactivar una campaña agotada solo si queda presupuesto
+        let mut status = update.status.unwrap_or_else(|| current.status.clone());
+        if status == "active" && budget <= current.spent_lumis {
+            status = "exhausted".to_string();
+        } else if current.status == "exhausted" && budget > current.spent_lumis && status == "exhausted" {
+            status = "active".to_string();
+        }
+
+        let updated = sqlx::query_as::<_, EarnCampaign>(&format!(
+            r#"
+            UPDATE rewards.earn_campaigns
+            SET name = COALESCE($2, name),
+                status = $3,
+                budget_lumis = $4,
+                ends_at = $5,
+                updated_at = NOW()
+            WHERE campaign_id = $1
+            RETURNING {}
+            "#,
+            CAMPAIGN_COLUMNS
+        ))
+        .bind(campaign_id)
+        .bind(update.name.as_deref().map(str::trim))
+        .bind(&status)
+        .bind(budget)
+        .bind(ends_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    pub async fn list_campaigns(
+        &self,
+        merchant_id: Option<Uuid>,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<EarnCampaign>, CampaignError> {
+        let campaigns = sqlx::query_as::<_, EarnCampaign>(&format!(
+            r#"
+            SELECT {}
+            FROM rewards.earn_campaigns
+            WHERE ($1::UUID IS NULL OR merchant_id = $1)
+              AND ($2::TEXT IS NULL OR status = $2)
+            ORDER BY starts_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            CAMPAIGN_COLUMNS
+        ))
+        .bind(merchant_id)
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(campaigns)
+    }
+
+    /// Gasto y ROI de una campaña. `merchant_id` restringe al dueño de la campaña.
+    pub async fn campaign_report(
+        &self,
+        campaign_id: Uuid,
+        merchant_id: Option<Uuid>,
+    ) -> Result<CampaignReport, CampaignError> {
+        let campaign = sqlx::query_as::<_, EarnCampaign>(&format!(
+            r#"
+            SELECT {}
+            FROM rewards.earn_campaigns
+            WHERE campaign_id = $1 AND ($2::UUID IS NULL OR merchant_id = $2)
+            "#,
+            CAMPAIGN_COLUMNS
+        ))
+        .bind(campaign_id)
+        .bind(merchant_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or(CampaignError::NotFound)?;
+
+        let (awards, unique_users, lumis_awarded, attributed_sales, matched_sales) =
+            sqlx::query_as::<_, (i64, i64, i64, Decimal, Decimal)>(
+                r#"
+                SELECT COUNT(*),
+                       COUNT(DISTINCT user_id),
+                       COALESCE(SUM(lumis), 0)::BIGINT,
+                       COALESCE(SUM(basket_amount), 0),
+                       COALESCE(SUM(matched_amount), 0)
+                FROM rewards.earn_campaign_awards
+                WHERE campaign_id = $1
+                "#,
+            )
+            .bind(campaign_id)
+            .fetch_one(&self.db)
+            .await?;
+
+        // Línea base: mismo número de días transcurridos, justo antes del inicio
+        let now = Utc::now();
+        let window_end = campaign.ends_at.min(now).max(campaign.starts_at);
+        let elapsed = window_end - campaign.starts_at;
+        let baseline_start = campaign.starts_at - elapsed.max(Duration::zero());
+
+        let sales_between = |from: DateTime<Utc>, to: DateTime<Utc>| {
+            sqlx::query_scalar::<_, Decimal>(
+                r#"
+                SELECT COALESCE(SUM(tot_amount), 0)::NUMERIC
+                FROM public.invoice_header
+                WHERE UPPER(TRIM(issuer_ruc)) = ANY($1)
+                  AND date >= $2 AND date < $3
+                "#,
+            )
+            .bind(campaign.issuer_rucs.clone())
+            .bind(from)
+            .bind(to)
+            .fetch_one(&self.db)
+        };
+        let campaign_sales = sales_between(campaign.starts_at, window_end).await?;
+        let baseline_sales = sales_between(baseline_start, campaign.starts_at).await?;
+
+        let daily = sqlx::query_as::<_, CampaignDailySpend>(
+            r#"
+            SELECT (created_at AT TIME ZONE 'America/Panama')::DATE AS day,
+                   COUNT(*) AS awards,
+                   COALESCE(SUM(lumis), 0)::BIGINT AS lumis
+            FROM rewards.earn_campaign_awards
+            WHERE campaign_id = $1
+            GROUP BY day
+            ORDER BY day
+            "#,
+        )
+        .bind(campaign_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        let budget_used_pct = if campaign.budget_lumis > 0 {
+            (Decimal::from(campaign.spent_lumis) * Decimal::from(100) / Decimal::from(campaign.budget_lumis))
+                .round_dp(2)
+        } else {
+            Decimal::ZERO
+        };
+        let roi = compute_roi(lumis_awarded, campaign.lumi_unit_cost, campaign_sales, baseline_sales);
+
+        Ok(CampaignReport {
+            campaign,
+            awards,
+            unique_users,
+            lumis_awarded,
+            attributed_sales,
+            matched_sales,
+            budget_used_pct,
+            roi,
+            daily,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn campaign(reward_type: &str) -> EarnCampaign {
+        EarnCampaign {
+            campaign_id: Uuid::nil(),
+            merchant_id: Uuid::nil(),
+            name: "3x en Super 99".to_string(),
+            description: None,
+            issuer_rucs: vec!["155596713-2-2015".to_string()],
+            product_codes: None,
+            min_basket_amount: None,
+            reward_type: reward_type.to_string(),
+            multiplier: Some(Decimal::from(3)),
+            bonus_lumis: Some(50),
+            starts_at: Utc.with_ymd_and_hms(2026, 10, 1, 5, 0, 0).unwrap(),
+            ends_at: Utc.with_ymd_and_hms(2026, 11, 1, 5, 0, 0).unwrap(),
+            budget_lumis: 1000,
+            spent_lumis: 0,
+            lumi_unit_cost: Decimal::new(1, 2),
+            max_awards_per_user: None,
+            status: "active".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn invoice(ruc: &str, total: &str, lines: &[(&str, &str)]) -> InvoiceFacts {
+        InvoiceFacts {
+            cufe: "FE0120000".to_string(),
+            issuer_ruc: ruc.to_string(),
+            basket_amount: Decimal::from_str(total).unwrap(),
+            purchased_at: Utc.with_ymd_and_hms(2026, 10, 15, 18, 0, 0).unwrap(),
+            lines: lines
+                .iter()
+                .map(|(code, amount)| InvoiceLine {
+                    code: code.to_string(),
+                    amount: Decimal::from_str(amount).unwrap(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_multiplier_awards_extra_over_base_rule() {
+        let c = campaign(REWARD_MULTIPLIER);
+        let hit = evaluate_campaign(&c, &invoice(" 155596713-2-2015 ", "25.00", &[]), 10, 0).unwrap();
+        assert_eq!(hit.lumis, 20);
+        assert_eq!(hit.matched_amount, Decimal::from(25));
+    }
+
+    #[test]
+    fn test_issuer_and_window_must_match() {
+        let c = campaign(REWARD_FIXED_BONUS);
+        assert!(evaluate_campaign(&c, &invoice("8-888-888", "25.00", &[]), 10, 0).is_none());
+
+        let mut late = invoice("155596713-2-2015", "25.00", &[]);
+        late.purchased_at = c.ends_at;
+        assert!(evaluate_campaign(&c, &late, 10, 0).is_none());
+
+        let mut paused = campaign(REWARD_FIXED_BONUS);
+        paused.status = "paused".to_string();
+        assert!(evaluate_campaign(&paused, &invoice("155596713-2-2015", "25.00", &[]), 10, 0).is_none());
+    }
+
+    #[test]
+    fn test_product_codes_and_min_basket_use_matching_lines() {
+        let mut c = campaign(REWARD_FIXED_BONUS);
+        c.product_codes = Some(vec!["7501055300075".to_string()]);
+        c.min_basket_amount = Some(Decimal::from(5));
+
+        let no_product = invoice("155596713-2-2015", "40.00", &[("123", "40.00")]);
+        assert!(evaluate_campaign(&c, &no_product, 10, 0).is_none());
+
+        let small = invoice("155596713-2-2015", "40.00", &[("7501055300075", "3.50"), ("123", "36.50")]);
+        assert!(evaluate_campaign(&c, &small, 10, 0).is_none());
+
+        let ok = invoice(
+            "155596713-2-2015",
+            "40.00",
+            &[("7501055300075", "3.50"), ("7501055300075", "3.50"), ("123", "33.00")],
+        );
+        let hit = evaluate_campaign(&c, &ok, 10, 0).unwrap();
+        assert_eq!(hit.lumis, 50);
+        assert_eq!(hit.matched_amount, Decimal::from(7));
+    }
+
+    #[test]
+    fn test_budget_and_per_user_caps() {
+        let mut c = campaign(REWARD_FIXED_BONUS);
+        c.spent_lumis = 980;
+        let hit = evaluate_campaign(&c, &invoice("155596713-2-2015", "10.00", &[]), 10, 0).unwrap();
+        assert_eq!(hit.lumis, 20);
+
+        c.spent_lumis = 1000;
+        assert!(evaluate_campaign(&c, &invoice("155596713-2-2015", "10.00", &[]), 10, 0).is_none());
+
+        let mut capped = campaign(REWARD_FIXED_BONUS);
+        capped.max_awards_per_user = Some(2);
+        assert!(evaluate_campaign(&capped, &invoice("155596713-2-2015", "10.00", &[]), 10, 1).is_some());
+        assert!(evaluate_campaign(&capped, &invoice("155596713-2-2015", "10.00", &[]), 10, 2).is_none());
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount(" 1,234.50 "), Some(Decimal::from_str("1234.50").unwrap()));
+        assert_eq!(parse_amount("$3"), Some(Decimal::from(3)));
+        assert_eq!(parse_amount(""), None);
+        assert_eq!(parse_amount("n/a"), None);
+    }
+
+    #[test]
+    fn test_compute_roi() {
+        let roi = compute_roi(2000, Decimal::new(1, 2), Decimal::from(500), Decimal::from(300));
+        assert_eq!(roi.spend_amount, Decimal::from(20));
+        assert_eq!(roi.incremental_sales, Decimal::from(200));
+        assert_eq!(roi.roi, Some(Decimal::from(9)));
+
+        assert_eq!(compute_roi(0, Decimal::new(1, 2), Decimal::ZERO, Decimal::ZERO).roi, None);
+    }
+}
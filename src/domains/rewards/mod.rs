@@ -2,6 +2,10 @@ pub mod models;
 pub mod offer_service;
 pub mod qr_generator;
 pub mod redemption_service;
+pub mod settlement_service;
+pub mod campaign_service;
+pub mod raffle_service;
+pub mod accumulation_rules;
 pub mod service;
 pub mod async_qr;
 
@@ -10,5 +14,9 @@ pub use models::*;
 pub use offer_service::OfferService;
 pub use qr_generator::{QrConfig, QrGenerator, ValidationTokenClaims};
 pub use redemption_service::RedemptionService;
+pub use settlement_service::{SettlementError, SettlementService};
+pub use campaign_service::{CampaignError, CampaignService};
+pub use raffle_service::{RaffleError, RaffleService};
+pub use accumulation_rules::AccumulationRuleEngine;
 pub use service::*;
 pub use async_qr::{AsyncQrService, QrGenerationTask, QrWorkerConfig};
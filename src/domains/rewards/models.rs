@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::shared::i18n::Text;
+
 // ======================================================================
 // OFERTAS
 // ======================================================================
@@ -384,37 +386,40 @@ impl AuditActionType {
 
 #[derive(Debug, thiserror::Error)]
 pub enum RedemptionError {
-    #[error("Saldo insuficiente. Tienes {current} Lümis, necesitas {required}")]
+    #[error(
+        "{}",
+        Text::new("api.redemption.insufficient_balance").with("current", .current).with("required", .required)
+    )]
     InsufficientBalance { current: i64, required: i32 },
 
-    #[error("Oferta no encontrada")]
+    #[error("{}", Text::new("api.not_found.offer"))]
     OfferNotFound,
 
-    #[error("Oferta expirada o inactiva")]
+    #[error("{}", Text::new("api.redemption.offer_inactive"))]
     OfferInactive,
 
-    #[error("Sin stock disponible")]
+    #[error("{}", Text::new("api.redemption.out_of_stock"))]
     OutOfStock,
 
-    #[error("Límite de redenciones alcanzado. Máximo: {max}, actual: {current}")]
+    #[error("{}", Text::new("api.redemption.max_reached").with("max", .max).with("current", .current))]
     MaxRedemptionsReached { max: i32, current: i32 },
 
-    #[error("Redención no encontrada")]
+    #[error("{}", Text::new("api.not_found.redemption"))]
     RedemptionNotFound,
 
-    #[error("No puedes cancelar una redención {status}")]
+    #[error("{}", Text::new("api.redemption.cannot_cancel").with("status", .status))]
     CannotCancel { status: String },
 
-    #[error("Código de redención inválido o ya usado")]
+    #[error("{}", Text::new("api.redemption.invalid_code"))]
     InvalidRedemptionCode,
 
-    #[error("Token de validación expirado o inválido")]
+    #[error("{}", Text::new("api.redemption.invalid_validation_token"))]
     InvalidValidationToken,
 
-    #[error("Redención ya confirmada anteriormente")]
+    #[error("{}", Text::new("api.redemption.already_confirmed"))]
     AlreadyConfirmed,
 
-    #[error("Código expirado")]
+    #[error("{}", Text::new("api.redemption.code_expired"))]
     CodeExpired,
 
     #[error("Error de base de datos: {0}")]
@@ -0,0 +1,1180 @@
+//! Tómbolas con sorteo verificable (commit-reveal)
+//!
+//! Los usuarios compran boletos con Lümis (API o WhatsApp) hasta el tope por
+//! usuario. El sorteo es demostrablemente justo:
+//!
+//! 1. Al crear la tómbola se genera un `seed` secreto, se publica
+//!    `seed_commitment = sha256(seed)` y se fija `beacon_round`: la primera
+//!    ronda del beacon público de drand posterior al fin de la venta.
+//! 2. Al cerrar, la lista de boletos se congela en `entries_hash`.
+//! 3. Al sortear se revela el `seed` y se lee la aleatoriedad de
+//!    `beacon_round`; los ganadores salen de
+//!    `sha256(sha256(seed:beacon):entries_hash:round:attempt)` con muestreo por
+//!    rechazo.
+//!
+//! El seed está en la base de datos desde la creación, así que por sí solo no
+//! protege contra quien tenga acceso a ella. Lo que hace imprevisible el
+//! resultado es el beacon: su ronda queda fijada al crear la tómbola pero nadie
+//! la conoce hasta después del fin de la venta, cuando la lista de boletos ya no
+//! cambia. El commitment impide a su vez cambiar el seed al ver el beacon.
+//! Cualquiera puede recalcular el sorteo con `GET /api/v4/raffles/:id/proof` y
+//! contrastar la ronda en drand. Cada usuario gana a lo sumo un premio.
+//!
+//! En la lista pública cada usuario aparece como un pseudónimo derivado de un
+//! salt secreto por tómbola (`holder_salt`), que no se puede revertir probando
+//! user_ids.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration as StdDuration;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::services::event_bus_service::{DomainEvent, EventBus};
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+pub const STATUS_OPEN: &str = "open";
+pub const STATUS_CLOSED: &str = "closed";
+pub const STATUS_DRAWN: &str = "drawn";
+
+pub const PRIZE_TYPES: &[&str] = &["cash", "merch", "other"];
+
+pub const CHANNEL_API: &str = "api";
+pub const CHANNEL_WHATSAPP: &str = "whatsapp";
+
+/// Máximo de boletos por compra
+pub const MAX_TICKETS_PER_PURCHASE: i32 = 50;
+
+pub const DRAW_ALGORITHM: &str = "holder = pseudónimo del usuario en la tómbola (16 hex, salt secreto); \
+entries_hash = sha256(\"{raffle_id}\\n\" + \"{ticket_number}:{holder}\\n\"... ordenados por ticket_number); \
+beacon = randomness de la ronda {beacon_round} de drand (chain {beacon_chain_hash}); \
+s = sha256(\"{seed}:{beacon}\") en hex; \
+por cada posición (round = 0, 1, ...): h = sha256(\"{s}:{entries_hash}:{round}:{attempt}\"), \
+x = primeros 8 bytes big-endian; se acepta si x < 2^64 - (2^64 mod n) y gana el boleto (x mod n) \
+entre los n boletos restantes; luego se retiran todos los boletos de ese holder";
+
+/// Beacon público de drand (red quicknet de League of Entropy, una ronda cada 3 s)
+pub const BEACON_CHAIN_HASH: &str = "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971";
+const BEACON_GENESIS_UNIX: i64 = 1_692_803_367;
+const BEACON_PERIOD_SECS: i64 = 3;
+/// Margen entre el fin de la venta y la ronda usada (desfase de relojes)
+const BEACON_MARGIN_SECS: i64 = 60;
+const DEFAULT_DRAND_URL: &str = "https://api.drand.sh";
+const BEACON_TIMEOUT_SECS: u64 = 10;
+
+static BEACON_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(StdDuration::from_secs(BEACON_TIMEOUT_SECS))
+        .build()
+        .expect("Failed to create HTTP client")
+});
+
+const RAFFLE_COLUMNS: &str = r#"
+    raffle_id, title, description, prize_type, prize_description, ticket_price_lumis,
+    max_tickets_per_user, max_tickets_total, winners_count, starts_at, ends_at, status,
+    tickets_sold, seed_commitment, beacon_round, beacon_randomness, entries_hash,
+    created_at, closed_at, drawn_at
+"#;
+
+/// Tómbola tal como se publica (el seed solo se expone tras el sorteo)
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Raffle {
+    pub raffle_id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub prize_type: String,
+    pub prize_description: String,
+    pub ticket_price_lumis: i32,
+    pub max_tickets_per_user: i32,
+    pub max_tickets_total: Option<i32>,
+    pub winners_count: i32,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub status: String,
+    pub tickets_sold: i32,
+    pub seed_commitment: String,
+    /// Ronda de drand que se mezcla con el seed (fijada al crear)
+    pub beacon_round: i64,
+    /// Aleatoriedad de esa ronda (desde el sorteo)
+    pub beacon_randomness: Option<String>,
+    pub entries_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub drawn_at: Option<DateTime<Utc>>,
+}
+
+impl Raffle {
+    pub fn remaining_tickets(&self) -> Option<i32> {
+        self.max_tickets_total.map(|max| (max - self.tickets_sold).max(0))
+    }
+
+    pub fn is_selling(&self, now: DateTime<Utc>) -> bool {
+        self.status == STATUS_OPEN && now >= self.starts_at && now < self.ends_at
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewRaffle {
+    pub title: String,
+    pub description: Option<String>,
+    pub prize_type: String,
+    pub prize_description: String,
+    pub ticket_price_lumis: i32,
+    pub max_tickets_per_user: i32,
+    pub max_tickets_total: Option<i32>,
+    #[serde(default = "default_winners")]
+    pub winners_count: i32,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+fn default_winners() -> i32 {
+    1
+}
+
+/// Tómbola con los boletos del usuario
+#[derive(Debug, Clone, Serialize)]
+pub struct RaffleView {
+    #[serde(flatten)]
+    pub raffle: Raffle,
+    pub my_tickets: Vec<i32>,
+    pub winners: Vec<DrawnWinner>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketPurchase {
+    pub raffle_id: i32,
+    pub purchase_id: Uuid,
+    pub ticket_numbers: Vec<i32>,
+    pub lumis_spent: i32,
+    pub tickets_owned: i32,
+    pub new_balance: i64,
+    pub seed_commitment: String,
+}
+
+/// Boleto en la lista pública (el usuario aparece como pseudónimo)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketEntry {
+    pub ticket_number: i32,
+    pub holder: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DrawnWinner {
+    pub position: i32,
+    pub ticket_number: i32,
+    pub holder: String,
+}
+
+/// Todo lo necesario para verificar el sorteo de forma independiente
+#[derive(Debug, Clone, Serialize)]
+pub struct RaffleProof {
+    pub raffle_id: i32,
+    pub status: String,
+    pub winners_count: i32,
+    pub seed_commitment: String,
+    /// Solo tras el sorteo
+    pub seed: Option<String>,
+    pub beacon_chain_hash: &'static str,
+    pub beacon_round: i64,
+    /// Solo tras el sorteo
+    pub beacon_randomness: Option<String>,
+    /// Desde el cierre
+    pub entries_hash: Option<String>,
+    pub entries: Vec<TicketEntry>,
+    pub winners: Vec<DrawnWinner>,
+    pub algorithm: &'static str,
+    /// Resultado de recalcular commitment, entries_hash y ganadores (solo tras el sorteo)
+    pub verified: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ParticipantAudit {
+    pub user_id: i32,
+    pub tickets: i64,
+    pub lumis_spent: i64,
+    pub channels: Vec<String>,
+    pub first_purchase_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct WinnerAudit {
+    pub position: i32,
+    pub ticket_number: i32,
+    pub user_id: i32,
+    pub notified_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RaffleAudit {
+    pub raffle: Raffle,
+    pub seed: Option<String>,
+    pub participants: Vec<ParticipantAudit>,
+    pub lumis_collected: i64,
+    /// Boletos en la tabla; debe coincidir con `tickets_sold`
+    pub tickets_recorded: i64,
+    pub winners: Vec<WinnerAudit>,
+    pub verified: Option<bool>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RaffleError {
+    #[error("{}", Text::new("api.raffle.invalid").with("reason", .0))]
+    Invalid(Text),
+
+    #[error("{}", Text::new("api.not_found.raffle"))]
+    NotFound,
+
+    #[error("{}", Text::new("api.raffle.none_open"))]
+    NoneOpen,
+
+    #[error("{}", Text::new("api.raffle.not_selling"))]
+    NotSelling,
+
+    #[error("{}", Text::new("api.validation.quantity").with("max", .0))]
+    InvalidQuantity(i32),
+
+    #[error("{}", Text::new("api.raffle.user_limit").with("max", .max).with("owned", .owned))]
+    UserLimit { max: i32, owned: i32 },
+
+    #[error("{}", Text::new("api.raffle.sold_out").with("remaining", .0))]
+    SoldOut(i32),
+
+    #[error("{}", Text::new("api.balance.insufficient").with("balance", .balance).with("cost", .cost))]
+    InsufficientBalance { balance: i64, cost: i64 },
+
+    #[error("{}", Text::new("api.raffle.invalid_state").with("expected", .expected).with("actual", .actual))]
+    InvalidState { expected: &'static str, actual: String },
+
+    #[error("{}", Text::new("api.raffle.entries_mismatch"))]
+    EntriesMismatch,
+
+    #[error("{}", Text::new("api.raffle.beacon_pending").with("round", .0))]
+    BeaconPending(i64),
+
+    #[error("Beacon público no disponible: {0}")]
+    Beacon(String),
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for RaffleError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+fn sha256_hex(input: &str) -> String {
+    hex::encode(Sha256::digest(input.as_bytes()))
+}
+
+/// Seed secreto: 32 bytes aleatorios en hex
+pub fn generate_seed() -> String {
+    use rand::Rng;
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    hex::encode(bytes)
+}
+
+pub fn seed_commitment(seed: &str) -> String {
+    sha256_hex(seed)
+}
+
+/// Pseudónimo estable del usuario dentro de una tómbola (`holder_salt` es
+/// secreto, así que no se puede recuperar el user_id probando candidatos)
+pub fn holder_id(holder_salt: &str, user_id: i32) -> String {
+    sha256_hex(&format!("{}:user:{}", holder_salt, user_id))[..16].to_string()
+}
+
+/// Primera ronda del beacon publicada después de `at` (más el margen)
+pub fn beacon_round_after(at: DateTime<Utc>) -> i64 {
+    let elapsed = (at.timestamp() + BEACON_MARGIN_SECS - BEACON_GENESIS_UNIX).max(0);
+    elapsed / BEACON_PERIOD_SECS + 2
+}
+
+/// Momento en que drand publica la ronda `round`
+pub fn beacon_round_time(round: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(BEACON_GENESIS_UNIX + (round - 1) * BEACON_PERIOD_SECS, 0).unwrap_or_default()
+}
+
+/// Semilla efectiva del sorteo: el seed comprometido mezclado con el beacon
+pub fn draw_seed(seed: &str, beacon_randomness: &str) -> String {
+    sha256_hex(&format!("{}:{}", seed, beacon_randomness))
+}
+
+/// Hash de la lista de boletos (debe venir ordenada por ticket_number)
+pub fn entries_hash(raffle_id: i32, entries: &[TicketEntry]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}\n", raffle_id).as_bytes());
+    for entry in entries {
+        hasher.update(format!("{}:{}\n", entry.ticket_number, entry.holder).as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Índice uniforme en `0..n` para la ronda `round` (muestreo por rechazo, sin sesgo de módulo)
+pub fn draw_index(seed: &str, entries_hash: &str, round: u32, n: usize) -> usize {
+    assert!(n > 0, "draw_index requires at least one entry");
+    let n = n as u64;
+    let limit = u64::MAX - (u64::MAX % n);
+    let mut attempt: u32 = 0;
+    loop {
+        let digest = Sha256::digest(format!("{}:{}:{}:{}", seed, entries_hash, round, attempt).as_bytes());
+        let mut first = [0u8; 8];
+        first.copy_from_slice(&digest[..8]);
+        let x = u64::from_be_bytes(first);
+        if x < limit {
+            return (x % n) as usize;
+        }
+        attempt += 1;
+    }
+}
+
+/// Ganadores en orden; un premio por holder. Si hay menos holders que premios,
+/// ganan todos.
+pub fn draw_winners(seed: &str, entries_hash: &str, entries: &[TicketEntry], winners_count: i32) -> Vec<DrawnWinner> {
+    let mut remaining: Vec<&TicketEntry> = entries.iter().collect();
+    let mut winners = Vec::new();
+    let mut round: u32 = 0;
+
+    while (winners.len() as i32) < winners_count && !remaining.is_empty() {
+        let picked = remaining[draw_index(seed, entries_hash, round, remaining.len())];
+        winners.push(DrawnWinner {
+            position: winners.len() as i32 + 1,
+            ticket_number: picked.ticket_number,
+            holder: picked.holder.clone(),
+        });
+        let holder = picked.holder.clone();
+        remaining.retain(|entry| entry.holder != holder);
+        round += 1;
+    }
+    winners
+}
+
+/// Recalcula el sorteo a partir de los datos publicados
+#[allow(clippy::too_many_arguments)]
+pub fn verify_draw(
+    raffle_id: i32,
+    seed: &str,
+    commitment: &str,
+    beacon_randomness: &str,
+    published_entries_hash: &str,
+    entries: &[TicketEntry],
+    winners_count: i32,
+    published_winners: &[DrawnWinner],
+) -> bool {
+    seed_commitment(seed) == commitment
+        && entries_hash(raffle_id, entries) == published_entries_hash
+        && draw_winners(&draw_seed(seed, beacon_randomness), published_entries_hash, entries, winners_count)
+            == published_winners
+}
+
+pub fn validate_new_raffle(raffle: &NewRaffle) -> Result<(), RaffleError> {
+    let invalid = |text: Text| Err(RaffleError::Invalid(text));
+
+    if raffle.title.trim().is_empty() || raffle.title.len() > 150 {
+        return invalid(Text::new("api.validation.length").with("field", "title").with("min", 1).with("max", 150));
+    }
+    if raffle.prize_description.trim().is_empty() {
+        return invalid(Text::new("api.validation.required").with("field", "prize_description"));
+    }
+    if !PRIZE_TYPES.contains(&raffle.prize_type.as_str()) {
+        return invalid(
+            Text::new("api.validation.one_of").with("field", "prize_type").with("values", "cash, merch, other"),
+        );
+    }
+    if raffle.ticket_price_lumis <= 0 {
+        return invalid(Text::new("api.validation.positive").with("field", "ticket_price_lumis"));
+    }
+    if raffle.max_tickets_per_user <= 0 {
+        return invalid(Text::new("api.validation.positive").with("field", "max_tickets_per_user"));
+    }
+    if raffle.winners_count <= 0 {
+        return invalid(Text::new("api.validation.positive").with("field", "winners_count"));
+    }
+    if let Some(total) = raffle.max_tickets_total {
+        if total < raffle.winners_count {
+            return invalid(
+                Text::new("api.validation.not_less").with("field", "max_tickets_total").with("other", "winners_count"),
+            );
+        }
+    }
+    if raffle.ends_at <= raffle.starts_at {
+        return invalid(Text::new("api.validation.after").with("field", "ends_at").with("other", "starts_at"));
+    }
+    if raffle.ends_at <= Utc::now() {
+        return invalid(Text::new("api.validation.in_past").with("field", "ends_at"));
+    }
+    Ok(())
+}
+
+/// Boletos que se pueden comprar ahora según los topes
+pub fn check_purchase(raffle: &Raffle, owned: i32, quantity: i32) -> Result<(), RaffleError> {
+    if !(1..=MAX_TICKETS_PER_PURCHASE).contains(&quantity) {
+        return Err(RaffleError::InvalidQuantity(MAX_TICKETS_PER_PURCHASE));
+    }
+    if owned + quantity > raffle.max_tickets_per_user {
+        return Err(RaffleError::UserLimit { max: raffle.max_tickets_per_user, owned });
+    }
+    if let Some(remaining) = raffle.remaining_tickets() {
+        if quantity > remaining {
+            return Err(RaffleError::SoldOut(remaining));
+        }
+    }
+    Ok(())
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+#[derive(Debug, FromRow)]
+struct TicketRow {
+    ticket_number: i32,
+    user_id: i32,
+    holder_salt: String,
+}
+
+#[derive(Deserialize)]
+struct BeaconRound {
+    round: i64,
+    randomness: String,
+}
+
+/// Aleatoriedad publicada por drand para `round`
+async fn fetch_beacon(round: i64) -> Result<String, RaffleError> {
+    if Utc::now() < beacon_round_time(round) {
+        return Err(RaffleError::BeaconPending(round));
+    }
+    let base = std::env::var("DRAND_URL").unwrap_or_else(|_| DEFAULT_DRAND_URL.to_string());
+    let url = format!("{}/{}/public/{}", base.trim_end_matches('/'), BEACON_CHAIN_HASH, round);
+
+    let response = BEACON_CLIENT.get(&url).send().await.map_err(|e| RaffleError::Beacon(e.to_string()))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND || response.status() == reqwest::StatusCode::TOO_EARLY {
+        return Err(RaffleError::BeaconPending(round));
+    }
+    let beacon: BeaconRound = response
+        .error_for_status()
+        .map_err(|e| RaffleError::Beacon(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| RaffleError::Beacon(e.to_string()))?;
+
+    let valid_hex = beacon.randomness.len() == 64 && beacon.randomness.bytes().all(|b| b.is_ascii_hexdigit());
+    if beacon.round != round || !valid_hex {
+        return Err(RaffleError::Beacon(format!("respuesta inválida para la ronda {}", round)));
+    }
+    Ok(beacon.randomness.to_lowercase())
+}
+
+pub struct RaffleService {
+    db: PgPool,
+}
+
+impl RaffleService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    async fn lock_raffle(tx: &mut Transaction<'_, Postgres>, raffle_id: i32) -> Result<Raffle, RaffleError> {
+        sqlx::query_as::<_, Raffle>(&format!(
+            "SELECT {} FROM rewards.raffles WHERE raffle_id = $1 FOR UPDATE",
+            RAFFLE_COLUMNS
+        ))
+        .bind(raffle_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(RaffleError::NotFound)
+    }
+
+    fn require_status(raffle: &Raffle, expected: &'static str) -> Result<(), RaffleError> {
+        if raffle.status != expected {
+            return Err(RaffleError::InvalidState { expected, actual: raffle.status.clone() });
+        }
+        Ok(())
+    }
+
+    async fn load_entries<'e, E: sqlx::PgExecutor<'e>>(
+        executor: E,
+        raffle_id: i32,
+    ) -> Result<(Vec<TicketEntry>, HashMap<String, i32>), sqlx::Error> {
+        let rows = sqlx::query_as::<_, TicketRow>(
+            r#"
+            SELECT t.ticket_number, t.user_id, r.holder_salt::TEXT AS holder_salt
+            FROM rewards.raffle_tickets t
+            JOIN rewards.raffles r ON r.raffle_id = t.raffle_id
+            WHERE t.raffle_id = $1
+            ORDER BY t.ticket_number
+            "#,
+        )
+        .bind(raffle_id)
+        .fetch_all(executor)
+        .await?;
+
+        let mut holders = HashMap::new();
+        let entries = rows
+            .into_iter()
+            .map(|row| {
+                let holder = holder_id(&row.holder_salt, row.user_id);
+                holders.insert(holder.clone(), row.user_id);
+                TicketEntry { ticket_number: row.ticket_number, holder }
+            })
+            .collect();
+        Ok((entries, holders))
+    }
+
+    async fn stored_winners(&self, raffle_id: i32) -> Result<Vec<DrawnWinner>, sqlx::Error> {
+        let rows: Vec<(i32, i32, i32, String)> = sqlx::query_as(
+            r#"
+            SELECT w.position, w.ticket_number, w.user_id, r.holder_salt::TEXT
+            FROM rewards.raffle_winners w
+            JOIN rewards.raffles r ON r.raffle_id = w.raffle_id
+            WHERE w.raffle_id = $1
+            ORDER BY w.position
+            "#,
+        )
+        .bind(raffle_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(position, ticket_number, user_id, holder_salt)| DrawnWinner {
+                position,
+                ticket_number,
+                holder: holder_id(&holder_salt, user_id),
+            })
+            .collect())
+    }
+
+    async fn seed(&self, raffle_id: i32) -> Result<String, sqlx::Error> {
+        sqlx::query_scalar("SELECT seed::TEXT FROM rewards.raffles WHERE raffle_id = $1")
+            .bind(raffle_id)
+            .fetch_one(&self.db)
+            .await
+    }
+
+    pub async fn create(&self, raffle: NewRaffle, created_by: i64) -> Result<Raffle, RaffleError> {
+        validate_new_raffle(&raffle)?;
+
+        let seed = generate_seed();
+        let created = sqlx::query_as::<_, Raffle>(&format!(
+            r#"
+            INSERT INTO rewards.raffles (
+                title, description, prize_type, prize_description, ticket_price_lumis,
+                max_tickets_per_user, max_tickets_total, winners_count, starts_at, ends_at,
+                seed_commitment, seed, beacon_round, holder_salt, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            RETURNING {}
+            "#,
+            RAFFLE_COLUMNS
+        ))
+        .bind(raffle.title.trim())
+        .bind(&raffle.description)
+        .bind(&raffle.prize_type)
+        .bind(raffle.prize_description.trim())
+        .bind(raffle.ticket_price_lumis)
+        .bind(raffle.max_tickets_per_user)
+        .bind(raffle.max_tickets_total)
+        .bind(raffle.winners_count)
+        .bind(raffle.starts_at)
+        .bind(raffle.ends_at)
+        .bind(seed_commitment(&seed))
+        .bind(&seed)
+        .bind(beacon_round_after(raffle.ends_at))
+        .bind(generate_seed())
+        .bind(created_by)
+        .fetch_one(&self.db)
+        .await?;
+
+        info!(
+            "🎟️ Raffle {} '{}' created by {} (commitment {}, beacon round {})",
+            created.raffle_id, created.title, created_by, created.seed_commitment, created.beacon_round
+        );
+        Ok(created)
+    }
+
+    /// Tómbolas vendiendo boletos ahora o con sorteo en los últimos 30 días
+    pub async fn list(&self, user_id: i32) -> Result<Vec<RaffleView>, RaffleError> {
+        let raffles = sqlx::query_as::<_, Raffle>(&format!(
+            r#"
+            SELECT {} FROM rewards.raffles
+            WHERE (status = 'open' AND starts_at <= NOW() AND ends_at > NOW())
+               OR status = 'closed'
+               OR (status = 'drawn' AND drawn_at > NOW() - INTERVAL '30 days')
+            ORDER BY (status = 'open') DESC, ends_at
+            "#,
+            RAFFLE_COLUMNS
+        ))
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut views = Vec::with_capacity(raffles.len());
+        for raffle in raffles {
+            views.push(self.view(raffle, user_id).await?);
+        }
+        Ok(views)
+    }
+
+    pub async fn get(&self, raffle_id: i32, user_id: i32) -> Result<RaffleView, RaffleError> {
+        let raffle = self.find(raffle_id).await?;
+        self.view(raffle, user_id).await
+    }
+
+    async fn find(&self, raffle_id: i32) -> Result<Raffle, RaffleError> {
+        sqlx::query_as::<_, Raffle>(&format!("SELECT {} FROM rewards.raffles WHERE raffle_id = $1", RAFFLE_COLUMNS))
+            .bind(raffle_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or(RaffleError::NotFound)
+    }
+
+    async fn view(&self, raffle: Raffle, user_id: i32) -> Result<RaffleView, RaffleError> {
+        let my_tickets: Vec<i32> = sqlx::query_scalar(
+            "SELECT ticket_number FROM rewards.raffle_tickets WHERE raffle_id = $1 AND user_id = $2 ORDER BY ticket_number",
+        )
+        .bind(raffle.raffle_id)
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+        let winners = if raffle.status == STATUS_DRAWN {
+            self.stored_winners(raffle.raffle_id).await?
+        } else {
+            Vec::new()
+        };
+        Ok(RaffleView { raffle, my_tickets, winners })
+    }
+
+    /// Tómbola vendiendo boletos del tipo de premio (la que cierra primero)
+    pub async fn current_open(&self, prize_type: &str) -> Result<Option<Raffle>, RaffleError> {
+        Ok(sqlx::query_as::<_, Raffle>(&format!(
+            r#"
+            SELECT {} FROM rewards.raffles
+            WHERE status = 'open' AND prize_type = $1 AND starts_at <= NOW() AND ends_at > NOW()
+            ORDER BY ends_at
+            LIMIT 1
+            "#,
+            RAFFLE_COLUMNS
+        ))
+        .bind(prize_type)
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    /// Compra en la tómbola abierta del tipo de premio (flujo de WhatsApp)
+    pub async fn buy_current(
+        &self,
+        prize_type: &str,
+        user_id: i32,
+        quantity: i32,
+        channel: &str,
+    ) -> Result<(Raffle, TicketPurchase), RaffleError> {
+        let raffle = self.current_open(prize_type).await?.ok_or(RaffleError::NoneOpen)?;
+        let purchase = self.buy_tickets(raffle.raffle_id, user_id, quantity, channel).await?;
+        Ok((raffle, purchase))
+    }
+
+    /// Compra `quantity` boletos: bloquea la tómbola y el balance, valida topes,
+    /// descuenta los Lümis y asigna números consecutivos.
+    pub async fn buy_tickets(
+        &self,
+        raffle_id: i32,
+        user_id: i32,
+        quantity: i32,
+        channel: &str,
+    ) -> Result<TicketPurchase, RaffleError> {
+        let mut tx = self.db.begin().await?;
+        let raffle = Self::lock_raffle(&mut tx, raffle_id).await?;
+        if !raffle.is_selling(Utc::now()) {
+            return Err(RaffleError::NotSelling);
+        }
+
+        let owned: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM rewards.raffle_tickets WHERE raffle_id = $1 AND user_id = $2",
+        )
+        .bind(raffle_id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let owned = owned as i32;
+        check_purchase(&raffle, owned, quantity)?;
+
+        let cost = raffle.ticket_price_lumis as i64 * quantity as i64;
+        let balance = sqlx::query_scalar::<_, i64>(
+            "SELECT COALESCE(balance, 0)::BIGINT FROM rewards.fact_balance_points WHERE user_id = $1 FOR UPDATE",
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .unwrap_or(0);
+        if balance < cost {
+            return Err(RaffleError::InsufficientBalance { balance, cost });
+        }
+
+        let purchase_id = Uuid::new_v4();
+        // Ledger: spend negativo; el trigger actualiza fact_balance_points
+        sqlx::query(
+            r#"
+            INSERT INTO rewards.fact_accumulations (
+                user_id, accum_type, accum_key, dtype, quantity, balance, date
+            )
+            SELECT
+                $1, 'spend', $2, 'raffle_ticket', -$3::NUMERIC,
+                COALESCE(fbp.balance, 0) - $3,
+                NOW()
+            FROM rewards.fact_balance_points fbp
+            WHERE fbp.user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(format!("raffle_{}_{}", raffle_id, purchase_id))
+        .bind(cost)
+        .execute(&mut *tx)
+        .await?;
+
+        let ticket_numbers: Vec<i32> = sqlx::query_scalar(
+            r#"
+            INSERT INTO rewards.raffle_tickets (raffle_id, ticket_number, user_id, purchase_id, channel, lumis_spent)
+            SELECT $1, $2 + n, $3, $4, $5, $6
+            FROM generate_series(1, $7) AS n
+            RETURNING ticket_number
+            "#,
+        )
+        .bind(raffle_id)
+        .bind(raffle.tickets_sold)
+        .bind(user_id)
+        .bind(purchase_id)
+        .bind(channel)
+        .bind(raffle.ticket_price_lumis)
+        .bind(quantity)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE rewards.raffles SET tickets_sold = tickets_sold + $2 WHERE raffle_id = $1")
+            .bind(raffle_id)
+            .bind(quantity)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        info!(
+            "🎟️ User {} bought {} tickets for raffle {} via {} ({} Lümis)",
+            user_id, quantity, raffle_id, channel, cost
+        );
+        Ok(TicketPurchase {
+            raffle_id,
+            purchase_id,
+            ticket_numbers,
+            lumis_spent: cost as i32,
+            tickets_owned: owned + quantity,
+            new_balance: balance - cost,
+            seed_commitment: raffle.seed_commitment,
+        })
+    }
+
+    /// Cierra la venta y publica el hash de la lista de boletos
+    pub async fn close(&self, raffle_id: i32) -> Result<Raffle, RaffleError> {
+        let mut tx = self.db.begin().await?;
+        let raffle = Self::lock_raffle(&mut tx, raffle_id).await?;
+        Self::require_status(&raffle, STATUS_OPEN)?;
+
+        let (entries, _) = Self::load_entries(&mut *tx, raffle_id).await?;
+        let hash = entries_hash(raffle_id, &entries);
+
+        let closed = sqlx::query_as::<_, Raffle>(&format!(
+            r#"
+            UPDATE rewards.raffles
+            SET status = 'closed', entries_hash = $2, closed_at = NOW()
+            WHERE raffle_id = $1
+            RETURNING {}
+            "#,
+            RAFFLE_COLUMNS
+        ))
+        .bind(raffle_id)
+        .bind(&hash)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        info!("🔒 Raffle {} closed with {} tickets (entries_hash {})", raffle_id, entries.len(), hash);
+        Ok(closed)
+    }
+
+    /// Revela el seed, lo mezcla con la ronda del beacon, calcula y guarda los
+    /// ganadores y publica `raffle.won`
+    pub async fn draw(&self, raffle_id: i32) -> Result<RaffleProof, RaffleError> {
+        // Fuera de la transacción: no retener el lock de la fila durante la llamada HTTP
+        let beacon_round = self.find(raffle_id).await?.beacon_round;
+        let beacon = fetch_beacon(beacon_round).await?;
+
+        let mut tx = self.db.begin().await?;
+        let raffle = Self::lock_raffle(&mut tx, raffle_id).await?;
+        Self::require_status(&raffle, STATUS_CLOSED)?;
+
+        let seed: String = sqlx::query_scalar("SELECT seed::TEXT FROM rewards.raffles WHERE raffle_id = $1")
+            .bind(raffle_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        let (entries, holders) = Self::load_entries(&mut *tx, raffle_id).await?;
+        let published_hash = raffle.entries_hash.clone().unwrap_or_default();
+        if entries_hash(raffle_id, &entries) != published_hash {
+            return Err(RaffleError::EntriesMismatch);
+        }
+
+        let winners = draw_winners(&draw_seed(&seed, &beacon), &published_hash, &entries, raffle.winners_count);
+        for winner in &winners {
+            let user_id = holders[&winner.holder];
+            sqlx::query(
+                "INSERT INTO rewards.raffle_winners (raffle_id, position, ticket_number, user_id) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(raffle_id)
+            .bind(winner.position)
+            .bind(winner.ticket_number)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+            let event = DomainEvent::RaffleWon {
+                user_id: user_id as i64,
+                raffle_id,
+                raffle_title: raffle.title.clone(),
+                prize_description: raffle.prize_description.clone(),
+                ticket_number: winner.ticket_number,
+            };
+            EventBus::publish(&mut *tx, &event)
+                .await
+                .map_err(|e| RaffleError::Database(e.to_string()))?;
+        }
+
+        sqlx::query(
+            "UPDATE rewards.raffles SET status = 'drawn', beacon_randomness = $2, drawn_at = NOW() WHERE raffle_id = $1",
+        )
+        .bind(raffle_id)
+        .bind(&beacon)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        info!("🎉 Raffle {} drawn: {} winners from {} tickets", raffle_id, winners.len(), entries.len());
+        Ok(RaffleProof {
+            raffle_id,
+            status: STATUS_DRAWN.to_string(),
+            winners_count: raffle.winners_count,
+            seed_commitment: raffle.seed_commitment,
+            seed: Some(seed),
+            beacon_chain_hash: BEACON_CHAIN_HASH,
+            beacon_round: raffle.beacon_round,
+            beacon_randomness: Some(beacon),
+            entries_hash: Some(published_hash),
+            entries,
+            winners,
+            algorithm: DRAW_ALGORITHM,
+            verified: Some(true),
+        })
+    }
+
+    /// Datos públicos para verificar el sorteo
+    pub async fn proof(&self, raffle_id: i32) -> Result<RaffleProof, RaffleError> {
+        let raffle = self.find(raffle_id).await?;
+        let drawn = raffle.status == STATUS_DRAWN;
+
+        let entries = if raffle.status == STATUS_OPEN {
+            Vec::new()
+        } else {
+            Self::load_entries(&self.db, raffle_id).await?.0
+        };
+        let (seed, winners, verified) = if drawn {
+            let seed = self.seed(raffle_id).await?;
+            let winners = self.stored_winners(raffle_id).await?;
+            let verified = verify_draw(
+                raffle_id,
+                &seed,
+                &raffle.seed_commitment,
+                raffle.beacon_randomness.as_deref().unwrap_or_default(),
+                raffle.entries_hash.as_deref().unwrap_or_default(),
+                &entries,
+                raffle.winners_count,
+                &winners,
+            );
+            (Some(seed), winners, Some(verified))
+        } else {
+            (None, Vec::new(), None)
+        };
+
+        Ok(RaffleProof {
+            raffle_id,
+            status: raffle.status,
+            winners_count: raffle.winners_count,
+            seed_commitment: raffle.seed_commitment,
+            seed,
+            beacon_chain_hash: BEACON_CHAIN_HASH,
+            beacon_round: raffle.beacon_round,
+            beacon_randomness: raffle.beacon_randomness,
+            entries_hash: raffle.entries_hash,
+            entries,
+            winners,
+            algorithm: DRAW_ALGORITHM,
+            verified,
+        })
+    }
+
+    /// Auditoría completa (admin): participantes, Lümis cobrados y notificaciones
+    pub async fn audit(&self, raffle_id: i32) -> Result<RaffleAudit, RaffleError> {
+        let proof = self.proof(raffle_id).await?;
+        let raffle = self.find(raffle_id).await?;
+
+        let participants = sqlx::query_as::<_, ParticipantAudit>(
+            r#"
+            SELECT user_id,
+                   COUNT(*) AS tickets,
+                   SUM(lumis_spent)::BIGINT AS lumis_spent,
+                   ARRAY_AGG(DISTINCT channel) AS channels,
+                   MIN(purchased_at) AS first_purchase_at
+            FROM rewards.raffle_tickets
+            WHERE raffle_id = $1
+            GROUP BY user_id
+            ORDER BY tickets DESC, user_id
+            "#,
+        )
+        .bind(raffle_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        let winners = sqlx::query_as::<_, WinnerAudit>(
+            r#"
+            SELECT position, ticket_number, user_id, notified_at
+            FROM rewards.raffle_winners
+            WHERE raffle_id = $1
+            ORDER BY position
+            "#,
+        )
+        .bind(raffle_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(RaffleAudit {
+            lumis_collected: participants.iter().map(|p| p.lumis_spent).sum(),
+            tickets_recorded: participants.iter().map(|p| p.tickets).sum(),
+            raffle,
+            seed: proof.seed,
+            participants,
+            winners,
+            verified: proof.verified,
+        })
+    }
+
+    pub async fn mark_notified(&self, raffle_id: i32, user_id: i32) -> Result<(), RaffleError> {
+        sqlx::query(
+            "UPDATE rewards.raffle_winners SET notified_at = NOW() WHERE raffle_id = $1 AND user_id = $2 AND notified_at IS NULL",
+        )
+        .bind(raffle_id)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::collections::HashSet;
+
+    fn entries(raffle_id: i32, owners: &[i32]) -> Vec<TicketEntry> {
+        owners
+            .iter()
+            .enumerate()
+            .map(|(i, user_id)| TicketEntry {
+                ticket_number: i as i32 + 1,
+                holder: holder_id(&format!("salt-{}", raffle_id), *user_id),
+            })
+            .collect()
+    }
+
+    fn raffle(max_per_user: i32, max_total: Option<i32>, sold: i32) -> Raffle {
+        let now = Utc::now();
+        Raffle {
+            raffle_id: 1,
+            title: "Tómbola".into(),
+            description: None,
+            prize_type: "cash".into(),
+            prize_description: "$100".into(),
+            ticket_price_lumis: 10,
+            max_tickets_per_user: max_per_user,
+            max_tickets_total: max_total,
+            winners_count: 1,
+            starts_at: now - Duration::days(1),
+            ends_at: now + Duration::days(1),
+            status: STATUS_OPEN.into(),
+            tickets_sold: sold,
+            seed_commitment: seed_commitment("seed"),
+            beacon_round: beacon_round_after(now + Duration::days(1)),
+            beacon_randomness: None,
+            entries_hash: None,
+            created_at: now,
+            closed_at: None,
+            drawn_at: None,
+        }
+    }
+
+    #[test]
+    fn test_commitment_matches_revealed_seed() {
+        let seed = generate_seed();
+        assert_eq!(seed.len(), 64);
+        assert_eq!(seed_commitment(&seed), seed_commitment(&seed));
+        assert_ne!(seed_commitment(&seed), seed_commitment(&generate_seed()));
+    }
+
+    #[test]
+    fn test_entries_hash_detects_changes() {
+        let list = entries(1, &[10, 11, 12]);
+        let hash = entries_hash(1, &list);
+
+        let mut tampered = list.clone();
+        tampered[2].holder = holder_id("salt-1", 99);
+        assert_ne!(entries_hash(1, &tampered), hash);
+        assert_ne!(entries_hash(1, &list[..2]), hash);
+        assert_ne!(entries_hash(2, &list), hash);
+    }
+
+    #[test]
+    fn test_holder_id_depends_on_secret_salt() {
+        assert_eq!(holder_id("salt-a", 7), holder_id("salt-a", 7));
+        assert_eq!(holder_id("salt-a", 7).len(), 16);
+        assert_ne!(holder_id("salt-a", 7), holder_id("salt-b", 7));
+        assert_ne!(holder_id("salt-a", 7), holder_id("salt-a", 8));
+    }
+
+    #[test]
+    fn test_beacon_round_is_published_after_sales_end() {
+        let ends_at = DateTime::parse_from_rfc3339("2026-10-25T23:59:59Z").unwrap().with_timezone(&Utc);
+        let round = beacon_round_after(ends_at);
+        let margin = Duration::seconds(BEACON_MARGIN_SECS);
+        assert!(beacon_round_time(round) > ends_at + margin);
+        assert!(beacon_round_time(round - 1) <= ends_at + margin);
+        assert_eq!(beacon_round_time(round + 1) - beacon_round_time(round), Duration::seconds(BEACON_PERIOD_SECS));
+        assert_eq!(beacon_round_time(1).timestamp(), BEACON_GENESIS_UNIX);
+    }
+
+    #[test]
+    fn test_beacon_changes_outcome() {
+        let list = entries(1, &(1..=200).collect::<Vec<_>>());
+        let hash = entries_hash(1, &list);
+        let outcomes: HashSet<i32> = (0..10)
+            .map(|i| draw_winners(&draw_seed("seed", &format!("beacon-{}", i)), &hash, &list, 1)[0].ticket_number)
+            .collect();
+        assert!(outcomes.len() > 1);
+    }
+
+    #[test]
+    fn test_draw_index_in_range_and_deterministic() {
+        for n in [1, 2, 3, 7, 1000] {
+            for round in 0..20 {
+                let index = draw_index("seed", "hash", round, n);
+                assert!(index < n);
+                assert_eq!(index, draw_index("seed", "hash", round, n));
+            }
+        }
+    }
+
+    #[test]
+    fn test_draw_is_deterministic_and_one_prize_per_user() {
+        let list = entries(1, &[1, 1, 1, 2, 2, 3, 4, 4, 5]);
+        let hash = entries_hash(1, &list);
+
+        let winners = draw_winners("abc", &hash, &list, 3);
+        assert_eq!(winners, draw_winners("abc", &hash, &list, 3));
+        assert_eq!(winners.len(), 3);
+        let holders: HashSet<&String> = winners.iter().map(|w| &w.holder).collect();
+        assert_eq!(holders.len(), 3);
+        assert_eq!(winners.iter().map(|w| w.position).collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_more_prizes_than_participants() {
+        let list = entries(1, &[1, 1, 2]);
+        let hash = entries_hash(1, &list);
+        assert_eq!(draw_winners("abc", &hash, &list, 5).len(), 2);
+        assert!(draw_winners("abc", &hash, &[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_seed_changes_outcome() {
+        let list = entries(1, &(1..=200).collect::<Vec<_>>());
+        let hash = entries_hash(1, &list);
+        let outcomes: HashSet<i32> = (0..10)
+            .map(|i| draw_winners(&format!("seed-{}", i), &hash, &list, 1)[0].ticket_number)
+            .collect();
+        assert!(outcomes.len() > 1);
+    }
+
+    #[test]
+    fn test_verify_draw() {
+        let seed = "f00d";
+        let beacon = "ab".repeat(32);
+        let commitment = seed_commitment(seed);
+        let list = entries(3, &[1, 2, 3, 4]);
+        let hash = entries_hash(3, &list);
+        let winners = draw_winners(&draw_seed(seed, &beacon), &hash, &list, 2);
+
+        assert!(verify_draw(3, seed, &commitment, &beacon, &hash, &list, 2, &winners));
+        assert!(!verify_draw(3, "other", &commitment, &beacon, &hash, &list, 2, &winners));
+        assert!(!verify_draw(3, seed, &commitment, &beacon, &hash, &list[..3], 2, &winners));
+
+        let mut swapped = winners.clone();
+        swapped[0].ticket_number = if swapped[0].ticket_number == 1 { 2 } else { 1 };
+        assert!(!verify_draw(3, seed, &commitment, &beacon, &hash, &list, 2, &swapped));
+    }
+
+    #[test]
+    fn test_purchase_limits() {
+        assert!(check_purchase(&raffle(5, None, 0), 0, 5).is_ok());
+        assert!(matches!(
+            check_purchase(&raffle(5, None, 0), 3, 3),
+            Err(RaffleError::UserLimit { max: 5, owned: 3 })
+        ));
+        assert!(matches!(check_purchase(&raffle(10, Some(20), 18), 0, 3), Err(RaffleError::SoldOut(2))));
+        assert!(matches!(check_purchase(&raffle(5, None, 0), 0, 0), Err(RaffleError::InvalidQuantity(_))));
+    }
+
+    #[test]
+    fn test_validate_new_raffle() {
+        let now = Utc::now();
+        let mut new = NewRaffle {
+            title: "Tómbola de Cash".into(),
+            description: None,
+            prize_type: "cash".into(),
+            prize_description: "$50".into(),
+            ticket_price_lumis: 5,
+            max_tickets_per_user: 10,
+            max_tickets_total: Some(500),
+            winners_count: 2,
+            starts_at: now,
+            ends_at: now + Duration::days(7),
+        };
+        assert!(validate_new_raffle(&new).is_ok());
+
+        new.prize_type = "car".into();
+        assert!(validate_new_raffle(&new).is_err());
+        new.prize_type = "merch".into();
+        new.max_tickets_total = Some(1);
+        assert!(validate_new_raffle(&new).is_err());
+        new.max_tickets_total = None;
+        new.ends_at = now - Duration::hours(1);
+        assert!(validate_new_raffle(&new).is_err());
+    }
+}
@@ -11,7 +11,7 @@ use uuid::Uuid;
 use crate::observability::metrics::{
     record_redemption_created, record_qr_generated, REDEMPTION_PROCESSING_DURATION,
 };
-use crate::services::{get_push_service, get_webhook_service};
+use crate::services::event_bus_service::{DomainEvent, EventBus};
 
 /// Servicio para gestionar redenciones de usuarios
 pub struct RedemptionService {
@@ -229,6 +229,19 @@ impl RedemptionService {
             .execute(&mut *tx)
             .await?;
 
+            // Push y webhook del merchant vía bus de eventos (solo si la transacción confirma)
+            let event = DomainEvent::RedemptionCreated {
+                user_id: user_id as i64,
+                redemption_id,
+                redemption_code: redemption_code.clone(),
+                offer_name: offer.name_friendly.clone().unwrap_or_else(|| offer.name.clone()),
+                lumis_spent: lumis_cost,
+                merchant_id: offer.merchant_id,
+            };
+            EventBus::publish(&mut *tx, &event)
+                .await
+                .map_err(|e| RedemptionError::Internal(e.to_string()))?;
+
             tx.commit().await?;
 
             // Generar QR (best-effort) después del commit
@@ -310,53 +323,6 @@ impl RedemptionService {
         // ✨ OPTIMIZATION: Calculate offer_name once to avoid multiple clones
         let offer_name = offer.name_friendly.unwrap_or(offer.name);
 
-        // 6. Enviar push notification (asíncrono, no bloqueante)
-        if let Some(push_service) = get_push_service() {
-            let push_user_id = user_id;
-            let push_redemption_id = redemption_id;
-            let push_offer_name = offer_name.clone();
-            let push_code = redemption_code.clone();
-
-            tokio::spawn(async move {
-                if let Err(e) = push_service
-                    .notify_redemption_created(
-                        push_user_id,
-                        push_redemption_id,
-                        &push_offer_name,
-                        &push_code,
-                    )
-                    .await
-                {
-                    tracing::error!("Failed to send push notification: {}", e);
-                }
-            });
-        }
-
-        // 7. Enviar webhook si merchant lo tiene configurado
-        if let Some(merchant_id) = offer.merchant_id {
-            if let Some(webhook_service) = get_webhook_service() {
-                let webhook_merchant_id = merchant_id;
-                let webhook_redemption_id = redemption_id;
-                let webhook_code = redemption_code.clone();
-                let webhook_offer_name = offer_name.clone();
-
-                tokio::spawn(async move {
-                    if let Err(e) = webhook_service
-                        .notify_redemption_created(
-                            webhook_merchant_id,
-                            webhook_redemption_id,
-                            &webhook_code,
-                            &webhook_offer_name,
-                            lumis_cost,
-                        )
-                        .await
-                    {
-                        tracing::error!("Failed to send webhook: {}", e);
-                    }
-                });
-            }
-        }
-
         Ok(RedemptionCreatedResponse {
             redemption_id,
             redemption_code,
@@ -520,14 +486,21 @@ impl RedemptionService {
 
         // 5. CRÍTICO: Restaurar stock de la oferta
         // Primero obtener offer_id de la redención
-        let offer_id_row: Option<(Uuid,)> = sqlx::query_as(
-            r#"SELECT offer_id FROM rewards.user_redemptions WHERE redemption_id = $1"#
+        let offer_id_row: Option<(Uuid, Option<Uuid>)> = sqlx::query_as(
+            r#"
+            SELECT ur.offer_id, ro.merchant_id
+            FROM rewards.user_redemptions ur
+            LEFT JOIN rewards.redemption_offers ro ON ro.offer_id = ur.offer_id
+            WHERE ur.redemption_id = $1
+            "#
         )
         .bind(redemption_id)
         .fetch_optional(&mut *tx)
         .await?;
         
-        if let Some((offer_id,)) = offer_id_row {
+        let mut merchant_id = None;
+        if let Some((offer_id, offer_merchant_id)) = offer_id_row {
+            merchant_id = offer_merchant_id;
             sqlx::query(
                 r#"
                 UPDATE rewards.redemption_offers
@@ -541,6 +514,18 @@ impl RedemptionService {
             .await?;
         }
 
+        // 6. Evento de dominio (webhook al merchant vía bus)
+        let event = DomainEvent::RedemptionCancelled {
+            user_id: user_id as i64,
+            redemption_id,
+            redemption_code: redemption.redemption_code.clone(),
+            merchant_id,
+            reason: reason.clone(),
+        };
+        EventBus::publish(&mut *tx, &event)
+            .await
+            .map_err(|e| RedemptionError::Internal(e.to_string()))?;
+
         tx.commit().await?;
 
         let new_balance = self.offer_service.get_user_balance(user_id).await?;
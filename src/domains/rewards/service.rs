@@ -5,6 +5,7 @@ use crate::{
     state::AppState,
 };
 use anyhow::Result;
+use super::raffle_service::{self, RaffleError, RaffleService};
 use sqlx::types::Decimal;
 use chrono::{DateTime, Utc, Duration};
 use sqlx::{types::Json, PgPool};
@@ -402,24 +403,44 @@ pub async fn send_challenges_info(app_state: &Arc<AppState>, ws_id: &str) -> Res
 }
 
 pub async fn send_tombola_cash_confirmation(app_state: &Arc<AppState>, ws_id: &str) -> Result<()> {
-    if user_service::get_user(app_state, ws_id).await?.is_some() {
-        let reply = "💸 *Tómbola de Cash*\n\n¡Tu participación en la tómbola de dinero ha sido registrada! Te notificaremos si resultas ganador. ¡Mucha suerte!";
-        whatsapp_service::send_text_message(app_state, ws_id, reply).await?;
-    } else {
-        let reply = "Debes estar registrado para usar esta función. Usa /start para registrarte.";
-        whatsapp_service::send_text_message(app_state, ws_id, reply).await?;
-    }
-    Ok(())
+    enter_tombola(app_state, ws_id, "cash", "💸 *Tómbola de Cash*").await
 }
 
 pub async fn send_tombola_merch_confirmation(app_state: &Arc<AppState>, ws_id: &str) -> Result<()> {
-    if user_service::get_user(app_state, ws_id).await?.is_some() {
-        let reply = "🧢 *Tómbola de Merch*\n\n¡Tu participación en la tómbola de merch ha sido registrada! Te notificaremos si resultas ganador. ¡Mucha suerte!";
-        whatsapp_service::send_text_message(app_state, ws_id, reply).await?;
-    } else {
+    enter_tombola(app_state, ws_id, "merch", "🧢 *Tómbola de Merch*").await
+}
+
+/// Compra un boleto en la tómbola abierta del tipo de premio y confirma por WhatsApp
+async fn enter_tombola(app_state: &Arc<AppState>, ws_id: &str, prize_type: &str, heading: &str) -> Result<()> {
+    let Some(user) = user_service::get_user(app_state, ws_id).await? else {
         let reply = "Debes estar registrado para usar esta función. Usa /start para registrarte.";
         whatsapp_service::send_text_message(app_state, ws_id, reply).await?;
-    }
+        return Ok(());
+    };
+
+    let raffles = RaffleService::new(app_state.db_pool.clone());
+    let reply = match raffles
+        .buy_current(prize_type, user.id as i32, 1, raffle_service::CHANNEL_WHATSAPP)
+        .await
+    {
+        Ok((raffle, purchase)) => format!(
+            "{}\n\n🎟️ ¡Listo! Tu boleto #{} para *{}* quedó registrado ({} Lümis).\n\nTienes {} de {} boletos posibles. Sorteo al cierre: {}.\n\n🔐 Compromiso del sorteo: {}\n\n¡Mucha suerte!",
+            heading,
+            purchase.ticket_numbers.first().copied().unwrap_or_default(),
+            raffle.title,
+            purchase.lumis_spent,
+            purchase.tickets_owned,
+            raffle.max_tickets_per_user,
+            raffle.ends_at.with_timezone(&chrono_tz::America::Panama).format("%d/%m/%Y %H:%M"),
+            &purchase.seed_commitment[..16],
+        ),
+        Err(RaffleError::Database(e)) => {
+            tracing::error!("Raffle purchase failed for {}: {}", ws_id, e);
+            format!("{}\n\nNo pudimos registrar tu boleto. Intenta de nuevo más tarde.", heading)
+        }
+        Err(e) => format!("{}\n\n{}", heading, e),
+    };
+    whatsapp_service::send_text_message(app_state, ws_id, &reply).await?;
     Ok(())
 }
 
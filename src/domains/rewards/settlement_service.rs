@@ -0,0 +1,627 @@
+//! Liquidaciones mensuales por comercio
+//!
+//! Un cierre toma las redenciones confirmadas del mes (hora de Panamá) que aún no
+//! pertenecen a ninguna liquidación, aplica el término comercial vigente de cada
+//! oferta al momento de la confirmación y guarda un snapshot en
+//! `rewards.merchant_settlement_lines`. La restricción UNIQUE (merchant_id, period_start)
+//! impide cerrar el mismo período dos veces.
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc};
+use chrono_tz::America::Panama;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+pub const DIRECTION_REIMBURSED: &str = "merchant_reimbursed";
+pub const DIRECTION_FEE: &str = "merchant_fee";
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Settlement {
+    pub settlement_id: Uuid,
+    pub merchant_id: Uuid,
+    pub merchant_name: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub status: String,
+    pub redemption_count: i32,
+    pub total_lumis: i64,
+    pub amount_owed_to_merchant: Decimal,
+    pub amount_owed_by_merchant: Decimal,
+    pub net_amount: Decimal,
+    pub currency: String,
+    pub closed_by: Option<i64>,
+    pub closed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SettlementLine {
+    pub redemption_id: Uuid,
+    pub redemption_code: String,
+    pub offer_id: Uuid,
+    pub offer_name: String,
+    pub branch_id: Option<Uuid>,
+    pub confirmed_at: DateTime<Utc>,
+    pub lumis_spent: i32,
+    pub term_id: Option<Uuid>,
+    pub direction: Option<String>,
+    pub unit_amount: Decimal,
+    pub line_amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettlementStatement {
+    pub settlement: Settlement,
+    pub lines: Vec<SettlementLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CommercialTerm {
+    pub term_id: Uuid,
+    pub offer_id: Uuid,
+    pub direction: String,
+    pub unit_amount: Decimal,
+    pub currency: String,
+    pub effective_from: NaiveDate,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettlementTotals {
+    pub redemption_count: i32,
+    pub total_lumis: i64,
+    pub owed_to_merchant: Decimal,
+    pub owed_by_merchant: Decimal,
+}
+
+impl SettlementTotals {
+    /// > 0: Lümis paga al comercio; < 0: el comercio paga a Lümis
+    pub fn net(&self) -> Decimal {
+        self.owed_to_merchant - self.owed_by_merchant
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SettlementError {
+    #[error("{}", Text::new("api.settlement.invalid_period"))]
+    InvalidPeriod,
+
+    #[error("{}", Text::new("api.settlement.period_not_ended").with("period", .0))]
+    PeriodNotEnded(String),
+
+    #[error("{}", Text::new("api.settlement.already_closed").with("period", .0))]
+    AlreadyClosed(String),
+
+    #[error("{}", Text::new("api.not_found.merchant"))]
+    MerchantNotFound,
+
+    #[error("{}", Text::new("api.not_found.offer"))]
+    OfferNotFound,
+
+    #[error("{}", Text::new("api.not_found.settlement"))]
+    NotFound,
+
+    #[error("{}", Text::new("api.settlement.invalid_term").with("reason", .0))]
+    InvalidTerm(Text),
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for SettlementError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+/// "2026-09" → (2026-09-01, 2026-10-01)
+pub fn parse_period(period: &str) -> Result<(NaiveDate, NaiveDate), SettlementError> {
+    let start = NaiveDate::parse_from_str(&format!("{}-01", period.trim()), "%Y-%m-%d")
+        .map_err(|_| SettlementError::InvalidPeriod)?;
+    let end = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    }
+    .ok_or(SettlementError::InvalidPeriod)?;
+    Ok((start, end))
+}
+
+/// Medianoche de Panamá de una fecha, en UTC
+pub fn panama_midnight_utc(date: NaiveDate) -> DateTime<Utc> {
+    Panama
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .single()
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&date.and_hms_opt(5, 0, 0).unwrap_or_default()))
+}
+
+/// Importe con signo de una línea según la dirección del término
+pub fn line_amount(direction: Option<&str>, unit_amount: Decimal) -> Decimal {
+    match direction {
+        Some(DIRECTION_REIMBURSED) => unit_amount,
+        Some(DIRECTION_FEE) => -unit_amount,
+        _ => Decimal::ZERO,
+    }
+}
+
+pub fn compute_totals(lines: &[SettlementLine]) -> SettlementTotals {
+    let mut totals = SettlementTotals::default();
+    for line in lines {
+        totals.redemption_count += 1;
+        totals.total_lumis += line.lumis_spent as i64;
+        if line.line_amount > Decimal::ZERO {
+            totals.owed_to_merchant += line.line_amount;
+        } else {
+            totals.owed_by_merchant += -line.line_amount;
+        }
+    }
+    totals
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Estado de cuenta en CSV: una fila por redención y filas de totales al final
+pub fn statement_csv(statement: &SettlementStatement) -> String {
+    let s = &statement.settlement;
+    let mut csv = "Redencion,Codigo,Oferta,Sucursal,Confirmada,Lumis,Tipo,Monto unitario,Monto\n".to_string();
+
+    for line in &statement.lines {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            line.redemption_id,
+            csv_field(&line.redemption_code),
+            csv_field(&line.offer_name),
+            line.branch_id.map(|b| b.to_string()).unwrap_or_default(),
+            line.confirmed_at.with_timezone(&Panama).format("%Y-%m-%d %H:%M:%S"),
+            line.lumis_spent,
+            line.direction.as_deref().unwrap_or("sin_termino"),
+            line.unit_amount,
+            line.line_amount,
+        ));
+    }
+
+    csv.push('\n');
+    csv.push_str(&format!("Comercio,{}\n", csv_field(&s.merchant_name)));
+    csv.push_str(&format!("Periodo,{} a {}\n", s.period_start, s.period_end.pred_opt().unwrap_or(s.period_end)));
+    csv.push_str(&format!("Redenciones,{}\n", s.redemption_count));
+    csv.push_str(&format!("Total Lumis,{}\n", s.total_lumis));
+    csv.push_str(&format!("A favor del comercio ({}),{}\n", s.currency, s.amount_owed_to_merchant));
+    csv.push_str(&format!("A cargo del comercio ({}),{}\n", s.currency, s.amount_owed_by_merchant));
+    csv.push_str(&format!("Neto ({}),{}\n", s.currency, s.net_amount));
+    csv
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct SettlementService {
+    db: PgPool,
+}
+
+impl SettlementService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Cerrar el período `period` (YYYY-MM) de un comercio
+    pub async fn close_period(
+        &self,
+        merchant_id: Uuid,
+        period: &str,
+        closed_by: i64,
+    ) -> Result<SettlementStatement, SettlementError> {
+        let (period_start, period_end) = parse_period(period)?;
+        let from = panama_midnight_utc(period_start);
+        let to = panama_midnight_utc(period_end);
+
+        if to > Utc::now() {
+            return Err(SettlementError::PeriodNotEnded(period.to_string()));
+        }
+
+        let mut tx = self.db.begin().await?;
+
+        let merchant_exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM rewards.merchants WHERE merchant_id = $1)",
+        )
+        .bind(merchant_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !merchant_exists {
+            return Err(SettlementError::MerchantNotFound);
+        }
+
+        // Reservar el período primero: un cierre concurrente espera aquí y luego no inserta nada
+        let settlement_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO rewards.merchant_settlements (merchant_id, period_start, period_end, closed_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (merchant_id, period_start) DO NOTHING
+            RETURNING settlement_id
+            "#,
+        )
+        .bind(merchant_id)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(closed_by)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let settlement_id = settlement_id
+            .ok_or_else(|| SettlementError::AlreadyClosed(period.to_string()))?;
+
+        // Redenciones confirmadas del período sin liquidar, con el término vigente al confirmar
+        let mut lines: Vec<SettlementLine> = sqlx::query_as(
+            r#"
+            SELECT
+                ur.redemption_id,
+                ur.redemption_code,
+                ur.offer_id,
+                COALESCE(ro.name_friendly, ro.name, '') as offer_name,
+                ur.validated_branch_id as branch_id,
+                ur.validated_at as confirmed_at,
+                ur.lumis_spent,
+                t.term_id,
+                t.direction,
+                COALESCE(t.unit_amount, 0) as unit_amount,
+                0::numeric as line_amount
+            FROM rewards.user_redemptions ur
+            JOIN rewards.redemption_offers ro ON ro.offer_id = ur.offer_id
+            LEFT JOIN LATERAL (
+                SELECT term_id, direction, unit_amount
+                FROM rewards.offer_commercial_terms
+                WHERE offer_id = ur.offer_id
+                  AND effective_from <= (ur.validated_at AT TIME ZONE 'America/Panama')::date
+                ORDER BY effective_from DESC
+                LIMIT 1
+            ) t ON true
+            WHERE ro.merchant_id = $1
+              AND ur.redemption_status = 'confirmed'
+              AND ur.settlement_id IS NULL
+              AND ur.validated_at >= $2
+              AND ur.validated_at < $3
+            ORDER BY ur.validated_at
+            FOR UPDATE OF ur
+            "#,
+        )
+        .bind(merchant_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for line in lines.iter_mut() {
+            line.line_amount = line_amount(line.direction.as_deref(), line.unit_amount);
+
+            sqlx::query(
+                r#"
+                INSERT INTO rewards.merchant_settlement_lines (
+                    settlement_id, redemption_id, redemption_code, offer_id, offer_name,
+                    branch_id, confirmed_at, lumis_spent, term_id, direction,
+                    unit_amount, line_amount
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                "#,
+            )
+            .bind(settlement_id)
+            .bind(line.redemption_id)
+            .bind(&line.redemption_code)
+            .bind(line.offer_id)
+            .bind(&line.offer_name)
+            .bind(line.branch_id)
+            .bind(line.confirmed_at)
+            .bind(line.lumis_spent)
+            .bind(line.term_id)
+            .bind(&line.direction)
+            .bind(line.unit_amount)
+            .bind(line.line_amount)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let redemption_ids: Vec<Uuid> = lines.iter().map(|l| l.redemption_id).collect();
+        sqlx::query(
+            "UPDATE rewards.user_redemptions SET settlement_id = $1 WHERE redemption_id = ANY($2)",
+        )
+        .bind(settlement_id)
+        .bind(&redemption_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        let totals = compute_totals(&lines);
+        sqlx::query(
+            r#"
+            UPDATE rewards.merchant_settlements
+            SET redemption_count = $2,
+                total_lumis = $3,
+                amount_owed_to_merchant = $4,
+                amount_owed_by_merchant = $5,
+                net_amount = $6
+            WHERE settlement_id = $1
+            "#,
+        )
+        .bind(settlement_id)
+        .bind(totals.redemption_count)
+        .bind(totals.total_lumis)
+        .bind(totals.owed_to_merchant)
+        .bind(totals.owed_by_merchant)
+        .bind(totals.net())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            "Settlement {} closed for merchant {} period {}: {} redemptions, net {}",
+            settlement_id, merchant_id, period, totals.redemption_count, totals.net()
+        );
+
+        let settlement = self.fetch_settlement(settlement_id, None).await?;
+        Ok(SettlementStatement { settlement, lines })
+    }
+
+    async fn fetch_settlement(
+        &self,
+        settlement_id: Uuid,
+        merchant_id: Option<Uuid>,
+    ) -> Result<Settlement, SettlementError> {
+        sqlx::query_as::<_, Settlement>(
+            r#"
+            SELECT s.settlement_id, s.merchant_id, m.merchant_name, s.period_start, s.period_end,
+                   s.status, s.redemption_count, s.total_lumis, s.amount_owed_to_merchant,
+                   s.amount_owed_by_merchant, s.net_amount, s.currency, s.closed_by, s.closed_at
+            FROM rewards.merchant_settlements s
+            JOIN rewards.merchants m ON m.merchant_id = s.merchant_id
+            WHERE s.settlement_id = $1
+              AND ($2::uuid IS NULL OR s.merchant_id = $2)
+            "#,
+        )
+        .bind(settlement_id)
+        .bind(merchant_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or(SettlementError::NotFound)
+    }
+
+    /// Estado de cuenta con líneas. `merchant_id` restringe a un comercio (portal de comercios)
+    pub async fn get_statement(
+        &self,
+        settlement_id: Uuid,
+        merchant_id: Option<Uuid>,
+    ) -> Result<SettlementStatement, SettlementError> {
+        let settlement = self.fetch_settlement(settlement_id, merchant_id).await?;
+
+        let lines: Vec<SettlementLine> = sqlx::query_as(
+            r#"
+            SELECT redemption_id, redemption_code, offer_id, offer_name, branch_id,
+                   confirmed_at, lumis_spent, term_id, direction, unit_amount, line_amount
+            FROM rewards.merchant_settlement_lines
+            WHERE settlement_id = $1
+            ORDER BY confirmed_at
+            "#,
+        )
+        .bind(settlement_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(SettlementStatement { settlement, lines })
+    }
+
+    /// Listar liquidaciones (más recientes primero)
+    pub async fn list_settlements(
+        &self,
+        merchant_id: Option<Uuid>,
+        period_start: Option<NaiveDate>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Settlement>, SettlementError> {
+        let rows = sqlx::query_as::<_, Settlement>(
+            r#"
+            SELECT s.settlement_id, s.merchant_id, m.merchant_name, s.period_start, s.period_end,
+                   s.status, s.redemption_count, s.total_lumis, s.amount_owed_to_merchant,
+                   s.amount_owed_by_merchant, s.net_amount, s.currency, s.closed_by, s.closed_at
+            FROM rewards.merchant_settlements s
+            JOIN rewards.merchants m ON m.merchant_id = s.merchant_id
+            WHERE ($1::uuid IS NULL OR s.merchant_id = $1)
+              AND ($2::date IS NULL OR s.period_start = $2)
+            ORDER BY s.period_start DESC, m.merchant_name
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(merchant_id)
+        .bind(period_start)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Registrar un término comercial para una oferta (nueva versión desde `effective_from`)
+    pub async fn set_commercial_term(
+        &self,
+        offer_id: Uuid,
+        direction: &str,
+        unit_amount: Decimal,
+        effective_from: NaiveDate,
+        notes: Option<&str>,
+        created_by: i64,
+    ) -> Result<CommercialTerm, SettlementError> {
+        if direction != DIRECTION_REIMBURSED && direction != DIRECTION_FEE {
+            return Err(SettlementError::InvalidTerm(
+                Text::new("api.validation.one_of")
+                    .with("field", "direction")
+                    .with("values", format!("{}, {}", DIRECTION_REIMBURSED, DIRECTION_FEE)),
+            ));
+        }
+        if unit_amount < Decimal::ZERO {
+            return Err(SettlementError::InvalidTerm(
+                Text::new("api.validation.not_negative").with("field", "unit_amount"),
+            ));
+        }
+
+        let offer_exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM rewards.redemption_offers WHERE offer_id = $1)",
+        )
+        .bind(offer_id)
+        .fetch_one(&self.db)
+        .await?;
+        if !offer_exists {
+            return Err(SettlementError::OfferNotFound);
+        }
+
+        let term = sqlx::query_as::<_, CommercialTerm>(
+            r#"
+            INSERT INTO rewards.offer_commercial_terms
+                (offer_id, direction, unit_amount, effective_from, notes, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (offer_id, effective_from) DO UPDATE
+            SET direction = EXCLUDED.direction,
+                unit_amount = EXCLUDED.unit_amount,
+                notes = EXCLUDED.notes,
+                created_by = EXCLUDED.created_by,
+                created_at = NOW()
+            RETURNING term_id, offer_id, direction, unit_amount, currency, effective_from, notes, created_at
+            "#,
+        )
+        .bind(offer_id)
+        .bind(direction)
+        .bind(unit_amount.round_dp(2))
+        .bind(effective_from)
+        .bind(notes)
+        .bind(created_by)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(term)
+    }
+
+    /// Historial de términos de una oferta
+    pub async fn list_commercial_terms(&self, offer_id: Uuid) -> Result<Vec<CommercialTerm>, SettlementError> {
+        let rows = sqlx::query_as::<_, CommercialTerm>(
+            r#"
+            SELECT term_id, offer_id, direction, unit_amount, currency, effective_from, notes, created_at
+            FROM rewards.offer_commercial_terms
+            WHERE offer_id = $1
+            ORDER BY effective_from DESC
+            "#,
+        )
+        .bind(offer_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn line(lumis: i32, direction: Option<&str>, unit: &str) -> SettlementLine {
+        let unit_amount = Decimal::from_str(unit).unwrap();
+        SettlementLine {
+            redemption_id: Uuid::new_v4(),
+            redemption_code: "LUMS-TEST".to_string(),
+            offer_id: Uuid::new_v4(),
+            offer_name: "Oferta".to_string(),
+            branch_id: None,
+            confirmed_at: Utc::now(),
+            lumis_spent: lumis,
+            term_id: None,
+            direction: direction.map(|d| d.to_string()),
+            unit_amount,
+            line_amount: line_amount(direction, unit_amount),
+        }
+    }
+
+    #[test]
+    fn test_parse_period() {
+        let (start, end) = parse_period("2026-09").unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2026, 9, 1).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2026, 10, 1).unwrap());
+
+        let (start, end) = parse_period("2025-12").unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2025, 12, 1).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2026, 1, 1).unwrap());
+
+        assert!(parse_period("2026-13").is_err());
+        assert!(parse_period("septiembre").is_err());
+    }
+
+    #[test]
+    fn test_period_boundaries_use_panama_time() {
+        let start = panama_midnight_utc(NaiveDate::from_ymd_opt(2026, 9, 1).unwrap());
+        assert_eq!(start.to_rfc3339(), "2026-09-01T05:00:00+00:00");
+    }
+
+    #[test]
+    fn test_compute_totals_nets_reimbursements_and_fees() {
+        let lines = vec![
+            line(500, Some(DIRECTION_REIMBURSED), "2.50"),
+            line(300, Some(DIRECTION_REIMBURSED), "2.50"),
+            line(200, Some(DIRECTION_FEE), "0.75"),
+            line(100, None, "0"),
+        ];
+        let totals = compute_totals(&lines);
+
+        assert_eq!(totals.redemption_count, 4);
+        assert_eq!(totals.total_lumis, 1100);
+        assert_eq!(totals.owed_to_merchant, Decimal::from_str("5.00").unwrap());
+        assert_eq!(totals.owed_by_merchant, Decimal::from_str("0.75").unwrap());
+        assert_eq!(totals.net(), Decimal::from_str("4.25").unwrap());
+    }
+
+    #[test]
+    fn test_statement_csv_escapes_and_totals() {
+        let mut l = line(500, Some(DIRECTION_FEE), "1.00");
+        l.offer_name = "2x1 \"Pizza\", grande".to_string();
+        let lines = vec![l];
+        let totals = compute_totals(&lines);
+        let statement = SettlementStatement {
+            settlement: Settlement {
+                settlement_id: Uuid::new_v4(),
+                merchant_id: Uuid::new_v4(),
+                merchant_name: "Pizzería, S.A.".to_string(),
+                period_start: NaiveDate::from_ymd_opt(2026, 9, 1).unwrap(),
+                period_end: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+                status: "closed".to_string(),
+                redemption_count: totals.redemption_count,
+                total_lumis: totals.total_lumis,
+                amount_owed_to_merchant: totals.owed_to_merchant,
+                amount_owed_by_merchant: totals.owed_by_merchant,
+                net_amount: totals.net(),
+                currency: "USD".to_string(),
+                closed_by: Some(1),
+                closed_at: Utc::now(),
+            },
+            lines,
+        };
+
+        let csv = statement_csv(&statement);
+        assert!(csv.contains("\"2x1 \"\"Pizza\"\", grande\""));
+        assert!(csv.contains("Comercio,\"Pizzería, S.A.\""));
+        assert!(csv.contains("Periodo,2026-09-01 a 2026-09-30"));
+        assert!(csv.contains("Neto (USD),-1.00"));
+    }
+}
@@ -0,0 +1,729 @@
+//! Resultados agregados de encuestas para admins y patrocinadores
+//!
+//! Todo se calcula en Rust a partir de la definición tipada y de las
+//! respuestas normalizadas en `fact_user_survey_status.responses`:
+//!
+//! - Distribución por pregunta (opciones o valores de la escala; el texto
+//!   libre solo se cuenta, nunca se exporta).
+//! - Cruce por edad, país o provincia del usuario.
+//! - Embudo: asignadas → completadas → alcanzó/respondió cada pregunta.
+//!
+//! Supresión de celdas pequeñas: cualquier conteo entre 1 y `min_cell - 1` se
+//! oculta. Si en un grupo queda una sola celda oculta, también se oculta la
+//! menor de las visibles para que no se pueda despejar restando del total.
+//! Un grupo (pregunta o segmento) con menos de `min_cell` personas se oculta
+//! completo. `min_cell` nunca baja de `MIN_CELL_SIZE`.
+
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use std::collections::{BTreeMap, HashMap};
+
+use super::definition::{QuestionType, SurveyAnswer, SurveyDefinition};
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+pub const MIN_CELL_SIZE: i64 = 5;
+pub const UNKNOWN_SEGMENT: &str = "desconocido";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    AgeBand,
+    Country,
+    Province,
+}
+
+impl Dimension {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "age_band" | "age" => Some(Dimension::AgeBand),
+            "country" => Some(Dimension::Country),
+            "province" => Some(Dimension::Province),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Dimension::AgeBand => "age_band",
+            Dimension::Country => "country",
+            Dimension::Province => "province",
+        }
+    }
+}
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+/// Una asignación de la encuesta con los datos demográficos del usuario
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct Respondent {
+    pub status: Option<String>,
+    pub responses: Option<serde_json::Value>,
+    pub date_of_birth: Option<String>,
+    pub country: Option<String>,
+    pub province: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Cell {
+    pub value: String,
+    pub label: String,
+    pub count: Option<i64>,
+    pub percentage: Option<f64>,
+    pub suppressed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuestionResults {
+    pub question_id: i32,
+    pub question_text: String,
+    pub question_type: QuestionType,
+    /// Personas a las que se les mostró (ramas `show_if`)
+    pub reached: Option<i64>,
+    pub answered: Option<i64>,
+    pub cells: Vec<Cell>,
+    /// Promedio de la escala (rating)
+    pub mean: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Segment {
+    pub segment: String,
+    pub respondents: Option<i64>,
+    pub suppressed: bool,
+    pub questions: Vec<QuestionResults>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrossTab {
+    pub dimension: Dimension,
+    pub segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FunnelStep {
+    pub step: String,
+    pub question_id: Option<i32>,
+    pub count: Option<i64>,
+    /// Personas que estaban en el paso anterior y no en este
+    pub drop_off: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SurveyResults {
+    pub survey_id: i32,
+    pub title: String,
+    pub min_cell_size: i64,
+    pub assigned: i64,
+    pub completed: i64,
+    pub generated_at: chrono::DateTime<Utc>,
+    pub questions: Vec<QuestionResults>,
+    pub funnel: Vec<FunnelStep>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cross_tab: Option<CrossTab>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AnalyzableSurvey {
+    pub survey_id: i32,
+    pub campaign_id: Option<i32>,
+    pub campaign_name: Option<String>,
+    pub title: String,
+    pub is_active: Option<bool>,
+    pub assigned: i64,
+    pub completed: i64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AnalyticsError {
+    #[error("{}", Text::new("api.not_found.survey"))]
+    NotFound,
+
+    #[error("{}", Text::new("api.survey_results.invalid_definition_reason").with("reason", .0))]
+    InvalidDefinition(Text),
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for AnalyticsError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+/// Rango de edad a partir de la fecha de nacimiento guardada como texto
+/// (`YYYY-MM-DD` desde la app, `DD/MM/AAAA` desde WhatsApp)
+pub fn age_band(date_of_birth: Option<&str>, today: NaiveDate) -> &'static str {
+    let Some(birth) = date_of_birth.and_then(|raw| {
+        let raw = raw.trim();
+        ["%Y-%m-%d", "%d/%m/%Y", "%d-%m-%Y"]
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(raw, format).ok())
+    }) else {
+        return UNKNOWN_SEGMENT;
+    };
+
+    let mut age = today.year() - birth.year();
+    if (today.month(), today.day()) < (birth.month(), birth.day()) {
+        age -= 1;
+    }
+    match age {
+        a if !(0..=110).contains(&a) => UNKNOWN_SEGMENT,
+        0..=17 => "<18",
+        18..=24 => "18-24",
+        25..=34 => "25-34",
+        35..=44 => "35-44",
+        45..=54 => "45-54",
+        55..=64 => "55-64",
+        _ => "65+",
+    }
+}
+
+fn segment_of(respondent: &Respondent, dimension: Dimension, today: NaiveDate) -> String {
+    let normalize = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| UNKNOWN_SEGMENT.to_string())
+    };
+    match dimension {
+        Dimension::AgeBand => age_band(respondent.date_of_birth.as_deref(), today).to_string(),
+        Dimension::Country => normalize(&respondent.country),
+        Dimension::Province => normalize(&respondent.province),
+    }
+}
+
+fn percentage(count: i64, total: i64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (10_000.0 * count as f64 / total as f64).round() / 100.0
+}
+
+/// Aplica supresión primaria y secundaria a las celdas de un grupo
+pub fn suppress_cells(counts: Vec<(String, String, i64)>, total: i64, min_cell: i64) -> Vec<Cell> {
+    let whole_group = total < min_cell;
+    let mut hidden: Vec<bool> = counts
+        .iter()
+        .map(|(_, _, count)| whole_group || (*count > 0 && *count < min_cell))
+        .collect();
+
+    // Una sola celda oculta se despejaría restando las demás del total
+    if hidden.iter().filter(|h| **h).count() == 1 {
+        if let Some(index) = counts
+            .iter()
+            .enumerate()
+            .filter(|(i, (_, _, count))| !hidden[*i] && *count > 0)
+            .min_by_key(|(_, (_, _, count))| *count)
+            .map(|(i, _)| i)
+        {
+            hidden[index] = true;
+        }
+    }
+
+    counts
+        .into_iter()
+        .zip(hidden)
+        .map(|((value, label, count), suppressed)| Cell {
+            value,
+            label,
+            count: (!suppressed).then_some(count),
+            percentage: (!suppressed).then(|| percentage(count, total)),
+            suppressed,
+        })
+        .collect()
+}
+
+fn visible_count(count: i64, min_cell: i64) -> Option<i64> {
+    (count == 0 || count >= min_cell).then_some(count)
+}
+
+/// Respuestas completadas con las preguntas que cada persona alcanzó
+struct Completed<'a> {
+    respondent: &'a Respondent,
+    answers: HashMap<i32, SurveyAnswer>,
+}
+
+fn question_results(definition: &SurveyDefinition, completed: &[&Completed], min_cell: i64) -> Vec<QuestionResults> {
+    definition
+        .questions
+        .iter()
+        .map(|question| {
+            let mut reached = 0;
+            let mut answered = 0;
+            let mut option_counts: BTreeMap<String, i64> = BTreeMap::new();
+            let mut rating_sum = 0i64;
+
+            for entry in completed {
+                if !definition.is_visible(question, &entry.answers) {
+                    continue;
+                }
+                reached += 1;
+                let Some(answer) = entry.answers.get(&question.question_id) else {
+                    continue;
+                };
+                answered += 1;
+                match question.question_type {
+                    QuestionType::SingleChoice | QuestionType::MultipleChoice => {
+                        for value in &answer.selected_options {
+                            *option_counts.entry(value.clone()).or_default() += 1;
+                        }
+                    }
+                    QuestionType::Rating => {
+                        if let Some(value) = answer.numeric_response {
+                            *option_counts.entry(value.to_string()).or_default() += 1;
+                            rating_sum += value as i64;
+                        }
+                    }
+                    QuestionType::OpenText => {}
+                }
+            }
+
+            let counts: Vec<(String, String, i64)> = match question.question_type {
+                QuestionType::SingleChoice | QuestionType::MultipleChoice => question
+                    .options
+                    .iter()
+                    .map(|o| (o.value.clone(), o.text.clone(), option_counts.get(&o.value).copied().unwrap_or(0)))
+                    .collect(),
+                QuestionType::Rating => {
+                    let (min, max) = (question.min_value.unwrap_or(0), question.max_value.unwrap_or(0));
+                    (min..=max)
+                        .map(|v| (v.to_string(), v.to_string(), option_counts.get(&v.to_string()).copied().unwrap_or(0)))
+                        .collect()
+                }
+                QuestionType::OpenText => Vec::new(),
+            };
+
+            let shown = answered >= min_cell;
+            QuestionResults {
+                question_id: question.question_id,
+                question_text: question.question_text.clone(),
+                question_type: question.question_type,
+                reached: visible_count(reached, min_cell),
+                answered: visible_count(answered, min_cell),
+                cells: suppress_cells(counts, answered, min_cell),
+                mean: (question.question_type == QuestionType::Rating && shown)
+                    .then(|| (100.0 * rating_sum as f64 / answered as f64).round() / 100.0),
+            }
+        })
+        .collect()
+}
+
+fn funnel(definition: &SurveyDefinition, assigned: i64, completed: &[&Completed], min_cell: i64) -> Vec<FunnelStep> {
+    let mut steps = vec![
+        ("assigned".to_string(), None, assigned),
+        ("completed".to_string(), None, completed.len() as i64),
+    ];
+    for question in &definition.questions {
+        let answered = completed
+            .iter()
+            .filter(|c| definition.is_visible(question, &c.answers) && c.answers.contains_key(&question.question_id))
+            .count() as i64;
+        steps.push((format!("q{}", question.question_id), Some(question.question_id), answered));
+    }
+
+    let mut previous: Option<i64> = None;
+    steps
+        .into_iter()
+        .map(|(step, question_id, count)| {
+            let drop_off = previous.map(|p| (p - count).max(0));
+            previous = Some(count);
+            FunnelStep {
+                step,
+                question_id,
+                count: visible_count(count, min_cell),
+                drop_off: drop_off.and_then(|d| visible_count(d, min_cell)),
+            }
+        })
+        .collect()
+}
+
+/// Calcula el reporte completo a partir de las asignaciones
+pub fn build_results(
+    survey_id: i32,
+    title: &str,
+    definition: &SurveyDefinition,
+    respondents: &[Respondent],
+    dimension: Option<Dimension>,
+    min_cell: i64,
+    today: NaiveDate,
+) -> SurveyResults {
+    let min_cell = min_cell.max(MIN_CELL_SIZE);
+    let completed: Vec<Completed> = respondents
+        .iter()
+        .filter(|r| r.status.as_deref() == Some("completed"))
+        .map(|respondent| Completed {
+            respondent,
+            answers: respondent
+                .responses
+                .as_ref()
+                .map(|r| definition.stored_answers(r))
+                .unwrap_or_default(),
+        })
+        .collect();
+    let all: Vec<&Completed> = completed.iter().collect();
+
+    let cross_tab = dimension.map(|dimension| {
+        let mut groups: BTreeMap<String, Vec<&Completed>> = BTreeMap::new();
+        for entry in &completed {
+            groups
+                .entry(segment_of(entry.respondent, dimension, today))
+                .or_default()
+                .push(entry);
+        }
+        let sizes = suppress_cells(
+            groups.iter().map(|(segment, members)| (segment.clone(), segment.clone(), members.len() as i64)).collect(),
+            completed.len() as i64,
+            min_cell,
+        );
+        let segments = groups
+            .into_iter()
+            .zip(sizes)
+            .map(|((segment, members), size)| Segment {
+                segment,
+                respondents: size.count,
+                suppressed: size.suppressed,
+                questions: if size.suppressed {
+                    Vec::new()
+                } else {
+                    question_results(definition, &members, min_cell)
+                },
+            })
+            .collect();
+        CrossTab { dimension, segments }
+    });
+
+    SurveyResults {
+        survey_id,
+        title: title.to_string(),
+        min_cell_size: min_cell,
+        assigned: respondents.len() as i64,
+        completed: completed.len() as i64,
+        generated_at: Utc::now(),
+        questions: question_results(definition, &all, min_cell),
+        funnel: funnel(definition, respondents.len() as i64, &all, min_cell),
+        cross_tab,
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// CSV en formato largo: una fila por celda. Las celdas ocultas salen como
+/// `<min_cell` y sin porcentaje.
+pub fn results_csv(results: &SurveyResults) -> String {
+    let mut csv = "seccion,segmento,question_id,pregunta,valor,opcion,conteo,porcentaje\n".to_string();
+    let hidden = format!("<{}", results.min_cell_size);
+    let count = |value: Option<i64>| value.map(|c| c.to_string()).unwrap_or_else(|| hidden.clone());
+
+    let push_questions = |csv: &mut String, section: &str, segment: &str, questions: &[QuestionResults]| {
+        for question in questions {
+            let prefix = format!(
+                "{},{},{},{}",
+                section,
+                csv_field(segment),
+                question.question_id,
+                csv_field(&question.question_text)
+            );
+            csv.push_str(&format!("{},_alcanzaron,,{},\n", prefix, count(question.reached)));
+            csv.push_str(&format!("{},_respondieron,,{},\n", prefix, count(question.answered)));
+            for cell in &question.cells {
+                csv.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    prefix,
+                    csv_field(&cell.value),
+                    csv_field(&cell.label),
+                    count(cell.count),
+                    cell.percentage.map(|p| p.to_string()).unwrap_or_default()
+                ));
+            }
+        }
+    };
+
+    push_questions(&mut csv, "total", "", &results.questions);
+    if let Some(cross_tab) = &results.cross_tab {
+        let section = cross_tab.dimension.as_str();
+        for segment in &cross_tab.segments {
+            if segment.suppressed {
+                csv.push_str(&format!("{},{},,,_respondentes,,{},\n", section, csv_field(&segment.segment), hidden));
+            } else {
+                push_questions(&mut csv, section, &segment.segment, &segment.questions);
+            }
+        }
+    }
+    for step in &results.funnel {
+        csv.push_str(&format!(
+            "embudo,,{},,{},,{},{}\n",
+            step.question_id.map(|q| q.to_string()).unwrap_or_default(),
+            step.step,
+            count(step.count),
+            step.drop_off.map(|d| d.to_string()).unwrap_or_default()
+        ));
+    }
+    csv
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct SurveyAnalyticsService {
+    db: PgPool,
+}
+
+impl SurveyAnalyticsService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Los patrocinadores solo ven las encuestas de sus campañas
+    pub async fn is_sponsor(&self, user_id: i64, survey_id: i32) -> Result<bool, AnalyticsError> {
+        Ok(sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM survey.dim_surveys s
+                JOIN survey.campaign_sponsors cs ON cs.campaign_id = s.campaign_id
+                WHERE s.survey_id = $1 AND cs.user_id = $2
+            )
+            "#,
+        )
+        .bind(survey_id)
+        .bind(user_id)
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    pub async fn add_sponsor(&self, campaign_id: i32, user_id: i64, created_by: i64) -> Result<(), AnalyticsError> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM survey.dim_campaigns WHERE campaign_id = $1)")
+            .bind(campaign_id)
+            .fetch_one(&self.db)
+            .await?;
+        if !exists {
+            return Err(AnalyticsError::NotFound);
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO survey.campaign_sponsors (campaign_id, user_id, created_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (campaign_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(campaign_id)
+        .bind(user_id)
+        .bind(created_by)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_sponsor(&self, campaign_id: i32, user_id: i64) -> Result<bool, AnalyticsError> {
+        let result = sqlx::query("DELETE FROM survey.campaign_sponsors WHERE campaign_id = $1 AND user_id = $2")
+            .bind(campaign_id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Encuestas con resultados disponibles: todas para admins, las de sus
+    /// campañas para patrocinadores
+    pub async fn analyzable_surveys(&self, user_id: i64, all: bool) -> Result<Vec<AnalyzableSurvey>, AnalyticsError> {
+        Ok(sqlx::query_as::<_, AnalyzableSurvey>(
+            r#"
+            SELECT s.survey_id, s.campaign_id, c.name AS campaign_name, s.title, s.is_active,
+                   COUNT(fuss.status_id) AS assigned,
+                   COUNT(fuss.status_id) FILTER (WHERE fuss.status = 'completed') AS completed
+            FROM survey.dim_surveys s
+            LEFT JOIN survey.dim_campaigns c ON c.campaign_id = s.campaign_id
+            LEFT JOIN survey.fact_user_survey_status fuss ON fuss.survey_id = s.survey_id
+            WHERE $2 OR s.campaign_id IN (SELECT campaign_id FROM survey.campaign_sponsors WHERE user_id = $1)
+            GROUP BY s.survey_id, s.campaign_id, c.name, s.title, s.is_active
+            ORDER BY s.survey_id DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(all)
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    pub async fn results(
+        &self,
+        survey_id: i32,
+        dimension: Option<Dimension>,
+        min_cell: i64,
+    ) -> Result<SurveyResults, AnalyticsError> {
+        let (title, questions): (String, serde_json::Value) =
+            sqlx::query_as("SELECT title, questions FROM survey.dim_surveys WHERE survey_id = $1")
+                .bind(survey_id)
+                .fetch_optional(&self.db)
+                .await?
+                .ok_or(AnalyticsError::NotFound)?;
+        let definition = SurveyDefinition::parse(&questions).map_err(AnalyticsError::InvalidDefinition)?;
+
+        let respondents = sqlx::query_as::<_, Respondent>(
+            r#"
+            SELECT fuss.status, fuss.responses, u.date_of_birth::TEXT AS date_of_birth,
+                   u.country_residence AS country, u.province
+            FROM survey.fact_user_survey_status fuss
+            LEFT JOIN public.dim_users u ON u.id = fuss.user_id
+            WHERE fuss.survey_id = $1
+            "#,
+        )
+        .bind(survey_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        let today = Utc::now().with_timezone(&chrono_tz::America::Panama).date_naive();
+        Ok(build_results(survey_id, &title, &definition, &respondents, dimension, min_cell, today))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn definition() -> SurveyDefinition {
+        SurveyDefinition::parse(&json!({"questions": [
+            {
+                "question_id": 1,
+                "question_text": "¿Pides delivery?",
+                "question_type": "single_choice",
+                "options": [{"value": "A", "text": "Sí"}, {"value": "B", "text": "No"}, {"value": "C", "text": "A veces"}]
+            },
+            {
+                "question_id": 2,
+                "question_text": "Satisfacción",
+                "question_type": "rating",
+                "min_value": 1,
+                "max_value": 3,
+                "show_if": [{"question_id": 1, "any_of": ["A"]}]
+            }
+        ]}))
+        .unwrap()
+    }
+
+    fn respondent(choice: &str, rating: Option<i32>, country: &str) -> Respondent {
+        let mut responses = vec![json!({"question_id": 1, "selected_options": [choice]})];
+        if let Some(value) = rating {
+            responses.push(json!({"question_id": 2, "numeric_response": value}));
+        }
+        Respondent {
+            status: Some("completed".into()),
+            responses: Some(json!({ "responses": responses })),
+            date_of_birth: Some("1990-05-01".into()),
+            country: Some(country.into()),
+            province: None,
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+    }
+
+    #[test]
+    fn test_age_band_formats() {
+        assert_eq!(age_band(Some("2000-10-19"), today()), "25-34");
+        assert_eq!(age_band(Some("2000-10-20"), today()), "25-34");
+        assert_eq!(age_band(Some("19/10/2008"), today()), "<18");
+        assert_eq!(age_band(Some("18/10/2008"), today()), "18-24");
+        assert_eq!(age_band(Some("1950-01-01"), today()), "65+");
+        assert_eq!(age_band(Some("ayer"), today()), UNKNOWN_SEGMENT);
+        assert_eq!(age_band(None, today()), UNKNOWN_SEGMENT);
+    }
+
+    #[test]
+    fn test_primary_and_secondary_suppression() {
+        let cells = suppress_cells(
+            vec![("A".into(), "Sí".into(), 12), ("B".into(), "No".into(), 2), ("C".into(), "A veces".into(), 7)],
+            21,
+            5,
+        );
+        // B se oculta por pequeña y C (la menor visible) para no despejar B
+        assert_eq!(cells[0].count, Some(12));
+        assert!(cells[1].suppressed && cells[2].suppressed);
+        assert_eq!(cells[2].percentage, None);
+
+        let zeros = suppress_cells(vec![("A".into(), "Sí".into(), 10), ("B".into(), "No".into(), 0)], 10, 5);
+        assert!(zeros.iter().all(|c| !c.suppressed));
+
+        let small_group = suppress_cells(vec![("A".into(), "Sí".into(), 3), ("B".into(), "No".into(), 1)], 4, 5);
+        assert!(small_group.iter().all(|c| c.suppressed && c.count.is_none()));
+    }
+
+    #[test]
+    fn test_distribution_and_branch_reach() {
+        let mut respondents: Vec<Respondent> = (0..6).map(|_| respondent("A", Some(3), "PA")).collect();
+        respondents.extend((0..5).map(|_| respondent("B", None, "PA")));
+        respondents.push(Respondent { status: Some("pending".into()), ..Default::default() });
+
+        let results = build_results(7, "Delivery", &definition(), &respondents, None, 5, today());
+        assert_eq!((results.assigned, results.completed), (12, 11));
+
+        let q1 = &results.questions[0];
+        assert_eq!(q1.answered, Some(11));
+        assert_eq!(q1.cells[0].count, Some(6));
+        assert_eq!(q1.cells[1].count, Some(5));
+        assert_eq!(q1.cells[2].count, Some(0));
+
+        // La 2 solo se muestra a quienes eligieron A
+        let q2 = &results.questions[1];
+        assert_eq!(q2.reached, Some(6));
+        assert_eq!(q2.mean, Some(3.0));
+
+        let steps: Vec<(&str, Option<i64>)> = results.funnel.iter().map(|s| (s.step.as_str(), s.count)).collect();
+        assert_eq!(steps, vec![("assigned", Some(12)), ("completed", Some(11)), ("q1", Some(11)), ("q2", Some(6))]);
+        assert_eq!(results.funnel[3].drop_off, Some(5));
+        assert_eq!(results.funnel[1].drop_off, None);
+    }
+
+    #[test]
+    fn test_cross_tab_hides_small_segments() {
+        let mut respondents: Vec<Respondent> = (0..8).map(|_| respondent("A", Some(2), "PA")).collect();
+        respondents.extend((0..2).map(|_| respondent("B", None, "CR")));
+        respondents.push(respondent("B", None, "CO"));
+
+        let results = build_results(7, "Delivery", &definition(), &respondents, Some(Dimension::Country), 5, today());
+        let cross_tab = results.cross_tab.unwrap();
+        let by_segment: HashMap<&str, &Segment> = cross_tab.segments.iter().map(|s| (s.segment.as_str(), s)).collect();
+
+        assert_eq!(by_segment["PA"].respondents, Some(8));
+        assert!(!by_segment["PA"].questions.is_empty());
+        assert!(by_segment["CR"].suppressed && by_segment["CR"].questions.is_empty());
+        assert!(by_segment["CO"].suppressed);
+    }
+
+    #[test]
+    fn test_csv_never_prints_suppressed_counts() {
+        let respondents: Vec<Respondent> = (0..6)
+            .map(|_| respondent("A", Some(1), "PA"))
+            .chain(std::iter::once(respondent("C", None, "PA")))
+            .collect();
+        let results = build_results(7, "Delivery", &definition(), &respondents, None, 5, today());
+        let csv = results_csv(&results);
+
+        assert!(csv.starts_with("seccion,segmento,question_id"));
+        assert!(csv.contains("total,,1,¿Pides delivery?,C,A veces,<5,\n"));
+        assert!(!csv.contains(",C,A veces,1,"));
+    }
+}
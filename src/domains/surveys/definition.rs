@@ -0,0 +1,862 @@
+//! Definición tipada de encuestas
+//!
+//! `survey.dim_surveys.questions` guarda `{"questions": [...]}`. Aquí se parsea
+//! a tipos de Rust, se valida al publicarla y se usa para validar y puntuar las
+//! respuestas en el servidor:
+//!
+//! ```json
+//! {
+//!   "question_id": 4,
+//!   "question_text": "¿Qué tan seguido pides delivery?",
+//!   "question_type": "single_choice",
+//!   "options": [{"value": "A", "text": "Nunca"}, {"value": "B", "text": "Cada semana"}],
+//!   "required": true,
+//!   "show_if": [{"question_id": 1, "any_of": ["C", "D"]}],
+//!   "weight": 20
+//! }
+//! ```
+//!
+//! - `show_if`: todas las condiciones deben cumplirse y solo pueden apuntar a
+//!   preguntas anteriores. Una pregunta oculta no se responde ni puntúa.
+//! - Puntaje: las preguntas con opciones `is_correct: true` son de quiz y dan
+//!   `weight` (o `points_per_question`) solo si la selección es exactamente la
+//!   correcta. Las de opinión dan los puntos por responderlas.
+//! - `correct_answers` cuenta solo aciertos en preguntas de quiz.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionType {
+    SingleChoice,
+    MultipleChoice,
+    OpenText,
+    /// Escala numérica entre `min_value` y `max_value`
+    Rating,
+}
+
+impl QuestionType {
+    fn is_choice(self) -> bool {
+        matches!(self, QuestionType::SingleChoice | QuestionType::MultipleChoice)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestionOption {
+    pub value: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_correct: Option<bool>,
+    /// Tags de segmentación que recibe quien elige esta opción
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// Condición sobre la respuesta a una pregunta anterior
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShowIf {
+    pub question_id: i32,
+    /// Se eligió al menos una de estas opciones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub any_of: Option<Vec<String>>,
+    /// No se eligió ninguna de estas opciones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub none_of: Option<Vec<String>>,
+    /// Para preguntas rating
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_value: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SurveyQuestion {
+    pub question_id: i32,
+    pub question_text: String,
+    pub question_type: QuestionType,
+    #[serde(default)]
+    pub options: Vec<QuestionOption>,
+    /// Las definiciones anteriores no traían el campo y se respondían sin
+    /// obligación, así que por defecto es opcional
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub show_if: Vec<ShowIf>,
+    /// Puntos de la pregunta; por defecto `points_per_question` de la encuesta
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_selections: Option<usize>,
+    /// Límites de texto (open_text) o de escala (rating)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_value: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SurveyDefinition {
+    pub questions: Vec<SurveyQuestion>,
+}
+
+/// Respuesta a una pregunta, con la forma que ya guardaba `responses`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SurveyAnswer {
+    pub question_id: i32,
+    #[serde(default)]
+    pub selected_options: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_response: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numeric_response: Option<i32>,
+    /// Formato documentado originalmente (`"answer": "A"` o `["A", "C"]`);
+    /// se traduce según el tipo de pregunta
+    #[serde(default, skip_serializing)]
+    pub answer: Option<serde_json::Value>,
+    /// Lo calcula el servidor; se ignora lo que mande el cliente
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_correct: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResponseIssue {
+    pub question_id: i32,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ResponseIssue {
+    /// El mensaje se renderiza en el idioma de la petición en curso
+    fn new(question_id: i32, code: &'static str, message: Text) -> Self {
+        Self { question_id, code, message: message.to_string() }
+    }
+}
+
+/// Respuestas validadas y puntuadas
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScoredResponses {
+    /// Solo preguntas visibles, en el orden de la encuesta
+    pub answers: Vec<SurveyAnswer>,
+    pub visible_questions: i32,
+    pub answered_questions: i32,
+    pub scorable_questions: i32,
+    pub correct_answers: i32,
+    pub total_score: i32,
+    pub max_score: i32,
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+pub const MAX_QUESTIONS: usize = 100;
+pub const MAX_OPEN_TEXT_LENGTH: usize = 2_000;
+
+/// Campos que el cliente no debe ver, en cualquier nivel del JSON
+const ANSWER_KEYS: &[&str] = &["is_correct", "weight", "tags", "correct_option", "correct_answer", "correct_answers"];
+
+/// Quita `ANSWER_KEYS` de preguntas que no respetan el esquema tipado
+pub fn strip_raw_answer_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|key, _| !ANSWER_KEYS.contains(&key.as_str()));
+            map.values_mut().for_each(strip_raw_answer_keys);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_raw_answer_keys),
+        _ => {}
+    }
+}
+
+impl SurveyDefinition {
+    /// Acepta `{"questions": [...]}` o directamente el arreglo
+    pub fn parse(value: &serde_json::Value) -> Result<Self, Text> {
+        let parsed = if value.is_array() {
+            serde_json::from_value::<Vec<SurveyQuestion>>(value.clone()).map(|questions| Self { questions })
+        } else {
+            serde_json::from_value::<Self>(value.clone())
+        };
+        parsed.map_err(|e| Text::new("api.survey.invalid_definition_json").with("error", e))
+    }
+
+    pub fn question(&self, question_id: i32) -> Option<&SurveyQuestion> {
+        self.questions.iter().find(|q| q.question_id == question_id)
+    }
+
+    /// Versión para el cliente: sin respuestas correctas ni pesos
+    pub fn public_view(&self) -> Self {
+        let mut view = self.clone();
+        for question in &mut view.questions {
+            question.weight = None;
+            for option in &mut question.options {
+                option.is_correct = None;
+                option.tags.clear();
+            }
+        }
+        view
+    }
+
+    /// Reemplaza `survey.questions` del detalle que devuelve `survey.api_get_survey_details`
+    /// por su versión pública. Sin preguntas no hay nada que limpiar. Si la
+    /// definición no parsea se devuelve el error, pero las preguntas quedan
+    /// igual sin respuestas correctas, pesos ni tags (ver `strip_raw_answer_keys`).
+    pub fn strip_answer_keys(detail: &mut serde_json::Value) -> Result<(), Text> {
+        let Some(questions) = detail.pointer_mut("/survey/questions") else {
+            return Ok(());
+        };
+        match Self::parse(questions) {
+            Ok(definition) => {
+                *questions = serde_json::to_value(definition.public_view())
+                    .map_err(|e| Text::new("api.validation.serialization").with("error", e))?;
+                Ok(())
+            }
+            Err(e) => {
+                strip_raw_answer_keys(questions);
+                Err(e)
+            }
+        }
+    }
+
+    /// Si la pregunta aplica según las respuestas a preguntas anteriores
+    pub fn is_visible(&self, question: &SurveyQuestion, answers: &HashMap<i32, SurveyAnswer>) -> bool {
+        question
+            .show_if
+            .iter()
+            .all(|c| condition_holds(c, answers.get(&c.question_id)))
+    }
+
+    /// Respuestas guardadas por pregunta, sin validar (para reportes). Se
+    /// descartan las que no corresponden a la definición actual.
+    pub fn stored_answers(&self, responses: &serde_json::Value) -> HashMap<i32, SurveyAnswer> {
+        parse_answers(responses)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|answer| {
+                let question = self.question(answer.question_id)?;
+                let resolved = resolve_legacy_answer(question, answer);
+                (!is_blank(&resolved)).then_some((answer.question_id, resolved))
+            })
+            .collect()
+    }
+
+    /// Tags de las opciones elegidas en un intento guardado, con la etiqueta
+    /// "pregunta: opción" que los explica
+    pub fn selected_option_tags(&self, responses: &serde_json::Value) -> Vec<(String, Vec<String>)> {
+        let answers = self.stored_answers(responses);
+        let mut tagged = Vec::new();
+        for question in &self.questions {
+            let Some(answer) = answers.get(&question.question_id) else {
+                continue;
+            };
+            for option in &question.options {
+                if !option.tags.is_empty() && answer.selected_options.contains(&option.value) {
+                    tagged.push((format!("{}: {}", question.question_text, option.text), option.tags.clone()));
+                }
+            }
+        }
+        tagged
+    }
+
+    pub fn validate(&self) -> Result<(), Text> {
+        if self.questions.is_empty() || self.questions.len() > MAX_QUESTIONS {
+            return Err(Text::new("api.survey.question_count").with("max", MAX_QUESTIONS));
+        }
+
+        let mut seen: HashMap<i32, &SurveyQuestion> = HashMap::new();
+        for question in &self.questions {
+            let id = question.question_id;
+            let fail = |reason: Text| {
+                Err(Text::new("api.survey.invalid_question").with("question_id", id).with("reason", reason))
+            };
+
+            if seen.contains_key(&id) {
+                return fail(Text::new("api.survey.duplicate_question_id"));
+            }
+            if question.question_text.trim().is_empty() {
+                return fail(Text::new("api.validation.required").with("field", "question_text"));
+            }
+            if question.weight.is_some_and(|w| w < 0) {
+                return fail(Text::new("api.validation.not_negative").with("field", "weight"));
+            }
+
+            if question.question_type.is_choice() {
+                if question.options.len() < 2 {
+                    return fail(Text::new("api.survey.min_options"));
+                }
+                let values: HashSet<&str> = question.options.iter().map(|o| o.value.as_str()).collect();
+                if values.len() != question.options.len() || values.contains("") {
+                    return fail(Text::new("api.survey.option_values"));
+                }
+                if question.options.iter().flat_map(|o| &o.tags).any(|tag| tag.trim().is_empty()) {
+                    return fail(Text::new("api.validation.empty_value").with("field", "options.tags"));
+                }
+                let correct = question.options.iter().filter(|o| o.is_correct == Some(true)).count();
+                if question.question_type == QuestionType::SingleChoice && correct > 1 {
+                    return fail(Text::new("api.survey.single_choice_correct"));
+                }
+                if let Some(max) = question.max_selections {
+                    if question.question_type == QuestionType::SingleChoice || max == 0 || max > question.options.len() {
+                        return fail(Text::new("api.validation.out_of_range").with("field", "max_selections"));
+                    }
+                    if correct > max {
+                        return fail(Text::new("api.survey.too_many_correct"));
+                    }
+                }
+            } else if !question.options.is_empty() && question.question_type == QuestionType::Rating {
+                return fail(Text::new("api.survey.rating_options"));
+            }
+
+            match question.question_type {
+                QuestionType::Rating => match (question.min_value, question.max_value) {
+                    (Some(min), Some(max)) if min < max => {}
+                    _ => return fail(Text::new("api.survey.rating_range")),
+                },
+                QuestionType::OpenText => {
+                    let min = question.min_length.unwrap_or(0);
+                    let max = question.max_length.unwrap_or(MAX_OPEN_TEXT_LENGTH);
+                    if min > max || max > MAX_OPEN_TEXT_LENGTH {
+                        return fail(Text::new("api.validation.out_of_range").with("field", "min_length/max_length"));
+                    }
+                }
+                _ => {}
+            }
+
+            for condition in &question.show_if {
+                let Some(target) = seen.get(&condition.question_id) else {
+                    return fail(Text::new("api.survey.show_if_forward"));
+                };
+                validate_condition(condition, target).map_err(|reason| {
+                    Text::new("api.survey.invalid_show_if").with("question_id", id).with("reason", reason)
+                })?;
+            }
+
+            seen.insert(id, question);
+        }
+        Ok(())
+    }
+
+    /// Valida las respuestas contra la definición y calcula el puntaje
+    pub fn evaluate(&self, answers: &[SurveyAnswer], points_per_question: i32) -> Result<ScoredResponses, Vec<ResponseIssue>> {
+        let mut issues = Vec::new();
+        let mut by_question: HashMap<i32, SurveyAnswer> = HashMap::new();
+        for answer in answers {
+            let Some(question) = self.question(answer.question_id) else {
+                issues.push(ResponseIssue::new(
+                    answer.question_id,
+                    "UNKNOWN_QUESTION",
+                    Text::new("api.survey.answer.unknown_question"),
+                ));
+                continue;
+            };
+            if by_question.insert(answer.question_id, resolve_legacy_answer(question, answer)).is_some() {
+                issues.push(ResponseIssue::new(
+                    answer.question_id,
+                    "DUPLICATE_ANSWER",
+                    Text::new("api.survey.answer.duplicate"),
+                ));
+            }
+        }
+
+        let mut scored = ScoredResponses {
+            answers: Vec::new(),
+            visible_questions: 0,
+            answered_questions: 0,
+            scorable_questions: 0,
+            correct_answers: 0,
+            total_score: 0,
+            max_score: 0,
+        };
+        // Respuestas ya aceptadas, para evaluar show_if
+        let mut accepted: HashMap<i32, SurveyAnswer> = HashMap::new();
+
+        for question in &self.questions {
+            let id = question.question_id;
+            let visible = self.is_visible(question, &accepted);
+            let answer = by_question.get(&id).filter(|a| !is_blank(a));
+
+            if !visible {
+                if answer.is_some() {
+                    issues.push(ResponseIssue::new(id, "HIDDEN_QUESTION", Text::new("api.survey.answer.hidden")));
+                }
+                continue;
+            }
+
+            let points = question.weight.unwrap_or(points_per_question).max(0);
+            let correct_values: HashSet<&str> = question
+                .options
+                .iter()
+                .filter(|o| o.is_correct == Some(true))
+                .map(|o| o.value.as_str())
+                .collect();
+            let scorable = !correct_values.is_empty();
+
+            scored.visible_questions += 1;
+            scored.max_score += points;
+            if scorable {
+                scored.scorable_questions += 1;
+            }
+
+            let Some(answer) = answer else {
+                if question.required {
+                    issues.push(ResponseIssue::new(id, "REQUIRED", Text::new("api.survey.answer.required")));
+                }
+                continue;
+            };
+            if let Err(message) = check_answer(question, answer) {
+                issues.push(ResponseIssue::new(id, "INVALID_ANSWER", message));
+                continue;
+            }
+
+            let mut normalized = normalize(question, answer);
+            if scorable {
+                let selected: HashSet<&str> = normalized.selected_options.iter().map(String::as_str).collect();
+                let correct = selected == correct_values;
+                normalized.is_correct = Some(correct);
+                if correct {
+                    scored.correct_answers += 1;
+                    scored.total_score += points;
+                }
+            } else {
+                scored.total_score += points;
+            }
+            scored.answered_questions += 1;
+            accepted.insert(id, normalized.clone());
+            scored.answers.push(normalized);
+        }
+
+        if issues.is_empty() {
+            Ok(scored)
+        } else {
+            Err(issues)
+        }
+    }
+}
+
+fn validate_condition(condition: &ShowIf, target: &SurveyQuestion) -> Result<(), Text> {
+    let option_sets = [&condition.any_of, &condition.none_of];
+    let has_options = option_sets.iter().any(|s| s.is_some());
+    let has_range = condition.min_value.is_some() || condition.max_value.is_some();
+
+    if !has_options && !has_range {
+        return Err(Text::new("api.survey.show_if_empty"));
+    }
+    if has_options {
+        if !target.question_type.is_choice() {
+            return Err(Text::new("api.survey.show_if_requires_choice").with("question_id", target.question_id));
+        }
+        for values in option_sets.into_iter().flatten() {
+            if values.is_empty() {
+                return Err(Text::new("api.validation.not_empty").with("field", "any_of/none_of"));
+            }
+            if let Some(unknown) = values.iter().find(|v| !target.options.iter().any(|o| &o.value == *v)) {
+                return Err(Text::new("api.survey.show_if_unknown_option")
+                    .with("value", unknown)
+                    .with("question_id", target.question_id));
+            }
+        }
+    }
+    if has_range && target.question_type != QuestionType::Rating {
+        return Err(Text::new("api.survey.show_if_requires_rating").with("question_id", target.question_id));
+    }
+    Ok(())
+}
+
+/// Una condición sobre una pregunta no respondida (u oculta) no se cumple
+fn condition_holds(condition: &ShowIf, answer: Option<&SurveyAnswer>) -> bool {
+    let Some(answer) = answer else {
+        return false;
+    };
+    let selected = |values: &Vec<String>| answer.selected_options.iter().any(|s| values.contains(s));
+
+    condition.any_of.as_ref().is_none_or(selected)
+        && condition.none_of.as_ref().is_none_or(|values| !selected(values))
+        && condition
+            .min_value
+            .is_none_or(|min| answer.numeric_response.is_some_and(|v| v >= min))
+        && condition
+            .max_value
+            .is_none_or(|max| answer.numeric_response.is_some_and(|v| v <= max))
+}
+
+/// Traduce `answer` al campo que corresponde al tipo de pregunta
+fn resolve_legacy_answer(question: &SurveyQuestion, answer: &SurveyAnswer) -> SurveyAnswer {
+    let mut resolved = answer.clone();
+    let Some(legacy) = resolved.answer.take() else {
+        return resolved;
+    };
+    match (question.question_type, legacy) {
+        (QuestionType::SingleChoice | QuestionType::MultipleChoice, serde_json::Value::String(value))
+            if resolved.selected_options.is_empty() =>
+        {
+            resolved.selected_options = vec![value];
+        }
+        (QuestionType::SingleChoice | QuestionType::MultipleChoice, serde_json::Value::Array(values))
+            if resolved.selected_options.is_empty() =>
+        {
+            // Un valor que no es texto deja la respuesta inválida en vez de ignorarlo
+            resolved.selected_options = values
+                .into_iter()
+                .map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string()))
+                .collect();
+        }
+        (QuestionType::OpenText, serde_json::Value::String(text)) if resolved.text_response.is_none() => {
+            resolved.text_response = Some(text);
+        }
+        (QuestionType::Rating, value) if resolved.numeric_response.is_none() => {
+            resolved.numeric_response = value
+                .as_i64()
+                .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+                .and_then(|v| i32::try_from(v).ok());
+        }
+        _ => {}
+    }
+    resolved
+}
+
+fn is_blank(answer: &SurveyAnswer) -> bool {
+    answer.selected_options.is_empty()
+        && answer.text_response.as_deref().is_none_or(|t| t.trim().is_empty())
+        && answer.numeric_response.is_none()
+}
+
+fn check_answer(question: &SurveyQuestion, answer: &SurveyAnswer) -> Result<(), Text> {
+    match question.question_type {
+        QuestionType::SingleChoice | QuestionType::MultipleChoice => {
+            let unique: HashSet<&String> = answer.selected_options.iter().collect();
+            if unique.len() != answer.selected_options.len() {
+                return Err(Text::new("api.survey.answer.repeated_options"));
+            }
+            if let Some(unknown) = answer
+                .selected_options
+                .iter()
+                .find(|v| !question.options.iter().any(|o| &&o.value == v))
+            {
+                return Err(Text::new("api.survey.answer.unknown_option").with("value", unknown));
+            }
+            let max = match question.question_type {
+                QuestionType::SingleChoice => 1,
+                _ => question.max_selections.unwrap_or(question.options.len()),
+            };
+            if answer.selected_options.is_empty() || answer.selected_options.len() > max {
+                return Err(Text::new("api.survey.answer.selection_count").with("max", max));
+            }
+        }
+        QuestionType::OpenText => {
+            let length = answer.text_response.as_deref().unwrap_or("").trim().chars().count();
+            let min = question.min_length.unwrap_or(1);
+            let max = question.max_length.unwrap_or(MAX_OPEN_TEXT_LENGTH);
+            if length < min || length > max {
+                return Err(Text::new("api.survey.answer.text_length").with("min", min).with("max", max));
+            }
+        }
+        QuestionType::Rating => {
+            let (min, max) = (question.min_value.unwrap_or(0), question.max_value.unwrap_or(0));
+            match answer.numeric_response {
+                Some(value) if (min..=max).contains(&value) => {}
+                _ => return Err(Text::new("api.survey.answer.value_range").with("min", min).with("max", max)),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Solo conserva los campos que aplican al tipo de pregunta
+fn normalize(question: &SurveyQuestion, answer: &SurveyAnswer) -> SurveyAnswer {
+    let mut normalized = SurveyAnswer { question_id: answer.question_id, ..Default::default() };
+    match question.question_type {
+        QuestionType::SingleChoice | QuestionType::MultipleChoice => {
+            // Orden de la definición, para comparar y agregar de forma estable
+            normalized.selected_options = question
+                .options
+                .iter()
+                .filter(|o| answer.selected_options.contains(&o.value))
+                .map(|o| o.value.clone())
+                .collect();
+        }
+        QuestionType::OpenText => normalized.text_response = answer.text_response.as_ref().map(|t| t.trim().to_string()),
+        QuestionType::Rating => normalized.numeric_response = answer.numeric_response,
+    }
+    normalized
+}
+
+/// Extrae las respuestas de `{"responses": [...]}`, `{"answers": [...]}` o de un arreglo
+pub fn parse_answers(value: &serde_json::Value) -> Result<Vec<SurveyAnswer>, Text> {
+    let list = value.get("responses").or_else(|| value.get("answers")).unwrap_or(value);
+    serde_json::from_value::<Vec<SurveyAnswer>>(list.clone())
+        .map_err(|e| Text::new("api.survey.invalid_answers_json").with("error", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn survey() -> SurveyDefinition {
+        SurveyDefinition::parse(&json!({
+            "questions": [
+                {
+                    "question_id": 1,
+                    "question_text": "¿Pides delivery?",
+                    "question_type": "single_choice",
+                    "required": true,
+                    "options": [{"value": "A", "text": "Sí"}, {"value": "B", "text": "No"}]
+                },
+                {
+                    "question_id": 2,
+                    "question_text": "¿Qué app usas?",
+                    "question_type": "multiple_choice",
+                    "required": true,
+                    "max_selections": 2,
+                    "options": [{"value": "A", "text": "PedidosYa", "tags": ["delivery_pedidosya"]}, {"value": "B", "text": "Uber Eats"}, {"value": "C", "text": "Appetito"}],
+                    "show_if": [{"question_id": 1, "any_of": ["A"]}]
+                },
+                {
+                    "question_id": 3,
+                    "question_text": "¿Capital de Panamá?",
+                    "question_type": "single_choice",
+                    "required": true,
+                    "weight": 20,
+                    "options": [{"value": "A", "text": "Colón", "is_correct": false}, {"value": "B", "text": "Ciudad de Panamá", "is_correct": true}]
+                },
+                {
+                    "question_id": 4,
+                    "question_text": "Del 1 al 5, ¿qué tan satisfecho estás?",
+                    "question_type": "rating",
+                    "required": true,
+                    "min_value": 1,
+                    "max_value": 5
+                },
+                {
+                    "question_id": 5,
+                    "question_text": "¿Qué mejorarías?",
+                    "question_type": "open_text",
+                    "required": false,
+                    "show_if": [{"question_id": 4, "max_value": 2}]
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn choice(question_id: i32, values: &[&str]) -> SurveyAnswer {
+        SurveyAnswer {
+            question_id,
+            selected_options: values.iter().map(|v| v.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn rating(question_id: i32, value: i32) -> SurveyAnswer {
+        SurveyAnswer { question_id, numeric_response: Some(value), ..Default::default() }
+    }
+
+    fn codes(result: Result<ScoredResponses, Vec<ResponseIssue>>) -> Vec<(i32, &'static str)> {
+        result.unwrap_err().into_iter().map(|i| (i.question_id, i.code)).collect()
+    }
+
+    #[test]
+    fn test_parses_legacy_definition() {
+        let legacy = json!({"questions": [{
+            "question_id": 1,
+            "question_text": "Describe tu experiencia:",
+            "question_type": "open_text",
+            "options": [],
+            "explanation": "Pregunta abierta"
+        }, {
+            "question_id": 2,
+            "question_text": "¿Con qué frecuencia?",
+            "question_type": "single_choice",
+            "options": [{"value": "A", "text": "Nunca", "is_correct": null}, {"value": "B", "text": "Siempre", "is_correct": null}]
+        }]});
+        let definition = SurveyDefinition::parse(&legacy).unwrap();
+        assert!(definition.validate().is_ok());
+        assert!(!definition.questions[0].required);
+        assert!(!definition.questions[1].required);
+    }
+
+    #[test]
+    fn test_strip_answer_keys_from_nested_detail() {
+        let mut detail = json!({
+            "survey": {
+                "survey_id": 7,
+                "questions": serde_json::to_value(survey()).unwrap()
+            },
+            "user_status": {"status": "pending"}
+        });
+        SurveyDefinition::strip_answer_keys(&mut detail).unwrap();
+
+        let questions = detail.pointer("/survey/questions").unwrap();
+        let text = questions.to_string();
+        assert!(!text.contains("is_correct") && !text.contains("weight"));
+        let public = SurveyDefinition::parse(questions).unwrap();
+        assert_eq!(public.questions.len(), survey().questions.len());
+        assert_eq!(detail["user_status"]["status"], "pending");
+
+        // Una definición que no parsea se devuelve igual, sin claves de respuesta
+        let mut broken = json!({"survey": {"questions": {"questions": [{
+            "question_id": 1,
+            "question_type": "ranking",
+            "weight": 5,
+            "options": [{"value": "A", "text": "Sí", "is_correct": true, "tags": ["vip"]}]
+        }]}}});
+        assert!(SurveyDefinition::strip_answer_keys(&mut broken).is_err());
+        assert_eq!(
+            broken,
+            json!({"survey": {"questions": {"questions": [{
+                "question_id": 1,
+                "question_type": "ranking",
+                "options": [{"value": "A", "text": "Sí"}]
+            }]}}})
+        );
+
+        // Sin `survey.questions` el detalle no se toca
+        let mut empty = json!({"survey": {"survey_id": 7}});
+        assert!(SurveyDefinition::strip_answer_keys(&mut empty).is_ok());
+        assert_eq!(empty, json!({"survey": {"survey_id": 7}}));
+    }
+
+    #[test]
+    fn test_validate_rejects_forward_and_invalid_conditions() {
+        let mut definition = survey();
+        definition.questions[1].show_if = vec![ShowIf { question_id: 4, min_value: Some(3), ..Default::default() }];
+        assert!(definition.validate().unwrap_err().to_string().contains("anteriores"));
+
+        let mut definition = survey();
+        definition.questions[1].show_if = vec![ShowIf { question_id: 1, any_of: Some(vec!["Z".into()]), ..Default::default() }];
+        assert!(definition.validate().is_err());
+
+        let mut definition = survey();
+        definition.questions[4].show_if = vec![ShowIf { question_id: 4, any_of: Some(vec!["A".into()]), ..Default::default() }];
+        assert!(definition.validate().is_err());
+
+        assert!(survey().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_questions() {
+        let mut definition = survey();
+        definition.questions[2].question_id = 1;
+        assert!(definition.validate().is_err());
+
+        let mut definition = survey();
+        definition.questions[3].max_value = Some(1);
+        assert!(definition.validate().is_err());
+
+        let mut definition = survey();
+        definition.questions[1].max_selections = Some(4);
+        assert!(definition.validate().is_err());
+    }
+
+    #[test]
+    fn test_branch_taken_requires_follow_up() {
+        let answers = vec![choice(1, &["A"]), choice(3, &["B"]), rating(4, 4)];
+        assert_eq!(codes(survey().evaluate(&answers, 10)), vec![(2, "REQUIRED")]);
+    }
+
+    #[test]
+    fn test_branch_not_taken_skips_and_rejects_hidden_answers() {
+        let answers = vec![choice(1, &["B"]), choice(3, &["B"]), rating(4, 5)];
+        let scored = survey().evaluate(&answers, 10).unwrap();
+        assert_eq!(scored.visible_questions, 3);
+        assert_eq!(scored.answered_questions, 3);
+
+        let mut with_hidden = answers.clone();
+        with_hidden.push(choice(2, &["A"]));
+        assert_eq!(codes(survey().evaluate(&with_hidden, 10)), vec![(2, "HIDDEN_QUESTION")]);
+    }
+
+    #[test]
+    fn test_scoring_quiz_and_opinion_questions() {
+        let answers = vec![choice(1, &["A"]), choice(2, &["C", "A"]), choice(3, &["B"]), rating(4, 2)];
+        let scored = survey().evaluate(&answers, 10).unwrap();
+        // 1, 2 y 4 de opinión (10 c/u) + 3 correcta (20); la 5 es opcional
+        assert_eq!(scored.total_score, 50);
+        assert_eq!(scored.max_score, 60);
+        assert_eq!(scored.correct_answers, 1);
+        assert_eq!(scored.scorable_questions, 1);
+        assert_eq!(scored.answers[1].selected_options, vec!["A", "C"]);
+        assert_eq!(scored.answers[2].is_correct, Some(true));
+
+        let wrong = vec![choice(1, &["B"]), choice(3, &["A"]), rating(4, 3)];
+        let scored = survey().evaluate(&wrong, 10).unwrap();
+        assert_eq!((scored.correct_answers, scored.total_score), (0, 20));
+        assert_eq!(scored.answers[1].is_correct, Some(false));
+    }
+
+    #[test]
+    fn test_invalid_answers() {
+        let answers = vec![
+            choice(1, &["A", "B"]),
+            choice(2, &["A", "B", "C"]),
+            choice(3, &["X"]),
+            rating(4, 9),
+            choice(99, &["A"]),
+        ];
+        let codes = codes(survey().evaluate(&answers, 10));
+        assert!(codes.contains(&(99, "UNKNOWN_QUESTION")));
+        assert!(codes.contains(&(1, "INVALID_ANSWER")));
+        assert!(codes.contains(&(3, "INVALID_ANSWER")));
+        assert!(codes.contains(&(4, "INVALID_ANSWER")));
+    }
+
+    #[test]
+    fn test_documented_answer_format() {
+        let body = json!({"answers": [
+            {"question_id": 1, "answer": "A"},
+            {"question_id": 2, "answer": ["B"]},
+            {"question_id": 3, "answer": "B"},
+            {"question_id": 4, "answer": 1},
+            {"question_id": 5, "answer": "Más variedad"}
+        ]});
+        let scored = survey().evaluate(&parse_answers(&body).unwrap(), 10).unwrap();
+        assert_eq!(scored.answered_questions, 5);
+        assert_eq!(scored.correct_answers, 1);
+        assert_eq!(scored.answers[4].text_response.as_deref(), Some("Más variedad"));
+    }
+
+    #[test]
+    fn test_public_view_hides_correct_answers() {
+        let view = survey().public_view();
+        assert!(view.questions.iter().all(|q| q.weight.is_none()));
+        assert!(view.questions.iter().flat_map(|q| &q.options).all(|o| o.is_correct.is_none()));
+        assert!(view.questions.iter().flat_map(|q| &q.options).all(|o| o.tags.is_empty()));
+        let json = serde_json::to_string(&view).unwrap();
+        assert!(!json.contains("is_correct"));
+    }
+
+    #[test]
+    fn test_parse_answers_accepts_wrapped_and_bare() {
+        let wrapped = json!({"responses": [{"question_id": 1, "selected_options": ["A"]}], "submitted_at": "2026-10-18T10:00:00Z"});
+        let bare = json!([{"question_id": 4, "numeric_response": 3}]);
+        assert_eq!(parse_answers(&wrapped).unwrap()[0].selected_options, vec!["A"]);
+        assert_eq!(parse_answers(&bare).unwrap()[0].numeric_response, Some(3));
+        assert!(parse_answers(&json!({"foo": 1})).is_err());
+    }
+
+    #[test]
+    fn test_selected_option_tags() {
+        let stored = json!({"responses": [
+            {"question_id": 1, "selected_options": ["A"]},
+            {"question_id": 2, "selected_options": ["A", "B"]}
+        ]});
+        assert_eq!(
+            survey().selected_option_tags(&stored),
+            vec![("¿Qué app usas?: PedidosYa".to_string(), vec!["delivery_pedidosya".to_string()])]
+        );
+        assert!(survey().selected_option_tags(&json!({"responses": []})).is_empty());
+    }
+}
@@ -0,0 +1,10 @@
+pub mod analytics;
+pub mod definition;
+pub mod quotas;
+pub mod service;
+
+// Re-exports para facilitar imports
+pub use analytics::{AnalyticsError, Dimension, SurveyAnalyticsService, SurveyResults};
+pub use definition::{ResponseIssue, SurveyAnswer, SurveyDefinition};
+pub use quotas::{QuotaDefinition, QuotaError, QuotaFill, QuotaService};
+pub use service::{SurveyError, SurveyService, SurveySubmission};
@@ -0,0 +1,918 @@
+//! Cuotas de muestra por encuesta
+//!
+//! Los patrocinadores compran encuestas con cuotas ("200 respuestas, 50%
+//! mujeres, al menos 30 por provincia"). La definición vive en
+//! `survey.survey_quotas` y cada usuario que empieza la encuesta ocupa un
+//! cupo en `survey.quota_reservations` con las celdas de su perfil:
+//!
+//! - `reserved`: la abrió y tiene hasta `expires_at` para responder.
+//! - `completed`: envió sus respuestas; el cupo queda ocupado para siempre.
+//! - `released`: la reserva expiró (job programado) y el cupo se liberó.
+//!
+//! Una reserva vencida deja de contar aunque el job todavía no la haya
+//! marcado, así que el conteo siempre es `completed + reserved vigentes`.
+//! La reserva se hace con la fila de la cuota bloqueada (`FOR UPDATE`), por
+//! lo que dos usuarios no pueden tomar el último cupo de una celda a la vez.
+//!
+//! Balanceo: si lo que queda del total apenas alcanza para cubrir los
+//! mínimos pendientes de otras celdas, solo entran usuarios de esas celdas.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::info;
+
+use super::analytics::{self, UNKNOWN_SEGMENT};
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+/// Minutos que se guarda el cupo si la encuesta no tiene `time_limit_minutes`
+pub const DEFAULT_RESERVATION_MINUTES: i32 = 60;
+
+/// Celda de los usuarios cuyo valor no está listado en un grupo abierto
+pub const OTHER_CELL: &str = "otro";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaAttribute {
+    Gender,
+    AgeBand,
+    Country,
+    Province,
+}
+
+impl QuotaAttribute {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QuotaAttribute::Gender => "gender",
+            QuotaAttribute::AgeBand => "age_band",
+            QuotaAttribute::Country => "country",
+            QuotaAttribute::Province => "province",
+        }
+    }
+
+    /// Normaliza un valor de celda o de perfil para este atributo
+    pub fn normalize(self, raw: &str) -> Option<String> {
+        match self {
+            QuotaAttribute::Gender => normalize_gender(raw),
+            _ => normalize_value(raw),
+        }
+    }
+}
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaCell {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i64>,
+    /// Porcentaje exacto del total (fija mínimo y máximo)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaGroup {
+    pub attribute: QuotaAttribute,
+    pub cells: Vec<QuotaCell>,
+    /// Cerrado: quien no cae en una celda listada no puede responder
+    #[serde(default)]
+    pub closed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaDefinition {
+    pub total: i64,
+    #[serde(default)]
+    pub groups: Vec<QuotaGroup>,
+}
+
+/// Atributos del usuario ya normalizados
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RespondentProfile {
+    pub gender: Option<String>,
+    pub age_band: Option<String>,
+    pub country: Option<String>,
+    pub province: Option<String>,
+}
+
+/// Cupos ocupados (completadas + reservas vigentes)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuotaCounts {
+    pub total: i64,
+    pub cells: HashMap<(QuotaAttribute, String), i64>,
+}
+
+/// Por qué un usuario no puede tomar la encuesta
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum QuotaBlock {
+    TotalFull,
+    CellFull { attribute: QuotaAttribute, value: String },
+    NotInQuota { attribute: QuotaAttribute },
+    ReservedForOtherCells { attribute: QuotaAttribute },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaReservation {
+    pub survey_id: i32,
+    pub status: String,
+    pub cells: BTreeMap<String, String>,
+    pub reserved_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CellFill {
+    pub value: String,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub completed: i64,
+    pub reserved: i64,
+    pub fill_percentage: Option<f64>,
+    /// open, full o below_min
+    pub status: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupFill {
+    pub attribute: QuotaAttribute,
+    pub closed: bool,
+    pub cells: Vec<CellFill>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaFill {
+    pub survey_id: i32,
+    pub target: i64,
+    pub completed: i64,
+    pub reserved: i64,
+    pub remaining: i64,
+    pub fill_percentage: f64,
+    pub groups: Vec<GroupFill>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum QuotaError {
+    #[error("{}", Text::new("api.not_found.survey"))]
+    NotFound,
+
+    #[error("{}", Text::new("api.quota.none_defined"))]
+    NoQuotas,
+
+    #[error("{}", Text::new("api.quota.invalid").with("reason", .0))]
+    Invalid(Text),
+
+    #[error("{}", Text::new("api.quota.full"))]
+    Full(QuotaBlock),
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for QuotaError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct ProfileRow {
+    genre: Option<String>,
+    date_of_birth: Option<String>,
+    country: Option<String>,
+    province: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct ReservationRow {
+    survey_id: i32,
+    status: String,
+    cells: Json<BTreeMap<String, String>>,
+    reserved_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+impl From<ReservationRow> for QuotaReservation {
+    fn from(row: ReservationRow) -> Self {
+        Self {
+            survey_id: row.survey_id,
+            status: row.status,
+            cells: row.cells.0,
+            reserved_at: row.reserved_at,
+            expires_at: row.expires_at,
+        }
+    }
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+/// Minúsculas y sin espacios extra; vacío cuenta como desconocido
+pub fn normalize_value(raw: &str) -> Option<String> {
+    let value = raw.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    (!value.is_empty()).then_some(value)
+}
+
+/// `genre` es texto libre en dim_users; se agrupa en female/male
+pub fn normalize_gender(raw: &str) -> Option<String> {
+    let value = normalize_value(raw)?;
+    Some(match value.as_str() {
+        "f" | "femenino" | "female" | "mujer" => "female".to_string(),
+        "m" | "masculino" | "male" | "hombre" => "male".to_string(),
+        _ => value,
+    })
+}
+
+impl RespondentProfile {
+    pub fn new(
+        genre: Option<&str>,
+        date_of_birth: Option<&str>,
+        country: Option<&str>,
+        province: Option<&str>,
+        today: NaiveDate,
+    ) -> Self {
+        let age_band = analytics::age_band(date_of_birth, today);
+        Self {
+            gender: genre.and_then(normalize_gender),
+            age_band: (age_band != UNKNOWN_SEGMENT).then(|| age_band.to_string()),
+            country: country.and_then(normalize_value),
+            province: province.and_then(normalize_value),
+        }
+    }
+
+    pub fn value(&self, attribute: QuotaAttribute) -> Option<&str> {
+        match attribute {
+            QuotaAttribute::Gender => self.gender.as_deref(),
+            QuotaAttribute::AgeBand => self.age_band.as_deref(),
+            QuotaAttribute::Country => self.country.as_deref(),
+            QuotaAttribute::Province => self.province.as_deref(),
+        }
+    }
+}
+
+impl QuotaCell {
+    fn percent_of(&self, total: i64) -> Option<i64> {
+        self.percent.map(|percent| (total as f64 * percent / 100.0).round() as i64)
+    }
+
+    /// Mínimo efectivo (el mayor entre `min` y el porcentaje)
+    pub fn min_for(&self, total: i64) -> Option<i64> {
+        match (self.min, self.percent_of(total)) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Máximo efectivo (el menor entre `max` y el porcentaje)
+    pub fn max_for(&self, total: i64) -> Option<i64> {
+        match (self.max, self.percent_of(total)) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+impl QuotaGroup {
+    /// Celda en la que cae un valor del perfil; `None` si el grupo es
+    /// cerrado y el valor no está listado (o es desconocido)
+    pub fn cell_for(&self, value: Option<&str>) -> Option<String> {
+        let listed = value.and_then(|value| {
+            self.cells
+                .iter()
+                .find(|cell| self.attribute.normalize(&cell.value).as_deref() == Some(value))
+                .and_then(|cell| self.attribute.normalize(&cell.value))
+        });
+        match listed {
+            Some(cell) => Some(cell),
+            None if self.closed => None,
+            None => Some(OTHER_CELL.to_string()),
+        }
+    }
+}
+
+impl QuotaDefinition {
+    pub fn validate(&self) -> Result<(), Text> {
+        if self.total <= 0 {
+            return Err(Text::new("api.validation.positive").with("field", "total"));
+        }
+        let mut attributes = HashSet::new();
+        for group in &self.groups {
+            let attribute = group.attribute.as_str();
+            if !attributes.insert(group.attribute) {
+                return Err(Text::new("api.quota.duplicate_attribute").with("attribute", attribute));
+            }
+            if group.cells.is_empty() {
+                return Err(Text::new("api.quota.empty_group").with("attribute", attribute));
+            }
+            let mut values = HashSet::new();
+            let (mut min_sum, mut percent_sum) = (0, 0.0);
+            for cell in &group.cells {
+                let Some(value) = group.attribute.normalize(&cell.value) else {
+                    return Err(Text::new("api.quota.cell_without_value").with("attribute", attribute));
+                };
+                if value == OTHER_CELL {
+                    return Err(Text::new("api.quota.reserved_value").with("value", OTHER_CELL));
+                }
+                if !values.insert(value) {
+                    return Err(Text::new("api.quota.duplicate_cell")
+                        .with("attribute", attribute)
+                        .with("value", &cell.value));
+                }
+                if let Some(percent) = cell.percent {
+                    if !(0.0..=100.0).contains(&percent) {
+                        return Err(Text::new("api.quota.invalid_percent")
+                            .with("attribute", attribute)
+                            .with("value", &cell.value));
+                    }
+                    percent_sum += percent;
+                }
+                if cell.min.is_some_and(|min| min < 0) || cell.max.is_some_and(|max| max < 0) {
+                    return Err(Text::new("api.quota.negative_limits")
+                        .with("attribute", attribute)
+                        .with("value", &cell.value));
+                }
+                let (min, max) = (cell.min_for(self.total), cell.max_for(self.total));
+                if let (Some(min), Some(max)) = (min, max) {
+                    if min > max {
+                        return Err(Text::new("api.quota.min_above_max")
+                            .with("attribute", attribute)
+                            .with("value", &cell.value));
+                    }
+                }
+                if min.is_some_and(|min| min > self.total) {
+                    return Err(Text::new("api.quota.min_above_total")
+                        .with("attribute", attribute)
+                        .with("value", &cell.value));
+                }
+                min_sum += min.unwrap_or(0);
+            }
+            if min_sum > self.total {
+                return Err(Text::new("api.quota.mins_above_total").with("attribute", attribute));
+            }
+            if percent_sum > 100.0 + f64::EPSILON {
+                return Err(Text::new("api.quota.percents_above_100").with("attribute", attribute));
+            }
+        }
+        Ok(())
+    }
+
+    /// Decide si el perfil puede tomar un cupo con los conteos actuales
+    /// (sin contar al propio usuario). Devuelve las celdas que ocuparía.
+    pub fn check(&self, counts: &QuotaCounts, profile: &RespondentProfile) -> Result<BTreeMap<String, String>, QuotaBlock> {
+        if counts.total >= self.total {
+            return Err(QuotaBlock::TotalFull);
+        }
+        let remaining = self.total - counts.total;
+        let filled = |attribute: QuotaAttribute, value: &str| {
+            counts.cells.get(&(attribute, value.to_string())).copied().unwrap_or(0)
+        };
+
+        let mut cells = BTreeMap::new();
+        for group in &self.groups {
+            let attribute = group.attribute;
+            let Some(own) = group.cell_for(profile.value(attribute)) else {
+                return Err(QuotaBlock::NotInQuota { attribute });
+            };
+
+            // Cupo máximo de la celda del usuario
+            let own_cell = group.cells.iter().find(|cell| attribute.normalize(&cell.value).as_deref() == Some(own.as_str()));
+            if let Some(max) = own_cell.and_then(|cell| cell.max_for(self.total)) {
+                if filled(attribute, &own) >= max {
+                    return Err(QuotaBlock::CellFull { attribute, value: own });
+                }
+            }
+
+            // Asientos que hay que guardar para los mínimos de otras celdas
+            let pending_others: i64 = group
+                .cells
+                .iter()
+                .filter_map(|cell| Some((attribute.normalize(&cell.value)?, cell.min_for(self.total)?)))
+                .filter(|(value, _)| *value != own)
+                .map(|(value, min)| (min - filled(attribute, &value)).max(0))
+                .sum();
+            if remaining - 1 < pending_others {
+                return Err(QuotaBlock::ReservedForOtherCells { attribute });
+            }
+
+            cells.insert(attribute.as_str().to_string(), own);
+        }
+        Ok(cells)
+    }
+
+    /// Avance por celda para el patrocinador
+    pub fn fill(&self, survey_id: i32, completed: &QuotaCounts, reserved: &QuotaCounts) -> QuotaFill {
+        let percentage = |count: i64, of: i64| (of > 0).then(|| (10_000.0 * count as f64 / of as f64).round() / 100.0);
+        let get = |counts: &QuotaCounts, attribute: QuotaAttribute, value: &str| {
+            counts.cells.get(&(attribute, value.to_string())).copied().unwrap_or(0)
+        };
+
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| {
+                let attribute = group.attribute;
+                let mut cells: Vec<CellFill> = group
+                    .cells
+                    .iter()
+                    .map(|cell| {
+                        let value = attribute.normalize(&cell.value).unwrap_or_default();
+                        let (min, max) = (cell.min_for(self.total), cell.max_for(self.total));
+                        let done = get(completed, attribute, &value);
+                        let held = get(reserved, attribute, &value);
+                        let status = if max.is_some_and(|max| done + held >= max) {
+                            "full"
+                        } else if min.is_some_and(|min| done < min) {
+                            "below_min"
+                        } else {
+                            "open"
+                        };
+                        CellFill {
+                            fill_percentage: percentage(done, max.or(min).unwrap_or(self.total)),
+                            value,
+                            min,
+                            max,
+                            completed: done,
+                            reserved: held,
+                            status,
+                        }
+                    })
+                    .collect();
+                if !group.closed {
+                    let done = get(completed, attribute, OTHER_CELL);
+                    let held = get(reserved, attribute, OTHER_CELL);
+                    cells.push(CellFill {
+                        value: OTHER_CELL.to_string(),
+                        min: None,
+                        max: None,
+                        completed: done,
+                        reserved: held,
+                        fill_percentage: None,
+                        status: "open",
+                    });
+                }
+                GroupFill { attribute, closed: group.closed, cells }
+            })
+            .collect();
+
+        QuotaFill {
+            survey_id,
+            target: self.total,
+            completed: completed.total,
+            reserved: reserved.total,
+            remaining: (self.total - completed.total - reserved.total).max(0),
+            fill_percentage: percentage(completed.total, self.total).unwrap_or(0.0),
+            groups,
+            generated_at: Utc::now(),
+        }
+    }
+}
+
+/// Agrega filas `(cells, n)` en conteos por atributo/celda
+fn tally(rows: Vec<(Json<BTreeMap<String, String>>, i64)>) -> QuotaCounts {
+    let mut counts = QuotaCounts::default();
+    for (cells, n) in rows {
+        counts.total += n;
+        for (attribute, value) in cells.0 {
+            let attribute = match attribute.as_str() {
+                "gender" => QuotaAttribute::Gender,
+                "age_band" => QuotaAttribute::AgeBand,
+                "country" => QuotaAttribute::Country,
+                "province" => QuotaAttribute::Province,
+                _ => continue,
+            };
+            *counts.cells.entry((attribute, value)).or_default() += n;
+        }
+    }
+    counts
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct QuotaService {
+    db: PgPool,
+}
+
+impl QuotaService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn definition(&self, survey_id: i32) -> Result<Option<QuotaDefinition>, QuotaError> {
+        let definition: Option<Json<QuotaDefinition>> =
+            sqlx::query_scalar("SELECT definition FROM survey.survey_quotas WHERE survey_id = $1")
+                .bind(survey_id)
+                .fetch_optional(&self.db)
+                .await?;
+        Ok(definition.map(|definition| definition.0))
+    }
+
+    /// Crea o reemplaza las cuotas de una encuesta (admin). Las reservas
+    /// existentes se conservan y cuentan contra la nueva definición.
+    pub async fn set(&self, survey_id: i32, definition: &QuotaDefinition, admin_id: i64) -> Result<(), QuotaError> {
+        definition.validate().map_err(QuotaError::Invalid)?;
+
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM survey.dim_surveys WHERE survey_id = $1)")
+            .bind(survey_id)
+            .fetch_one(&self.db)
+            .await?;
+        if !exists {
+            return Err(QuotaError::NotFound);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO survey.survey_quotas (survey_id, definition, updated_by, updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (survey_id) DO UPDATE
+            SET definition = EXCLUDED.definition, updated_by = EXCLUDED.updated_by, updated_at = NOW()
+            "#,
+        )
+        .bind(survey_id)
+        .bind(Json(definition))
+        .bind(admin_id)
+        .execute(&self.db)
+        .await?;
+
+        info!("📊 Survey {} quotas set by admin {} (target {})", survey_id, admin_id, definition.total);
+        Ok(())
+    }
+
+    /// Quita las cuotas; la encuesta vuelve a estar abierta para todos
+    pub async fn remove(&self, survey_id: i32) -> Result<bool, QuotaError> {
+        let result = sqlx::query("DELETE FROM survey.survey_quotas WHERE survey_id = $1")
+            .bind(survey_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Encuestas (de las dadas) cuya cuota ya no admite al usuario. Se
+    /// excluyen las que el usuario ya reservó o completó.
+    pub async fn blocked_surveys(&self, user_id: i64, survey_ids: &[i32]) -> Result<HashSet<i32>, QuotaError> {
+        let quotas: Vec<(i32, Json<QuotaDefinition>)> = sqlx::query_as(
+            r#"
+            SELECT q.survey_id, q.definition
+            FROM survey.survey_quotas q
+            WHERE q.survey_id = ANY($1)
+              AND NOT EXISTS (
+                  SELECT 1 FROM survey.quota_reservations r
+                  WHERE r.survey_id = q.survey_id AND r.user_id = $2
+                    AND (r.status = 'completed' OR (r.status = 'reserved' AND r.expires_at > NOW()))
+              )
+            "#,
+        )
+        .bind(survey_ids)
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+        if quotas.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let mut conn = self.db.acquire().await?;
+        let profile = profile(&mut conn, user_id).await?;
+        let mut blocked = HashSet::new();
+        for (survey_id, definition) in quotas {
+            let counts = active_counts(&mut conn, survey_id, user_id).await?;
+            if definition.0.check(&counts, &profile).is_err() {
+                blocked.insert(survey_id);
+            }
+        }
+        Ok(blocked)
+    }
+
+    /// Reserva un cupo al empezar la encuesta. `Ok(None)` si la encuesta no
+    /// tiene cuotas; si el usuario ya tiene reserva vigente se devuelve esa.
+    pub async fn reserve(&self, user_id: i64, survey_id: i32) -> Result<Option<QuotaReservation>, QuotaError> {
+        let mut tx = self.db.begin().await?;
+        let reservation = claim(&mut tx, user_id, survey_id, false).await?;
+        tx.commit().await?;
+        Ok(reservation)
+    }
+
+    /// Marca como vencidas las reservas que pasaron `expires_at`
+    pub async fn release_expired(&self) -> Result<u64, QuotaError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE survey.quota_reservations
+            SET status = 'released', released_at = NOW()
+            WHERE status = 'reserved' AND expires_at <= NOW()
+            "#,
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Avance de la cuota en vivo
+    pub async fn fill(&self, survey_id: i32) -> Result<QuotaFill, QuotaError> {
+        let definition = self.definition(survey_id).await?.ok_or(QuotaError::NoQuotas)?;
+
+        // (status, celdas, reservas)
+        type Row = (String, Json<BTreeMap<String, String>>, i64);
+        let rows: Vec<Row> = sqlx::query_as(
+            r#"
+            SELECT status, cells, COUNT(*)
+            FROM survey.quota_reservations
+            WHERE survey_id = $1
+              AND (status = 'completed' OR (status = 'reserved' AND expires_at > NOW()))
+            GROUP BY status, cells
+            "#,
+        )
+        .bind(survey_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        let (completed, reserved): (Vec<_>, Vec<_>) = rows.into_iter().partition(|(status, _, _)| status == "completed");
+        let strip = |rows: Vec<Row>| {
+            rows.into_iter().map(|(_, cells, n)| (cells, n)).collect::<Vec<_>>()
+        };
+        Ok(definition.fill(survey_id, &tally(strip(completed)), &tally(strip(reserved))))
+    }
+}
+
+/// Toma (o confirma) el cupo del usuario dentro de una transacción.
+/// Con `complete` la reserva queda como `completed`; lo usa el envío de
+/// respuestas para que completar y ocupar el cupo sean atómicos.
+pub async fn claim(
+    conn: &mut PgConnection,
+    user_id: i64,
+    survey_id: i32,
+    complete: bool,
+) -> Result<Option<QuotaReservation>, QuotaError> {
+    // El bloqueo de la fila de la cuota serializa las reservas de la encuesta
+    let Some((definition, time_limit)): Option<(Json<QuotaDefinition>, Option<i32>)> = sqlx::query_as(
+        r#"
+        SELECT q.definition, s.time_limit_minutes
+        FROM survey.survey_quotas q
+        JOIN survey.dim_surveys s ON s.survey_id = q.survey_id
+        WHERE q.survey_id = $1
+        FOR UPDATE OF q
+        "#,
+    )
+    .bind(survey_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let existing: Option<ReservationRow> = sqlx::query_as(
+        r#"
+        SELECT survey_id, status, cells, reserved_at, expires_at
+        FROM survey.quota_reservations
+        WHERE survey_id = $1 AND user_id = $2
+          AND (status = 'completed' OR (status = 'reserved' AND expires_at > NOW()))
+        "#,
+    )
+    .bind(survey_id)
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let (cells, reserved_at) = match existing {
+        Some(row) if row.status == "completed" || !complete => return Ok(Some(row.into())),
+        Some(row) => (row.cells.0, row.reserved_at),
+        None => {
+            let profile = profile(&mut *conn, user_id).await?;
+            let counts = active_counts(&mut *conn, survey_id, user_id).await?;
+            let cells = definition.0.check(&counts, &profile).map_err(QuotaError::Full)?;
+            (cells, Utc::now())
+        }
+    };
+
+    let minutes = time_limit.filter(|m| *m > 0).unwrap_or(DEFAULT_RESERVATION_MINUTES);
+    let expires_at = reserved_at + Duration::minutes(minutes as i64);
+    let status = if complete { "completed" } else { "reserved" };
+
+    let row: ReservationRow = sqlx::query_as(
+        r#"
+        INSERT INTO survey.quota_reservations (survey_id, user_id, cells, status, reserved_at, expires_at, completed_at)
+        VALUES ($1, $2, $3, $4, $5, $6, CASE WHEN $4 = 'completed' THEN NOW() END)
+        ON CONFLICT (survey_id, user_id) DO UPDATE SET
+            cells = EXCLUDED.cells,
+            status = EXCLUDED.status,
+            reserved_at = EXCLUDED.reserved_at,
+            expires_at = EXCLUDED.expires_at,
+            completed_at = EXCLUDED.completed_at,
+            released_at = NULL
+        RETURNING survey_id, status, cells, reserved_at, expires_at
+        "#,
+    )
+    .bind(survey_id)
+    .bind(user_id)
+    .bind(Json(&cells))
+    .bind(status)
+    .bind(reserved_at)
+    .bind(expires_at)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    info!("📊 Survey {} quota {} for user {}: {:?}", survey_id, status, user_id, cells);
+    Ok(Some(row.into()))
+}
+
+async fn profile(conn: &mut PgConnection, user_id: i64) -> Result<RespondentProfile, QuotaError> {
+    let row: Option<ProfileRow> = sqlx::query_as(
+        r#"
+        SELECT genre, date_of_birth, country_residence AS country, province
+        FROM public.dim_users
+        WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let today = Utc::now().date_naive();
+    Ok(row
+        .map(|row| {
+            RespondentProfile::new(
+                row.genre.as_deref(),
+                row.date_of_birth.as_deref(),
+                row.country.as_deref(),
+                row.province.as_deref(),
+                today,
+            )
+        })
+        .unwrap_or_default())
+}
+
+/// Cupos ocupados por los demás usuarios
+async fn active_counts(conn: &mut PgConnection, survey_id: i32, user_id: i64) -> Result<QuotaCounts, QuotaError> {
+    let rows: Vec<(Json<BTreeMap<String, String>>, i64)> = sqlx::query_as(
+        r#"
+        SELECT cells, COUNT(*)
+        FROM survey.quota_reservations
+        WHERE survey_id = $1 AND user_id <> $2
+          AND (status = 'completed' OR (status = 'reserved' AND expires_at > NOW()))
+        GROUP BY cells
+        "#,
+    )
+    .bind(survey_id)
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(tally(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(value: &str, min: Option<i64>, max: Option<i64>) -> QuotaCell {
+        QuotaCell { value: value.to_string(), min, max, percent: None }
+    }
+
+    fn sponsor_quota() -> QuotaDefinition {
+        // 10 respuestas, 50% mujeres, al menos 3 de Colón
+        QuotaDefinition {
+            total: 10,
+            groups: vec![
+                QuotaGroup {
+                    attribute: QuotaAttribute::Gender,
+                    cells: vec![
+                        QuotaCell { value: "female".into(), min: None, max: None, percent: Some(50.0) },
+                        QuotaCell { value: "male".into(), min: None, max: None, percent: Some(50.0) },
+                    ],
+                    closed: true,
+                },
+                QuotaGroup {
+                    attribute: QuotaAttribute::Province,
+                    cells: vec![cell("Colón", Some(3), None), cell("Panamá", None, None)],
+                    closed: false,
+                },
+            ],
+        }
+    }
+
+    fn profile(gender: &str, province: &str) -> RespondentProfile {
+        RespondentProfile {
+            gender: normalize_gender(gender),
+            province: normalize_value(province),
+            ..Default::default()
+        }
+    }
+
+    fn counts(total: i64, cells: &[(QuotaAttribute, &str, i64)]) -> QuotaCounts {
+        QuotaCounts {
+            total,
+            cells: cells.iter().map(|(a, v, n)| ((*a, v.to_string()), *n)).collect(),
+        }
+    }
+
+    #[test]
+    fn normalizes_free_text_gender() {
+        assert_eq!(normalize_gender(" Femenino ").as_deref(), Some("female"));
+        assert_eq!(normalize_gender("M").as_deref(), Some("male"));
+        assert_eq!(normalize_gender("No binario").as_deref(), Some("no binario"));
+        assert_eq!(normalize_gender("  "), None);
+        assert_eq!(QuotaAttribute::Gender.normalize("Mujer").as_deref(), Some("female"));
+    }
+
+    #[test]
+    fn percent_sets_both_limits() {
+        let cell = QuotaCell { value: "female".into(), min: Some(2), max: Some(8), percent: Some(50.0) };
+        assert_eq!(cell.min_for(10), Some(5));
+        assert_eq!(cell.max_for(10), Some(5));
+        assert_eq!(cell.max_for(200), Some(8));
+    }
+
+    #[test]
+    fn validates_definitions() {
+        assert!(sponsor_quota().validate().is_ok());
+
+        let mut zero = sponsor_quota();
+        zero.total = 0;
+        assert!(zero.validate().is_err());
+
+        let mut repeated = sponsor_quota();
+        repeated.groups[1].cells.push(cell(" colón ", None, None));
+        assert!(repeated.validate().unwrap_err().to_string().contains("repetida"));
+
+        let mut mins = sponsor_quota();
+        mins.groups[1].cells[1].min = Some(8);
+        assert!(mins.validate().unwrap_err().to_string().contains("suman"));
+
+        let mut inverted = sponsor_quota();
+        inverted.groups[1].cells[0].max = Some(2);
+        assert!(inverted.validate().is_err());
+    }
+
+    #[test]
+    fn open_group_puts_unlisted_values_in_other() {
+        let quota = sponsor_quota();
+        let cells = quota.check(&QuotaCounts::default(), &profile("mujer", "Chiriquí")).unwrap();
+        assert_eq!(cells.get("gender").map(String::as_str), Some("female"));
+        assert_eq!(cells.get("province").map(String::as_str), Some(OTHER_CELL));
+    }
+
+    #[test]
+    fn closed_group_rejects_unknown_values() {
+        let quota = sponsor_quota();
+        let blocked = quota.check(&QuotaCounts::default(), &RespondentProfile::default());
+        assert_eq!(blocked, Err(QuotaBlock::NotInQuota { attribute: QuotaAttribute::Gender }));
+    }
+
+    #[test]
+    fn full_cell_hides_survey_only_for_that_cell() {
+        let quota = sponsor_quota();
+        let counts = counts(5, &[(QuotaAttribute::Gender, "female", 5), (QuotaAttribute::Province, "colón", 3)]);
+        assert_eq!(
+            quota.check(&counts, &profile("f", "Panamá")),
+            Err(QuotaBlock::CellFull { attribute: QuotaAttribute::Gender, value: "female".into() })
+        );
+        assert!(quota.check(&counts, &profile("m", "Panamá")).is_ok());
+    }
+
+    #[test]
+    fn total_target_closes_survey() {
+        let quota = sponsor_quota();
+        assert_eq!(quota.check(&counts(10, &[]), &profile("f", "Colón")), Err(QuotaBlock::TotalFull));
+    }
+
+    #[test]
+    fn last_seats_are_kept_for_unmet_minimums() {
+        let quota = sponsor_quota();
+        // 8 ocupados, ninguno de Colón: quedan 2 y faltan 3 de Colón
+        let counts = counts(8, &[(QuotaAttribute::Gender, "female", 4), (QuotaAttribute::Gender, "male", 4)]);
+        assert_eq!(
+            quota.check(&counts, &profile("f", "Panamá")),
+            Err(QuotaBlock::ReservedForOtherCells { attribute: QuotaAttribute::Province })
+        );
+        assert!(quota.check(&counts, &profile("f", "Colón")).is_ok());
+    }
+
+    #[test]
+    fn fill_reports_status_per_cell() {
+        let quota = sponsor_quota();
+        let completed = counts(6, &[(QuotaAttribute::Gender, "female", 5), (QuotaAttribute::Province, "colón", 1)]);
+        let reserved = counts(1, &[(QuotaAttribute::Gender, "male", 1)]);
+        let fill = quota.fill(7, &completed, &reserved);
+
+        assert_eq!(fill.remaining, 3);
+        assert_eq!(fill.fill_percentage, 60.0);
+        let gender = &fill.groups[0];
+        assert_eq!(gender.cells.len(), 2);
+        assert_eq!(gender.cells[0].status, "full");
+        assert_eq!(gender.cells[1].reserved, 1);
+        let province = &fill.groups[1];
+        assert_eq!(province.cells[0].status, "below_min");
+        assert_eq!(province.cells.last().map(|c| c.value.as_str()), Some(OTHER_CELL));
+    }
+}
@@ -0,0 +1,270 @@
+//! Envío de encuestas con validación y puntaje en el servidor
+//!
+//! Reemplaza el placeholder de `survey.api_submit_survey_responses` (que
+//! contaba cada respuesta como correcta): la definición se parsea con
+//! `SurveyDefinition`, las respuestas se validan contra ella (obligatorias,
+//! ramas `show_if`, tipos) y se guardan normalizadas con `is_correct` por
+//! pregunta.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::types::Json;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use super::definition::{self, ResponseIssue, ScoredResponses, SurveyDefinition};
+use super::quotas::{self, QuotaError};
+use crate::services::event_bus_service::{DomainEvent, EventBus};
+use crate::shared::i18n::Text;
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SurveySubmission {
+    pub status_id: i32,
+    pub survey_id: i32,
+    pub status: String,
+    pub total_score: i32,
+    pub max_score: i32,
+    pub correct_answers: i32,
+    pub scorable_questions: i32,
+    pub answered_questions: i32,
+    /// Preguntas visibles según las ramas tomadas
+    pub total_questions: i32,
+    pub accuracy_percentage: Option<f64>,
+    pub attempts_made: i32,
+    pub max_attempts: i32,
+    pub attempts_remaining: i32,
+    pub time_taken_minutes: Option<i32>,
+    pub completed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SurveyError {
+    #[error("{}", Text::new("api.survey.not_found_or_inactive"))]
+    NotFound,
+
+    #[error("{}", Text::new("api.survey.no_assignment"))]
+    NoAssignment,
+
+    #[error("{}", Text::new("api.survey.max_attempts"))]
+    MaxAttemptsReached,
+
+    #[error("{0}")]
+    InvalidFormat(Text),
+
+    #[error("{}", Text::new("api.survey.invalid_responses"))]
+    InvalidResponses(Vec<ResponseIssue>),
+
+    #[error("{}", Text::new("api.quota.full"))]
+    QuotaFull,
+
+    #[error("{}", Text::new("api.survey.invalid_definition").with("survey_id", .survey_id).with("reason", .reason))]
+    InvalidDefinition { survey_id: i32, reason: Text },
+
+    #[error("Error de base de datos: {0}")]
+    Database(String),
+}
+
+impl SurveyError {
+    /// Códigos que ya devolvía la función SQL
+    pub fn code(&self) -> &'static str {
+        match self {
+            SurveyError::NotFound => "SURVEY_NOT_FOUND",
+            SurveyError::NoAssignment => "NO_ASSIGNMENT",
+            SurveyError::MaxAttemptsReached => "MAX_ATTEMPTS_REACHED",
+            SurveyError::InvalidFormat(_) => "INVALID_FORMAT",
+            SurveyError::InvalidResponses(_) => "INVALID_RESPONSES",
+            SurveyError::QuotaFull => "SURVEY_QUOTA_FULL",
+            SurveyError::InvalidDefinition { .. } => "INVALID_SURVEY_DEFINITION",
+            SurveyError::Database(_) => "DATABASE_ERROR",
+        }
+    }
+}
+
+impl From<sqlx::Error> for SurveyError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+impl From<QuotaError> for SurveyError {
+    fn from(err: QuotaError) -> Self {
+        match err {
+            QuotaError::Full(_) => Self::QuotaFull,
+            other => Self::Database(other.to_string()),
+        }
+    }
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct SurveyService {
+    db: PgPool,
+}
+
+impl SurveyService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Definición tipada de una encuesta activa
+    pub async fn definition(&self, survey_id: i32) -> Result<SurveyDefinition, SurveyError> {
+        let questions: serde_json::Value =
+            sqlx::query_scalar("SELECT questions FROM survey.dim_surveys WHERE survey_id = $1 AND is_active = TRUE")
+                .bind(survey_id)
+                .fetch_optional(&self.db)
+                .await?
+                .ok_or(SurveyError::NotFound)?;
+        SurveyDefinition::parse(&questions).map_err(|reason| SurveyError::InvalidDefinition { survey_id, reason })
+    }
+
+    /// Reemplaza la definición de una encuesta (admin). Se valida completa
+    /// antes de guardarla y `total_questions` se recalcula.
+    pub async fn update_definition(&self, survey_id: i32, definition: &SurveyDefinition) -> Result<(), SurveyError> {
+        definition
+            .validate()
+            .map_err(|reason| SurveyError::InvalidDefinition { survey_id, reason })?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE survey.dim_surveys
+            SET questions = $2, total_questions = $3, updated_at = CURRENT_TIMESTAMP
+            WHERE survey_id = $1
+            "#,
+        )
+        .bind(survey_id)
+        .bind(Json(definition))
+        .bind(definition.questions.len() as i32)
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(SurveyError::NotFound);
+        }
+        info!("📝 Survey {} definition updated ({} questions)", survey_id, definition.questions.len());
+        Ok(())
+    }
+
+    /// Valida, puntúa y guarda un intento completo
+    pub async fn submit(
+        &self,
+        user_id: i32,
+        survey_id: i32,
+        responses: &serde_json::Value,
+        time_minutes: Option<i32>,
+    ) -> Result<SurveySubmission, SurveyError> {
+        let answers = definition::parse_answers(responses).map_err(SurveyError::InvalidFormat)?;
+
+        let mut tx = self.db.begin().await?;
+
+        let (questions, points_per_question, max_attempts): (serde_json::Value, Option<i32>, Option<i32>) = sqlx::query_as(
+            r#"
+            SELECT questions, points_per_question, max_attempts
+            FROM survey.dim_surveys
+            WHERE survey_id = $1 AND is_active = TRUE
+            "#,
+        )
+        .bind(survey_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(SurveyError::NotFound)?;
+        let max_attempts = max_attempts.unwrap_or(1);
+
+        let survey = SurveyDefinition::parse(&questions).map_err(|reason| {
+            error!("Survey {} has an invalid definition: {}", survey_id, reason);
+            SurveyError::InvalidDefinition { survey_id, reason }
+        })?;
+
+        let (status_id, attempts_made): (i32, Option<i32>) = sqlx::query_as(
+            r#"
+            SELECT status_id, attempts_made
+            FROM survey.fact_user_survey_status
+            WHERE user_id = $1 AND survey_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(user_id)
+        .bind(survey_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(SurveyError::NoAssignment)?;
+        let attempts_made = attempts_made.unwrap_or(0);
+        if attempts_made >= max_attempts {
+            return Err(SurveyError::MaxAttemptsReached);
+        }
+
+        let scored: ScoredResponses = survey
+            .evaluate(&answers, points_per_question.unwrap_or(10))
+            .map_err(SurveyError::InvalidResponses)?;
+
+        // Si la encuesta tiene cuotas, el cupo se confirma en la misma transacción
+        quotas::claim(&mut *tx, user_id as i64, survey_id, true).await?;
+
+        let completed_at = Utc::now();
+        let stored = serde_json::json!({
+            "responses": scored.answers,
+            "submitted_at": completed_at,
+            "duration_minutes": time_minutes,
+        });
+
+        sqlx::query(
+            r#"
+            UPDATE survey.fact_user_survey_status SET
+                status = 'completed',
+                completed_at = $2,
+                responses = $3,
+                total_score = $4,
+                correct_answers = $5,
+                attempts_made = $6,
+                total_time_minutes = COALESCE(total_time_minutes, 0) + COALESCE($7, 0),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE status_id = $1
+            "#,
+        )
+        .bind(status_id)
+        .bind(completed_at)
+        .bind(&stored)
+        .bind(scored.total_score)
+        .bind(scored.correct_answers)
+        .bind(attempts_made + 1)
+        .bind(time_minutes)
+        .execute(&mut *tx)
+        .await?;
+
+        // Misiones y logros reaccionan a survey.completed
+        let completed = DomainEvent::SurveyCompleted { user_id: user_id as i64, survey_id };
+        EventBus::publish(&mut *tx, &completed)
+            .await
+            .map_err(|e| SurveyError::Database(e.to_string()))?;
+        tx.commit().await?;
+
+        info!(
+            "📋 Survey {} submitted by user {}: score {}/{}, {} correct",
+            survey_id, user_id, scored.total_score, scored.max_score, scored.correct_answers
+        );
+
+        Ok(SurveySubmission {
+            status_id,
+            survey_id,
+            status: "completed".to_string(),
+            total_score: scored.total_score,
+            max_score: scored.max_score,
+            correct_answers: scored.correct_answers,
+            scorable_questions: scored.scorable_questions,
+            answered_questions: scored.answered_questions,
+            total_questions: scored.visible_questions,
+            accuracy_percentage: (scored.scorable_questions > 0).then(|| {
+                (10_000.0 * scored.correct_answers as f64 / scored.scorable_questions as f64).round() / 100.0
+            }),
+            attempts_made: attempts_made + 1,
+            max_attempts,
+            attempts_remaining: max_attempts - (attempts_made + 1),
+            time_taken_minutes: time_minutes,
+            completed_at,
+        })
+    }
+}
@@ -39,6 +39,7 @@ use state::AppState;
 use security::{security_headers_middleware, rate_limiting_middleware, get_cors_layer};
 use monitoring::endpoints::monitoring_router;
 use observability::metrics_middleware;
+use middleware::locale_middleware;
 
 use axum::middleware as axum_middleware;
 
@@ -66,6 +67,7 @@ pub fn create_app_router(app_state: Arc<AppState>) -> Router {
         ))
         // Middlewares sin estado
         .layer(axum_middleware::from_fn(metrics_middleware)) // 📊 Captura métricas automáticamente
+        .layer(axum_middleware::from_fn(locale_middleware)) // 🌐 Idioma de la petición (Accept-Language)
         .layer(DefaultBodyLimit::max(15 * 1024 * 1024))  // 📦 15MB body limit for image uploads
         .layer(
             CompressionLayer::new()
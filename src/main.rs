@@ -63,7 +63,7 @@ async fn main() -> Result<()> {
     info!("🔍 Monitoring system initialized");
 
     // Crea el estado de la aplicación con configuración optimizada
-    let app_state = AppState::new().await?;
+    let app_state = Arc::new(AppState::new().await?);
     info!("🚀 Application state initialized with optimized configuration");
 
     // Inicializar ONNX readers para QR detection ML
@@ -77,7 +77,14 @@ async fn main() -> Result<()> {
         init_webhook_service, 
         init_rate_limiter, 
         init_scheduled_jobs,
-        start_push_queue_worker
+        start_push_queue_worker,
+        start_webhook_dispatcher,
+        init_merchant_api_key_service,
+        init_merchant_session_service,
+        init_leaderboard_service,
+        get_leaderboard_service,
+        init_event_bus,
+        start_event_dispatcher
     };
     
     // Push Notification Service (FCM HTTP v1)
@@ -94,11 +101,52 @@ async fn main() -> Result<()> {
     // Webhook Service (HMAC-SHA256 signatures)
     init_webhook_service(app_state.db_pool.clone());
     info!("🔗 Webhook service initialized (merchant notifications ready)");
+
+    // Start webhook outbox dispatcher as background task
+    let webhook_db = app_state.db_pool.clone();
+    tokio::spawn(async move {
+        start_webhook_dispatcher(webhook_db).await;
+    });
+    info!("📤 Webhook outbox dispatcher started (polling every 5s)");
     
     // Rate Limiter Service (Redis-backed)
     init_rate_limiter(app_state.redis_pool.clone());
     info!("🚦 Rate limiter service initialized (abuse prevention active)");
     
+    // Merchant API keys (server-to-server auth for POS integrations)
+    init_merchant_api_key_service(app_state.db_pool.clone());
+    info!("🔑 Merchant API key service initialized");
+
+    // Staff sessions (role/branch/is_active re-checked on every request)
+    init_merchant_session_service(app_state.db_pool.clone());
+    
+    // Leaderboards (Redis sorted sets, Postgres as source of truth)
+    init_leaderboard_service(app_state.db_pool.clone(), app_state.redis_pool.clone());
+    info!("🏆 Leaderboard service initialized (daily/weekly/monthly/all-time)");
+    tokio::spawn(async {
+        let Some(leaderboards) = get_leaderboard_service() else {
+            return;
+        };
+        match leaderboards.rebuild_missing().await {
+            Ok(rebuilt) if !rebuilt.is_empty() => info!("🔁 Rebuilt missing leaderboards: {:?}", rebuilt),
+            Ok(_) => {}
+            Err(e) => tracing::error!("❌ Failed to rebuild missing leaderboards: {}", e),
+        }
+    });
+    
+    // Multi-channel notifier (in-app, push, WhatsApp, email) used by event subscribers and jobs
+    use lum_rust_ws::domains::notifications::init_notifier;
+    init_notifier(&app_state);
+    info!("📣 Notifier initialized (push → WhatsApp → email fallback)");
+
+    // Domain event bus (transactional outbox + subscribers)
+    init_event_bus(app_state.db_pool.clone());
+    let events_db = app_state.db_pool.clone();
+    tokio::spawn(async move {
+        start_event_dispatcher(events_db).await;
+    });
+    info!("📨 Domain event dispatcher started (polling every 1s)");
+    
     // Scheduled Jobs Service (balance validation, expiration checks)
     init_scheduled_jobs(app_state.db_pool.clone()).await?;
     info!("⏰ Scheduled jobs service started (nightly validation, expiration checks)");
@@ -119,7 +167,7 @@ async fn main() -> Result<()> {
     }
 
     // Crea el router de la aplicación
-    let app = create_app_router(Arc::new(app_state));
+    let app = create_app_router(app_state);
 
     // Inicia el servidor
     let port = std::env::var("PORT")
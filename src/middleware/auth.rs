@@ -13,6 +13,10 @@ use tracing::{error, info, warn};
 
 use crate::{
     api::models::ErrorResponse,
+    services::merchant_api_key_service::{
+        get_merchant_api_key_service, looks_like_api_key, ApiKeyAuthError,
+    },
+    services::merchant_session_service::{get_merchant_session_service, StaffSessionError},
 };
 
 // ============================================================================
@@ -52,6 +56,16 @@ pub struct MerchantClaims {
     pub iat: i64,
     #[serde(default)]
     pub merchant_id: Option<uuid::Uuid>,  // Optional for backward compatibility
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staff_id: Option<uuid::Uuid>,     // None = merchant-level login (api_key)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staff_role: Option<String>,       // owner, manager, cashier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch_id: Option<uuid::Uuid>,    // None = all branches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_id: Option<uuid::Uuid>,   // Set when authenticated with an API key (never in JWTs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_scopes: Option<Vec<String>>,
 }
 
 impl MerchantClaims {
@@ -59,6 +73,15 @@ impl MerchantClaims {
     pub fn get_merchant_id(&self) -> Option<uuid::Uuid> {
         self.merchant_id.or_else(|| uuid::Uuid::parse_str(&self.sub).ok())
     }
+
+    /// Identifier of who acted, for logs and audit: staff_id when present, merchant otherwise
+    pub fn actor_label(&self) -> String {
+        match (self.api_key_id, self.staff_id) {
+            (Some(key_id), _) => format!("apikey:{}", key_id),
+            (None, Some(staff_id)) => format!("staff:{}", staff_id),
+            (None, None) => format!("merchant:{}", self.sub),
+        }
+    }
 }
 
 impl JwtClaims {
@@ -298,6 +321,24 @@ pub async fn extract_merchant(
     mut request: Request,
     next: Next,
 ) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    // Server-to-server API key: X-Api-Key header or "Bearer lum_sk_..."
+    let bearer = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+    let api_key = headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .or_else(|| bearer.filter(|t| looks_like_api_key(t)));
+
+    if let Some(api_key) = api_key {
+        let claims = authenticate_merchant_api_key(&headers, api_key).await?;
+        request.extensions_mut().insert(claims);
+        return Ok(next.run(request).await);
+    }
+
     // Get Authorization header
     let auth_header = headers
         .get(AUTHORIZATION)
@@ -384,6 +425,11 @@ pub async fn extract_merchant(
         ));
     }
     
+    // Staff: rol, sucursal y estado se releen de la base en cada request
+    if claims.staff_id.is_some() {
+        verify_staff_session(&claims).await?;
+    }
+
     info!("🏪 Merchant authentication successful: {} ({})", 
           claims.merchant_name, claims.sub);
     
@@ -393,6 +439,92 @@ pub async fn extract_merchant(
     Ok(next.run(request).await)
 }
 
+/// Reject staff tokens whose account was deactivated or whose role/branch changed
+async fn verify_staff_session(claims: &MerchantClaims) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let service = get_merchant_session_service().ok_or_else(|| {
+        error!("Merchant session service not initialized");
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "Staff authentication unavailable".to_string(),
+                message: "Staff authentication is not available on this server.".to_string(),
+                details: None,
+            }),
+        )
+    })?;
+
+    service.verify(claims).await.map_err(|e| {
+        let (status, error) = match e {
+            StaffSessionError::Inactive => (StatusCode::UNAUTHORIZED, "Staff account inactive"),
+            StaffSessionError::PermissionsChanged => (StatusCode::UNAUTHORIZED, "Staff permissions changed"),
+            StaffSessionError::Internal(ref msg) => {
+                error!("Staff session verification error: {}", msg);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Staff session verification failed")
+            }
+        };
+        (
+            status,
+            Json(ErrorResponse {
+                error: error.to_string(),
+                message: e.to_string(),
+                details: None,
+            }),
+        )
+    })
+}
+
+/// Resolve an API key into MerchantClaims (scopes restrict permissions downstream)
+async fn authenticate_merchant_api_key(
+    headers: &HeaderMap,
+    api_key: &str,
+) -> Result<MerchantClaims, (StatusCode, Json<ErrorResponse>)> {
+    let service = get_merchant_api_key_service().ok_or_else(|| {
+        error!("Merchant API key service not initialized");
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "API key authentication unavailable".to_string(),
+                message: "API key authentication is not available on this server.".to_string(),
+                details: None,
+            }),
+        )
+    })?;
+
+    let client_ip = headers
+        .get("x-forwarded-for")
+        .or_else(|| headers.get("x-real-ip"))
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.split(',').next().unwrap_or(s).trim().to_string());
+
+    match service.authenticate(api_key, client_ip.as_deref()).await {
+        Ok(claims) => {
+            info!("🔑 Merchant API key authentication successful: {} ({})",
+                  claims.merchant_name, claims.actor_label());
+            Ok(claims)
+        }
+        Err(e) => {
+            let status = match e {
+                ApiKeyAuthError::Invalid | ApiKeyAuthError::Revoked => StatusCode::UNAUTHORIZED,
+                ApiKeyAuthError::MerchantInactive => StatusCode::FORBIDDEN,
+                ApiKeyAuthError::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+                ApiKeyAuthError::Internal(ref msg) => {
+                    error!("Merchant API key authentication error: {}", msg);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            };
+            warn!("Merchant API key rejected: {}", e);
+            Err((
+                status,
+                Json(ErrorResponse {
+                    error: "Invalid API key".to_string(),
+                    message: e.to_string(),
+                    details: None,
+                }),
+            ))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,13 @@
+use axum::{extract::Request, http::header::ACCEPT_LANGUAGE, middleware::Next, response::Response};
+use crate::shared::i18n::{self, Locale};
+
+// Idioma de la petición para los mensajes de la API (Accept-Language, español por defecto)
+pub async fn locale_middleware(req: Request, next: Next) -> Response {
+    let locale = req
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(Locale::from_accept_language)
+        .unwrap_or_default();
+    i18n::with_request_locale(locale, next.run(req)).await
+}
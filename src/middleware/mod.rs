@@ -3,6 +3,7 @@ pub mod rate_limit;
 pub mod idempotency;
 pub mod request_limits;
 pub mod mime_validation;
+pub mod locale;
 
 pub use auth::{
     extract_current_user, 
@@ -18,3 +19,4 @@ pub use rate_limit::rate_limit_middleware;
 pub use idempotency::idempotency_middleware;
 pub use request_limits::request_limits_middleware;
 pub use mime_validation::{validate_upload_middleware, MimeValidator, validate_file_data};
+pub use locale::locale_middleware;
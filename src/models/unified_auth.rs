@@ -30,6 +30,10 @@ pub struct UnifiedAuthRequest {
     
     /// Linking token for account linking flows
     pub linking_token: Option<String>,
+    
+    /// Referral code or link, applied only when a new account is created
+    #[serde(default)]
+    pub referral_code: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -334,6 +334,46 @@ lazy_static! {
     )
     .unwrap();
 
+    /// Webhooks que agotaron reintentos (dead-letter)
+    pub static ref WEBHOOKS_DEAD_LETTER_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "webhooks_dead_letter_total",
+        "Total webhook deliveries moved to dead-letter",
+        &["event_type"]
+    )
+    .unwrap();
+
+    /// Firmas X-Hub-Signature-256 de webhooks entrantes de WhatsApp
+    pub static ref WHATSAPP_WEBHOOK_SIGNATURES_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "whatsapp_webhook_signatures_total",
+        "Total incoming WhatsApp webhook signature checks",
+        &["result", "secret"]
+    )
+    .unwrap();
+
+    /// Eventos de dominio procesados por suscriptor
+    pub static ref DOMAIN_EVENTS_HANDLED_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "domain_events_handled_total",
+        "Total domain events handled by subscribers",
+        &["event_type", "subscriber", "status"]
+    )
+    .unwrap();
+
+    /// Eventos de dominio que agotaron reintentos (dead-letter)
+    pub static ref DOMAIN_EVENTS_DEAD_LETTER_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "domain_events_dead_letter_total",
+        "Total domain events moved to dead-letter",
+        &["event_type"]
+    )
+    .unwrap();
+
+    /// Eventos de negocio (suscriptor de analítica)
+    pub static ref BUSINESS_EVENTS_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "business_events_total",
+        "Total business events by type and channel",
+        &["event_type", "source"]
+    )
+    .unwrap();
+
     /// Push notifications enviadas
     pub static ref PUSH_NOTIFICATIONS_SENT_TOTAL: IntCounterVec = register_int_counter_vec!(
         "push_notifications_sent_total",
@@ -493,6 +533,43 @@ pub fn record_webhook_sent(event_type: &str, success: bool) {
         .inc();
 }
 
+/// Helper para registrar webhook en dead-letter
+pub fn record_webhook_dead_letter(event_type: &str) {
+    WEBHOOKS_DEAD_LETTER_TOTAL
+        .with_label_values(&[event_type])
+        .inc();
+}
+
+/// Helper para registrar la verificación de firma de un webhook de WhatsApp.
+/// `secret` es `current`/`previous` si la firma es válida y `none` si se rechazó.
+pub fn record_whatsapp_webhook_signature(result: &str, secret: &str) {
+    WHATSAPP_WEBHOOK_SIGNATURES_TOTAL
+        .with_label_values(&[result, secret])
+        .inc();
+}
+
+/// Helper para registrar el resultado de un suscriptor del bus de eventos
+pub fn record_domain_event_handled(event_type: &str, subscriber: &str, success: bool) {
+    let status = if success { "success" } else { "error" };
+    DOMAIN_EVENTS_HANDLED_TOTAL
+        .with_label_values(&[event_type, subscriber, status])
+        .inc();
+}
+
+/// Helper para registrar evento de dominio en dead-letter
+pub fn record_domain_event_dead_letter(event_type: &str) {
+    DOMAIN_EVENTS_DEAD_LETTER_TOTAL
+        .with_label_values(&[event_type])
+        .inc();
+}
+
+/// Helper para registrar un evento de negocio
+pub fn record_business_event(event_type: &str, source: &str) {
+    BUSINESS_EVENTS_TOTAL
+        .with_label_values(&[event_type, source])
+        .inc();
+}
+
 /// Helper para registrar push notification
 pub fn record_push_notification(notification_type: &str, success: bool) {
     let status = if success { "success" } else { "error" };
@@ -1,2 +1,3 @@
 pub mod survey_flow;
 pub mod product_search_flow;
+pub mod trivia_flow;
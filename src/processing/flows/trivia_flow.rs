@@ -0,0 +1,111 @@
+use crate::{
+    domains::gamification::trivia_service::{self, ServedQuestion, TriviaDay, TriviaError, TriviaService},
+    models::whatsapp::{Row, Section},
+    services::{user_service, whatsapp_service},
+    state::AppState,
+};
+use anyhow::Result;
+use std::sync::Arc;
+use tracing::{error, info};
+
+const NOT_REGISTERED: &str = "Debes estar registrado para jugar las trivias. Usa /start para registrarte.";
+
+/// `/trivia`: envía la pregunta en curso o la siguiente del día
+pub async fn start_trivia(app_state: &Arc<AppState>, whatsapp_id: &str) -> Result<()> {
+    let Some(user) = user_service::get_user(app_state, whatsapp_id).await? else {
+        return whatsapp_service::send_text_message(app_state, whatsapp_id, NOT_REGISTERED).await;
+    };
+    send_next_question(app_state, whatsapp_id, user.id as i32).await
+}
+
+/// Respuesta elegida en la lista interactiva (`trivia:<question_id>:<opción>`)
+pub async fn handle_trivia_answer(app_state: &Arc<AppState>, whatsapp_id: &str, user_id: i32, row_id: &str) -> Result<()> {
+    let Some((question_id, option)) = trivia_service::parse_whatsapp_row_id(row_id) else {
+        info!("Invalid trivia row id '{}' from {}", row_id, whatsapp_id);
+        return Ok(());
+    };
+
+    let service = TriviaService::new(app_state.db_pool.clone());
+    let reply = match service.answer(user_id, question_id, option, trivia_service::CHANNEL_WHATSAPP).await {
+        Ok(result) => {
+            let mut reply = if result.timed_out {
+                "⏰ *Se acabó el tiempo.* Esta no suma.".to_string()
+            } else if result.flagged {
+                "🤔 Respondiste demasiado rápido; esta respuesta no suma Lümis.".to_string()
+            } else if result.correct {
+                format!("✅ *¡Correcto!* +{} Lümis", result.lumis_awarded)
+            } else {
+                format!(
+                    "❌ *Incorrecto.* La respuesta era la {}.",
+                    trivia_service::option_letter(result.correct_option as usize)
+                )
+            };
+            if result.streak_bonus > 0 {
+                reply.push_str(&format!("\n🔥 Racha de {}: bono de {} Lümis incluido", result.streak, result.streak_bonus));
+            }
+            if let Some(explanation) = &result.explanation {
+                reply.push_str(&format!("\n\n💡 {}", explanation));
+            }
+            reply
+        }
+        Err(TriviaError::Database(e)) => {
+            error!("Trivia answer failed for {}: {}", whatsapp_id, e);
+            "No pudimos registrar tu respuesta. Intenta de nuevo más tarde.".to_string()
+        }
+        Err(e) => e.to_string(),
+    };
+    whatsapp_service::send_text_message(app_state, whatsapp_id, &reply).await?;
+
+    send_next_question(app_state, whatsapp_id, user_id).await
+}
+
+async fn send_next_question(app_state: &Arc<AppState>, whatsapp_id: &str, user_id: i32) -> Result<()> {
+    let service = TriviaService::new(app_state.db_pool.clone());
+    let next = match service.next_question(user_id, trivia_service::CHANNEL_WHATSAPP).await {
+        Ok(next) => next,
+        Err(TriviaError::NotEnoughQuestions) => {
+            let message = "🧠 Hoy no hay trivia disponible. ¡Vuelve mañana!";
+            return whatsapp_service::send_text_message(app_state, whatsapp_id, message).await;
+        }
+        Err(e) => {
+            error!("Trivia next question failed for {}: {}", whatsapp_id, e);
+            let message = "No pudimos cargar la trivia. Intenta de nuevo más tarde.";
+            return whatsapp_service::send_text_message(app_state, whatsapp_id, message).await;
+        }
+    };
+
+    match next {
+        Some(question) => send_question(app_state, whatsapp_id, &question).await,
+        None => {
+            let day = service.today(user_id).await.unwrap_or_default();
+            whatsapp_service::send_text_message(app_state, whatsapp_id, &day_summary(&day)).await
+        }
+    }
+}
+
+async fn send_question(app_state: &Arc<AppState>, whatsapp_id: &str, question: &ServedQuestion) -> Result<()> {
+    let body = format!(
+        "🧠 *Trivia Lüm* · Pregunta {}/{}\n\n{}\n\n⏱️ Tienes {} segundos.",
+        question.position, question.total_questions, question.question_text, question.time_limit_seconds
+    );
+    let rows = question
+        .options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| Row {
+            id: trivia_service::whatsapp_row_id(question.question_id, index),
+            title: format!("Opción {}", trivia_service::option_letter(index)),
+            description: Some(option.clone()),
+        })
+        .collect();
+    let sections = vec![Section { title: "Opciones".to_string(), rows }];
+
+    whatsapp_service::send_interactive_list_message(app_state, whatsapp_id, &body, "Responder", sections).await
+}
+
+fn day_summary(day: &TriviaDay) -> String {
+    format!(
+        "🏁 *¡Terminaste la trivia de hoy!*\n\nAciertos: {}/{}\nLümis ganados: {}\n\nMañana hay preguntas nuevas. 🚀",
+        day.correct, day.total_questions, day.lumis_earned
+    )
+}
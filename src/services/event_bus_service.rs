@@ -0,0 +1,670 @@
+// ============================================================================
+// EVENT BUS SERVICE - Eventos de dominio con outbox transaccional
+// ============================================================================
+//
+// Los cambios de negocio publican un `DomainEvent` con `EventBus::publish`
+// usando la misma transacción del cambio (`&mut *tx`): el evento existe si y
+// solo si el cambio se confirma. Un dispatcher en background
+// (`start_event_dispatcher`) reclama los eventos del outbox y los entrega a
+// cada suscriptor interesado (`EventSubscriber`).
+//
+// ENTREGA: al menos una vez por suscriptor. Cada éxito se registra en
+// public.domain_event_subscriber_runs, así un reintento solo vuelve a correr
+// los suscriptores que fallaron. Los suscriptores deben ser idempotentes.
+// Tras `max_attempts` el evento pasa a 'dead'.
+// ============================================================================
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+use crate::observability::metrics::{record_domain_event_dead_letter, record_domain_event_handled};
+
+/// Eventos de negocio. El tag `type` es también el `event_type` del outbox.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum DomainEvent {
+    /// Factura guardada en invoice_header (cualquier canal)
+    #[serde(rename = "invoice.saved")]
+    InvoiceSaved { user_id: i64, cufe: String, source: String },
+
+    /// Lümis acreditados al usuario (reglas, campañas, misiones...)
+    #[serde(rename = "lumis.credited")]
+    LumisCredited {
+        user_id: i64,
+        lumis: i32,
+        source: String,
+        reference: Option<String>,
+    },
+
+    #[serde(rename = "redemption.created")]
+    RedemptionCreated {
+        user_id: i64,
+        redemption_id: Uuid,
+        redemption_code: String,
+        offer_name: String,
+        lumis_spent: i32,
+        merchant_id: Option<Uuid>,
+    },
+
+    #[serde(rename = "redemption.confirmed")]
+    RedemptionConfirmed {
+        user_id: i64,
+        redemption_id: Uuid,
+        redemption_code: String,
+        offer_name: String,
+        merchant_id: Option<Uuid>,
+        confirmed_by: String,
+    },
+
+    #[serde(rename = "redemption.cancelled")]
+    RedemptionCancelled {
+        user_id: i64,
+        redemption_id: Uuid,
+        redemption_code: String,
+        merchant_id: Option<Uuid>,
+        reason: String,
+    },
+
+    #[serde(rename = "redemption.expired")]
+    RedemptionExpired {
+        redemption_id: Uuid,
+        redemption_code: String,
+        offer_name: String,
+        merchant_id: Option<Uuid>,
+    },
+
+    #[serde(rename = "survey.completed")]
+    SurveyCompleted { user_id: i64, survey_id: i32 },
+
+    /// Primera respuesta del usuario a una pregunta de LumiMatch
+    #[serde(rename = "lumimatch.answered")]
+    LumiMatchAnswered { user_id: i64, question_id: Uuid, option_id: Uuid },
+
+    #[serde(rename = "user.registered")]
+    UserRegistered { user_id: i64, source: String },
+
+    /// Acción registrada por el cliente en /gamification/track
+    #[serde(rename = "action.tracked")]
+    ActionTracked { user_id: i64, action: String, channel: String },
+
+    /// Ganador de una tómbola (una vez por usuario y tómbola)
+    #[serde(rename = "raffle.won")]
+    RaffleWon {
+        user_id: i64,
+        raffle_id: i32,
+        raffle_title: String,
+        prize_description: String,
+        ticket_number: i32,
+    },
+}
+
+impl DomainEvent {
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            DomainEvent::InvoiceSaved { .. } => "invoice.saved",
+            DomainEvent::LumisCredited { .. } => "lumis.credited",
+            DomainEvent::RedemptionCreated { .. } => "redemption.created",
+            DomainEvent::RedemptionConfirmed { .. } => "redemption.confirmed",
+            DomainEvent::RedemptionCancelled { .. } => "redemption.cancelled",
+            DomainEvent::RedemptionExpired { .. } => "redemption.expired",
+            DomainEvent::SurveyCompleted { .. } => "survey.completed",
+            DomainEvent::LumiMatchAnswered { .. } => "lumimatch.answered",
+            DomainEvent::UserRegistered { .. } => "user.registered",
+            DomainEvent::ActionTracked { .. } => "action.tracked",
+            DomainEvent::RaffleWon { .. } => "raffle.won",
+        }
+    }
+
+    /// Usuario afectado (los eventos de sistema, como la expiración, no tienen)
+    pub fn user_id(&self) -> Option<i64> {
+        match self {
+            DomainEvent::InvoiceSaved { user_id, .. }
+            | DomainEvent::LumisCredited { user_id, .. }
+            | DomainEvent::RedemptionCreated { user_id, .. }
+            | DomainEvent::RedemptionConfirmed { user_id, .. }
+            | DomainEvent::RedemptionCancelled { user_id, .. }
+            | DomainEvent::SurveyCompleted { user_id, .. }
+            | DomainEvent::LumiMatchAnswered { user_id, .. }
+            | DomainEvent::UserRegistered { user_id, .. }
+            | DomainEvent::ActionTracked { user_id, .. }
+            | DomainEvent::RaffleWon { user_id, .. } => Some(*user_id),
+            DomainEvent::RedemptionExpired { .. } => None,
+        }
+    }
+}
+
+/// Evento tal como lo recibe un suscriptor
+#[derive(Debug, Clone)]
+pub struct EventEnvelope {
+    /// Estable entre reintentos: úsese como clave de idempotencia
+    pub event_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    /// Intento actual (1 = primera entrega)
+    pub attempt: i32,
+    pub event: DomainEvent,
+}
+
+/// Reacción a eventos de dominio
+#[async_trait]
+pub trait EventSubscriber: Send + Sync {
+    /// Nombre estable; se guarda en domain_event_subscriber_runs
+    fn name(&self) -> &'static str;
+
+    fn handles(&self, event: &DomainEvent) -> bool;
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<()>;
+}
+
+/// Fila del outbox reclamada por el dispatcher
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct OutboxEvent {
+    event_id: Uuid,
+    event_type: String,
+    payload: serde_json::Value,
+    occurred_at: DateTime<Utc>,
+    attempts: i32,
+    max_attempts: i32,
+}
+
+/// Resultado de un suscriptor para un evento
+#[derive(Debug)]
+pub struct SubscriberRun {
+    pub subscriber: &'static str,
+    pub result: Result<()>,
+}
+
+/// Resumen de un lote del dispatcher
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DispatchSummary {
+    pub processed: usize,
+    pub retrying: usize,
+    pub dead: usize,
+}
+
+/// Espera antes del siguiente intento: 10s, 40s, ~2.7m, ~11m, ~43m y luego 1h
+pub fn retry_delay(attempts: i32) -> chrono::Duration {
+    let exponent = attempts.clamp(1, 10) as u32 - 1;
+    let seconds = BACKOFF_BASE_SECONDS.saturating_mul(4_i64.saturating_pow(exponent));
+    chrono::Duration::seconds(seconds.min(BACKOFF_MAX_SECONDS))
+}
+
+pub struct EventBus {
+    db: PgPool,
+    subscribers: Vec<Arc<dyn EventSubscriber>>,
+}
+
+impl EventBus {
+    pub fn new(db: PgPool) -> Self {
+        Self { db, subscribers: Vec::new() }
+    }
+
+    /// Registrar un suscriptor (los nombres deben ser únicos)
+    pub fn subscribe(mut self, subscriber: Arc<dyn EventSubscriber>) -> Self {
+        if self.subscribers.iter().any(|s| s.name() == subscriber.name()) {
+            warn!("Event subscriber '{}' already registered, ignoring", subscriber.name());
+            return self;
+        }
+        self.subscribers.push(subscriber);
+        self
+    }
+
+    pub fn db(&self) -> &PgPool {
+        &self.db
+    }
+
+    pub fn subscriber_names(&self) -> Vec<&'static str> {
+        self.subscribers.iter().map(|s| s.name()).collect()
+    }
+
+    /// Publicar un evento en el outbox.
+    ///
+    /// Debe llamarse con la transacción del cambio de negocio (`&mut *tx`) cuando
+    /// exista; con el pool el evento se guarda igual pero no es atómico con el cambio.
+    pub async fn publish<'e, E>(executor: E, event: &DomainEvent) -> Result<Uuid>
+    where
+        E: sqlx::PgExecutor<'e>,
+    {
+        let event_id = Uuid::new_v4();
+        let payload = serde_json::to_value(event)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO public.domain_event_outbox (event_id, event_type, payload)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(event_id)
+        .bind(event.event_type())
+        .bind(&payload)
+        .execute(executor)
+        .await
+        .with_context(|| format!("Failed to publish {}", event.event_type()))?;
+
+        Ok(event_id)
+    }
+
+    /// Publicar fuera de una transacción sin afectar la operación original
+    /// (el error se registra). Para efectos donde el cambio ya se confirmó.
+    pub async fn publish_best_effort(db: &PgPool, event: &DomainEvent) {
+        if let Err(e) = Self::publish(db, event).await {
+            warn!("⚠️ {}", e);
+        }
+    }
+
+    /// Corre los suscriptores interesados que aún no procesaron el evento
+    pub async fn run_subscribers(&self, envelope: &EventEnvelope, already_handled: &HashSet<String>) -> Vec<SubscriberRun> {
+        let mut runs = Vec::new();
+        for subscriber in &self.subscribers {
+            if !subscriber.handles(&envelope.event) || already_handled.contains(subscriber.name()) {
+                continue;
+            }
+            // Un suscriptor colgado (p. ej. FCM sin responder) no puede retener el lote más allá del lease
+            let result = match tokio::time::timeout(Duration::from_secs(SUBSCRIBER_TIMEOUT_SECS), subscriber.handle(envelope)).await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow::anyhow!("timeout after {}s", SUBSCRIBER_TIMEOUT_SECS)),
+            };
+            record_domain_event_handled(envelope.event.event_type(), subscriber.name(), result.is_ok());
+            runs.push(SubscriberRun { subscriber: subscriber.name(), result });
+        }
+        runs
+    }
+
+    // ========================================================================
+    // DISPATCHER
+    // ========================================================================
+
+    /// Reclamar un lote de eventos vencidos (incluye leases expirados de procesos caídos)
+    async fn claim_due_events(&self) -> Result<Vec<OutboxEvent>> {
+        let rows = sqlx::query_as::<_, OutboxEvent>(
+            r#"
+            UPDATE public.domain_event_outbox o
+            SET status = 'dispatching',
+                locked_until = NOW() + make_interval(secs => $2)
+            WHERE o.event_id IN (
+                SELECT event_id
+                FROM public.domain_event_outbox
+                WHERE (status = 'pending' AND next_attempt_at <= NOW())
+                   OR (status = 'dispatching' AND locked_until < NOW())
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING o.event_id, o.event_type, o.payload, o.occurred_at, o.attempts, o.max_attempts
+            "#,
+        )
+        .bind(DISPATCH_BATCH_SIZE)
+        .bind(dispatch_lease_secs(self.subscribers.len()) as f64)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn handled_subscribers(&self, event_id: Uuid) -> Result<HashSet<String>> {
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT subscriber FROM public.domain_event_subscriber_runs WHERE event_id = $1",
+        )
+        .bind(event_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(names.into_iter().collect())
+    }
+
+    /// Procesar un lote del outbox
+    pub async fn dispatch_due(&self) -> Result<DispatchSummary> {
+        let events = self.claim_due_events().await?;
+        let mut summary = DispatchSummary::default();
+
+        for row in events {
+            let attempt_number = row.attempts + 1;
+
+            let errors: Vec<String> = match serde_json::from_value::<DomainEvent>(row.payload.clone()) {
+                Ok(event) => {
+                    let envelope = EventEnvelope {
+                        event_id: row.event_id,
+                        occurred_at: row.occurred_at,
+                        attempt: attempt_number,
+                        event,
+                    };
+                    let handled = self.handled_subscribers(row.event_id).await?;
+                    let mut errors = Vec::new();
+                    for run in self.run_subscribers(&envelope, &handled).await {
+                        match run.result {
+                            Ok(()) => {
+                                sqlx::query(
+                                    r#"
+                                    INSERT INTO public.domain_event_subscriber_runs (event_id, subscriber)
+                                    VALUES ($1, $2)
+                                    ON CONFLICT DO NOTHING
+                                    "#,
+                                )
+                                .bind(row.event_id)
+                                .bind(run.subscriber)
+                                .execute(&self.db)
+                                .await?;
+                            }
+                            Err(e) => {
+                                warn!("Subscriber '{}' failed on {} {}: {:#}", run.subscriber, row.event_type, row.event_id, e);
+                                errors.push(format!("{}: {:#}", run.subscriber, e));
+                            }
+                        }
+                    }
+                    errors
+                }
+                // Un payload que no deserializa no se arregla reintentando
+                Err(e) => {
+                    self.mark_dead(&row, attempt_number, &format!("Invalid payload: {}", e)).await?;
+                    summary.dead += 1;
+                    continue;
+                }
+            };
+
+            if errors.is_empty() {
+                sqlx::query(
+                    r#"
+                    UPDATE public.domain_event_outbox
+                    SET status = 'processed', attempts = $2, processed_at = NOW(),
+                        locked_until = NULL, last_error = NULL
+                    WHERE event_id = $1
+                    "#,
+                )
+                .bind(row.event_id)
+                .bind(attempt_number)
+                .execute(&self.db)
+                .await?;
+                summary.processed += 1;
+                continue;
+            }
+
+            let error_text = errors.join("; ");
+            if attempt_number >= row.max_attempts {
+                self.mark_dead(&row, attempt_number, &error_text).await?;
+                summary.dead += 1;
+            } else {
+                let next_attempt_at = Utc::now() + retry_delay(attempt_number);
+                sqlx::query(
+                    r#"
+                    UPDATE public.domain_event_outbox
+                    SET status = 'pending', attempts = $2, next_attempt_at = $3,
+                        locked_until = NULL, last_error = $4
+                    WHERE event_id = $1
+                    "#,
+                )
+                .bind(row.event_id)
+                .bind(attempt_number)
+                .bind(next_attempt_at)
+                .bind(&error_text)
+                .execute(&self.db)
+                .await?;
+                summary.retrying += 1;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    async fn mark_dead(&self, row: &OutboxEvent, attempt_number: i32, error_text: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE public.domain_event_outbox
+            SET status = 'dead', attempts = $2, locked_until = NULL, last_error = $3
+            WHERE event_id = $1
+            "#,
+        )
+        .bind(row.event_id)
+        .bind(attempt_number)
+        .bind(error_text)
+        .execute(&self.db)
+        .await?;
+        record_domain_event_dead_letter(&row.event_type);
+        error!(
+            "Domain event {} ({}) moved to dead-letter after {} attempts: {}",
+            row.event_id, row.event_type, attempt_number, error_text
+        );
+        Ok(())
+    }
+
+    /// Borrar eventos procesados con más de `days` días
+    pub async fn purge_processed(&self, days: i32) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM public.domain_event_outbox
+            WHERE status = 'processed' AND processed_at < NOW() - make_interval(days => $1)
+            "#,
+        )
+        .bind(days)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+// ============================================================================
+// SHARED INSTANCE
+// ============================================================================
+
+use std::sync::OnceLock;
+
+static EVENT_BUS: OnceLock<Arc<EventBus>> = OnceLock::new();
+
+/// Inicializar el bus con los suscriptores por defecto
+pub fn init_event_bus(db: PgPool) {
+    let bus = crate::services::event_subscribers::register_default_subscribers(EventBus::new(db));
+    info!("Event bus subscribers: {:?}", bus.subscriber_names());
+    if EVENT_BUS.set(Arc::new(bus)).is_err() {
+        warn!("Event bus already initialized");
+    }
+}
+
+pub fn get_event_bus() -> Option<Arc<EventBus>> {
+    EVENT_BUS.get().cloned()
+}
+
+// ============================================================================
+// BACKGROUND DISPATCHER
+// ============================================================================
+
+const DISPATCH_POLL_INTERVAL_SECS: u64 = 1;
+const DISPATCH_ERROR_BACKOFF_SECS: u64 = 30;
+/// Lotes chicos: los eventos de un lote se procesan en secuencia y todos comparten el lease
+const DISPATCH_BATCH_SIZE: i64 = 10;
+/// Tiempo máximo de un suscriptor para un evento; al vencer cuenta como fallo y se reintenta
+const SUBSCRIBER_TIMEOUT_SECS: u64 = 15;
+const BACKOFF_BASE_SECONDS: i64 = 10;
+const BACKOFF_MAX_SECONDS: i64 = 3600;
+/// Días que se conservan los eventos procesados
+pub const PROCESSED_RETENTION_DAYS: i32 = 14;
+
+/// Tiempo que un evento queda reservado por un dispatcher antes de poder reclamarse de nuevo.
+/// Debe cubrir el peor caso del lote (cada suscriptor de cada evento agotando el timeout) con
+/// margen; si expira a mitad del lote otro dispatcher reclama los eventos y los suscriptores
+/// corren dos veces.
+pub fn dispatch_lease_secs(subscriber_count: usize) -> i64 {
+    DISPATCH_BATCH_SIZE * SUBSCRIBER_TIMEOUT_SECS as i64 * subscriber_count.max(1) as i64 * 2 + 60
+}
+
+/// Start the domain event dispatcher as a background task
+///
+/// Several instances can run concurrently: rows are claimed with
+/// FOR UPDATE SKIP LOCKED and a lease (locked_until).
+pub async fn start_event_dispatcher(db: PgPool) {
+    let bus = get_event_bus().unwrap_or_else(|| {
+        Arc::new(crate::services::event_subscribers::register_default_subscribers(EventBus::new(db)))
+    });
+
+    info!(
+        "Starting domain event dispatcher (poll interval: {}s)",
+        DISPATCH_POLL_INTERVAL_SECS
+    );
+
+    let mut consecutive_errors = 0u32;
+
+    loop {
+        match bus.dispatch_due().await {
+            Ok(summary) => {
+                consecutive_errors = 0;
+                if summary.retrying + summary.dead > 0 {
+                    info!(
+                        "Event dispatcher: processed={}, retrying={}, dead={}",
+                        summary.processed, summary.retrying, summary.dead
+                    );
+                }
+                // Lote lleno: seguir sin esperar
+                if summary.processed + summary.retrying + summary.dead >= DISPATCH_BATCH_SIZE as usize {
+                    continue;
+                }
+            }
+            Err(e) => {
+                consecutive_errors += 1;
+                error!("Event dispatcher error (consecutive: {}): {}", consecutive_errors, e);
+
+                if consecutive_errors >= 3 {
+                    let backoff = std::cmp::min(
+                        DISPATCH_ERROR_BACKOFF_SECS * 2u64.pow(consecutive_errors.min(6) - 3),
+                        300
+                    );
+                    warn!("Event dispatcher backing off for {}s due to repeated errors", backoff);
+                    tokio::time::sleep(Duration::from_secs(backoff)).await;
+                    continue;
+                }
+            }
+        }
+
+        tokio::time::sleep(Duration::from_secs(DISPATCH_POLL_INTERVAL_SECS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingSubscriber {
+        name: &'static str,
+        only: &'static str,
+        fail: bool,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EventSubscriber for CountingSubscriber {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn handles(&self, event: &DomainEvent) -> bool {
+            event.event_type() == self.only
+        }
+
+        async fn handle(&self, _envelope: &EventEnvelope) -> Result<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                anyhow::bail!("boom");
+            }
+            Ok(())
+        }
+    }
+
+    fn subscriber(name: &'static str, only: &'static str, fail: bool) -> Arc<CountingSubscriber> {
+        Arc::new(CountingSubscriber { name, only, fail, calls: AtomicUsize::new(0) })
+    }
+
+    fn envelope(event: DomainEvent) -> EventEnvelope {
+        EventEnvelope { event_id: Uuid::new_v4(), occurred_at: Utc::now(), attempt: 1, event }
+    }
+
+    #[test]
+    fn test_event_type_matches_serde_tag() {
+        let events = vec![
+            DomainEvent::InvoiceSaved { user_id: 1, cufe: "FE01".into(), source: "url".into() },
+            DomainEvent::LumisCredited { user_id: 1, lumis: 5, source: "invoice_rules".into(), reference: None },
+            DomainEvent::RedemptionExpired {
+                redemption_id: Uuid::nil(),
+                redemption_code: "LUM-1".into(),
+                offer_name: "Café".into(),
+                merchant_id: None,
+            },
+            DomainEvent::SurveyCompleted { user_id: 1, survey_id: 7 },
+            DomainEvent::LumiMatchAnswered { user_id: 1, question_id: Uuid::nil(), option_id: Uuid::nil() },
+            DomainEvent::UserRegistered { user_id: 1, source: "email".into() },
+            DomainEvent::ActionTracked { user_id: 1, action: "daily_login".into(), channel: "mobile_app".into() },
+            DomainEvent::RaffleWon {
+                user_id: 1,
+                raffle_id: 3,
+                raffle_title: "Tómbola de Cash".into(),
+                prize_description: "$100".into(),
+                ticket_number: 17,
+            },
+        ];
+        for event in events {
+            let value = serde_json::to_value(&event).unwrap();
+            assert_eq!(value["type"], event.event_type());
+            let back: DomainEvent = serde_json::from_value(value).unwrap();
+            assert_eq!(back, event);
+        }
+    }
+
+    #[test]
+    fn test_payload_shape() {
+        let event: DomainEvent = serde_json::from_value(json!({
+            "type": "survey.completed",
+            "user_id": 42,
+            "survey_id": 3
+        }))
+        .unwrap();
+        assert_eq!(event.user_id(), Some(42));
+        assert!(serde_json::from_value::<DomainEvent>(json!({"type": "unknown.event"})).is_err());
+    }
+
+    #[test]
+    fn test_lease_outlasts_worst_case_batch() {
+        for subscribers in [0, 1, 6, 20] {
+            let worst_case = DISPATCH_BATCH_SIZE * SUBSCRIBER_TIMEOUT_SECS as i64 * subscribers.max(1) as i64;
+            assert!(dispatch_lease_secs(subscribers) > worst_case);
+        }
+    }
+
+    #[test]
+    fn test_retry_delay_is_capped() {
+        assert_eq!(retry_delay(1).num_seconds(), 10);
+        assert_eq!(retry_delay(2).num_seconds(), 40);
+        assert_eq!(retry_delay(10).num_seconds(), BACKOFF_MAX_SECONDS);
+    }
+
+    #[tokio::test]
+    async fn test_run_subscribers_filters_and_skips_handled() {
+        let db = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let invoices = subscriber("invoices", "invoice.saved", false);
+        let surveys = subscriber("surveys", "survey.completed", false);
+        let failing = subscriber("failing", "invoice.saved", true);
+        let bus = EventBus::new(db)
+            .subscribe(invoices.clone())
+            .subscribe(surveys.clone())
+            .subscribe(failing.clone())
+            .subscribe(subscriber("invoices", "invoice.saved", false)); // nombre repetido
+        assert_eq!(bus.subscriber_names(), vec!["invoices", "surveys", "failing"]);
+
+        let env = envelope(DomainEvent::InvoiceSaved { user_id: 1, cufe: "FE01".into(), source: "url".into() });
+        let runs = bus.run_subscribers(&env, &HashSet::new()).await;
+        assert_eq!(runs.len(), 2);
+        assert!(runs.iter().any(|r| r.subscriber == "invoices" && r.result.is_ok()));
+        assert!(runs.iter().any(|r| r.subscriber == "failing" && r.result.is_err()));
+        assert_eq!(surveys.calls.load(Ordering::SeqCst), 0);
+
+        // Reintento: solo corre el que falló
+        let handled: HashSet<String> = ["invoices".to_string()].into_iter().collect();
+        let runs = bus.run_subscribers(&env, &handled).await;
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].subscriber, "failing");
+        assert_eq!(invoices.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(failing.calls.load(Ordering::SeqCst), 2);
+    }
+}
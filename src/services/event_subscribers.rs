@@ -0,0 +1,485 @@
+// ============================================================================
+// EVENT SUBSCRIBERS - Reacciones a eventos de dominio
+// ============================================================================
+//
+// Cada suscriptor reacciona a los eventos publicados con `EventBus::publish`.
+// Se entregan al menos una vez: todos deben ser idempotentes (claves estables
+// por CUFE, encuesta o event_id). Un error hace que el dispatcher reintente
+// solo ese suscriptor.
+// ============================================================================
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::domains::gamification::mission_service::{MissionEvent, MissionService};
+use crate::domains::gamification::referral_service::ReferralService;
+use crate::domains::lumimatch::TagService;
+use crate::domains::notifications::campaigns::{CampaignService, ConversionGoal};
+use crate::domains::notifications::{get_notifier, LogicalNotification};
+use crate::domains::rewards::campaign_service::CampaignService as EarnCampaignService;
+use crate::domains::rewards::raffle_service::RaffleService;
+use crate::observability::metrics::record_business_event;
+use crate::services::event_bus_service::{DomainEvent, EventBus, EventEnvelope, EventSubscriber};
+use crate::services::webhook_service::{WebhookEvent, WebhookService};
+
+/// Suscriptores registrados en producción
+pub fn register_default_subscribers(bus: EventBus) -> EventBus {
+    let db = bus.db().clone();
+    bus.subscribe(Arc::new(LeaderboardSubscriber))
+        .subscribe(Arc::new(ReferralSubscriber { db: db.clone() }))
+        .subscribe(Arc::new(EarnCampaignSubscriber { db: db.clone() }))
+        .subscribe(Arc::new(MissionSubscriber { db: db.clone() }))
+        .subscribe(Arc::new(AchievementSubscriber { db: db.clone() }))
+        .subscribe(Arc::new(UserTagSubscriber { db: db.clone() }))
+        .subscribe(Arc::new(NotificationSubscriber { db: db.clone() }))
+        .subscribe(Arc::new(CampaignConversionSubscriber { db: db.clone() }))
+        .subscribe(Arc::new(MerchantWebhookSubscriber { db }))
+        .subscribe(Arc::new(AnalyticsSubscriber))
+}
+
+// ============================================================================
+// GAMIFICACIÓN
+// ============================================================================
+
+/// 1 XP por factura en los leaderboards (idempotente por CUFE)
+pub struct LeaderboardSubscriber;
+
+#[async_trait]
+impl EventSubscriber for LeaderboardSubscriber {
+    fn name(&self) -> &'static str {
+        "leaderboards"
+    }
+
+    fn handles(&self, event: &DomainEvent) -> bool {
+        matches!(event, DomainEvent::InvoiceSaved { .. })
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+        let DomainEvent::InvoiceSaved { user_id, cufe, .. } = &envelope.event else {
+            return Ok(());
+        };
+        let Some(leaderboards) = crate::services::get_leaderboard_service() else {
+            return Ok(());
+        };
+        leaderboards
+            .record_xp(*user_id as i32, &format!("invoice:{}", cufe), 1, "invoice", envelope.occurred_at)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Cuenta la factura para el referido pendiente (recalcula, es idempotente)
+pub struct ReferralSubscriber {
+    db: PgPool,
+}
+
+#[async_trait]
+impl EventSubscriber for ReferralSubscriber {
+    fn name(&self) -> &'static str {
+        "referrals"
+    }
+
+    fn handles(&self, event: &DomainEvent) -> bool {
+        matches!(event, DomainEvent::InvoiceSaved { .. })
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+        let DomainEvent::InvoiceSaved { user_id, .. } = &envelope.event else {
+            return Ok(());
+        };
+        ReferralService::new(self.db.clone()).record_invoice(*user_id as i32).await?;
+        Ok(())
+    }
+}
+
+/// Campañas de acumulación de comercios sobre la factura (idempotente por
+/// campaña y CUFE); cada premio publica su `lumis.credited` en su transacción
+pub struct EarnCampaignSubscriber {
+    db: PgPool,
+}
+
+#[async_trait]
+impl EventSubscriber for EarnCampaignSubscriber {
+    fn name(&self) -> &'static str {
+        "earn_campaigns"
+    }
+
+    fn handles(&self, event: &DomainEvent) -> bool {
+        matches!(event, DomainEvent::InvoiceSaved { .. })
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+        let DomainEvent::InvoiceSaved { user_id, cufe, .. } = &envelope.event else {
+            return Ok(());
+        };
+        EarnCampaignService::new(self.db.clone())
+            .evaluate_invoice(*user_id, cufe)
+            .await
+            .with_context(|| format!("earn campaign evaluation failed for CUFE {}", cufe))?;
+        Ok(())
+    }
+}
+
+/// Misiones declarativas (cada evento cuenta una vez por misión)
+pub struct MissionSubscriber {
+    db: PgPool,
+}
+
+#[async_trait]
+impl EventSubscriber for MissionSubscriber {
+    fn name(&self) -> &'static str {
+        "missions"
+    }
+
+    fn handles(&self, event: &DomainEvent) -> bool {
+        matches!(
+            event,
+            DomainEvent::InvoiceSaved { .. } | DomainEvent::SurveyCompleted { .. } | DomainEvent::ActionTracked { .. }
+        )
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+        let service = MissionService::new(self.db.clone());
+        let event = match &envelope.event {
+            DomainEvent::InvoiceSaved { user_id, cufe, .. } => {
+                match service.invoice_event(*user_id as i32, cufe, envelope.occurred_at).await? {
+                    Some(event) => event,
+                    None => return Ok(()),
+                }
+            }
+            DomainEvent::SurveyCompleted { user_id, survey_id } => {
+                MissionEvent::survey(*user_id as i32, *survey_id, envelope.occurred_at)
+            }
+            DomainEvent::ActionTracked { user_id, action, .. } => {
+                MissionEvent::action(*user_id as i32, action, envelope.event_id, envelope.occurred_at)
+            }
+            _ => return Ok(()),
+        };
+        service.handle_event(&event).await?;
+        Ok(())
+    }
+}
+
+/// Registra la actividad en gamification.track_user_action (logros, eventos)
+pub struct AchievementSubscriber {
+    db: PgPool,
+}
+
+#[async_trait]
+impl EventSubscriber for AchievementSubscriber {
+    fn name(&self) -> &'static str {
+        "achievements"
+    }
+
+    fn handles(&self, event: &DomainEvent) -> bool {
+        matches!(event, DomainEvent::InvoiceSaved { .. } | DomainEvent::SurveyCompleted { .. })
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+        let (user_id, action, metadata) = match &envelope.event {
+            DomainEvent::InvoiceSaved { user_id, cufe, source } => {
+                (*user_id, "invoice_upload", json!({ "cufe": cufe, "source": source }))
+            }
+            DomainEvent::SurveyCompleted { user_id, survey_id } => {
+                (*user_id, "survey_complete", json!({ "survey_id": survey_id }))
+            }
+            _ => return Ok(()),
+        };
+
+        sqlx::query("SELECT 1 FROM gamification.track_user_action($1, $2, 'api', $3)")
+            .bind(user_id as i32)
+            .bind(action)
+            .bind(json!({ "event_id": envelope.event_id, "data": metadata }))
+            .execute(&self.db)
+            .await
+            .with_context(|| format!("track_user_action({}) failed for user {}", action, user_id))?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// SEGMENTACIÓN
+// ============================================================================
+
+/// Deriva tags del usuario (reemplaza la evidencia de la misma factura,
+/// pregunta o encuesta, así que reintentar no duplica)
+pub struct UserTagSubscriber {
+    db: PgPool,
+}
+
+#[async_trait]
+impl EventSubscriber for UserTagSubscriber {
+    fn name(&self) -> &'static str {
+        "user_tags"
+    }
+
+    fn handles(&self, event: &DomainEvent) -> bool {
+        matches!(
+            event,
+            DomainEvent::InvoiceSaved { .. }
+                | DomainEvent::SurveyCompleted { .. }
+                | DomainEvent::LumiMatchAnswered { .. }
+        )
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+        let tags = TagService::new(self.db.clone());
+        let observed_at = envelope.occurred_at;
+        match &envelope.event {
+            DomainEvent::InvoiceSaved { user_id, cufe, .. } => {
+                tags.record_invoice(*user_id as i32, cufe, observed_at).await?;
+            }
+            DomainEvent::SurveyCompleted { user_id, survey_id } => {
+                tags.record_survey(*user_id as i32, *survey_id, observed_at).await?;
+            }
+            DomainEvent::LumiMatchAnswered { user_id, question_id, option_id } => {
+                tags.record_lumimatch_answer(*user_id as i32, *question_id, *option_id, observed_at).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// NOTIFICACIONES Y WEBHOOKS
+// ============================================================================
+
+/// Atribuye conversiones a campañas de push (factura, encuesta o respuesta
+/// de LumiMatch dentro de la ventana); solo la primera cuenta
+pub struct CampaignConversionSubscriber {
+    db: PgPool,
+}
+
+#[async_trait]
+impl EventSubscriber for CampaignConversionSubscriber {
+    fn name(&self) -> &'static str {
+        "push_campaign_conversions"
+    }
+
+    fn handles(&self, event: &DomainEvent) -> bool {
+        matches!(
+            event,
+            DomainEvent::InvoiceSaved { .. }
+                | DomainEvent::SurveyCompleted { .. }
+                | DomainEvent::LumiMatchAnswered { .. }
+        )
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+        let (user_id, goal) = match &envelope.event {
+            DomainEvent::InvoiceSaved { user_id, .. } => (*user_id, ConversionGoal::InvoiceSaved),
+            DomainEvent::SurveyCompleted { user_id, .. } => (*user_id, ConversionGoal::SurveyCompleted),
+            DomainEvent::LumiMatchAnswered { user_id, .. } => (*user_id, ConversionGoal::LumimatchAnswered),
+            _ => return Ok(()),
+        };
+        CampaignService::new(self.db.clone())
+            .record_conversion(user_id, goal, envelope.occurred_at)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Avisos al usuario por redenciones y a ganadores de tómbolas, vía el `Notifier`
+/// (canales, fallback y dedupe por clave)
+pub struct NotificationSubscriber {
+    db: PgPool,
+}
+
+#[async_trait]
+impl EventSubscriber for NotificationSubscriber {
+    fn name(&self) -> &'static str {
+        "notifications"
+    }
+
+    fn handles(&self, event: &DomainEvent) -> bool {
+        matches!(
+            event,
+            DomainEvent::RedemptionCreated { .. }
+                | DomainEvent::RedemptionConfirmed { .. }
+                | DomainEvent::RaffleWon { .. }
+        )
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+        // Sin notificador el evento se reintenta más tarde en vez de perderse
+        let notifier = get_notifier().context("notifier not initialized")?;
+        let notification = match &envelope.event {
+            DomainEvent::RedemptionCreated { user_id, redemption_id, redemption_code, offer_name, .. } => {
+                LogicalNotification::redemption_created(*user_id, *redemption_id, offer_name, redemption_code)
+            }
+            DomainEvent::RedemptionConfirmed { user_id, redemption_id, offer_name, .. } => {
+                LogicalNotification::redemption_confirmed(*user_id, *redemption_id, offer_name)
+            }
+            DomainEvent::RaffleWon { user_id, raffle_id, raffle_title, prize_description, ticket_number } => {
+                LogicalNotification::new(
+                    *user_id,
+                    "reward",
+                    "notification.raffle_won.title",
+                    "notification.raffle_won.body",
+                    &format!("raffle_won:{}:{}", raffle_id, user_id),
+                )
+                .priority("high")
+                .param("ticket", ticket_number)
+                .param("raffle", raffle_title)
+                .param("prize", prize_description)
+                .action_url(&format!("/raffles/{}", raffle_id))
+                .payload(json!({ "raffle_id": raffle_id, "ticket_number": ticket_number }))
+            }
+            _ => return Ok(()),
+        };
+
+        notifier.notify(&notification).await.context("notifier failed")?;
+        if let DomainEvent::RaffleWon { user_id, raffle_id, .. } = &envelope.event {
+            RaffleService::new(self.db.clone()).mark_notified(*raffle_id, *user_id as i32).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Encola el webhook del merchant (el id del webhook es el event_id del dominio)
+pub struct MerchantWebhookSubscriber {
+    db: PgPool,
+}
+
+impl MerchantWebhookSubscriber {
+    fn webhook_event(event: &DomainEvent) -> Option<WebhookEvent> {
+        match event {
+            DomainEvent::RedemptionCreated {
+                merchant_id: Some(merchant_id),
+                redemption_id,
+                redemption_code,
+                offer_name,
+                lumis_spent,
+                ..
+            } => Some(WebhookEvent::redemption_created(
+                *merchant_id,
+                *redemption_id,
+                redemption_code,
+                offer_name,
+                *lumis_spent,
+            )),
+            DomainEvent::RedemptionConfirmed {
+                merchant_id: Some(merchant_id),
+                redemption_id,
+                redemption_code,
+                offer_name,
+                confirmed_by,
+                ..
+            } => Some(WebhookEvent::redemption_confirmed(
+                *merchant_id,
+                *redemption_id,
+                redemption_code,
+                offer_name,
+                confirmed_by,
+            )),
+            DomainEvent::RedemptionCancelled {
+                merchant_id: Some(merchant_id),
+                redemption_id,
+                redemption_code,
+                reason,
+                ..
+            } => Some(WebhookEvent::redemption_cancelled(*merchant_id, *redemption_id, redemption_code, reason)),
+            DomainEvent::RedemptionExpired {
+                merchant_id: Some(merchant_id),
+                redemption_id,
+                redemption_code,
+                offer_name,
+            } => Some(WebhookEvent::redemption_expired(*merchant_id, *redemption_id, redemption_code, offer_name)),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for MerchantWebhookSubscriber {
+    fn name(&self) -> &'static str {
+        "merchant_webhooks"
+    }
+
+    fn handles(&self, event: &DomainEvent) -> bool {
+        Self::webhook_event(event).is_some()
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+        let Some(mut webhook) = Self::webhook_event(&envelope.event) else {
+            return Ok(());
+        };
+        webhook.id = envelope.event_id;
+        webhook.timestamp = envelope.occurred_at;
+
+        let mut tx = self.db.begin().await?;
+        let already_enqueued: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM rewards.webhook_outbox WHERE event_id = $1 AND replay_of IS NULL)",
+        )
+        .bind(webhook.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !already_enqueued {
+            WebhookService::enqueue(&mut *tx, &webhook).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// ANALÍTICA
+// ============================================================================
+
+/// Contadores de eventos de negocio por tipo y canal (Prometheus)
+pub struct AnalyticsSubscriber;
+
+#[async_trait]
+impl EventSubscriber for AnalyticsSubscriber {
+    fn name(&self) -> &'static str {
+        "analytics"
+    }
+
+    fn handles(&self, _event: &DomainEvent) -> bool {
+        true
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+        let source = match &envelope.event {
+            DomainEvent::InvoiceSaved { source, .. }
+            | DomainEvent::LumisCredited { source, .. }
+            | DomainEvent::UserRegistered { source, .. } => source.as_str(),
+            DomainEvent::ActionTracked { channel, .. } => channel.as_str(),
+            _ => "system",
+        };
+        record_business_event(envelope.event.event_type(), source);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_webhook_only_for_merchant_redemptions() {
+        let with_merchant = DomainEvent::RedemptionCancelled {
+            user_id: 1,
+            redemption_id: Uuid::new_v4(),
+            redemption_code: "LUM-1".into(),
+            merchant_id: Some(Uuid::new_v4()),
+            reason: "user".into(),
+        };
+        let webhook = MerchantWebhookSubscriber::webhook_event(&with_merchant).unwrap();
+        assert_eq!(webhook.event, "redemption.cancelled");
+
+        let without_merchant = DomainEvent::RedemptionExpired {
+            redemption_id: Uuid::new_v4(),
+            redemption_code: "LUM-2".into(),
+            offer_name: "Café".into(),
+            merchant_id: None,
+        };
+        assert!(MerchantWebhookSubscriber::webhook_event(&without_merchant).is_none());
+        assert!(MerchantWebhookSubscriber::webhook_event(&DomainEvent::SurveyCompleted { user_id: 1, survey_id: 2 }).is_none());
+    }
+}
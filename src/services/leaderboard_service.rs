@@ -0,0 +1,957 @@
+// ============================================================================
+// LEADERBOARD SERVICE - Rankings por periodo y alcance en Redis sorted sets
+// ============================================================================
+//
+// Cada evento de XP (hoy: 1 XP por factura guardada, igual que
+// gamification.user_status.total_xp) se guarda en
+// gamification.leaderboard_xp_events y luego incrementa un sorted set por
+// periodo (diario/semanal/mensual/histórico) y alcance (global y ciudad):
+//
+//   lb:{period}:{period_key}:{scope}   ej. lb:weekly:2026-W42:city:david
+//
+// El ranking de amigos se arma con los puntajes del set global. Postgres es la
+// fuente de verdad: `rebuild` reconstruye Redis y `archive_period` guarda las
+// posiciones finales de un periodo cerrado para poder premiar.
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use chrono_tz::America::Panama;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+pub const MAX_PAGE_SIZE: i64 = 100;
+pub const MAX_NEIGHBORS: i64 = 25;
+pub const MAX_FRIENDS: i64 = 200;
+
+// ======================================================================
+// PERIODOS Y ALCANCES
+// ======================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+    AllTime,
+}
+
+impl Period {
+    pub const ALL: [Period; 4] = [Period::Daily, Period::Weekly, Period::Monthly, Period::AllTime];
+    pub const CLOSABLE: [Period; 3] = [Period::Daily, Period::Weekly, Period::Monthly];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "daily" => Some(Period::Daily),
+            "weekly" => Some(Period::Weekly),
+            "monthly" => Some(Period::Monthly),
+            "all_time" | "alltime" => Some(Period::AllTime),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Period::Daily => "daily",
+            Period::Weekly => "weekly",
+            Period::Monthly => "monthly",
+            Period::AllTime => "all_time",
+        }
+    }
+
+    /// Clave del periodo que contiene `date` (fecha de Panamá)
+    pub fn key_for(&self, date: NaiveDate) -> String {
+        match self {
+            Period::Daily => date.format("%Y-%m-%d").to_string(),
+            Period::Weekly => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Period::Monthly => date.format("%Y-%m").to_string(),
+            Period::AllTime => "all".to_string(),
+        }
+    }
+
+    /// Clave del periodo inmediatamente anterior al que contiene `date`
+    pub fn previous_key(&self, date: NaiveDate) -> Option<String> {
+        let (start, _) = self.bounds(&self.key_for(date))?;
+        Some(self.key_for(start - Duration::days(1)))
+    }
+
+    /// Claves de los periodos ya terminados desde el que contiene `first` hasta
+    /// el anterior al actual, en orden
+    pub fn ended_keys_since(&self, first: NaiveDate, today: NaiveDate) -> Vec<String> {
+        let Some((current_start, _)) = self.bounds(&self.key_for(today)) else {
+            return Vec::new();
+        };
+        let mut keys = Vec::new();
+        let mut date = first;
+        while date < current_start {
+            let key = self.key_for(date);
+            let Some((_, end)) = self.bounds(&key) else {
+                break;
+            };
+            keys.push(key);
+            date = end;
+        }
+        keys
+    }
+
+    /// Rango [inicio, fin) en fechas de Panamá de una clave de periodo
+    pub fn bounds(&self, key: &str) -> Option<(NaiveDate, NaiveDate)> {
+        match self {
+            Period::Daily => {
+                let day = NaiveDate::parse_from_str(key, "%Y-%m-%d").ok()?;
+                Some((day, day + Duration::days(1)))
+            }
+            Period::Weekly => {
+                let (year, week) = key.split_once("-W")?;
+                let start = NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, chrono::Weekday::Mon)?;
+                Some((start, start + Duration::days(7)))
+            }
+            Period::Monthly => {
+                let start = NaiveDate::parse_from_str(&format!("{}-01", key), "%Y-%m-%d").ok()?;
+                let end = if start.month() == 12 {
+                    NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)?
+                } else {
+                    NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)?
+                };
+                Some((start, end))
+            }
+            Period::AllTime => None,
+        }
+    }
+
+    /// TTL del sorted set: se conserva un tiempo después del cierre
+    pub fn ttl_secs(&self) -> Option<i64> {
+        match self {
+            Period::Daily => Some(3 * 24 * 3600),
+            Period::Weekly => Some(15 * 24 * 3600),
+            Period::Monthly => Some(62 * 24 * 3600),
+            Period::AllTime => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scope {
+    Global,
+    City(String),
+    Friends,
+}
+
+impl Scope {
+    /// Clave de alcance en Redis / archivo (`friends` no tiene set propio)
+    pub fn key(&self) -> String {
+        match self {
+            Scope::Global => "global".to_string(),
+            Scope::City(slug) => format!("city:{}", slug),
+            Scope::Friends => "friends".to_string(),
+        }
+    }
+}
+
+/// Normaliza una ciudad: minúsculas, sin tildes, separadores como '-'
+pub fn city_slug(city: &str) -> Option<String> {
+    let mut slug = String::with_capacity(city.len());
+    for c in city.trim().to_lowercase().chars() {
+        let c = match c {
+            'á' => 'a',
+            'é' => 'e',
+            'í' => 'i',
+            'ó' => 'o',
+            'ú' | 'ü' => 'u',
+            'ñ' => 'n',
+            other => other,
+        };
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches('-').to_string();
+    if slug.is_empty() {
+        None
+    } else {
+        Some(slug)
+    }
+}
+
+pub fn redis_key(period: Period, period_key: &str, scope_key: &str) -> String {
+    format!("lb:{}:{}:{}", period.as_str(), period_key, scope_key)
+}
+
+/// Rango [start, stop] (0-based, inclusivo) alrededor de una posición
+pub fn neighbor_window(position: i64, neighbors: i64) -> (i64, i64) {
+    ((position - neighbors).max(0), position + neighbors)
+}
+
+/// Fecha de Panamá de un instante
+pub fn panama_date(at: DateTime<Utc>) -> NaiveDate {
+    at.with_timezone(&Panama).date_naive()
+}
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardEntry {
+    pub rank: i64,
+    pub user_id: i32,
+    pub display_name: String,
+    pub score: i64,
+    pub is_me: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaderboardPage {
+    pub period: Period,
+    pub period_key: String,
+    pub scope: String,
+    pub total_participants: i64,
+    pub entries: Vec<LeaderboardEntry>,
+    pub me: Option<LeaderboardEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MyRank {
+    pub period: Period,
+    pub period_key: String,
+    pub scope: String,
+    pub total_participants: i64,
+    /// None si aún no tiene XP en el periodo
+    pub me: Option<LeaderboardEntry>,
+    pub neighbors: Vec<LeaderboardEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchivedStandings {
+    pub period: Period,
+    pub period_key: String,
+    pub scope: String,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddFriendOutcome {
+    Added,
+    AlreadyFriends,
+    UserNotFound,
+    LimitReached,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Friend {
+    pub user_id: i32,
+    pub display_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+// ======================================================================
+// SERVICIO
+// ======================================================================
+
+pub struct LeaderboardService {
+    db: PgPool,
+    redis: deadpool_redis::Pool,
+}
+
+impl LeaderboardService {
+    pub fn new(db: PgPool, redis: deadpool_redis::Pool) -> Self {
+        Self { db, redis }
+    }
+
+    async fn user_city_slug(&self, user_id: i32) -> Result<Option<String>> {
+        let city = sqlx::query_scalar::<_, Option<String>>("SELECT city FROM public.dim_users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.db)
+            .await?
+            .flatten();
+        Ok(city.as_deref().and_then(city_slug))
+    }
+
+    /// Resuelve `global` | `city` | `friends` para el usuario
+    pub async fn resolve_scope(&self, user_id: i32, scope: &str) -> Result<Option<Scope>> {
+        Ok(match scope {
+            "global" => Some(Scope::Global),
+            "friends" => Some(Scope::Friends),
+            "city" => self.user_city_slug(user_id).await?.map(Scope::City),
+            _ => None,
+        })
+    }
+
+    /// Registra XP una sola vez por `event_key` y actualiza los sorted sets.
+    /// Devuelve false si el evento ya estaba registrado. La fila de dedupe se
+    /// confirma solo después del ZINCRBY: si Redis falla se hace rollback y el
+    /// reintento vuelve a sumar.
+    pub async fn record_xp(
+        &self,
+        user_id: i32,
+        event_key: &str,
+        xp: i64,
+        source: &str,
+        occurred_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let city = self.user_city_slug(user_id).await?;
+        let mut tx = self.db.begin().await?;
+
+        let inserted = sqlx::query_scalar::<_, i32>(
+            r#"
+            INSERT INTO gamification.leaderboard_xp_events (event_key, user_id, xp, source, city_slug, occurred_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (event_key) DO NOTHING
+            RETURNING 1
+            "#,
+        )
+        .bind(event_key)
+        .bind(user_id)
+        .bind(xp as i32)
+        .bind(source)
+        .bind(city.as_deref())
+        .bind(occurred_at)
+        .fetch_optional(&mut *tx)
+        .await?
+        .is_some();
+
+        if !inserted {
+            return Ok(false);
+        }
+
+        let date = panama_date(occurred_at);
+        let mut pipe = redis::pipe();
+        for period in Period::ALL {
+            let period_key = period.key_for(date);
+            let mut scopes = vec![Scope::Global.key()];
+            if let Some(slug) = &city {
+                scopes.push(Scope::City(slug.clone()).key());
+            }
+            for scope in scopes {
+                let key = redis_key(period, &period_key, &scope);
+                pipe.cmd("ZINCRBY").arg(&key).arg(xp).arg(user_id).ignore();
+                if let Some(ttl) = period.ttl_secs() {
+                    pipe.cmd("EXPIRE").arg(&key).arg(ttl).ignore();
+                }
+            }
+        }
+
+        let mut conn = self.redis.get().await?;
+        pipe.query_async::<()>(&mut conn).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn display_names(&self, user_ids: &[i32]) -> Result<HashMap<i32, String>> {
+        if user_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows = sqlx::query_as::<_, (i32, String)>(
+            r#"
+            SELECT id::INT, COALESCE(NULLIF(TRIM(name), ''), 'Lümer #' || id)
+            FROM public.dim_users
+            WHERE id = ANY($1)
+            "#,
+        )
+        .bind(user_ids)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn to_entries(&self, ranked: Vec<(i64, i32, i64)>, me: i32) -> Result<Vec<LeaderboardEntry>> {
+        let ids: Vec<i32> = ranked.iter().map(|(_, id, _)| *id).collect();
+        let names = self.display_names(&ids).await?;
+        Ok(ranked
+            .into_iter()
+            .map(|(rank, user_id, score)| LeaderboardEntry {
+                rank,
+                user_id,
+                display_name: names.get(&user_id).cloned().unwrap_or_else(|| format!("Lümer #{}", user_id)),
+                score,
+                is_me: user_id == me,
+            })
+            .collect())
+    }
+
+    /// Posiciones [start, stop] (0-based) de un sorted set, como (rank, user_id, score)
+    async fn range(&self, key: &str, start: i64, stop: i64) -> Result<Vec<(i64, i32, i64)>> {
+        let mut conn = self.redis.get().await?;
+        let rows: Vec<(String, f64)> = redis::cmd("ZREVRANGE")
+            .arg(key)
+            .arg(start)
+            .arg(stop)
+            .arg("WITHSCORES")
+            .query_async(&mut conn)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, (member, score))| {
+                member.parse::<i32>().ok().map(|id| (start + i as i64 + 1, id, score as i64))
+            })
+            .collect())
+    }
+
+    async fn position(&self, key: &str, user_id: i32) -> Result<Option<(i64, i64)>> {
+        let mut conn = self.redis.get().await?;
+        let (rank, score): (Option<i64>, Option<f64>) = redis::pipe()
+            .cmd("ZREVRANK")
+            .arg(key)
+            .arg(user_id)
+            .cmd("ZSCORE")
+            .arg(key)
+            .arg(user_id)
+            .query_async(&mut conn)
+            .await?;
+        Ok(rank.zip(score).map(|(r, s)| (r, s as i64)))
+    }
+
+    async fn cardinality(&self, key: &str) -> Result<i64> {
+        let mut conn = self.redis.get().await?;
+        Ok(redis::cmd("ZCARD").arg(key).query_async(&mut conn).await?)
+    }
+
+    async fn friend_ids(&self, user_id: i32) -> Result<Vec<i32>> {
+        Ok(sqlx::query_scalar::<_, i32>(
+            "SELECT friend_user_id FROM gamification.user_friends WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    /// Ranking de amigos + yo con los puntajes del set global
+    async fn friends_ranking(&self, period: Period, period_key: &str, user_id: i32) -> Result<Vec<(i64, i32, i64)>> {
+        let mut ids = self.friend_ids(user_id).await?;
+        ids.push(user_id);
+
+        let key = redis_key(period, period_key, &Scope::Global.key());
+        let mut pipe = redis::pipe();
+        for id in &ids {
+            pipe.cmd("ZSCORE").arg(&key).arg(*id);
+        }
+        let mut conn = self.redis.get().await?;
+        let scores: Vec<Option<f64>> = pipe.query_async(&mut conn).await?;
+
+        let mut scored: Vec<(i32, i64)> = ids
+            .into_iter()
+            .zip(scores)
+            .filter_map(|(id, score)| score.map(|s| (id, s as i64)))
+            .collect();
+        scored.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        Ok(scored
+            .into_iter()
+            .enumerate()
+            .map(|(i, (id, score))| (i as i64 + 1, id, score))
+            .collect())
+    }
+
+    /// Top del periodo actual con la posición del usuario
+    pub async fn top(&self, period: Period, scope: &Scope, user_id: i32, limit: i64, offset: i64) -> Result<LeaderboardPage> {
+        let period_key = period.key_for(panama_date(Utc::now()));
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let offset = offset.max(0);
+
+        let (ranked, total, me) = match scope {
+            Scope::Friends => {
+                let all = self.friends_ranking(period, &period_key, user_id).await?;
+                let me = all.iter().find(|(_, id, _)| *id == user_id).cloned();
+                let total = all.len() as i64;
+                let page = all.into_iter().skip(offset as usize).take(limit as usize).collect();
+                (page, total, me)
+            }
+            _ => {
+                let key = redis_key(period, &period_key, &scope.key());
+                let page = self.range(&key, offset, offset + limit - 1).await?;
+                let total = self.cardinality(&key).await?;
+                let me = self
+                    .position(&key, user_id)
+                    .await?
+                    .map(|(position, score)| (position + 1, user_id, score));
+                (page, total, me)
+            }
+        };
+
+        let entries = self.to_entries(ranked, user_id).await?;
+        let me = match me {
+            Some(row) => self.to_entries(vec![row], user_id).await?.pop(),
+            None => None,
+        };
+
+        Ok(LeaderboardPage {
+            period,
+            period_key,
+            scope: scope.key(),
+            total_participants: total,
+            entries,
+            me,
+        })
+    }
+
+    /// Mi posición y `neighbors` usuarios arriba y abajo
+    pub async fn around_me(&self, period: Period, scope: &Scope, user_id: i32, neighbors: i64) -> Result<MyRank> {
+        let period_key = period.key_for(panama_date(Utc::now()));
+        let neighbors = neighbors.clamp(0, MAX_NEIGHBORS);
+
+        let (window, total) = match scope {
+            Scope::Friends => {
+                let all = self.friends_ranking(period, &period_key, user_id).await?;
+                let total = all.len() as i64;
+                let window = match all.iter().position(|(_, id, _)| *id == user_id) {
+                    Some(position) => {
+                        let (start, stop) = neighbor_window(position as i64, neighbors);
+                        all.into_iter()
+                            .skip(start as usize)
+                            .take((stop - start + 1) as usize)
+                            .collect()
+                    }
+                    None => Vec::new(),
+                };
+                (window, total)
+            }
+            _ => {
+                let key = redis_key(period, &period_key, &scope.key());
+                let total = self.cardinality(&key).await?;
+                let window = match self.position(&key, user_id).await? {
+                    Some((position, _)) => {
+                        let (start, stop) = neighbor_window(position, neighbors);
+                        self.range(&key, start, stop).await?
+                    }
+                    None => Vec::new(),
+                };
+                (window, total)
+            }
+        };
+
+        let neighbors = self.to_entries(window, user_id).await?;
+        let me = neighbors.iter().find(|e| e.is_me).cloned();
+
+        Ok(MyRank {
+            period,
+            period_key,
+            scope: scope.key(),
+            total_participants: total,
+            me,
+            neighbors,
+        })
+    }
+
+    /// Guarda las posiciones finales de un periodo (global y por ciudad) desde
+    /// los eventos de XP. Idempotente: devuelve None si ya estaba cerrado.
+    pub async fn archive_period(&self, period: Period, period_key: &str) -> Result<Option<i64>> {
+        let (start, end) = period
+            .bounds(period_key)
+            .ok_or_else(|| anyhow::anyhow!("Periodo inválido: {} {}", period.as_str(), period_key))?;
+
+        let mut tx = self.db.begin().await?;
+
+        let claimed = sqlx::query_scalar::<_, i32>(
+            r#"
+            INSERT INTO gamification.leaderboard_closures (period, period_key)
+            VALUES ($1, $2)
+            ON CONFLICT (period, period_key) DO NOTHING
+            RETURNING 1
+            "#,
+        )
+        .bind(period.as_str())
+        .bind(period_key)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if claimed.is_none() {
+            return Ok(None);
+        }
+
+        sqlx::query(
+            r#"
+            WITH scores AS (
+                SELECT 'global' AS scope, user_id, SUM(xp)::BIGINT AS score
+                FROM gamification.leaderboard_xp_events
+                WHERE occurred_at >= ($3::DATE)::TIMESTAMP AT TIME ZONE 'America/Panama'
+                  AND occurred_at < ($4::DATE)::TIMESTAMP AT TIME ZONE 'America/Panama'
+                GROUP BY user_id
+                UNION ALL
+                SELECT 'city:' || city_slug, user_id, SUM(xp)::BIGINT
+                FROM gamification.leaderboard_xp_events
+                WHERE occurred_at >= ($3::DATE)::TIMESTAMP AT TIME ZONE 'America/Panama'
+                  AND occurred_at < ($4::DATE)::TIMESTAMP AT TIME ZONE 'America/Panama'
+                  AND city_slug IS NOT NULL
+                GROUP BY city_slug, user_id
+            )
+            INSERT INTO gamification.leaderboard_archives (period, period_key, scope, user_id, rank, score)
+            SELECT $1, $2, scope, user_id,
+                   RANK() OVER (PARTITION BY scope ORDER BY score DESC)::INT,
+                   score
+            FROM scores
+            ON CONFLICT (period, period_key, scope, user_id) DO NOTHING
+            "#,
+        )
+        .bind(period.as_str())
+        .bind(period_key)
+        .bind(start)
+        .bind(end)
+        .execute(&mut *tx)
+        .await?;
+
+        let participants = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM gamification.leaderboard_archives
+            WHERE period = $1 AND period_key = $2 AND scope = 'global'
+            "#,
+        )
+        .bind(period.as_str())
+        .bind(period_key)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE gamification.leaderboard_closures SET participants = $3 WHERE period = $1 AND period_key = $2",
+        )
+        .bind(period.as_str())
+        .bind(period_key)
+        .bind(participants as i32)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        info!("🏁 Archived {} leaderboard {} ({} participants)", period.as_str(), period_key, participants);
+        Ok(Some(participants))
+    }
+
+    /// Cierra los periodos que terminaron (diario, semanal, mensual). Pensado para
+    /// correr poco después de medianoche en Panamá; si el job no corrió algún
+    /// día, archiva también todos los periodos pasados que quedaron sin cerrar.
+    pub async fn close_due_periods(&self) -> Result<Vec<(Period, String, i64)>> {
+        let today = panama_date(Utc::now());
+        let mut closed = Vec::new();
+
+        let first_event = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+            "SELECT MIN(occurred_at) FROM gamification.leaderboard_xp_events",
+        )
+        .fetch_one(&self.db)
+        .await?;
+        let Some(first_event) = first_event else {
+            return Ok(closed);
+        };
+
+        for period in Period::CLOSABLE {
+            let ended = period.ended_keys_since(panama_date(first_event), today);
+            let archived: Vec<String> = sqlx::query_scalar(
+                "SELECT period_key FROM gamification.leaderboard_closures WHERE period = $1 AND period_key = ANY($2)",
+            )
+            .bind(period.as_str())
+            .bind(&ended)
+            .fetch_all(&self.db)
+            .await?;
+
+            for key in ended.into_iter().filter(|k| !archived.contains(k)) {
+                match self.archive_period(period, &key).await {
+                    Ok(Some(participants)) => closed.push((period, key, participants)),
+                    Ok(None) => {}
+                    Err(e) => warn!("Failed to archive {} leaderboard {}: {}", period.as_str(), key, e),
+                }
+            }
+        }
+        Ok(closed)
+    }
+
+    /// Posiciones finales archivadas. `friends` re-rankea el archivo global.
+    pub async fn archived(
+        &self,
+        period: Period,
+        period_key: &str,
+        scope: &Scope,
+        user_id: i32,
+        limit: i64,
+    ) -> Result<ArchivedStandings> {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+
+        let closed_at = sqlx::query_scalar::<_, DateTime<Utc>>(
+            "SELECT closed_at FROM gamification.leaderboard_closures WHERE period = $1 AND period_key = $2",
+        )
+        .bind(period.as_str())
+        .bind(period_key)
+        .fetch_optional(&self.db)
+        .await?;
+
+        let rows = match scope {
+            Scope::Friends => {
+                let mut ids = self.friend_ids(user_id).await?;
+                ids.push(user_id);
+                sqlx::query_as::<_, (i64, i32, i64)>(
+                    r#"
+                    SELECT RANK() OVER (ORDER BY score DESC)::BIGINT, user_id, score
+                    FROM gamification.leaderboard_archives
+                    WHERE period = $1 AND period_key = $2 AND scope = 'global' AND user_id = ANY($3)
+                    ORDER BY score DESC, user_id
+                    LIMIT $4
+                    "#,
+                )
+                .bind(period.as_str())
+                .bind(period_key)
+                .bind(&ids)
+                .bind(limit)
+                .fetch_all(&self.db)
+                .await?
+            }
+            _ => {
+                sqlx::query_as::<_, (i64, i32, i64)>(
+                    r#"
+                    SELECT rank::BIGINT, user_id, score
+                    FROM gamification.leaderboard_archives
+                    WHERE period = $1 AND period_key = $2 AND scope = $3
+                    ORDER BY rank, user_id
+                    LIMIT $4
+                    "#,
+                )
+                .bind(period.as_str())
+                .bind(period_key)
+                .bind(scope.key())
+                .bind(limit)
+                .fetch_all(&self.db)
+                .await?
+            }
+        };
+
+        Ok(ArchivedStandings {
+            period,
+            period_key: period_key.to_string(),
+            scope: scope.key(),
+            closed_at,
+            entries: self.to_entries(rows, user_id).await?,
+        })
+    }
+
+    /// Reconstruye desde Postgres los sorted sets del periodo actual
+    pub async fn rebuild(&self, period: Period) -> Result<usize> {
+        let period_key = period.key_for(panama_date(Utc::now()));
+
+        // Histórico sin límites: suma los mismos eventos que record_xp, así el
+        // set reconstruido coincide con los incrementos
+        let (start, end) = period.bounds(&period_key).unzip();
+        let rows: Vec<(String, i32, i64)> = sqlx::query_as(
+            r#"
+            WITH events AS (
+                SELECT user_id, xp, city_slug
+                FROM gamification.leaderboard_xp_events
+                WHERE ($1::DATE IS NULL OR occurred_at >= ($1::DATE)::TIMESTAMP AT TIME ZONE 'America/Panama')
+                  AND ($2::DATE IS NULL OR occurred_at < ($2::DATE)::TIMESTAMP AT TIME ZONE 'America/Panama')
+            )
+            SELECT 'global', user_id, SUM(xp)::BIGINT
+            FROM events
+            GROUP BY user_id
+            UNION ALL
+            SELECT 'city:' || city_slug, user_id, SUM(xp)::BIGINT
+            FROM events
+            WHERE city_slug IS NOT NULL
+            GROUP BY city_slug, user_id
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut by_scope: HashMap<String, Vec<(i64, i32)>> = HashMap::new();
+        for (scope, user_id, score) in rows {
+            by_scope.entry(scope).or_default().push((score, user_id));
+        }
+
+        let mut conn = self.redis.get().await?;
+        for (scope, members) in &by_scope {
+            let key = redis_key(period, &period_key, scope);
+            let mut pipe = redis::pipe();
+            pipe.atomic().cmd("DEL").arg(&key).ignore();
+            for chunk in members.chunks(1000) {
+                let mut zadd = redis::cmd("ZADD");
+                zadd.arg(&key);
+                for (score, user_id) in chunk {
+                    zadd.arg(*score).arg(*user_id);
+                }
+                pipe.add_command(zadd).ignore();
+            }
+            if let Some(ttl) = period.ttl_secs() {
+                pipe.cmd("EXPIRE").arg(&key).arg(ttl).ignore();
+            }
+            pipe.query_async::<()>(&mut conn).await?;
+        }
+
+        info!("🔁 Rebuilt {} leaderboard {} ({} scopes)", period.as_str(), period_key, by_scope.len());
+        Ok(by_scope.len())
+    }
+
+    /// Reconstruye los periodos actuales cuyo set global no existe en Redis
+    /// (Redis reiniciado o vaciado). Se llama al arrancar.
+    pub async fn rebuild_missing(&self) -> Result<Vec<Period>> {
+        let today = panama_date(Utc::now());
+        let mut rebuilt = Vec::new();
+        for period in Period::ALL {
+            let key = redis_key(period, &period.key_for(today), &Scope::Global.key());
+            if self.cardinality(&key).await? == 0 {
+                self.rebuild(period).await?;
+                rebuilt.push(period);
+            }
+        }
+        Ok(rebuilt)
+    }
+
+    pub async fn list_friends(&self, user_id: i32) -> Result<Vec<Friend>> {
+        Ok(sqlx::query_as::<_, Friend>(
+            r#"
+            SELECT f.friend_user_id AS user_id,
+                   COALESCE(NULLIF(TRIM(u.name), ''), 'Lümer #' || u.id) AS display_name,
+                   f.created_at
+            FROM gamification.user_friends f
+            JOIN public.dim_users u ON u.id = f.friend_user_id
+            WHERE f.user_id = $1
+            ORDER BY f.created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    /// Agrega un amigo (máximo `MAX_FRIENDS`)
+    pub async fn add_friend(&self, user_id: i32, friend_user_id: i32) -> Result<AddFriendOutcome> {
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM gamification.user_friends WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await?;
+        if count >= MAX_FRIENDS {
+            return Ok(AddFriendOutcome::LimitReached);
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO gamification.user_friends (user_id, friend_user_id)
+            SELECT $1, id FROM public.dim_users WHERE id = $2 AND is_active = true
+            ON CONFLICT (user_id, friend_user_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(friend_user_id)
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            return Ok(AddFriendOutcome::Added);
+        }
+
+        let exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM gamification.user_friends WHERE user_id = $1 AND friend_user_id = $2)",
+        )
+        .bind(user_id)
+        .bind(friend_user_id)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(if exists { AddFriendOutcome::AlreadyFriends } else { AddFriendOutcome::UserNotFound })
+    }
+
+    pub async fn remove_friend(&self, user_id: i32, friend_user_id: i32) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM gamification.user_friends WHERE user_id = $1 AND friend_user_id = $2")
+            .bind(user_id)
+            .bind(friend_user_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+// ============================================================================
+// GLOBAL INSTANCE
+// ============================================================================
+
+use std::sync::OnceLock;
+
+static LEADERBOARD_SERVICE: OnceLock<Arc<LeaderboardService>> = OnceLock::new();
+
+pub fn init_leaderboard_service(db: PgPool, redis: deadpool_redis::Pool) {
+    let service = Arc::new(LeaderboardService::new(db, redis));
+    if LEADERBOARD_SERVICE.set(service).is_err() {
+        warn!("Leaderboard service already initialized");
+    }
+}
+
+pub fn get_leaderboard_service() -> Option<Arc<LeaderboardService>> {
+    LEADERBOARD_SERVICE.get().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn test_period_keys() {
+        let date = d(2026, 10, 18);
+        assert_eq!(Period::Daily.key_for(date), "2026-10-18");
+        assert_eq!(Period::Weekly.key_for(date), "2026-W42");
+        assert_eq!(Period::Monthly.key_for(date), "2026-10");
+        assert_eq!(Period::AllTime.key_for(date), "all");
+    }
+
+    #[test]
+    fn test_iso_week_across_year_boundary() {
+        // 2027-01-01 es viernes: pertenece a la semana 53 de 2026
+        assert_eq!(Period::Weekly.key_for(d(2027, 1, 1)), "2026-W53");
+        let (start, end) = Period::Weekly.bounds("2026-W53").unwrap();
+        assert_eq!(start, d(2026, 12, 28));
+        assert_eq!(end, d(2027, 1, 4));
+    }
+
+    #[test]
+    fn test_bounds_and_previous_keys() {
+        assert_eq!(Period::Monthly.bounds("2026-12"), Some((d(2026, 12, 1), d(2027, 1, 1))));
+        assert_eq!(Period::Daily.previous_key(d(2026, 10, 1)).as_deref(), Some("2026-09-30"));
+        assert_eq!(Period::Weekly.previous_key(d(2026, 10, 19)).as_deref(), Some("2026-W42"));
+        assert_eq!(Period::Monthly.previous_key(d(2026, 1, 5)).as_deref(), Some("2025-12"));
+        assert_eq!(Period::AllTime.previous_key(d(2026, 1, 5)), None);
+        assert_eq!(Period::Weekly.bounds("2026-42"), None);
+    }
+
+    #[test]
+    fn test_ended_keys_since_covers_missed_periods() {
+        let today = d(2026, 10, 19);
+        assert_eq!(
+            Period::Daily.ended_keys_since(d(2026, 10, 16), today),
+            vec!["2026-10-16", "2026-10-17", "2026-10-18"]
+        );
+        assert_eq!(Period::Weekly.ended_keys_since(d(2026, 10, 1), today), vec!["2026-W40", "2026-W41", "2026-W42"]);
+        assert_eq!(Period::Monthly.ended_keys_since(d(2026, 8, 20), today), vec!["2026-08", "2026-09"]);
+        assert!(Period::Daily.ended_keys_since(today, today).is_empty());
+        assert!(Period::AllTime.ended_keys_since(d(2026, 1, 1), today).is_empty());
+    }
+
+    #[test]
+    fn test_city_slug() {
+        assert_eq!(city_slug("  Ciudad de Panamá ").as_deref(), Some("ciudad-de-panama"));
+        assert_eq!(city_slug("David, Chiriquí").as_deref(), Some("david-chiriqui"));
+        assert_eq!(city_slug("Colón").as_deref(), Some("colon"));
+        assert_eq!(city_slug(" -- "), None);
+    }
+
+    #[test]
+    fn test_redis_key_and_neighbor_window() {
+        assert_eq!(redis_key(Period::Weekly, "2026-W42", "city:david"), "lb:weekly:2026-W42:city:david");
+        assert_eq!(neighbor_window(1, 5), (0, 6));
+        assert_eq!(neighbor_window(20, 5), (15, 25));
+    }
+
+    #[test]
+    fn test_panama_date_uses_local_midnight() {
+        // 03:00 UTC del 19 = 22:00 del 18 en Panamá
+        let at = DateTime::parse_from_rfc3339("2026-10-19T03:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(panama_date(at), d(2026, 10, 18));
+    }
+}
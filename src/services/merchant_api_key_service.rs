@@ -0,0 +1,380 @@
+// ============================================================================
+// MERCHANT API KEY SERVICE - Credenciales servidor a servidor para comercios
+// ============================================================================
+//
+// Formato: lum_sk_<prefix>_<secret>
+//   - prefix: 12 caracteres públicos, indexados para el lookup
+//   - secret: 32 caracteres aleatorios
+// Solo se guarda el SHA-256 del key completo (el key tiene suficiente entropía
+// para no necesitar bcrypt, y la verificación es por request).
+// ============================================================================
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::api::merchant::permissions::ApiKeyScope;
+use crate::middleware::auth::MerchantClaims;
+use crate::services::rate_limiter_service::{
+    get_rate_limiter, rate_limit_key_merchant_validations, RateLimitConfig,
+};
+
+pub const API_KEY_PREFIX: &str = "lum_sk_";
+const PUBLIC_PREFIX_LEN: usize = 12;
+const SECRET_LEN: usize = 32;
+
+/// Evitar un UPDATE por request: last_used_at se refresca como mucho una vez por minuto
+const LAST_USED_REFRESH_SECS: i64 = 60;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct MerchantApiKey {
+    pub key_id: Uuid,
+    pub name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub rate_limit_per_minute: Option<i32>,
+    pub created_by_staff_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_ip: Option<String>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ApiKeyLookup {
+    key_id: Uuid,
+    merchant_id: Uuid,
+    merchant_name: String,
+    merchant_active: bool,
+    key_hash: String,
+    scopes: Vec<String>,
+    rate_limit_per_minute: Option<i32>,
+    expires_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiKeyAuthError {
+    #[error("API key inválido")]
+    Invalid,
+    #[error("API key revocado o expirado")]
+    Revoked,
+    #[error("Comercio inactivo")]
+    MerchantInactive,
+    #[error("Límite de solicitudes excedido para este API key")]
+    RateLimited,
+    #[error("Error interno: {0}")]
+    Internal(String),
+}
+
+/// Key recién creado; `api_key` solo se devuelve una vez
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    pub api_key: String,
+    pub key: MerchantApiKey,
+}
+
+// ============================================================================
+// PURE HELPERS
+// ============================================================================
+
+fn random_alphanumeric(len: usize) -> String {
+    use rand::Rng;
+    const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    let mut rng = rand::thread_rng();
+    (0..len)
+        .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+        .collect()
+}
+
+pub fn hash_api_key(key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(key.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Generar un key nuevo. Devuelve (key completo, prefijo público)
+pub fn generate_api_key() -> (String, String) {
+    let prefix = random_alphanumeric(PUBLIC_PREFIX_LEN);
+    let secret = random_alphanumeric(SECRET_LEN);
+    (format!("{}{}_{}", API_KEY_PREFIX, prefix, secret), prefix)
+}
+
+/// true si el valor tiene forma de API key (y no de JWT)
+pub fn looks_like_api_key(token: &str) -> bool {
+    token.starts_with(API_KEY_PREFIX)
+}
+
+/// Extraer el prefijo público de un key bien formado
+pub fn parse_api_key_prefix(key: &str) -> Option<&str> {
+    let rest = key.strip_prefix(API_KEY_PREFIX)?;
+    let (prefix, secret) = rest.split_once('_')?;
+    let well_formed = prefix.len() == PUBLIC_PREFIX_LEN
+        && secret.len() == SECRET_LEN
+        && prefix.chars().chain(secret.chars()).all(|c| c.is_ascii_alphanumeric());
+    well_formed.then_some(prefix)
+}
+
+/// Normalizar y validar scopes solicitados (sin duplicados)
+pub fn normalize_scopes(scopes: &[String]) -> Result<Vec<String>, String> {
+    if scopes.is_empty() {
+        return Err("Debe indicar al menos un scope".to_string());
+    }
+    let mut normalized: Vec<String> = Vec::new();
+    for raw in scopes {
+        let scope = ApiKeyScope::parse(raw).ok_or_else(|| {
+            let valid: Vec<&str> = ApiKeyScope::ALL.iter().map(|s| s.as_str()).collect();
+            format!("Scope inválido: {}. Use: {}", raw, valid.join(", "))
+        })?;
+        let value = scope.as_str().to_string();
+        if !normalized.contains(&value) {
+            normalized.push(value);
+        }
+    }
+    Ok(normalized)
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ============================================================================
+// SERVICE
+// ============================================================================
+
+pub struct MerchantApiKeyService {
+    db: PgPool,
+}
+
+impl MerchantApiKeyService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Autenticar un API key y construir los claims equivalentes para el router de comercios
+    pub async fn authenticate(
+        &self,
+        api_key: &str,
+        client_ip: Option<&str>,
+    ) -> Result<MerchantClaims, ApiKeyAuthError> {
+        let prefix = parse_api_key_prefix(api_key).ok_or(ApiKeyAuthError::Invalid)?;
+
+        let row = sqlx::query_as::<_, ApiKeyLookup>(
+            r#"
+            SELECT
+                k.key_id, k.merchant_id, m.merchant_name,
+                COALESCE(m.is_active, true) as merchant_active,
+                k.key_hash, k.scopes, k.rate_limit_per_minute,
+                k.expires_at, k.revoked_at, k.last_used_at
+            FROM rewards.merchant_api_keys k
+            JOIN rewards.merchants m ON m.merchant_id = k.merchant_id
+            WHERE k.key_prefix = $1
+            "#,
+        )
+        .bind(prefix)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| ApiKeyAuthError::Internal(e.to_string()))?
+        .ok_or(ApiKeyAuthError::Invalid)?;
+
+        if !constant_time_eq(&hash_api_key(api_key), &row.key_hash) {
+            return Err(ApiKeyAuthError::Invalid);
+        }
+        let now = Utc::now();
+        if row.revoked_at.is_some() || row.expires_at.map_or(false, |exp| exp <= now) {
+            return Err(ApiKeyAuthError::Revoked);
+        }
+        if !row.merchant_active {
+            return Err(ApiKeyAuthError::MerchantInactive);
+        }
+
+        // Límite por key (si Redis falla se permite la request, como el resto de límites)
+        if let Some(limiter) = get_rate_limiter() {
+            let config = row
+                .rate_limit_per_minute
+                .map(|max| RateLimitConfig { max_requests: max as u32, window_secs: 60 })
+                .unwrap_or(RateLimitConfig::VALIDATIONS_PER_MINUTE_MERCHANT);
+            let key = rate_limit_key_merchant_validations(&format!("apikey:{}", row.key_id));
+            match limiter.check_rate_limit(&key, config).await {
+                Ok(false) => return Err(ApiKeyAuthError::RateLimited),
+                Ok(true) => {}
+                Err(e) => warn!("API key rate limit check failed: {}", e),
+            }
+        }
+
+        let stale = row
+            .last_used_at
+            .map_or(true, |t| (now - t).num_seconds() >= LAST_USED_REFRESH_SECS);
+        if stale {
+            if let Err(e) = sqlx::query(
+                "UPDATE rewards.merchant_api_keys SET last_used_at = NOW(), last_used_ip = $2 WHERE key_id = $1",
+            )
+            .bind(row.key_id)
+            .bind(client_ip)
+            .execute(&self.db)
+            .await
+            {
+                warn!("Failed to update API key last_used_at: {}", e);
+            }
+        }
+
+        Ok(MerchantClaims {
+            sub: row.merchant_id.to_string(),
+            merchant_name: row.merchant_name,
+            role: "merchant".to_string(),
+            exp: 0,
+            iat: now.timestamp(),
+            merchant_id: Some(row.merchant_id),
+            staff_id: None,
+            staff_role: None,
+            branch_id: None,
+            api_key_id: Some(row.key_id),
+            api_key_scopes: Some(row.scopes),
+        })
+    }
+
+    pub async fn create_key(
+        &self,
+        merchant_id: Uuid,
+        name: &str,
+        scopes: &[String],
+        rate_limit_per_minute: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+        created_by_staff_id: Option<Uuid>,
+    ) -> Result<CreatedApiKey> {
+        let (api_key, prefix) = generate_api_key();
+
+        let key = sqlx::query_as::<_, MerchantApiKey>(
+            r#"
+            INSERT INTO rewards.merchant_api_keys
+                (merchant_id, name, key_prefix, key_hash, scopes,
+                 rate_limit_per_minute, expires_at, created_by_staff_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING key_id, name, key_prefix, scopes, rate_limit_per_minute,
+                      created_by_staff_id, created_at, expires_at, last_used_at,
+                      last_used_ip, revoked_at
+            "#,
+        )
+        .bind(merchant_id)
+        .bind(name)
+        .bind(&prefix)
+        .bind(hash_api_key(&api_key))
+        .bind(scopes)
+        .bind(rate_limit_per_minute)
+        .bind(expires_at)
+        .bind(created_by_staff_id)
+        .fetch_one(&self.db)
+        .await?;
+
+        info!("API key {} ({}) created for merchant {}", key.key_id, prefix, merchant_id);
+        Ok(CreatedApiKey { api_key, key })
+    }
+
+    pub async fn list_keys(&self, merchant_id: Uuid) -> Result<Vec<MerchantApiKey>> {
+        let keys = sqlx::query_as::<_, MerchantApiKey>(
+            r#"
+            SELECT key_id, name, key_prefix, scopes, rate_limit_per_minute,
+                   created_by_staff_id, created_at, expires_at, last_used_at,
+                   last_used_ip, revoked_at
+            FROM rewards.merchant_api_keys
+            WHERE merchant_id = $1
+            ORDER BY revoked_at IS NOT NULL, created_at DESC
+            "#,
+        )
+        .bind(merchant_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(keys)
+    }
+
+    /// Revocar un key del comercio. Devuelve false si no existe o ya estaba revocado
+    pub async fn revoke_key(
+        &self,
+        merchant_id: Uuid,
+        key_id: Uuid,
+        revoked_by_staff_id: Option<Uuid>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE rewards.merchant_api_keys
+            SET revoked_at = NOW(), revoked_by_staff_id = $3
+            WHERE key_id = $1 AND merchant_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(key_id)
+        .bind(merchant_id)
+        .bind(revoked_by_staff_id)
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+// ============================================================================
+// SHARED INSTANCE
+// ============================================================================
+
+use std::sync::OnceLock;
+
+static MERCHANT_API_KEY_SERVICE: OnceLock<Arc<MerchantApiKeyService>> = OnceLock::new();
+
+pub fn init_merchant_api_key_service(db: PgPool) {
+    let service = Arc::new(MerchantApiKeyService::new(db));
+    if MERCHANT_API_KEY_SERVICE.set(service).is_err() {
+        warn!("Merchant API key service already initialized");
+    }
+}
+
+pub fn get_merchant_api_key_service() -> Option<Arc<MerchantApiKeyService>> {
+    MERCHANT_API_KEY_SERVICE.get().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_key_roundtrip() {
+        let (key, prefix) = generate_api_key();
+        assert!(looks_like_api_key(&key));
+        assert_eq!(parse_api_key_prefix(&key), Some(prefix.as_str()));
+        assert_eq!(hash_api_key(&key).len(), 64);
+        assert_ne!(generate_api_key().0, key);
+    }
+
+    #[test]
+    fn test_malformed_keys_are_rejected() {
+        assert_eq!(parse_api_key_prefix("eyJhbGciOiJIUzI1NiJ9.x.y"), None);
+        assert_eq!(parse_api_key_prefix("lum_sk_short_secret"), None);
+        assert_eq!(parse_api_key_prefix("lum_mk_abcdefghijklmnopqrstuvwxyz012345"), None);
+        let (key, _) = generate_api_key();
+        assert_eq!(parse_api_key_prefix(&format!("{}!", key)), None);
+    }
+
+    #[test]
+    fn test_normalize_scopes() {
+        let scopes = vec!["Validate".to_string(), "confirm".to_string(), "validate".to_string()];
+        assert_eq!(normalize_scopes(&scopes).unwrap(), vec!["validate", "confirm"]);
+        assert!(normalize_scopes(&[]).is_err());
+        assert!(normalize_scopes(&["admin".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq("abc", "abc"));
+        assert!(!constant_time_eq("abc", "abd"));
+        assert!(!constant_time_eq("abc", "abcd"));
+    }
+}
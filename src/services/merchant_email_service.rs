@@ -13,6 +13,11 @@ use sqlx::PgPool;
 use tracing::{error, info};
 use uuid::Uuid;
 
+use crate::shared::i18n::{self, Locale};
+
+const SUPPORT_EMAIL: &str = "soporte@lumapp.org";
+const PORTAL_URL: &str = "https://comercios.lumapp.org";
+
 pub struct MerchantEmailService {
     smtp_server: String,
     smtp_username: String,
@@ -42,14 +47,15 @@ impl MerchantEmailService {
         info!("📧 Generating weekly report for merchant: {}", merchant_name);
 
         // Obtener estadísticas de la semana
-        let stats = self.get_weekly_stats(merchant_id, pool).await?;
+        let locale = i18n::merchant_locale(pool, merchant_id).await;
+        let stats = self.get_weekly_stats(merchant_id, locale, pool).await?;
 
-        let html_body = self.generate_html_report(merchant_name, &stats);
-        let plain_body = self.generate_plain_report(merchant_name, &stats);
+        let html_body = self.generate_html_report(merchant_name, &stats, locale);
+        let plain_body = self.generate_plain_report(merchant_name, &stats, locale);
 
         self.send_email(
             merchant_email,
-            &format!("📊 Reporte Semanal - {}", merchant_name),
+            &i18n::render(locale, "email.weekly_report.subject", &[("merchant", &merchant_name)]),
             &html_body,
             &plain_body,
         )
@@ -57,7 +63,7 @@ impl MerchantEmailService {
     }
 
     /// Obtener estadísticas de la semana pasada
-    async fn get_weekly_stats(&self, merchant_id: Uuid, pool: &PgPool) -> Result<WeeklyStats> {
+    async fn get_weekly_stats(&self, merchant_id: Uuid, locale: Locale, pool: &PgPool) -> Result<WeeklyStats> {
         let now = Utc::now();
         let week_ago = now - Duration::days(7);
 
@@ -120,15 +126,19 @@ impl MerchantEmailService {
                     redemptions: r.redemptions.unwrap_or(0),
                 })
                 .collect(),
-            week_start: week_ago.format("%d/%m/%Y").to_string(),
-            week_end: now.format("%d/%m/%Y").to_string(),
+            week_start: week_ago.format(locale.date_format()).to_string(),
+            week_end: now.format(locale.date_format()).to_string(),
         })
     }
 
     /// Generar HTML del reporte
-    fn generate_html_report(&self, merchant_name: &str, stats: &WeeklyStats) -> String {
+    fn generate_html_report(&self, merchant_name: &str, stats: &WeeklyStats, locale: Locale) -> String {
+        let support_link = format!(
+            r#"<a href="mailto:{0}" style="color: #6B46C1;">{0}</a>"#,
+            SUPPORT_EMAIL
+        );
         let top_offers_html = if stats.top_offers.is_empty() {
-            "<p style='color: #666;'>No hubo redenciones esta semana.</p>".to_string()
+            format!("<p style='color: #666;'>{}</p>", i18n::t(locale, "email.weekly_report.no_redemptions"))
         } else {
             stats
                 .top_offers
@@ -138,11 +148,11 @@ impl MerchantEmailService {
                     format!(
                         r#"<div style="padding: 10px; background: #f8f9fa; margin: 5px 0; border-radius: 5px;">
                             <span style="font-weight: bold; color: #6B46C1;">#{}</span> {} 
-                            <span style="float: right; color: #666;">{} canjes</span>
+                            <span style="float: right; color: #666;">{}</span>
                         </div>"#,
                         i + 1,
                         offer.name,
-                        offer.redemptions
+                        i18n::render_count(locale, "email.weekly_report.redemptions", offer.redemptions, &[])
                     )
                 })
                 .collect::<Vec<_>>()
@@ -152,7 +162,7 @@ impl MerchantEmailService {
         format!(
             r#"
 <!DOCTYPE html>
-<html>
+<html lang="{}">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
@@ -161,7 +171,7 @@ impl MerchantEmailService {
     <div style="background: white; padding: 30px; border-radius: 10px; box-shadow: 0 2px 10px rgba(0,0,0,0.1);">
         <!-- Header -->
         <div style="text-align: center; margin-bottom: 30px;">
-            <h1 style="color: #6B46C1; margin: 0;">📊 Reporte Semanal</h1>
+            <h1 style="color: #6B46C1; margin: 0;">📊 {}</h1>
             <p style="color: #666; margin: 10px 0 0 0;">{} - {}</p>
             <h2 style="color: #333; margin: 10px 0 0 0;">{}</h2>
         </div>
@@ -170,90 +180,115 @@ impl MerchantEmailService {
         <div style="display: grid; grid-template-columns: 1fr 1fr; gap: 15px; margin-bottom: 30px;">
             <div style="background: linear-gradient(135deg, #667eea 0%, #764ba2 100%); padding: 20px; border-radius: 8px; text-align: center; color: white;">
                 <div style="font-size: 32px; font-weight: bold;">{}</div>
-                <div style="font-size: 14px; opacity: 0.9;">Redenciones Totales</div>
+                <div style="font-size: 14px; opacity: 0.9;">{}</div>
             </div>
             <div style="background: linear-gradient(135deg, #f093fb 0%, #f5576c 100%); padding: 20px; border-radius: 8px; text-align: center; color: white;">
                 <div style="font-size: 32px; font-weight: bold;">{}</div>
-                <div style="font-size: 14px; opacity: 0.9;">Confirmadas</div>
+                <div style="font-size: 14px; opacity: 0.9;">{}</div>
             </div>
             <div style="background: linear-gradient(135deg, #4facfe 0%, #00f2fe 100%); padding: 20px; border-radius: 8px; text-align: center; color: white;">
                 <div style="font-size: 32px; font-weight: bold;">{}</div>
-                <div style="font-size: 14px; opacity: 0.9;">Pendientes</div>
+                <div style="font-size: 14px; opacity: 0.9;">{}</div>
             </div>
             <div style="background: linear-gradient(135deg, #43e97b 0%, #38f9d7 100%); padding: 20px; border-radius: 8px; text-align: center; color: white;">
                 <div style="font-size: 32px; font-weight: bold;">{}</div>
-                <div style="font-size: 14px; opacity: 0.9;">Lümis Generados</div>
+                <div style="font-size: 14px; opacity: 0.9;">{}</div>
             </div>
         </div>
 
         <!-- Top Offers -->
         <div style="margin-bottom: 30px;">
-            <h3 style="color: #333; margin-bottom: 15px;">🏆 Ofertas Más Populares</h3>
+            <h3 style="color: #333; margin-bottom: 15px;">🏆 {}</h3>
             {}
         </div>
 
         <!-- Footer -->
         <div style="text-align: center; padding-top: 20px; border-top: 1px solid #eee; color: #666; font-size: 14px;">
-            <p>¿Necesitas ayuda? Contáctanos en <a href="mailto:soporte@lumapp.org" style="color: #6B46C1;">soporte@lumapp.org</a></p>
+            <p>{}</p>
             <p style="margin-top: 10px;">
-                <a href="https://comercios.lumapp.org" style="color: #6B46C1; text-decoration: none;">Visitar Portal de Comercios →</a>
+                <a href="{}" style="color: #6B46C1; text-decoration: none;">{}</a>
             </p>
         </div>
     </div>
 </body>
 </html>
             "#,
+            locale.as_str(),
+            i18n::t(locale, "email.weekly_report.title"),
             stats.week_start,
             stats.week_end,
             merchant_name,
             stats.total_redemptions,
+            i18n::t(locale, "email.weekly_report.total"),
             stats.confirmed,
+            i18n::t(locale, "email.weekly_report.confirmed"),
             stats.pending,
+            i18n::t(locale, "email.weekly_report.pending"),
             stats.total_lumis,
-            top_offers_html
+            i18n::t(locale, "email.weekly_report.lumis"),
+            i18n::t(locale, "email.weekly_report.top_offers"),
+            top_offers_html,
+            i18n::render(locale, "email.footer.help", &[("email", &support_link)]),
+            PORTAL_URL,
+            i18n::t(locale, "email.weekly_report.portal")
         )
     }
 
     /// Generar versión plain text del reporte
-    fn generate_plain_report(&self, merchant_name: &str, stats: &WeeklyStats) -> String {
+    fn generate_plain_report(&self, merchant_name: &str, stats: &WeeklyStats, locale: Locale) -> String {
         let top_offers_text = if stats.top_offers.is_empty() {
-            "No hubo redenciones esta semana.".to_string()
+            i18n::t(locale, "email.weekly_report.no_redemptions")
         } else {
             stats
                 .top_offers
                 .iter()
                 .enumerate()
-                .map(|(i, offer)| format!("  {}. {} ({} canjes)", i + 1, offer.name, offer.redemptions))
+                .map(|(i, offer)| {
+                    let count = i18n::render_count(locale, "email.weekly_report.redemptions", offer.redemptions, &[]);
+                    format!("  {}. {} ({})", i + 1, offer.name, count)
+                })
                 .collect::<Vec<_>>()
                 .join("\n")
         };
 
         format!(
             r#"
-REPORTE SEMANAL - {}
-Período: {} - {}
+{} - {}
+{}
 
-ESTADÍSTICAS:
-- Redenciones Totales: {}
-- Confirmadas: {}
-- Pendientes: {}
-- Lümis Generados: {}
+{}:
+- {}: {}
+- {}: {}
+- {}: {}
+- {}: {}
 
-OFERTAS MÁS POPULARES:
+{}:
 {}
 
 ---
-¿Necesitas ayuda? Contáctanos en soporte@lumapp.org
-Visita: https://comercios.lumapp.org
+{}
+{}
             "#,
+            i18n::t(locale, "email.weekly_report.title").to_uppercase(),
             merchant_name,
-            stats.week_start,
-            stats.week_end,
+            i18n::render(
+                locale,
+                "email.weekly_report.period",
+                &[("start", &stats.week_start), ("end", &stats.week_end)]
+            ),
+            i18n::t(locale, "email.weekly_report.stats").to_uppercase(),
+            i18n::t(locale, "email.weekly_report.total"),
             stats.total_redemptions,
+            i18n::t(locale, "email.weekly_report.confirmed"),
             stats.confirmed,
+            i18n::t(locale, "email.weekly_report.pending"),
             stats.pending,
+            i18n::t(locale, "email.weekly_report.lumis"),
             stats.total_lumis,
-            top_offers_text
+            i18n::t(locale, "email.weekly_report.top_offers").to_uppercase(),
+            top_offers_text,
+            i18n::render(locale, "email.footer.help", &[("email", &SUPPORT_EMAIL)]),
+            i18n::render(locale, "email.weekly_report.visit", &[("url", &PORTAL_URL)])
         )
     }
 
@@ -0,0 +1,177 @@
+// ============================================================================
+// MERCHANT SESSION SERVICE - Revalidación de sesiones del personal de comercios
+// ============================================================================
+//
+// Los JWT del personal duran hasta 8h y llevan rol y sucursal. En cada request
+// se relee la fila de rewards.merchant_users (lookup por PK) para que desactivar
+// a un empleado, cambiarle el rol o moverlo de sucursal tenga efecto inmediato:
+// si algo cambió, el token se rechaza y el empleado debe volver a iniciar sesión.
+// ============================================================================
+
+use sqlx::PgPool;
+use std::sync::{Arc, OnceLock};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::middleware::auth::MerchantClaims;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StaffSessionRow {
+    pub merchant_id: Uuid,
+    pub role: String,
+    pub branch_id: Option<Uuid>,
+    pub is_active: bool,
+    pub merchant_active: bool,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum StaffSessionError {
+    #[error("La cuenta del empleado está desactivada")]
+    Inactive,
+    #[error("Los permisos del empleado cambiaron; inicia sesión de nuevo")]
+    PermissionsChanged,
+    #[error("Error interno: {0}")]
+    Internal(String),
+}
+
+/// Compara el token con el estado actual del empleado
+pub fn check_staff_claims(claims: &MerchantClaims, row: Option<&StaffSessionRow>) -> Result<(), StaffSessionError> {
+    let Some(row) = row else {
+        return Err(StaffSessionError::Inactive);
+    };
+    if !row.is_active || !row.merchant_active {
+        return Err(StaffSessionError::Inactive);
+    }
+    if claims.get_merchant_id() != Some(row.merchant_id)
+        || claims.staff_role.as_deref() != Some(row.role.as_str())
+        || claims.branch_id != row.branch_id
+    {
+        return Err(StaffSessionError::PermissionsChanged);
+    }
+    Ok(())
+}
+
+pub struct MerchantSessionService {
+    db: PgPool,
+}
+
+impl MerchantSessionService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Tokens sin staff_id (login a nivel comercio, API keys) no se revalidan aquí
+    pub async fn verify(&self, claims: &MerchantClaims) -> Result<(), StaffSessionError> {
+        let Some(staff_id) = claims.staff_id else {
+            return Ok(());
+        };
+
+        let row = sqlx::query_as::<_, StaffSessionRow>(
+            r#"
+            SELECT u.merchant_id, u.role, u.branch_id, u.is_active,
+                   COALESCE(m.is_active, true) AS merchant_active
+            FROM rewards.merchant_users u
+            JOIN rewards.merchants m ON m.merchant_id = u.merchant_id
+            WHERE u.staff_id = $1
+            "#,
+        )
+        .bind(staff_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| StaffSessionError::Internal(e.to_string()))?;
+
+        let result = check_staff_claims(claims, row.as_ref());
+        if let Err(ref e) = result {
+            warn!("Staff session {} rejected: {}", staff_id, e);
+        }
+        result
+    }
+}
+
+// ============================================================================
+// SHARED INSTANCE
+// ============================================================================
+
+static MERCHANT_SESSION_SERVICE: OnceLock<Arc<MerchantSessionService>> = OnceLock::new();
+
+pub fn init_merchant_session_service(db: PgPool) {
+    let service = Arc::new(MerchantSessionService::new(db));
+    if MERCHANT_SESSION_SERVICE.set(service).is_err() {
+        warn!("Merchant session service already initialized");
+    }
+}
+
+pub fn get_merchant_session_service() -> Option<Arc<MerchantSessionService>> {
+    MERCHANT_SESSION_SERVICE.get().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(role: &str, branch_id: Option<Uuid>, merchant_id: Uuid) -> MerchantClaims {
+        MerchantClaims {
+            sub: merchant_id.to_string(),
+            merchant_name: "Café Central".to_string(),
+            role: "merchant".to_string(),
+            exp: 0,
+            iat: 0,
+            merchant_id: Some(merchant_id),
+            staff_id: Some(Uuid::new_v4()),
+            staff_role: Some(role.to_string()),
+            branch_id,
+            api_key_id: None,
+            api_key_scopes: None,
+        }
+    }
+
+    fn row(role: &str, branch_id: Option<Uuid>, merchant_id: Uuid) -> StaffSessionRow {
+        StaffSessionRow {
+            merchant_id,
+            role: role.to_string(),
+            branch_id,
+            is_active: true,
+            merchant_active: true,
+        }
+    }
+
+    #[test]
+    fn test_unchanged_staff_is_accepted() {
+        let merchant = Uuid::new_v4();
+        let branch = Some(Uuid::new_v4());
+        assert_eq!(check_staff_claims(&claims("cashier", branch, merchant), Some(&row("cashier", branch, merchant))), Ok(()));
+    }
+
+    #[test]
+    fn test_deactivated_or_deleted_staff_is_rejected() {
+        let merchant = Uuid::new_v4();
+        let token = claims("manager", None, merchant);
+        let mut inactive = row("manager", None, merchant);
+        inactive.is_active = false;
+        assert_eq!(check_staff_claims(&token, Some(&inactive)), Err(StaffSessionError::Inactive));
+
+        let mut merchant_off = row("manager", None, merchant);
+        merchant_off.merchant_active = false;
+        assert_eq!(check_staff_claims(&token, Some(&merchant_off)), Err(StaffSessionError::Inactive));
+
+        assert_eq!(check_staff_claims(&token, None), Err(StaffSessionError::Inactive));
+    }
+
+    #[test]
+    fn test_changed_role_or_branch_is_rejected() {
+        let merchant = Uuid::new_v4();
+        let token = claims("manager", None, merchant);
+        assert_eq!(
+            check_staff_claims(&token, Some(&row("cashier", None, merchant))),
+            Err(StaffSessionError::PermissionsChanged)
+        );
+        assert_eq!(
+            check_staff_claims(&token, Some(&row("manager", Some(Uuid::new_v4()), merchant))),
+            Err(StaffSessionError::PermissionsChanged)
+        );
+        assert_eq!(
+            check_staff_claims(&token, Some(&row("manager", None, Uuid::new_v4()))),
+            Err(StaffSessionError::PermissionsChanged)
+        );
+    }
+}
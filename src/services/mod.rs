@@ -42,10 +42,19 @@ pub mod webhook_service;
 pub mod rate_limiter_service;
 pub mod scheduled_jobs_service;
 pub mod merchant_email_service;
+pub mod merchant_api_key_service;
+pub mod merchant_session_service;
+pub mod leaderboard_service;
+pub mod event_bus_service;
+pub mod event_subscribers;
 
 // Re-export new services
 pub use push_notification_service::{PushNotificationService, init_push_service, get_push_service, start_push_queue_worker, QueueProcessResult};
-pub use webhook_service::{WebhookService, init_webhook_service, get_webhook_service};
+pub use webhook_service::{WebhookService, WebhookEvent, init_webhook_service, get_webhook_service, start_webhook_dispatcher};
 pub use rate_limiter_service::{RateLimiter, RateLimitConfig, init_rate_limiter, get_rate_limiter};
 pub use scheduled_jobs_service::{ScheduledJobsService, init_scheduled_jobs, get_scheduled_jobs};
 pub use merchant_email_service::{send_weekly_reports_task};
+pub use merchant_api_key_service::{MerchantApiKeyService, init_merchant_api_key_service, get_merchant_api_key_service};
+pub use merchant_session_service::{MerchantSessionService, init_merchant_session_service, get_merchant_session_service};
+pub use leaderboard_service::{LeaderboardService, init_leaderboard_service, get_leaderboard_service};
+pub use event_bus_service::{DomainEvent, EventBus, init_event_bus, get_event_bus, start_event_dispatcher};
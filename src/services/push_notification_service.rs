@@ -11,17 +11,21 @@
 // - GOOGLE_APPLICATION_CREDENTIALS: Path to service account JSON file
 // - FIREBASE_PROJECT_ID: Your Firebase project ID
 //
+// Every push (direct or from the queue) goes through the user's preferences
+// and quiet hours (domains::notifications::preferences).
+//
 // ============================================================================
 
 use anyhow::{Context, Result};
 use chrono::{Duration, Utc};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
 use sqlx::PgPool;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
+use crate::domains::notifications::preferences::{Delivery, PreferenceService};
+use crate::domains::notifications::campaigns::CAMPAIGN_NOTIFICATION_TYPE;
 use crate::observability::metrics::{record_push_notification, record_notification_queue_processed};
 
 // ============================================================================
@@ -253,13 +257,61 @@ impl PushNotificationService {
         self.is_configured
     }
 
-    /// Send push notification to a user
+    /// Send push notification to a user, honoring their preferences and quiet hours
     pub async fn send_notification(&self, notification: PushNotification) -> Result<()> {
         if !self.is_configured {
             warn!("FCM not configured, skipping notification");
             return Ok(());
         }
 
+        match self.delivery_for(&notification).await {
+            Delivery::Now => self.deliver(notification).await,
+            Delivery::Defer(until) => self.defer(&notification, until).await,
+            Delivery::Suppress(reason) => {
+                info!("Push to user {} suppressed: {}", notification.user_id, reason);
+                Ok(())
+            }
+        }
+    }
+
+    /// Central preferences policy for a direct push (fails open)
+    async fn delivery_for(&self, notification: &PushNotification) -> Delivery {
+        let notification_type = notification.data.get("type").and_then(|t| t.as_str()).unwrap_or("system");
+        let priority = match notification.priority {
+            NotificationPriority::High => "high",
+            NotificationPriority::Normal => "normal",
+        };
+
+        PreferenceService::new(self.db.clone())
+            .decide(notification.user_id as i64, notification_type, priority, Utc::now())
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Notification preferences check failed for user {}: {}", notification.user_id, e);
+                Delivery::Now
+            })
+    }
+
+    /// Keep a push until the user's quiet hours end
+    async fn defer(&self, notification: &PushNotification, until: chrono::DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO public.deferred_push_notifications (user_id, notification, deliver_after)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(notification.user_id)
+        .bind(serde_json::to_value(notification)?)
+        .bind(until)
+        .execute(&self.db)
+        .await
+        .context("Failed to defer push notification")?;
+
+        info!("Push to user {} deferred until {} (quiet hours)", notification.user_id, until);
+        Ok(())
+    }
+
+    /// Send a push right away (no preferences check)
+    async fn deliver(&self, notification: PushNotification) -> Result<()> {
         // Get FCM token for user
         let fcm_token = self.get_user_fcm_token(notification.user_id).await?;
 
@@ -464,105 +516,6 @@ impl PushNotificationService {
 
         Ok(())
     }
-
-    /// Notify when a redemption is confirmed
-    pub async fn notify_redemption_confirmed(
-        &self,
-        user_id: i32,
-        redemption_id: uuid::Uuid,
-        offer_name: &str,
-    ) -> Result<()> {
-        // Personalizar el mensaje según el tipo de oferta
-        let (title, body) = Self::get_confirmation_message(offer_name);
-        
-        let notification = PushNotification {
-            user_id,
-            title,
-            body,
-            data: json!({
-                "type": "redemption_confirmed",
-                "redemption_id": redemption_id.to_string(),
-                "offer_name": offer_name,
-            }),
-            priority: NotificationPriority::High,
-        };
-
-        self.send_notification(notification).await
-    }
-    
-    /// Genera mensajes personalizados según el tipo de oferta
-    fn get_confirmation_message(offer_name: &str) -> (String, String) {
-        let offer_lower = offer_name.to_lowercase();
-        
-        // Detectar categorías para mensajes personalizados
-        if offer_lower.contains("café") || offer_lower.contains("coffee") {
-            ("☕ ¡Disfruta tu café!".to_string(), 
-             "Tu cupón ha sido canjeado exitosamente. ¡Que lo disfrutes!".to_string())
-        } else if offer_lower.contains("comida") || offer_lower.contains("almuerzo") || offer_lower.contains("cena") {
-            ("🍽️ ¡Buen provecho!".to_string(),
-             "Tu cupón ha sido canjeado exitosamente. ¡Disfruta tu comida!".to_string())
-        } else if offer_lower.contains("descuento") || offer_lower.contains("%") {
-            ("🎉 ¡Descuento aplicado!".to_string(),
-             "Tu cupón ha sido canjeado exitosamente. ¡Aprovecha tu descuento!".to_string())
-        } else if offer_lower.contains("gratis") || offer_lower.contains("free") {
-            ("🎁 ¡Es tuyo!".to_string(),
-             format!("¡Disfruta tu {}! Tu cupón ha sido canjeado exitosamente.", offer_name))
-        } else {
-            ("✅ ¡Cupón canjeado!".to_string(),
-             format!("¡Disfruta tu {}! Tu redención fue confirmada exitosamente.", offer_name))
-        }
-    }
-
-    /// Notify when a redemption is about to expire
-    pub async fn notify_redemption_expiring(
-        &self,
-        user_id: i32,
-        redemption_id: uuid::Uuid,
-        offer_name: &str,
-        minutes_remaining: i32,
-    ) -> Result<()> {
-        let notification = PushNotification {
-            user_id,
-            title: "⏰ Tu redención expira pronto".to_string(),
-            body: format!(
-                "{} expira en {} minutos. ¡Úsala antes de que sea tarde!",
-                offer_name, minutes_remaining
-            ),
-            data: json!({
-                "type": "redemption_expiring",
-                "redemption_id": redemption_id.to_string(),
-                "offer_name": offer_name,
-                "minutes_remaining": minutes_remaining,
-            }),
-            priority: NotificationPriority::High,
-        };
-
-        self.send_notification(notification).await
-    }
-
-    /// Notify when a redemption is created
-    pub async fn notify_redemption_created(
-        &self,
-        user_id: i32,
-        redemption_id: uuid::Uuid,
-        offer_name: &str,
-        redemption_code: &str,
-    ) -> Result<()> {
-        let notification = PushNotification {
-            user_id,
-            title: "🎁 Nueva redención creada".to_string(),
-            body: format!("Muestra el código {} al comercio", redemption_code),
-            data: json!({
-                "type": "redemption_created",
-                "redemption_id": redemption_id.to_string(),
-                "offer_name": offer_name,
-                "redemption_code": redemption_code,
-            }),
-            priority: NotificationPriority::Normal,
-        };
-
-        self.send_notification(notification).await
-    }
 }
 
 // ============================================================================
@@ -592,6 +545,9 @@ pub fn get_push_service() -> Option<Arc<PushNotificationService>> {
 const QUEUE_BATCH_SIZE: i64 = 50;
 const MAX_RETRY_ATTEMPTS: i32 = 3;
 const BACKOFF_BASE_SECONDS: i64 = 30;
+/// Lease on a claimed deferred push. If the worker dies mid-batch the rows stay
+/// 'processing' until it expires and the next run reclaims them.
+const DEFERRED_LEASE_SECS: i64 = 600;
 
 /// Result of processing the notification queue
 #[derive(Debug, Default)]
@@ -599,6 +555,8 @@ pub struct QueueProcessResult {
     pub sent: usize,
     pub failed: usize,
     pub skipped: usize,
+    /// Moved to the end of the user's quiet hours
+    pub deferred: usize,
     pub invalid_tokens: usize,
 }
 
@@ -658,7 +616,46 @@ impl PushNotificationService {
         // Process each notification item sequentially to avoid lifetime issues
         // with nested async closures. The actual FCM sends are still concurrent
         // per user via tokio::spawn.
+        let preferences = PreferenceService::new(self.db.clone());
+
         for item in pending_items {
+            // Central preferences policy (also covers notifications created from SQL)
+            match preferences.decide_for_notification(item.notification_id, now).await {
+                Ok(Delivery::Now) => {}
+                Ok(Delivery::Defer(until)) => {
+                    sqlx::query("UPDATE public.notification_push_queue SET next_attempt_at = $2 WHERE id = $1")
+                        .bind(item.id)
+                        .bind(until)
+                        .execute(&self.db)
+                        .await
+                        .ok();
+
+                    final_result.deferred += 1;
+                    continue;
+                }
+                Ok(Delivery::Suppress(reason)) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE public.notification_push_queue
+                        SET status = 'skipped',
+                            last_attempt_at = $2,
+                            error_message = $3
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(item.id)
+                    .bind(now)
+                    .bind(reason)
+                    .execute(&self.db)
+                    .await
+                    .ok();
+
+                    final_result.skipped += 1;
+                    continue;
+                }
+                Err(e) => warn!("Notification preferences check failed for user {}: {}", item.user_id, e),
+            }
+
             let tokens_result = self.get_all_user_tokens(item.user_id).await;
             
             match tokens_result {
@@ -810,6 +807,22 @@ impl PushNotificationService {
                         .execute(&self.db)
                         .await
                         .ok();
+
+                        // Push campaigns keep their own delivery record; queue rows are purged after 7 days
+                        if item_sent > 0 && item.notification_type == CAMPAIGN_NOTIFICATION_TYPE {
+                            sqlx::query(
+                                r#"
+                                UPDATE public.push_campaign_recipients
+                                SET delivered_at = $2
+                                WHERE notification_id = $1 AND delivered_at IS NULL
+                                "#,
+                            )
+                            .bind(item.notification_id)
+                            .bind(now)
+                            .execute(&self.db)
+                            .await
+                            .ok();
+                        }
                     } else {
                         // Schedule retry with exponential backoff
                         let backoff_seconds = BACKOFF_BASE_SECONDS * 2_i64.pow(item.attempts as u32);
@@ -869,6 +882,9 @@ impl PushNotificationService {
                 elapsed, final_result.sent, final_result.failed, final_result.skipped, final_result.invalid_tokens
             );
         }
+        if final_result.deferred > 0 {
+            info!("Deferred {} push notifications to the end of quiet hours", final_result.deferred);
+        }
 
         Ok(final_result)
     }
@@ -913,6 +929,73 @@ impl PushNotificationService {
     }
 }
 
+impl PushNotificationService {
+    /// Send direct pushes whose quiet hours are over. Preferences are checked
+    /// again: the user may have turned the category off in the meantime.
+    pub async fn process_deferred_pushes(&self) -> Result<usize> {
+        if !self.is_configured {
+            return Ok(0);
+        }
+
+        let due: Vec<(i64, serde_json::Value)> = sqlx::query_as(
+            r#"
+            UPDATE public.deferred_push_notifications
+            SET status = 'processing',
+                locked_until = NOW() + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT id FROM public.deferred_push_notifications
+                WHERE (status = 'pending' AND deliver_after <= NOW())
+                   OR (status = 'processing' AND locked_until < NOW())
+                ORDER BY deliver_after
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, notification
+            "#,
+        )
+        .bind(QUEUE_BATCH_SIZE)
+        .bind(DEFERRED_LEASE_SECS as f64)
+        .fetch_all(&self.db)
+        .await?;
+
+        let processed = due.len();
+        for (id, raw) in due {
+            let (status, deliver_after, error_message) = match serde_json::from_value::<PushNotification>(raw) {
+                Err(e) => ("failed", None, Some(e.to_string())),
+                Ok(notification) => match self.delivery_for(&notification).await {
+                    Delivery::Now => match self.deliver(notification).await {
+                        Ok(()) => ("sent", None, None),
+                        Err(e) => ("failed", None, Some(e.to_string())),
+                    },
+                    Delivery::Defer(until) => ("pending", Some(until), None),
+                    Delivery::Suppress(reason) => ("skipped", None, Some(reason.to_string())),
+                },
+            };
+
+            sqlx::query(
+                r#"
+                UPDATE public.deferred_push_notifications
+                SET status = $2,
+                    deliver_after = COALESCE($3, deliver_after),
+                    error_message = $4,
+                    processed_at = CASE WHEN $2 = 'pending' THEN NULL ELSE NOW() END,
+                    locked_until = NULL
+                WHERE id = $1
+                "#,
+            )
+            .bind(id)
+            .bind(status)
+            .bind(deliver_after)
+            .bind(error_message)
+            .execute(&self.db)
+            .await
+            .ok();
+        }
+
+        Ok(processed)
+    }
+}
+
 // ============================================================================
 // BACKGROUND WORKER
 // ============================================================================
@@ -969,6 +1052,12 @@ pub async fn start_push_queue_worker(db: PgPool) {
             }
         }
 
+        match service.process_deferred_pushes().await {
+            Ok(0) => {}
+            Ok(count) => info!("Push worker: processed {} deferred pushes", count),
+            Err(e) => error!("Push worker deferred pushes error: {}", e),
+        }
+
         tokio::time::sleep(std::time::Duration::from_secs(WORKER_POLL_INTERVAL_SECS)).await;
     }
 }
@@ -7,7 +7,11 @@ use sqlx::PgPool;
 use std::sync::Arc;
 use tokio_cron_scheduler::{Job, JobScheduler};
 use tracing::{error, info};
+use crate::domains::lumimatch::TagService;
+use crate::domains::notifications::CampaignService;
+use crate::domains::surveys::QuotaService;
 use crate::observability::metrics::record_redemption_expired;
+use crate::services::event_bus_service::{get_event_bus, DomainEvent, EventBus, PROCESSED_RETENTION_DAYS};
 
 pub struct ScheduledJobsService {
     scheduler: JobScheduler,
@@ -43,6 +47,21 @@ impl ScheduledJobsService {
         // Job 5: Enviar reportes semanales a comercios (domingos a las 9 AM)
         self.add_weekly_merchant_reports_job().await?;
 
+        // Job 6: Cerrar y archivar leaderboards (00:05 hora de Panamá)
+        self.add_close_leaderboards_job().await?;
+
+        // Job 7: Purgar eventos de dominio ya procesados (cada día a las 3:30 AM)
+        self.add_purge_domain_events_job().await?;
+
+        // Job 8: Liberar cupos de encuestas con reserva vencida (cada 5 minutos)
+        self.add_release_survey_quotas_job().await?;
+
+        // Job 9: Purgar evidencia vencida de tags derivados (diario 4:30 AM)
+        self.add_purge_user_tags_job().await?;
+
+        // Job 10: Enviar campañas de push programadas (cada minuto)
+        self.add_push_campaigns_job().await?;
+
         // Iniciar el scheduler
         self.scheduler.start().await?;
 
@@ -181,6 +200,109 @@ impl ScheduledJobsService {
         Ok(())
     }
 
+    /// Job 6: Archivar posiciones finales de los periodos que terminaron
+    async fn add_close_leaderboards_job(&self) -> Result<()> {
+        // 05:05 UTC = 00:05 en Panamá (UTC-5, sin horario de verano)
+        let job = Job::new_async("0 5 5 * * *", move |_uuid, _l| {
+            Box::pin(async move {
+                info!("🏁 Running close_leaderboards job...");
+
+                let Some(service) = crate::services::get_leaderboard_service() else {
+                    error!("Leaderboard service not initialized, skipping close_leaderboards");
+                    return;
+                };
+
+                match service.close_due_periods().await {
+                    Ok(closed) => {
+                        for (period, key, participants) in closed {
+                            info!("Closed {} leaderboard {} ({} participants)", period.as_str(), key, participants);
+                        }
+                    }
+                    Err(e) => error!("Error closing leaderboards: {}", e),
+                }
+            })
+        })?;
+
+        self.scheduler.add(job).await?;
+        info!("Added close_leaderboards job (daily at 00:05 Panama)");
+        Ok(())
+    }
+
+    /// Job 7: Purgar eventos de dominio procesados (>PROCESSED_RETENTION_DAYS días)
+    async fn add_purge_domain_events_job(&self) -> Result<()> {
+        let job = Job::new_async("0 30 3 * * *", move |_uuid, _l| {
+            Box::pin(async move {
+                let Some(bus) = get_event_bus() else {
+                    return;
+                };
+                match bus.purge_processed(PROCESSED_RETENTION_DAYS).await {
+                    Ok(count) => info!("Purged {} processed domain events", count),
+                    Err(e) => error!("Error purging domain events: {}", e),
+                }
+            })
+        })?;
+
+        self.scheduler.add(job).await?;
+        info!("Added purge_domain_events job (daily at 3:30 AM)");
+        Ok(())
+    }
+
+    /// Job 8: Marcar como liberadas las reservas de cuota vencidas
+    async fn add_release_survey_quotas_job(&self) -> Result<()> {
+        let db = self.db.clone();
+        let job = Job::new_async("0 */5 * * * *", move |_uuid, _l| {
+            let db = db.clone();
+            Box::pin(async move {
+                match QuotaService::new(db).release_expired().await {
+                    Ok(0) => {}
+                    Ok(count) => info!("Released {} expired survey quota reservations", count),
+                    Err(e) => error!("Error releasing survey quota reservations: {}", e),
+                }
+            })
+        })?;
+
+        self.scheduler.add(job).await?;
+        info!("Added release_survey_quotas job (every 5 minutes)");
+        Ok(())
+    }
+
+    /// Job 9: Borrar evidencia de tags vencida y recalcular los tags afectados
+    async fn add_purge_user_tags_job(&self) -> Result<()> {
+        let db = self.db.clone();
+        let job = Job::new_async("0 30 4 * * *", move |_uuid, _l| {
+            let db = db.clone();
+            Box::pin(async move {
+                if let Err(e) = TagService::new(db).purge_expired().await {
+                    error!("Error purging expired user tags: {}", e);
+                }
+            })
+        })?;
+
+        self.scheduler.add(job).await?;
+        info!("Added purge_user_tags job (daily at 4:30 AM)");
+        Ok(())
+    }
+
+    /// Job 10: Enviar campañas de push cuya hora llegó y retomar envíos
+    /// interrumpidos
+    async fn add_push_campaigns_job(&self) -> Result<()> {
+        let db = self.db.clone();
+        let job = Job::new_async("0 * * * * *", move |_uuid, _l| {
+            let db = db.clone();
+            Box::pin(async move {
+                match CampaignService::new(db).run_due().await {
+                    Ok(0) => {}
+                    Ok(count) => info!("📣 Started sending {} push campaigns", count),
+                    Err(e) => error!("Error sending push campaigns: {}", e),
+                }
+            })
+        })?;
+
+        self.scheduler.add(job).await?;
+        info!("Added push_campaigns job (every minute)");
+        Ok(())
+    }
+
     /// Detener el scheduler
     pub async fn shutdown(&mut self) -> Result<()> {
         info!("Shutting down scheduled jobs...");
@@ -195,20 +317,39 @@ impl ScheduledJobsService {
 
 /// Expirar redenciones antiguas
 async fn expire_old_redemptions(db: &PgPool) -> Result<u64> {
-    let result = sqlx::query(
+    let mut tx = db.begin().await?;
+
+    let expired: Vec<(uuid::Uuid, String, Option<uuid::Uuid>, String)> = sqlx::query_as(
         r#"
-        UPDATE rewards.user_redemptions
+        UPDATE rewards.user_redemptions ur
         SET 
             redemption_status = 'expired',
             updated_at = NOW()
-        WHERE redemption_status = 'pending'
-          AND code_expires_at < NOW()
+        FROM rewards.redemption_offers ro
+        WHERE ro.offer_id = ur.offer_id
+          AND ur.redemption_status = 'pending'
+          AND ur.code_expires_at < NOW()
+        RETURNING ur.redemption_id, ur.redemption_code, ro.merchant_id,
+                  COALESCE(ro.name_friendly, ro.name) as offer_name
         "#,
     )
-    .execute(db)
+    .fetch_all(&mut *tx)
     .await?;
 
-    Ok(result.rows_affected())
+    // Eventos de dominio en la misma transacción (webhook al merchant vía bus)
+    for (redemption_id, redemption_code, merchant_id, offer_name) in &expired {
+        let event = DomainEvent::RedemptionExpired {
+            redemption_id: *redemption_id,
+            redemption_code: redemption_code.clone(),
+            offer_name: offer_name.clone(),
+            merchant_id: *merchant_id,
+        };
+        EventBus::publish(&mut *tx, &event).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(expired.len() as u64)
 }
 
 /// Limpiar códigos QR antiguos (opcional: si se almacenan imágenes)
@@ -296,7 +437,7 @@ async fn recalculate_merchant_stats(db: &PgPool) -> Result<u64> {
 
 /// Enviar alertas de redenciones próximas a expirar
 async fn send_expiration_alerts(db: &PgPool) -> Result<u64> {
-    use crate::services::push_notification_service::get_push_service;
+    use crate::domains::notifications::{get_notifier, LogicalNotification};
 
     // Obtener redenciones que expiran en los próximos 5 minutos
     let expiring_redemptions = sqlx::query_as::<_, ExpiringRedemption>(
@@ -318,20 +459,17 @@ async fn send_expiration_alerts(db: &PgPool) -> Result<u64> {
 
     let count = expiring_redemptions.len() as u64;
 
-    if let Some(push_service) = get_push_service() {
+    if let Some(notifier) = get_notifier() {
         for redemption in expiring_redemptions {
-            let minutes_remaining = (redemption.code_expires_at - chrono::Utc::now())
-                .num_minutes() as i32;
-
-            if let Err(e) = push_service
-                .notify_redemption_expiring(
-                    redemption.user_id,
-                    redemption.redemption_id,
-                    &redemption.offer_name,
-                    minutes_remaining,
-                )
-                .await
-            {
+            let minutes_remaining = (redemption.code_expires_at - chrono::Utc::now()).num_minutes();
+            let notification = LogicalNotification::redemption_expiring(
+                redemption.user_id as i64,
+                redemption.redemption_id,
+                &redemption.offer_name,
+                minutes_remaining,
+            );
+
+            if let Err(e) = notifier.notify(&notification).await {
                 error!("Failed to send expiration alert: {}", e);
             } else {
                 // Marcar como enviada
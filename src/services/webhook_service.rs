@@ -1,25 +1,58 @@
 // ============================================================================
 // WEBHOOK SERVICE - Notificaciones asíncronas a Merchants
 // ============================================================================
+//
+// Los eventos NO se envían inline: se escriben en rewards.webhook_outbox dentro
+// de la misma transacción que el cambio de la redención (`WebhookService::enqueue`)
+// y un dispatcher en background (`start_webhook_dispatcher`) los entrega con
+// backoff exponencial largo. Tras `max_attempts` fallos la entrega pasa a 'dead'
+// y el merchant puede reenviarla desde la API (replay).
+//
+// FIRMA:
+//   X-Webhook-Timestamp: <unix seconds>
+//   X-Webhook-Signature: t=<unix seconds>,v1=<hex(HMAC-SHA256(secret, "<t>.<body>"))>
+// El merchant debe rechazar timestamps con más de 5 minutos de diferencia.
+// ============================================================================
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use reqwest::Client;
+use reqwest::{redirect, Client};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sqlx::PgPool;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::time::Duration;
 use tracing::{error, info, warn};
 use uuid::Uuid;
-use crate::observability::metrics::record_webhook_sent;
+use crate::observability::metrics::{record_webhook_dead_letter, record_webhook_sent};
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Tolerancia recomendada para verificar X-Webhook-Timestamp
+pub const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+/// Máximo de bytes de la respuesta del merchant que se guardan en el log
+const MAX_RESPONSE_BODY_BYTES: usize = 4096;
+
+/// Eventos a los que un merchant puede suscribirse
+pub const SUPPORTED_EVENTS: [&str; 4] = [
+    "redemption.created",
+    "redemption.confirmed",
+    "redemption.expired",
+    "redemption.cancelled",
+];
+
+/// Evento enviado por el endpoint de prueba (no pasa por el outbox)
+pub const TEST_EVENT: &str = "webhook.test";
+
+/// Cuerpo JSON enviado al merchant
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebhookEvent {
+    /// ID estable del evento (se mantiene en replays para deduplicar)
+    pub id: Uuid,
     pub event: String,
     pub timestamp: DateTime<Utc>,
     pub data: serde_json::Value,
@@ -35,148 +68,353 @@ pub struct MerchantWebhook {
     pub is_active: bool,
 }
 
-pub struct WebhookService {
-    db: PgPool,
-    http_client: Client,
+/// Fila del outbox reclamada por el dispatcher
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutboxDelivery {
+    pub delivery_id: Uuid,
+    pub merchant_id: Uuid,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub max_attempts: i32,
 }
 
-impl WebhookService {
-    pub fn new(db: PgPool) -> Self {
-        let http_client = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .build()
-            .expect("Failed to create HTTP client");
+/// Resultado de un intento HTTP contra el endpoint del merchant
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryOutcome {
+    pub success: bool,
+    pub status_code: Option<u16>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
+
+impl WebhookEvent {
+    fn new(event: &str, merchant_id: Uuid, data: serde_json::Value) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            event: event.to_string(),
+            timestamp: Utc::now(),
+            data,
+            merchant_id,
+        }
+    }
 
-        Self { db, http_client }
+    pub fn test_ping(merchant_id: Uuid) -> Self {
+        Self::new(TEST_EVENT, merchant_id, json!({
+            "message": "Evento de prueba enviado desde el portal de comercios",
+        }))
     }
 
-    /// Enviar webhook a merchant
-    pub async fn send_webhook(
-        &self,
+    pub fn redemption_created(
         merchant_id: Uuid,
-        event: WebhookEvent,
-    ) -> Result<()> {
-        // 1. Obtener configuración de webhook del merchant
-        let webhook = match self.get_merchant_webhook(merchant_id).await? {
-            Some(w) if w.is_active => w,
-            Some(_) => {
-                info!("Webhook disabled for merchant {}", merchant_id);
-                return Ok(());
-            }
-            None => {
-                info!("No webhook configured for merchant {}", merchant_id);
-                return Ok(());
+        redemption_id: Uuid,
+        redemption_code: &str,
+        offer_name: &str,
+        lumis_spent: i32,
+    ) -> Self {
+        Self::new("redemption.created", merchant_id, json!({
+            "redemption_id": redemption_id,
+            "redemption_code": redemption_code,
+            "offer_name": offer_name,
+            "lumis_spent": lumis_spent,
+        }))
+    }
+
+    pub fn redemption_confirmed(
+        merchant_id: Uuid,
+        redemption_id: Uuid,
+        redemption_code: &str,
+        offer_name: &str,
+        confirmed_by: &str,
+    ) -> Self {
+        Self::new("redemption.confirmed", merchant_id, json!({
+            "redemption_id": redemption_id,
+            "redemption_code": redemption_code,
+            "offer_name": offer_name,
+            "confirmed_by": confirmed_by,
+            "confirmed_at": Utc::now(),
+        }))
+    }
+
+    pub fn redemption_expired(
+        merchant_id: Uuid,
+        redemption_id: Uuid,
+        redemption_code: &str,
+        offer_name: &str,
+    ) -> Self {
+        Self::new("redemption.expired", merchant_id, json!({
+            "redemption_id": redemption_id,
+            "redemption_code": redemption_code,
+            "offer_name": offer_name,
+            "expired_at": Utc::now(),
+        }))
+    }
+
+    pub fn redemption_cancelled(
+        merchant_id: Uuid,
+        redemption_id: Uuid,
+        redemption_code: &str,
+        reason: &str,
+    ) -> Self {
+        Self::new("redemption.cancelled", merchant_id, json!({
+            "redemption_id": redemption_id,
+            "redemption_code": redemption_code,
+            "reason": reason,
+            "cancelled_at": Utc::now(),
+        }))
+    }
+}
+
+/// Firma `"<timestamp>.<body>"` con HMAC-SHA256 y devuelve el header completo
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> Result<String> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .context("Invalid HMAC key")?;
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    Ok(format!("t={},v1={}", timestamp, hex::encode(mac.finalize().into_bytes())))
+}
+
+/// Verificación de referencia (la misma que debe implementar el merchant)
+pub fn verify_signature(secret: &str, header: &str, body: &str, now: i64) -> bool {
+    let mut timestamp: Option<i64> = None;
+    let mut signature: Option<&str> = None;
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", v)) => timestamp = v.parse().ok(),
+            Some(("v1", v)) => signature = Some(v),
+            _ => {}
+        }
+    }
+
+    let (Some(timestamp), Some(signature)) = (timestamp, signature) else {
+        return false;
+    };
+    if (now - timestamp).abs() > SIGNATURE_TOLERANCE_SECS {
+        return false;
+    }
+    let Ok(expected) = hex::decode(signature) else {
+        return false;
+    };
+
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Nuevo secreto de firma (`whsec_` + 32 bytes aleatorios en hex)
+pub fn generate_webhook_secret() -> String {
+    use rand::Rng;
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// Validar la URL de webhook configurada por un merchant.
+/// Solo HTTPS y sin destinos internos (localhost, IPs privadas o link-local)
+/// porque el servidor hace POST a esta URL. Un dominio puede resolver a una IP
+/// interna: antes de cada envío se revisa además con `resolve_webhook_target`.
+pub fn validate_webhook_url(raw: &str) -> Result<(), String> {
+    let parsed = url::Url::parse(raw.trim()).map_err(|_| "URL inválida".to_string())?;
+
+    if parsed.scheme() != "https" {
+        return Err("La URL del webhook debe usar https".to_string());
+    }
+
+    match parsed.host() {
+        None => Err("La URL del webhook debe incluir un host".to_string()),
+        Some(url::Host::Domain(domain)) => {
+            let domain = domain.to_lowercase();
+            let internal = domain == "localhost"
+                || domain.ends_with(".localhost")
+                || domain.ends_with(".local")
+                || domain.ends_with(".internal");
+            if internal {
+                Err("La URL del webhook no puede apuntar a un host interno".to_string())
+            } else {
+                Ok(())
             }
-        };
+        }
+        Some(url::Host::Ipv4(ip)) if is_internal_ip(IpAddr::V4(ip)) => {
+            Err("La URL del webhook no puede apuntar a una IP interna".to_string())
+        }
+        Some(url::Host::Ipv6(ip)) if is_internal_ip(IpAddr::V6(ip)) => {
+            Err("La URL del webhook no puede apuntar a una IP interna".to_string())
+        }
+        Some(_) => Ok(()),
+    }
+}
 
-        // 2. Verificar que el merchant está suscrito a este evento
-        if !webhook.events.contains(&event.event) {
-            info!(
-                "Merchant {} not subscribed to event {}",
-                merchant_id, event.event
-            );
-            return Ok(());
+/// IPs que no son destinos públicos: loopback, privadas, link-local (metadata
+/// de la nube), CGNAT 100.64.0.0/10, reservadas y multicast. Las IPv6 que
+/// envuelven una IPv4 (`::ffff:a.b.c.d`, NAT64 `64:ff9b::/96`) se juzgan por la
+/// IPv4 de adentro.
+pub fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (b & 0xc0) == 64)
+                || (a == 192 && b == 0 && ip.octets()[2] == 0)
+                || (a == 198 && (b & 0xfe) == 18)
+                || a >= 240
         }
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_internal_ip(IpAddr::V4(v4));
+            }
+            let segments = ip.segments();
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [a, b] = segments[6].to_be_bytes();
+                let [c, d] = segments[7].to_be_bytes();
+                return is_internal_ip(IpAddr::V4(std::net::Ipv4Addr::new(a, b, c, d)));
+            }
+            let unique_local = (segments[0] & 0xfe00) == 0xfc00;
+            let link_local = (segments[0] & 0xffc0) == 0xfe80;
+            // ::/96 (IPv4-compatible, obsoleto) incluye ::1 y ::
+            let ipv4_compatible = segments[..6] == [0, 0, 0, 0, 0, 0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || unique_local
+                || link_local
+                || ipv4_compatible
+        }
+    }
+}
 
-        // 3. Construir payload
-        let payload = serde_json::to_string(&event)?;
-
-        // 4. Generar firma HMAC
-        let signature = self.generate_signature(&payload, &webhook.webhook_secret)?;
-
-        // 5. Enviar request
-        let max_retries = 3;
-        let mut retry_count = 0;
-
-        loop {
-            let response = self
-                .http_client
-                .post(&webhook.webhook_url)
-                .header("Content-Type", "application/json")
-                .header("X-Webhook-Signature", &signature)
-                .header("X-Webhook-Event", &event.event)
-                .header("X-Webhook-Timestamp", event.timestamp.to_rfc3339())
-                .body(payload.clone())
-                .send()
-                .await;
-
-            match response {
-                Ok(resp) if resp.status().is_success() => {
-                    info!(
-                        "Webhook sent successfully to merchant {} for event {}",
-                        merchant_id, event.event
-                    );
-                    record_webhook_sent(&event.event, true);
+/// Host y dirección a la que se conecta el envío. Se resuelve el DNS y se
+/// rechaza si CUALQUIER IP resuelta es interna; el envío se fija a la IP
+/// revisada para que un segundo lookup (DNS rebinding) no cambie el destino.
+pub async fn resolve_webhook_target(raw: &str) -> Result<(String, SocketAddr), String> {
+    validate_webhook_url(raw)?;
+    let parsed = url::Url::parse(raw.trim()).map_err(|_| "URL inválida".to_string())?;
+    let host = parsed.host_str().ok_or_else(|| "La URL del webhook debe incluir un host".to_string())?;
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    // Url deja las IPv6 entre corchetes
+    let lookup_host = host.trim_start_matches('[').trim_end_matches(']');
+
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((lookup_host, port))
+        .await
+        .map_err(|_| format!("No se pudo resolver el host del webhook: {}", host))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("No se pudo resolver el host del webhook: {}", host));
+    }
+    if addrs.iter().any(|addr| is_internal_ip(addr.ip())) {
+        return Err("La URL del webhook resuelve a una IP interna".to_string());
+    }
+    Ok((host.to_string(), addrs[0]))
+}
 
-                    // Guardar en log
-                    self.log_webhook_sent(merchant_id, &event, true, None)
-                        .await?;
+/// Espera antes del siguiente intento: 30s, 2m, 8m, 32m, ~2h, ~8.5h y luego 24h
+pub fn retry_delay(attempts: i32) -> chrono::Duration {
+    let exponent = attempts.clamp(1, 10) as u32 - 1;
+    let seconds = BACKOFF_BASE_SECONDS.saturating_mul(4_i64.saturating_pow(exponent));
+    chrono::Duration::seconds(seconds.min(BACKOFF_MAX_SECONDS))
+}
 
-                    return Ok(());
-                }
-                Ok(resp) => {
-                    let status = resp.status();
-                    let error_body = resp
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "Unknown error".to_string());
-
-                    error!(
-                        "Webhook failed (status {}): {}",
-                        status, error_body
-                    );
+pub struct WebhookService {
+    db: PgPool,
+}
 
-                    if retry_count < max_retries {
-                        retry_count += 1;
-                        let backoff_secs = 2_u64.pow(retry_count);
-                        warn!(
-                            "Retrying webhook in {} seconds (attempt {}/{})",
-                            backoff_secs, retry_count, max_retries
-                        );
-                        tokio::time::sleep(Duration::from_secs(backoff_secs)).await;
-                        continue;
-                    }
+impl WebhookService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
 
-                    record_webhook_sent(&event.event, false);
-                    self.log_webhook_sent(merchant_id, &event, false, Some(&error_body))
-                        .await?;
+    /// Cliente fijado a la IP ya validada y sin seguir redirects (un 30x podría
+    /// mandar el POST a un destino interno)
+    fn pinned_client(host: &str, addr: SocketAddr) -> reqwest::Result<Client> {
+        Client::builder()
+            .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+            .redirect(redirect::Policy::none())
+            .resolve(host, addr)
+            .build()
+    }
 
-                    return Err(anyhow::anyhow!("Webhook failed after {} retries", max_retries));
-                }
-                Err(e) => {
-                    error!("Webhook request error: {}", e);
-
-                    if retry_count < max_retries {
-                        retry_count += 1;
-                        let backoff_secs = 2_u64.pow(retry_count);
-                        warn!(
-                            "Retrying webhook in {} seconds (attempt {}/{})",
-                            backoff_secs, retry_count, max_retries
-                        );
-                        tokio::time::sleep(Duration::from_secs(backoff_secs)).await;
-                        continue;
-                    }
+    /// Encolar un evento en el outbox.
+    ///
+    /// Debe llamarse con la transacción del cambio de negocio (`&mut *tx`) para que
+    /// el evento exista si y solo si el cambio se confirma. Solo se inserta si el
+    /// merchant tiene webhook activo y suscrito al evento; devuelve el delivery_id.
+    pub async fn enqueue<'e, E>(executor: E, event: &WebhookEvent) -> Result<Option<Uuid>>
+    where
+        E: sqlx::PgExecutor<'e>,
+    {
+        let payload = serde_json::to_value(event)?;
+
+        let delivery_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO rewards.webhook_outbox (event_id, merchant_id, event_type, payload)
+            SELECT $1, m.merchant_id, $3, $4
+            FROM rewards.merchants m
+            WHERE m.merchant_id = $2
+              AND COALESCE(m.webhook_enabled, false) = true
+              AND m.webhook_url IS NOT NULL AND m.webhook_url != ''
+              AND m.webhook_secret IS NOT NULL AND m.webhook_secret != ''
+              AND $3 = ANY(m.webhook_events)
+            RETURNING delivery_id
+            "#,
+        )
+        .bind(event.id)
+        .bind(event.merchant_id)
+        .bind(&event.event)
+        .bind(&payload)
+        .fetch_optional(executor)
+        .await
+        .context("Failed to enqueue webhook")?;
 
-                    record_webhook_sent(&event.event, false);
-                    self.log_webhook_sent(merchant_id, &event, false, Some(&e.to_string()))
-                        .await?;
+        Ok(delivery_id)
+    }
 
-                    return Err(anyhow::anyhow!("Webhook failed after {} retries: {}", max_retries, e));
-                }
-            }
+    /// Crear una nueva entrega con el mismo payload (y event_id) de una existente
+    pub async fn replay<'e, E>(executor: E, merchant_id: Uuid, delivery_id: Uuid) -> Result<Option<Uuid>>
+    where
+        E: sqlx::PgExecutor<'e>,
+    {
+        let new_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO rewards.webhook_outbox (event_id, merchant_id, event_type, payload, replay_of)
+            SELECT event_id, merchant_id, event_type, payload, delivery_id
+            FROM rewards.webhook_outbox
+            WHERE delivery_id = $1 AND merchant_id = $2
+            RETURNING delivery_id
+            "#,
+        )
+        .bind(delivery_id)
+        .bind(merchant_id)
+        .fetch_optional(executor)
+        .await
+        .context("Failed to replay webhook")?;
+
+        if let Some(id) = new_id {
+            info!("Webhook delivery {} replayed as {} for merchant {}", delivery_id, id, merchant_id);
         }
+        Ok(new_id)
     }
 
     /// Obtener configuración de webhook del merchant
-    async fn get_merchant_webhook(&self, merchant_id: Uuid) -> Result<Option<MerchantWebhook>> {
+    pub async fn get_merchant_webhook(&self, merchant_id: Uuid) -> Result<Option<MerchantWebhook>> {
         let result = sqlx::query_as::<_, MerchantWebhook>(
             r#"
-            SELECT 
+            SELECT
                 merchant_id,
                 webhook_url,
                 webhook_secret,
-                webhook_events as events,
-                webhook_enabled as is_active
+                COALESCE(webhook_events, ARRAY[]::text[]) as events,
+                COALESCE(webhook_enabled, false) as is_active
             FROM rewards.merchants
             WHERE merchant_id = $1
               AND webhook_url IS NOT NULL
@@ -192,140 +430,239 @@ impl WebhookService {
         Ok(result)
     }
 
-    /// Generar firma HMAC SHA256
-    fn generate_signature(&self, payload: &str, secret: &str) -> Result<String> {
-        let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
-            .context("Invalid HMAC key")?;
-        mac.update(payload.as_bytes());
-        let result = mac.finalize();
-        let code_bytes = result.into_bytes();
-        Ok(hex::encode(code_bytes))
-    }
-
-    /// Guardar log de webhook enviado
-    async fn log_webhook_sent(
+    /// POST firmado al endpoint del merchant (un solo intento, sin reintentos).
+    /// El destino se resuelve y valida en cada envío, no solo al guardar la URL.
+    pub async fn post_signed(
         &self,
-        merchant_id: Uuid,
-        event: &WebhookEvent,
-        success: bool,
-        error_message: Option<&str>,
-    ) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO rewards.webhook_logs 
-                (merchant_id, event_type, payload, success, error_message, sent_at)
-            VALUES ($1, $2, $3, $4, $5, NOW())
-            "#,
-        )
-        .bind(merchant_id)
-        .bind(&event.event)
-        .bind(&event.data)
-        .bind(success)
-        .bind(error_message)
-        .execute(&self.db)
-        .await
-        .context("Failed to log webhook")?;
+        webhook: &MerchantWebhook,
+        event_type: &str,
+        body: &str,
+    ) -> DeliveryOutcome {
+        let failed = |error: String| DeliveryOutcome {
+            success: false,
+            status_code: None,
+            response_body: None,
+            error: Some(error),
+            duration_ms: 0,
+        };
+        let timestamp = Utc::now().timestamp();
+        let signature = match sign_payload(&webhook.webhook_secret, timestamp, body) {
+            Ok(s) => s,
+            Err(e) => return failed(e.to_string()),
+        };
+        let client = match resolve_webhook_target(&webhook.webhook_url).await {
+            Ok((host, addr)) => match Self::pinned_client(&host, addr) {
+                Ok(client) => client,
+                Err(e) => return failed(e.to_string()),
+            },
+            Err(e) => {
+                warn!("Webhook for merchant {} blocked: {}", webhook.merchant_id, e);
+                return failed(e);
+            }
+        };
 
-        Ok(())
+        let start = std::time::Instant::now();
+        let response = client
+            .post(&webhook.webhook_url)
+            .header("Content-Type", "application/json")
+            .header("X-Webhook-Signature", &signature)
+            .header("X-Webhook-Event", event_type)
+            .header("X-Webhook-Timestamp", timestamp.to_string())
+            .body(body.to_string())
+            .send()
+            .await;
+
+        match response {
+            Ok(resp) => {
+                let status = resp.status();
+                let mut text = resp.text().await.unwrap_or_default();
+                if text.len() > MAX_RESPONSE_BODY_BYTES {
+                    let mut cut = MAX_RESPONSE_BODY_BYTES;
+                    while !text.is_char_boundary(cut) {
+                        cut -= 1;
+                    }
+                    text.truncate(cut);
+                }
+                DeliveryOutcome {
+                    success: status.is_success(),
+                    status_code: Some(status.as_u16()),
+                    response_body: Some(text),
+                    error: None,
+                    duration_ms: start.elapsed().as_millis() as i64,
+                }
+            }
+            Err(e) => DeliveryOutcome {
+                success: false,
+                status_code: None,
+                response_body: None,
+                error: Some(e.to_string()),
+                duration_ms: start.elapsed().as_millis() as i64,
+            },
+        }
     }
 
     // ========================================================================
-    // WEBHOOK EVENTS - Eventos específicos de redenciones
+    // DISPATCHER
     // ========================================================================
 
-    /// Notificar creación de redención
-    pub async fn notify_redemption_created(
-        &self,
-        merchant_id: Uuid,
-        redemption_id: Uuid,
-        redemption_code: &str,
-        offer_name: &str,
-        lumis_spent: i32,
-    ) -> Result<()> {
-        let event = WebhookEvent {
-            event: "redemption.created".to_string(),
-            timestamp: Utc::now(),
-            data: json!({
-                "redemption_id": redemption_id,
-                "redemption_code": redemption_code,
-                "offer_name": offer_name,
-                "lumis_spent": lumis_spent,
-            }),
-            merchant_id,
-        };
+    /// Reclamar un lote de entregas vencidas (incluye leases expirados de procesos caídos)
+    async fn claim_due_deliveries(&self) -> Result<Vec<OutboxDelivery>> {
+        let rows = sqlx::query_as::<_, OutboxDelivery>(
+            r#"
+            UPDATE rewards.webhook_outbox o
+            SET status = 'delivering',
+                locked_until = NOW() + make_interval(secs => $2)
+            WHERE o.delivery_id IN (
+                SELECT delivery_id
+                FROM rewards.webhook_outbox
+                WHERE (status = 'pending' AND next_attempt_at <= NOW())
+                   OR (status = 'delivering' AND locked_until < NOW())
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING o.delivery_id, o.merchant_id, o.event_type, o.payload, o.attempts, o.max_attempts
+            "#,
+        )
+        .bind(DISPATCH_BATCH_SIZE)
+        .bind(DELIVERY_LEASE_SECS as f64)
+        .fetch_all(&self.db)
+        .await?;
 
-        self.send_webhook(merchant_id, event).await
+        Ok(rows)
     }
 
-    /// Notificar confirmación de redención
-    pub async fn notify_redemption_confirmed(
-        &self,
-        merchant_id: Uuid,
-        redemption_id: Uuid,
-        redemption_code: &str,
-        offer_name: &str,
-        confirmed_by: &str,
-    ) -> Result<()> {
-        let event = WebhookEvent {
-            event: "redemption.confirmed".to_string(),
-            timestamp: Utc::now(),
-            data: json!({
-                "redemption_id": redemption_id,
-                "redemption_code": redemption_code,
-                "offer_name": offer_name,
-                "confirmed_by": confirmed_by,
-                "confirmed_at": Utc::now(),
-            }),
-            merchant_id,
-        };
+    /// Procesar un lote del outbox. Devuelve (entregados, reintentos, dead)
+    pub async fn dispatch_due(&self) -> Result<(usize, usize, usize)> {
+        let deliveries = self.claim_due_deliveries().await?;
+        let (mut delivered, mut retrying, mut dead) = (0, 0, 0);
 
-        self.send_webhook(merchant_id, event).await
-    }
+        for delivery in deliveries {
+            let attempt_number = delivery.attempts + 1;
+            let webhook = self.get_merchant_webhook(delivery.merchant_id).await?;
 
-    /// Notificar expiración de redención
-    pub async fn notify_redemption_expired(
-        &self,
-        merchant_id: Uuid,
-        redemption_id: Uuid,
-        redemption_code: &str,
-        offer_name: &str,
-    ) -> Result<()> {
-        let event = WebhookEvent {
-            event: "redemption.expired".to_string(),
-            timestamp: Utc::now(),
-            data: json!({
-                "redemption_id": redemption_id,
-                "redemption_code": redemption_code,
-                "offer_name": offer_name,
-                "expired_at": Utc::now(),
-            }),
-            merchant_id,
-        };
+            let outcome = match webhook {
+                Some(ref w) if w.is_active => {
+                    let body = serde_json::to_string(&delivery.payload)?;
+                    self.post_signed(w, &delivery.event_type, &body).await
+                }
+                _ => DeliveryOutcome {
+                    success: false,
+                    status_code: None,
+                    response_body: None,
+                    error: Some("Webhook deshabilitado o sin configurar".to_string()),
+                    duration_ms: 0,
+                },
+            };
+
+            record_webhook_sent(&delivery.event_type, outcome.success);
+            if let Err(e) = self.log_attempt(&delivery, attempt_number, &outcome).await {
+                warn!("Failed to log webhook attempt {}: {}", delivery.delivery_id, e);
+            }
 
-        self.send_webhook(merchant_id, event).await
+            if outcome.success {
+                sqlx::query(
+                    r#"
+                    UPDATE rewards.webhook_outbox
+                    SET status = 'delivered', attempts = $2, last_attempt_at = NOW(),
+                        delivered_at = NOW(), locked_until = NULL,
+                        last_status_code = $3, last_error = NULL
+                    WHERE delivery_id = $1
+                    "#,
+                )
+                .bind(delivery.delivery_id)
+                .bind(attempt_number)
+                .bind(outcome.status_code.map(i32::from))
+                .execute(&self.db)
+                .await?;
+                delivered += 1;
+                continue;
+            }
+
+            let error_text = outcome.error.clone().or_else(|| {
+                outcome.status_code.map(|c| format!("HTTP {}", c))
+            });
+
+            if attempt_number >= delivery.max_attempts {
+                sqlx::query(
+                    r#"
+                    UPDATE rewards.webhook_outbox
+                    SET status = 'dead', attempts = $2, last_attempt_at = NOW(),
+                        locked_until = NULL, last_status_code = $3, last_error = $4
+                    WHERE delivery_id = $1
+                    "#,
+                )
+                .bind(delivery.delivery_id)
+                .bind(attempt_number)
+                .bind(outcome.status_code.map(i32::from))
+                .bind(&error_text)
+                .execute(&self.db)
+                .await?;
+                record_webhook_dead_letter(&delivery.event_type);
+                error!(
+                    "Webhook {} for merchant {} moved to dead-letter after {} attempts",
+                    delivery.delivery_id, delivery.merchant_id, attempt_number
+                );
+                dead += 1;
+            } else {
+                let next_attempt_at = Utc::now() + retry_delay(attempt_number);
+                sqlx::query(
+                    r#"
+                    UPDATE rewards.webhook_outbox
+                    SET status = 'pending', attempts = $2, last_attempt_at = NOW(),
+                        next_attempt_at = $3, locked_until = NULL,
+                        last_status_code = $4, last_error = $5
+                    WHERE delivery_id = $1
+                    "#,
+                )
+                .bind(delivery.delivery_id)
+                .bind(attempt_number)
+                .bind(next_attempt_at)
+                .bind(outcome.status_code.map(i32::from))
+                .bind(&error_text)
+                .execute(&self.db)
+                .await?;
+                warn!(
+                    "Webhook {} failed (attempt {}/{}), next attempt at {}",
+                    delivery.delivery_id, attempt_number, delivery.max_attempts, next_attempt_at
+                );
+                retrying += 1;
+            }
+        }
+
+        Ok((delivered, retrying, dead))
     }
 
-    /// Notificar cancelación de redención
-    pub async fn notify_redemption_cancelled(
+    /// Guardar el intento en rewards.webhook_logs
+    async fn log_attempt(
         &self,
-        merchant_id: Uuid,
-        redemption_id: Uuid,
-        redemption_code: &str,
-        reason: &str,
+        delivery: &OutboxDelivery,
+        attempt_number: i32,
+        outcome: &DeliveryOutcome,
     ) -> Result<()> {
-        let event = WebhookEvent {
-            event: "redemption.cancelled".to_string(),
-            timestamp: Utc::now(),
-            data: json!({
-                "redemption_id": redemption_id,
-                "redemption_code": redemption_code,
-                "reason": reason,
-                "cancelled_at": Utc::now(),
-            }),
-            merchant_id,
-        };
+        sqlx::query(
+            r#"
+            INSERT INTO rewards.webhook_logs
+                (merchant_id, event_type, payload, success, error_message, sent_at,
+                 response_time_ms, delivery_id, attempt_number, status_code, response_body)
+            VALUES ($1, $2, $3, $4, $5, NOW(), $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(delivery.merchant_id)
+        .bind(&delivery.event_type)
+        .bind(&delivery.payload)
+        .bind(outcome.success)
+        .bind(&outcome.error)
+        .bind(outcome.duration_ms as i32)
+        .bind(delivery.delivery_id)
+        .bind(attempt_number)
+        .bind(outcome.status_code.map(i32::from))
+        .bind(&outcome.response_body)
+        .execute(&self.db)
+        .await
+        .context("Failed to log webhook")?;
 
-        self.send_webhook(merchant_id, event).await
+        Ok(())
     }
 }
 
@@ -347,3 +684,155 @@ pub fn init_webhook_service(db: PgPool) {
 pub fn get_webhook_service() -> Option<Arc<WebhookService>> {
     WEBHOOK_SERVICE.get().cloned()
 }
+
+// ============================================================================
+// BACKGROUND DISPATCHER
+// ============================================================================
+
+const DISPATCH_POLL_INTERVAL_SECS: u64 = 5;
+const DISPATCH_ERROR_BACKOFF_SECS: u64 = 30;
+/// Timeout del POST al merchant
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+/// Lotes chicos: las entregas de un lote se hacen en secuencia y todas comparten el lease
+const DISPATCH_BATCH_SIZE: i64 = 10;
+/// Tiempo que una entrega queda reservada por un dispatcher antes de poder reclamarse de nuevo.
+/// Debe cubrir el peor caso del lote (todas las entregas agotando el timeout) con margen;
+/// si expira a mitad del lote otro dispatcher la reclama y el merchant la recibe dos veces.
+const DELIVERY_LEASE_SECS: i64 = DISPATCH_BATCH_SIZE * WEBHOOK_TIMEOUT_SECS as i64 * 2 + 60;
+const _: () = assert!(DELIVERY_LEASE_SECS > DISPATCH_BATCH_SIZE * WEBHOOK_TIMEOUT_SECS as i64);
+const BACKOFF_BASE_SECONDS: i64 = 30;
+const BACKOFF_MAX_SECONDS: i64 = 24 * 3600;
+
+/// Start the webhook outbox dispatcher as a background task
+///
+/// Several instances can run concurrently: rows are claimed with
+/// FOR UPDATE SKIP LOCKED and a lease (locked_until).
+pub async fn start_webhook_dispatcher(db: PgPool) {
+    let service = get_webhook_service().unwrap_or_else(|| Arc::new(WebhookService::new(db)));
+
+    info!(
+        "Starting webhook outbox dispatcher (poll interval: {}s)",
+        DISPATCH_POLL_INTERVAL_SECS
+    );
+
+    let mut consecutive_errors = 0u32;
+
+    loop {
+        match service.dispatch_due().await {
+            Ok((delivered, retrying, dead)) => {
+                consecutive_errors = 0;
+                if delivered + retrying + dead > 0 {
+                    info!(
+                        "Webhook dispatcher: delivered={}, retrying={}, dead={}",
+                        delivered, retrying, dead
+                    );
+                }
+                // Lote completo: puede haber más pendientes, seguir sin esperar
+                if (delivered + retrying + dead) as i64 >= DISPATCH_BATCH_SIZE {
+                    continue;
+                }
+            }
+            Err(e) => {
+                consecutive_errors += 1;
+                error!("Webhook dispatcher error (consecutive: {}): {}", consecutive_errors, e);
+
+                if consecutive_errors >= 3 {
+                    let backoff = std::cmp::min(
+                        DISPATCH_ERROR_BACKOFF_SECS * 2u64.pow(consecutive_errors.min(6) - 3),
+                        300
+                    );
+                    warn!("Webhook dispatcher backing off for {}s due to repeated errors", backoff);
+                    tokio::time::sleep(Duration::from_secs(backoff)).await;
+                    continue;
+                }
+            }
+        }
+
+        tokio::time::sleep(Duration::from_secs(DISPATCH_POLL_INTERVAL_SECS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_roundtrip() {
+        let body = r#"{"event":"redemption.created"}"#;
+        let now = 1_760_000_000;
+        let header = sign_payload("secret", now, body).unwrap();
+
+        assert!(header.starts_with(&format!("t={},v1=", now)));
+        assert!(verify_signature("secret", &header, body, now));
+        assert!(verify_signature("secret", &header, body, now + SIGNATURE_TOLERANCE_SECS));
+    }
+
+    #[test]
+    fn test_signature_rejects_tampering_and_replay() {
+        let body = r#"{"event":"redemption.created"}"#;
+        let now = 1_760_000_000;
+        let header = sign_payload("secret", now, body).unwrap();
+
+        assert!(!verify_signature("other", &header, body, now));
+        assert!(!verify_signature("secret", &header, r#"{"event":"x"}"#, now));
+        // Mismo cuerpo y firma, pero fuera de la ventana de tolerancia
+        assert!(!verify_signature("secret", &header, body, now + SIGNATURE_TOLERANCE_SECS + 1));
+        // Cambiar el timestamp invalida la firma
+        let forged = header.replacen(&now.to_string(), &(now + 60).to_string(), 1);
+        assert!(!verify_signature("secret", &forged, body, now + 60));
+    }
+
+    #[test]
+    fn test_webhook_url_validation() {
+        assert!(validate_webhook_url("https://api.comercio.com/hooks/lumis").is_ok());
+        assert!(validate_webhook_url("https://8.8.8.8/hook").is_ok());
+
+        assert!(validate_webhook_url("http://api.comercio.com/hook").is_err());
+        assert!(validate_webhook_url("not a url").is_err());
+        assert!(validate_webhook_url("https://localhost:8000/hook").is_err());
+        assert!(validate_webhook_url("https://127.0.0.1/hook").is_err());
+        assert!(validate_webhook_url("https://10.0.0.5/hook").is_err());
+        assert!(validate_webhook_url("https://192.168.1.10/hook").is_err());
+        assert!(validate_webhook_url("https://169.254.169.254/latest/meta-data").is_err());
+        assert!(validate_webhook_url("https://[::1]/hook").is_err());
+        assert!(validate_webhook_url("https://[fd00::1]/hook").is_err());
+        assert!(validate_webhook_url("https://[::ffff:127.0.0.1]/hook").is_err());
+        assert!(validate_webhook_url("https://100.64.0.1/hook").is_err());
+    }
+
+    #[test]
+    fn test_is_internal_ip() {
+        let internal = [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.0.1", "169.254.169.254", "100.64.0.1",
+            "100.127.255.254", "0.0.0.0", "255.255.255.255", "224.0.0.1", "::1", "::", "fd00::1",
+            "fe80::1", "::ffff:127.0.0.1", "::ffff:169.254.169.254", "64:ff9b::a00:1", "::127.0.0.1",
+        ];
+        for ip in internal {
+            assert!(is_internal_ip(ip.parse().unwrap()), "{} debería ser interna", ip);
+        }
+
+        let public = ["8.8.8.8", "100.128.0.1", "1.1.1.1", "2606:4700::1111", "::ffff:8.8.8.8"];
+        for ip in public {
+            assert!(!is_internal_ip(ip.parse().unwrap()), "{} debería ser pública", ip);
+        }
+    }
+
+    #[test]
+    fn test_generated_secret_format() {
+        let a = generate_webhook_secret();
+        let b = generate_webhook_secret();
+        assert!(a.starts_with("whsec_"));
+        assert_eq!(a.len(), "whsec_".len() + 64);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_retry_delay_schedule() {
+        assert_eq!(retry_delay(1).num_seconds(), 30);
+        assert_eq!(retry_delay(2).num_seconds(), 120);
+        assert_eq!(retry_delay(3).num_seconds(), 480);
+        assert_eq!(retry_delay(6).num_seconds(), 30 * 4_i64.pow(5));
+        assert_eq!(retry_delay(7).num_seconds(), BACKOFF_MAX_SECONDS);
+        assert_eq!(retry_delay(50).num_seconds(), BACKOFF_MAX_SECONDS);
+    }
+}
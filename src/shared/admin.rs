@@ -0,0 +1,46 @@
+//! Administradores de la plataforma (`ADMIN_USER_IDS`, ids separados por coma)
+//!
+//! Es la única lectura de la variable. Sin ella no hay administradores: los
+//! endpoints de admin responden 403. Antes se usaban los ids 1, 2 y 3 por
+//! defecto, que en una base real son usuarios cualquiera.
+
+use std::sync::Once;
+use tracing::warn;
+
+static MISSING_WARNING: Once = Once::new();
+
+/// Ids válidos de la lista; entradas vacías o no numéricas se ignoran
+pub fn parse_admin_ids(raw: Option<&str>) -> Vec<i64> {
+    raw.unwrap_or_default()
+        .split(',')
+        .filter_map(|s| s.trim().parse().ok())
+        .collect()
+}
+
+pub fn admin_user_ids() -> Vec<i64> {
+    let raw = std::env::var("ADMIN_USER_IDS").ok();
+    if raw.is_none() {
+        MISSING_WARNING.call_once(|| warn!("ADMIN_USER_IDS not set: admin endpoints are disabled"));
+    }
+    parse_admin_ids(raw.as_deref())
+}
+
+pub fn is_admin(user_id: i64) -> bool {
+    admin_user_ids().contains(&user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_comma_separated_ids() {
+        assert_eq!(parse_admin_ids(Some("7, 12,abc,,40")), vec![7, 12, 40]);
+    }
+
+    #[test]
+    fn fails_closed_without_variable() {
+        assert!(parse_admin_ids(None).is_empty());
+        assert!(parse_admin_ids(Some("")).is_empty());
+    }
+}
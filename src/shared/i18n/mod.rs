@@ -0,0 +1,382 @@
+//! Catálogo de mensajes localizados (es/en)
+//!
+//! Todos los textos salientes (push, notificaciones in-app, WhatsApp, emails
+//! y errores de la API) se buscan por id en `locales/<locale>.json`. Un
+//! mensaje es un texto con parámetros `{nombre}` o un objeto con formas
+//! plurales (`zero` opcional, `one`, `other`) que se elige con `{count}`.
+//! Si falta en el idioma pedido se usa el español; si falta en ambos se
+//! devuelve el id, para que el hueco se vea en QA y no rompa el envío.
+//!
+//! El idioma sale de `dim_users.locale` (push, in-app, WhatsApp, emails), de
+//! `rewards.merchants.locale` (reportes a comercios) o del `Accept-Language`
+//! de la petición HTTP (errores de la API), vía `request_locale()`.
+//!
+//! Los errores de dominio guardan un `Text` (id + parámetros) en lugar de un
+//! texto fijo; al mostrarse (`Display`) se renderiza en el idioma de la
+//! petición en curso.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+use tracing::warn;
+use uuid::Uuid;
+
+// ======================================================================
+// CONFIGURACIÓN
+// ======================================================================
+
+const ES_CATALOG: &str = include_str!("locales/es.json");
+const EN_CATALOG: &str = include_str!("locales/en.json");
+
+// ======================================================================
+// MODELOS
+// ======================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    Es,
+    En,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Message {
+    Text(String),
+    Plural {
+        zero: Option<String>,
+        one: Option<String>,
+        other: String,
+    },
+}
+
+type Catalog = HashMap<String, Message>;
+
+/// Mensaje del catálogo pendiente de renderizar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Text {
+    pub id: &'static str,
+    pub params: Vec<(&'static str, String)>,
+}
+
+tokio::task_local! {
+    static REQUEST_LOCALE: Locale;
+}
+
+// ======================================================================
+// LÓGICA PURA
+// ======================================================================
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::Es, Locale::En];
+
+    /// Acepta `en`, `en-US`, `EN_us`...; otros idiomas no soportados → None
+    pub fn parse(value: &str) -> Option<Self> {
+        let language = value.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        match language.as_str() {
+            "es" => Some(Locale::Es),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+
+    /// Primer idioma soportado de un `Accept-Language` (respeta `q`)
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut candidates: Vec<(f32, Locale)> = header
+            .split(',')
+            .filter_map(|part| {
+                let mut pieces = part.split(';');
+                let locale = Locale::parse(pieces.next()?)?;
+                let quality = pieces
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse().ok())
+                    .unwrap_or(1.0);
+                Some((quality, locale))
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+        candidates.first().map(|(_, locale)| *locale)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Locale::Es => "es",
+            Locale::En => "en",
+        }
+    }
+
+    /// Formato de fecha corta para textos
+    pub fn date_format(self) -> &'static str {
+        match self {
+            Locale::Es => "%d/%m/%Y",
+            Locale::En => "%b %-d, %Y",
+        }
+    }
+
+    /// Categoría plural (CLDR simplificado: en y es solo distinguen 1)
+    pub fn plural_category(self, count: i64) -> &'static str {
+        match (self, count) {
+            (Locale::Es | Locale::En, 1) => "one",
+            _ => "other",
+        }
+    }
+}
+
+fn catalogs() -> &'static HashMap<Locale, Catalog> {
+    static CATALOGS: OnceLock<HashMap<Locale, Catalog>> = OnceLock::new();
+    CATALOGS.get_or_init(|| {
+        Locale::ALL
+            .into_iter()
+            .map(|locale| {
+                let raw = match locale {
+                    Locale::Es => ES_CATALOG,
+                    Locale::En => EN_CATALOG,
+                };
+                let catalog = serde_json::from_str(raw).unwrap_or_else(|e| {
+                    warn!("Invalid {} message catalog: {}", locale.as_str(), e);
+                    Catalog::new()
+                });
+                (locale, catalog)
+            })
+            .collect()
+    })
+}
+
+fn lookup(locale: Locale, id: &str) -> Option<&'static Message> {
+    let catalogs = catalogs();
+    catalogs
+        .get(&locale)
+        .and_then(|catalog| catalog.get(id))
+        .or_else(|| catalogs.get(&Locale::Es).and_then(|catalog| catalog.get(id)))
+}
+
+/// Sustituye `{nombre}`; los parámetros desconocidos quedan tal cual
+fn interpolate(template: &str, params: &[(&str, &dyn Display)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        rendered.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        match after.find('}') {
+            Some(close) => {
+                let name = &after[..close];
+                match params.iter().find(|(key, _)| *key == name) {
+                    Some((_, value)) => rendered.push_str(&value.to_string()),
+                    None => rendered.push_str(&rest[open..open + close + 2]),
+                }
+                rest = &after[close + 1..];
+            }
+            None => {
+                rendered.push_str(&rest[open..]);
+                rest = "";
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Mensaje sin parámetros
+pub fn t(locale: Locale, id: &str) -> String {
+    render(locale, id, &[])
+}
+
+/// Mensaje con parámetros; un mensaje plural usa la forma `other`
+pub fn render(locale: Locale, id: &str, params: &[(&str, &dyn Display)]) -> String {
+    match lookup(locale, id) {
+        Some(Message::Text(template)) => interpolate(template, params),
+        Some(Message::Plural { other, .. }) => interpolate(other, params),
+        None => {
+            warn!("Missing message '{}' in catalog", id);
+            id.to_string()
+        }
+    }
+}
+
+/// Mensaje plural elegido por `count` (disponible también como `{count}`)
+pub fn render_count(locale: Locale, id: &str, count: i64, params: &[(&str, &dyn Display)]) -> String {
+    let mut all: Vec<(&str, &dyn Display)> = Vec::with_capacity(params.len() + 1);
+    all.push(("count", &count));
+    all.extend_from_slice(params);
+
+    match lookup(locale, id) {
+        Some(Message::Text(template)) => interpolate(template, &all),
+        Some(Message::Plural { zero, one, other }) => {
+            let form = match (count, locale.plural_category(count)) {
+                (0, _) if zero.is_some() => zero.as_ref(),
+                (_, "one") => one.as_ref(),
+                _ => None,
+            };
+            interpolate(form.unwrap_or(other), &all)
+        }
+        None => {
+            warn!("Missing message '{}' in catalog", id);
+            id.to_string()
+        }
+    }
+}
+
+impl Text {
+    pub fn new(id: &'static str) -> Self {
+        Self { id, params: Vec::new() }
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl Display) -> Self {
+        self.params.push((name, value.to_string()));
+        self
+    }
+
+    pub fn render(&self, locale: Locale) -> String {
+        let params: Vec<(&str, &dyn Display)> =
+            self.params.iter().map(|(name, value)| (*name, value as &dyn Display)).collect();
+        render(locale, self.id, &params)
+    }
+}
+
+/// En el idioma de la petición en curso
+impl Display for Text {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render(request_locale()))
+    }
+}
+
+/// Idioma de la petición HTTP en curso (español fuera de una petición)
+pub fn request_locale() -> Locale {
+    REQUEST_LOCALE.try_with(|locale| *locale).unwrap_or_default()
+}
+
+/// Ejecuta `future` con `locale` como idioma de la petición
+pub async fn with_request_locale<F: std::future::Future>(locale: Locale, future: F) -> F::Output {
+    REQUEST_LOCALE.scope(locale, future).await
+}
+
+// ======================================================================
+// PREFERENCIA DEL USUARIO
+// ======================================================================
+
+fn stored(value: Result<Option<Option<String>>, sqlx::Error>, what: &str) -> Locale {
+    match value {
+        Ok(locale) => locale.flatten().as_deref().and_then(Locale::parse).unwrap_or_default(),
+        Err(e) => {
+            warn!("Failed to load locale for {}: {}", what, e);
+            Locale::default()
+        }
+    }
+}
+
+/// Idioma guardado del usuario (español si no hay o falla la consulta)
+pub async fn user_locale(pool: &PgPool, user_id: i64) -> Locale {
+    let locale = sqlx::query_scalar::<_, Option<String>>("SELECT locale FROM public.dim_users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await;
+    stored(locale, &format!("user {}", user_id))
+}
+
+/// Idioma del usuario de WhatsApp (español si aún no está registrado)
+pub async fn whatsapp_locale(pool: &PgPool, whatsapp_id: &str) -> Locale {
+    let locale = sqlx::query_scalar::<_, Option<String>>("SELECT locale FROM public.dim_users WHERE ws_id = $1 LIMIT 1")
+        .bind(whatsapp_id)
+        .fetch_optional(pool)
+        .await;
+    stored(locale, &format!("WhatsApp user {}", whatsapp_id))
+}
+
+/// Idioma del comercio para sus reportes
+pub async fn merchant_locale(pool: &PgPool, merchant_id: Uuid) -> Locale {
+    let locale = sqlx::query_scalar::<_, Option<String>>("SELECT locale FROM rewards.merchants WHERE merchant_id = $1")
+        .bind(merchant_id)
+        .fetch_optional(pool)
+        .await;
+    stored(locale, &format!("merchant {}", merchant_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn placeholders(message: &Message) -> BTreeSet<String> {
+        let texts: Vec<&String> = match message {
+            Message::Text(text) => vec![text],
+            Message::Plural { zero, one, other } => {
+                zero.iter().chain(one.iter()).chain(std::iter::once(other)).collect()
+            }
+        };
+        texts
+            .into_iter()
+            .flat_map(|text| {
+                text.split('{')
+                    .skip(1)
+                    .filter_map(|part| part.split_once('}').map(|(name, _)| name.to_string()))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|name| name != "count")
+            .collect()
+    }
+
+    #[test]
+    fn catalogs_parse_and_match() {
+        let es: Catalog = serde_json::from_str(ES_CATALOG).expect("es.json");
+        let en: Catalog = serde_json::from_str(EN_CATALOG).expect("en.json");
+        let es_keys: BTreeSet<&String> = es.keys().collect();
+        let en_keys: BTreeSet<&String> = en.keys().collect();
+        assert_eq!(es_keys, en_keys, "es y en deben tener los mismos ids");
+
+        for (id, message) in &es {
+            assert_eq!(placeholders(message), placeholders(&en[id]), "parámetros distintos en '{}'", id);
+        }
+    }
+
+    #[test]
+    fn parses_locales() {
+        assert_eq!(Locale::parse("en-US"), Some(Locale::En));
+        assert_eq!(Locale::parse("ES_pa"), Some(Locale::Es));
+        assert_eq!(Locale::parse("fr"), None);
+        assert_eq!(Locale::from_accept_language("fr-FR, en;q=0.8, es;q=0.5"), Some(Locale::En));
+        assert_eq!(Locale::from_accept_language("es-PA,es;q=0.9,en;q=0.8"), Some(Locale::Es));
+        assert_eq!(Locale::from_accept_language("de"), None);
+    }
+
+    #[test]
+    fn interpolates_params() {
+        let offer = "Café gratis";
+        assert_eq!(interpolate("¡Disfruta tu {offer}!", &[("offer", &offer)]), "¡Disfruta tu Café gratis!");
+        assert_eq!(interpolate("{missing} y {x", &[]), "{missing} y {x");
+        assert_eq!(interpolate("{a}{b}", &[("a", &1), ("b", &2)]), "12");
+    }
+
+    #[test]
+    fn renders_with_plurals_and_fallback() {
+        let offer = "Café";
+        let one = render_count(Locale::Es, "push.redemption_expiring.body", 1, &[("offer", &offer)]);
+        let many = render_count(Locale::Es, "push.redemption_expiring.body", 5, &[("offer", &offer)]);
+        assert!(one.contains("1 minuto") && !one.contains("minutos"));
+        assert!(many.contains("5 minutos"));
+
+        let en = render_count(Locale::En, "push.redemption_expiring.body", 1, &[("offer", &offer)]);
+        assert!(en.contains("1 minute") && !en.contains("minutes"));
+
+        assert_eq!(t(Locale::En, "no.such.message"), "no.such.message");
+        assert_ne!(t(Locale::En, "api.forbidden"), t(Locale::Es, "api.forbidden"));
+    }
+
+    #[test]
+    fn text_renders_with_params() {
+        let text = Text::new("push.redemption_created.body").with("code", "ABC123");
+        assert_eq!(text.render(Locale::Es), "Muestra el código ABC123 al comercio");
+        assert_eq!(text.to_string(), text.render(Locale::Es));
+        assert!(text.render(Locale::En).contains("ABC123"));
+    }
+
+    #[test]
+    fn zero_form_is_optional() {
+        let empty = render_count(Locale::Es, "wa.history.title", 0, &[]);
+        let five = render_count(Locale::Es, "wa.history.title", 5, &[]);
+        assert!(five.contains('5'));
+        assert!(!empty.is_empty());
+    }
+}
@@ -5,6 +5,8 @@ pub mod users;
 pub mod whatsapp;
 pub mod dashboard;
 pub mod performance;
+pub mod i18n; // Catálogo de mensajes es/en
+pub mod admin; // ADMIN_USER_IDS
 
 // Re-export shared services for easier access
 pub use database as db_service;
@@ -6,7 +6,7 @@ use crate::domains::rewards::qr_generator::{QrGenerator, QrConfig};
 use crate::cache::UserCache;
 use crate::shared::performance::{PerformanceManager, PerformanceConfig};
 use crate::optimization::{DatabaseConfig, RedisConfig, create_optimized_db_pool, create_optimized_redis_client};
-use crate::webhook::MessageDeduplicator;
+use crate::webhook::{MessageDeduplicator, SignatureVerifier};
 use dashmap::DashMap;
 use redis::Client as RedisClient;
 use reqwest::Client as ReqwestClient;
@@ -40,6 +40,8 @@ pub struct AppState {
     pub qr_service: QrService,
     pub performance_manager: Arc<PerformanceManager>,
     pub message_deduplicator: MessageDeduplicator,
+    // App secrets de Meta para verificar X-Hub-Signature-256 (actual y anterior)
+    pub webhook_signature: Arc<SignatureVerifier>,
     // Redemption system services
     pub offer_service: Arc<OfferService>,
     pub redemption_service: Arc<RedemptionService>,
@@ -101,6 +103,11 @@ impl AppState {
         
         // Initialize MessageDeduplicator
         let message_deduplicator = MessageDeduplicator::default();
+
+        let webhook_signature = Arc::new(SignatureVerifier::from_env());
+        if !webhook_signature.is_configured() {
+            tracing::error!("❌ WHATSAPP_APP_SECRET not set: WhatsApp webhooks will be rejected");
+        }
         
         // Warm up connections and caches
         if let Err(e) = performance_manager.warm_up(&db_pool, &redis_client).await {
@@ -146,6 +153,7 @@ impl AppState {
             qr_service,
             performance_manager,
             message_deduplicator,
+            webhook_signature,
             processed_messages: Arc::new(DashMap::new()),
             whatsapp_token,
             phone_number_id,
@@ -1,5 +1,6 @@
 use crate::{
     models::whatsapp::Message,
+    processing::flows::trivia_flow,
     services::{rewards_service, user_service},
     state::AppState,
 };
@@ -36,6 +37,9 @@ pub async fn handle_interactive_message(message: &Message, app_state: &Arc<AppSt
                     "red_giftcard" => rewards_service::send_giftcard_info(app_state, user_id).await?,
                     "red_tombola_cash" => rewards_service::send_tombola_cash_confirmation(app_state, user_id).await?,
                     "red_tombola_merch" => rewards_service::send_tombola_merch_confirmation(app_state, user_id).await?,
+                    id if id.starts_with("trivia:") => {
+                        trivia_flow::handle_trivia_answer(app_state, user_id, user.id as i32, id).await?
+                    }
                     _ => info!("Unknown list ID '{}' from user {}", list_id, user_id),
                 }
             } else {
@@ -4,6 +4,7 @@ use crate::{
     processing::flows::{product_search_flow, survey_flow},
     services::{redis_service, user_service, whatsapp_service, rewards_service},
     domains::invoices::service as invoice_service,
+    shared::i18n::{self, Locale},
     state::AppState,
 };
 use std::sync::Arc;
@@ -18,12 +19,13 @@ pub async fn handle_text_message(message: &Message, app_state: &Arc<AppState>) -
     let whatsapp_id = &message.from;
     let text_body = message.text.body.trim();
     info!("Routing text message from {}: '{}'", whatsapp_id, text_body);
+    let locale = i18n::whatsapp_locale(&app_state.db_pool, whatsapp_id).await;
 
     // 1. Verificar si el mensaje es una URL de factura
     if let Ok(url) = Url::parse(text_body) {
         if url.scheme() == "http" || url.scheme() == "https" {
             if let Some(user_id) = user_service::get_user_id_by_ws_id(app_state, &message.from).await? {
-                whatsapp_service::send_text_message(app_state, &message.from, "Hemos recibido tu factura. La procesaremos en breve.").await?;
+                whatsapp_service::send_text_message(app_state, &message.from, &i18n::t(locale, "wa.invoice_url.received")).await?;
                 // Procesar en segundo plano para no bloquear la respuesta
                 let state_clone = Arc::clone(app_state);
                 let url_string = url.to_string();
@@ -32,12 +34,12 @@ pub async fn handle_text_message(message: &Message, app_state: &Arc<AppState>) -
                                                             if let Err(e) = invoice_service::process_invoice_url(state_clone.clone(), &url_string, &from_clone, user_id.into()).await {
                         tracing::error!("Error procesando la factura desde la URL {}: {}", url_string, e);
                         // Opcional: notificar al usuario del error
-                        let _ = whatsapp_service::send_text_message(&state_clone, &from_clone, "Tuvimos un problema al procesar tu factura. Por favor, inténtalo de nuevo más tarde.").await;
+                        let _ = whatsapp_service::send_text_message(&state_clone, &from_clone, &i18n::t(locale, "wa.invoice_url.failed")).await;
                     }
                 });
                 return Ok(());
             } else {
-                whatsapp_service::send_text_message(app_state, &message.from, "Parece que aún no te has registrado. Por favor, usa el comando /registro para empezar.").await?;
+                whatsapp_service::send_text_message(app_state, &message.from, &i18n::t(locale, "wa.not_registered")).await?;
                 return Ok(());
             }
         }
@@ -85,7 +87,7 @@ pub async fn handle_text_message(message: &Message, app_state: &Arc<AppState>) -
     
     if normalized_text.contains("ver ofertas web") || normalized_text.contains("activar radar de ofertas") || normalized_text.contains("radar ofertas") || normalized_text.contains("ofertas web") {
         info!("Natural phrase detected for offers radar: '{}'", text_body);
-        return handle_offers_radar_request(app_state, whatsapp_id).await;
+        return handle_offers_radar_request(app_state, whatsapp_id, locale).await;
     }
     
     if normalized_text.contains("cancelar") || normalized_text.contains("salir") || normalized_text.contains("stop") {
@@ -106,38 +108,38 @@ pub async fn handle_text_message(message: &Message, app_state: &Arc<AppState>) -
         }
         Some(UserState::OcrInvoice) => {
             info!("User {} is in OCR mode but sent text instead of image/document", whatsapp_id);
-            let response = "Estoy esperando que me envíes la imagen o PDF de tu factura. Si quieres cancelar, escribe `/cancelar`.";
-            whatsapp_service::send_text_message(app_state, whatsapp_id, response).await
+            let response = i18n::t(locale, "wa.waiting.ocr_document");
+            whatsapp_service::send_text_message(app_state, whatsapp_id, &response).await
         }
         Some(UserState::WaitingForImage) => {
             info!("User {} is waiting for image but sent text instead", whatsapp_id);
-            let response = "Estoy esperando que me envíes una imagen para procesar el QR. Si quieres cancelar, escribe `/cancelar`.";
-            whatsapp_service::send_text_message(app_state, whatsapp_id, response).await
+            let response = i18n::t(locale, "wa.waiting.image_qr");
+            whatsapp_service::send_text_message(app_state, whatsapp_id, &response).await
         }
         Some(UserState::WaitingForImageOcr) => {
             info!("User {} is waiting for OCR image but sent text instead", whatsapp_id);
-            let response = "Estoy esperando que me envíes una imagen para procesar con OCR. Si quieres cancelar, escribe `/cancelar`.";
-            whatsapp_service::send_text_message(app_state, whatsapp_id, response).await
+            let response = i18n::t(locale, "wa.waiting.image_ocr");
+            whatsapp_service::send_text_message(app_state, whatsapp_id, &response).await
         }
         Some(UserState::OffersRadar { step, categories }) => {
             info!("Handling offers radar response for user {}: step={}", whatsapp_id, step);
-            handle_offers_radar_response(app_state, whatsapp_id, text_body, &step, &categories).await
+            handle_offers_radar_response(app_state, whatsapp_id, text_body, &step, &categories, locale).await
         }
         Some(UserState::PriceRange(state_json)) => {
             info!("Handling price range flow for user {}", whatsapp_id);
-            handle_price_range_flow(app_state, whatsapp_id, text_body, &state_json).await
+            handle_price_range_flow(app_state, whatsapp_id, text_body, &state_json, locale).await
         }
         // 5. Si no hay un flujo activo y no es un comando, responder amigablemente.
         None => {
             info!("No active state and not a command for user {}. Sending default response.", whatsapp_id);
-            let response = "No te he entendido. Si necesitas algo, escribe `/ayuda` para ver la lista de comandos.";
-            whatsapp_service::send_text_message(app_state, whatsapp_id, response).await
+            let response = i18n::t(locale, "wa.not_understood");
+            whatsapp_service::send_text_message(app_state, whatsapp_id, &response).await
         }
     }
 }
 
 /// Handles the "ver ofertas web" request, replicating the Python logic
-async fn handle_offers_radar_request(app_state: &Arc<AppState>, whatsapp_id: &str) -> Result<()> {
+async fn handle_offers_radar_request(app_state: &Arc<AppState>, whatsapp_id: &str, locale: Locale) -> Result<()> {
     info!("Processing offers radar request for user {}", whatsapp_id);
     
     // 1. Check if user is registered
@@ -145,8 +147,8 @@ async fn handle_offers_radar_request(app_state: &Arc<AppState>, whatsapp_id: &st
     let user = match user_opt {
         Some(user) => user,
         None => {
-            let message = "Debes estar registrado para acceder a tus retos y beneficios.";
-            whatsapp_service::send_text_message(app_state, whatsapp_id, message).await?;
+            let message = i18n::t(locale, "wa.offers.must_register");
+            whatsapp_service::send_text_message(app_state, whatsapp_id, &message).await?;
             return Ok(());
         }
     };
@@ -172,11 +174,11 @@ async fn handle_offers_radar_request(app_state: &Arc<AppState>, whatsapp_id: &st
     
     if !rows.is_empty() {
         // 3. Show available categories
-        let mut mensaje = "📋 *Categorías disponibles:*\n\n".to_string();
+        let mut mensaje = format!("{}\n\n", i18n::t(locale, "wa.offers.categories_title"));
         for (i, categoria) in rows.iter().enumerate() {
             mensaje.push_str(&format!("{}. {}\n", i + 1, categoria));
         }
-        mensaje.push_str("\n*Escribe el nombre de la categoría que te interesa*");
+        mensaje.push_str(&format!("\n{}", i18n::t(locale, "wa.offers.choose_category")));
         
         whatsapp_service::send_text_message(app_state, whatsapp_id, &mensaje).await?;
         
@@ -192,8 +194,8 @@ async fn handle_offers_radar_request(app_state: &Arc<AppState>, whatsapp_id: &st
         info!("Offers radar categories shown to user {}", whatsapp_id);
     } else {
         // No active offers, send summary (simplified version)
-        let message = "No tienes ofertas activas en este momento. Te notificaremos cuando haya nuevas ofertas disponibles.";
-        whatsapp_service::send_text_message(app_state, whatsapp_id, message).await?;
+        let message = i18n::t(locale, "wa.offers.none");
+        whatsapp_service::send_text_message(app_state, whatsapp_id, &message).await?;
         warn!("No active offers found for user {}", whatsapp_id);
     }
     
@@ -206,7 +208,8 @@ async fn handle_offers_radar_response(
     whatsapp_id: &str, 
     text_body: &str, 
     step: &str, 
-    categories: &[String]
+    categories: &[String],
+    locale: Locale,
 ) -> Result<()> {
     match step {
         "seleccionar_categoria" => {
@@ -235,21 +238,18 @@ async fn handle_offers_radar_response(
                     600
                 ).await?;
                 
-                let message = format!(
-                    "✅ Categoría seleccionada: *{}*\n\n📊 Ahora digita el rango de precios que te interesa\n(ejemplo: 100-200)",
-                    selected_category
-                );
+                let message = i18n::render(locale, "wa.offers.category_selected", &[("category", &selected_category)]);
                 
                 whatsapp_service::send_text_message(app_state, whatsapp_id, &message).await?;
             } else {
                 // Category not found, ask again
                 warn!("User {} selected invalid category: {}", whatsapp_id, selected_category);
                 
-                let mut mensaje = "❌ No encontré esa categoría. Las opciones disponibles son:\n\n".to_string();
+                let mut mensaje = format!("{}\n\n", i18n::t(locale, "wa.offers.category_not_found"));
                 for (i, categoria) in categories.iter().enumerate() {
                     mensaje.push_str(&format!("{}. {}\n", i + 1, categoria));
                 }
-                mensaje.push_str("\n*Por favor, escribe el nombre exacto de una categoría de la lista.*");
+                mensaje.push_str(&format!("\n{}", i18n::t(locale, "wa.offers.choose_exact")));
                 
                 whatsapp_service::send_text_message(app_state, whatsapp_id, &mensaje).await?;
             }
@@ -257,8 +257,8 @@ async fn handle_offers_radar_response(
         _ => {
             warn!("Unknown offers radar step: {} for user {}", step, whatsapp_id);
             redis_service::delete_user_state(app_state, whatsapp_id).await?;
-            let message = "Ha ocurrido un error. Por favor, intenta de nuevo con 'ver ofertas web'.";
-            whatsapp_service::send_text_message(app_state, whatsapp_id, message).await?;
+            let message = i18n::t(locale, "wa.offers.restart");
+            whatsapp_service::send_text_message(app_state, whatsapp_id, &message).await?;
         }
     }
     
@@ -270,6 +270,7 @@ async fn handle_price_range_flow(
     whatsapp_id: &str,
     text_body: &str,
     state_json: &str,
+    locale: Locale,
 ) -> Result<()> {
     use serde_json::Value;
     
@@ -307,18 +308,15 @@ async fn handle_price_range_flow(
                     600
                 ).await?;
                 
-                let mensaje = format!(
-                    "✅ Categoría seleccionada: *{}*\n\n📊 Ahora digita el rango de precios que te interesa\n(ejemplo: 100-200)",
-                    categoria
-                );
+                let mensaje = i18n::render(locale, "wa.offers.category_selected", &[("category", &categoria)]);
                 whatsapp_service::send_text_message(app_state, whatsapp_id, &mensaje).await?;
             } else {
                 // Categoría no válida, mostrar opciones nuevamente
-                let mut mensaje = "❌ Por favor selecciona una categoría válida de la lista:\n\n".to_string();
+                let mut mensaje = format!("{}\n\n", i18n::t(locale, "wa.offers.invalid_category"));
                 for (i, cat) in categorias_disponibles.iter().enumerate() {
                     mensaje.push_str(&format!("{}. {}\n", i + 1, cat));
                 }
-                mensaje.push_str("\n*Escribe el nombre exacto de la categoría*");
+                mensaje.push_str(&format!("\n{}", i18n::t(locale, "wa.offers.choose_exact_short")));
                 whatsapp_service::send_text_message(app_state, whatsapp_id, &mensaje).await?;
             }
         }
@@ -333,9 +331,10 @@ async fn handle_price_range_flow(
                 ) {
                     if minprice < maxprice {
                         // Enviar mensaje de procesamiento
-                        let processing_msg = format!(
-                            "🔄 Analizando ofertas de *{}* en el rango ${}-${}...",
-                            categoria_seleccionada, minprice, maxprice
+                        let processing_msg = i18n::render(
+                            locale,
+                            "wa.offers.analyzing",
+                            &[("category", &categoria_seleccionada), ("min", &minprice), ("max", &maxprice)],
                         );
                         whatsapp_service::send_text_message(app_state, whatsapp_id, &processing_msg).await?;
                         
@@ -351,9 +350,10 @@ async fn handle_price_range_flow(
                             ).await {
                                 Ok(offers) => {
                                     if offers.is_empty() {
-                                        let no_offers_msg = format!(
-                                            "📭 No encontramos ofertas de *{}* en el rango ${}-${}\n\n💡 *Tip*: Prueba con un rango más amplio (ej: 50-500)",
-                                            categoria_seleccionada, minprice, maxprice
+                                        let no_offers_msg = i18n::render(
+                                            locale,
+                                            "wa.offers.no_results",
+                                            &[("category", &categoria_seleccionada), ("min", &minprice), ("max", &maxprice)],
                                         );
                                         whatsapp_service::send_text_message(app_state, whatsapp_id, &no_offers_msg).await?;
                                     } else {
@@ -381,13 +381,13 @@ async fn handle_price_range_flow(
                                 }
                                 Err(e) => {
                                     tracing::error!("Error searching offers: {:?}", e);
-                                    let error_msg = "❌ Hubo un error al buscar ofertas. Inténtalo más tarde.";
-                                    whatsapp_service::send_text_message(app_state, whatsapp_id, error_msg).await?;
+                                    let error_msg = i18n::t(locale, "wa.offers.search_error");
+                                    whatsapp_service::send_text_message(app_state, whatsapp_id, &error_msg).await?;
                                 }
                             }
                         } else {
-                            let error_msg = "❌ Usuario no encontrado. Usa /registro para crear tu cuenta.";
-                            whatsapp_service::send_text_message(app_state, whatsapp_id, error_msg).await?;
+                            let error_msg = i18n::t(locale, "wa.offers.user_not_found");
+                            whatsapp_service::send_text_message(app_state, whatsapp_id, &error_msg).await?;
                         }
                         
                         // Limpiar estado
@@ -396,21 +396,21 @@ async fn handle_price_range_flow(
                         whatsapp_service::send_text_message(
                             app_state,
                             whatsapp_id,
-                            "❌ El precio mínimo debe ser menor que el máximo. Intenta nuevamente (ej: 100-200)"
+                            &i18n::t(locale, "wa.offers.min_below_max")
                         ).await?;
                     }
                 } else {
                     whatsapp_service::send_text_message(
                         app_state,
                         whatsapp_id,
-                        "❌ Formato de rango inválido. Usa el formato: minimo-maximo (ej: 100-200)"
+                        &i18n::t(locale, "wa.offers.invalid_range")
                     ).await?;
                 }
             } else {
                 whatsapp_service::send_text_message(
                     app_state,
                     whatsapp_id,
-                    "❌ Formato de rango inválido. Usa el formato: minimo-maximo (ej: 100-200)"
+                    &i18n::t(locale, "wa.offers.invalid_range")
                 ).await?;
             }
         }
@@ -420,7 +420,7 @@ async fn handle_price_range_flow(
             whatsapp_service::send_text_message(
                 app_state,
                 whatsapp_id,
-                "❌ Ocurrió un error en el proceso. Por favor, intenta nuevamente con 'ver ofertas web'."
+                &i18n::t(locale, "wa.offers.flow_error")
             ).await?;
         }
     }
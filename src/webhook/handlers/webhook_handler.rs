@@ -1,11 +1,12 @@
 use crate::models::whatsapp::{WebhookPayload, WebhookVerification};
+use crate::observability::metrics::record_whatsapp_webhook_signature;
 use crate::state::AppState;
+use crate::webhook::{SignatureError, SIGNATURE_HEADER};
 use axum::{
-    body::Body,
+    body::{Body, Bytes},
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
-    Json,
 };
 use std::sync::Arc;
 use tracing::{info, warn, error};
@@ -50,8 +51,32 @@ pub async fn get_webhook(
 
 pub async fn post_webhook(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<WebhookPayload>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> impl IntoResponse {
+    // La firma de Meta se verifica sobre el cuerpo crudo, antes de parsear el JSON
+    let signature = headers.get(SIGNATURE_HEADER).and_then(|v| v.to_str().ok());
+    match state.webhook_signature.verify(signature, &body) {
+        Ok(slot) => record_whatsapp_webhook_signature("valid", slot.as_str()),
+        Err(e) => {
+            record_whatsapp_webhook_signature(e.as_str(), "none");
+            if e == SignatureError::NotConfigured {
+                error!("❌ Webhook rejected: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR;
+            }
+            warn!("🚫 Webhook rejected: {}", e);
+            return StatusCode::UNAUTHORIZED;
+        }
+    }
+
+    let payload: WebhookPayload = match serde_json::from_slice(&body) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("📭 Invalid webhook payload: {}", e);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
     // ✅ FASE 1: Respuesta inmediata HTTP 200 para prevenir retries de Facebook
     info!("📥 Webhook received, processing in background...");
     
@@ -117,6 +142,7 @@ async fn process_webhook_async(
 mod tests {
     use super::*;
     use crate::create_app_router;
+    use lum_shared::webhook_signature::sign;
     use axum::{
         body::Body,
         http::{self, Request},
@@ -127,9 +153,28 @@ mod tests {
         Mock, MockServer, ResponseTemplate,
     };
 
+    const TEST_APP_SECRET: &str = "test_app_secret";
+
+    fn load_test_env() {
+        // El secreto de prueba tiene prioridad sobre el del .env (dotenv no sobrescribe)
+        std::env::set_var("WHATSAPP_APP_SECRET", TEST_APP_SECRET);
+        dotenvy::dotenv().ok();
+    }
+
+    fn signed_webhook_request(body: impl Into<Vec<u8>>) -> Request<Body> {
+        let body = body.into();
+        Request::builder()
+            .method(http::Method::POST)
+            .uri("/webhookws")
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(SIGNATURE_HEADER, sign(TEST_APP_SECRET, &body))
+            .body(Body::from(body))
+            .unwrap()
+    }
+
     async fn setup_test_app() -> axum::Router {
         // Carga las variables de entorno para la prueba
-        dotenvy::dotenv().ok();
+        load_test_env();
         let app_state = AppState::new().await.expect("Failed to create AppState for test");
         create_app_router(app_state.into())
     }
@@ -181,12 +226,7 @@ mod tests {
         "#;
 
         let response = app
-            .oneshot(Request::builder()
-                .method(http::Method::POST)
-                .uri("/webhookws")
-                .header(http::header::CONTENT_TYPE, "application/json")
-                .body(Body::from(message))
-                .unwrap())
+            .oneshot(signed_webhook_request(message))
             .await
             .unwrap();
 
@@ -244,18 +284,38 @@ mod tests {
         "#;
 
         let response = app
-            .oneshot(Request::builder()
-                .method(http::Method::POST)
-                .uri("/webhookws")
-                .header(http::header::CONTENT_TYPE, "application/json")
-                .body(Body::from(message))
-                .unwrap())
+            .oneshot(signed_webhook_request(message))
             .await
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_webhook_rejects_unsigned_or_forged_payload() {
+        let app = setup_test_app().await;
+        let body = r#"{"object":"whatsapp_business_account","entry":[]}"#;
+
+        let unsigned = Request::builder()
+            .method(http::Method::POST)
+            .uri("/webhookws")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.clone().oneshot(unsigned).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let forged = Request::builder()
+            .method(http::Method::POST)
+            .uri("/webhookws")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, sign("otro_secreto", body.as_bytes()))
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.oneshot(forged).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
     #[tokio::test]
     async fn test_start_survey_interactive_flow() {
         // 1. Setup Wiremock server
@@ -263,7 +323,7 @@ mod tests {
         let mock_uri = mock_server.uri();
 
         // 2. Setup AppState to use the mock server
-        dotenvy::dotenv().ok();
+        load_test_env();
         let mut app_state = AppState::new().await.expect("Failed to create AppState");
         app_state.whatsapp_api_base_url = mock_uri;
         let redis_client = app_state.redis_client.clone();
@@ -323,14 +383,7 @@ mod tests {
         });
 
         let response = app
-            .oneshot(
-                Request::builder()
-                    .method(http::Method::POST)
-                    .uri("/webhookws")
-                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
-                    .body(Body::from(serde_json::to_vec(&test_payload).unwrap()))
-                    .unwrap(),
-            )
+            .oneshot(signed_webhook_request(serde_json::to_vec(&test_payload).unwrap()))
             .await
             .unwrap();
 
@@ -7,4 +7,5 @@ pub mod stats;
 pub use handlers::{get_webhook, post_webhook};
 pub use routes::create_webhook_router;
 pub use deduplication::{MessageDeduplicator, DeduplicationStats};
+pub use lum_shared::webhook_signature::{SignatureError, SignatureVerifier, SIGNATURE_HEADER};
 pub use stats::{get_webhook_stats, WebhookStats};